@@ -1,155 +1,2186 @@
+use crate::appearance::{Appearance, Theme};
+use crate::import::FileKind;
+use crate::model::{edge_measures, nearest_edge, Assembly, Feature, History};
+use crate::project::{unique_sketch_name, PlacedSketch, Project, PROJECT_EXTENSION};
+use crate::renderer::background::BackgroundStyle;
+use crate::renderer::camera::{Projection, RotationMode, StandardView, Turntable, ViewBookmarks};
+use crate::renderer::gizmo::{GizmoMode, TransformGizmo};
+use crate::renderer::grid::grid_spacing;
+use crate::renderer::jobs::Jobs;
+use crate::renderer::matcap::{MatcapImage, ShadingMode};
+use crate::renderer::mesh::{
+    display_tolerance, BoundingBox3, GpuMesh, MeshDetail, MeshQuality, NormalMode,
+};
+use crate::renderer::mesh_cache::MeshCache;
+use crate::renderer::pick::PickResult;
+use crate::renderer::scene::{to_matrix4, DisplayMode, Material, ObjectId, Scene};
+use crate::renderer::shadow::ShadowQuality;
+use crate::renderer::texture::TextureImage;
+use crate::renderer::Highlight;
+use crate::script::ScriptOutput;
+use crate::sketch::{Plane, SketchCurve2D, SnapSettings};
+use crate::units::Units;
+use console::ScriptConsole;
+use diagnostics::{Diagnostics, Severity};
 use eframe::egui;
 use eframe::wgpu;
+use hover::HoverPicker;
+use inspector::Inspector;
+use measure::{MeasureKind, MeasureTool};
+use palette::{Command, CommandPalette};
+use selection::{Selected, Selection, SelectionKind};
+use session::Session;
+use sketcher::{SketchSession, SketchTool};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use tree::{ModelTree, TreeAction};
+use truck_geometry::prelude::{
+    EuclideanSpace, Matrix4, MetricSpace, Point2, Point3, SquareMatrix, Vector3,
+};
+use viewports::{ViewLayout, Viewports};
 
-// Import RenderState properly
-use eframe::egui_wgpu::RenderState;
+mod console;
+mod diagnostics;
+mod hover;
+mod inspector;
+mod measure;
+mod palette;
+mod selection;
+mod session;
+mod sketcher;
+mod status;
+mod tree;
+mod viewports;
+
+/// Where turntable captures go, relative to the working directory
+const TURNTABLE_DIR: &str = "turntable";
+
+/// Frames per captured turn
+const TURNTABLE_FRAMES: usize = 36;
+
+/// Marker and coordinates left on the picked point (linear RGB)
+const PICK_MARK_COLOR: [f32; 3] = [1.0, 0.9, 0.2];
+
+/// Sketch curves drawn over the scene (linear RGB)
+const SKETCH_COLOR: [f32; 3] = [0.3, 0.85, 1.0];
+
+/// Straight pieces a selected sketch curve is outlined with
+const SKETCH_CURVE_SEGMENTS: usize = 32;
+
+/// How close in pixels the cursor snaps to curve ends in sketch mode, and
+/// to vertices and edges in measure mode
+const SNAP_PIXELS: f32 = 8.0;
 
 pub struct CadApp {
     renderer: crate::renderer::Renderer,
-    render_texture: Option<RenderTexture>,
-}
-
-struct RenderTexture {
-    texture: wgpu::Texture,
-    view: wgpu::TextureView,
-    egui_texture_id: egui::TextureId,
-    size: (u32, u32),
+    viewports: Viewports,
+    /// Parts shown in the viewport
+    assembly: Assembly,
+    /// Features that made the parts, kept with the project
+    history: History,
+    /// Imported display-only meshes shown next to the parts
+    references: Vec<GpuMesh>,
+    /// Sketches kept with the project
+    sketches: Vec<PlacedSketch>,
+    /// File path typed into the toolbar
+    open_path: String,
+    /// Outcome of the last file operation
+    status: String,
+    /// Bodies, edges and sketches picked in the viewport and the tree
+    selection: Selection,
+    /// Where a Shift+drag box select started
+    select_box: Option<egui::Pos2>,
+    /// Handles shown on the selection
+    gizmo_mode: GizmoMode,
+    turntable: Turntable,
+    /// Whether the turntable is spinning the view
+    spinning: bool,
+    /// What model coordinates are in, kept with the project
+    units: Units,
+    /// Saved viewpoints of the project
+    views: ViewBookmarks,
+    /// Name typed for the next saved view
+    view_name: String,
+    tree: ModelTree,
+    /// Curves being drawn in sketch mode
+    sketching: Option<SketchSession>,
+    /// How far the last sketch is extruded
+    extrude_depth: f64,
+    /// Whether clicks measure instead of selecting
+    measuring: bool,
+    measure: MeasureTool,
+    /// Point last picked, marked with its coordinates
+    pick_mark: Option<glam::Vec3>,
+    /// Model point under the cursor, from the last hover pick
+    cursor_point: Option<Point3>,
+    /// Cursor on the sketch plane while sketching
+    sketch_cursor: Option<Point2>,
+    /// Object and edge under the cursor
+    hover: HoverPicker,
+    console: ScriptConsole,
+    show_console: bool,
+    inspector: Inspector,
+    show_inspector: bool,
+    palette: CommandPalette,
+    /// What sketch clicks snap to
+    snap: SnapSettings,
+    show_snap: bool,
+    /// Look of the window, kept with the appearance settings
+    theme: Theme,
+    /// Failures and warnings of everything done so far
+    diagnostics: Diagnostics,
+    show_diagnostics: bool,
+    /// Scenes being tessellated off the UI thread
+    scene_jobs: Jobs<Scene>,
+    /// Part meshes from the last rebuild, shared with the scene jobs
+    mesh_cache: Arc<Mutex<MeshCache>>,
+    /// How finely parts are tessellated for display
+    mesh_detail: MeshDetail,
+    normal_mode: NormalMode,
+    /// Scene objects of dropped files, framed once the scene arrives
+    frame_on_load: Option<Vec<String>>,
+    /// Recent projects and what to open again next time
+    session: Session,
 }
 
 impl CadApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         let wgpu_state = cc.wgpu_render_state.as_ref().expect("wgpu required");
 
-        let renderer =
-            crate::renderer::Renderer::new(&wgpu_state.device, wgpu_state.target_format, 800, 600);
+        let mut renderer = crate::renderer::Renderer::new(
+            &wgpu_state.device,
+            &wgpu_state.queue,
+            wgpu_state.target_format,
+            800,
+            600,
+        );
 
-        // Load test geometry
-        let solid = crate::geometry::create_test_solid();
-        let mesh = crate::renderer::mesh::GpuMesh::from_solid(&solid, 0.0001);
-        let mut renderer = renderer;
-        renderer.set_mesh(&wgpu_state.device, &mesh);
+        // The appearance saved last, if any
+        let config = Appearance::config_path().filter(|path| path.exists());
+        let (appearance, error) = match config.as_ref().map(Appearance::load) {
+            Some(Ok(appearance)) => (appearance, None),
+            Some(Err(e)) => (Appearance::default(), Some(e)),
+            None => (Appearance::default(), None),
+        };
+        cc.egui_ctx.set_theme(appearance.theme.preference());
+        renderer.display = appearance.display;
+
+        // and the session before
+        let session_path = Session::config_path().filter(|path| path.exists());
+        let (session, session_error) = match session_path.as_ref().map(Session::load) {
+            Some(Ok(session)) => (session, None),
+            Some(Err(e)) => (Session::default(), Some(e)),
+            None => (Session::default(), None),
+        };
 
-        Self {
+        let mut app = Self {
             renderer,
-            render_texture: None,
-        }
-    }
-
-    fn ensure_render_texture(&mut self, wgpu_state: &RenderState, width: u32, height: u32) {
-        let needs_recreate = match &self.render_texture {
-            None => true,
-            Some(rt) => rt.size != (width, height),
-        };
-
-        if needs_recreate && width > 0 && height > 0 {
-            // Remove old texture from egui
-            if let Some(old) = self.render_texture.take() {
-                wgpu_state
-                    .renderer
-                    .write()
-                    .free_texture(&old.egui_texture_id);
-            }
-
-            // Create new texture
-            let texture = wgpu_state.device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("Viewport Texture"),
-                size: wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu_state.target_format,
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                    | wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[],
+            viewports: Viewports::default(),
+            assembly: Assembly::new(),
+            history: History::new(),
+            references: Vec::new(),
+            sketches: Vec::new(),
+            open_path: String::new(),
+            status: String::new(),
+            selection: Selection::default(),
+            select_box: None,
+            gizmo_mode: GizmoMode::default(),
+            turntable: Turntable::default(),
+            spinning: false,
+            units: Units::default(),
+            views: ViewBookmarks::new(),
+            view_name: String::new(),
+            tree: ModelTree::default(),
+            sketching: None,
+            extrude_depth: 10.0,
+            measuring: false,
+            measure: MeasureTool::default(),
+            pick_mark: None,
+            cursor_point: None,
+            sketch_cursor: None,
+            hover: HoverPicker::default(),
+            console: ScriptConsole::default(),
+            show_console: false,
+            inspector: Inspector::default(),
+            show_inspector: false,
+            palette: CommandPalette::default(),
+            snap: SnapSettings::default(),
+            show_snap: false,
+            theme: appearance.theme,
+            diagnostics: Diagnostics::default(),
+            show_diagnostics: false,
+            scene_jobs: Jobs::new(),
+            mesh_cache: Arc::default(),
+            mesh_detail: MeshDetail::default(),
+            normal_mode: NormalMode::default(),
+            frame_on_load: None,
+            session: Session::default(),
+        };
+        if let (Some(e), Some(path)) = (error, &config) {
+            app.report(&path.display().to_string(), e);
+        }
+        if let (Some(e), Some(path)) = (session_error, &session_path) {
+            app.report(&path.display().to_string(), e);
+        }
+        app.restore_session(session, &wgpu_state.device, &wgpu_state.queue);
+        app
+    }
+
+    /// Show the panels of `session` and open its project where it was
+    /// looked at from, or else start with no parts
+    fn restore_session(&mut self, session: Session, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let panels = session.panels;
+        self.show_inspector = panels.inspector;
+        self.show_console = panels.console;
+        self.show_diagnostics = panels.log;
+        self.show_snap = panels.snap;
+        self.viewports.set_layout(panels.views, &mut self.renderer);
+        let (project, camera) = (session.project.clone(), session.camera);
+        // Set again once the project opens
+        self.session = Session {
+            project: None,
+            ..session
+        };
+        if let Some(path) = project {
+            self.load_file(&path, device, queue);
+        }
+        if self.session.project.is_some() {
+            if let Some(camera) = camera {
+                self.renderer.camera = camera;
+            }
+        } else {
+            self.upload_assembly();
+        }
+    }
+
+    /// Keep the recent projects, camera and panels for next time
+    fn save_session(&mut self) {
+        self.session.camera = Some(self.renderer.camera);
+        self.session.panels = session::PanelLayout {
+            inspector: self.show_inspector,
+            console: self.show_console,
+            log: self.show_diagnostics,
+            snap: self.show_snap,
+            views: self.viewports.layout(),
+        };
+        if let Some(path) = Session::config_path() {
+            if let Err(e) = self.session.save(&path) {
+                self.report(&path.display().to_string(), e);
+            }
+        }
+    }
+
+    /// Show `message` in the status line and log it about `entity`
+    fn log(&mut self, severity: Severity, entity: &str, message: impl ToString) {
+        self.status = message.to_string();
+        let entity = (!entity.is_empty()).then_some(entity);
+        self.diagnostics.push(severity, entity, self.status.clone());
+    }
+
+    /// Show the error in the status line and log it about `entity`
+    fn report(&mut self, entity: &str, e: impl ToString) {
+        self.log(Severity::Error, entity, e);
+    }
+
+    /// Pick a file in the system dialog and open it
+    fn browse_file(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let supported: Vec<&str> = FileKind::ALL
+            .iter()
+            .flat_map(|kind| kind.extensions())
+            .copied()
+            .collect();
+        let mut dialog = rfd::FileDialog::new().add_filter("Supported files", &supported);
+        for kind in FileKind::ALL {
+            dialog = dialog.add_filter(kind.name(), kind.extensions());
+        }
+        if let Some(path) = dialog.add_filter("All files", &["*"]).pick_file() {
+            self.open_path = path.display().to_string();
+            self.open_file(device, queue);
+        }
+    }
+
+    /// Open the file at `open_path`
+    fn open_file(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let path = PathBuf::from(self.open_path.trim());
+        self.load_file(&path, device, queue);
+    }
+
+    /// Load files dropped on the window, and frame what they add once it
+    /// is tessellated
+    fn open_dropped(&mut self, paths: Vec<PathBuf>, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut added = Vec::new();
+        for path in paths {
+            added.extend(self.load_file(&path, device, queue));
+        }
+        if !added.is_empty() {
+            self.frame_on_load = Some(added);
+        }
+    }
+
+    /// Load `path` by its [`FileKind`]: a project replaces everything, STEP
+    /// solids are added as parts, DXF profiles as sketches on XY, OBJ and
+    /// STL as reference meshes, and PNG becomes the matcap. Returns the
+    /// names of the scene objects it adds.
+    fn load_file(
+        &mut self,
+        path: &Path,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Vec<String> {
+        let stem = path
+            .file_stem()
+            .map_or("part".into(), |s| s.to_string_lossy().into_owned());
+        let file = path.display().to_string();
+        let Some(kind) = FileKind::of(path) else {
+            self.report(&file, "Not a file type that can be opened");
+            return Vec::new();
+        };
+        match kind {
+            FileKind::Project => match Project::load(path) {
+                Ok(project) => {
+                    self.log(Severity::Info, &file, "Opened project");
+                    self.assembly = project.assembly;
+                    self.history = project.history;
+                    self.sketches = project.sketches;
+                    self.renderer.camera = project.camera;
+                    self.units = project.units;
+                    self.views = project.views;
+                    self.renderer.display = project.display;
+                    self.references.clear();
+                    self.upload_assembly();
+                    self.open_path = file;
+                    self.session.opened(path);
+                    self.save_session();
+                    return self
+                        .assembly
+                        .parts()
+                        .iter()
+                        .map(|p| p.name().to_string())
+                        .collect();
+                }
+                Err(e) => {
+                    if !path.exists() {
+                        self.session.forget(path);
+                    }
+                    self.report(&file, e);
+                }
+            },
+            FileKind::Matcap => match MatcapImage::load(path) {
+                Ok(image) => {
+                    self.log(Severity::Info, &file, "Loaded matcap");
+                    self.renderer.set_matcap(device, queue, &image);
+                    self.renderer.display.shading = ShadingMode::Matcap;
+                }
+                Err(e) => self.report(&file, e),
+            },
+            FileKind::Mesh => match crate::import::mesh::read(path) {
+                Ok(mesh) => {
+                    self.log(Severity::Info, &file, "Added reference mesh");
+                    self.references.push(mesh);
+                    self.upload_assembly();
+                    return vec![reference_name(self.references.len() - 1)];
+                }
+                Err(e) => self.report(&file, e),
+            },
+            FileKind::Dxf => match crate::import::dxf::read_with_warnings(path) {
+                Ok((sketches, warnings)) => {
+                    for warning in warnings {
+                        self.diagnostics
+                            .push(Severity::Warning, Some(&file), warning);
+                    }
+                    let message = format!("Added {} sketch(es)", sketches.len());
+                    self.log(Severity::Info, &file, message);
+                    for (i, sketch) in sketches.into_iter().enumerate() {
+                        let name = format!("{} {}", stem, i + 1);
+                        self.sketches.push(PlacedSketch {
+                            name: unique_sketch_name(&self.sketches, &name),
+                            plane: Plane::xy(),
+                            sketch,
+                        });
+                    }
+                }
+                Err(e) => self.report(&file, e),
+            },
+            FileKind::Step => match crate::import::step::read(path) {
+                Ok(solids) => {
+                    let message = format!("Added {} solid(s)", solids.len());
+                    self.log(Severity::Info, &file, message);
+                    let mut names = Vec::new();
+                    for (i, solid) in solids.into_iter().enumerate() {
+                        let name = self.assembly.unique_name(&format!("{} {}", stem, i + 1));
+                        self.assembly
+                            .add(name.clone(), solid, Matrix4::identity())
+                            .expect("the name is unused");
+                        let path = path.to_path_buf();
+                        self.history.record(name.clone(), Feature::Import { path });
+                        names.push(name);
+                    }
+                    self.upload_assembly();
+                    return names;
+                }
+                Err(e) => self.report(&file, e),
+            },
+        }
+        Vec::new()
+    }
+
+    /// Wrap the PNG at `open_path` around the selected bodies until the
+    /// scene is next rebuilt
+    fn apply_texture(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let path = PathBuf::from(self.open_path.trim());
+        match TextureImage::load(&path) {
+            Ok(image) => {
+                for id in self.selection.bodies() {
+                    self.renderer.set_texture(device, queue, id, Some(&image));
+                }
+                self.status = format!("Texture {}", path.display());
+            }
+            Err(e) => self.report(&path.display().to_string(), e),
+        }
+    }
+
+    /// Save sketches, parts, camera and display settings to `open_path`,
+    /// adding the project extension when it is missing
+    fn save_project(&mut self) {
+        let mut path = PathBuf::from(self.open_path.trim());
+        if path.as_os_str().is_empty() {
+            self.status = "Enter a file name to save to".to_string();
+            return;
+        }
+        if path.extension().is_none() {
+            path.set_extension(PROJECT_EXTENSION);
+        }
+        let project = Project {
+            units: self.units,
+            sketches: self.sketches.clone(),
+            assembly: self.assembly.clone(),
+            history: self.history.clone(),
+            camera: self.renderer.camera,
+            views: self.views.clone(),
+            display: self.renderer.display,
+        };
+        let file = path.display().to_string();
+        match project.save(&path) {
+            Ok(()) => {
+                self.log(Severity::Info, &file, "Saved project");
+                self.session.opened(&path);
+                self.save_session();
+            }
+            Err(e) => self.report(&file, e),
+        }
+    }
+
+    /// Theme and viewport colors, saved to the config file read at startup
+    fn appearance_menu(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Theme");
+            for theme in Theme::ALL {
+                if ui
+                    .selectable_value(&mut self.theme, theme, theme.name())
+                    .clicked()
+                {
+                    ui.ctx().set_theme(theme.preference());
+                }
+            }
+        });
+        let display = &mut self.renderer.display;
+        let rgb_row = |ui: &mut egui::Ui, label: &str, color: &mut [f32; 3]| {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.color_edit_button_rgb(color);
             });
+        };
+        rgb_row(ui, "Edges", &mut display.outline_color);
+        for (label, tint) in [
+            ("Selection", &mut display.selection_tint),
+            ("Hover", &mut display.hover_tint),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                let [r, g, b, amount] = tint;
+                let mut rgb = [*r, *g, *b];
+                if ui.color_edit_button_rgb(&mut rgb).changed() {
+                    [*r, *g, *b] = rgb;
+                }
+                ui.add(egui::Slider::new(amount, 0.0..=1.0).text("Mix"));
+            });
+        }
+        let [minor, major] = &mut display.grid_colors;
+        rgb_row(ui, "Grid", minor);
+        rgb_row(ui, "Major grid", major);
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui
+                .button("Save as default")
+                .on_hover_text("Start with this look, background and lighting next time")
+                .clicked()
+            {
+                let appearance = Appearance {
+                    theme: self.theme,
+                    display: self.renderer.display,
+                };
+                match Appearance::config_path() {
+                    Some(path) => match appearance.save(&path) {
+                        Ok(()) => self.log(
+                            Severity::Info,
+                            &path.display().to_string(),
+                            "Saved appearance",
+                        ),
+                        Err(e) => self.report(&path.display().to_string(), e),
+                    },
+                    None => self.report("", "No config directory to save the appearance in"),
+                }
+            }
+            if ui.button("Reset").clicked() {
+                let display = &mut self.renderer.display;
+                let defaults = crate::renderer::DisplaySettings::default();
+                display.outline_color = defaults.outline_color;
+                display.selection_tint = defaults.selection_tint;
+                display.hover_tint = defaults.hover_tint;
+                display.grid_colors = defaults.grid_colors;
+            }
+        });
+    }
 
-            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    /// Render one turn around the parts from the current view into
+    /// numbered PNGs in `turntable/`
+    fn capture_turntable(&mut self) {
+        let size = self.viewports.size().unwrap_or((800, 600));
+        let dir = PathBuf::from(TURNTABLE_DIR);
+        let file = dir.display().to_string();
+        match crate::renderer::offscreen::render_turntable(
+            &self.assembly,
+            &self.renderer.camera,
+            &self.turntable,
+            TURNTABLE_FRAMES,
+            size,
+            &dir,
+        ) {
+            Ok(paths) => self.log(
+                Severity::Info,
+                &file,
+                format!("Wrote {} turntable frames", paths.len()),
+            ),
+            Err(e) => self.report(&file, e),
+        }
+    }
 
-            // Register with egui for display
-            let egui_texture_id = wgpu_state.renderer.write().register_native_texture(
-                &wgpu_state.device,
-                &view,
-                wgpu::FilterMode::Linear,
+    /// Tessellate the assembly (finer for smaller models) on a worker
+    /// thread, along with the reference meshes, which are tinted to tell
+    /// them apart; [`CadApp::receive_scene`] uploads the result
+    fn upload_assembly(&mut self) {
+        self.inspector.invalidate();
+        let assembly = self.assembly.clone();
+        let references = self.references.clone();
+        let detail = self.mesh_detail;
+        let normals = self.normal_mode;
+        let cache = self.mesh_cache.clone();
+        self.scene_jobs.submit(move || {
+            let quality = MeshQuality {
+                normals,
+                ..detail.quality(display_tolerance(&assembly))
+            };
+            // Only parts whose solids changed are tessellated again
+            let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut scene = Scene::from_assembly_cached(&assembly, quality, &mut cache);
+            cache.sweep();
+            drop(cache);
+            for (i, mesh) in references.into_iter().enumerate() {
+                let id = scene.add(reference_name(i), mesh.with_normals(normals));
+                if let Some(object) = scene.object_mut(id) {
+                    object.material = Material {
+                        base_color: [0.45, 0.6, 0.8],
+                        ..Default::default()
+                    };
+                }
+            }
+            scene
+        });
+    }
+
+    /// Upload the scene tessellated last once it is ready; the old one stays
+    /// on screen until then, and when tessellation fails
+    fn receive_scene(&mut self, device: &wgpu::Device) {
+        match self.scene_jobs.poll() {
+            Some(Ok(scene)) => {
+                // Keep what is selected by name, as parts are rebuilt with
+                // new ids
+                let old = self.renderer.scene();
+                let named: Vec<(Selected, String)> = self
+                    .selection
+                    .items()
+                    .iter()
+                    .filter_map(|&item| {
+                        let Some(id) = item.body() else {
+                            return Some((item, String::new()));
+                        };
+                        Some((item, old.object(id)?.name.clone()))
+                    })
+                    .collect();
+                self.renderer.set_scene(device, scene);
+                self.hover.reset();
+                let scene = self.renderer.scene();
+                let items: Vec<Selected> = named
+                    .into_iter()
+                    .filter_map(|(item, name)| match item {
+                        Selected::Body(_) => scene.find(&name).map(Selected::Body),
+                        Selected::Edge(_, edge) => {
+                            scene.find(&name).map(|id| Selected::Edge(id, edge))
+                        }
+                        Selected::Face(_, face) => {
+                            scene.find(&name).map(|id| Selected::Face(id, face))
+                        }
+                        Selected::Sketch(_) | Selected::SketchCurve(..) => Some(item),
+                    })
+                    .collect();
+                self.selection.clear();
+                self.selection.extend(items);
+                self.selection_changed();
+                if let Some(names) = self.frame_on_load.take() {
+                    let scene = self.renderer.scene();
+                    let bounds = names
+                        .iter()
+                        .filter_map(|name| scene.find(name))
+                        .filter_map(|id| self.renderer.object_bounds(id))
+                        .reduce(|a, b| a.union(&b));
+                    if let Some(bounds) = bounds {
+                        self.renderer.fit_view(&bounds);
+                    }
+                }
+            }
+            // The scene before stays on screen
+            Some(Err(message)) => {
+                self.report("assembly", format!("Tessellation failed: {}", message))
+            }
+            None => {}
+        }
+    }
+
+    /// Enter sketch mode with `session`, looking straight at its plane with
+    /// the plane's X axis to the right
+    fn start_sketch(&mut self, session: SketchSession) {
+        self.select(None);
+        self.spinning = false;
+        self.measuring = false;
+        self.pick_mark = None;
+        self.renderer.overlay.clear();
+        let camera = &mut self.renderer.camera;
+        let plane = &session.plane;
+        let axes = glam::Mat3::from_cols(
+            to_glam(plane.x_dir()),
+            to_glam(plane.y_dir()),
+            to_glam(plane.normal()),
+        );
+        camera.set_orientation(glam::Quat::from_mat3(&axes));
+        // Bring the target onto the plane so zooming and panning stay on it
+        let target = plane.project_point(Point3::from_vec(from_glam(camera.target)));
+        camera.target = to_glam(plane.lift_point(target).to_vec());
+        self.sketching = Some(session);
+    }
+
+    /// Enter sketch mode on placed sketch `index` with its curves loaded;
+    /// finishing replaces it
+    fn edit_sketch(&mut self, index: usize) {
+        let Some(placed) = self.sketches.get(index) else {
+            return;
+        };
+        let name = placed.name.clone();
+        self.start_sketch(SketchSession::edit(
+            placed.plane.clone(),
+            &placed.sketch,
+            index,
+        ));
+        self.status = format!("Editing {}", name);
+    }
+
+    /// Leave sketch mode, dropping what was drawn
+    fn end_sketch(&mut self) {
+        self.sketching = None;
+        self.renderer.overlay.clear();
+        let camera = &mut self.renderer.camera;
+        camera.set_rotation(camera.rotation);
+    }
+
+    /// Keep the closed profiles drawn as sketches and leave sketch mode;
+    /// parts extruded from an edited sketch are rebuilt from it
+    fn finish_sketch(&mut self) {
+        let Some(session) = &self.sketching else {
+            return;
+        };
+        match session.finish() {
+            Ok(sketches) => {
+                let plane = session.plane.clone();
+                let mut sketches = sketches.into_iter();
+                let edited = session
+                    .editing
+                    .and_then(|index| self.sketches.get_mut(index));
+                let mut status = Vec::new();
+                let mut rebuild = None;
+                if let Some(placed) = edited {
+                    // Construction geometry is not drawn in the session, so
+                    // it stays as it was
+                    let construction = std::mem::take(&mut placed.sketch.construction);
+                    placed.sketch = sketches.next().expect("finish gives a profile");
+                    placed.sketch.construction = construction;
+                    status.push(format!("Updated {}", placed.name));
+                    rebuild = Some(placed.name.clone());
+                }
+                let count = sketches.len();
+                for sketch in sketches {
+                    let name = format!("Sketch {}", self.sketches.len() + 1);
+                    self.sketches.push(PlacedSketch {
+                        name: unique_sketch_name(&self.sketches, &name),
+                        plane: plane.clone(),
+                        sketch,
+                    });
+                }
+                if count > 0 {
+                    status.push(format!("Added {} sketch(es)", count));
+                }
+                self.status = status.join(", ");
+                self.end_sketch();
+                if let Some(name) = rebuild {
+                    self.rebuild_from_sketch(&name);
+                }
+            }
+            Err(e) => self.report("sketch", e),
+        }
+    }
+
+    /// Make the parts extruded from sketch `name` again from its current
+    /// curves, logging the ones that cannot be rebuilt
+    fn rebuild_from_sketch(&mut self, name: &str) {
+        let (mut rebuilt, mut failed) = (0, false);
+        for part in self.history.parts_from_sketch(name) {
+            let result = self
+                .history
+                .rebuild(&part, &self.sketches)
+                .and_then(|solid| self.assembly.set_solid(&part, solid));
+            match result {
+                Ok(()) => rebuilt += 1,
+                Err(e) => {
+                    self.report(&part, e);
+                    failed = true;
+                }
+            }
+        }
+        if rebuilt > 0 {
+            if !failed {
+                self.status = format!("{}, rebuilt {} part(s)", self.status, rebuilt);
+            }
+            self.upload_assembly();
+        }
+    }
+
+    /// Extrude the selected sketches, or else the newest, along their plane
+    /// normals into new parts
+    fn extrude_sketches(&mut self) {
+        let mut indices: Vec<usize> = self.selection.sketches().collect();
+        if indices.is_empty() {
+            if self.sketches.is_empty() {
+                self.status = "Draw a sketch to extrude first".to_string();
+                return;
+            }
+            indices.push(self.sketches.len() - 1);
+        }
+        let mut added = 0;
+        for index in indices {
+            let Some(placed) = self.sketches.get(index) else {
+                continue;
+            };
+            let direction = placed.plane.normal() * self.extrude_depth;
+            let sketch = placed.name.clone();
+            let result = placed
+                .sketch
+                .extrude(&placed.plane, direction)
+                .and_then(|solid| {
+                    let name = self
+                        .assembly
+                        .unique_name(&format!("{} extrude", placed.name));
+                    self.assembly
+                        .add(name, solid, Matrix4::identity())
+                        .map(|part| part.name().to_string())
+                });
+            match result {
+                Ok(name) => {
+                    self.history
+                        .record(name.clone(), Feature::Extrude { sketch, direction });
+                    self.status = format!("Added {}", name);
+                    added += 1;
+                }
+                Err(e) => self.report(&sketch, e),
+            }
+        }
+        if added > 0 {
+            self.upload_assembly();
+        }
+    }
+
+    /// Take in the parts and sketches a console run added
+    fn add_script_output(&mut self, output: ScriptOutput) {
+        let (parts, sketches) = (output.parts.len(), output.sketches.len());
+        for (name, solid) in output.parts {
+            let name = self.assembly.unique_name(&name);
+            if let Err(e) = self.assembly.add(name.clone(), solid, Matrix4::identity()) {
+                self.report(&name, e);
+                return;
+            }
+            self.history.record(name, Feature::Script);
+        }
+        for mut placed in output.sketches {
+            placed.name = unique_sketch_name(&self.sketches, &placed.name);
+            self.sketches.push(placed);
+        }
+        if parts > 0 {
+            self.upload_assembly();
+        }
+        if parts + sketches > 0 {
+            self.status = format!("Script added {} part(s), {} sketch(es)", parts, sketches);
+        }
+    }
+
+    /// Clicks add points to the sketch; the drawn curves, the one under way
+    /// and the snapped cursor are shown on the overlay
+    fn sketch_input(&mut self, ui: &egui::Ui, response: &egui::Response, rect: egui::Rect) {
+        let Some(session) = &mut self.sketching else {
+            return;
+        };
+        let camera = &self.renderer.camera;
+        let viewport = glam::Vec2::new(rect.width(), rect.height());
+        session.grid = grid_spacing(camera.view_height()) as f64;
+        session.snapping = self.snap;
+        // Cursor on the plane, and how many world units a pixel is there
+        let cursor = response.hover_pos().and_then(|pos| {
+            let ray = camera.screen_ray(
+                glam::Vec2::new(pos.x - rect.min.x, pos.y - rect.min.y),
+                viewport,
             );
+            let normal = to_glam(session.plane.normal());
+            let origin = to_glam(session.plane.origin().to_vec());
+            let along = ray.direction.dot(normal);
+            (along.abs() > 1e-6).then(|| {
+                let point = ray.at((origin - ray.origin).dot(normal) / along);
+                let pixel = 1.0 / camera.pixels_per_unit(point, viewport.y);
+                let local = session
+                    .plane
+                    .project_point(Point3::from_vec(from_glam(point)));
+                (session.snap(local, (SNAP_PIXELS * pixel) as f64), pixel)
+            })
+        });
 
-            // Update depth texture in our renderer
-            self.renderer.resize(&wgpu_state.device, width, height);
+        if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+            session.cancel();
+        }
+        if let (true, Some((point, _))) = (response.clicked(), cursor) {
+            if let Err(e) = session.click(point) {
+                self.status = e.to_string();
+                self.diagnostics
+                    .push(Severity::Warning, Some("sketch"), self.status.clone());
+            }
+        }
 
-            self.render_texture = Some(RenderTexture {
-                texture,
-                view,
-                egui_texture_id,
-                size: (width, height),
-            });
+        let overlay = &mut self.renderer.overlay;
+        overlay.clear();
+        let pixel = cursor.map_or(camera.view_height() / viewport.y, |(_, pixel)| pixel);
+        let lift = |p: Point2| to_glam(session.plane.lift_point(p).to_vec());
+        for polyline in session.polylines(cursor.map(|(p, _)| p), pixel as f64 * 0.5) {
+            for pair in polyline.windows(2) {
+                overlay.line(
+                    to_glam(pair[0].to_vec()),
+                    to_glam(pair[1].to_vec()),
+                    SKETCH_COLOR,
+                );
+            }
+        }
+        for &point in session.pending() {
+            overlay.marker(lift(point), SKETCH_COLOR);
+        }
+        if let Some((point, _)) = cursor {
+            overlay.marker(lift(point), PICK_MARK_COLOR);
+        }
+        self.sketch_cursor = cursor.map(|(point, _)| point);
+    }
+
+    /// Carry out an edit from the model tree on the assembly and the scene
+    fn apply_tree_action(&mut self, action: TreeAction) {
+        let find = |app: &Self, name: &str| app.renderer.scene().find(name);
+        let part = match &action {
+            TreeAction::Select(name, _)
+            | TreeAction::SetVisible(name, _)
+            | TreeAction::Rename(name, _)
+            | TreeAction::Delete(name)
+            | TreeAction::Reorder(name, _) => name.clone(),
+            TreeAction::SelectSketch(index, _)
+            | TreeAction::SelectSketchCurve(index, _, _)
+            | TreeAction::EditSketch(index) => self
+                .sketches
+                .get(*index)
+                .map_or_else(String::new, |placed| placed.name.clone()),
+        };
+        let result = match action {
+            TreeAction::Select(name, toggle) => {
+                let item = find(self, &name).map(Selected::Body);
+                self.click_select(item, toggle);
+                Ok(())
+            }
+            TreeAction::SelectSketch(index, toggle) => {
+                self.click_select(Some(Selected::Sketch(index)), toggle);
+                Ok(())
+            }
+            TreeAction::SelectSketchCurve(index, curve, toggle) => {
+                self.click_select(Some(Selected::SketchCurve(index, curve)), toggle);
+                Ok(())
+            }
+            TreeAction::EditSketch(index) => {
+                self.edit_sketch(index);
+                Ok(())
+            }
+            TreeAction::SetVisible(name, visible) => {
+                self.assembly.set_visible(&name, visible).map(|()| {
+                    if let Some(id) = find(self, &name) {
+                        if let Some(object) = self.renderer.object_mut(id) {
+                            object.visible = visible;
+                        }
+                        if !visible {
+                            self.selection.retain(|item| item.body() != Some(id));
+                            self.selection_changed();
+                        }
+                    }
+                })
+            }
+            TreeAction::Rename(name, new_name) => {
+                let id = find(self, &name);
+                self.assembly.rename(&name, new_name.clone()).map(|()| {
+                    self.history.rename(&name, &new_name);
+                    if let Some(object) = id.and_then(|id| self.renderer.object_mut(id)) {
+                        object.name = new_name;
+                    }
+                    // Edges are found by part name
+                    self.hover.reset();
+                })
+            }
+            TreeAction::Delete(name) => {
+                self.assembly.remove(&name);
+                self.history.remove(&name);
+                self.upload_assembly();
+                Ok(())
+            }
+            TreeAction::Reorder(name, index) => self
+                .assembly
+                .reorder(&name, index)
+                .map(|()| self.upload_assembly()),
+        };
+        if let Err(e) = result {
+            self.report(&part, e);
+        }
+    }
+
+    /// Measure each selected edge, as clicking it in edge mode does
+    fn measure_selected_edges(&mut self) {
+        let edges: Vec<(ObjectId, usize)> = self.selection.edges().collect();
+        for (id, index) in edges {
+            let Some(object) = self.renderer.scene().object(id) else {
+                continue;
+            };
+            if let Some(edge) = self
+                .hover
+                .part_edges(&object.name, &self.assembly)
+                .get(index)
+            {
+                self.measure.pick_edge(edge);
+            }
+        }
+    }
+
+    /// Feed a click in measure mode to the measure tool. Points snap to
+    /// nearby vertices of the picked part; edges are the part's nearest
+    /// edge within [`SNAP_PIXELS`].
+    fn measure_click(&mut self, hit: PickResult, viewport: glam::Vec2) {
+        let solid = self
+            .renderer
+            .scene()
+            .object(hit.object)
+            .and_then(|object| self.assembly.get(&object.name))
+            .map(|part| part.placed_solid());
+        let pixel = 1.0 / self.renderer.camera.pixels_per_unit(hit.point, viewport.y);
+        let radius = (SNAP_PIXELS * pixel) as f64;
+        let point = Point3::from_vec(from_glam(hit.point));
+        match self.measure.kind {
+            MeasureKind::Edge => {
+                let edges = solid.map(|solid| edge_measures(&solid)).unwrap_or_default();
+                match nearest_edge(&edges, point, radius) {
+                    Some(i) => self.measure.pick_edge(&edges[i]),
+                    None => {
+                        self.status = "No edge under the cursor".to_string();
+                        return;
+                    }
+                }
+            }
+            MeasureKind::Distance | MeasureKind::Angle => {
+                let vertex = solid.and_then(|solid| {
+                    solid
+                        .vertex_iter()
+                        .map(|vertex| vertex.point())
+                        .map(|p| (p.distance(point), p))
+                        .filter(|(distance, _)| *distance <= radius)
+                        .min_by(|a, b| a.0.total_cmp(&b.0))
+                });
+                let snapped = vertex.map_or(hit.point, |(_, p)| to_glam(p.to_vec()));
+                if !self.measure.pick_point(snapped) {
+                    return;
+                }
+            }
+        }
+        if let Some(measurement) = self.measure.results().last() {
+            self.status = measurement.describe(self.units);
+        }
+    }
+
+    /// Pick what is under `cursor` once the picker is due, and ask for
+    /// another frame while a move is still to be picked
+    fn hover_input(&mut self, ui: &egui::Ui, cursor: glam::Vec2, viewport: glam::Vec2) {
+        let now = std::time::Instant::now();
+        if !self.hover.due(cursor.into(), now) {
+            if self.hover.pending(cursor.into()) {
+                ui.ctx().request_repaint_after(hover::PICK_INTERVAL);
+            }
+            return;
+        }
+        let hit = self.pick_at(cursor, viewport);
+        self.cursor_point = hit.map(|hit| Point3::from_vec(from_glam(hit.point)));
+        let on_edge = self.edge_picking() && self.hover.edge_index().is_some();
+        let body = self.hover.object().filter(|_| {
+            let filter = self.selection.filter;
+            !on_edge && (self.measuring || filter.bodies || filter.faces)
+        });
+        let face = hit
+            .filter(|hit| body == Some(hit.object))
+            .and_then(|hit| hit.face);
+        self.renderer.set_hover(body, face);
+    }
+
+    /// Pick the object at `cursor`, and the edge near the hit point, into
+    /// the hover picker
+    fn pick_at(&mut self, cursor: glam::Vec2, viewport: glam::Vec2) -> Option<PickResult> {
+        let hit = self.renderer.pick(cursor, viewport);
+        let scene = self.renderer.scene();
+        let hovered = hit.and_then(|hit| {
+            let name = scene.object(hit.object)?.name.as_str();
+            Some((hit.object, name, Point3::from_vec(from_glam(hit.point))))
+        });
+        let radius = hit.map_or(0.0, |hit| {
+            SNAP_PIXELS / self.renderer.camera.pixels_per_unit(hit.point, viewport.y)
+        });
+        let now = std::time::Instant::now();
+        self.hover
+            .update(cursor.into(), now, hovered, &self.assembly, radius as f64);
+        hit
+    }
+
+    /// Whether a click near an edge picks the edge rather than its body
+    fn edge_picking(&self) -> bool {
+        if self.measuring {
+            self.measure.kind == MeasureKind::Edge
+        } else {
+            self.selection.filter.edges
+        }
+    }
+
+    /// Redraw the pick mark and the measurements on the overlay
+    fn annotate(&mut self) {
+        let overlay = &mut self.renderer.overlay;
+        overlay.clear();
+        overlay.units = self.units;
+        if let Some(p) = self.pick_mark {
+            let text = format!(
+                "({:.3}, {:.3}, {:.3}) {}",
+                p.x,
+                p.y,
+                p.z,
+                self.units.suffix()
+            );
+            overlay.marker(p, PICK_MARK_COLOR);
+            overlay.label(p, text, PICK_MARK_COLOR);
+        }
+        self.measure.annotate(overlay);
+
+        let scene = self.renderer.scene();
+        let display = &self.renderer.display;
+        let mut edges = Vec::new();
+        for (id, index) in self.selection.edges() {
+            let Some(object) = scene.object(id) else {
+                continue;
+            };
+            let part_edges = self.hover.part_edges(&object.name, &self.assembly);
+            if let Some(edge) = part_edges.get(index) {
+                edges.push((edge.points.clone(), display.selection_tint));
+            }
+        }
+        if let Some(edge) = self.hover.edge().filter(|_| self.edge_picking()) {
+            edges.push((edge.points.clone(), display.hover_tint));
+        }
+        let hovered = match self.renderer.hover() {
+            Some(Highlight {
+                object,
+                face: Some(face),
+            }) => Some((object, face, display.hover_tint)),
+            _ => None,
+        };
+        let selected = self.selection.faces();
+        let mut lines = Vec::new();
+        for (id, face, [r, g, b, _]) in selected
+            .map(|(id, face)| (id, face, display.selection_tint))
+            .chain(hovered)
+        {
+            if let Some(object) = scene.object(id) {
+                let world = |p| object.transform.transform_point3(p);
+                for [from, to] in scene.mesh(object.mesh).face_border(face) {
+                    lines.push((world(from), world(to), [r, g, b]));
+                }
+            }
+        }
+        let [r, g, b, _] = display.selection_tint;
+        for (index, curve) in self.selection.sketch_curves() {
+            let Some(placed) = self.sketches.get(index) else {
+                continue;
+            };
+            let Some(curve) = placed.sketch.curves().nth(curve) else {
+                continue;
+            };
+            let lift = |t| to_glam(placed.plane.lift_point(curve.point_at(t)).to_vec());
+            for i in 0..SKETCH_CURVE_SEGMENTS {
+                let t = |i| i as f64 / SKETCH_CURVE_SEGMENTS as f64;
+                lines.push((lift(t(i)), lift(t(i + 1)), [r, g, b]));
+            }
+        }
+        let overlay = &mut self.renderer.overlay;
+        for (a, b, color) in lines {
+            overlay.line(a, b, color);
+        }
+        for (points, [r, g, b, _]) in edges {
+            for pair in points.windows(2) {
+                overlay.line(
+                    to_glam(pair[0].to_vec()),
+                    to_glam(pair[1].to_vec()),
+                    [r, g, b],
+                );
+            }
+        }
+    }
+
+    /// Select only body `id`, or nothing with `None`
+    fn select(&mut self, id: Option<ObjectId>) {
+        self.selection.set(id.map(Selected::Body));
+        self.selection_changed();
+    }
+
+    /// Select `item` alone, or add or drop it with `toggle` as Ctrl+click
+    /// does
+    fn click_select(&mut self, item: Option<Selected>, toggle: bool) {
+        match item {
+            Some(item) if toggle => self.selection.toggle(item),
+            // Ctrl+clicking the background keeps the selection
+            None if toggle => {}
+            item => self.selection.set(item),
+        }
+        self.selection_changed();
+    }
+
+    /// Highlight the selected bodies and put the transform handles on them
+    fn selection_changed(&mut self) {
+        let highlights: Vec<Highlight> = self
+            .selection
+            .bodies()
+            .map(|object| Highlight { object, face: None })
+            .collect();
+        self.renderer.set_highlight(&highlights);
+        let centre = self.selection_bounds().map(|bounds| bounds.center());
+        self.renderer.gizmo = centre.map(|centre| TransformGizmo::new(self.gizmo_mode, centre));
+    }
+
+    /// World-space extent of the selected bodies
+    fn selection_bounds(&self) -> Option<BoundingBox3> {
+        self.selection
+            .bodies()
+            .filter_map(|id| self.renderer.object_bounds(id))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Select what lies wholly inside `area` of the active view: bodies
+    /// whose bounds do and, with the edge filter on, edges of the others;
+    /// `add` keeps what was selected before
+    fn box_select(&mut self, area: egui::Rect, viewport: glam::Vec2, add: bool) {
+        let camera = self.renderer.camera;
+        let inside = |p: glam::Vec3| {
+            camera
+                .world_to_screen(p, viewport)
+                .is_some_and(|p| area.contains(egui::pos2(p.x, p.y)))
+        };
+        let filter = self.selection.filter;
+        let mut items = Vec::new();
+        let scene = self.renderer.scene();
+        // Ghosts cannot be picked, so boxes pass over them too
+        let shown = scene
+            .objects()
+            .filter(|(_, object)| object.visible && object.display != DisplayMode::Ghost);
+        for (id, object) in shown {
+            let Some(bounds) = self.renderer.object_bounds(id) else {
+                continue;
+            };
+            if filter.bodies && bounds.corners().into_iter().all(inside) {
+                items.push(Selected::Body(id));
+            } else if filter.edges {
+                let edges = self.hover.part_edges(&object.name, &self.assembly);
+                let enclosed = edges
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, edge)| edge.points.iter().all(|&p| inside(to_glam(p.to_vec()))));
+                items.extend(enclosed.map(|(index, _)| Selected::Edge(id, index)));
+            }
+        }
+        if !add {
+            self.selection.clear();
+        }
+        self.selection.extend(items);
+        self.selection_changed();
+        self.status = format!("{} selected", self.selection.items().len());
+    }
+
+    /// Write the dragged transforms of the selected bodies back to their
+    /// parts
+    fn commit_transform(&mut self) {
+        let ids: Vec<ObjectId> = self.selection.bodies().collect();
+        for id in ids {
+            let Some(object) = self.renderer.scene().object(id) else {
+                continue;
+            };
+            // Reference meshes are not parts and only move on screen
+            if self.assembly.get(&object.name).is_some() {
+                let transform = to_matrix4(object.transform);
+                let name = object.name.clone();
+                if let Err(e) = self.assembly.set_transform(&name, transform) {
+                    self.report(&name, e);
+                }
+            }
+        }
+        // Edges found before are where the parts were
+        self.hover.reset();
+    }
+
+    /// Frame the selected bodies, or everything shown when none are
+    fn fit_view(&mut self) {
+        let bounds = self
+            .selection_bounds()
+            .or_else(|| self.renderer.scene_bounds());
+        if let Some(bounds) = bounds {
+            self.renderer.fit_view(&bounds);
+        }
+    }
+
+    /// Show the selected bodies see-through, or every ghost solid again
+    /// when none are selected; ghosts cannot be picked
+    fn toggle_ghost(&mut self) {
+        let selected: Vec<ObjectId> = self.selection.bodies().collect();
+        let (ids, display): (Vec<ObjectId>, _) = if selected.is_empty() {
+            (
+                self.renderer.scene().objects().map(|(id, _)| id).collect(),
+                DisplayMode::Shaded,
+            )
+        } else {
+            (selected, DisplayMode::Ghost)
+        };
+        for id in ids {
+            if let Some(object) = self.renderer.object_mut(id) {
+                object.display = display;
+            }
+        }
+        self.select(None);
+    }
+
+    /// Carry out a command picked in the palette
+    fn run_command(&mut self, command: Command, device: &wgpu::Device, queue: &wgpu::Queue) {
+        match command {
+            Command::Open => self.browse_file(device, queue),
+            Command::Save => self.save_project(),
+            Command::FitView => self.fit_view(),
+            Command::Ghost => self.toggle_ghost(),
+            Command::View(view) => self.renderer.camera.set_view(view),
+            Command::Layout(layout) => self.viewports.set_layout(layout, &mut self.renderer),
+            Command::Sketch(plane) => self.start_sketch(SketchSession::new(plane.to_plane())),
+            Command::Extrude => self.extrude_sketches(),
+            Command::Measure => self.measuring = !self.measuring && self.sketching.is_none(),
+            Command::Inspector => self.show_inspector = !self.show_inspector,
+            Command::Console => self.show_console = !self.show_console,
+            Command::Log => self.show_diagnostics = !self.show_diagnostics,
+            Command::Spin => self.spinning = !self.spinning,
         }
     }
 }
 
 impl eframe::App for CadApp {
+    fn on_exit(&mut self) {
+        self.save_session();
+    }
+
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         // Get wgpu state from frame
         let wgpu_state = frame.wgpu_render_state().expect("wgpu required");
+        self.receive_scene(&wgpu_state.device);
+        let dropped: Vec<PathBuf> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|file| file.path.clone())
+                .collect()
+        });
+        if !dropped.is_empty() {
+            self.open_dropped(dropped, &wgpu_state.device, &wgpu_state.queue);
+        }
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            let layer = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop"));
+            let painter = ctx.layer_painter(layer);
+            let screen = ctx.screen_rect();
+            painter.rect_filled(screen, 0.0, egui::Color32::from_black_alpha(160));
+            painter.text(
+                screen.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop to import",
+                egui::TextStyle::Heading.resolve(&ctx.style()),
+                egui::Color32::WHITE,
+            );
+        }
+        if self.scene_jobs.is_busy() {
+            ctx.request_repaint();
+        }
 
         // Toolbar
         egui::TopBottomPanel::top("toolbar").show(ctx, |ui| {
-            ui.label("CAD Viewer - Drag to rotate, scroll to zoom");
+            ui.horizontal(|ui| {
+                ui.label("CAD Viewer - Drag to rotate, scroll to zoom");
+                if self.scene_jobs.is_busy() {
+                    ui.spinner().on_hover_text("Tessellating");
+                }
+                ui.separator();
+                if ui
+                    .button("Fit")
+                    .on_hover_text("Frame selection (F)")
+                    .clicked()
+                {
+                    self.fit_view();
+                }
+                for (mode, label) in [
+                    (GizmoMode::Translate, "Move"),
+                    (GizmoMode::Rotate, "Rotate"),
+                ] {
+                    if ui
+                        .selectable_label(self.gizmo_mode == mode, label)
+                        .clicked()
+                    {
+                        self.gizmo_mode = mode;
+                        if let Some(gizmo) = &mut self.renderer.gizmo {
+                            gizmo.mode = mode;
+                        }
+                    }
+                }
+                let ghost_label = if self.selection.bodies().next().is_some() {
+                    "Ghost"
+                } else {
+                    "Unghost all"
+                };
+                if ui
+                    .button(ghost_label)
+                    .on_hover_text("Show the selection see-through (G)")
+                    .clicked()
+                {
+                    self.toggle_ghost();
+                }
+                ui.separator();
+                ui.label("Select")
+                    .on_hover_text("Ctrl+click adds or drops, Shift+drag selects in a box");
+                for kind in SelectionKind::ALL {
+                    ui.toggle_value(self.selection.filter.allow_mut(kind), kind.name());
+                }
+                for view in StandardView::ALL {
+                    if ui.button(view.name()).clicked() {
+                        self.renderer.camera.set_view(view);
+                    }
+                }
+                let mut layout = self.viewports.layout();
+                egui::ComboBox::from_id_salt("layout")
+                    .selected_text(layout.name())
+                    .show_ui(ui, |ui| {
+                        for option in ViewLayout::ALL {
+                            ui.selectable_value(&mut layout, option, option.name());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Split the viewport; each view has its own camera and display");
+                if layout != self.viewports.layout() {
+                    self.viewports.set_layout(layout, &mut self.renderer);
+                }
+                ui.menu_button("Views", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.view_name);
+                        let name = self.view_name.trim();
+                        if ui
+                            .add_enabled(!name.is_empty(), egui::Button::new("Save"))
+                            .clicked()
+                        {
+                            self.views.save_view(name, &self.renderer.camera);
+                        }
+                    });
+                    let mut removed = None;
+                    for name in self.views.names() {
+                        ui.horizontal(|ui| {
+                            if ui.button(name).clicked() {
+                                self.views.restore_view(name, &mut self.renderer.camera);
+                            }
+                            if ui.small_button("x").on_hover_text("Forget").clicked() {
+                                removed = Some(name.to_string());
+                            }
+                        });
+                    }
+                    if let Some(name) = removed {
+                        self.views.remove(&name);
+                    }
+                });
+                ui.menu_button("Sketch", |ui| {
+                    for (name, plane) in [
+                        ("On XY", Plane::xy()),
+                        ("On XZ", Plane::xz()),
+                        ("On YZ", Plane::yz()),
+                    ] {
+                        if ui.button(name).clicked() {
+                            self.start_sketch(SketchSession::new(plane));
+                            ui.close_menu();
+                        }
+                    }
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            length_value(&mut self.extrude_depth, self.units)
+                                .range(-1e4..=1e4)
+                                .prefix("Depth "),
+                        );
+                        if ui
+                            .add_enabled(!self.sketches.is_empty(), egui::Button::new("Extrude"))
+                            .on_hover_text(
+                                "Extrude the selected sketches, or else the last, into new parts",
+                            )
+                            .clicked()
+                        {
+                            self.extrude_sketches();
+                        }
+                    });
+                });
+                ui.add_enabled_ui(self.sketching.is_none(), |ui| {
+                    ui.toggle_value(&mut self.measuring, "Measure")
+                        .on_hover_text("Click points, edges or corners to measure them");
+                });
+                ui.toggle_value(&mut self.show_inspector, "Inspect")
+                    .on_hover_text("Show the properties of the selected part");
+                ui.toggle_value(&mut self.show_console, "Console")
+                    .on_hover_text("Model with Rhai scripts");
+                let errors = self.diagnostics.count(Severity::Error);
+                let log = match errors {
+                    0 => "Log".to_string(),
+                    errors => format!("Log ({})", errors),
+                };
+                ui.toggle_value(&mut self.show_diagnostics, log)
+                    .on_hover_text("Errors, warnings and reports so far");
+                ui.checkbox(&mut self.renderer.display.show_grid, "Grid");
+                ui.checkbox(&mut self.renderer.display.show_gizmo, "Axes");
+                ui.checkbox(&mut self.renderer.display.show_outline, "Outline");
+                ui.checkbox(&mut self.renderer.display.double_sided, "Double-sided");
+                let mut ortho = self.renderer.camera.projection == Projection::Orthographic;
+                if ui.checkbox(&mut ortho, "Ortho").changed() {
+                    self.renderer.camera.set_projection(if ortho {
+                        Projection::Orthographic
+                    } else {
+                        Projection::Perspective
+                    });
+                }
+                egui::ComboBox::from_id_salt("units")
+                    .selected_text(self.units.suffix())
+                    .show_ui(ui, |ui| {
+                        for units in Units::ALL {
+                            ui.selectable_value(&mut self.units, units, units.name());
+                        }
+                    })
+                    .response
+                    .on_hover_text("What model coordinates are in");
+                ui.menu_button("Camera", |ui| {
+                    let camera = &mut self.renderer.camera;
+                    let mut rotation = camera.rotation;
+                    for mode in RotationMode::ALL {
+                        ui.radio_value(&mut rotation, mode, mode.name());
+                    }
+                    if rotation != camera.rotation {
+                        camera.set_rotation(rotation);
+                    }
+                    let settings = &mut camera.settings;
+                    ui.add(
+                        egui::Slider::new(&mut settings.orbit_speed, 0.001..=0.05)
+                            .logarithmic(true)
+                            .text("Orbit speed"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut settings.zoom_speed, 0.01..=0.5)
+                            .logarithmic(true)
+                            .text("Zoom speed"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut settings.pan_speed, 0.1..=4.0)
+                            .logarithmic(true)
+                            .text("Pan speed"),
+                    );
+                    ui.checkbox(&mut settings.invert_x, "Invert horizontal");
+                    ui.checkbox(&mut settings.invert_y, "Invert vertical");
+                    ui.checkbox(&mut settings.zoom_to_cursor, "Zoom to cursor");
+                    ui.checkbox(&mut camera.auto_clip, "Auto clip")
+                        .on_hover_text("Fit the clipping planes to the scene");
+                    if !camera.auto_clip {
+                        ui.add(
+                            egui::DragValue::new(&mut camera.near)
+                                .range(1e-4..=camera.far)
+                                .speed(0.01)
+                                .prefix("Near "),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut camera.far)
+                                .range(camera.near..=1e7)
+                                .prefix("Far "),
+                        );
+                    }
+                    if ui.button("Reset").clicked() {
+                        camera.settings = Default::default();
+                    }
+                });
+                ui.checkbox(&mut self.spinning, "Spin");
+                ui.add(
+                    egui::DragValue::new(&mut self.turntable.speed_deg)
+                        .range(-360.0..=360.0)
+                        .suffix("°/s"),
+                );
+                if ui
+                    .button("Capture")
+                    .on_hover_text("Render one turn as numbered PNG frames")
+                    .clicked()
+                {
+                    self.capture_turntable();
+                }
+                let samples = self.renderer.sample_count();
+                egui::ComboBox::from_id_salt("msaa")
+                    .selected_text(format!("MSAA {}x", samples))
+                    .show_ui(ui, |ui| {
+                        let counts = crate::renderer::supported_sample_counts(
+                            &wgpu_state.adapter,
+                            wgpu_state.target_format,
+                        );
+                        for count in counts {
+                            if ui
+                                .selectable_label(count == samples, format!("{}x", count))
+                                .clicked()
+                            {
+                                if let Err(e) = self.renderer.set_sample_count(
+                                    &wgpu_state.device,
+                                    &wgpu_state.adapter,
+                                    count,
+                                ) {
+                                    self.report("", e);
+                                }
+                            }
+                        }
+                    });
+                let mut detail = MeshDetail::ALL
+                    .iter()
+                    .position(|&detail| detail == self.mesh_detail)
+                    .unwrap_or(1);
+                let slider = egui::Slider::new(&mut detail, 0..=MeshDetail::ALL.len() - 1)
+                    .show_value(false)
+                    .text(self.mesh_detail.name());
+                if ui
+                    .add(slider)
+                    .on_hover_text("How finely surfaces are tessellated for display")
+                    .changed()
+                {
+                    self.mesh_detail = MeshDetail::ALL[detail];
+                    self.upload_assembly();
+                }
+                egui::ComboBox::from_id_salt("normals")
+                    .selected_text(format!("Normals: {}", self.normal_mode.name()))
+                    .show_ui(ui, |ui| {
+                        for mode in NormalMode::ALL {
+                            if ui
+                                .selectable_label(mode == self.normal_mode, mode.name())
+                                .clicked()
+                            {
+                                self.normal_mode = mode;
+                                self.upload_assembly();
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "Smooth shades across creases up to 30°, flat shades each triangle",
+                    );
+                let quality = self.renderer.shadow_quality();
+                egui::ComboBox::from_id_salt("shadows")
+                    .selected_text(format!("Shadows: {}", quality.name()))
+                    .show_ui(ui, |ui| {
+                        for option in ShadowQuality::ALL {
+                            if ui
+                                .selectable_label(option == quality, option.name())
+                                .clicked()
+                            {
+                                self.renderer.set_shadow_quality(&wgpu_state.device, option);
+                            }
+                        }
+                    });
+                let shading = &mut self.renderer.display.shading;
+                egui::ComboBox::from_id_salt("shading")
+                    .selected_text(format!("Shading: {}", shading.name()))
+                    .show_ui(ui, |ui| {
+                        for option in ShadingMode::ALL {
+                            ui.selectable_value(shading, option, option.name());
+                        }
+                    });
+                ui.menu_button("Lighting", |ui| {
+                    let lighting = &mut self.renderer.display.lighting;
+                    for (name, light) in [("Key", &mut lighting.key), ("Fill", &mut lighting.fill)]
+                    {
+                        ui.horizontal(|ui| {
+                            ui.label(name);
+                            ui.color_edit_button_rgb(&mut light.color);
+                            ui.add(egui::Slider::new(&mut light.intensity, 0.0..=2.0));
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Ambient");
+                        ui.color_edit_button_rgb(&mut lighting.ambient);
+                    });
+                    ui.add(egui::Slider::new(&mut lighting.specular, 0.0..=1.0).text("Specular"));
+                    ui.add(
+                        egui::Slider::new(&mut lighting.shininess, 1.0..=256.0)
+                            .logarithmic(true)
+                            .text("Shininess"),
+                    );
+                    if ui.button("Reset").clicked() {
+                        *lighting = Default::default();
+                    }
+                });
+                ui.menu_button("Background", |ui| {
+                    let display = &mut self.renderer.display;
+                    for style in BackgroundStyle::ALL {
+                        ui.radio_value(&mut display.background_style, style, style.name());
+                    }
+                    let solid = display.background_style == BackgroundStyle::Solid;
+                    if !solid {
+                        color_row(ui, "Top", &mut display.background_top);
+                    }
+                    let bottom = if solid { "Color" } else { "Bottom" };
+                    color_row(ui, bottom, &mut display.background);
+                });
+                ui.menu_button("Appearance", |ui| self.appearance_menu(ui));
+                ui.separator();
+                ui.label("File:");
+                let field = ui.text_edit_singleline(&mut self.open_path);
+                let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Open").clicked() || entered {
+                    self.open_file(&wgpu_state.device, &wgpu_state.queue);
+                }
+                if ui
+                    .button("Browse...")
+                    .on_hover_text("Open or import a file")
+                    .clicked()
+                {
+                    self.browse_file(&wgpu_state.device, &wgpu_state.queue);
+                }
+                if ui.button("Save").clicked() {
+                    self.save_project();
+                }
+                ui.menu_button("Recent", |ui| {
+                    if self.session.recent.is_empty() {
+                        ui.weak("No recent projects");
+                    }
+                    let mut picked = None;
+                    for path in &self.session.recent {
+                        let name = path.file_name().unwrap_or(path.as_os_str());
+                        if ui
+                            .button(name.to_string_lossy())
+                            .on_hover_text(path.display().to_string())
+                            .clicked()
+                        {
+                            picked = Some(path.clone());
+                            ui.close_menu();
+                        }
+                    }
+                    if let Some(path) = picked {
+                        self.load_file(&path, &wgpu_state.device, &wgpu_state.queue);
+                    }
+                    if !self.session.recent.is_empty() && ui.button("Clear").clicked() {
+                        self.session.recent.clear();
+                        ui.close_menu();
+                    }
+                });
+                if ui
+                    .add_enabled(
+                        self.selection.bodies().next().is_some(),
+                        egui::Button::new("Texture"),
+                    )
+                    .on_hover_text("Wrap the PNG named in the file field around the selection")
+                    .clicked()
+                {
+                    self.apply_texture(&wgpu_state.device, &wgpu_state.queue);
+                }
+            });
+        });
+
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(&self.status);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(self.units.name());
+                    ui.separator();
+                    let cursor = match &self.sketching {
+                        Some(_) => self
+                            .sketch_cursor
+                            .map(|point| status::describe_sketch_point(point, self.units)),
+                        None => self
+                            .cursor_point
+                            .map(|point| status::describe_point(point, self.units)),
+                    };
+                    ui.monospace(cursor.unwrap_or_default());
+                    ui.separator();
+                    ui.label(status::describe_selection(
+                        &self.selection,
+                        self.renderer.scene(),
+                        &self.sketches,
+                    ));
+                });
+            });
         });
 
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::P)) {
+            self.palette.toggle();
+        }
+        if let Some(command) = self.palette.show(ctx) {
+            self.run_command(command, &wgpu_state.device, &wgpu_state.queue);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F)) && !ctx.wants_keyboard_input() {
+            self.fit_view();
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::G)) && !ctx.wants_keyboard_input() {
+            self.toggle_ghost();
+        }
+
+        let dt = ctx.input(|i| i.stable_dt);
+        self.viewports.animate(dt, &mut self.renderer);
+        if self.spinning {
+            self.turntable.advance(&mut self.renderer.camera, dt);
+        }
+
         // 3D viewport
+        let mut finish = false;
+        let mut exit = false;
+        let units = self.units;
+        let show_snap = &mut self.show_snap;
+        if let Some(session) = &mut self.sketching {
+            egui::TopBottomPanel::top("sketch_bar").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Sketch - click to place points, Esc stops a curve");
+                    ui.separator();
+                    ui.label(format!("Grid {}", units.format(session.grid_spacing())));
+                    ui.toggle_value(show_snap, "Snap")
+                        .on_hover_text("Grid, point and angle snapping");
+                    ui.separator();
+                    for tool in SketchTool::ALL {
+                        if ui
+                            .selectable_label(session.tool == tool, tool.name())
+                            .clicked()
+                        {
+                            session.set_tool(tool);
+                        }
+                    }
+                    if ui.button("Undo").clicked() {
+                        session.undo();
+                    }
+                    finish = ui
+                        .add_enabled(!session.curves().is_empty(), egui::Button::new("Finish"))
+                        .clicked();
+                    exit = ui.button("Discard").clicked();
+                });
+            });
+        }
+        if finish {
+            self.finish_sketch();
+        }
+        if exit {
+            self.end_sketch();
+        }
+
+        if self.show_diagnostics {
+            egui::TopBottomPanel::bottom("diagnostics")
+                .resizable(true)
+                .show(ctx, |ui| self.diagnostics.show(ui));
+        }
+
+        if self.show_console {
+            let output = egui::TopBottomPanel::bottom("console")
+                .resizable(true)
+                .show(ctx, |ui| self.console.show(ui, &self.assembly, self.units))
+                .inner;
+            if let Some(output) = output {
+                self.add_script_output(output);
+            }
+        }
+
+        egui::SidePanel::left("model_tree")
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.heading("Parts");
+                let scene = self.renderer.scene();
+                let parts: Vec<String> = self
+                    .selection
+                    .bodies()
+                    .filter_map(|id| scene.object(id))
+                    .map(|object| object.name.clone())
+                    .collect();
+                let action = self.tree.show(
+                    ui,
+                    &self.assembly,
+                    &self.history,
+                    &self.sketches,
+                    &parts,
+                    &self.selection,
+                );
+                if let Some(action) = action {
+                    self.apply_tree_action(action);
+                }
+            });
+
+        if self.show_snap && self.sketching.is_some() {
+            egui::SidePanel::right("snap")
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.heading("Snap");
+                    let snap = &mut self.snap;
+                    ui.checkbox(&mut snap.to_grid, "Grid");
+                    ui.add_enabled_ui(snap.to_grid, |ui| {
+                        ui.horizontal(|ui| {
+                            let mut fixed = snap.grid > 0.0;
+                            if ui
+                                .checkbox(&mut fixed, "Fixed spacing")
+                                .on_hover_text("Otherwise the grid follows the zoom")
+                                .changed()
+                            {
+                                snap.grid = if fixed { 1.0 } else { 0.0 };
+                            }
+                            if fixed {
+                                ui.add(length_value(&mut snap.grid, self.units).range(1e-3..=1e6));
+                            }
+                        });
+                    });
+                    ui.checkbox(&mut snap.to_endpoints, "Endpoints");
+                    ui.checkbox(&mut snap.to_midpoints, "Midpoints");
+                    ui.checkbox(&mut snap.to_centers, "Centers");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut snap.to_angle, "Angle");
+                        ui.add_enabled(
+                            snap.to_angle,
+                            egui::DragValue::new(&mut snap.angle_step)
+                                .range(1.0..=90.0)
+                                .suffix("°"),
+                        );
+                    });
+                    ui.weak("Points win over angles, and angles over the grid");
+                });
+        }
+
+        if self.show_inspector {
+            egui::SidePanel::right("inspector")
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.heading("Inspector");
+                    let object = self
+                        .selection
+                        .primary_body()
+                        .and_then(|id| self.renderer.scene().object(id));
+                    let part = object.and_then(|object| self.assembly.get(&object.name));
+                    let triangles = object
+                        .map(|object| self.renderer.scene().mesh(object.mesh).indices.len() / 3);
+                    self.inspector.show(ui, part, triangles, self.units);
+                });
+        }
+
+        if self.measuring {
+            egui::SidePanel::right("measurements")
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.heading("Measure");
+                    ui.horizontal(|ui| {
+                        for kind in MeasureKind::ALL {
+                            if ui
+                                .selectable_label(self.measure.kind == kind, kind.name())
+                                .clicked()
+                            {
+                                self.measure.set_kind(kind);
+                            }
+                        }
+                    });
+                    ui.weak(self.measure.kind.hint());
+                    let has_edges = self.selection.edges().next().is_some();
+                    if ui
+                        .add_enabled(has_edges, egui::Button::new("Measure selected edges"))
+                        .clicked()
+                    {
+                        self.measure_selected_edges();
+                    }
+                    ui.separator();
+                    let mut removed = None;
+                    for (index, measurement) in self.measure.results().iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("x").on_hover_text("Remove").clicked() {
+                                removed = Some(index);
+                            }
+                            ui.label(measurement.describe(self.units));
+                        });
+                    }
+                    if let Some(index) = removed {
+                        self.measure.remove(index);
+                    }
+                    if self.measure.results().is_empty() {
+                        ui.weak("No measurements");
+                    } else if ui.button("Clear").clicked() {
+                        self.measure.clear();
+                    }
+                });
+        }
+
         egui::CentralPanel::default()
             .frame(egui::Frame::NONE) // Use NONE instead of none()
             .show(ctx, |ui| {
-                let available = ui.available_size();
-                let width = available.x as u32;
-                let height = available.y as u32;
+                let (panel, _) = ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+                let cells = self.viewports.layout().cells(panel);
+
+                // The view being dragged in, or else the one under the
+                // cursor, takes the input
+                let responses: Vec<egui::Response> = cells
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| {
+                        ui.interact(
+                            *cell,
+                            ui.id().with(("view", i)),
+                            egui::Sense::click_and_drag(),
+                        )
+                    })
+                    .collect();
+                let pointed = responses
+                    .iter()
+                    .position(|r| r.dragged())
+                    .or_else(|| responses.iter().position(|r| r.hovered()));
+                if let Some(i) = pointed {
+                    self.viewports.activate(i, &mut self.renderer);
+                }
+                let Some(response) = responses.get(self.viewports.active()).cloned() else {
+                    return;
+                };
+                let rect = response.rect;
+                // Box being dragged out, drawn over the views
+                let mut box_area = None;
 
-                // Ensure render texture exists and is correct size
-                self.ensure_render_texture(wgpu_state, width, height);
+                // Dragging a handle moves the selection, anywhere else orbits
+                let viewport = glam::Vec2::new(rect.width(), rect.height());
+                let local =
+                    |pos: egui::Pos2| glam::Vec2::new(pos.x - rect.min.x, pos.y - rect.min.y);
+                if response.drag_started() {
+                    let origin = ui.input(|i| i.pointer.press_origin());
+                    if let (Some(gizmo), Some(origin)) = (&mut self.renderer.gizmo, origin) {
+                        gizmo.begin_drag(&self.renderer.camera, local(origin), viewport);
+                    }
+                }
+                let handle_drag = self.renderer.gizmo.is_some_and(|g| g.is_dragging());
+                let boxing = response.drag_started_by(egui::PointerButton::Primary)
+                    && !handle_drag
+                    && self.sketching.is_none()
+                    && ui.input(|i| i.modifiers.shift);
+                if boxing {
+                    self.select_box = ui.input(|i| i.pointer.press_origin());
+                }
+                if response.dragged() && self.select_box.is_none() {
+                    match (handle_drag, response.interact_pointer_pos()) {
+                        (true, Some(pos)) => {
+                            let camera = self.renderer.camera;
+                            let delta = self
+                                .renderer
+                                .gizmo
+                                .as_mut()
+                                .map_or(glam::Mat4::IDENTITY, |g| {
+                                    g.drag(&camera, local(pos), viewport)
+                                });
+                            let ids: Vec<ObjectId> = self.selection.bodies().collect();
+                            for id in ids {
+                                if let Some(object) = self.renderer.object_mut(id) {
+                                    object.transform = delta * object.transform;
+                                }
+                            }
+                        }
+                        _ => {
+                            // Right or middle drags pan
+                            // and so do all drags while sketching, which keeps
+                            // the view on the plane
+                            let delta = response.drag_delta();
+                            let orbit = response.dragged_by(egui::PointerButton::Primary)
+                                && self.sketching.is_none();
+                            if orbit {
+                                self.renderer.camera.orbit(delta.x, delta.y);
+                            } else {
+                                self.renderer.camera.pan(delta.x, delta.y, viewport.y);
+                            }
+                        }
+                    }
+                }
+                if response.drag_stopped() && handle_drag {
+                    if let Some(gizmo) = &mut self.renderer.gizmo {
+                        gizmo.end_drag();
+                    }
+                    self.commit_transform();
+                }
+                if let Some(origin) = self.select_box {
+                    let pointer = ui.input(|i| i.pointer.latest_pos());
+                    let area = pointer.map(|pos| egui::Rect::from_two_pos(origin, pos));
+                    if response.drag_stopped() {
+                        self.select_box = None;
+                        if let Some(area) = area {
+                            let add = ui.input(|i| i.modifiers.command);
+                            self.box_select(area.translate(-rect.min.to_vec2()), viewport, add);
+                        }
+                    } else if let Some(area) = area {
+                        box_area = Some(area);
+                    }
+                }
 
-                // Handle input
-                let (rect, response) =
-                    ui.allocate_exact_size(available, egui::Sense::click_and_drag());
+                if self.sketching.is_some() {
+                    self.sketch_input(ui, &response, rect);
+                } else if let Some(pos) = response
+                    .clicked()
+                    .then(|| response.interact_pointer_pos())
+                    .flatten()
+                {
+                    let cursor = glam::Vec2::new(pos.x - rect.min.x, pos.y - rect.min.y);
+                    let viewport = glam::Vec2::new(rect.width(), rect.height());
+                    let hit = self.pick_at(cursor, viewport);
+                    if self.measuring {
+                        if let Some(hit) = hit {
+                            self.measure_click(hit, viewport);
+                        }
+                    } else {
+                        let faces = self.selection.filter.faces;
+                        let item = hit.map(|hit| match (self.hover.edge_index(), hit.face) {
+                            (Some(edge), _) if self.edge_picking() => {
+                                Selected::Edge(hit.object, edge)
+                            }
+                            (_, Some(face)) if faces => Selected::Face(hit.object, face),
+                            _ => Selected::Body(hit.object),
+                        });
+                        let toggle = ui.input(|i| i.modifiers.command);
+                        self.click_select(item, toggle);
+                        // Mark the picked point until the next click
+                        self.pick_mark = hit.map(|hit| hit.point);
+                        self.status = match hit {
+                            Some(hit) => {
+                                let scene = self.renderer.scene();
+                                let name = scene.object(hit.object).map_or("", |o| o.name.as_str());
+                                let face = hit
+                                    .face
+                                    .map_or(String::new(), |face| format!(" face {}", face + 1));
+                                let p = hit.point;
+                                format!(
+                                    "Picked {}{} at ({:.3}, {:.3}, {:.3}) {}",
+                                    name,
+                                    face,
+                                    p.x,
+                                    p.y,
+                                    p.z,
+                                    self.units.suffix()
+                                )
+                            }
+                            None => String::new(),
+                        };
+                    }
+                }
 
-                if response.dragged() {
-                    let delta = response.drag_delta();
-                    self.renderer.camera.orbit(delta.x, delta.y);
+                // Pre-highlight what a click would pick
+                match response.hover_pos() {
+                    Some(pos) if !response.dragged() && self.sketching.is_none() => {
+                        let cursor = glam::Vec2::new(pos.x - rect.min.x, pos.y - rect.min.y);
+                        self.hover_input(ui, cursor, glam::Vec2::new(rect.width(), rect.height()));
+                    }
+                    _ => {
+                        self.cursor_point = None;
+                        self.hover.clear();
+                        self.renderer.set_hover(None, None);
+                    }
                 }
 
                 if response.hovered() {
                     let scroll = ui.input(|i| i.raw_scroll_delta.y);
                     if scroll != 0.0 {
-                        self.renderer.camera.zoom(scroll * 0.01);
+                        let viewport = glam::Vec2::new(rect.width(), rect.height());
+                        let ray = response.hover_pos().map(|pos| {
+                            let cursor = glam::Vec2::new(pos.x - rect.min.x, pos.y - rect.min.y);
+                            self.renderer.camera.screen_ray(cursor, viewport)
+                        });
+                        self.renderer.camera.zoom(scroll * 0.01, ray.as_ref());
                     }
                 }
 
-                // Render to our texture
-                if let Some(rt) = &self.render_texture {
-                    let mut encoder =
-                        wgpu_state
-                            .device
-                            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                                label: Some("CAD Encoder"),
-                            });
-
-                    self.renderer
-                        .render(&mut encoder, &rt.view, &wgpu_state.queue, width, height);
-
-                    wgpu_state.queue.submit(std::iter::once(encoder.finish()));
+                if self.sketching.is_none() {
+                    self.annotate();
+                }
 
-                    // Display texture
-                    ui.painter().image(
-                        rt.egui_texture_id,
-                        rect,
-                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                        egui::Color32::WHITE,
-                    );
+                self.viewports
+                    .show(ui, &mut self.renderer, wgpu_state, &cells);
+                if let Some(area) = box_area {
+                    let stroke = egui::Stroke::new(1.0, ui.visuals().selection.stroke.color);
+                    ui.painter()
+                        .rect_stroke(area, 0.0, stroke, egui::StrokeKind::Inside);
                 }
             });
 
         ctx.request_repaint();
     }
 }
+
+/// Scene name of reference mesh `index`
+fn reference_name(index: usize) -> String {
+    format!("reference {}", index + 1)
+}
+
+fn to_glam(v: Vector3) -> glam::Vec3 {
+    glam::Vec3::new(v.x as f32, v.y as f32, v.z as f32)
+}
+
+fn from_glam(v: glam::Vec3) -> Vector3 {
+    Vector3::new(v.x as f64, v.y as f64, v.z as f64)
+}
+
+/// Field for a length in `units` that also takes values typed with another
+/// unit's symbol, such as "1 in"
+fn length_value(value: &mut f64, units: Units) -> egui::DragValue<'_> {
+    egui::DragValue::new(value)
+        .suffix(format!(" {}", units.suffix()))
+        .custom_parser(move |text| units.parse(text).ok())
+}
+
+/// Labelled picker for a linear RGB color kept in double precision
+fn color_row(ui: &mut egui::Ui, label: &str, color: &mut [f64; 3]) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        let mut rgb = color.map(|c| c as f32);
+        if ui.color_edit_button_rgb(&mut rgb).changed() {
+            *color = rgb.map(f64::from);
+        }
+    });
+}
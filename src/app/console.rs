@@ -0,0 +1,155 @@
+use crate::model::Assembly;
+use crate::script::{ScriptEngine, ScriptOutput, SCRIPT_EXTENSION};
+use crate::units::Units;
+use eframe::egui;
+
+/// Console lines kept before the oldest are dropped
+const MAX_LINES: usize = 500;
+
+enum Line {
+    Input(String),
+    Output(String),
+    Error(String),
+}
+
+/// Panel running Rhai code against the model, one block at a time; the
+/// blocks that ran cleanly make up a recipe that can be saved and run again
+#[derive(Default)]
+pub struct ScriptConsole {
+    engine: ScriptEngine,
+    /// Code in the editor
+    code: String,
+    lines: Vec<Line>,
+    /// Every block that ran without an error, in order
+    recipe: String,
+}
+
+impl ScriptConsole {
+    /// Draw the console; returns what a run added for the app to take in
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        assembly: &Assembly,
+        units: Units,
+    ) -> Option<ScriptOutput> {
+        let mut output = None;
+        ui.horizontal(|ui| {
+            let run = ui
+                .button("Run")
+                .on_hover_text("Run the code below (Ctrl+Enter)")
+                .clicked()
+                || ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Enter));
+            if run && !self.code.trim().is_empty() {
+                self.engine.set_assembly(assembly);
+                self.engine.set_units(units);
+                output = self.run();
+            }
+            if ui.button("Open...").clicked() {
+                self.open();
+            }
+            if ui
+                .add_enabled(!self.recipe.is_empty(), egui::Button::new("Save recipe..."))
+                .on_hover_text("Save the code that ran, to run again later")
+                .clicked()
+            {
+                self.save();
+            }
+            if ui
+                .button("Reset")
+                .on_hover_text("Forget variables and the recipe")
+                .clicked()
+            {
+                self.engine.reset();
+                self.recipe.clear();
+                self.lines.clear();
+            }
+        });
+        egui::ScrollArea::vertical()
+            .max_height(ui.available_height() * 0.5)
+            .stick_to_bottom(true)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for line in &self.lines {
+                    let (text, color) = match line {
+                        Line::Input(text) => (text, ui.visuals().weak_text_color()),
+                        Line::Output(text) => (text, ui.visuals().text_color()),
+                        Line::Error(text) => (text, ui.visuals().error_fg_color),
+                    };
+                    ui.label(egui::RichText::new(text).monospace().color(color));
+                }
+            });
+        ui.add(
+            egui::TextEdit::multiline(&mut self.code)
+                .code_editor()
+                .desired_rows(4)
+                .desired_width(f32::INFINITY)
+                .hint_text(
+                    "add_part(\"plate\", extrude(sketch(rectangle(0, 0, 40, 20)), plane_xy(), 5));",
+                ),
+        );
+        output
+    }
+
+    fn run(&mut self) -> Option<ScriptOutput> {
+        let code = std::mem::take(&mut self.code);
+        self.push(Line::Input(code.trim_end().to_string()));
+        match self.engine.run(&code) {
+            Ok(output) => {
+                self.recipe.push_str(code.trim_end());
+                self.recipe.push('\n');
+                for text in &output.log {
+                    self.push(Line::Output(text.clone()));
+                }
+                if let Some(value) = &output.value {
+                    self.push(Line::Output(value.clone()));
+                }
+                Some(output)
+            }
+            Err(e) => {
+                self.push(Line::Error(e.to_string()));
+                // Keep the code to fix it
+                self.code = code;
+                None
+            }
+        }
+    }
+
+    fn push(&mut self, line: Line) {
+        self.lines.push(line);
+        let excess = self.lines.len().saturating_sub(MAX_LINES);
+        self.lines.drain(..excess);
+    }
+
+    /// Load a script into the editor
+    fn open(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Rhai scripts", &[SCRIPT_EXTENSION])
+            .pick_file()
+        else {
+            return;
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(code) => self.code = code,
+            Err(e) => self.push(Line::Error(format!(
+                "could not read {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    fn save(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Rhai scripts", &[SCRIPT_EXTENSION])
+            .set_file_name(format!("recipe.{}", SCRIPT_EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+        let line = match std::fs::write(&path, &self.recipe) {
+            Ok(()) => Line::Output(format!("Saved {}", path.display())),
+            Err(e) => Line::Error(format!("could not write {}: {}", path.display(), e)),
+        };
+        self.push(line);
+    }
+}
@@ -0,0 +1,153 @@
+use eframe::egui;
+use std::time::Instant;
+
+/// Entries kept before the oldest are dropped
+const MAX_ENTRIES: usize = 500;
+
+/// How bad a logged event is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Info, Severity::Warning, Severity::Error];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+
+    fn color(&self, ui: &egui::Ui) -> egui::Color32 {
+        match self {
+            Severity::Info => ui.visuals().text_color(),
+            Severity::Warning => ui.visuals().warn_fg_color,
+            Severity::Error => ui.visuals().error_fg_color,
+        }
+    }
+}
+
+/// One logged event and what it was about
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Part, sketch or file the event concerns
+    pub entity: Option<String>,
+    pub time: Instant,
+}
+
+/// Log of failed operations, import warnings and repairs, so failures are
+/// reported where they can be read again rather than lost or fatal
+pub struct Diagnostics {
+    entries: Vec<Diagnostic>,
+    /// Least severe entry shown
+    filter: Severity,
+    started: Instant,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            filter: Severity::Info,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Diagnostics {
+    pub fn push(&mut self, severity: Severity, entity: Option<&str>, message: impl Into<String>) {
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(Diagnostic {
+            severity,
+            message: message.into(),
+            entity: entity.map(String::from),
+            time: Instant::now(),
+        });
+    }
+
+    pub fn entries(&self) -> &[Diagnostic] {
+        &self.entries
+    }
+
+    /// Number of entries at `severity`
+    pub fn count(&self, severity: Severity) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.severity == severity)
+            .count()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Entries newest first, with a severity filter
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Show");
+            for severity in Severity::ALL {
+                let label = format!("{} ({})", severity.name(), self.count(severity));
+                ui.selectable_value(&mut self.filter, severity, label)
+                    .on_hover_text("This and anything more severe");
+            }
+            if ui.button("Clear").clicked() {
+                self.clear();
+            }
+        });
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                let filter = self.filter;
+                let shown = self.entries().iter().rev().filter(|e| e.severity >= filter);
+                let mut empty = true;
+                for entry in shown {
+                    empty = false;
+                    ui.horizontal_wrapped(|ui| {
+                        let seconds = entry.time.duration_since(self.started).as_secs_f32();
+                        ui.monospace(format!("{:>8.1}s", seconds));
+                        ui.colored_label(entry.severity.color(ui), entry.severity.name());
+                        if let Some(entity) = &entry.entity {
+                            ui.strong(entity);
+                        }
+                        ui.label(&entry.message);
+                    });
+                }
+                if empty {
+                    ui.weak("Nothing to report");
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostics_log() {
+        let mut log = Diagnostics::default();
+        log.push(Severity::Info, None, "opened");
+        log.push(Severity::Error, Some("bracket"), "boolean failed");
+        assert_eq!(log.count(Severity::Error), 1);
+        assert_eq!(log.entries()[1].entity.as_deref(), Some("bracket"));
+        assert!(Severity::Error > Severity::Warning);
+
+        for i in 0..MAX_ENTRIES {
+            log.push(Severity::Warning, None, i.to_string());
+        }
+        assert_eq!(log.entries().len(), MAX_ENTRIES);
+        assert_eq!(log.count(Severity::Info), 0);
+        log.clear();
+        assert!(log.entries().is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::model::{edge_measures, nearest_edge, Assembly, EdgeMeasure};
+use crate::renderer::scene::ObjectId;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use truck_geometry::prelude::Point3;
+
+/// Shortest time between two hover picks while the cursor moves
+pub const PICK_INTERVAL: Duration = Duration::from_millis(40);
+
+/// What is under the cursor, picked at most every [`PICK_INTERVAL`] so
+/// moving over a large scene stays cheap
+#[derive(Default)]
+pub struct HoverPicker {
+    /// Cursor position and time of the last pick
+    last: Option<([f32; 2], Instant)>,
+    object: Option<ObjectId>,
+    /// Hovered edge, by part name and index in the part's edges
+    edge: Option<(String, usize)>,
+    /// Edges of the parts hovered so far, found once per part
+    edges: HashMap<String, Vec<EdgeMeasure>>,
+}
+
+impl HoverPicker {
+    /// Whether the cursor at `cursor` should be picked again at `now`; it
+    /// must have moved, and the pick before be old enough
+    pub fn due(&self, cursor: [f32; 2], now: Instant) -> bool {
+        match self.last {
+            Some((last, time)) => last != cursor && now - time >= PICK_INTERVAL,
+            None => true,
+        }
+    }
+
+    /// Whether a move has not been picked yet, so another frame is needed
+    pub fn pending(&self, cursor: [f32; 2]) -> bool {
+        self.last.is_some_and(|(last, _)| last != cursor)
+    }
+
+    /// Take the pick at `cursor`: `hit` is the hovered object with the part
+    /// it shows and the hit point, and `radius` how near an edge must be
+    pub fn update(
+        &mut self,
+        cursor: [f32; 2],
+        now: Instant,
+        hit: Option<(ObjectId, &str, Point3)>,
+        assembly: &Assembly,
+        radius: f64,
+    ) {
+        self.last = Some((cursor, now));
+        self.object = hit.map(|(object, _, _)| object);
+        self.edge = None;
+        let Some((_, name, point)) = hit else {
+            return;
+        };
+        let edges = self.part_edges(name, assembly);
+        self.edge = nearest_edge(edges, point, radius).map(|index| (name.to_string(), index));
+    }
+
+    /// Edges of part `name`, in [`edge_measures`] order; none for meshes
+    /// that are not parts
+    pub fn part_edges(&mut self, name: &str, assembly: &Assembly) -> &[EdgeMeasure] {
+        self.edges.entry(name.to_string()).or_insert_with(|| {
+            assembly
+                .get(name)
+                .map(|part| edge_measures(&part.placed_solid()))
+                .unwrap_or_default()
+        })
+    }
+
+    /// Forget what is hovered; the edges found are kept
+    pub fn clear(&mut self) {
+        self.last = None;
+        self.object = None;
+        self.edge = None;
+    }
+
+    /// Forget the edges found too, as after parts change
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn object(&self) -> Option<ObjectId> {
+        self.object
+    }
+
+    /// Hovered edge, when the cursor is near one of the hovered part's
+    pub fn edge(&self) -> Option<&EdgeMeasure> {
+        let (name, index) = self.edge.as_ref()?;
+        self.edges.get(name)?.get(*index)
+    }
+
+    /// Index of the hovered edge in its part's edges
+    pub fn edge_index(&self) -> Option<usize> {
+        self.edge.as_ref().map(|(_, index)| *index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::cylinder;
+    use crate::renderer::scene::Scene;
+    use truck_geometry::prelude::{EuclideanSpace, Matrix4, SquareMatrix, Vector3};
+
+    #[test]
+    fn test_hover_picker() {
+        let mut assembly = Assembly::default();
+        let rod = cylinder(Point3::origin(), Vector3::unit_z() * 4.0, 1.5).unwrap();
+        assembly.add("rod", rod, Matrix4::identity()).unwrap();
+        let object = Scene::from_assembly(&assembly, 0.01).find("rod").unwrap();
+
+        let mut hover = HoverPicker::default();
+        let start = Instant::now();
+        assert!(hover.due([0.0, 0.0], start));
+        let rim = Point3::new(0.0, -1.5, 4.0);
+        hover.update(
+            [0.0, 0.0],
+            start,
+            Some((object, "rod", rim)),
+            &assembly,
+            0.1,
+        );
+        assert_eq!(hover.object(), Some(object));
+        assert!(hover.edge().unwrap().circle.is_some());
+        assert!(hover.edge_index().is_some());
+
+        // Too soon, or not moved
+        assert!(!hover.due([1.0, 0.0], start + PICK_INTERVAL / 2));
+        assert!(hover.pending([1.0, 0.0]));
+        assert!(!hover.due([0.0, 0.0], start + PICK_INTERVAL));
+        assert!(hover.due([1.0, 0.0], start + PICK_INTERVAL));
+
+        let side = Point3::new(0.0, -1.5, 2.0);
+        let later = start + PICK_INTERVAL;
+        hover.update(
+            [1.0, 0.0],
+            later,
+            Some((object, "rod", side)),
+            &assembly,
+            0.1,
+        );
+        assert!(hover.edge().is_none());
+        hover.update([2.0, 0.0], later, None, &assembly, 0.1);
+        assert_eq!(hover.object(), None);
+        hover.clear();
+        assert!(hover.due([2.0, 0.0], later));
+        assert_eq!(
+            hover.part_edges("rod", &assembly).len(),
+            hover.edges["rod"].len()
+        );
+        hover.reset();
+        assert!(hover.edges.is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::model::{BodyProperties, Part};
+use crate::units::Units;
+use eframe::egui;
+use truck_geometry::prelude::*;
+
+/// Panel with the mass properties and topology of the selected part. They
+/// take a fine mesh to measure, so they are kept until the part or its
+/// placement changes.
+#[derive(Default)]
+pub struct Inspector {
+    /// Part measured last, where it was, and what it measured
+    cached: Option<(String, Matrix4, BodyProperties)>,
+}
+
+impl Inspector {
+    /// Measure again on the next show, for when a part's shape changed
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+
+    /// `triangles` is the count of the mesh shown for the part
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        part: Option<&Part>,
+        triangles: Option<usize>,
+        units: Units,
+    ) {
+        let Some(part) = part else {
+            ui.weak("Select a part");
+            return;
+        };
+        let stale = self
+            .cached
+            .as_ref()
+            .is_none_or(|(name, transform, _)| name != part.name() || *transform != part.transform);
+        if stale {
+            let props = BodyProperties::new(&part.placed_solid());
+            self.cached = Some((part.name().to_string(), part.transform, props));
+        }
+        let Some((_, _, props)) = &self.cached else {
+            return;
+        };
+
+        let suffix = units.suffix();
+        let point = |p: Point3| format!("({:.3}, {:.3}, {:.3})", p.x, p.y, p.z);
+        let size = props.bounds.diagonal();
+        ui.strong(part.name());
+        egui::Grid::new("inspector")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                let mut row = |label: &str, value: String| {
+                    ui.label(label);
+                    ui.monospace(value);
+                    ui.end_row();
+                };
+                row("Volume", format!("{:.3} {}³", props.volume, suffix));
+                row("Surface area", format!("{:.3} {}²", props.area, suffix));
+                row("Centroid", format!("{} {}", point(props.centroid), suffix));
+                row("Min", format!("{} {}", point(props.bounds.min()), suffix));
+                row("Max", format!("{} {}", point(props.bounds.max()), suffix));
+                row(
+                    "Size",
+                    format!("{:.3} × {:.3} × {:.3} {}", size.x, size.y, size.z, suffix),
+                );
+                row("Faces", props.faces.to_string());
+                row("Edges", props.edges.to_string());
+                row("Vertices", props.vertices.to_string());
+                if let Some(triangles) = triangles {
+                    row("Triangles", triangles.to_string());
+                }
+            });
+        ui.weak("Measured on a fine mesh, assuming uniform density");
+    }
+}
@@ -0,0 +1,216 @@
+use crate::model::EdgeMeasure;
+use crate::renderer::overlay::Overlay;
+use crate::units::Units;
+use glam::Vec3;
+
+/// Measurements on the overlay (linear RGB)
+const MEASURE_COLOR: [f32; 3] = [1.0, 0.55, 0.2];
+
+/// What the next clicks in measure mode measure
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeasureKind {
+    /// Between two picked points
+    #[default]
+    Distance,
+    /// Length, and radius if round, of one picked edge
+    Edge,
+    /// At the second of three picked points
+    Angle,
+}
+
+impl MeasureKind {
+    pub const ALL: [MeasureKind; 3] =
+        [MeasureKind::Distance, MeasureKind::Edge, MeasureKind::Angle];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MeasureKind::Distance => "Distance",
+            MeasureKind::Edge => "Edge",
+            MeasureKind::Angle => "Angle",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            MeasureKind::Distance => "Click two points",
+            MeasureKind::Edge => "Click an edge",
+            MeasureKind::Angle => "Click a point, the corner, then another point",
+        }
+    }
+}
+
+/// One finished measurement in world coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub enum Measurement {
+    Distance {
+        from: Vec3,
+        to: Vec3,
+    },
+    Edge {
+        points: Vec<Vec3>,
+        length: f32,
+        /// Centre and radius of a round edge
+        circle: Option<(Vec3, f32)>,
+    },
+    Angle {
+        vertex: Vec3,
+        a: Vec3,
+        b: Vec3,
+    },
+}
+
+impl Measurement {
+    /// Result with lengths in `units`
+    pub fn describe(&self, units: Units) -> String {
+        let length = |value: f32| units.format(value as f64);
+        match self {
+            Measurement::Distance { from, to } => {
+                let d = *to - *from;
+                format!(
+                    "Distance {} (dx {:.3}, dy {:.3}, dz {:.3})",
+                    length(d.length()),
+                    d.x,
+                    d.y,
+                    d.z
+                )
+            }
+            Measurement::Edge {
+                length: edge,
+                circle,
+                ..
+            } => match circle {
+                Some((_, radius)) => {
+                    format!("Edge length {}, radius {}", length(*edge), length(*radius))
+                }
+                None => format!("Edge length {}", length(*edge)),
+            },
+            Measurement::Angle { vertex, a, b } => {
+                format!("Angle {:.2}°", angle(*vertex, *a, *b).to_degrees())
+            }
+        }
+    }
+
+    fn annotate(&self, overlay: &mut Overlay) {
+        match self {
+            Measurement::Distance { from, to } => overlay.dimension(*from, *to, MEASURE_COLOR),
+            Measurement::Edge { points, circle, .. } => {
+                for pair in points.windows(2) {
+                    overlay.line(pair[0], pair[1], MEASURE_COLOR);
+                }
+                if let Some(&(centre, _)) = circle.as_ref() {
+                    overlay.marker(centre, MEASURE_COLOR);
+                }
+                let middle = points[points.len() / 2];
+                let text = self.describe(overlay.units);
+                overlay.label(middle, text, MEASURE_COLOR);
+            }
+            Measurement::Angle { vertex, a, b } => {
+                overlay.line(*vertex, *a, MEASURE_COLOR);
+                overlay.line(*vertex, *b, MEASURE_COLOR);
+                let text = format!("{:.2}°", angle(*vertex, *a, *b).to_degrees());
+                overlay.label(*vertex, text, MEASURE_COLOR);
+            }
+        }
+    }
+}
+
+fn angle(vertex: Vec3, a: Vec3, b: Vec3) -> f32 {
+    (a - vertex).angle_between(b - vertex)
+}
+
+/// Measurements taken by clicking in the viewport, kept until removed
+#[derive(Clone, Debug, Default)]
+pub struct MeasureTool {
+    pub kind: MeasureKind,
+    /// Points picked towards the next measurement
+    picked: Vec<Vec3>,
+    results: Vec<Measurement>,
+}
+
+impl MeasureTool {
+    pub fn set_kind(&mut self, kind: MeasureKind) {
+        self.kind = kind;
+        self.picked.clear();
+    }
+
+    /// Take a picked point; the last one a measurement needs adds it and
+    /// returns true
+    pub fn pick_point(&mut self, point: Vec3) -> bool {
+        self.picked.push(point);
+        let measurement = match (self.kind, self.picked.as_slice()) {
+            (MeasureKind::Distance, &[from, to]) => Measurement::Distance { from, to },
+            (MeasureKind::Angle, &[a, vertex, b]) => Measurement::Angle { vertex, a, b },
+            _ => return false,
+        };
+        self.picked.clear();
+        self.results.push(measurement);
+        true
+    }
+
+    /// Measure a picked edge, given in world coordinates
+    pub fn pick_edge(&mut self, edge: &EdgeMeasure) {
+        let to_vec =
+            |p: truck_geometry::prelude::Point3| Vec3::new(p.x as f32, p.y as f32, p.z as f32);
+        self.picked.clear();
+        self.results.push(Measurement::Edge {
+            points: edge.points.iter().map(|&p| to_vec(p)).collect(),
+            length: edge.length as f32,
+            circle: edge
+                .circle
+                .map(|(centre, radius)| (to_vec(centre), radius as f32)),
+        });
+    }
+
+    pub fn results(&self) -> &[Measurement] {
+        &self.results
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.results.len() {
+            self.results.remove(index);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.picked.clear();
+        self.results.clear();
+    }
+
+    /// Draw the measurements and the points picked so far
+    pub fn annotate(&self, overlay: &mut Overlay) {
+        for measurement in &self.results {
+            measurement.annotate(overlay);
+        }
+        for &point in &self.picked {
+            overlay.marker(point, MEASURE_COLOR);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_tool() {
+        let mut tool = MeasureTool::default();
+        assert!(!tool.pick_point(Vec3::ZERO));
+        assert!(tool.pick_point(Vec3::new(3.0, 4.0, 0.0)));
+        let distance = tool.results()[0].describe(Units::Inch);
+        assert!(distance.starts_with("Distance 5.000 in"));
+
+        tool.set_kind(MeasureKind::Angle);
+        for point in [Vec3::X, Vec3::ZERO, Vec3::Y] {
+            tool.pick_point(point);
+        }
+        assert_eq!(tool.results()[1].describe(Units::Inch), "Angle 90.00°");
+
+        let mut overlay = Overlay::new();
+        tool.annotate(&mut overlay);
+        assert_eq!(overlay.labels().len(), 2);
+        tool.remove(0);
+        assert_eq!(tool.results().len(), 1);
+        tool.clear();
+        assert!(tool.results().is_empty());
+    }
+}
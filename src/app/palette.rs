@@ -0,0 +1,238 @@
+use super::viewports::ViewLayout;
+use crate::renderer::camera::StandardView;
+use crate::sketch::StandardPlane;
+use eframe::egui;
+
+/// Matches listed at once
+const MAX_ROWS: usize = 12;
+
+/// App action the command palette can run
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    Open,
+    Save,
+    FitView,
+    Ghost,
+    View(StandardView),
+    Layout(ViewLayout),
+    Sketch(StandardPlane),
+    Extrude,
+    Measure,
+    Inspector,
+    Console,
+    Log,
+    Spin,
+}
+
+impl Command {
+    /// Every command, in the order listed before anything is typed
+    pub fn all() -> Vec<Command> {
+        let mut commands = vec![
+            Command::Open,
+            Command::Save,
+            Command::FitView,
+            Command::Ghost,
+        ];
+        commands.extend(StandardView::ALL.map(Command::View));
+        commands.extend(ViewLayout::ALL.map(Command::Layout));
+        commands.extend(
+            [
+                StandardPlane::XY(0.0),
+                StandardPlane::XZ(0.0),
+                StandardPlane::YZ(0.0),
+            ]
+            .map(Command::Sketch),
+        );
+        commands.extend([
+            Command::Extrude,
+            Command::Measure,
+            Command::Inspector,
+            Command::Console,
+            Command::Log,
+            Command::Spin,
+        ]);
+        commands
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Command::Open => "Open file...".to_string(),
+            Command::Save => "Save project".to_string(),
+            Command::FitView => "Fit view".to_string(),
+            Command::Ghost => "Ghost selection".to_string(),
+            Command::View(view) => format!("View {}", view.name()),
+            Command::Layout(layout) => format!("Layout {}", layout.name()),
+            Command::Sketch(plane) => {
+                let name = match plane {
+                    StandardPlane::XY(_) => "XY",
+                    StandardPlane::XZ(_) => "XZ",
+                    StandardPlane::YZ(_) => "YZ",
+                };
+                format!("Sketch on {}", name)
+            }
+            Command::Extrude => "Extrude sketches".to_string(),
+            Command::Measure => "Toggle measure".to_string(),
+            Command::Inspector => "Toggle inspector".to_string(),
+            Command::Console => "Toggle console".to_string(),
+            Command::Log => "Toggle log".to_string(),
+            Command::Spin => "Toggle spin".to_string(),
+        }
+    }
+
+    /// Key that runs the command outside the palette
+    pub fn shortcut(&self) -> Option<&'static str> {
+        match self {
+            Command::FitView => Some("F"),
+            Command::Ghost => Some("G"),
+            _ => None,
+        }
+    }
+}
+
+/// How well `text` matches `query`: every character of the query must
+/// appear in order, ignoring case and spaces; adjacent characters and word
+/// starts score higher. `None` when some character is missing.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last = None;
+    for c in query.chars().flat_map(char::to_lowercase) {
+        if c.is_whitespace() {
+            continue;
+        }
+        let found = next + text[next..].iter().position(|&t| t == c)?;
+        score += 1;
+        if last.is_some_and(|last| last + 1 == found) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        last = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// Ctrl+P list of [`Command`]s, searched by [`fuzzy_score`] and run from
+/// the keyboard
+#[derive(Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    /// Highlighted row, run by Enter
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// Commands matching what was typed, best first
+    pub fn matches(&self) -> Vec<Command> {
+        let mut scored: Vec<(i32, Command)> = Command::all()
+            .into_iter()
+            .filter_map(|command| Some((fuzzy_score(&self.query, &command.label())?, command)))
+            .collect();
+        // Stable, so ties keep the listed order
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, command)| command).collect()
+    }
+
+    /// Draw the palette while open; returns the command picked by Enter or
+    /// a click, which closes it, as does Escape
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<Command> {
+        if !self.open {
+            return None;
+        }
+        let (up, down, enter, escape) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowUp),
+                i.key_pressed(egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if escape {
+            self.open = false;
+            return None;
+        }
+        let mut chosen = None;
+        egui::Window::new("Commands")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 48.0])
+            .show(ctx, |ui| {
+                let field = egui::TextEdit::singleline(&mut self.query)
+                    .hint_text("Type a command")
+                    .desired_width(320.0);
+                let field = ui.add(field);
+                field.request_focus();
+                if field.changed() {
+                    self.selected = 0;
+                }
+                let matches = self.matches();
+                if down {
+                    self.selected += 1;
+                }
+                if up {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                self.selected = self
+                    .selected
+                    .min(matches.len().min(MAX_ROWS).saturating_sub(1));
+                if enter {
+                    chosen = matches.get(self.selected).copied();
+                }
+                ui.separator();
+                for (row, command) in matches.iter().take(MAX_ROWS).enumerate() {
+                    ui.horizontal(|ui| {
+                        let label = ui.selectable_label(row == self.selected, command.label());
+                        if label.clicked() {
+                            chosen = Some(*command);
+                        }
+                        if let Some(key) = command.shortcut() {
+                            ui.weak(key);
+                        }
+                    });
+                }
+                if matches.is_empty() {
+                    ui.weak("No matching command");
+                }
+            });
+        if chosen.is_some() {
+            self.open = false;
+        }
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_search() {
+        assert_eq!(fuzzy_score("", "Fit view"), Some(0));
+        assert!(fuzzy_score("fv", "Fit view").is_some());
+        assert_eq!(fuzzy_score("vf", "Fit view"), None);
+        // Adjacent letters beat scattered ones
+        assert!(fuzzy_score("fit", "Fit view") > fuzzy_score("fit", "Find it"));
+
+        let mut palette = CommandPalette::default();
+        assert_eq!(palette.matches().len(), Command::all().len());
+        palette.query = "view top".to_string();
+        assert_eq!(palette.matches()[0], Command::View(StandardView::Top));
+        palette.query = "skxz".to_string();
+        assert_eq!(
+            palette.matches()[0],
+            Command::Sketch(StandardPlane::XZ(0.0))
+        );
+        palette.query = "qqq".to_string();
+        assert!(palette.matches().is_empty());
+    }
+}
@@ -0,0 +1,261 @@
+use crate::renderer::scene::ObjectId;
+
+/// One selected thing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selected {
+    /// A part or reference mesh as a whole
+    Body(ObjectId),
+    /// Edge of a body's part, by its index in [`crate::model::edge_measures`]
+    Edge(ObjectId, usize),
+    /// Face of a body, by the B-rep face id its mesh records
+    Face(ObjectId, usize),
+    /// Placed sketch, by index
+    Sketch(usize),
+    /// Curve of a placed sketch, by sketch index and then its index in
+    /// [`crate::sketch::Sketch::curves`]
+    SketchCurve(usize, usize),
+}
+
+impl Selected {
+    pub fn kind(&self) -> SelectionKind {
+        match self {
+            Selected::Body(_) => SelectionKind::Body,
+            Selected::Edge(..) => SelectionKind::Edge,
+            Selected::Face(..) => SelectionKind::Face,
+            Selected::Sketch(_) => SelectionKind::Sketch,
+            Selected::SketchCurve(..) => SelectionKind::SketchCurve,
+        }
+    }
+
+    /// Body the item belongs to, if any
+    pub fn body(&self) -> Option<ObjectId> {
+        match self {
+            Selected::Body(id) | Selected::Edge(id, _) | Selected::Face(id, _) => Some(*id),
+            Selected::Sketch(_) | Selected::SketchCurve(..) => None,
+        }
+    }
+}
+
+/// What a selected thing is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionKind {
+    Body,
+    Edge,
+    Face,
+    Sketch,
+    SketchCurve,
+}
+
+impl SelectionKind {
+    pub const ALL: [SelectionKind; 5] = [
+        SelectionKind::Body,
+        SelectionKind::Edge,
+        SelectionKind::Face,
+        SelectionKind::Sketch,
+        SelectionKind::SketchCurve,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SelectionKind::Body => "Bodies",
+            SelectionKind::Edge => "Edges",
+            SelectionKind::Face => "Faces",
+            SelectionKind::Sketch => "Sketches",
+            SelectionKind::SketchCurve => "Sketch curves",
+        }
+    }
+}
+
+/// Kinds clicks and boxes may select
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelectionFilter {
+    pub bodies: bool,
+    pub edges: bool,
+    pub faces: bool,
+    pub sketches: bool,
+    pub sketch_curves: bool,
+}
+
+impl Default for SelectionFilter {
+    fn default() -> Self {
+        Self {
+            bodies: true,
+            edges: false,
+            faces: false,
+            sketches: true,
+            // Curves are picked from the tree only, so they never get in the
+            // way of viewport clicks
+            sketch_curves: true,
+        }
+    }
+}
+
+impl SelectionFilter {
+    pub fn allows(&self, kind: SelectionKind) -> bool {
+        match kind {
+            SelectionKind::Body => self.bodies,
+            SelectionKind::Edge => self.edges,
+            SelectionKind::Face => self.faces,
+            SelectionKind::Sketch => self.sketches,
+            SelectionKind::SketchCurve => self.sketch_curves,
+        }
+    }
+
+    pub fn allow_mut(&mut self, kind: SelectionKind) -> &mut bool {
+        match kind {
+            SelectionKind::Body => &mut self.bodies,
+            SelectionKind::Edge => &mut self.edges,
+            SelectionKind::Face => &mut self.faces,
+            SelectionKind::Sketch => &mut self.sketches,
+            SelectionKind::SketchCurve => &mut self.sketch_curves,
+        }
+    }
+}
+
+/// The current selection, in the order things were picked, which the
+/// transform, measure, texture and extrude tools work on
+#[derive(Default)]
+pub struct Selection {
+    items: Vec<Selected>,
+    pub filter: SelectionFilter,
+}
+
+impl Selection {
+    pub fn items(&self) -> &[Selected] {
+        &self.items
+    }
+
+    /// Select only `item`, as a plain click does, or nothing with `None`;
+    /// items the filter leaves out clear the selection
+    pub fn set(&mut self, item: Option<Selected>) {
+        self.items.clear();
+        self.extend(item);
+    }
+
+    /// Add `item`, or drop it when already selected, as a Ctrl+click does
+    pub fn toggle(&mut self, item: Selected) {
+        if let Some(index) = self.items.iter().position(|&i| i == item) {
+            self.items.remove(index);
+        } else {
+            self.extend([item]);
+        }
+    }
+
+    /// Add the items the filter allows and are not selected yet
+    pub fn extend(&mut self, items: impl IntoIterator<Item = Selected>) {
+        for item in items {
+            if self.filter.allows(item.kind()) && !self.items.contains(&item) {
+                self.items.push(item);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Keep only the items `keep` accepts
+    pub fn retain(&mut self, keep: impl FnMut(&Selected) -> bool) {
+        self.items.retain(keep);
+    }
+
+    pub fn bodies(&self) -> impl Iterator<Item = ObjectId> + '_ {
+        self.items.iter().filter_map(|item| match item {
+            Selected::Body(id) => Some(*id),
+            _ => None,
+        })
+    }
+
+    /// Body picked last, which the inspector shows
+    pub fn primary_body(&self) -> Option<ObjectId> {
+        self.bodies().last()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (ObjectId, usize)> + '_ {
+        self.items.iter().filter_map(|item| match item {
+            Selected::Edge(id, edge) => Some((*id, *edge)),
+            _ => None,
+        })
+    }
+
+    pub fn faces(&self) -> impl Iterator<Item = (ObjectId, usize)> + '_ {
+        self.items.iter().filter_map(|item| match item {
+            Selected::Face(id, face) => Some((*id, *face)),
+            _ => None,
+        })
+    }
+
+    pub fn sketches(&self) -> impl Iterator<Item = usize> + '_ {
+        self.items.iter().filter_map(|item| match item {
+            Selected::Sketch(index) => Some(*index),
+            _ => None,
+        })
+    }
+
+    pub fn sketch_curves(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.items.iter().filter_map(|item| match item {
+            Selected::SketchCurve(sketch, curve) => Some((*sketch, *curve)),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{box_solid, Assembly};
+    use crate::renderer::scene::Scene;
+    use truck_geometry::prelude::{Matrix4, Point3, SquareMatrix, Vector3};
+
+    #[test]
+    fn test_selection() {
+        let mut assembly = Assembly::default();
+        for name in ["a", "b"] {
+            let cube = box_solid(Point3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+            assembly.add(name, cube, Matrix4::identity()).unwrap();
+        }
+        let scene = Scene::from_assembly(&assembly, 0.01);
+        let (a, b) = (scene.find("a").unwrap(), scene.find("b").unwrap());
+
+        let mut selection = Selection::default();
+        selection.set(Some(Selected::Body(a)));
+        selection.toggle(Selected::Body(b));
+        selection.toggle(Selected::Sketch(0));
+        assert_eq!(selection.bodies().collect::<Vec<_>>(), [a, b]);
+        assert_eq!(selection.primary_body(), Some(b));
+        assert_eq!(selection.sketches().collect::<Vec<_>>(), [0]);
+        selection.toggle(Selected::Body(b));
+        assert_eq!(selection.primary_body(), Some(a));
+
+        // Edges are filtered out until asked for
+        selection.extend([Selected::Edge(a, 3), Selected::Body(a)]);
+        assert_eq!(selection.items().len(), 2);
+        selection.filter.edges = true;
+        selection.set(Some(Selected::Edge(a, 3)));
+        assert_eq!(selection.edges().collect::<Vec<_>>(), [(a, 3)]);
+        selection.retain(|item| item.kind() != SelectionKind::Edge);
+        assert!(selection.items().is_empty());
+
+        // and so are faces
+        selection.set(Some(Selected::Face(b, 2)));
+        assert!(selection.items().is_empty());
+        selection.filter.faces = true;
+        selection.extend([Selected::Face(b, 2), Selected::Edge(a, 1)]);
+        assert_eq!(selection.faces().collect::<Vec<_>>(), [(b, 2)]);
+        assert_eq!(selection.items()[1].body(), Some(a));
+
+        // Sketch curves sit apart from their sketch and belong to no body
+        selection.set(Some(Selected::Sketch(1)));
+        selection.toggle(Selected::SketchCurve(1, 2));
+        selection.toggle(Selected::SketchCurve(0, 0));
+        assert_eq!(selection.sketches().collect::<Vec<_>>(), [1]);
+        assert_eq!(
+            selection.sketch_curves().collect::<Vec<_>>(),
+            [(1, 2), (0, 0)]
+        );
+        assert_eq!(selection.items()[1].body(), None);
+        selection.filter.sketch_curves = false;
+        selection.set(Some(Selected::SketchCurve(1, 2)));
+        assert!(selection.items().is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+use super::viewports::ViewLayout;
+use crate::appearance::{config_dir, read_json, write_json};
+use crate::renderer::camera::OrbitCamera;
+use crate::sketch::SketchResult;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File the session is kept in, next to the appearance
+const SESSION_FILE: &str = "session.json";
+
+/// Projects kept in the recent list
+const MAX_RECENT: usize = 10;
+
+/// Panels shown around the viewport, and how it is split
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelLayout {
+    pub inspector: bool,
+    pub console: bool,
+    pub log: bool,
+    pub snap: bool,
+    pub views: ViewLayout,
+}
+
+/// Recent projects and what was open when the app last closed, restored
+/// at startup
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Projects opened or saved, newest first
+    pub recent: Vec<PathBuf>,
+    /// Project open at the end, opened again at startup
+    pub project: Option<PathBuf>,
+    pub camera: Option<OrbitCamera>,
+    pub panels: PanelLayout,
+}
+
+impl Session {
+    /// Where the session is kept, see [`config_dir`]
+    pub fn config_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(SESSION_FILE))
+    }
+
+    /// Make `path` the open project and put it first in the recent list
+    pub fn opened(&mut self, path: &Path) {
+        self.recent.retain(|recent| recent != path);
+        self.recent.insert(0, path.to_path_buf());
+        self.recent.truncate(MAX_RECENT);
+        self.project = Some(path.to_path_buf());
+    }
+
+    /// Drop `path` from the recent list, as when it is gone
+    pub fn forget(&mut self, path: &Path) {
+        self.recent.retain(|recent| recent != path);
+        if self.project.as_deref() == Some(path) {
+            self.project = None;
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> SketchResult<()> {
+        write_json(path.as_ref(), self)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> SketchResult<Self> {
+        read_json(path.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let mut session = Session::default();
+        for i in 0..=MAX_RECENT {
+            session.opened(Path::new(&format!("part {}.tpp", i)));
+        }
+        session.opened(Path::new("part 3.tpp"));
+        assert_eq!(session.recent.len(), MAX_RECENT);
+        assert_eq!(session.recent[0], Path::new("part 3.tpp"));
+        assert_eq!(
+            session
+                .recent
+                .iter()
+                .filter(|p| p.ends_with("part 3.tpp"))
+                .count(),
+            1
+        );
+        session.forget(Path::new("part 3.tpp"));
+        assert_eq!(session.project, None);
+        assert_eq!(session.recent.len(), MAX_RECENT - 1);
+
+        session.camera = Some(OrbitCamera {
+            distance: 42.0,
+            ..Default::default()
+        });
+        session.panels.log = true;
+        session.panels.views = ViewLayout::Quad;
+        let path = std::env::temp_dir()
+            .join(format!("truck-session-{}", std::process::id()))
+            .join(SESSION_FILE);
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        assert_eq!(loaded.recent, session.recent);
+        assert_eq!(loaded.panels, session.panels);
+        assert_eq!(loaded.camera.map(|camera| camera.distance), Some(42.0));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,280 @@
+use crate::sketch::constants::HEAL_TOLERANCE;
+use crate::sketch::hatch::flatten_curve;
+use crate::sketch::{
+    Arc2D, Circle2D, Curve2D, Line2D, Plane, Sketch, SketchCurve2D, SketchError, SketchResult,
+    SnapSettings,
+};
+use truck_geometry::prelude::*;
+
+/// Chaining tolerance of finished profiles, as a fraction of their size
+const REGION_TOLERANCE: f64 = 1e-5;
+
+/// What clicks in sketch mode draw
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SketchTool {
+    /// From one click to the next, chaining on from the last end
+    #[default]
+    Line,
+    /// Start, end, then a point the arc passes through
+    Arc,
+    /// Centre, then a point on the circle
+    Circle,
+}
+
+impl SketchTool {
+    pub const ALL: [SketchTool; 3] = [SketchTool::Line, SketchTool::Arc, SketchTool::Circle];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SketchTool::Line => "Line",
+            SketchTool::Arc => "Arc",
+            SketchTool::Circle => "Circle",
+        }
+    }
+
+    /// Clicks one curve takes
+    fn points(&self) -> usize {
+        match self {
+            SketchTool::Line | SketchTool::Circle => 2,
+            SketchTool::Arc => 3,
+        }
+    }
+
+    fn build(&self, points: &[Point2]) -> SketchResult<Curve2D> {
+        Ok(match self {
+            SketchTool::Line => Line2D::new(points[0], points[1])?.into(),
+            SketchTool::Arc => Arc2D::from_three_points(points[0], points[2], points[1])?.into(),
+            SketchTool::Circle => {
+                Circle2D::new(points[0], (points[1] - points[0]).magnitude())?.into()
+            }
+        })
+    }
+}
+
+/// Curves being drawn with the mouse on a plane, in the plane's 2D
+/// coordinates; [`SketchSession::finish`] turns the closed ones into
+/// sketches
+#[derive(Clone, Debug)]
+pub struct SketchSession {
+    pub plane: Plane,
+    pub tool: SketchTool,
+    /// Spacing of the grid in view, snapped to unless `snapping` sets its
+    /// own; 0 for none
+    pub grid: f64,
+    pub snapping: SnapSettings,
+    /// Placed sketch the finished profiles replace, when editing one
+    pub editing: Option<usize>,
+    curves: Vec<Curve2D>,
+    /// Points clicked towards the next curve
+    pending: Vec<Point2>,
+}
+
+impl SketchSession {
+    pub fn new(plane: Plane) -> Self {
+        Self {
+            plane,
+            tool: SketchTool::default(),
+            grid: 0.0,
+            snapping: SnapSettings::default(),
+            editing: None,
+            curves: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Session on `plane` with the profile curves of `sketch`, placed
+    /// sketch `index`, loaded to edit
+    pub fn edit(plane: Plane, sketch: &Sketch, index: usize) -> Self {
+        let loops = std::iter::once(&sketch.outer).chain(&sketch.holes);
+        Self {
+            editing: Some(index),
+            curves: loops.flat_map(|l| l.curves().iter().cloned()).collect(),
+            ..Self::new(plane)
+        }
+    }
+
+    pub fn curves(&self) -> &[Curve2D] {
+        &self.curves
+    }
+
+    pub fn pending(&self) -> &[Point2] {
+        &self.pending
+    }
+
+    pub fn set_tool(&mut self, tool: SketchTool) {
+        self.tool = tool;
+        self.pending.clear();
+    }
+
+    /// Grid spacing clicks snap to; 0 for none
+    pub fn grid_spacing(&self) -> f64 {
+        if self.snapping.grid > 0.0 {
+            self.snapping.grid
+        } else {
+            self.grid
+        }
+    }
+
+    /// `point` snapped as `snapping` says onto the drawn curves and pending
+    /// points within `radius`, turned about the last pending point, or onto
+    /// the grid
+    pub fn snap(&self, point: Point2, radius: f64) -> Point2 {
+        let mut targets = self.snapping.targets(&self.curves);
+        if self.snapping.to_endpoints {
+            targets.extend(&self.pending);
+        }
+        let from = self.pending.last().copied();
+        self.snapping
+            .snap(point, from, &targets, radius, self.grid_spacing())
+    }
+
+    /// Take a clicked point; the last one a curve needs adds it. Lines
+    /// chain on from their end until one lands on an existing end.
+    pub fn click(&mut self, point: Point2) -> SketchResult<()> {
+        self.pending.push(point);
+        if self.pending.len() < self.tool.points() {
+            return Ok(());
+        }
+        let points = std::mem::take(&mut self.pending);
+        let curve = match self.tool.build(&points) {
+            Ok(curve) => curve,
+            Err(e) => {
+                // Keep the start so the click can be retried
+                self.pending.push(points[0]);
+                return Err(e);
+            }
+        };
+        let joined = self
+            .curves
+            .iter()
+            .any(|c| c.start() == curve.end() || c.end() == curve.end());
+        if self.tool == SketchTool::Line && !joined {
+            self.pending.push(curve.end());
+        }
+        self.curves.push(curve);
+        Ok(())
+    }
+
+    /// Curve the pending points would give with the last click at `cursor`;
+    /// an arc shows its chord until the third click
+    pub fn preview(&self, cursor: Point2) -> Option<Curve2D> {
+        let mut points = self.pending.clone();
+        points.push(cursor);
+        match points.len() {
+            1 => None,
+            2 if self.tool == SketchTool::Arc => SketchTool::Line.build(&points).ok(),
+            _ => self.tool.build(&points).ok(),
+        }
+    }
+
+    /// Drop the curve being drawn, or else the last one drawn
+    pub fn undo(&mut self) {
+        if self.pending.is_empty() {
+            self.curves.pop();
+        } else {
+            self.pending.clear();
+        }
+    }
+
+    /// Stop the curve being drawn, keeping the rest
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Closed profiles bounded by the drawn curves; open chains are left
+    /// out
+    pub fn finish(&self) -> SketchResult<Vec<Sketch>> {
+        let size = self
+            .curves
+            .iter()
+            .map(|curve| curve.bounding_box())
+            .reduce(|a, b| a.union(&b))
+            .map_or(0.0, |bounds| (bounds.max - bounds.min).magnitude());
+        let tolerance = (size * REGION_TOLERANCE).max(HEAL_TOLERANCE);
+        let sketches = Sketch::regions(&self.curves, tolerance)?;
+        if sketches.is_empty() {
+            return Err(SketchError::InvalidFeature(
+                "the sketch has no closed profile".to_string(),
+            ));
+        }
+        Ok(sketches)
+    }
+
+    /// Drawn curves and the preview at `cursor` as 3D polylines on the
+    /// plane, flattened within `tolerance`
+    pub fn polylines(&self, cursor: Option<Point2>, tolerance: f64) -> Vec<Vec<Point3>> {
+        let preview = cursor.and_then(|cursor| self.preview(cursor));
+        self.curves
+            .iter()
+            .chain(preview.as_ref())
+            .map(|curve| {
+                let mut points = flatten_curve(curve, tolerance);
+                points.push(curve.end());
+                points
+                    .into_iter()
+                    .map(|p| self.plane.lift_point(p))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sketch_session() {
+        let mut session = SketchSession::new(Plane::xz());
+        session.grid = 1.0;
+        // A closed triangle from chained line clicks, snapping to the grid
+        // and back onto the first point
+        for (x, y) in [(0.1, -0.2), (4.2, 0.0), (3.9, 3.1)] {
+            let point = session.snap(Point2::new(x, y), 0.5);
+            session.click(point).unwrap();
+        }
+        assert_eq!(session.pending(), [Point2::new(4.0, 3.0)]);
+        let closing = session.snap(Point2::new(0.3, 0.2), 0.5);
+        assert_eq!(closing, Point2::origin());
+        session.click(closing).unwrap();
+        assert!(session.pending().is_empty());
+
+        // A circle inside becomes a hole; a stray open line is left out
+        session.set_tool(SketchTool::Circle);
+        session.click(Point2::new(2.5, 1.0)).unwrap();
+        assert!(session.preview(Point2::new(3.0, 1.0)).is_some());
+        session.click(Point2::new(3.0, 1.0)).unwrap();
+        session.set_tool(SketchTool::Line);
+        session.click(Point2::new(10.0, 10.0)).unwrap();
+        session.click(Point2::new(12.0, 10.0)).unwrap();
+        session.cancel();
+
+        let sketches = session.finish().unwrap();
+        assert_eq!(sketches.len(), 1);
+        assert_eq!(sketches[0].hole_count(), 1);
+        let area = 6.0 - std::f64::consts::PI * 0.25;
+        assert!((sketches[0].area() - area).abs() < 1e-3);
+        assert!(sketches[0]
+            .extrude(&session.plane, Vector3::unit_y())
+            .is_ok());
+
+        // Undo takes the curves back off
+        session.undo();
+        session.undo();
+        assert_eq!(session.curves().len(), 3);
+        session.set_tool(SketchTool::Arc);
+        for _ in 0..3 {
+            session.undo();
+        }
+        assert!(session.finish().is_err());
+
+        // Finished profiles load back in to be edited
+        let mut session = SketchSession::edit(Plane::xz(), &sketches[0], 2);
+        assert_eq!(session.editing, Some(2));
+        assert_eq!(session.curves().len(), 4);
+        session.undo();
+        let edited = session.finish().unwrap();
+        assert_eq!(edited[0].hole_count(), 0);
+        assert!((edited[0].area() - 6.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,130 @@
+use super::selection::{Selected, Selection, SelectionKind};
+use crate::project::PlacedSketch;
+use crate::renderer::scene::Scene;
+use crate::units::Units;
+use truck_geometry::prelude::{Point2, Point3};
+
+/// Model point under the cursor, for the status bar
+pub fn describe_point(point: Point3, units: Units) -> String {
+    format!(
+        "X {:.3}  Y {:.3}  Z {:.3} {}",
+        point.x,
+        point.y,
+        point.z,
+        units.suffix()
+    )
+}
+
+/// Cursor on the sketch plane, in the plane's own axes
+pub fn describe_sketch_point(point: Point2, units: Units) -> String {
+    format!("u {:.3}  v {:.3} {}", point.x, point.y, units.suffix())
+}
+
+/// One line on what is selected: the item itself when there is one, else
+/// how many of each kind
+pub fn describe_selection(
+    selection: &Selection,
+    scene: &Scene,
+    sketches: &[PlacedSketch],
+) -> String {
+    let body = |id| scene.object(id).map_or("?", |object| object.name.as_str());
+    let sketch = |index| {
+        sketches
+            .get(index)
+            .map_or("?", |placed: &PlacedSketch| placed.name.as_str())
+    };
+    match selection.items() {
+        [] => "Nothing selected".to_string(),
+        [Selected::Body(id)] => format!("Body {}", body(*id)),
+        [Selected::Edge(id, edge)] => format!("Edge {} of {}", edge + 1, body(*id)),
+        [Selected::Face(id, face)] => format!("Face {} of {}", face + 1, body(*id)),
+        [Selected::Sketch(index)] => format!("Sketch {}", sketch(*index)),
+        [Selected::SketchCurve(index, curve)] => {
+            format!("Curve {} of {}", curve + 1, sketch(*index))
+        }
+        items => {
+            let counts: Vec<String> = SelectionKind::ALL
+                .into_iter()
+                .filter_map(|kind| {
+                    let count = items.iter().filter(|item| item.kind() == kind).count();
+                    (count > 0).then(|| count_of(kind, count))
+                })
+                .collect();
+            format!("{} selected: {}", items.len(), counts.join(", "))
+        }
+    }
+}
+
+/// "1 edge", "2 edges" and so on
+fn count_of(kind: SelectionKind, count: usize) -> String {
+    let noun = match (kind, count) {
+        (SelectionKind::Body, 1) => "body",
+        (SelectionKind::Edge, 1) => "edge",
+        (SelectionKind::Face, 1) => "face",
+        (SelectionKind::Sketch, 1) => "sketch",
+        (SelectionKind::SketchCurve, 1) => "sketch curve",
+        (SelectionKind::Body, _) => "bodies",
+        (SelectionKind::Edge, _) => "edges",
+        (SelectionKind::Face, _) => "faces",
+        (SelectionKind::Sketch, _) => "sketches",
+        (SelectionKind::SketchCurve, _) => "sketch curves",
+    };
+    format!("{} {}", count, noun)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{box_solid, Assembly};
+    use truck_geometry::prelude::{Matrix4, SquareMatrix, Vector3};
+
+    #[test]
+    fn test_status_text() {
+        assert_eq!(
+            describe_point(Point3::new(1.0, -2.0, 0.5), Units::Millimetre),
+            "X 1.000  Y -2.000  Z 0.500 mm"
+        );
+        assert_eq!(
+            describe_sketch_point(Point2::new(0.25, 3.0), Units::Inch),
+            "u 0.250  v 3.000 in"
+        );
+
+        let mut assembly = Assembly::default();
+        let cube = box_solid(Point3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        assembly.add("bracket", cube, Matrix4::identity()).unwrap();
+        let scene = Scene::from_assembly(&assembly, 0.01);
+        let bracket = scene.find("bracket").unwrap();
+
+        let mut selection = Selection::default();
+        selection.filter.edges = true;
+        assert_eq!(
+            describe_selection(&selection, &scene, &[]),
+            "Nothing selected"
+        );
+        selection.set(Some(Selected::Body(bracket)));
+        assert_eq!(describe_selection(&selection, &scene, &[]), "Body bracket");
+        selection.set(Some(Selected::Edge(bracket, 2)));
+        assert_eq!(
+            describe_selection(&selection, &scene, &[]),
+            "Edge 3 of bracket"
+        );
+        selection.extend([Selected::Edge(bracket, 4), Selected::Sketch(0)]);
+        assert_eq!(
+            describe_selection(&selection, &scene, &[]),
+            "3 selected: 2 edges, 1 sketch"
+        );
+        selection.filter.faces = true;
+        selection.set(Some(Selected::Face(bracket, 0)));
+        assert_eq!(
+            describe_selection(&selection, &scene, &[]),
+            "Face 1 of bracket"
+        );
+        selection.set(Some(Selected::SketchCurve(0, 1)));
+        assert_eq!(describe_selection(&selection, &scene, &[]), "Curve 2 of ?");
+        selection.toggle(Selected::SketchCurve(0, 3));
+        assert_eq!(
+            describe_selection(&selection, &scene, &[]),
+            "2 selected: 2 sketch curves"
+        );
+    }
+}
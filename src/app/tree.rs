@@ -0,0 +1,174 @@
+use super::selection::{Selected, Selection};
+use crate::model::{Assembly, History};
+use crate::project::PlacedSketch;
+use crate::sketch::Curve2D;
+use eframe::egui;
+
+/// Edit asked for in the model tree, applied by the app once the panel is
+/// drawn
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeAction {
+    /// Select the part, or with `true` add or drop it as Ctrl+click does
+    Select(String, bool),
+    /// Select a sketch by index, the same way
+    SelectSketch(usize, bool),
+    /// Select one curve of a sketch by sketch and curve index, the same way
+    SelectSketchCurve(usize, usize, bool),
+    /// Open a sketch in sketch mode to change its curves
+    EditSketch(usize),
+    SetVisible(String, bool),
+    Rename(String, String),
+    Delete(String),
+    /// Move the part to this place in the part order
+    Reorder(String, usize),
+}
+
+/// Part whose name is being edited, with the text typed so far
+struct Renaming {
+    part: String,
+    text: String,
+    /// Focus the field on the first frame it shows
+    focus: bool,
+}
+
+/// Side panel listing the assembly's parts, with a visibility box each and
+/// a context menu to rename, move and delete them, then the sketches with
+/// their curves folded under each.
+///
+/// A part's feature is shown when hovering it rather than as a child row,
+/// so the tree has one level.
+#[derive(Default)]
+pub struct ModelTree {
+    renaming: Option<Renaming>,
+}
+
+impl ModelTree {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        assembly: &Assembly,
+        history: &History,
+        sketches: &[PlacedSketch],
+        selected_parts: &[String],
+        selection: &Selection,
+    ) -> Option<TreeAction> {
+        let mut action = None;
+        let toggle = ui.input(|i| i.modifiers.command);
+        let count = assembly.len();
+        if count == 0 {
+            ui.weak("No parts");
+        }
+        for (index, part) in assembly.parts().iter().enumerate() {
+            let name = part.name();
+            ui.horizontal(|ui| {
+                let mut visible = part.visible;
+                if ui
+                    .checkbox(&mut visible, "")
+                    .on_hover_text("Show")
+                    .changed()
+                {
+                    action = Some(TreeAction::SetVisible(name.to_string(), visible));
+                }
+
+                if let Some(renaming) = self.renaming.as_mut().filter(|r| r.part == name) {
+                    let field = ui.text_edit_singleline(&mut renaming.text);
+                    if std::mem::take(&mut renaming.focus) {
+                        field.request_focus();
+                    }
+                    if field.lost_focus() {
+                        let text = renaming.text.trim().to_string();
+                        let entered = ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if entered && !text.is_empty() && text != name {
+                            action = Some(TreeAction::Rename(name.to_string(), text));
+                        }
+                        self.renaming = None;
+                    }
+                    return;
+                }
+
+                let selected = selected_parts.iter().any(|p| p == name);
+                let mut label = ui.selectable_label(selected, name);
+                if let Some(feature) = history.feature_of(name) {
+                    label = label.on_hover_text(feature.describe());
+                }
+                if label.clicked() {
+                    action = Some(TreeAction::Select(name.to_string(), toggle));
+                }
+                let mut rename = label.double_clicked();
+                label.context_menu(|ui| {
+                    if ui.button("Rename").clicked() {
+                        rename = true;
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(index > 0, egui::Button::new("Move up"))
+                        .clicked()
+                    {
+                        action = Some(TreeAction::Reorder(name.to_string(), index - 1));
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(index + 1 < count, egui::Button::new("Move down"))
+                        .clicked()
+                    {
+                        action = Some(TreeAction::Reorder(name.to_string(), index + 1));
+                        ui.close_menu();
+                    }
+                    if ui.button("Delete").clicked() {
+                        action = Some(TreeAction::Delete(name.to_string()));
+                        ui.close_menu();
+                    }
+                });
+                if rename {
+                    self.renaming = Some(Renaming {
+                        part: name.to_string(),
+                        text: name.to_string(),
+                        focus: true,
+                    });
+                }
+            });
+        }
+        if !sketches.is_empty() {
+            ui.separator();
+            ui.label("Sketches");
+            for (index, placed) in sketches.iter().enumerate() {
+                let selected = selection.items().contains(&Selected::Sketch(index));
+                let label = ui
+                    .selectable_label(selected, &placed.name)
+                    .on_hover_text(format!(
+                        "{} curve(s), {} hole(s); double-click to edit",
+                        placed.sketch.outer.len(),
+                        placed.sketch.hole_count()
+                    ));
+                if label.double_clicked() {
+                    action = Some(TreeAction::EditSketch(index));
+                } else if label.clicked() {
+                    action = Some(TreeAction::SelectSketch(index, toggle));
+                }
+                egui::CollapsingHeader::new("Curves")
+                    .id_salt(("sketch curves", index))
+                    .show(ui, |ui| {
+                        for (i, curve) in placed.sketch.curves().enumerate() {
+                            let item = Selected::SketchCurve(index, i);
+                            let selected = selection.items().contains(&item);
+                            let text = format!("{} {}", curve_kind(curve), i + 1);
+                            if ui.selectable_label(selected, text).clicked() {
+                                action = Some(TreeAction::SelectSketchCurve(index, i, toggle));
+                            }
+                        }
+                    });
+            }
+        }
+        action
+    }
+}
+
+/// Name of a curve's type for the tree
+fn curve_kind(curve: &Curve2D) -> &'static str {
+    match curve {
+        Curve2D::Line(_) => "Line",
+        Curve2D::Arc(_) => "Arc",
+        Curve2D::Circle(_) => "Circle",
+        Curve2D::BSpline(_) => "Spline",
+    }
+}
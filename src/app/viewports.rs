@@ -0,0 +1,308 @@
+use crate::renderer::camera::{OrbitCamera, Projection, StandardView};
+use crate::renderer::{DisplaySettings, Renderer};
+use eframe::egui;
+use eframe::egui_wgpu::RenderState;
+use eframe::wgpu;
+use serde::{Deserialize, Serialize};
+
+/// Where overlay labels sit from their anchor, clear of the marker
+const LABEL_OFFSET: egui::Vec2 = egui::vec2(6.0, -6.0);
+
+/// Frame around the view taking input when there are several
+const ACTIVE_STROKE: egui::Stroke = egui::Stroke {
+    width: 1.5,
+    color: egui::Color32::from_rgb(90, 160, 255),
+};
+
+/// What the views after the first start out showing, in order
+const PRESETS: [StandardView; 3] = [StandardView::Top, StandardView::Front, StandardView::Right];
+
+/// How the central panel is split into views
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViewLayout {
+    #[default]
+    Single,
+    /// Side by side
+    Split,
+    /// Two by two
+    Quad,
+}
+
+impl ViewLayout {
+    pub const ALL: [ViewLayout; 3] = [ViewLayout::Single, ViewLayout::Split, ViewLayout::Quad];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ViewLayout::Single => "1 view",
+            ViewLayout::Split => "2 views",
+            ViewLayout::Quad => "4 views",
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        match self {
+            ViewLayout::Single => 1,
+            ViewLayout::Split => 2,
+            ViewLayout::Quad => 4,
+        }
+    }
+
+    /// Screen rectangles of the views in `rect`, row by row; whole pixels
+    /// and all the same size, so the views can share depth buffers
+    pub fn cells(&self, rect: egui::Rect) -> Vec<egui::Rect> {
+        let (columns, rows) = match self {
+            ViewLayout::Single => (1, 1),
+            ViewLayout::Split => (2, 1),
+            ViewLayout::Quad => (2, 2),
+        };
+        let size = egui::vec2(
+            (rect.width() / columns as f32).floor(),
+            (rect.height() / rows as f32).floor(),
+        );
+        (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .map(|(row, column)| {
+                let min = rect.min + egui::vec2(column as f32 * size.x, row as f32 * size.y);
+                egui::Rect::from_min_size(min, size)
+            })
+            .collect()
+    }
+}
+
+struct RenderTexture {
+    /// Owns the GPU allocation backing `view`
+    #[allow(dead_code)]
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    egui_texture_id: egui::TextureId,
+    size: (u32, u32),
+}
+
+/// Camera and display settings of one view, and the texture it is drawn in
+struct View {
+    camera: OrbitCamera,
+    display: DisplaySettings,
+    texture: Option<RenderTexture>,
+}
+
+/// Views of the one scene, each with its own camera and display settings.
+/// The active view, which takes input, keeps its state in the renderer; the
+/// others are swapped in to be drawn.
+pub struct Viewports {
+    layout: ViewLayout,
+    views: Vec<View>,
+    active: usize,
+}
+
+impl Default for Viewports {
+    fn default() -> Self {
+        Self {
+            layout: ViewLayout::default(),
+            views: (0..ViewLayout::Quad.count())
+                .map(|_| View {
+                    camera: OrbitCamera::default(),
+                    display: DisplaySettings::default(),
+                    texture: None,
+                })
+                .collect(),
+            active: 0,
+        }
+    }
+}
+
+impl Viewports {
+    pub fn layout(&self) -> ViewLayout {
+        self.layout
+    }
+
+    /// Views the layout adds start as orthographic standard views of what
+    /// the active view shows, with its display settings
+    pub fn set_layout(&mut self, layout: ViewLayout, renderer: &mut Renderer) {
+        if self.active >= layout.count() {
+            self.activate(0, renderer);
+        }
+        for i in self.layout.count()..layout.count() {
+            let mut camera = renderer.camera;
+            camera.projection = Projection::Orthographic;
+            camera.set_view(PRESETS[i - 1]);
+            self.views[i].camera = camera;
+            self.views[i].display = renderer.display;
+        }
+        self.layout = layout;
+    }
+
+    /// Let view `index` take input, keeping the state of the one before
+    pub fn activate(&mut self, index: usize, renderer: &mut Renderer) {
+        if index != self.active {
+            self.swap(self.active, renderer);
+            self.swap(index, renderer);
+            self.active = index;
+        }
+    }
+
+    /// Advance the running view switches of every view by `dt` seconds
+    pub fn animate(&mut self, dt: f32, renderer: &mut Renderer) {
+        renderer.camera.animate(dt);
+        for (i, view) in self.views.iter_mut().enumerate() {
+            if i != self.active {
+                view.camera.animate(dt);
+            }
+        }
+    }
+
+    /// View taking input
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Size of the active view's texture, once it has been drawn
+    pub fn size(&self) -> Option<(u32, u32)> {
+        let view = &self.views[self.active];
+        view.texture.as_ref().map(|texture| texture.size)
+    }
+
+    /// Draw each view into its cell from [`ViewLayout::cells`], with the
+    /// overlay labels, and frame the active one when there are several
+    pub fn show(
+        &mut self,
+        ui: &egui::Ui,
+        renderer: &mut Renderer,
+        wgpu_state: &RenderState,
+        cells: &[egui::Rect],
+    ) {
+        for (i, cell) in cells.iter().enumerate() {
+            let (width, height) = (cell.width() as u32, cell.height() as u32);
+            if width == 0 || height == 0 {
+                continue;
+            }
+            self.ensure_texture(i, wgpu_state, (width, height));
+            if renderer.size() != (width, height) {
+                renderer.resize(&wgpu_state.device, width, height);
+            }
+            let Some(rt) = &self.views[i].texture else {
+                continue;
+            };
+            let (target, texture_id) = (rt.view.clone(), rt.egui_texture_id);
+            if i != self.active {
+                self.swap(i, renderer);
+            }
+
+            let mut encoder =
+                wgpu_state
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("CAD Encoder"),
+                    });
+            renderer.fit_clip_planes();
+            renderer.render(&mut encoder, &target, &wgpu_state.queue, width, height);
+            // Submitted one by one, as each view writes the shared uniforms
+            wgpu_state.queue.submit(std::iter::once(encoder.finish()));
+
+            ui.painter().image(
+                texture_id,
+                *cell,
+                egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                egui::Color32::WHITE,
+            );
+            let viewport = glam::Vec2::new(cell.width(), cell.height());
+            for (pos, label) in renderer.overlay.screen_labels(&renderer.camera, viewport) {
+                let [r, g, b] = label.color;
+                ui.painter().text(
+                    cell.min + egui::vec2(pos.x, pos.y) + LABEL_OFFSET,
+                    egui::Align2::LEFT_BOTTOM,
+                    &label.text,
+                    egui::FontId::monospace(12.0),
+                    egui::Rgba::from_rgb(r, g, b).into(),
+                );
+            }
+
+            if i != self.active {
+                self.swap(i, renderer);
+            } else if cells.len() > 1 {
+                ui.painter()
+                    .rect_stroke(*cell, 0.0, ACTIVE_STROKE, egui::StrokeKind::Inside);
+            }
+        }
+    }
+
+    /// Trade the camera and display settings of view `index` with the
+    /// renderer's
+    fn swap(&mut self, index: usize, renderer: &mut Renderer) {
+        let view = &mut self.views[index];
+        std::mem::swap(&mut view.camera, &mut renderer.camera);
+        std::mem::swap(&mut view.display, &mut renderer.display);
+    }
+
+    /// (Re)create the texture of view `index` at `size`; the renderer
+    /// resolves multisampled frames into it, so it stays single-sampled
+    fn ensure_texture(&mut self, index: usize, wgpu_state: &RenderState, size: (u32, u32)) {
+        let view = &mut self.views[index];
+        if view.texture.as_ref().is_some_and(|rt| rt.size == size) {
+            return;
+        }
+        if let Some(old) = view.texture.take() {
+            wgpu_state
+                .renderer
+                .write()
+                .free_texture(&old.egui_texture_id);
+        }
+
+        let texture = wgpu_state.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Viewport Texture"),
+            size: wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu_state.target_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // Register with egui for display
+        let egui_texture_id = wgpu_state.renderer.write().register_native_texture(
+            &wgpu_state.device,
+            &texture_view,
+            wgpu::FilterMode::Linear,
+        );
+        view.texture = Some(RenderTexture {
+            texture,
+            view: texture_view,
+            egui_texture_id,
+            size,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_cells() {
+        let rect = egui::Rect::from_min_size(egui::pos2(10.0, 20.0), egui::vec2(801.0, 600.0));
+        assert_eq!(
+            ViewLayout::Single.cells(rect),
+            [egui::Rect {
+                min: rect.min,
+                max: egui::pos2(811.0, 620.0)
+            }]
+        );
+
+        let cells = ViewLayout::Quad.cells(rect);
+        assert_eq!(cells.len(), ViewLayout::Quad.count());
+        for cell in &cells {
+            assert_eq!(cell.size(), egui::vec2(400.0, 300.0));
+            assert!(rect.contains_rect(*cell));
+        }
+        assert_eq!(cells[1].min, egui::pos2(410.0, 20.0));
+        assert_eq!(cells[2].min, egui::pos2(10.0, 320.0));
+        assert_eq!(
+            ViewLayout::Split.cells(rect)[1].size(),
+            egui::vec2(400.0, 600.0)
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::renderer::DisplaySettings;
+use crate::sketch::{SketchError, SketchResult};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Folder under the user's config directory the settings go in
+const CONFIG_DIR: &str = "truck-playground";
+
+/// File the appearance is kept in
+const CONFIG_FILE: &str = "appearance.json";
+
+/// Light or dark look of the window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    /// Whatever the system is set to
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Dark, Theme::Light];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::System => "System",
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+        }
+    }
+
+    pub fn preference(&self) -> egui::ThemePreference {
+        match self {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Dark => egui::ThemePreference::Dark,
+            Theme::Light => egui::ThemePreference::Light,
+        }
+    }
+}
+
+/// Look of the window and the viewport, kept between runs in a config
+/// file. Projects still carry their own display settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub theme: Theme,
+    pub display: DisplaySettings,
+}
+
+impl Appearance {
+    /// Where the appearance is kept, see [`config_dir`]
+    pub fn config_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> SketchResult<()> {
+        write_json(path.as_ref(), self)
+    }
+
+    /// Settings missing from the file keep their defaults
+    pub fn load(path: impl AsRef<Path>) -> SketchResult<Self> {
+        read_json(path.as_ref())
+    }
+}
+
+/// The app's folder under `$XDG_CONFIG_HOME`, else `~/.config`, or
+/// `%APPDATA%` on Windows; `None` when none is set
+pub fn config_dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    let base = var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("APPDATA").map(PathBuf::from))
+        .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join(CONFIG_DIR))
+}
+
+/// Write `value` as JSON to the config file at `path`, making its folder
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> SketchResult<()> {
+    let failed = |e: std::io::Error| {
+        SketchError::InvalidSettings(format!("could not write {}: {}", path.display(), e))
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(failed)?;
+    }
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| SketchError::InvalidSettings(e.to_string()))?;
+    std::fs::write(path, json).map_err(failed)
+}
+
+/// Read the JSON config file at `path`
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path) -> SketchResult<T> {
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        SketchError::InvalidSettings(format!("could not read {}: {}", path.display(), e))
+    })?;
+    serde_json::from_str(&text)
+        .map_err(|e| SketchError::InvalidSettings(format!("{}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_appearance_round_trip() {
+        let dir = std::env::temp_dir().join(format!("truck-appearance-{}", std::process::id()));
+        let path = dir.join(CONFIG_DIR).join(CONFIG_FILE);
+        let mut appearance = Appearance {
+            theme: Theme::Light,
+            ..Default::default()
+        };
+        appearance.display.background = [0.9, 0.9, 0.9];
+        appearance.display.selection_tint = [0.0, 1.0, 0.0, 0.5];
+        appearance.save(&path).unwrap();
+        assert_eq!(Appearance::load(&path).unwrap(), appearance);
+
+        // Older files without the newer settings still load
+        std::fs::write(
+            &path,
+            r#"{"theme": "Dark", "display": {"show_grid": false}}"#,
+        )
+        .unwrap();
+        let loaded = Appearance::load(&path).unwrap();
+        assert_eq!(loaded.theme, Theme::Dark);
+        assert!(!loaded.display.show_grid);
+        assert_eq!(
+            loaded.display.grid_colors,
+            DisplaySettings::default().grid_colors
+        );
+        std::fs::write(&path, "not json").unwrap();
+        assert!(Appearance::load(&path).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,282 @@
+use crate::model::Assembly;
+use crate::renderer::mesh::{GpuMesh, MeshQuality};
+use crate::units::Units;
+use serde_json::{json, Value};
+use std::io;
+use std::path::Path;
+use truck_geometry::prelude::*;
+use truck_modeling::Solid;
+
+/// Color given to objects created with [`GltfObject::new`] (the viewer's gray)
+pub const DEFAULT_COLOR: [f32; 4] = [0.7, 0.7, 0.7, 1.0];
+
+/// Colors cycled through for the parts of an assembly
+const PART_COLORS: [[f32; 4]; 6] = [
+    [0.7, 0.7, 0.7, 1.0],
+    [0.35, 0.55, 0.8, 1.0],
+    [0.85, 0.5, 0.3, 1.0],
+    [0.45, 0.7, 0.4, 1.0],
+    [0.8, 0.75, 0.35, 1.0],
+    [0.6, 0.45, 0.75, 1.0],
+];
+
+// GLB container constants (glTF 2.0 spec, section 4.4)
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+
+/// A solid written as one named glTF node with its own material
+#[derive(Clone, Debug)]
+pub struct GltfObject<'a> {
+    pub name: &'a str,
+    pub solid: &'a Solid,
+    /// Linear RGBA base color; alpha below 1 makes the material blended
+    pub color: [f32; 4],
+    /// Placement of the node (the solid stays in its own coordinates)
+    pub transform: Matrix4,
+}
+
+impl<'a> GltfObject<'a> {
+    pub fn new(name: &'a str, solid: &'a Solid) -> Self {
+        Self {
+            name,
+            solid,
+            color: DEFAULT_COLOR,
+            transform: Matrix4::identity(),
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Binary glTF of the objects, tessellated to `quality`.
+///
+/// Normals come from the surfaces, so they are smooth across each face and
+/// split along the edges between faces. Models are Z-up in `units`; a root
+/// node turns them into glTF's Y-up convention and scales them to metres.
+pub fn to_glb(objects: &[GltfObject], quality: impl Into<MeshQuality>, units: Units) -> Vec<u8> {
+    let quality = quality.into();
+    let mut bin = Vec::new();
+    let (mut views, mut accessors) = (Vec::new(), Vec::new());
+    let (mut meshes, mut materials, mut nodes) = (Vec::new(), Vec::new(), Vec::new());
+
+    for (i, object) in objects.iter().enumerate() {
+        let mesh = GpuMesh::from_solid(object.solid, quality);
+        let mut node = json!({ "name": object.name });
+        if object.transform != Matrix4::identity() {
+            let cols: [[f64; 4]; 4] = object.transform.into();
+            node["matrix"] = json!(cols.concat());
+        }
+        materials.push(json!({
+            "name": object.name,
+            "pbrMetallicRoughness": {
+                "baseColorFactor": object.color,
+                "metallicFactor": 0.0,
+                "roughnessFactor": 0.6,
+            },
+            "alphaMode": if object.color[3] < 1.0 { "BLEND" } else { "OPAQUE" },
+        }));
+
+        if !mesh.indices.is_empty() {
+            let positions: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.position).collect();
+            let normals: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.normal).collect();
+            let (min, max) = bounds(&positions);
+
+            let position = accessors.len();
+            let view = push_view(&mut bin, &mut views, &positions, ARRAY_BUFFER);
+            accessors.push(json!({
+                "bufferView": view, "componentType": FLOAT, "count": positions.len(),
+                "type": "VEC3", "min": min, "max": max,
+            }));
+            let view = push_view(&mut bin, &mut views, &normals, ARRAY_BUFFER);
+            accessors.push(json!({
+                "bufferView": view, "componentType": FLOAT, "count": normals.len(),
+                "type": "VEC3",
+            }));
+            let view = push_view(&mut bin, &mut views, &mesh.indices, ELEMENT_ARRAY_BUFFER);
+            accessors.push(json!({
+                "bufferView": view, "componentType": UNSIGNED_INT,
+                "count": mesh.indices.len(), "type": "SCALAR",
+            }));
+
+            node["mesh"] = json!(meshes.len());
+            meshes.push(json!({
+                "name": object.name,
+                "primitives": [{
+                    "attributes": { "POSITION": position, "NORMAL": position + 1 },
+                    "indices": position + 2,
+                    "material": i,
+                }],
+            }));
+        }
+        nodes.push(node);
+    }
+
+    // Root node: -90° about X takes Z-up to Y-up
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    let mut root = json!({
+        "name": "model",
+        "rotation": [-half, 0.0, 0.0, half],
+        "children": (0..objects.len()).collect::<Vec<_>>(),
+    });
+    if units != Units::Metre {
+        let scale = units.convert(1.0, Units::Metre);
+        root["scale"] = json!([scale, scale, scale]);
+    }
+    nodes.push(root);
+
+    let mut document = json!({
+        "asset": { "version": "2.0", "generator": "truck-playground" },
+        "scene": 0,
+        "scenes": [{ "nodes": [objects.len()] }],
+        "nodes": nodes,
+        "materials": materials,
+        "meshes": meshes,
+        "accessors": accessors,
+        "bufferViews": views,
+    });
+    if !bin.is_empty() {
+        document["buffers"] = json!([{ "byteLength": bin.len() }]);
+    }
+    glb(&document, bin)
+}
+
+/// Binary glTF of every part of an assembly, one node per part placed by
+/// its transform, with colors cycling through a fixed palette
+pub fn assembly_to_glb(
+    assembly: &Assembly,
+    quality: impl Into<MeshQuality>,
+    units: Units,
+) -> Vec<u8> {
+    let objects: Vec<_> = assembly
+        .parts()
+        .iter()
+        .enumerate()
+        .map(|(i, part)| GltfObject {
+            transform: part.transform,
+            ..GltfObject::new(part.name(), &part.solid)
+                .with_color(PART_COLORS[i % PART_COLORS.len()])
+        })
+        .collect();
+    to_glb(&objects, quality, units)
+}
+
+/// Write [`to_glb`] to a `.glb` file
+pub fn write_glb(
+    path: impl AsRef<Path>,
+    objects: &[GltfObject],
+    quality: impl Into<MeshQuality>,
+    units: Units,
+) -> io::Result<()> {
+    std::fs::write(path, to_glb(objects, quality, units))
+}
+
+/// Append `items` to the binary buffer (4-byte aligned) as a new buffer view
+fn push_view<T: bytemuck::Pod>(
+    bin: &mut Vec<u8>,
+    views: &mut Vec<Value>,
+    items: &[T],
+    target: u32,
+) -> usize {
+    let bytes: &[u8] = bytemuck::cast_slice(items);
+    views.push(json!({
+        "buffer": 0, "byteOffset": bin.len(), "byteLength": bytes.len(), "target": target,
+    }));
+    bin.extend_from_slice(bytes);
+    bin.resize(bin.len().next_multiple_of(4), 0);
+    views.len() - 1
+}
+
+fn bounds(points: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    points.iter().fold(
+        ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]),
+        |(min, max), p| {
+            (
+                std::array::from_fn(|k| min[k].min(p[k])),
+                std::array::from_fn(|k| max[k].max(p[k])),
+            )
+        },
+    )
+}
+
+/// Header, space-padded JSON chunk and zero-padded binary chunk
+fn glb(document: &Value, bin: Vec<u8>) -> Vec<u8> {
+    let mut json = document.to_string().into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let mut total = 12 + 8 + json.len();
+    if !bin.is_empty() {
+        total += 8 + bin.len();
+    }
+
+    let mut out = Vec::with_capacity(total);
+    for word in [
+        GLB_MAGIC,
+        GLB_VERSION,
+        total as u32,
+        json.len() as u32,
+        CHUNK_JSON,
+    ] {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+    out.extend_from_slice(&json);
+    if !bin.is_empty() {
+        out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        out.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+        out.extend_from_slice(&bin);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+
+    fn read_u32(bytes: &[u8], at: usize) -> usize {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn test_assembly_glb_layout() {
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 2.0, 3.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly
+            .add("base", cube.clone(), Matrix4::identity())
+            .unwrap();
+        let lifted = Matrix4::from_translation(Vector3::new(0.0, 0.0, 5.0));
+        assembly.add("lid", cube, lifted).unwrap();
+
+        let glb = assembly_to_glb(&assembly, 0.01, Units::Millimetre);
+        assert_eq!(read_u32(&glb, 0) as u32, GLB_MAGIC);
+        assert_eq!(read_u32(&glb, 8), glb.len());
+        let json_len = read_u32(&glb, 12);
+        let doc: Value = serde_json::from_slice(&glb[20..20 + json_len]).unwrap();
+        let bin_len = read_u32(&glb, 20 + json_len);
+        assert_eq!(
+            doc["buffers"][0]["byteLength"].as_u64().unwrap() as usize,
+            bin_len
+        );
+        assert_eq!(20 + json_len + 8 + bin_len, glb.len());
+
+        assert_eq!(doc["materials"].as_array().unwrap().len(), 2);
+        assert_eq!(doc["nodes"][1]["name"], "lid");
+        assert_eq!(doc["nodes"][1]["matrix"][14], 5.0);
+        assert!(doc["nodes"][0].get("matrix").is_none());
+        assert_eq!(doc["nodes"][2]["children"], json!([0, 1]));
+        assert_eq!(doc["nodes"][2]["scale"], json!([0.001, 0.001, 0.001]));
+
+        let position = &doc["accessors"][0];
+        assert_eq!(position["max"], json!([1.0, 2.0, 3.0]));
+        let indices = doc["accessors"][2]["count"].as_u64().unwrap();
+        assert_eq!(indices % 3, 0);
+        assert!(indices >= 36);
+    }
+}
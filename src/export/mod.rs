@@ -0,0 +1,5 @@
+pub mod gltf;
+pub mod ply;
+pub mod step;
+pub mod stl;
+pub mod svg;
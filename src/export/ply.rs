@@ -0,0 +1,69 @@
+use crate::renderer::mesh::GpuMesh;
+use std::io;
+use std::path::Path;
+
+/// Binary little-endian PLY of a mesh: float positions and normals per
+/// vertex, triangles as `uchar`-counted `uint` index lists
+pub fn to_ply(mesh: &GpuMesh) -> Vec<u8> {
+    let triangles = mesh.indices.len() / 3;
+    let header = format!(
+        "ply\n\
+         format binary_little_endian 1.0\n\
+         comment truck-playground\n\
+         element vertex {}\n\
+         property float x\nproperty float y\nproperty float z\n\
+         property float nx\nproperty float ny\nproperty float nz\n\
+         element face {}\n\
+         property list uchar uint vertex_indices\n\
+         end_header\n",
+        mesh.vertices.len(),
+        triangles
+    );
+
+    let mut out = header.into_bytes();
+    out.reserve(mesh.vertices.len() * 24 + triangles * 13);
+    for vertex in &mesh.vertices {
+        for value in vertex.position.iter().chain(&vertex.normal) {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    for triangle in mesh.indices.chunks_exact(3) {
+        out.push(3);
+        for index in triangle {
+            out.extend_from_slice(&index.to_le_bytes());
+        }
+    }
+    out
+}
+
+/// Write [`to_ply`] to a `.ply` file
+pub fn write_ply(path: impl AsRef<Path>, mesh: &GpuMesh) -> io::Result<()> {
+    std::fs::write(path, to_ply(mesh))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+    use truck_geometry::prelude::*;
+
+    #[test]
+    fn test_binary_layout() {
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let mesh = GpuMesh::from_solid(&cube, 0.01);
+        let ply = to_ply(&mesh);
+
+        let end = b"end_header\n";
+        let body = ply.windows(end.len()).position(|w| w == end).unwrap() + end.len();
+        let header = std::str::from_utf8(&ply[..body]).unwrap();
+        assert!(header.contains("format binary_little_endian 1.0\n"));
+        assert!(header.contains(&format!("element vertex {}\n", mesh.vertices.len())));
+        let faces = mesh.indices.len() / 3;
+        assert!(header.contains(&format!("element face {}\n", faces)));
+        assert_eq!(ply.len(), body + mesh.vertices.len() * 24 + faces * 13);
+
+        let x = f32::from_le_bytes(ply[body..body + 4].try_into().unwrap());
+        assert_eq!(x, mesh.vertices[0].position[0]);
+        assert_eq!(ply[body + mesh.vertices.len() * 24], 3);
+    }
+}
@@ -0,0 +1,671 @@
+use crate::sketch::{Curve2D, Plane, Sketch, SketchCurve2D};
+use crate::units::Units;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+use truck_geometry::prelude::*;
+use truck_modeling::Solid;
+use truck_stepio::out::{CompleteStepDisplay, FloatDisplay, StepHeaderDescriptor, StepModels};
+
+/// Application protocol the file declares
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StepSchema {
+    /// AP203, configuration controlled design
+    Ap203,
+    /// AP214, automotive design
+    #[default]
+    Ap214,
+    /// AP242, managed model-based 3D engineering
+    Ap242,
+}
+
+impl StepSchema {
+    /// `FILE_SCHEMA` entry
+    fn file_schema(self) -> &'static str {
+        match self {
+            StepSchema::Ap203 => "CONFIG_CONTROL_DESIGN",
+            StepSchema::Ap214 => "AUTOMOTIVE_DESIGN { 1 0 10303 214 1 1 1 1 }",
+            StepSchema::Ap242 => {
+                "AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF { 1 0 10303 442 1 1 4 }"
+            }
+        }
+    }
+
+    /// Application name and year for `APPLICATION_PROTOCOL_DEFINITION`
+    fn protocol(self) -> (&'static str, u32) {
+        match self {
+            StepSchema::Ap203 => ("config_control_design", 1994),
+            StepSchema::Ap214 => ("automotive_design", 2000),
+            StepSchema::Ap242 => ("ap242_managed_model_based_3d_engineering", 2014),
+        }
+    }
+}
+
+/// Metadata written into an exported STEP file
+#[derive(Clone, Debug)]
+pub struct StepExportOptions {
+    /// Length unit the model coordinates are declared in; coordinates are
+    /// written as they are, not scaled
+    pub unit: Units,
+    /// Name of the product (part) the solids belong to
+    pub product_name: String,
+    pub description: String,
+    pub author: String,
+    pub organization: String,
+    pub schema: StepSchema,
+}
+
+impl Default for StepExportOptions {
+    fn default() -> Self {
+        Self {
+            unit: Units::default(),
+            product_name: "part".to_string(),
+            description: String::new(),
+            author: String::new(),
+            organization: String::new(),
+            schema: StepSchema::default(),
+        }
+    }
+}
+
+/// STEP text of one solid
+pub fn export_step(solid: &Solid, options: &StepExportOptions) -> String {
+    export_step_solids(std::slice::from_ref(solid), options)
+}
+
+/// STEP text with each solid as its own body of one product
+pub fn export_step_solids(solids: &[Solid], options: &StepExportOptions) -> String {
+    let compressed: Vec<_> = solids.iter().map(|solid| solid.compress()).collect();
+    let text = CompleteStepDisplay::new(
+        StepModels::from_iter(&compressed),
+        StepHeaderDescriptor {
+            organization_system: "truck-playground".to_owned(),
+            ..Default::default()
+        },
+    )
+    .to_string();
+    apply_options(&fix_revolution_sense(&text), options)
+}
+
+/// STEP text of named parts as an assembly: the options' product uses one
+/// product per part through a `NEXT_ASSEMBLY_USAGE_OCCURRENCE`. Part solids
+/// are written in assembly coordinates, so every occurrence is placed by the
+/// identity transformation.
+pub fn export_step_assembly(parts: &[(&str, Solid)], options: &StepExportOptions) -> String {
+    let solids: Vec<Solid> = parts.iter().map(|(_, solid)| solid.clone()).collect();
+    let names: Vec<&str> = parts.iter().map(|(name, _)| *name).collect();
+    add_assembly_structure(&export_step_solids(&solids, options), &names)
+}
+
+/// Write [`export_step`] to a `.step` file
+pub fn write_step(
+    path: impl AsRef<Path>,
+    solid: &Solid,
+    options: &StepExportOptions,
+) -> io::Result<()> {
+    std::fs::write(path, export_step(solid, options))
+}
+
+/// STEP text of the loops of `sketch` lifted onto `plane`, as one
+/// geometric curve set of a wireframe product
+pub fn export_sketch_wireframe(
+    sketch: &Sketch,
+    plane: &Plane,
+    options: &StepExportOptions,
+) -> String {
+    let mut wireframe = Wireframe {
+        plane,
+        entities: Entities::new(17),
+    };
+    let curves: Vec<String> = std::iter::once(&sketch.outer)
+        .chain(&sketch.holes)
+        .flat_map(|l| l.curves())
+        .map(|curve| format!("#{}", wireframe.curve(curve)))
+        .collect();
+    // Same product preamble as truck-stepio, so `apply_options` can patch it
+    let data = format!(
+        "#1 = APPLICATION_PROTOCOL_DEFINITION('international standard', 'automotive_design', 2000, #2);
+#2 = APPLICATION_CONTEXT('core data for automotive mechanical design processes');
+#3 = SHAPE_DEFINITION_REPRESENTATION(#4, #10);
+#4 = PRODUCT_DEFINITION_SHAPE('','', #5);
+#5 = PRODUCT_DEFINITION('design','', #6, #9);
+#6 = PRODUCT_DEFINITION_FORMATION('','', #7);
+#7 = PRODUCT('','','', (#8));
+#8 = PRODUCT_CONTEXT('', #2, 'mechanical');
+#9 = PRODUCT_DEFINITION_CONTEXT('part definition', #2, 'design');
+#10 = GEOMETRICALLY_BOUNDED_WIREFRAME_SHAPE_REPRESENTATION('', (#16), #11);
+#11 = (
+    GEOMETRIC_REPRESENTATION_CONTEXT(3)
+    GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT((#15))
+    GLOBAL_UNIT_ASSIGNED_CONTEXT((#12, #13, #14))
+    REPRESENTATION_CONTEXT('Context #1', '3D Context with UNIT and UNCERTAINTY')
+);
+#12 = ( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.) );
+#13 = ( NAMED_UNIT(*) PLANE_ANGLE_UNIT() SI_UNIT($,.RADIAN.) );
+#14 = ( NAMED_UNIT(*) SI_UNIT($,.STERADIAN.) SOLID_ANGLE_UNIT() );
+#15 = UNCERTAINTY_MEASURE_WITH_UNIT(1.0E-6, #12, 'distance_accuracy_value','confusion accuracy');
+#16 = GEOMETRIC_CURVE_SET('', ({}));
+{}",
+        curves.join(", "),
+        wireframe.entities.data
+    );
+    let text = CompleteStepDisplay::new(
+        data,
+        StepHeaderDescriptor {
+            organization_system: "truck-playground".to_owned(),
+            ..Default::default()
+        },
+    )
+    .to_string();
+    apply_options(&text, options)
+}
+
+/// Write [`export_sketch_wireframe`] with default options to a `.step` file,
+/// so profiles can be exchanged before any solid exists
+pub fn write_sketch_wireframe(
+    sketch: &Sketch,
+    plane: &Plane,
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let options = StepExportOptions::default();
+    std::fs::write(path, export_sketch_wireframe(sketch, plane, &options))
+}
+
+/// DATA entities of lifted sketch curves
+struct Wireframe<'a> {
+    plane: &'a Plane,
+    entities: Entities,
+}
+
+impl Wireframe<'_> {
+    fn entity(&mut self, body: impl std::fmt::Display) -> usize {
+        self.entities.entity(body)
+    }
+
+    fn point(&mut self, p: Point2) -> usize {
+        let p = self.plane.lift_point(p);
+        self.entity(format_args!(
+            "CARTESIAN_POINT('', ({}, {}, {}))",
+            FloatDisplay(p.x),
+            FloatDisplay(p.y),
+            FloatDisplay(p.z)
+        ))
+    }
+
+    fn direction(&mut self, v: Vector3) -> usize {
+        self.entity(format_args!(
+            "DIRECTION('', ({}, {}, {}))",
+            FloatDisplay(v.x),
+            FloatDisplay(v.y),
+            FloatDisplay(v.z)
+        ))
+    }
+
+    /// Circle centred at `center` starting at `angle` in the plane, running
+    /// counter-clockwise seen from the normal when `ccw`
+    fn circle(&mut self, center: Point2, radius: f64, angle: f64, ccw: bool) -> usize {
+        let location = self.point(center);
+        let normal = match ccw {
+            true => self.plane.normal(),
+            false => -self.plane.normal(),
+        };
+        let axis = self.direction(normal);
+        let reference =
+            self.direction(self.plane.x_dir() * angle.cos() + self.plane.y_dir() * angle.sin());
+        let placement = self.entity(format_args!(
+            "AXIS2_PLACEMENT_3D('', #{}, #{}, #{})",
+            location, axis, reference
+        ));
+        self.entity(format_args!(
+            "CIRCLE('', #{}, {})",
+            placement,
+            FloatDisplay(radius)
+        ))
+    }
+
+    /// Bounded curve for one sketch curve, trimmed by points so the angle
+    /// unit does not matter
+    fn curve(&mut self, curve: &Curve2D) -> usize {
+        match curve {
+            Curve2D::Line(line) => {
+                let (start, end) = (self.point(line.start()), self.point(line.end()));
+                self.entity(format_args!("POLYLINE('', (#{}, #{}))", start, end))
+            }
+            Curve2D::Arc(arc) => {
+                let circle =
+                    self.circle(arc.center(), arc.radius(), arc.start_angle(), arc.is_ccw());
+                let (start, end) = (self.point(arc.start()), self.point(arc.end()));
+                self.entity(format_args!(
+                    "TRIMMED_CURVE('', #{}, (#{}), (#{}), .T., .CARTESIAN.)",
+                    circle, start, end
+                ))
+            }
+            Curve2D::Circle(circle) => {
+                let seam = circle.start() - circle.center();
+                let angle = seam.y.atan2(seam.x);
+                self.circle(circle.center(), circle.radius(), angle, circle.is_ccw())
+            }
+            Curve2D::BSpline(spline) => {
+                let points: Vec<String> = spline
+                    .control_points()
+                    .iter()
+                    .map(|&p| format!("#{}", self.point(p)))
+                    .collect();
+                let (knots, multiplicities) = spline.inner().knot_vec().to_single_multi();
+                let multiplicities: Vec<String> =
+                    multiplicities.iter().map(usize::to_string).collect();
+                let knots: Vec<String> =
+                    knots.iter().map(|&k| FloatDisplay(k).to_string()).collect();
+                self.entity(format_args!(
+                    "B_SPLINE_CURVE_WITH_KNOTS('', {}, ({}), .UNSPECIFIED., .F., .F., ({}), ({}), .UNSPECIFIED.)",
+                    spline.degree(),
+                    points.join(", "),
+                    multiplicities.join(", "),
+                    knots.join(", ")
+                ))
+            }
+        }
+    }
+}
+
+/// truck-stepio writes every face as having the same sense as its surface,
+/// but truck revolves against STEP's parametrisation, so faces on surfaces of
+/// revolution need their `same_sense` flag toggled
+fn fix_revolution_sense(text: &str) -> String {
+    let entity = |line: &str| {
+        let (id, body) = line.split_once('=')?;
+        Some((id.trim().to_string(), body.trim().to_string()))
+    };
+    let revolutions: HashSet<String> = text
+        .lines()
+        .filter_map(entity)
+        .filter(|(_, body)| body.starts_with("SURFACE_OF_REVOLUTION"))
+        .map(|(id, _)| id)
+        .collect();
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        let face = entity(line)
+            .filter(|(_, body)| {
+                body.starts_with("FACE_SURFACE") || body.starts_with("ADVANCED_FACE")
+            })
+            .and_then(|(id, body)| {
+                let args = top_level_args(&body)?;
+                // (name, bounds, face_geometry, same_sense)
+                let [.., surface, sense_arg] = args.as_slice() else {
+                    return None;
+                };
+                if !revolutions.contains(surface.trim()) {
+                    return None;
+                }
+                let sense = match sense_arg.trim() {
+                    ".T." => ".F.",
+                    ".F." => ".T.",
+                    _ => return None,
+                };
+                // The last argument runs up to the closing parenthesis
+                let keep = body.rfind(')')? - sense_arg.trim_start().len();
+                Some(format!("{} = {}{});", id, &body[..keep], sense))
+            });
+        out.push_str(face.as_deref().unwrap_or(line));
+        out.push('\n');
+    }
+    out
+}
+
+/// Arguments of a `NAME(...);` entity body split at top-level commas, so
+/// strings and nested lists may contain commas
+fn top_level_args(body: &str) -> Option<Vec<&str>> {
+    let open = body.find('(')?;
+    let close = body.rfind(')')?;
+    let inner = body.get(open + 1..close)?;
+    let mut args = Vec::new();
+    let (mut depth, mut quoted, mut start) = (0usize, false, 0);
+    for (i, c) in inner.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth = depth.checked_sub(1)?,
+            ',' if !quoted && depth == 0 => {
+                args.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(&inner[start..]);
+    Some(args)
+}
+
+/// Move each solid of the root shape representation into a product of its
+/// own, used once by the root product
+fn add_assembly_structure(text: &str, names: &[&str]) -> String {
+    let root = "#10 = ADVANCED_BREP_SHAPE_REPRESENTATION(";
+    let solids: Vec<&str> = text
+        .lines()
+        .find(|line| line.starts_with(root))
+        .and_then(top_level_args)
+        .and_then(|args| args.get(1).copied())
+        .map(|list| {
+            list.trim()
+                .trim_matches(['(', ')'])
+                .split(',')
+                .map(str::trim)
+                .collect()
+        })
+        .unwrap_or_default();
+    if solids.len() != names.len() {
+        return text.to_string();
+    }
+
+    let mut entities = Entities::new(next_entity_id(text));
+    let origin = entities.entity("CARTESIAN_POINT('', (0.0, 0.0, 0.0))");
+    let z = entities.entity("DIRECTION('', (0.0, 0.0, 1.0))");
+    let x = entities.entity("DIRECTION('', (1.0, 0.0, 0.0))");
+    let placement = format!("AXIS2_PLACEMENT_3D('', #{}, #{}, #{})", origin, z, x);
+    let root_placement = entities.entity(&placement);
+    for (i, (name, solid)) in names.iter().zip(&solids).enumerate() {
+        let name = step_string(name);
+        let part_placement = entities.entity(&placement);
+        let product = entities.entity(format_args!("PRODUCT('{}','{}','', (#8))", name, name));
+        let formation = entities.entity(format_args!(
+            "PRODUCT_DEFINITION_FORMATION('','', #{})",
+            product
+        ));
+        let definition = entities.entity(format_args!(
+            "PRODUCT_DEFINITION('design','', #{}, #9)",
+            formation
+        ));
+        let shape = entities.entity(format_args!(
+            "PRODUCT_DEFINITION_SHAPE('','', #{})",
+            definition
+        ));
+        let representation = entities.entity(format_args!(
+            "ADVANCED_BREP_SHAPE_REPRESENTATION('{}', ({}, #{}), #11)",
+            name, solid, part_placement
+        ));
+        entities.entity(format_args!(
+            "SHAPE_DEFINITION_REPRESENTATION(#{}, #{})",
+            shape, representation
+        ));
+        let usage = entities.entity(format_args!(
+            "NEXT_ASSEMBLY_USAGE_OCCURRENCE('{}','{}','', #5, #{}, $)",
+            i + 1,
+            name,
+            definition
+        ));
+        let usage_shape =
+            entities.entity(format_args!("PRODUCT_DEFINITION_SHAPE('','', #{})", usage));
+        let transformation = entities.entity(format_args!(
+            "ITEM_DEFINED_TRANSFORMATION('','', #{}, #{})",
+            part_placement, root_placement
+        ));
+        let relationship = entities.entity(format_args!(
+            "( REPRESENTATION_RELATIONSHIP('','', #{}, #10) \
+             REPRESENTATION_RELATIONSHIP_WITH_TRANSFORMATION(#{}) \
+             SHAPE_REPRESENTATION_RELATIONSHIP() )",
+            representation, transformation
+        ));
+        entities.entity(format_args!(
+            "CONTEXT_DEPENDENT_SHAPE_REPRESENTATION(#{}, #{})",
+            relationship, usage_shape
+        ));
+    }
+
+    let mut out = String::with_capacity(text.len() + entities.data.len());
+    let end = text.rfind("ENDSEC;").unwrap_or(text.len());
+    for line in text[..end].lines() {
+        if line.starts_with(root) {
+            let _ = writeln!(
+                out,
+                "#10 = SHAPE_REPRESENTATION('', (#{}), #11);",
+                root_placement
+            );
+        } else {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out.push_str(&entities.data);
+    out.push_str(&text[end..]);
+    out
+}
+
+/// DATA entities numbered from `next_id`
+struct Entities {
+    data: String,
+    next_id: usize,
+}
+
+impl Entities {
+    fn new(next_id: usize) -> Self {
+        Self {
+            data: String::new(),
+            next_id,
+        }
+    }
+
+    fn entity(&mut self, body: impl std::fmt::Display) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        let _ = writeln!(self.data, "#{} = {};", id, body);
+        id
+    }
+}
+
+/// One past the largest entity id in `text`
+fn next_entity_id(text: &str) -> usize {
+    text.lines()
+        .filter_map(|line| {
+            line.strip_prefix('#')?
+                .split(' ')
+                .next()?
+                .parse::<usize>()
+                .ok()
+        })
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+/// Patch the fixed header, product and unit entities truck-stepio writes
+fn apply_options(text: &str, options: &StepExportOptions) -> String {
+    let name = step_string(&options.product_name);
+    let description = step_string(&options.description);
+    let (application, year) = options.schema.protocol();
+    let mut next_id = next_entity_id(text);
+    let mut extra = String::new();
+
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        let line = if line.starts_with("FILE_DESCRIPTION(") {
+            format!("FILE_DESCRIPTION(('{}'), '2;1');", description)
+        } else if let Some(rest) = line.strip_prefix("FILE_NAME('', '") {
+            // truck-stepio nests the author lists and writes a non-ISO time
+            let time = rest.split(['.', '\'']).next().unwrap_or_default();
+            format!(
+                "FILE_NAME('{}', '{}', ('{}'), ('{}'), 'truck', 'truck-playground', '');",
+                name,
+                time.replace(' ', "T"),
+                step_string(&options.author),
+                step_string(&options.organization)
+            )
+        } else if line.starts_with("FILE_SCHEMA(") {
+            format!("FILE_SCHEMA(('{}'));", options.schema.file_schema())
+        } else if line.contains("= APPLICATION_PROTOCOL_DEFINITION(") {
+            let (id, _) = line.split_once(" = ").unwrap_or_default();
+            format!(
+                "{} = APPLICATION_PROTOCOL_DEFINITION('international standard', '{}', {}, #2);",
+                id, application, year
+            )
+        } else if line.contains("= PRODUCT('','','',") {
+            line.replacen(
+                "PRODUCT('','','',",
+                &format!("PRODUCT('{}','{}','{}',", name, name, description),
+                1,
+            )
+        } else if line.contains("LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.)") {
+            let (id, _) = line.split_once(" = ").unwrap_or_default();
+            format!(
+                "{} = {};",
+                id,
+                length_unit(options.unit, &mut next_id, &mut extra)
+            )
+        } else {
+            line.to_string()
+        };
+        if line == "END-ISO-10303-21;" {
+            // Entities added for conversion-based units go at the end of DATA
+            let end = out.trim_end().strip_suffix("ENDSEC;").map(str::len);
+            if let Some(end) = end {
+                out.truncate(end);
+                out.push_str(&extra);
+                out.push_str("ENDSEC;\n");
+            }
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Unit entity for `unit`; units defined through millimetres append their
+/// helper entities to `extra`
+fn length_unit(unit: Units, next_id: &mut usize, extra: &mut String) -> String {
+    let si = |prefix: &str| {
+        format!(
+            "( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT({},.METRE.) )",
+            prefix
+        )
+    };
+    match unit {
+        Units::Millimetre => si(".MILLI."),
+        Units::Centimetre => si(".CENTI."),
+        Units::Metre => si("$"),
+        Units::Inch => {
+            let (measure, exponents, millimetre) = (*next_id, *next_id + 1, *next_id + 2);
+            *next_id += 3;
+            extra.push_str(&format!(
+                "#{} = LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(25.4), #{});\n\
+                 #{} = DIMENSIONAL_EXPONENTS(1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);\n\
+                 #{} = {};\n",
+                measure,
+                millimetre,
+                exponents,
+                millimetre,
+                si(".MILLI.")
+            ));
+            format!(
+                "( CONVERSION_BASED_UNIT('INCH', #{}) LENGTH_UNIT() NAMED_UNIT(#{}) )",
+                measure, exponents
+            )
+        }
+    }
+}
+
+/// STEP string contents: backslashes doubled, apostrophes and non-ASCII as
+/// `\X2\` UTF-16 runs (ruststep cannot read the `''` escape back)
+fn step_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            c if c.is_ascii() && !c.is_ascii_control() && c != '\'' => out.push(c),
+            c => {
+                out.push_str("\\X2\\");
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    out.push_str(&format!("{:04X}", unit));
+                }
+                out.push_str("\\X0\\");
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import;
+    use crate::model::cylinder;
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::Shapes;
+    use truck_stepio::r#in::Table;
+
+    #[test]
+    fn test_options() {
+        let rod = cylinder(Point3::origin(), Vector3::unit_z() * 4.0, 1.0).unwrap();
+        let options = StepExportOptions {
+            unit: Units::Inch,
+            product_name: "Bracket's rod".to_string(),
+            description: "Ø10 rod".to_string(),
+            author: "J. Doe".to_string(),
+            organization: "ACME".to_string(),
+            schema: StepSchema::Ap203,
+        };
+        let step = export_step(&rod, &options);
+
+        assert!(
+            step.contains("PRODUCT('Bracket\\X2\\0027\\X0\\s rod','Bracket\\X2\\0027\\X0\\s rod','\\X2\\00D8\\X0\\10 rod',")
+        );
+        assert!(step.contains("FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));"));
+        assert!(step.contains("'config_control_design', 1994"));
+        assert!(step.contains("('J. Doe'), ('ACME')"));
+        assert!(step.contains("CONVERSION_BASED_UNIT('INCH'"));
+        assert!(step.contains("LENGTH_MEASURE(25.4)"));
+        assert!(step.trim_end().ends_with("ENDSEC;\nEND-ISO-10303-21;"));
+    }
+
+    #[test]
+    fn test_revolution_sense_toggled() {
+        let text = "#1 = SURFACE_OF_REVOLUTION('', #9, #10);
+#2 = PLANE('', #11);
+#3 = ADVANCED_FACE('a, (b)', (#4, #5), #1, .T.);
+#6 = FACE_SURFACE('', (#7),#1,.F.);
+#8 = ADVANCED_FACE('', (#4), #2, .T.);
+";
+        let fixed = fix_revolution_sense(text);
+        let lines: Vec<&str> = fixed.lines().collect();
+        assert_eq!(lines[2], "#3 = ADVANCED_FACE('a, (b)', (#4, #5), #1, .F.);");
+        assert_eq!(lines[3], "#6 = FACE_SURFACE('', (#7),#1,.T.);");
+        assert_eq!(lines[4], "#8 = ADVANCED_FACE('', (#4), #2, .T.);");
+        assert_eq!(lines[..2], text.lines().collect::<Vec<_>>()[..2]);
+    }
+
+    #[test]
+    fn test_revolved_round_trip() {
+        let rod = cylinder(Point3::origin(), Vector3::unit_z() * 4.0, 1.0).unwrap();
+        let step = export_step(&rod, &StepExportOptions::default());
+
+        // Faces on revolved surfaces read back on the right side of the seam
+        let solids = import::step::parse(&step).unwrap();
+        let volume = GpuMesh::from_solid(&solids[0], 0.001).volume();
+        assert!((volume - 4.0 * std::f64::consts::PI).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_sketch_wireframe() {
+        let outer = Shapes::rounded_rectangle(Point2::origin(), 10.0, 6.0, 1.0).unwrap();
+        let hole = Shapes::circle(Point2::new(5.0, 3.0), 1.0).unwrap();
+        let sketch = Sketch::with_holes(outer, vec![hole]);
+        let options = StepExportOptions {
+            product_name: "profile".to_string(),
+            ..Default::default()
+        };
+        let step = export_sketch_wireframe(&sketch, &Plane::xy_at(2.5), &options);
+
+        assert!(
+            step.contains("GEOMETRICALLY_BOUNDED_WIREFRAME_SHAPE_REPRESENTATION('', (#16), #11);")
+        );
+        assert!(step.contains("PRODUCT('profile','profile','',"));
+        let set = step
+            .lines()
+            .find(|l| l.starts_with("#16 = GEOMETRIC_CURVE_SET("))
+            .unwrap();
+        assert_eq!(set.matches('#').count(), 1 + 8 + 1);
+        assert_eq!(step.matches("TRIMMED_CURVE(").count(), 4);
+        assert_eq!(step.matches("CIRCLE(").count(), 5);
+        assert!(step.contains("CARTESIAN_POINT('', (10.0, 1.0, 2.5))"));
+        assert!(Table::from_step(&step).is_some());
+    }
+}
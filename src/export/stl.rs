@@ -0,0 +1,56 @@
+use crate::renderer::mesh::GpuMesh;
+use std::io;
+use std::path::Path;
+
+/// Bytes in a binary STL header
+const HEADER_LEN: usize = 80;
+
+/// Binary STL of a mesh: one facet per triangle with the normal of its
+/// plane, since STL has no vertex normals
+pub fn to_stl(mesh: &GpuMesh) -> Vec<u8> {
+    let triangles = mesh.indices.len() / 3;
+    let mut out = vec![0; HEADER_LEN];
+    let name = b"truck-playground";
+    out[..name.len()].copy_from_slice(name);
+    out.reserve(4 + triangles * 50);
+    out.extend_from_slice(&(triangles as u32).to_le_bytes());
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] =
+            [0, 1, 2].map(|i| glam::Vec3::from(mesh.vertices[triangle[i] as usize].position));
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        for point in [normal, a, b, c] {
+            for value in point.to_array() {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        // Attribute byte count, unused
+        out.extend_from_slice(&[0, 0]);
+    }
+    out
+}
+
+/// Write [`to_stl`] to a `.stl` file
+pub fn write_stl(path: impl AsRef<Path>, mesh: &GpuMesh) -> io::Result<()> {
+    std::fs::write(path, to_stl(mesh))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::mesh::read_stl;
+    use crate::model::box_solid;
+    use truck_geometry::prelude::*;
+
+    #[test]
+    fn test_stl_round_trip() {
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 2.0, 3.0)).unwrap();
+        let mesh = GpuMesh::from_solid(&cube, 0.01);
+        let stl = to_stl(&mesh);
+        let triangles = mesh.indices.len() / 3;
+        assert_eq!(stl.len(), HEADER_LEN + 4 + triangles * 50);
+
+        let read = read_stl(&stl[..]).unwrap();
+        assert_eq!(read.indices.len(), mesh.indices.len());
+        assert!((read.volume() - 6.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::renderer::mesh::{GpuMesh, MeshQuality};
+use crate::sketch::hatch::flatten_curve;
+use crate::sketch::{BoundingBox2D, Curve2D, Loop2D, Plane, Sketch, SketchCurve2D};
+use crate::units::Units;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+use truck_geometry::prelude::*;
+use truck_modeling::Solid;
+
+/// Chord tolerance for flattening splines, relative to the drawing size
+const RELATIVE_CHORD_TOLERANCE: f64 = 1e-4;
+
+/// Blank border around the drawing, relative to its size
+const MARGIN: f64 = 0.02;
+
+/// 2D drawing of sketch profiles and solid silhouettes, one SVG user unit
+/// per model unit with +Y up like the sketches; the page is sized in
+/// millimetres from [`SvgDrawing::units`].
+///
+/// Profiles are filled with the even-odd rule so holes stay open whichever
+/// way their loops wind; silhouettes are outlines only.
+#[derive(Clone, Debug, Default)]
+pub struct SvgDrawing {
+    profiles: Vec<Sketch>,
+    outlines: Vec<Vec<Point2>>,
+    /// What the coordinates are in
+    pub units: Units,
+}
+
+impl SvgDrawing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a filled profile
+    pub fn add_sketch(&mut self, sketch: &Sketch) -> &mut Self {
+        self.profiles.push(sketch.clone());
+        self
+    }
+
+    /// Add the outline of `solid` seen along the normal of `plane`, in the
+    /// plane's coordinates: every mesh edge between a triangle facing the
+    /// viewer and one facing away, so inner contours are drawn too
+    pub fn add_silhouette(
+        &mut self,
+        solid: &Solid,
+        plane: impl Into<Plane>,
+        quality: impl Into<MeshQuality>,
+    ) -> &mut Self {
+        let plane = plane.into();
+        let mesh = GpuMesh::from_solid(solid, quality);
+
+        // Faces meet on the same edge polylines, so equal positions weld
+        let mut positions = Vec::new();
+        let mut welded: HashMap<[u32; 3], usize> = HashMap::new();
+        let index: Vec<usize> = mesh
+            .vertices
+            .iter()
+            .map(|v| {
+                *welded
+                    .entry(v.position.map(f32::to_bits))
+                    .or_insert_with(|| {
+                        let [x, y, z] = v.position.map(f64::from);
+                        positions.push(Point3::new(x, y, z));
+                        positions.len() - 1
+                    })
+            })
+            .collect();
+
+        // Facing of the triangles on each side of every edge
+        let mut edges: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| index[triangle[k] as usize]);
+            let normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+            let front = normal.dot(plane.normal()) > 0.0;
+            for (p, q) in [(a, b), (b, c), (c, a)] {
+                let sides = edges.entry((p.min(q), p.max(q))).or_default();
+                if front {
+                    sides.0 += 1;
+                } else {
+                    sides.1 += 1;
+                }
+            }
+        }
+        let mut segments: Vec<(usize, usize)> = edges
+            .into_iter()
+            .filter(|&(_, (front, back))| front == 1 && back <= 1)
+            .map(|(edge, _)| edge)
+            .collect();
+        segments.sort();
+
+        for chain in chains(&segments) {
+            let outline = chain
+                .iter()
+                .map(|&i| plane.project_point(positions[i]))
+                .collect();
+            self.outlines.push(outline);
+        }
+        self
+    }
+
+    /// Extent of everything drawn
+    pub fn bounding_box(&self) -> Option<BoundingBox2D> {
+        let profiles = self.profiles.iter().filter_map(Sketch::bounding_box);
+        let outlines = self
+            .outlines
+            .iter()
+            .filter_map(|outline| BoundingBox2D::from_points(outline));
+        profiles.chain(outlines).reduce(|a, b| a.union(&b))
+    }
+
+    /// The SVG document; empty drawings give an empty canvas
+    pub fn to_svg(&self) -> String {
+        let (min, max) = self
+            .bounding_box()
+            .map_or((Point2::origin(), Point2::origin()), |b| (b.min, b.max));
+        let size = (max - min).magnitude().max(1.0);
+        let margin = size * MARGIN;
+        let (width, height) = (max.x - min.x + 2.0 * margin, max.y - min.y + 2.0 * margin);
+        let tol = size * RELATIVE_CHORD_TOLERANCE;
+
+        let mm = self.units.millimetres();
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}mm\" height=\"{}mm\" \
+             viewBox=\"{} {} {w} {h}\">\n",
+            num(width * mm),
+            num(height * mm),
+            num(min.x - margin),
+            num(-max.y - margin),
+            w = num(width),
+            h = num(height),
+        );
+        for sketch in &self.profiles {
+            let mut d = String::new();
+            for l in std::iter::once(&sketch.outer).chain(&sketch.holes) {
+                loop_path(&mut d, l, tol);
+            }
+            let _ = writeln!(
+                svg,
+                "  <path d=\"{}\" fill=\"#d0d0d0\" fill-rule=\"evenodd\" stroke=\"#000\" \
+                 stroke-width=\"1\" vector-effect=\"non-scaling-stroke\"/>",
+                d.trim_end()
+            );
+        }
+        if !self.outlines.is_empty() {
+            let mut d = String::new();
+            for outline in &self.outlines {
+                for (i, p) in outline.iter().enumerate() {
+                    let command = if i == 0 { 'M' } else { 'L' };
+                    let _ = write!(d, "{}{} ", command, point(*p));
+                }
+            }
+            let _ = writeln!(
+                svg,
+                "  <path d=\"{}\" fill=\"none\" stroke=\"#000\" stroke-width=\"1\" \
+                 vector-effect=\"non-scaling-stroke\"/>",
+                d.trim_end()
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Drawing of `sketches` as SVG text
+pub fn sketches_to_svg(sketches: &[Sketch]) -> String {
+    let mut drawing = SvgDrawing::new();
+    for sketch in sketches {
+        drawing.add_sketch(sketch);
+    }
+    drawing.to_svg()
+}
+
+/// Write [`SvgDrawing::to_svg`] to a `.svg` file
+pub fn write_svg(path: impl AsRef<Path>, drawing: &SvgDrawing) -> io::Result<()> {
+    std::fs::write(path, drawing.to_svg())
+}
+
+/// Closed subpath for one loop; lines, arcs and circles are exact, splines
+/// are flattened within `tol`
+fn loop_path(d: &mut String, l: &Loop2D, tol: f64) {
+    let Some(first) = l.curves().first() else {
+        return;
+    };
+    let _ = write!(d, "M{} ", point(first.start()));
+    for curve in l.curves() {
+        match curve {
+            Curve2D::Line(line) => {
+                let _ = write!(d, "L{} ", point(line.end()));
+            }
+            // Y is flipped, so counter-clockwise arcs sweep the negative way
+            Curve2D::Arc(arc) => {
+                let sweep = u8::from(!arc.is_ccw());
+                let r = num(arc.radius());
+                // Split past half a turn so the large-arc flag can stay 0
+                if arc.sweep_angle().abs() > std::f64::consts::PI {
+                    let _ = write!(d, "A{r} {r} 0 0 {} {} ", sweep, point(arc.point_at(0.5)));
+                }
+                let _ = write!(d, "A{r} {r} 0 0 {} {} ", sweep, point(arc.end()));
+            }
+            Curve2D::Circle(circle) => {
+                let sweep = u8::from(!circle.is_ccw());
+                let r = num(circle.radius());
+                let (mid, end) = (circle.point_at(0.5), circle.end());
+                let _ = write!(d, "A{r} {r} 0 0 {sweep} {} ", point(mid));
+                let _ = write!(d, "A{r} {r} 0 0 {sweep} {} ", point(end));
+            }
+            Curve2D::BSpline(_) => {
+                for p in flatten_curve(curve, tol).into_iter().skip(1) {
+                    let _ = write!(d, "L{} ", point(p));
+                }
+                let _ = write!(d, "L{} ", point(curve.end()));
+            }
+        }
+    }
+    d.push_str("Z ");
+}
+
+/// Polylines through `segments`, each joined end to end where it can be
+fn chains(segments: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut neighbours: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, &(a, b)) in segments.iter().enumerate() {
+        neighbours.entry(a).or_default().push(i);
+        neighbours.entry(b).or_default().push(i);
+    }
+    let mut used = vec![false; segments.len()];
+    let mut chains = Vec::new();
+    for first in 0..segments.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut chain = vec![segments[first].0, segments[first].1];
+        // Grow from one end, then from the other
+        for _ in 0..2 {
+            while let Some(&next) = neighbours[&chain[chain.len() - 1]]
+                .iter()
+                .find(|&&i| !used[i])
+            {
+                used[next] = true;
+                let (a, b) = segments[next];
+                chain.push(if a == chain[chain.len() - 1] { b } else { a });
+            }
+            chain.reverse();
+        }
+        chains.push(chain);
+    }
+    chains
+}
+
+/// SVG coordinates, which point Y down
+fn point(p: Point2) -> String {
+    format!("{},{}", num(p.x), num(-p.y))
+}
+
+/// Shortest decimal within a micrometre
+fn num(value: f64) -> String {
+    let text = format!("{:.6}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+    use crate::sketch::Shapes;
+
+    #[test]
+    fn test_svg_profiles_and_silhouette() {
+        let outer = Shapes::rectangle(Point2::origin(), 10.0, 6.0).unwrap();
+        let hole = Shapes::circle(Point2::new(5.0, 3.0), 1.5).unwrap();
+        let svg = sketches_to_svg(&[Sketch::with_holes(outer, vec![hole])]);
+        assert!(svg.contains("fill-rule=\"evenodd\""));
+        assert!(svg.contains("M0,0 L10,0 L10,-6 L0,-6 L0,0 Z M6.5,-3 A1.5 1.5 0 0 0 3.5,-3"));
+        assert!(svg.contains("width=\"10.466476mm\""));
+
+        // A box seen from above outlines its top face only
+        let cube = box_solid(Point3::origin(), Vector3::new(4.0, 2.0, 1.0)).unwrap();
+        let mut drawing = SvgDrawing::new();
+        drawing.add_silhouette(&cube, Plane::xy(), 0.01);
+        assert_eq!(drawing.outlines.len(), 1);
+        assert_eq!(drawing.outlines[0].len(), 5);
+        let bbox = drawing.bounding_box().unwrap();
+        assert!((bbox.max - bbox.min - Vector2::new(4.0, 2.0)).magnitude() < 1e-9);
+        assert!(drawing.to_svg().contains("fill=\"none\""));
+        drawing.units = Units::Centimetre;
+        // The page grows with the unit while the coordinates stay put
+        let svg = drawing.to_svg();
+        assert!(svg.contains("width=\"41.788854mm\""), "{}", svg);
+        assert!(svg.contains(" 4.178885 "));
+    }
+}
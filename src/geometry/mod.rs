@@ -1,15 +1,8 @@
 use truck_geometry::prelude::*;
 use truck_modeling::*;
 
-pub fn create_test_solid() -> Solid {
-    // Create a simple box
-    let vertex = builder::vertex(Point3::new(-10.0, -10.0, 0.0));
-    let edge = builder::tsweep(&vertex, Vector3::new(20.0, 0.0, 0.0));
-    let face = builder::tsweep(&edge, Vector3::new(0.0, 20.0, 0.0));
-    let solid = builder::tsweep(&face, Vector3::new(0.0, 0.0, 20.0));
-
-    solid
-}
+/// Tolerance used to intersect faces in boolean operations
+pub const BOOLEAN_TOLERANCE: f64 = 0.05;
 
 pub fn solid_from_sketch(
     sketch: &crate::sketch::Sketch,
@@ -18,3 +11,22 @@ pub fn solid_from_sketch(
     let plane = crate::sketch::Plane::xy();
     sketch.extrude(&plane, Vector3::new(0.0, 0.0, height))
 }
+
+/// Subtract `tool` from `base`
+pub fn cut(base: &Solid, tool: &Solid) -> std::result::Result<Solid, crate::sketch::SketchError> {
+    let mut inverted = tool.clone();
+    inverted.not();
+    truck_shapeops::and(base, &inverted, BOOLEAN_TOLERANCE).ok_or_else(|| {
+        crate::sketch::SketchError::BooleanFailed(
+            "could not intersect the base and tool faces".to_string(),
+        )
+    })
+}
+
+/// Join `a` and `b` into one solid
+pub fn union(a: &Solid, b: &Solid) -> std::result::Result<Solid, crate::sketch::SketchError> {
+    truck_shapeops::or(a, b, BOOLEAN_TOLERANCE).ok_or_else(|| {
+        crate::sketch::SketchError::BooleanFailed("could not intersect the faces to join".to_string())
+    })
+}
+
@@ -0,0 +1,450 @@
+use crate::export::gltf::{to_glb, GltfObject};
+use crate::export::ply::to_ply;
+use crate::export::step::{export_step, StepExportOptions};
+use crate::export::stl::to_stl;
+use crate::export::svg::SvgDrawing;
+use crate::model::Assembly;
+use crate::renderer::camera::{OrbitCamera, Turntable};
+use crate::renderer::mesh::GpuMesh;
+use crate::renderer::offscreen::{encode_png, max_image_size, OffscreenRenderer};
+use crate::script::run_script;
+use crate::sketch::{SketchError, SketchResult};
+use crate::units::Units;
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use truck_geometry::prelude::*;
+use truck_modeling::Solid;
+
+pub const USAGE: &str = "usage: truck-playground --headless <script.rhai> --out <dir> \
+                         [--formats step,stl,glb,ply,svg,png] [--units mm|cm|m|in] \
+                         [--size <width>x<height>] [--turntable <frames>]";
+
+/// Pixel size of thumbnails and turntable frames unless `--size` is given
+pub const IMAGE_SIZE: (u32, u32) = (512, 512);
+
+/// Folder under `--out` the turntable frames go in
+const TURNTABLE_DIR: &str = "turntable";
+
+/// File formats a batch run can write; solids go to all but SVG, which
+/// takes the sketches, and PNG, which is a rendered thumbnail of each part
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Step,
+    Stl,
+    Glb,
+    Ply,
+    Svg,
+    Png,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 6] = [
+        ExportFormat::Step,
+        ExportFormat::Stl,
+        ExportFormat::Glb,
+        ExportFormat::Ply,
+        ExportFormat::Svg,
+        ExportFormat::Png,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Step => "step",
+            ExportFormat::Stl => "stl",
+            ExportFormat::Glb => "glb",
+            ExportFormat::Ply => "ply",
+            ExportFormat::Svg => "svg",
+            ExportFormat::Png => "png",
+        }
+    }
+
+    fn parse(name: &str) -> SketchResult<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        let name = if name == "stp" { "step" } else { name.as_str() };
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == name)
+            .ok_or_else(|| SketchError::InvalidArguments(format!("unknown format '{}'", name)))
+    }
+}
+
+/// Command line of a run without the window
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadlessArgs {
+    pub script: PathBuf,
+    /// Directory the files go in, created if missing
+    pub out: PathBuf,
+    pub formats: Vec<ExportFormat>,
+    pub units: Units,
+    /// Width and height of rendered images
+    pub image_size: (u32, u32),
+    /// Frames of one turn around all parts to render; none when 0
+    pub turntable: usize,
+}
+
+impl HeadlessArgs {
+    /// Read the arguments after the program name; `None` unless they ask
+    /// for `--headless`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> SketchResult<Option<Self>> {
+        let invalid = |message: String| SketchError::InvalidArguments(message);
+        let mut args = args.into_iter();
+        let (mut script, mut out) = (None, None);
+        let mut formats = vec![ExportFormat::Step];
+        let mut units = Units::default();
+        let mut image_size = IMAGE_SIZE;
+        let mut turntable = 0;
+        let mut headless = false;
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| invalid(format!("{} needs a value", arg)))
+            };
+            match arg.as_str() {
+                "--headless" => {
+                    headless = true;
+                    script = Some(PathBuf::from(value()?));
+                }
+                "--out" => out = Some(PathBuf::from(value()?)),
+                "--formats" => {
+                    formats = value()?
+                        .split(',')
+                        .filter(|name| !name.trim().is_empty())
+                        .map(ExportFormat::parse)
+                        .collect::<SketchResult<_>>()?;
+                }
+                "--units" => {
+                    let symbol = value()?;
+                    units = Units::ALL
+                        .into_iter()
+                        .find(|units| units.suffix() == symbol)
+                        .ok_or_else(|| invalid(format!("unknown units '{}'", symbol)))?;
+                }
+                "--size" => {
+                    let size = value()?;
+                    image_size = size
+                        .split_once('x')
+                        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                        .filter(|&(w, h)| w > 0 && h > 0)
+                        .ok_or_else(|| {
+                            invalid(format!(
+                                "bad image size '{}', expected <width>x<height> in pixels",
+                                size
+                            ))
+                        })?;
+                    let max = max_image_size();
+                    if image_size.0 > max || image_size.1 > max {
+                        return Err(invalid(format!(
+                            "image size '{}' is over the {} pixel limit",
+                            size, max
+                        )));
+                    }
+                }
+                "--turntable" => {
+                    let frames = value()?;
+                    turntable = frames
+                        .parse()
+                        .map_err(|_| invalid(format!("bad frame count '{}'", frames)))?;
+                }
+                _ => return Err(invalid(format!("unexpected argument '{}'", arg))),
+            }
+        }
+        if !headless {
+            return match out {
+                Some(_) => Err(invalid("--out needs --headless".to_string())),
+                None => Ok(None),
+            };
+        }
+        if formats.is_empty() {
+            return Err(invalid("no formats given".to_string()));
+        }
+        Ok(Some(Self {
+            script: script.unwrap_or_default(),
+            out: out.ok_or_else(|| invalid("missing --out".to_string()))?,
+            formats,
+            units,
+            image_size,
+            turntable,
+        }))
+    }
+}
+
+/// Run the script and write each part and sketch it added in every format,
+/// named after it and numbered where names would collide, then the turntable frames if asked for; parts are
+/// exported in parallel. Returns the files written.
+pub fn run_headless(args: &HeadlessArgs) -> SketchResult<Vec<PathBuf>> {
+    let code = std::fs::read_to_string(&args.script).map_err(|e| {
+        SketchError::ScriptFailed(format!("could not read {}: {}", args.script.display(), e))
+    })?;
+    let output = run_script(&code, args.units)?;
+    if output.parts.is_empty() && output.sketches.is_empty() {
+        return Err(SketchError::ScriptFailed(
+            "the script added no parts or sketches".to_string(),
+        ));
+    }
+    std::fs::create_dir_all(&args.out).map_err(|e| write_failed(&args.out, e))?;
+
+    let part_stems = unique_stems(output.parts.iter().map(|(name, _)| name.as_str()));
+    let sketch_stems = unique_stems(output.sketches.iter().map(|placed| placed.name.as_str()));
+    let mut written = Vec::new();
+    let mut write = |stem: &str, format: ExportFormat, bytes: Vec<u8>| {
+        let path = args.out.join(format!("{}.{}", stem, format.extension()));
+        std::fs::write(&path, bytes).map_err(|e| write_failed(&path, e))?;
+        written.push(path);
+        Ok::<_, SketchError>(())
+    };
+    for format in &args.formats {
+        if *format == ExportFormat::Svg {
+            for (placed, stem) in output.sketches.iter().zip(&sketch_stems) {
+                let mut drawing = SvgDrawing::new();
+                drawing.units = args.units;
+                drawing.add_sketch(&placed.sketch);
+                write(stem, *format, drawing.to_svg().into_bytes())?;
+            }
+            continue;
+        }
+        if *format == ExportFormat::Png {
+            let mut offscreen = OffscreenRenderer::new(args.image_size)?;
+            for ((name, solid), stem) in output.parts.iter().zip(&part_stems) {
+                let mut assembly = Assembly::new();
+                assembly.add(name.clone(), solid.clone(), Matrix4::identity())?;
+                offscreen.set_assembly(&assembly);
+                let camera = offscreen.framed(&OrbitCamera::default());
+                let pixels = offscreen.render(&camera)?;
+                write(stem, *format, encode_png(offscreen.size(), &pixels)?)?;
+            }
+            continue;
+        }
+        let files: Vec<Vec<u8>> = output
+            .parts
+            .par_iter()
+            .map(|(name, solid)| export_part(name, solid, *format, args.units))
+            .collect();
+        for (stem, bytes) in part_stems.iter().zip(files) {
+            write(stem, *format, bytes)?;
+        }
+    }
+    if args.turntable > 0 && !output.parts.is_empty() {
+        let mut assembly = Assembly::new();
+        for (name, solid) in &output.parts {
+            let name = assembly.unique_name(name);
+            assembly.add(name, solid.clone(), Matrix4::identity())?;
+        }
+        let mut offscreen = OffscreenRenderer::new(args.image_size)?;
+        offscreen.set_assembly(&assembly);
+        let camera = offscreen.framed(&OrbitCamera::default());
+        let dir = args.out.join(TURNTABLE_DIR);
+        let frames =
+            offscreen.render_turntable(&camera, &Turntable::default(), args.turntable, dir)?;
+        written.extend(frames);
+    }
+    Ok(written)
+}
+
+/// Contents of the `format` file for part `name`
+fn export_part(name: &str, solid: &Solid, format: ExportFormat, units: Units) -> Vec<u8> {
+    let tolerance = mesh_tolerance(solid);
+    match format {
+        ExportFormat::Step => {
+            let options = StepExportOptions {
+                unit: units,
+                product_name: name.to_string(),
+                ..Default::default()
+            };
+            export_step(solid, &options).into_bytes()
+        }
+        ExportFormat::Stl => to_stl(&GpuMesh::from_solid(solid, tolerance)),
+        ExportFormat::Ply => to_ply(&GpuMesh::from_solid(solid, tolerance)),
+        ExportFormat::Glb => to_glb(&[GltfObject::new(name, solid)], tolerance, units),
+        ExportFormat::Svg | ExportFormat::Png => unreachable!("written apart from the files"),
+    }
+}
+
+fn write_failed(path: &Path, e: std::io::Error) -> SketchError {
+    SketchError::ExportFailed(format!("could not write {}: {}", path.display(), e))
+}
+
+/// Chord tolerance for meshing `solid`: finer for smaller parts
+fn mesh_tolerance(solid: &Solid) -> f64 {
+    let bounds: BoundingBox<Point3> = solid.vertex_iter().map(|v| v.point()).collect();
+    (bounds.diameter() * 0.001).max(1e-6)
+}
+
+/// `name` with the characters file systems disallow replaced
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    match stem.trim() {
+        "" | "." | ".." => "part".to_string(),
+        stem => stem.to_string(),
+    }
+}
+
+/// File stems of `names`, numbered from 2 up where one would overwrite an
+/// earlier file, including on case-insensitive file systems
+fn unique_stems<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut taken = HashSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let base = file_stem(name);
+            let stem = std::iter::once(base.clone())
+                .chain((2..).map(|i| format!("{} {}", base, i)))
+                .find(|stem| !taken.contains(&stem.to_lowercase()))
+                .expect("some numbered stem is free");
+            taken.insert(stem.to_lowercase());
+            stem
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> SketchResult<Option<HeadlessArgs>> {
+        HeadlessArgs::parse(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_headless_run() {
+        assert_eq!(args("").unwrap(), None);
+        assert!(args("--out dir").is_err());
+        assert!(args("--headless a.rhai").is_err());
+        assert!(args("--headless a.rhai --out dir --formats step,obj").is_err());
+        assert!(args("--headless a.rhai --out dir --units ft").is_err());
+        assert!(args("--headless a.rhai --out dir --size 0x5").is_err());
+        assert!(args("--headless a.rhai --out dir --size 20000x20000").is_err());
+        assert!(args("--headless a.rhai --out dir --turntable many").is_err());
+
+        let dir = std::env::temp_dir().join(format!("truck-headless-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("bracket.rhai");
+        std::fs::write(
+            &script,
+            r#"
+            for size in [10, 20] {
+                let plate = sketch(rectangle(0, 0, size, size / 2));
+                add_part(`plate ${size}/a`, extrude(plate, plane_xy(), 2));
+            }
+            add_sketch("outline", plane_xy(), sketch(circle(0, 0, 5)));
+            "#,
+        )
+        .unwrap();
+        let line = format!(
+            "--headless {} --out {} --formats step,STL,svg --units in",
+            script.display(),
+            dir.join("out").display()
+        );
+        let parsed = args(&line).unwrap().unwrap();
+        assert_eq!(parsed.units, Units::Inch);
+
+        let written = run_headless(&parsed).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "plate 10_a.step",
+                "plate 20_a.step",
+                "plate 10_a.stl",
+                "plate 20_a.stl",
+                "outline.svg"
+            ]
+        );
+        let step = std::fs::read_to_string(&written[1]).unwrap();
+        assert!(step.contains("CONVERSION_BASED_UNIT('INCH'"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_headless_duplicate_names() {
+        let dir = std::env::temp_dir().join(format!("truck-duplicates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("parts.rhai");
+        std::fs::write(
+            &script,
+            r#"
+            let block = extrude(sketch(rectangle(0, 0, 1, 1)), plane_xy(), 1);
+            for name in ["a", "a", "a/b", "a_b", "A"] {
+                add_part(name, block);
+            }
+            "#,
+        )
+        .unwrap();
+        let line = format!(
+            "--headless {} --out {} --formats step",
+            script.display(),
+            dir.join("out").display()
+        );
+        let written = run_headless(&args(&line).unwrap().unwrap()).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            ["a.step", "a 2.step", "a_b.step", "a_b 2.step", "A 3.step"]
+        );
+        assert!(written.iter().all(|path| path.exists()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_headless_images() {
+        let dir = std::env::temp_dir().join(format!("truck-images-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("plate.rhai");
+        std::fs::write(
+            &script,
+            r#"add_part("plate", extrude(sketch(rectangle(0, 0, 4, 2)), plane_xy(), 1));"#,
+        )
+        .unwrap();
+        let line = format!(
+            "--headless {} --out {} --formats png --size 40x30 --turntable 3",
+            script.display(),
+            dir.join("out").display()
+        );
+        let parsed = args(&line).unwrap().unwrap();
+        assert_eq!(parsed.image_size, (40, 30));
+
+        let written = run_headless(&parsed).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|path| {
+                path.strip_prefix(&parsed.out)
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "plate.png",
+                "turntable/frame_0000.png",
+                "turntable/frame_0001.png",
+                "turntable/frame_0002.png"
+            ]
+        );
+        let decoder = png::Decoder::new(std::io::BufReader::new(
+            std::fs::File::open(&written[0]).unwrap(),
+        ));
+        let mut reader = decoder.read_info().unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (40, 30));
+        // The framed part covers the middle, so not every pixel is background
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        let middle = &pixels[(15 * 40 + 20) * 4..][..4];
+        assert_ne!(middle, &pixels[..4]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,319 @@
+use crate::sketch::constants::{ANGLE_TOLERANCE, HEAL_TOLERANCE, LENGTH_TOLERANCE};
+use crate::sketch::{
+    Arc2D, BSpline2D, Circle2D, Curve2D, Line2D, Sketch, SketchError, SketchResult,
+};
+use std::collections::BTreeMap;
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::path::Path;
+use truck_geometry::prelude::*;
+
+/// Entity types turned into curves; the rest are skipped
+const SUPPORTED: [&str; 5] = ["LINE", "ARC", "CIRCLE", "LWPOLYLINE", "SPLINE"];
+
+/// Profiles drawn in an ASCII `.dxf` file, see [`parse`]
+pub fn read(path: impl AsRef<Path>) -> SketchResult<Vec<Sketch>> {
+    parse(&read_text(path.as_ref())?)
+}
+
+/// Like [`read`], with the notes of [`parse_with_warnings`]
+pub fn read_with_warnings(path: impl AsRef<Path>) -> SketchResult<(Vec<Sketch>, Vec<String>)> {
+    parse_with_warnings(&read_text(path.as_ref())?)
+}
+
+/// Like [`parse`], with a note for each type of entity skipped and for
+/// curves left out of every profile
+pub fn parse_with_warnings(text: &str) -> SketchResult<(Vec<Sketch>, Vec<String>)> {
+    let (curves, skipped) = entities(text)?;
+    let sketches = Sketch::regions(&curves, HEAL_TOLERANCE)?;
+    let mut warnings: Vec<String> = skipped
+        .into_iter()
+        .map(|(kind, count)| {
+            let plural = if count == 1 { "entity" } else { "entities" };
+            format!("skipped {} {} {}", count, kind, plural)
+        })
+        .collect();
+    let used: usize = sketches
+        .iter()
+        .flat_map(|sketch| std::iter::once(&sketch.outer).chain(&sketch.holes))
+        .map(|l| l.curves().len())
+        .sum();
+    if used < curves.len() {
+        warnings.push(format!(
+            "{} curve(s) close no profile and were left out",
+            curves.len() - used
+        ));
+    }
+    Ok((sketches, warnings))
+}
+
+fn read_text(path: &Path) -> SketchResult<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| SketchError::ImportFailed(format!("could not read {}: {}", path.display(), e)))
+}
+
+/// Closed profiles found among the drawing's curves by
+/// [`Sketch::regions`]; curves that don't close into a loop are ignored
+pub fn parse(text: &str) -> SketchResult<Vec<Sketch>> {
+    Sketch::regions(&curves(text)?, HEAL_TOLERANCE)
+}
+
+/// LINE, ARC, CIRCLE, LWPOLYLINE and SPLINE entities of the ENTITIES
+/// section in drawing order, flattened onto XY. Other entities (text,
+/// dimensions, block inserts, ...) are skipped.
+pub fn curves(text: &str) -> SketchResult<Vec<Curve2D>> {
+    Ok(entities(text)?.0)
+}
+
+/// Curves as [`curves`] gives them, and how many entities of each
+/// unsupported type were skipped
+fn entities(text: &str) -> SketchResult<(Vec<Curve2D>, BTreeMap<String, usize>)> {
+    let groups = groups(text)?;
+    let start = groups
+        .windows(2)
+        .position(|w| w[0] == (0, "SECTION") && w[1] == (2, "ENTITIES"))
+        .ok_or_else(|| dxf_error("no ENTITIES section"))?;
+
+    let mut curves = Vec::new();
+    let mut skipped = BTreeMap::new();
+    let mut entity = &groups[start + 2..];
+    while let Some(&(0, kind)) = entity.first() {
+        if kind == "ENDSEC" {
+            break;
+        }
+        let len = entity[1..]
+            .iter()
+            .position(|g| g.0 == 0)
+            .map_or(entity.len(), |n| n + 1);
+        let (head, rest) = entity.split_at(len);
+        if SUPPORTED.contains(&kind) {
+            curves.extend(Entity(&head[1..]).curves(kind)?);
+        } else {
+            *skipped.entry(kind.to_string()).or_insert(0) += 1;
+        }
+        entity = rest;
+    }
+    Ok((curves, skipped))
+}
+
+/// Group code / value pairs
+fn groups(text: &str) -> SketchResult<Vec<(i32, &str)>> {
+    let mut lines = text.lines().map(str::trim);
+    let mut groups = Vec::new();
+    while let Some(code) = lines.next() {
+        if code.is_empty() {
+            continue;
+        }
+        let code = code
+            .parse()
+            .map_err(|_| dxf_error(&format!("bad group code '{}'", code)))?;
+        let value = lines
+            .next()
+            .ok_or_else(|| dxf_error("file ends inside a group"))?;
+        groups.push((code, value));
+    }
+    Ok(groups)
+}
+
+fn dxf_error(message: &str) -> SketchError {
+    SketchError::ImportFailed(format!("DXF: {}", message))
+}
+
+fn number(code: i32, value: &str) -> SketchResult<f64> {
+    value
+        .parse()
+        .map_err(|_| dxf_error(&format!("bad value '{}' for group {}", value, code)))
+}
+
+/// Groups of one entity, after its type
+struct Entity<'a>(&'a [(i32, &'a str)]);
+
+impl Entity<'_> {
+    fn values(&self, code: i32) -> impl Iterator<Item = SketchResult<f64>> + '_ {
+        self.0
+            .iter()
+            .filter(move |g| g.0 == code)
+            .map(|&(code, value)| number(code, value))
+    }
+
+    fn get(&self, code: i32) -> SketchResult<f64> {
+        self.values(code)
+            .next()
+            .unwrap_or_else(|| Err(dxf_error(&format!("missing group {}", code))))
+    }
+
+    fn get_or(&self, code: i32, default: f64) -> SketchResult<f64> {
+        self.values(code).next().unwrap_or(Ok(default))
+    }
+
+    fn point(&self, x: i32, y: i32) -> SketchResult<Point2> {
+        Ok(Point2::new(self.get(x)?, self.get(y)?))
+    }
+
+    fn curves(&self, kind: &str) -> SketchResult<Vec<Curve2D>> {
+        let curves = match kind {
+            "LINE" => segment(self.point(10, 20)?, self.point(11, 21)?, 0.0)?
+                .into_iter()
+                .collect(),
+            "ARC" => {
+                let (start, end) = (self.get(50)?.to_radians(), self.get(51)?.to_radians());
+                let sweep = match (end - start).rem_euclid(TAU) {
+                    sweep if sweep < ANGLE_TOLERANCE => TAU,
+                    sweep => sweep,
+                };
+                vec![Arc2D::new(self.point(10, 20)?, self.get(40)?, start, sweep)?.into()]
+            }
+            "CIRCLE" => vec![Circle2D::new(self.point(10, 20)?, self.get(40)?)?.into()],
+            "LWPOLYLINE" => self.polyline()?,
+            "SPLINE" => vec![self.spline()?],
+            _ => Vec::new(),
+        };
+        // Entities with a downward extrusion direction are drawn in an
+        // object coordinate system whose X axis is world −X
+        if self.get_or(230, 1.0)? < 0.0 {
+            return Ok(curves
+                .iter()
+                .map(|c| c.mirrored(Point2::origin(), FRAC_PI_2))
+                .collect());
+        }
+        Ok(curves)
+    }
+
+    /// Straight and bulged segments between consecutive vertices
+    fn polyline(&self) -> SketchResult<Vec<Curve2D>> {
+        // Each vertex starts at its X; Y and bulge follow it
+        let mut vertices: Vec<(Point2, f64)> = Vec::new();
+        for &(code, value) in self.0 {
+            let field = match (code, vertices.last_mut()) {
+                (10, _) => {
+                    vertices.push((Point2::new(number(code, value)?, 0.0), 0.0));
+                    continue;
+                }
+                (20, Some(vertex)) => &mut vertex.0.y,
+                (42, Some(vertex)) => &mut vertex.1,
+                (20 | 42, None) => return Err(dxf_error("LWPOLYLINE group before vertex")),
+                _ => continue,
+            };
+            *field = number(code, value)?;
+        }
+
+        let closed = (self.get_or(70, 0.0)? as i32 & 1) != 0;
+        let count = if closed {
+            vertices.len()
+        } else {
+            vertices.len().saturating_sub(1)
+        };
+        let mut curves = Vec::new();
+        for i in 0..count {
+            let (start, bulge) = vertices[i];
+            let end = vertices[(i + 1) % vertices.len()].0;
+            curves.extend(segment(start, end, bulge)?);
+        }
+        Ok(curves)
+    }
+
+    /// Non-rational B-spline from its knots and control points
+    fn spline(&self) -> SketchResult<Curve2D> {
+        let degree = self.get(71)? as usize;
+        let knots = self.values(40).collect::<SketchResult<Vec<_>>>()?;
+        let points = self
+            .values(10)
+            .zip(self.values(20))
+            .map(|(x, y)| Ok(Point2::new(x?, y?)))
+            .collect::<SketchResult<Vec<_>>>()?;
+        if points.len() <= degree {
+            return Err(SketchError::InsufficientControlPoints {
+                min: degree + 1,
+                degree,
+                got: points.len(),
+            });
+        }
+        let weights = self.values(41).collect::<SketchResult<Vec<_>>>()?;
+        if weights
+            .iter()
+            .any(|w| (w - weights[0]).abs() > LENGTH_TOLERANCE)
+        {
+            return Err(dxf_error("rational SPLINE is not supported"));
+        }
+        let curve = BSplineCurve::try_new(KnotVec::from(knots), points)
+            .map_err(|e| dxf_error(&format!("SPLINE: {}", e)))?;
+        Ok(BSpline2D::from_truck_curve(curve).into())
+    }
+}
+
+/// Line or, for a nonzero `bulge` (tan of a quarter of the CCW sweep), arc
+/// from `start` to `end`; nothing for coincident points
+fn segment(start: Point2, end: Point2, bulge: f64) -> SketchResult<Option<Curve2D>> {
+    let chord = end - start;
+    if chord.magnitude() < LENGTH_TOLERANCE {
+        return Ok(None);
+    }
+    if bulge.abs() < ANGLE_TOLERANCE {
+        return Ok(Some(Line2D::new(start, end)?.into()));
+    }
+    // The arc bulges to the right of the chord when it turns CCW
+    let right = Vector2::new(chord.y, -chord.x);
+    let mid = start + chord / 2.0 + right * (bulge / 2.0);
+    Ok(Some(Arc2D::from_three_points(start, mid, end)?.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::SketchCurve2D;
+    use std::f64::consts::PI;
+
+    /// ENTITIES section holding `entities`, given as group code / value pairs
+    fn dxf(entities: &[(i32, &str)]) -> String {
+        let mut text = String::from("0\nSECTION\n2\nENTITIES\n");
+        for (code, value) in entities {
+            text.push_str(&format!("{}\n{}\n", code, value));
+        }
+        text + "0\nENDSEC\n0\nEOF\n"
+    }
+
+    #[test]
+    fn test_dxf_profiles() {
+        // 20 × 10 slot outline: two lines and two half-circle arcs, one
+        // drawn in a mirrored object coordinate system, around a drilled
+        // hole and a rounded polyline pocket
+        #[rustfmt::skip]
+        let text = dxf(&[
+            (0, "LINE"), (8, "0"), (10, "0"), (20, "0"), (11, "10"), (21, "0"),
+            (0, "LINE"), (10, "10"), (20, "10"), (11, "0"), (21, "10"),
+            (0, "ARC"), (10, "10"), (20, "5"), (40, "5"), (50, "270"), (51, "90"),
+            (0, "ARC"), (10, "0"), (20, "5"), (40, "5"), (50, "270"), (51, "90"),
+            (210, "0"), (220, "0"), (230, "-1"),
+            (0, "CIRCLE"), (10, "10"), (20, "5"), (40, "1"),
+            (0, "LWPOLYLINE"), (90, "2"), (70, "1"),
+            (10, "1"), (20, "5"), (42, "1"), (10, "3"), (20, "5"), (42, "1"),
+            (0, "TEXT"), (10, "0"), (20, "0"), (1, "ignored"),
+        ]);
+
+        let curves = curves(&text).unwrap();
+        assert_eq!(curves.len(), 7);
+        let mirrored = &curves[3];
+        assert!((mirrored.point_at(0.5) - Point2::new(-5.0, 5.0)).magnitude() < 1e-9);
+
+        let (sketches, warnings) = parse_with_warnings(&text).unwrap();
+        assert_eq!(warnings, ["skipped 1 TEXT entity"]);
+        assert_eq!(sketches.len(), 1);
+        assert_eq!(sketches[0].hole_count(), 2);
+        let expected = 10.0 * 10.0 + PI * 25.0 - PI - PI;
+        assert!((sketches[0].area() - expected).abs() < 1e-9);
+
+        #[rustfmt::skip]
+        let spline = dxf(&[
+            (0, "SPLINE"), (70, "8"), (71, "2"), (72, "6"), (73, "3"),
+            (40, "0"), (40, "0"), (40, "0"), (40, "1"), (40, "1"), (40, "1"),
+            (10, "0"), (20, "0"), (10, "1"), (20, "2"), (10, "2"), (20, "0"),
+        ]);
+        let curves = super::curves(&spline).unwrap();
+        let (_, warnings) = parse_with_warnings(&spline).unwrap();
+        assert_eq!(warnings, ["1 curve(s) close no profile and were left out"]);
+        assert!((curves[0].point_at(0.5) - Point2::new(1.0, 1.0)).magnitude() < 1e-9);
+        assert!(matches!(
+            parse("0\nEOF\n"),
+            Err(SketchError::ImportFailed(_))
+        ));
+    }
+}
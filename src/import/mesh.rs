@@ -0,0 +1,76 @@
+use crate::renderer::mesh::GpuMesh;
+use crate::sketch::{SketchError, SketchResult};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use truck_meshalgo::prelude::*;
+
+/// Largest angle between face normals smoothed over where an imported
+/// mesh has no normals of its own
+const CREASE_ANGLE: f64 = std::f64::consts::FRAC_PI_6;
+
+/// Display-only mesh from an `.obj` or `.stl` file, picked by extension
+pub fn read(path: impl AsRef<Path>) -> SketchResult<GpuMesh> {
+    let path = path.as_ref();
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let reader = match extension.to_ascii_lowercase().as_str() {
+        "obj" => read_obj::<File>,
+        "stl" => read_stl::<File>,
+        _ => {
+            return Err(SketchError::ImportFailed(format!(
+                "unsupported mesh format '{}' (expected .obj or .stl)",
+                extension
+            )))
+        }
+    };
+    let file = File::open(path).map_err(|e| {
+        SketchError::ImportFailed(format!("could not read {}: {}", path.display(), e))
+    })?;
+    reader(file)
+}
+
+/// Wavefront OBJ; faces without normals are smoothed up to [`CREASE_ANGLE`]
+pub fn read_obj<R: Read>(reader: R) -> SketchResult<GpuMesh> {
+    let mut mesh =
+        obj::read(reader).map_err(|e| SketchError::ImportFailed(format!("OBJ: {}", e)))?;
+    mesh.add_smooth_normals(CREASE_ANGLE, false);
+    Ok(GpuMesh::from_polygon(&mesh))
+}
+
+/// ASCII or binary STL. Stored facet normals are often zero or flat, so
+/// the shared corners are re-smoothed up to [`CREASE_ANGLE`] instead.
+pub fn read_stl<R: Read>(reader: R) -> SketchResult<GpuMesh> {
+    let mut mesh = stl::read(reader, stl::StlType::Automatic)
+        .map_err(|e| SketchError::ImportFailed(format!("STL: {}", e)))?;
+    mesh.add_smooth_normals(CREASE_ANGLE, true);
+    Ok(GpuMesh::from_polygon(&mesh))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+
+    #[test]
+    fn test_read_obj_and_stl() {
+        let quad = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n";
+        let mesh = read_obj(quad.as_bytes()).unwrap();
+        assert_eq!(mesh.indices.len(), 6);
+        assert_eq!(mesh.vertices.len(), 4);
+        assert!(mesh.vertices.iter().all(|v| v.normal == [0.0, 0.0, 1.0]));
+
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 2.0, 3.0)).unwrap();
+        let polygon = cube.triangulation(0.01).to_polygon();
+        let mut bytes = Vec::new();
+        stl::write(&polygon, &mut bytes, stl::StlType::Binary).unwrap();
+        let mesh = read_stl(bytes.as_slice()).unwrap();
+        assert!((mesh.volume() - 6.0).abs() < 1e-5);
+        // Box corners are 90° creases, so each keeps one normal per face
+        assert_eq!(mesh.vertices.len(), 24);
+
+        assert!(matches!(
+            read("part.igs"),
+            Err(SketchError::ImportFailed(_))
+        ));
+    }
+}
@@ -0,0 +1,77 @@
+pub mod dxf;
+pub mod mesh;
+pub mod step;
+
+use crate::project::PROJECT_EXTENSION;
+use std::path::Path;
+
+/// Kinds of file the app opens, told apart by extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    /// Saved project, replacing what is open
+    Project,
+    /// STEP solids, added as parts
+    Step,
+    /// OBJ or STL triangles, shown as reference meshes
+    Mesh,
+    /// DXF profiles, added as sketches on XY
+    Dxf,
+    /// PNG image, used as the matcap
+    Matcap,
+}
+
+impl FileKind {
+    pub const ALL: [FileKind; 5] = [
+        FileKind::Project,
+        FileKind::Step,
+        FileKind::Mesh,
+        FileKind::Dxf,
+        FileKind::Matcap,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileKind::Project => "Projects",
+            FileKind::Step => "STEP models",
+            FileKind::Mesh => "Meshes",
+            FileKind::Dxf => "DXF drawings",
+            FileKind::Matcap => "Matcap images",
+        }
+    }
+
+    /// Lowercase extensions, without the dot
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            FileKind::Project => &[PROJECT_EXTENSION],
+            FileKind::Step => &["step", "stp"],
+            FileKind::Mesh => &["obj", "stl"],
+            FileKind::Dxf => &["dxf"],
+            FileKind::Matcap => &["png"],
+        }
+    }
+
+    /// Kind of `path` by its extension, in any case
+    pub fn of(path: impl AsRef<Path>) -> Option<FileKind> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.extensions().contains(&extension.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_kind() {
+        assert_eq!(FileKind::of("bracket.STEP"), Some(FileKind::Step));
+        assert_eq!(FileKind::of("/tmp/scan.stl"), Some(FileKind::Mesh));
+        assert_eq!(
+            FileKind::of(format!("model.{}", PROJECT_EXTENSION)),
+            Some(FileKind::Project)
+        );
+        assert_eq!(FileKind::of("notes.txt"), None);
+        assert_eq!(FileKind::of("Makefile"), None);
+    }
+}
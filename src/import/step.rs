@@ -0,0 +1,340 @@
+use crate::sketch::{SketchError, SketchResult};
+use std::f64::consts::FRAC_1_SQRT_2;
+use std::path::Path;
+use truck_geometry::prelude::*;
+use truck_modeling::{Curve, Shell, Solid, Surface};
+use truck_stepio::r#in::{alias as step, Table};
+use truck_topology::compress::{
+    CompressedEdge, CompressedEdgeIndex, CompressedFace, CompressedShell,
+};
+
+/// Distance tolerance for curves truck cannot represent exactly (conics,
+/// curves on surfaces), which are refitted as cubic B-splines
+const FIT_TOLERANCE: f64 = 1e-5;
+const FIT_TRIALS: usize = 20;
+/// Samples per boundary edge when sizing an extruded surface
+const EDGE_SAMPLES: usize = 16;
+
+/// Solids of every closed shell in a STEP file
+pub fn read(path: impl AsRef<Path>) -> SketchResult<Vec<Solid>> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        SketchError::ImportFailed(format!("could not read {}: {}", path.display(), e))
+    })?;
+    parse(&text)
+}
+
+/// Solids of every closed shell in STEP text, in file order
+pub fn parse(text: &str) -> SketchResult<Vec<Solid>> {
+    let table = Table::from_step(text)
+        .ok_or_else(|| SketchError::ImportFailed("not a valid STEP file".to_string()))?;
+    let mut shells: Vec<_> = table.shell.iter().collect();
+    shells.sort_by_key(|(id, _)| **id);
+    if shells.is_empty() {
+        return Err(SketchError::ImportFailed(
+            "no shells in the file".to_string(),
+        ));
+    }
+
+    shells
+        .into_iter()
+        .map(|(id, holder)| {
+            let shell = table
+                .to_compressed_shell(holder)
+                .map_err(|e| SketchError::ImportFailed(format!("shell #{}: {}", id, e)))?;
+            let shell = Shell::extract(convert_shell(shell)?)
+                .map_err(|e| SketchError::ImportFailed(format!("shell #{}: {:?}", id, e)))?;
+            Solid::try_new(vec![shell]).map_err(|e| {
+                SketchError::ImportFailed(format!("shell #{} is not a closed solid: {:?}", id, e))
+            })
+        })
+        .collect()
+}
+
+/// STEP geometry to the curve and surface types of truck-modeling
+fn convert_shell(
+    shell: CompressedShell<Point3, step::Curve3D, step::Surface>,
+) -> SketchResult<CompressedShell<Point3, Curve, Surface>> {
+    let mut vertices = shell.vertices;
+    let mut edges = shell
+        .edges
+        .iter()
+        .map(|edge| {
+            Ok(CompressedEdge {
+                vertices: edge.vertices,
+                curve: curve(&edge.curve)?,
+            })
+        })
+        .collect::<SketchResult<Vec<_>>>()?;
+
+    // truck edges need two distinct vertices: split full circles and other
+    // closed edges in the middle, appending the second halves
+    let mut second_halves = vec![None; edges.len()];
+    for (index, second_half) in second_halves.iter_mut().enumerate() {
+        let (v0, v1) = edges[index].vertices;
+        if v0 != v1 {
+            continue;
+        }
+        let (t0, t1) = edges[index].curve.range_tuple();
+        let back = edges[index].curve.cut((t0 + t1) / 2.0);
+        vertices.push(back.front());
+        edges[index].vertices.1 = vertices.len() - 1;
+        *second_half = Some(edges.len());
+        edges.push(CompressedEdge {
+            vertices: (vertices.len() - 1, v1),
+            curve: back,
+        });
+    }
+    let split = |e: CompressedEdgeIndex| match second_halves[e.index] {
+        None => vec![e],
+        Some(index) if e.orientation => vec![e, (index, true).into()],
+        Some(index) => vec![(index, false).into(), e],
+    };
+
+    let faces = shell
+        .faces
+        .into_iter()
+        .map(|face| {
+            let boundaries: Vec<Vec<CompressedEdgeIndex>> = face
+                .boundaries
+                .into_iter()
+                .map(|wire| wire.into_iter().flat_map(split).collect())
+                .collect();
+            let samples: Vec<Point3> = boundaries
+                .iter()
+                .flatten()
+                .flat_map(|e| sample(&edges[e.index].curve))
+                .collect();
+            Ok(CompressedFace {
+                surface: surface(&face.surface, &samples)?,
+                boundaries,
+                orientation: face.orientation,
+            })
+        })
+        .collect::<SketchResult<Vec<_>>>()?;
+    Ok(CompressedShell {
+        vertices,
+        edges,
+        faces,
+    })
+}
+
+fn curve(curve: &step::Curve3D) -> SketchResult<Curve> {
+    Ok(match curve {
+        step::Curve3D::Line(line) => Curve::Line(*line),
+        step::Curve3D::Polyline(polyline) => {
+            // Point i of a polyline sits at parameter i
+            let n = polyline.len();
+            let mut knots = vec![0.0];
+            knots.extend((0..n).map(|i| i as f64));
+            knots.push((n - 1) as f64);
+            Curve::BSplineCurve(BSplineCurve::new(KnotVec::from(knots), polyline.to_vec()))
+        }
+        step::Curve3D::BSplineCurve(bspline) => Curve::BSplineCurve(bspline.clone()),
+        step::Curve3D::NurbsCurve(nurbs) => Curve::NurbsCurve(nurbs.clone()),
+        step::Curve3D::Conic(_) | step::Curve3D::PCurve(_) => {
+            let fitted = BSplineCurve::cubic_approximation(
+                curve,
+                curve.range_tuple(),
+                FIT_TOLERANCE,
+                FIT_TOLERANCE,
+                FIT_TRIALS,
+            )
+            .ok_or_else(|| SketchError::ImportFailed("could not fit a STEP curve".to_string()))?;
+            Curve::BSplineCurve(fitted)
+        }
+    })
+}
+
+/// `boundary` samples the face's edges, to bound surfaces that are
+/// unbounded in STEP
+fn surface(surface: &step::Surface, boundary: &[Point3]) -> SketchResult<Surface> {
+    Ok(match surface {
+        step::Surface::ElementarySurface(elementary) => match **elementary {
+            step::ElementarySurface::Plane(plane) => Surface::Plane(plane),
+            step::ElementarySurface::CylindricalSurface(cylinder)
+            | step::ElementarySurface::ConicalSurface(cylinder) => {
+                Surface::RevolutedCurve(cylinder.map_ref(|revolved| {
+                    RevolutedCurve::by_revolution(
+                        Curve::Line(*revolved.entity_curve()),
+                        revolved.origin(),
+                        revolved.axis(),
+                    )
+                }))
+            }
+            // Revolve a meridian half circle, pole to pole (normal outward)
+            step::ElementarySurface::Sphere(sphere) => {
+                Surface::RevolutedCurve(sphere.map_ref(|&step::Sphere(sphere)| {
+                    let meridian = quarter_arcs(
+                        sphere.center(),
+                        sphere.radius(),
+                        Vector3::unit_z(),
+                        Vector3::unit_x(),
+                        2,
+                    );
+                    RevolutedCurve::by_revolution(meridian, sphere.center(), Vector3::unit_z())
+                }))
+            }
+            // Revolve the tube circle, starting outward and heading down
+            step::ElementarySurface::ToroidalSurface(torus) => {
+                Surface::RevolutedCurve(torus.map_ref(|torus| {
+                    let tube = quarter_arcs(
+                        torus.center() + Vector3::unit_x() * torus.large_radius(),
+                        torus.small_radius(),
+                        Vector3::unit_x(),
+                        -Vector3::unit_z(),
+                        4,
+                    );
+                    RevolutedCurve::by_revolution(tube, torus.center(), Vector3::unit_z())
+                }))
+            }
+        },
+        step::Surface::SweptCurve(swept) => match &**swept {
+            step::SweptCurve::ExtrudedCurve(extruded) => extrusion(
+                curve(extruded.entity_curve())?,
+                extruded.extruding_vector(),
+                boundary,
+            )?,
+            step::SweptCurve::RevolutedCurve(revolved) => {
+                let profile = curve(revolved.entity().entity_curve())?;
+                Surface::RevolutedCurve(revolved.map_ref(move |revolved| {
+                    RevolutedCurve::by_revolution(profile, revolved.origin(), revolved.axis())
+                }))
+            }
+        },
+        step::Surface::BSplineSurface(bspline) => Surface::BSplineSurface((**bspline).clone()),
+        step::Surface::NurbsSurface(nurbs) => Surface::NurbsSurface((**nurbs).clone()),
+    })
+}
+
+/// Finite patch of the extrusion of `profile` along `vector`, long enough
+/// to contain the `boundary` points
+fn extrusion(profile: Curve, vector: Vector3, boundary: &[Point3]) -> SketchResult<Surface> {
+    let profile = match profile {
+        Curve::Line(line) => NurbsCurve::from(BSplineCurve::new(
+            KnotVec::bezier_knot(1),
+            vec![line.0, line.1],
+        )),
+        Curve::BSplineCurve(bspline) => NurbsCurve::from(bspline),
+        Curve::NurbsCurve(nurbs) => nurbs,
+        Curve::IntersectionCurve(_) => unreachable!("STEP curves are never intersections"),
+    };
+    let length2 = vector.magnitude2();
+    if length2 < TOLERANCE2 {
+        return Err(SketchError::ImportFailed(
+            "zero extrusion vector".to_string(),
+        ));
+    }
+
+    // Point = profile(u) + v·vector, so v is bounded by the spread of both
+    // along the vector; the profile stays within its control polygon
+    let along = |p: Point3| p.to_vec().dot(vector);
+    let (p_min, p_max) = extent(boundary.iter().map(|&p| along(p)));
+    let (c_min, c_max) = extent(
+        profile
+            .non_rationalized()
+            .control_points()
+            .iter()
+            .map(|v| along(v.to_point())),
+    );
+    let margin = 0.01 * (p_max - p_min + c_max - c_min) + TOLERANCE;
+    let (v0, v1) = (
+        (p_min - c_max - margin) / length2,
+        (p_max - c_min + margin) / length2,
+    );
+
+    let side = |v: f64| {
+        profile
+            .transformed(Matrix4::from_translation(vector * v))
+            .into_non_rationalized()
+    };
+    Ok(Surface::NurbsSurface(NurbsSurface::new(
+        BSplineSurface::homotopy(side(v0), side(v1)),
+    )))
+}
+
+fn extent(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), x| {
+        (lo.min(x), hi.max(x))
+    })
+}
+
+fn sample(curve: &Curve) -> Vec<Point3> {
+    let (t0, t1) = curve.range_tuple();
+    (0..=EDGE_SAMPLES)
+        .map(|i| curve.subs(t0 + (t1 - t0) * i as f64 / EDGE_SAMPLES as f64))
+        .collect()
+}
+
+/// Exact NURBS of `quarters` quarter circles starting at `center + radius·a`
+/// and heading towards `b`
+fn quarter_arcs(center: Point3, radius: f64, a: Vector3, b: Vector3, quarters: usize) -> Curve {
+    let direction = |k: usize| match k % 4 {
+        0 => a,
+        1 => b,
+        2 => -a,
+        _ => -b,
+    };
+    let mut points = vec![(center + direction(0) * radius).to_homogeneous()];
+    let mut knots = vec![0.0; 3];
+    for k in 0..quarters {
+        let corner = center + (direction(k) + direction(k + 1)) * radius;
+        points.push(corner.to_homogeneous() * FRAC_1_SQRT_2);
+        points.push((center + direction(k + 1) * radius).to_homogeneous());
+        knots.extend([k as f64 + 1.0; 2]);
+    }
+    knots.push(quarters as f64);
+    Curve::NurbsCurve(NurbsCurve::new(BSplineCurve::new(
+        KnotVec::from(knots),
+        points,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{box_solid, Assembly};
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::{Plane, Shapes, Sketch};
+
+    #[test]
+    fn test_round_trip_through_step() {
+        let mut assembly = Assembly::new();
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 2.0, 3.0)).unwrap();
+        assembly.add("cube", cube, Matrix4::identity()).unwrap();
+        let disc = Sketch::new(Shapes::circle(Point2::new(5.0, 0.0), 1.0).unwrap());
+        let rod = disc.extrude(Plane::xy(), Vector3::unit_z() * 4.0).unwrap();
+        assembly.add("rod", rod, Matrix4::identity()).unwrap();
+
+        let solids = parse(&assembly.to_step(&Default::default())).unwrap();
+        assert_eq!(solids.len(), 2);
+        let mut volumes: Vec<f64> = solids
+            .iter()
+            .map(|s| GpuMesh::from_solid(s, 0.001).volume())
+            .collect();
+        volumes.sort_by(f64::total_cmp);
+        assert!((volumes[0] - 6.0).abs() < 1e-6);
+        assert!((volumes[1] - 4.0 * std::f64::consts::PI).abs() < 0.01);
+
+        assert!(matches!(
+            parse("not step"),
+            Err(SketchError::ImportFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_quarter_arcs_stay_on_circle() {
+        let circle = quarter_arcs(
+            Point3::new(1.0, 2.0, 3.0),
+            2.0,
+            Vector3::unit_x(),
+            -Vector3::unit_z(),
+            4,
+        );
+        for i in 0..=40 {
+            let p = circle.subs(4.0 * i as f64 / 40.0);
+            assert!(((p - Point3::new(1.0, 2.0, 3.0)).magnitude() - 2.0).abs() < 1e-12);
+        }
+        assert!((circle.der(0.0).normalize() + Vector3::unit_z()).magnitude() < 1e-12);
+    }
+}
@@ -1,9 +1,17 @@
 pub mod app;
+pub mod appearance;
+pub mod export;
 pub mod geometry;
+pub mod headless;
+pub mod import;
+pub mod model;
+pub mod project;
 pub mod renderer;
+pub mod script;
 pub mod sketch;
+pub mod units;
 
 pub use sketch::{
-    Arc2D, BSpline2D, Circle2D, Curve2D, Line2D, Loop2D, Plane, Shapes, Sketch, SketchBuilder,
-    SketchCurve2D, SketchError, SketchResult,
+    Arc2D, BSpline2D, Circle2D, Curve2D, Line2D, Loop2D, ParamSet, ParamSketch, Plane, Shapes,
+    Sketch, SketchBuilder, SketchCurve2D, SketchError, SketchResult, StandardPlane,
 };
@@ -1,9 +1,28 @@
-mod app;
-mod geometry;
-mod renderer;
-pub mod sketch;
+use truck_playground::app;
+use truck_playground::headless::{run_headless, HeadlessArgs, USAGE};
 
 fn main() -> eframe::Result<()> {
+    // `--headless` runs a script and exports without opening a window
+    match HeadlessArgs::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => match run_headless(&args) {
+            Ok(written) => {
+                for path in written {
+                    println!("{}", path.display());
+                }
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1024.0, 768.0])
@@ -0,0 +1,108 @@
+use crate::renderer::mesh::GpuMesh;
+use std::collections::HashSet;
+use truck_geometry::prelude::*;
+use truck_modeling::Solid;
+
+/// Chord tolerance of the mesh properties are measured on, relative to
+/// the body's size
+const RELATIVE_TOLERANCE: f64 = 1e-4;
+
+/// Points sampled per edge to size the body before meshing it
+const EDGE_SAMPLES: usize = 16;
+
+/// Mass properties and topology of one solid, measured on a fine mesh, so
+/// curved bodies come out slightly small
+#[derive(Clone, Debug, PartialEq)]
+pub struct BodyProperties {
+    pub volume: f64,
+    pub area: f64,
+    /// Centre of mass at uniform density
+    pub centroid: Point3,
+    pub bounds: BoundingBox<Point3>,
+    pub faces: usize,
+    pub edges: usize,
+    pub vertices: usize,
+}
+
+impl BodyProperties {
+    pub fn new(solid: &Solid) -> Self {
+        let size: BoundingBox<Point3> = solid
+            .edge_iter()
+            .flat_map(|edge| {
+                let curve = edge.oriented_curve();
+                let (t0, t1) = curve.range_tuple();
+                (0..=EDGE_SAMPLES)
+                    .map(move |i| curve.subs(t0 + (t1 - t0) * i as f64 / EDGE_SAMPLES as f64))
+            })
+            .collect();
+        let tolerance = (size.diameter() * RELATIVE_TOLERANCE).max(1e-6);
+        let mesh = GpuMesh::from_solid(solid, tolerance);
+
+        let point = |i: u32| to_vector(mesh.vertices[i as usize].position);
+        let (mut volume, mut area, mut moment) = (0.0, 0.0, Vector3::zero());
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| point(triangle[k]));
+            // Signed tetrahedron from the origin, and its centre
+            let tetrahedron = a.dot(b.cross(c)) / 6.0;
+            volume += tetrahedron;
+            moment += (a + b + c) * (tetrahedron / 4.0);
+            area += (b - a).cross(c - a).magnitude() / 2.0;
+        }
+        let centroid = if volume.abs() > f64::EPSILON {
+            Point3::from_vec(moment / volume)
+        } else {
+            Point3::origin()
+        };
+
+        Self {
+            volume,
+            area,
+            centroid,
+            bounds: mesh
+                .vertices
+                .iter()
+                .map(|v| Point3::from_vec(to_vector(v.position)))
+                .collect(),
+            faces: solid.face_iter().count(),
+            // Edges and vertices are shared, so count each once
+            edges: solid
+                .edge_iter()
+                .map(|e| e.id())
+                .collect::<HashSet<_>>()
+                .len(),
+            vertices: solid
+                .vertex_iter()
+                .map(|v| v.id())
+                .collect::<HashSet<_>>()
+                .len(),
+        }
+    }
+}
+
+fn to_vector([x, y, z]: [f32; 3]) -> Vector3 {
+    Vector3::new(x as f64, y as f64, z as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{box_solid, cylinder};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_body_properties() {
+        let block = box_solid(Point3::new(1.0, 2.0, 3.0), Vector3::new(2.0, 4.0, 6.0)).unwrap();
+        let props = BodyProperties::new(&block);
+        assert!((props.volume - 48.0).abs() < 1e-6);
+        assert!((props.area - 88.0).abs() < 1e-6);
+        assert!((props.centroid - Point3::new(2.0, 4.0, 6.0)).magnitude() < 1e-6);
+        assert_eq!(props.bounds.max(), Point3::new(3.0, 6.0, 9.0));
+        assert_eq!((props.faces, props.edges, props.vertices), (6, 12, 8));
+
+        let rod = cylinder(Point3::origin(), Vector3::unit_z() * 4.0, 1.0).unwrap();
+        let props = BodyProperties::new(&rod);
+        assert!((props.volume - 4.0 * PI).abs() < 1e-2, "{}", props.volume);
+        assert!((props.area - 10.0 * PI).abs() < 1e-2, "{}", props.area);
+        assert!((props.centroid.z - 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::export::step::{self, StepExportOptions};
+use crate::model::Datums;
+use crate::sketch::constants::*;
+use crate::sketch::{SketchError, SketchResult};
+use serde::{Deserialize, Serialize};
+use truck_geometry::prelude::*;
+use truck_modeling::{builder as truck_builder, Curve, Solid, Surface};
+use truck_topology::compress::CompressedSolid;
+
+/// A named solid placed in an assembly
+///
+/// Serializes the solid as truck's exact compressed B-rep.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(into = "PartDef", try_from = "PartDef")]
+pub struct Part {
+    /// Unique in its assembly, so only the assembly may change it
+    name: String,
+    /// Geometry in the part's own coordinates
+    pub solid: Solid,
+    /// Rigid placement of the part in assembly coordinates
+    pub transform: Matrix4,
+    /// Shown in the viewport; hidden parts still export
+    pub visible: bool,
+}
+
+impl Part {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The part's solid moved to its place in the assembly
+    pub fn placed_solid(&self) -> Solid {
+        truck_builder::transformed(&self.solid, self.transform)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PartDef {
+    name: String,
+    solid: CompressedSolid<Point3, Curve, Surface>,
+    transform: Matrix4,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    hidden: bool,
+}
+
+impl From<Part> for PartDef {
+    fn from(part: Part) -> Self {
+        Self {
+            name: part.name,
+            solid: part.solid.compress(),
+            transform: part.transform,
+            hidden: !part.visible,
+        }
+    }
+}
+
+impl TryFrom<PartDef> for Part {
+    type Error = SketchError;
+
+    fn try_from(def: PartDef) -> SketchResult<Self> {
+        let solid = Solid::extract(def.solid)
+            .map_err(|e| SketchError::TruckSolidError(format!("{:?}", e)))?;
+        check_rigid(&def.transform)?;
+        Ok(Self {
+            name: def.name,
+            solid,
+            transform: def.transform,
+            visible: !def.hidden,
+        })
+    }
+}
+
+/// Named solids with rigid placements, shared by the viewer and exporters
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(try_from = "AssemblyDef")]
+pub struct Assembly {
+    parts: Vec<Part>,
+    /// Reference frames parts can be placed at by name
+    pub datums: Datums,
+}
+
+#[derive(Deserialize)]
+struct AssemblyDef {
+    parts: Vec<Part>,
+    datums: Datums,
+}
+
+impl TryFrom<AssemblyDef> for Assembly {
+    type Error = SketchError;
+
+    fn try_from(def: AssemblyDef) -> SketchResult<Self> {
+        let mut assembly = Self {
+            parts: Vec::with_capacity(def.parts.len()),
+            datums: def.datums,
+        };
+        for part in def.parts {
+            if assembly.get(&part.name).is_some() {
+                return Err(SketchError::InvalidFeature(format!(
+                    "assembly already has a part named '{}'",
+                    part.name
+                )));
+            }
+            assembly.parts.push(part);
+        }
+        Ok(assembly)
+    }
+}
+
+impl Assembly {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a part; names must be unique and the transform rigid (rotation
+    /// plus translation)
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        solid: Solid,
+        transform: Matrix4,
+    ) -> SketchResult<&mut Part> {
+        let name = name.into();
+        if self.get(&name).is_some() {
+            return Err(SketchError::InvalidFeature(format!(
+                "assembly already has a part named '{}'",
+                name
+            )));
+        }
+        check_rigid(&transform)?;
+        self.parts.push(Part {
+            name,
+            solid,
+            transform,
+            visible: true,
+        });
+        Ok(self.parts.last_mut().unwrap())
+    }
+
+    /// Add a part placed at the registered datum frame `datum`
+    pub fn add_at(
+        &mut self,
+        name: impl Into<String>,
+        solid: Solid,
+        datum: &str,
+    ) -> SketchResult<&mut Part> {
+        let transform = self.datums.get(datum)?.to_matrix();
+        self.add(name, solid, transform)
+    }
+
+    /// Move an existing part
+    pub fn set_transform(&mut self, name: &str, transform: Matrix4) -> SketchResult<()> {
+        check_rigid(&transform)?;
+        self.part_mut(name)?.transform = transform;
+        Ok(())
+    }
+
+    /// Give a part new geometry, keeping its name and placement
+    pub fn set_solid(&mut self, name: &str, solid: Solid) -> SketchResult<()> {
+        self.part_mut(name)?.solid = solid;
+        Ok(())
+    }
+
+    pub fn set_visible(&mut self, name: &str, visible: bool) -> SketchResult<()> {
+        self.part_mut(name)?.visible = visible;
+        Ok(())
+    }
+
+    /// Give a part a new name, which must be unique too
+    pub fn rename(&mut self, name: &str, new_name: impl Into<String>) -> SketchResult<()> {
+        let new_name = new_name.into();
+        if new_name != name && self.get(&new_name).is_some() {
+            return Err(SketchError::InvalidFeature(format!(
+                "assembly already has a part named '{}'",
+                new_name
+            )));
+        }
+        self.part_mut(name)?.name = new_name;
+        Ok(())
+    }
+
+    /// Move a part to `index` in the part order, shifting the ones between
+    pub fn reorder(&mut self, name: &str, index: usize) -> SketchResult<()> {
+        let from = self.position(name)?;
+        let part = self.parts.remove(from);
+        self.parts.insert(index.min(self.parts.len()), part);
+        Ok(())
+    }
+
+    fn position(&self, name: &str) -> SketchResult<usize> {
+        self.parts
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| SketchError::InvalidFeature(format!("no part named '{}'", name)))
+    }
+
+    fn part_mut(&mut self, name: &str) -> SketchResult<&mut Part> {
+        let index = self.position(name)?;
+        Ok(&mut self.parts[index])
+    }
+
+    /// Remove a part, returning it
+    pub fn remove(&mut self, name: &str) -> Option<Part> {
+        let index = self.parts.iter().position(|p| p.name == name)?;
+        Some(self.parts.remove(index))
+    }
+
+    /// `base`, or `base` numbered from 2 up when a part has that name
+    pub fn unique_name(&self, base: &str) -> String {
+        std::iter::once(base.to_string())
+            .chain((2..).map(|i| format!("{} {}", base, i)))
+            .find(|name| self.get(name).is_none())
+            .expect("some numbered name is free")
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Part> {
+        self.parts.iter().find(|p| p.name == name)
+    }
+
+    /// Parts in insertion order
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// Every part's solid in assembly coordinates
+    pub fn placed_solids(&self) -> Vec<Solid> {
+        self.parts.iter().map(Part::placed_solid).collect()
+    }
+
+    /// STEP file with every part as a named product under the assembly
+    /// product; solids are written placed, in assembly coordinates
+    pub fn to_step(&self, options: &StepExportOptions) -> String {
+        let parts: Vec<(&str, Solid)> = self
+            .parts
+            .iter()
+            .map(|part| (part.name(), part.placed_solid()))
+            .collect();
+        step::export_step_assembly(&parts, options)
+    }
+}
+
+fn check_rigid(transform: &Matrix4) -> SketchResult<()> {
+    let rotation = Matrix3::from_cols(
+        transform.x.truncate(),
+        transform.y.truncate(),
+        transform.z.truncate(),
+    );
+    let gram = rotation.transpose() * rotation;
+    let orthonormal =
+        (0..3).all(|i| (gram[i] - Matrix3::identity()[i]).magnitude() < ANGLE_TOLERANCE);
+    let affine = transform.row(3) == Vector4::unit_w();
+    if orthonormal && affine && rotation.determinant() > 0.0 {
+        Ok(())
+    } else {
+        Err(SketchError::InvalidFeature(
+            "part placements must be rotations plus translations".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+
+    #[test]
+    fn test_assembly_parts_and_step() {
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly
+            .add("base", cube.clone(), Matrix4::identity())
+            .unwrap();
+        let lid = Matrix4::from_translation(Vector3::new(0.0, 0.0, 2.0))
+            * Matrix4::from_angle_z(Rad(std::f64::consts::FRAC_PI_2));
+        assembly.add("lid", cube.clone(), lid).unwrap();
+
+        assert!(assembly.add("lid", cube.clone(), lid).is_err());
+        assert!(assembly
+            .add("stretched", cube.clone(), Matrix4::from_scale(2.0))
+            .is_err());
+
+        let placed = assembly.get("lid").unwrap().placed_solid();
+        let zs: Vec<f64> = placed.vertex_iter().map(|v| v.point().z).collect();
+        assert!(zs.iter().all(|&z| (2.0 - 1e-9..=3.0 + 1e-9).contains(&z)));
+
+        let step = assembly.to_step(&StepExportOptions::default());
+        assert_eq!(step.matches("MANIFOLD_SOLID_BREP").count(), 2);
+        // Each part is its own product used once by the assembly product
+        assert!(step.contains("PRODUCT('base','base',''"));
+        assert!(step.contains("PRODUCT('lid','lid',''"));
+        assert!(step.contains("NEXT_ASSEMBLY_USAGE_OCCURRENCE('2','lid','', #5,"));
+        assert_eq!(
+            step.matches("CONTEXT_DEPENDENT_SHAPE_REPRESENTATION(")
+                .count(),
+            2
+        );
+        assert!(step.contains("#10 = SHAPE_REPRESENTATION("));
+
+        let tall = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 3.0)).unwrap();
+        assembly.set_solid("lid", tall).unwrap();
+        assert_eq!(assembly.get("lid").unwrap().transform, lid);
+        assert!(assembly.set_solid("missing", cube.clone()).is_err());
+
+        assert!(assembly.remove("base").is_some());
+        assert_eq!(assembly.len(), 1);
+    }
+
+    #[test]
+    fn test_rename_reorder_and_hide() {
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let mut assembly = Assembly::new();
+        for name in ["a", "b", "c"] {
+            assembly
+                .add(name, cube.clone(), Matrix4::identity())
+                .unwrap();
+        }
+        assert!(assembly.rename("a", "b").is_err());
+        assembly.rename("a", "first").unwrap();
+        assembly.reorder("first", 2).unwrap();
+        assembly.reorder("c", 0).unwrap();
+        let names: Vec<&str> = assembly.parts().iter().map(Part::name).collect();
+        assert_eq!(names, ["c", "b", "first"]);
+        assert!(assembly.reorder("missing", 0).is_err());
+        assert_eq!(assembly.unique_name("a"), "a");
+        assert_eq!(assembly.unique_name("b"), "b 2");
+
+        // Hidden parts stay hidden through a save
+        assembly.set_visible("b", false).unwrap();
+        let json = serde_json::to_string(&assembly).unwrap();
+        let loaded: Assembly = serde_json::from_str(&json).unwrap();
+        let visible: Vec<bool> = loaded.parts().iter().map(|p| p.visible).collect();
+        assert_eq!(visible, [true, false, true]);
+
+        // Names stay unique in files edited by hand
+        let duplicated = json.replace("\"name\":\"c\"", "\"name\":\"b\"");
+        assert_ne!(duplicated, json);
+        assert!(serde_json::from_str::<Assembly>(&duplicated).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::sketch::{Plane, SketchError, SketchResult};
+use serde::{Deserialize, Serialize};
+use truck_geometry::prelude::*;
+
+/// Right-handed orthonormal reference frame (Z is `X × Y`)
+///
+/// Serializes as its XY [`Plane`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(into = "Plane", try_from = "Plane")]
+pub struct Csys {
+    origin: Point3,
+    x_dir: Vector3,
+    y_dir: Vector3,
+}
+
+impl Csys {
+    /// The world frame
+    pub fn world() -> Self {
+        Self {
+            origin: Point3::origin(),
+            x_dir: Vector3::unit_x(),
+            y_dir: Vector3::unit_y(),
+        }
+    }
+
+    /// Frame at `origin` with X along `x_dir`; `y_dir` only needs to lie
+    /// in the XY plane on the +Y side and is squared up against X
+    pub fn new(origin: Point3, x_dir: Vector3, y_dir: Vector3) -> SketchResult<Self> {
+        Self::from_plane(&Plane::new(origin, x_dir, y_dir)?)
+    }
+
+    /// Frame whose XY plane is `plane`
+    pub fn from_plane(plane: &Plane) -> SketchResult<Self> {
+        if !plane.is_orthonormal() {
+            return Err(SketchError::NonOrthonormalPlane);
+        }
+        Ok(Self {
+            origin: plane.origin(),
+            x_dir: plane.x_dir(),
+            y_dir: plane.y_dir(),
+        })
+    }
+
+    pub fn origin(&self) -> Point3 {
+        self.origin
+    }
+    pub fn x_dir(&self) -> Vector3 {
+        self.x_dir
+    }
+    pub fn y_dir(&self) -> Vector3 {
+        self.y_dir
+    }
+    pub fn z_dir(&self) -> Vector3 {
+        self.x_dir.cross(self.y_dir)
+    }
+
+    /// Local XY plane (normal +Z)
+    pub fn xy_plane(&self) -> Plane {
+        self.plane(self.x_dir, self.y_dir)
+    }
+
+    /// Local XZ plane (normal −Y), like [`Plane::xz`]
+    pub fn xz_plane(&self) -> Plane {
+        self.plane(self.x_dir, self.z_dir())
+    }
+
+    /// Local YZ plane (normal +X), like [`Plane::yz`]
+    pub fn yz_plane(&self) -> Plane {
+        self.plane(self.y_dir, self.z_dir())
+    }
+
+    fn plane(&self, x_dir: Vector3, y_dir: Vector3) -> Plane {
+        Plane::new(self.origin, x_dir, y_dir).expect("frame axes are orthonormal")
+    }
+
+    /// Local-to-world rigid transform, usable as a part placement
+    pub fn to_matrix(&self) -> Matrix4 {
+        Matrix4::from_cols(
+            self.x_dir.extend(0.0),
+            self.y_dir.extend(0.0),
+            self.z_dir().extend(0.0),
+            self.origin.to_homogeneous(),
+        )
+    }
+
+    /// Point given in local coordinates, in world coordinates
+    pub fn to_world(&self, local: Point3) -> Point3 {
+        self.origin + self.x_dir * local.x + self.y_dir * local.y + self.z_dir() * local.z
+    }
+
+    /// Child frame: `offset` in local coordinates, then turned `angle`
+    /// about the local Z axis
+    pub fn child(&self, offset: Vector3, angle: Rad<f64>) -> Self {
+        let (sin, cos) = angle.0.sin_cos();
+        Self {
+            origin: self.to_world(Point3::from_vec(offset)),
+            x_dir: self.x_dir * cos + self.y_dir * sin,
+            y_dir: self.y_dir * cos - self.x_dir * sin,
+        }
+    }
+}
+
+impl From<Csys> for Plane {
+    fn from(csys: Csys) -> Self {
+        csys.xy_plane()
+    }
+}
+
+impl TryFrom<Plane> for Csys {
+    type Error = SketchError;
+
+    fn try_from(plane: Plane) -> SketchResult<Self> {
+        Self::from_plane(&plane)
+    }
+}
+
+impl From<&Csys> for Plane {
+    fn from(csys: &Csys) -> Self {
+        csys.xy_plane()
+    }
+}
+
+/// Named reference frames shared by the sketches and parts of a model
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Datums {
+    frames: Vec<(String, Csys)>,
+}
+
+impl Datums {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a frame under a new name
+    pub fn register(&mut self, name: impl Into<String>, csys: Csys) -> SketchResult<()> {
+        let name = name.into();
+        if self.frames.iter().any(|(n, _)| *n == name) {
+            return Err(SketchError::InvalidFeature(format!(
+                "datum '{}' is already registered",
+                name
+            )));
+        }
+        self.frames.push((name, csys));
+        Ok(())
+    }
+
+    /// Replace a registered frame; everything looking it up by name follows
+    pub fn redefine(&mut self, name: &str, csys: Csys) -> SketchResult<()> {
+        let frame = self
+            .frames
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .ok_or_else(|| SketchError::UnknownDatum(name.to_string()))?;
+        frame.1 = csys;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> SketchResult<Csys> {
+        self.frames
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, csys)| *csys)
+            .ok_or_else(|| SketchError::UnknownDatum(name.to_string()))
+    }
+
+    /// XY plane of a registered frame, for sketching on
+    pub fn plane(&self, name: &str) -> SketchResult<Plane> {
+        Ok(self.get(name)?.xy_plane())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Csys> {
+        let index = self.frames.iter().position(|(n, _)| n == name)?;
+        Some(self.frames.remove(index).1)
+    }
+
+    /// Names in registration order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.frames.iter().map(|(n, _)| n.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{box_solid, Assembly};
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_datums_drive_planes_and_placements() {
+        let mut datums = Datums::new();
+        let flange = Csys::world().child(Vector3::new(0.0, 0.0, 10.0), Rad(FRAC_PI_2));
+        datums.register("flange", flange).unwrap();
+        assert!(datums.register("flange", Csys::world()).is_err());
+
+        // Sketch X runs along world Y on the turned frame
+        let plane = datums.plane("flange").unwrap();
+        let p = plane.lift_point(Point2::new(1.0, 0.0));
+        assert!((p - Point3::new(0.0, 1.0, 10.0)).magnitude() < 1e-12);
+        let m = flange.to_matrix();
+        assert!((m.transform_point(Point3::new(1.0, 0.0, 0.0)) - p).magnitude() < 1e-12);
+
+        let skewed = Csys::new(
+            Point3::origin(),
+            Vector3::unit_x(),
+            Vector3::new(1.0, 1.0, 0.0),
+        );
+        assert_eq!(skewed.unwrap().y_dir(), Vector3::unit_y());
+
+        let mut assembly = Assembly::new();
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        assembly.datums = datums.clone();
+        let part = assembly.add_at("cap", cube, "flange").unwrap();
+        assert!(part
+            .placed_solid()
+            .vertex_iter()
+            .all(|v| v.point().z >= 10.0 - 1e-12));
+
+        let json = serde_json::to_string(&flange).unwrap();
+        assert_eq!(serde_json::from_str::<Csys>(&json).unwrap(), flange);
+
+        datums.redefine("flange", Csys::world()).unwrap();
+        assert!(matches!(
+            datums.get("missing"),
+            Err(SketchError::UnknownDatum(_))
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::project::PlacedSketch;
+use crate::sketch::{SketchError, SketchResult};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use truck_geometry::prelude::*;
+use truck_modeling::Solid;
+
+/// The step a part was made by
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Feature {
+    /// The named sketch pushed along `direction`
+    Extrude { sketch: String, direction: Vector3 },
+    /// Added by a console script
+    Script,
+    /// Read from a STEP file
+    Import { path: PathBuf },
+}
+
+impl Feature {
+    /// Short description for the model tree
+    pub fn describe(&self) -> String {
+        match self {
+            Feature::Extrude { sketch, direction } => {
+                format!("Extrude of {} by {:.3}", sketch, direction.magnitude())
+            }
+            Feature::Script => "Added by a script".to_string(),
+            Feature::Import { path } => format!("Imported from {}", path.display()),
+        }
+    }
+}
+
+/// A feature and the part it made
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeatureRecord {
+    pub part: String,
+    pub feature: Feature,
+}
+
+/// Features in the order they were applied, kept with the project so parts
+/// can be traced back to their sketches and rebuilt from them
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    features: Vec<FeatureRecord>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, part: impl Into<String>, feature: Feature) {
+        self.features.push(FeatureRecord {
+            part: part.into(),
+            feature,
+        });
+    }
+
+    pub fn features(&self) -> &[FeatureRecord] {
+        &self.features
+    }
+
+    /// Latest feature that made `part`
+    pub fn feature_of(&self, part: &str) -> Option<&Feature> {
+        self.features
+            .iter()
+            .rev()
+            .find(|record| record.part == part)
+            .map(|record| &record.feature)
+    }
+
+    /// Parts whose latest feature extrudes the sketch named `sketch`, in the
+    /// order they were made
+    pub fn parts_from_sketch(&self, sketch: &str) -> Vec<String> {
+        let mut parts: Vec<String> = Vec::new();
+        for record in &self.features {
+            let extruded = matches!(
+                self.feature_of(&record.part),
+                Some(Feature::Extrude { sketch: s, .. }) if s == sketch
+            );
+            if extruded && !parts.contains(&record.part) {
+                parts.push(record.part.clone());
+            }
+        }
+        parts
+    }
+
+    /// Follow a part rename
+    pub fn rename(&mut self, part: &str, new_name: &str) {
+        for record in self.features.iter_mut().filter(|r| r.part == part) {
+            record.part = new_name.to_string();
+        }
+    }
+
+    /// Forget the features of a deleted part
+    pub fn remove(&mut self, part: &str) {
+        self.features.retain(|record| record.part != part);
+    }
+
+    /// Solid of `part` made again from its recorded feature; only extrusions
+    /// of sketches still in `sketches` can be rebuilt
+    pub fn rebuild(&self, part: &str, sketches: &[PlacedSketch]) -> SketchResult<Solid> {
+        let missing = |what: String| SketchError::InvalidFeature(what);
+        match self.feature_of(part) {
+            Some(Feature::Extrude { sketch, direction }) => {
+                let placed = sketches
+                    .iter()
+                    .find(|placed| &placed.name == sketch)
+                    .ok_or_else(|| missing(format!("sketch '{}' no longer exists", sketch)))?;
+                placed.sketch.extrude(&placed.plane, *direction)
+            }
+            Some(feature) => Err(missing(format!(
+                "'{}' cannot be rebuilt: {}",
+                part,
+                feature.describe()
+            ))),
+            None => Err(missing(format!("no feature recorded for '{}'", part))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::{Plane, Shapes, Sketch};
+
+    #[test]
+    fn test_rebuild_follows_renames() {
+        let square = Shapes::rectangle(Point2::origin(), 2.0, 3.0).unwrap();
+        let sketches = vec![PlacedSketch {
+            name: "base".to_string(),
+            plane: Plane::xy(),
+            sketch: Sketch::new(square),
+        }];
+        let mut history = History::new();
+        let direction = Vector3::unit_z() * 4.0;
+        history.record(
+            "block",
+            Feature::Extrude {
+                sketch: "base".to_string(),
+                direction,
+            },
+        );
+        history.record("bolt", Feature::Script);
+        history.record(
+            "rib",
+            Feature::Extrude {
+                sketch: "base".to_string(),
+                direction,
+            },
+        );
+        history.record("rib", Feature::Script);
+        assert_eq!(history.parts_from_sketch("base"), ["block"]);
+
+        history.rename("block", "plate");
+        assert!(history.feature_of("block").is_none());
+        let solid = history.rebuild("plate", &sketches).unwrap();
+        let volume = GpuMesh::from_solid(&solid, 0.001).volume();
+        assert!((volume - 24.0).abs() < 1e-6);
+
+        assert!(history.rebuild("bolt", &sketches).is_err());
+        assert!(history.rebuild("plate", &[]).is_err());
+        history.remove("plate");
+        assert_eq!(history.features().len(), 3);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::sketch::{Loft, Plane, Shapes, Sketch, SketchError, SketchResult};
+use truck_geometry::prelude::*;
+use truck_modeling::Solid;
+
+/// Height of the tool above the sketch plane, relative to the hole depth,
+/// so it clears a coplanar face
+const TOOL_CLEARANCE: f64 = 0.01;
+
+/// Shape of a hole's entry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HoleKind {
+    /// Straight bore
+    Simple,
+    /// Flat-bottomed recess for a bolt head
+    Counterbore { diameter: f64, depth: f64 },
+    /// Conical recess for a flat-head screw; `angle` is the included angle
+    Countersink { diameter: f64, angle: Rad<f64> },
+    /// Threaded hole, cut at the tap drill size (nominal diameter minus
+    /// `pitch`); threads themselves are not modeled
+    Tapped { pitch: f64 },
+}
+
+/// Hole specification that can be drilled at many points
+///
+/// Holes are drilled into the solid against the plane normal, so the plane
+/// should lie on the entry face with its normal pointing out of the solid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HoleFeature {
+    pub kind: HoleKind,
+    pub diameter: f64,
+    pub depth: f64,
+}
+
+impl HoleFeature {
+    /// Plain hole of `diameter` drilled `depth` deep
+    pub fn new(diameter: f64, depth: f64) -> Self {
+        Self {
+            kind: HoleKind::Simple,
+            diameter,
+            depth,
+        }
+    }
+
+    /// Add a counterbore
+    pub fn counterbore(mut self, diameter: f64, depth: f64) -> Self {
+        self.kind = HoleKind::Counterbore { diameter, depth };
+        self
+    }
+
+    /// Add a countersink with the given included angle
+    pub fn countersink(mut self, diameter: f64, angle: Rad<f64>) -> Self {
+        self.kind = HoleKind::Countersink { diameter, angle };
+        self
+    }
+
+    /// Make this a tapped hole with thread `pitch`
+    pub fn tapped(mut self, pitch: f64) -> Self {
+        self.kind = HoleKind::Tapped { pitch };
+        self
+    }
+
+    /// Diameter actually cut for the bore
+    pub fn bore_diameter(&self) -> f64 {
+        match self.kind {
+            HoleKind::Tapped { pitch } => self.diameter - pitch,
+            _ => self.diameter,
+        }
+    }
+
+    /// Drill the hole at each of `points` on `plane`
+    ///
+    /// `solid` is left untouched if any cut fails.
+    pub fn apply(
+        &self,
+        solid: &mut Solid,
+        plane: impl Into<Plane>,
+        points: &[Point2],
+    ) -> SketchResult<()> {
+        let plane = &plane.into();
+        let mut result = solid.clone();
+        for &p in points {
+            for tool in self.tools(plane, p)? {
+                result = crate::geometry::cut(&result, &tool)?;
+            }
+        }
+        *solid = result;
+        Ok(())
+    }
+
+    /// Cutting tools for one hole: the entry recess, then the bore
+    ///
+    /// Every tool starts a little above the plane so it clears a coplanar
+    /// face. The bore is cut last so it pierces the recess floor.
+    fn tools(&self, plane: &Plane, at: Point2) -> SketchResult<Vec<Solid>> {
+        let r = self.bore_diameter() / 2.0;
+        positive("diameter", r)?;
+        positive("depth", self.depth)?;
+        let clearance = self.depth * TOOL_CLEARANCE;
+        let circle = |radius: f64| Shapes::circle(at, radius).map(Sketch::new);
+        let offset = |height: f64| {
+            Plane::new(
+                plane.origin() + plane.normal() * height,
+                plane.x_dir(),
+                plane.y_dir(),
+            )
+        };
+
+        let mut tools = Vec::new();
+        match self.kind {
+            HoleKind::Simple | HoleKind::Tapped { .. } => {}
+            HoleKind::Counterbore { diameter, depth } => {
+                let cr = diameter / 2.0;
+                if cr <= r || depth <= 0.0 || depth >= self.depth {
+                    return Err(SketchError::InvalidFeature(format!(
+                        "counterbore {}x{} does not fit a {}x{} hole",
+                        diameter, depth, self.diameter, self.depth
+                    )));
+                }
+                tools.push(circle(cr)?.extrude_two_sided(plane, clearance, depth)?);
+            }
+            HoleKind::Countersink { diameter, angle } => {
+                let cr = diameter / 2.0;
+                let slope = (angle.0 / 2.0).tan();
+                let sink = (cr - r) / slope;
+                if cr <= r || !(slope > 0.0 && sink < self.depth) {
+                    return Err(SketchError::InvalidFeature(format!(
+                        "countersink {} at {:.1} deg does not fit a {}x{} hole",
+                        diameter,
+                        angle.0.to_degrees(),
+                        self.diameter,
+                        self.depth
+                    )));
+                }
+                // Cone from above the plane down to half the bore radius
+                let bottom = sink + r / 2.0 / slope;
+                tools.push(
+                    Loft::new()
+                        .section(circle(cr + clearance * slope)?, offset(clearance)?)
+                        .section(circle(r / 2.0)?, offset(-bottom)?)
+                        .build()?,
+                );
+            }
+        }
+        tools.push(circle(r)?.extrude_two_sided(plane, clearance, self.depth)?);
+        Ok(tools)
+    }
+}
+
+fn positive(name: &str, value: f64) -> SketchResult<()> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(SketchError::InvalidFeature(format!(
+            "hole {} must be positive, got {}",
+            name, value
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_hole_validation() {
+        let at = Point2::new(10.0, 10.0);
+        let plane = Plane::xy();
+        assert_eq!(
+            HoleFeature::new(6.0, 10.0).tools(&plane, at).unwrap().len(),
+            1
+        );
+        assert_eq!(
+            HoleFeature::new(6.0, 10.0)
+                .countersink(10.0, Rad(FRAC_PI_2))
+                .tools(&plane, at)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            HoleFeature::new(8.0, 10.0).tapped(1.25).bore_diameter(),
+            6.75
+        );
+        assert!(matches!(
+            HoleFeature::new(6.0, 10.0)
+                .counterbore(5.0, 4.0)
+                .tools(&plane, at),
+            Err(SketchError::InvalidFeature(_))
+        ));
+        assert!(matches!(
+            HoleFeature::new(6.0, 1.0)
+                .countersink(10.0, Rad(FRAC_PI_2))
+                .tools(&plane, at),
+            Err(SketchError::InvalidFeature(_))
+        ));
+    }
+
+    #[test]
+    fn test_counterbored_hole_in_plate() {
+        let mut plate = box_solid(Point3::origin(), Vector3::new(20.0, 20.0, 10.0)).unwrap();
+        HoleFeature::new(6.0, 12.0)
+            .counterbore(10.0, 4.0)
+            .apply(&mut plate, Plane::xy_at(10.0), &[Point2::new(10.0, 10.0)])
+            .unwrap();
+        assert_eq!(plate.boundaries().len(), 1);
+        let on_circle = |z: f64, r: f64| {
+            plate.vertex_iter().any(|v| {
+                let p = v.point();
+                (p.z - z).abs() < 1e-6
+                    && ((p - Point3::new(10.0, 10.0, z)).magnitude() - r).abs() < 1e-6
+            })
+        };
+        // Counterbore rim, counterbore floor and bore exit
+        assert!(on_circle(10.0, 5.0) && on_circle(6.0, 3.0) && on_circle(0.0, 3.0));
+    }
+}
@@ -0,0 +1,145 @@
+use truck_geometry::prelude::*;
+use truck_modeling::{Edge, Solid};
+
+/// Points sampled along an edge to measure it
+const EDGE_SAMPLES: usize = 256;
+
+/// How far samples may stray from a fitted circle, relative to its radius,
+/// for an edge to count as an arc
+const ARC_TOLERANCE: f64 = 1e-6;
+
+/// Length and shape of one B-rep edge
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeMeasure {
+    /// Points along the edge from start to end
+    pub points: Vec<Point3>,
+    pub length: f64,
+    /// Centre and radius when the edge is a circular arc
+    pub circle: Option<(Point3, f64)>,
+}
+
+impl EdgeMeasure {
+    pub fn new(edge: &Edge) -> Self {
+        let curve = edge.oriented_curve();
+        let (t0, t1) = curve.range_tuple();
+        let points: Vec<Point3> = (0..=EDGE_SAMPLES)
+            .map(|i| curve.subs(t0 + (t1 - t0) * i as f64 / EDGE_SAMPLES as f64))
+            .collect();
+        let length = points.windows(2).map(|w| w[0].distance(w[1])).sum();
+        let circle = fit_circle(&points);
+        Self {
+            points,
+            length,
+            circle,
+        }
+    }
+
+    /// Distance from `p` to the sampled edge
+    pub fn distance_to(&self, p: Point3) -> f64 {
+        self.points
+            .windows(2)
+            .map(|w| segment_distance(p, w[0], w[1]))
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+/// Every edge of `solid`, each once
+pub fn edge_measures(solid: &Solid) -> Vec<EdgeMeasure> {
+    let mut seen = std::collections::HashSet::new();
+    solid
+        .edge_iter()
+        .filter(|edge| seen.insert(edge.id()))
+        .map(|edge| EdgeMeasure::new(&edge))
+        .collect()
+}
+
+/// Index of the edge in `edges` nearest to `p`, if within `radius`
+pub fn nearest_edge(edges: &[EdgeMeasure], p: Point3, radius: f64) -> Option<usize> {
+    edges
+        .iter()
+        .map(|edge| edge.distance_to(p))
+        .enumerate()
+        .filter(|&(_, distance)| distance <= radius)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+/// Angle at `vertex` between the directions to `a` and `b`, in radians
+pub fn angle_at(vertex: Point3, a: Point3, b: Point3) -> f64 {
+    (a - vertex).angle(b - vertex).0
+}
+
+/// Circle through the first, middle and last points, if every point lies
+/// on it
+fn fit_circle(points: &[Point3]) -> Option<(Point3, f64)> {
+    let (a, b, c) = (points[0], points[points.len() / 2], *points.last()?);
+    // Closed edges start and end on the same point; take a quarter instead
+    let c = if a.distance(c) < a.distance(b) * 1e-6 {
+        points[points.len() / 4]
+    } else {
+        c
+    };
+    let (ab, ac) = (b - a, c - a);
+    let normal = ab.cross(ac);
+    let area = normal.magnitude2();
+    if area < 1e-24 {
+        return None;
+    }
+    let centre = a
+        + (normal.cross(ab) * ac.magnitude2() + ac.cross(normal) * ab.magnitude2()) / (2.0 * area);
+    let radius = centre.distance(a);
+    let on_circle = points.iter().all(|p| {
+        (centre.distance(*p) - radius).abs() <= radius * ARC_TOLERANCE
+            && (p - centre).dot(normal).abs() <= radius * ARC_TOLERANCE * normal.magnitude()
+    });
+    on_circle.then_some((centre, radius))
+}
+
+fn segment_distance(p: Point3, a: Point3, b: Point3) -> f64 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.magnitude2().max(f64::MIN_POSITIVE)).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::cylinder;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    #[test]
+    fn test_edge_measures() {
+        let rod = cylinder(Point3::origin(), Vector3::unit_z() * 4.0, 1.5).unwrap();
+        let edges = edge_measures(&rod);
+        let circles: Vec<&EdgeMeasure> = edges.iter().filter(|e| e.circle.is_some()).collect();
+        assert!(!circles.is_empty());
+        // Arcs of the two rims add up to two full turns
+        let rim: f64 = circles.iter().map(|e| e.length).sum();
+        assert!((rim - 2.0 * 2.0 * PI * 1.5).abs() < 1e-3, "{}", rim);
+        for edge in &circles {
+            let (centre, radius) = edge.circle.unwrap();
+            assert!((radius - 1.5).abs() < 1e-6);
+            assert!(centre.x.abs() < 1e-6 && centre.y.abs() < 1e-6);
+        }
+        // and the seam is straight and as long as the rod
+        let straight: Vec<f64> = edges
+            .iter()
+            .filter(|e| e.circle.is_none())
+            .map(|e| e.length)
+            .collect();
+        assert!(straight.iter().any(|length| (length - 4.0).abs() < 1e-6));
+
+        let top = Point3::new(1.5, 0.0, 4.0);
+        assert!(edges.iter().any(|e| e.distance_to(top) < 1e-6));
+        let near = nearest_edge(&edges, Point3::new(0.0, -1.6, 4.05), 0.2).unwrap();
+        assert!(edges[near].circle.is_some());
+        assert_eq!(nearest_edge(&edges, Point3::new(0.0, 0.0, 2.0), 0.2), None);
+
+        let angle = angle_at(
+            Point3::origin(),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, 3.0),
+        );
+        assert!((angle - FRAC_PI_2).abs() < 1e-12);
+    }
+}
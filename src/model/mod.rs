@@ -0,0 +1,19 @@
+pub mod analysis;
+pub mod assembly;
+pub mod datum;
+pub mod history;
+pub mod hole;
+pub mod measure;
+pub mod primitives;
+pub mod rib;
+pub mod thicken;
+
+pub use analysis::BodyProperties;
+pub use assembly::{Assembly, Part};
+pub use datum::{Csys, Datums};
+pub use history::{Feature, FeatureRecord, History};
+pub use hole::{HoleFeature, HoleKind};
+pub use measure::{angle_at, edge_measures, nearest_edge, EdgeMeasure};
+pub use primitives::{box_solid, cone, cylinder, sphere, torus};
+pub use rib::rib;
+pub use thicken::thicken;
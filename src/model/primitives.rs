@@ -0,0 +1,152 @@
+use crate::sketch::plane::perpendicular;
+use crate::sketch::{SketchError, SketchResult};
+use std::f64::consts::PI;
+use truck_geometry::prelude::*;
+use truck_modeling::{builder, Solid, Wire};
+
+/// Axis-aligned box with its minimum corner at `min`
+pub fn box_solid(min: Point3, size: Vector3) -> SketchResult<Solid> {
+    positive("width", size.x)?;
+    positive("depth", size.y)?;
+    positive("height", size.z)?;
+    let vertex = builder::vertex(min);
+    let edge = builder::tsweep(&vertex, Vector3::new(size.x, 0.0, 0.0));
+    let face = builder::tsweep(&edge, Vector3::new(0.0, size.y, 0.0));
+    Ok(builder::tsweep(&face, Vector3::new(0.0, 0.0, size.z)))
+}
+
+/// Cylinder standing on `base` (center of the bottom face); the length of
+/// `axis` is the height
+pub fn cylinder(base: Point3, axis: Vector3, radius: f64) -> SketchResult<Solid> {
+    cone(base, axis, radius, radius)
+}
+
+/// Cone or frustum standing on `base` along `axis`; a zero `top_radius`
+/// gives a pointed cone
+pub fn cone(base: Point3, axis: Vector3, base_radius: f64, top_radius: f64) -> SketchResult<Solid> {
+    positive("height", axis.magnitude())?;
+    positive("base radius", base_radius)?;
+    if top_radius < 0.0 || !top_radius.is_finite() {
+        return Err(invalid("top radius", top_radius));
+    }
+    let radial = perpendicular(axis);
+    let top = base + axis;
+
+    // Profile from the top of the axis down the side to the bottom of the axis
+    let mut points = vec![top];
+    if top_radius > 0.0 {
+        points.push(top + radial * top_radius);
+    }
+    points.push(base + radial * base_radius);
+    points.push(base);
+    let vertices: Vec<_> = points.into_iter().map(builder::vertex).collect();
+    let profile: Wire = vertices
+        .windows(2)
+        .map(|v| builder::line(&v[0], &v[1]))
+        .collect();
+    solid_of_revolution(&profile, axis)
+}
+
+/// Sphere centered at `center`
+pub fn sphere(center: Point3, radius: f64) -> SketchResult<Solid> {
+    positive("radius", radius)?;
+    let north = builder::vertex(center + Vector3::unit_z() * radius);
+    let south = builder::vertex(center - Vector3::unit_z() * radius);
+    let meridian = builder::circle_arc(&north, &south, center + Vector3::unit_x() * radius);
+    solid_of_revolution(&vec![meridian].into(), Vector3::unit_z())
+}
+
+/// Torus around `axis` through `center`: a tube of `minor_radius` whose
+/// center line is a circle of `major_radius`
+pub fn torus(
+    center: Point3,
+    axis: Vector3,
+    major_radius: f64,
+    minor_radius: f64,
+) -> SketchResult<Solid> {
+    positive("axis length", axis.magnitude())?;
+    positive("minor radius", minor_radius)?;
+    positive("major radius", major_radius)?;
+    if major_radius <= minor_radius {
+        return Err(SketchError::InvalidPrimitive(format!(
+            "major radius {} must exceed minor radius {}",
+            major_radius, minor_radius
+        )));
+    }
+    let axis = axis.normalize();
+    let radial = perpendicular(axis);
+    let tube_center = center + radial * major_radius;
+    let start = builder::vertex(tube_center + radial * minor_radius);
+    let tube: Wire = builder::rsweep(&start, tube_center, axis.cross(radial), Rad(2.0 * PI));
+    let shell = builder::rsweep(&tube, center, axis, Rad(2.0 * PI));
+    Solid::try_new(vec![shell]).map_err(|e| SketchError::TruckSolidError(format!("{:?}", e)))
+}
+
+/// Revolve a profile whose ends lie on `axis` a full turn
+fn solid_of_revolution(profile: &Wire, axis: Vector3) -> SketchResult<Solid> {
+    let shell = builder::cone(profile, axis.normalize(), Rad(2.0 * PI));
+    Solid::try_new(vec![shell]).map_err(|e| SketchError::TruckSolidError(format!("{:?}", e)))
+}
+
+fn invalid(name: &str, value: f64) -> SketchError {
+    SketchError::InvalidPrimitive(format!("{} must be positive, got {}", name, value))
+}
+
+fn positive(name: &str, value: f64) -> SketchResult<()> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(invalid(name, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::mesh::GpuMesh;
+
+    fn volume(solid: &Solid) -> f64 {
+        GpuMesh::from_solid(solid, 0.001).volume()
+    }
+
+    #[test]
+    fn test_primitive_volumes() {
+        let origin = Point3::origin();
+        let cases = [
+            (box_solid(origin, Vector3::new(2.0, 3.0, 4.0)), 24.0),
+            (cylinder(origin, Vector3::new(0.0, 0.0, 2.0), 1.0), 2.0 * PI),
+            (cone(origin, Vector3::new(0.0, 3.0, 0.0), 1.0, 0.0), PI),
+            (
+                cone(origin, Vector3::new(1.0, 1.0, 0.0), 2.0, 1.0),
+                PI * 2f64.sqrt() / 3.0 * 7.0,
+            ),
+            (sphere(origin, 1.0), 4.0 / 3.0 * PI),
+            (
+                torus(origin, Vector3::unit_z(), 2.0, 0.5),
+                2.0 * PI * PI * 2.0 * 0.25,
+            ),
+        ];
+        for (solid, expected) in cases {
+            let v = volume(&solid.unwrap());
+            assert!(
+                (v - expected).abs() < 0.02 * expected,
+                "{} vs {}",
+                v,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_dimensions() {
+        let origin = Point3::origin();
+        assert!(matches!(
+            box_solid(origin, Vector3::new(1.0, 0.0, 1.0)),
+            Err(SketchError::InvalidPrimitive(_))
+        ));
+        assert!(cylinder(origin, Vector3::zero(), 1.0).is_err());
+        assert!(torus(origin, Vector3::unit_z(), 1.0, 1.0).is_err());
+        assert!(torus(origin, Vector3::unit_z(), f64::NAN, 1.0).is_err());
+        assert!(torus(origin, Vector3::unit_z(), f64::INFINITY, 1.0).is_err());
+    }
+}
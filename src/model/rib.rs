@@ -0,0 +1,375 @@
+use crate::sketch::{
+    BoundingBox2D, Curve2D, Line2D, Loop2D, Plane, Sketch, SketchCurve2D, SketchError, SketchResult,
+};
+use std::collections::HashMap;
+use truck_geometry::prelude::*;
+use truck_meshalgo::prelude::*;
+use truck_modeling::Solid;
+
+/// Tessellation tolerance for finding the walls a rib grows towards
+const RAY_MESH_TOLERANCE: f64 = 0.05;
+
+/// Add a web of `thickness` (centered on `plane`) under an open `profile`
+///
+/// The profile is the free edge of the rib. Its ends run on tangentially to
+/// the first wall of `solid` they meet, and the web is closed along the
+/// walls between those two points, so it only fills the pocket the
+/// profile spans. The web reaches a quarter of the thickness into the
+/// walls, so they should be at least that thick.
+pub fn rib(
+    solid: &Solid,
+    profile: &[Curve2D],
+    plane: impl Into<Plane>,
+    thickness: f64,
+) -> SketchResult<Solid> {
+    let plane = &plane.into();
+    if !(thickness > 0.0 && thickness.is_finite()) {
+        return Err(SketchError::InvalidThickness(thickness));
+    }
+    let (first, last) = match (profile.first(), profile.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(SketchError::EmptyLoop),
+    };
+    let section = section_loops(solid, plane);
+    if section.is_empty() {
+        return Err(SketchError::InvalidFeature(
+            "rib plane does not cut the solid".to_string(),
+        ));
+    }
+
+    // Profile run on to the walls, and a little into them
+    let overlap = thickness / 4.0;
+    let back = -first.tangent_at(0.0).normalize();
+    let ahead = last.tangent_at(1.0).normalize();
+    let wall = |from: Point2, dir: Vector2| {
+        first_hit(&section, from, dir).ok_or_else(|| {
+            SketchError::InvalidFeature("rib profile does not run into a wall".to_string())
+        })
+    };
+    let (lead_hit, trail_hit) = (wall(first.start(), back)?, wall(last.end(), ahead)?);
+    if lead_hit.outline != trail_hit.outline {
+        return Err(SketchError::InvalidFeature(
+            "rib profile ends run into walls that are not joined".to_string(),
+        ));
+    }
+    let lead = lead_hit.point + back * overlap;
+    let trail = trail_hit.point + ahead * overlap;
+    // End lines are stretched rather than extended, so no vertex is left
+    // where the profile meets a wall
+    let mut curves = profile.to_vec();
+    match &mut curves[0] {
+        Curve2D::Line(line) => line.set_start(lead),
+        curve => {
+            let start = curve.start();
+            if let Ok(line) = Line2D::new(lead, start) {
+                curves.insert(0, Curve2D::Line(line));
+            }
+        }
+    }
+    let n = curves.len();
+    match &mut curves[n - 1] {
+        Curve2D::Line(line) => line.set_end(trail),
+        curve => {
+            let end = curve.end();
+            if let Ok(line) = Line2D::new(end, trail) {
+                curves.push(Curve2D::Line(line));
+            }
+        }
+    }
+
+    // Back along the walls facing the profile, moved into the material
+    let outline = &section[lead_hit.outline];
+    let edge: Vec<Point2> = profile
+        .iter()
+        .flat_map(|c| {
+            (0..PROFILE_SAMPLES).map(move |i| c.point_at(i as f64 / PROFILE_SAMPLES as f64))
+        })
+        .collect();
+    let area = |path: &[Point2]| {
+        let polygon: Vec<Point2> = edge.iter().chain(path).copied().collect();
+        signed_area(&polygon)
+    };
+    let (forward, backward) = (
+        wall_path(outline, &trail_hit, &lead_hit, true),
+        wall_path(outline, &trail_hit, &lead_hit, false),
+    );
+    let path = if area(&forward).abs() <= area(&backward).abs() {
+        forward
+    } else {
+        backward
+    };
+    let ccw = area(&path) > 0.0;
+    let mut corner = trail;
+    for next in offset_path(&path, overlap, ccw).into_iter().chain([lead]) {
+        curves.push(Curve2D::Line(Line2D::new(corner, next)?));
+        corner = next;
+    }
+
+    let mut region = Loop2D::new(curves)?;
+    if !region.is_ccw() {
+        region = region.reversed();
+    }
+    let web = Sketch::new(region).extrude_symmetric(plane, thickness)?;
+    crate::geometry::union(solid, &web)
+}
+
+/// Points taken on each profile curve to tell which way round the walls
+/// the web closes
+const PROFILE_SAMPLES: usize = 8;
+
+/// Where a profile end runs into a section outline
+struct WallHit {
+    outline: usize,
+    /// Outline segment hit, from point `segment` to the next
+    segment: usize,
+    /// How far along the segment, 0 to 1
+    along: f64,
+    point: Point2,
+}
+
+/// Outlines where `plane` cuts `solid`, as closed polylines in plane
+/// coordinates
+fn section_loops(solid: &Solid, plane: &Plane) -> Vec<Vec<Point2>> {
+    let mesh = solid.triangulation(RAY_MESH_TOLERANCE).to_polygon();
+    let positions = mesh.positions();
+    let height = |p: Point3| (p - plane.origin()).dot(plane.normal());
+    let mut segments = Vec::new();
+    for tri in mesh.tri_faces() {
+        let corners = tri.map(|v| positions[v.pos]);
+        let crossings: Vec<Point2> = [(0, 1), (1, 2), (2, 0)]
+            .into_iter()
+            .filter_map(|(i, j)| {
+                // Same order from both triangles on an edge, so the
+                // crossing points match exactly
+                let (a, b) = match corners[i].x.total_cmp(&corners[j].x) {
+                    std::cmp::Ordering::Equal if corners[i].y > corners[j].y => {
+                        (corners[j], corners[i])
+                    }
+                    std::cmp::Ordering::Greater => (corners[j], corners[i]),
+                    _ => (corners[i], corners[j]),
+                };
+                let (ha, hb) = (height(a), height(b));
+                ((ha >= 0.0) != (hb >= 0.0))
+                    .then(|| plane.project_point(a + (b - a) * (ha / (ha - hb))))
+            })
+            .collect();
+        if let [p, q] = crossings[..] {
+            segments.push([p, q]);
+        }
+    }
+
+    // Chain the segments by their end points
+    let size =
+        BoundingBox2D::from_points(&segments.concat()).map_or(0.0, |b| (b.max - b.min).magnitude());
+    let grid = (size * 1e-9).max(f64::MIN_POSITIVE);
+    let key = |p: Point2| ((p.x / grid).round() as i64, (p.y / grid).round() as i64);
+    let mut at: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, segment) in segments.iter().enumerate() {
+        for &p in segment {
+            at.entry(key(p)).or_default().push(i);
+        }
+    }
+    let mut used = vec![false; segments.len()];
+    let mut outlines = Vec::new();
+    for start in 0..segments.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let mut outline = vec![segments[start][0]];
+        let mut end = segments[start][1];
+        while key(end) != key(outline[0]) {
+            let Some(&next) = at[&key(end)].iter().find(|&&i| !used[i]) else {
+                break;
+            };
+            used[next] = true;
+            outline.push(end);
+            let [p, q] = segments[next];
+            end = if key(p) == key(end) { q } else { p };
+        }
+        if key(end) == key(outline[0]) && outline.len() > 2 {
+            outlines.push(simplify(outline));
+        }
+    }
+    outlines
+}
+
+/// `outline` without repeated points and points on a straight run
+fn simplify(outline: Vec<Point2>) -> Vec<Point2> {
+    let size = BoundingBox2D::from_points(&outline).map_or(0.0, |b| (b.max - b.min).magnitude());
+    let mut kept: Vec<Point2> = Vec::with_capacity(outline.len());
+    for p in outline {
+        if kept
+            .last()
+            .is_none_or(|&q| (p - q).magnitude() > size * 1e-9)
+        {
+            kept.push(p);
+        }
+    }
+    let n = kept.len();
+    let straight = |i: usize| {
+        let (a, b, c) = (kept[(i + n - 1) % n], kept[i], kept[(i + 1) % n]);
+        let (u, v) = (b - a, c - b);
+        u.perp_dot(v).abs() <= 1e-9 * u.magnitude() * v.magnitude() && u.dot(v) > 0.0
+    };
+    (0..n).filter(|&i| !straight(i)).map(|i| kept[i]).collect()
+}
+
+/// Nearest point where the ray from `from` along `dir` meets an outline;
+/// a ray starting on a wall meets it at once
+fn first_hit(outlines: &[Vec<Point2>], from: Point2, dir: Vector2) -> Option<WallHit> {
+    let mut best: Option<(f64, WallHit)> = None;
+    for (outline, points) in outlines.iter().enumerate() {
+        let size = BoundingBox2D::from_points(points).map_or(0.0, |b| (b.max - b.min).magnitude());
+        for segment in 0..points.len() {
+            let (p, q) = (points[segment], points[(segment + 1) % points.len()]);
+            let side = q - p;
+            let det = dir.perp_dot(side);
+            if det.abs() < 1e-12 * side.magnitude() {
+                continue;
+            }
+            let to = p - from;
+            let t = to.perp_dot(side) / det;
+            let along = to.perp_dot(dir) / det;
+            if t >= -1e-9 * size
+                && (0.0..=1.0).contains(&along)
+                && best.as_ref().is_none_or(|(nearest, _)| t < *nearest)
+            {
+                let point = p + side * along;
+                best = Some((
+                    t,
+                    WallHit {
+                        outline,
+                        segment,
+                        along,
+                        point,
+                    },
+                ));
+            }
+        }
+    }
+    best.map(|(_, hit)| hit)
+}
+
+/// Outline points passed going from `from` to `to`, forward along the
+/// outline or back, with both hit points at the ends
+fn wall_path(outline: &[Point2], from: &WallHit, to: &WallHit, forward: bool) -> Vec<Point2> {
+    let n = outline.len();
+    let (i, j) = (from.segment, to.segment);
+    let mut path = vec![from.point];
+    if forward {
+        let mut steps = (j + n - i) % n;
+        if steps == 0 && to.along < from.along {
+            steps = n;
+        }
+        path.extend((0..steps).map(|m| outline[(i + 1 + m) % n]));
+    } else {
+        let mut steps = (i + n - j) % n;
+        if steps == 0 && to.along > from.along {
+            steps = n;
+        }
+        path.extend((0..steps).map(|m| outline[(i + n - m) % n]));
+    }
+    path.push(to.point);
+    path
+}
+
+/// The inner points of `path` moved `distance` to its right, or left when
+/// `!right`, with mitred corners
+fn offset_path(path: &[Point2], distance: f64, right: bool) -> Vec<Point2> {
+    let side = |a: Point2, b: Point2| {
+        let d = (b - a).normalize();
+        if right {
+            Vector2::new(d.y, -d.x)
+        } else {
+            Vector2::new(-d.y, d.x)
+        }
+    };
+    path.windows(3)
+        .map(|w| {
+            let (n1, n2) = (side(w[0], w[1]), side(w[1], w[2]));
+            let cos = n1.dot(n2);
+            let mitre = if cos > -0.9 {
+                (n1 + n2) / (1.0 + cos)
+            } else {
+                n1
+            };
+            w[1] + mitre * distance
+        })
+        .collect()
+}
+
+/// Shoelace area of a closed polygon, positive when counterclockwise
+fn signed_area(points: &[Point2]) -> f64 {
+    let next = points.iter().cycle().skip(1);
+    points
+        .iter()
+        .zip(next)
+        .map(|(p, q)| p.x * q.y - q.x * p.y)
+        .sum::<f64>()
+        / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::{Shapes, SketchBuilder};
+
+    #[test]
+    fn test_rib_in_l_bracket() {
+        // L-bracket in XZ: 40 long base and 30 tall upright, both 4 thick,
+        // extruded 20 along the plane normal (-Y)
+        let bracket = Sketch::new(Shapes::l_shape(Point2::origin(), 40.0, 30.0, 4.0).unwrap())
+            .extrude(Plane::xz(), Vector3::new(0.0, -20.0, 0.0))
+            .unwrap();
+        let before = GpuMesh::from_solid(&bracket, 0.01).volume();
+
+        let midplane = Plane::new(
+            Point3::new(0.0, -10.0, 0.0),
+            Vector3::unit_x(),
+            Vector3::unit_z(),
+        )
+        .unwrap();
+        let profile = SketchBuilder::new()
+            .move_to(Point2::new(4.0, 24.0))
+            .line_to(Point2::new(28.0, 4.0))
+            .unwrap()
+            .build_open();
+        let ribbed = rib(&bracket, &profile, &midplane, 2.0).unwrap();
+        assert_eq!(ribbed.boundaries().len(), 1);
+
+        // Triangular web with legs 24 and 20
+        let added = GpuMesh::from_solid(&ribbed, 0.01).volume() - before;
+        assert!((added - 24.0 * 20.0 / 2.0 * 2.0).abs() < 5.0, "{}", added);
+    }
+
+    #[test]
+    fn test_rib_in_t_bracket() {
+        // Inverted T in XZ: 40 wide flange and a 30 tall stem, both 4
+        // thick. The profile's lead runs on through the stem into open
+        // space, which the web must not fill.
+        let bracket = Sketch::new(Shapes::t_shape(Point2::origin(), 40.0, 4.0, 30.0, 4.0).unwrap())
+            .extrude(Plane::xz(), Vector3::new(0.0, -20.0, 0.0))
+            .unwrap();
+        let before = GpuMesh::from_solid(&bracket, 0.01).volume();
+
+        let midplane = Plane::new(
+            Point3::new(0.0, -10.0, 0.0),
+            Vector3::unit_x(),
+            Vector3::unit_z(),
+        )
+        .unwrap();
+        let profile = SketchBuilder::new()
+            .move_to(Point2::new(2.0, 24.0))
+            .line_to(Point2::new(18.0, 4.0))
+            .unwrap()
+            .build_open();
+        let ribbed = rib(&bracket, &profile, &midplane, 2.0).unwrap();
+        assert_eq!(ribbed.boundaries().len(), 1);
+
+        // Triangular web with legs 16 and 20
+        let added = GpuMesh::from_solid(&ribbed, 0.01).volume() - before;
+        assert!((added - 16.0 * 20.0 / 2.0 * 2.0).abs() < 5.0, "{}", added);
+    }
+}
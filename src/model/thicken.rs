@@ -0,0 +1,404 @@
+use crate::sketch::constants::*;
+use crate::sketch::{SketchError, SketchResult};
+use std::collections::HashMap;
+use truck_geometry::prelude::*;
+use truck_modeling::{Curve, Edge, Face, Shell, ShellCondition, Solid, Surface};
+
+/// Correction passes when fitting offset B-spline/NURBS control points
+const FIT_PASSES: usize = 16;
+
+/// Newton iterations when locating boundary points on their faces
+const SEARCH_TRIALS: usize = 100;
+
+/// Thicken `faces` into a thin solid by offsetting them `thickness` along
+/// their normals (negative thickens behind them) and closing the gap with
+/// side walls along the free edges.
+///
+/// `faces` is a single face or a connected, consistently oriented open
+/// shell; where faces meet at an angle the offset is mitred. Planar faces
+/// are offset exactly. B-spline and NURBS faces (e.g. from sweeps) get
+/// control points fitted so the offset is exact at their Greville points;
+/// revolved faces are not supported.
+pub fn thicken(faces: &[Face], thickness: f64) -> SketchResult<Solid> {
+    if !thickness.is_finite() || thickness.abs() <= LENGTH_TOLERANCE {
+        return Err(SketchError::InvalidThickness(thickness));
+    }
+    let shell: Shell = faces.iter().cloned().collect();
+    if shell.is_empty()
+        || !shell.is_connected()
+        || shell.shell_condition() != ShellCondition::Oriented
+    {
+        return Err(SketchError::InvalidFeature(
+            "faces to thicken must form one connected, consistently oriented open shell"
+                .to_string(),
+        ));
+    }
+    let faces: Vec<Face> = shell.face_iter().cloned().collect();
+
+    // Faces meeting at each vertex and along each edge
+    let mut vertex_faces = HashMap::new();
+    let mut edge_faces = HashMap::new();
+    for (i, face) in faces.iter().enumerate() {
+        for vertex in face.vertex_iter() {
+            let (_, adjacent) = vertex_faces
+                .entry(vertex.id())
+                .or_insert_with(|| (vertex.point(), Vec::new()));
+            adjacent.push(i);
+        }
+        for edge in face.edge_iter() {
+            let (_, _, adjacent) = edge_faces.entry(edge.id()).or_insert_with(|| {
+                let ends = (edge.absolute_front().point(), edge.absolute_back().point());
+                (edge.curve(), ends, Vec::new())
+            });
+            adjacent.push(i);
+        }
+    }
+    let shift = |p: Point3, adjacent: &[usize]| -> SketchResult<Point3> {
+        let normals = adjacent
+            .iter()
+            .map(|&i| normal_at(&faces[i], p))
+            .collect::<SketchResult<Vec<_>>>()?;
+        Ok(p + mitre(&normals)? * thickness)
+    };
+
+    let mut points = HashMap::new();
+    for (p, adjacent) in vertex_faces.values() {
+        points.insert(point_key(*p), shift(*p, adjacent)?);
+    }
+    let mut curves = HashMap::new();
+    for (curve, (front, back), adjacent) in edge_faces.values() {
+        let offset = match curve {
+            Curve::Line(_) => {
+                Curve::Line(Line(points[&point_key(*front)], points[&point_key(*back)]))
+            }
+            Curve::BSplineCurve(_) | Curve::NurbsCurve(_) => {
+                let mut fitted = fit_curve(curve.clone().lift_up(), |p| shift(p, adjacent))?;
+                // Land exactly on the offset vertices
+                let last = fitted.control_points().len() - 1;
+                for (i, p) in [(0, *front), (last, *back)] {
+                    let w = fitted.control_point(i).w;
+                    *fitted.control_point_mut(i) = (points[&point_key(p)].to_vec() * w).extend(w);
+                }
+                match curve {
+                    Curve::BSplineCurve(_) => Curve::BSplineCurve(dehomogenized_curve(fitted)),
+                    _ => Curve::NurbsCurve(NurbsCurve::new(fitted)),
+                }
+            }
+            Curve::IntersectionCurve(_) => {
+                return Err(SketchError::InvalidFeature(
+                    "intersection edges cannot be thickened".to_string(),
+                ))
+            }
+        };
+        curves.insert(curve_key(curve), offset);
+    }
+    let mut surfaces = faces
+        .iter()
+        .map(|face| offset_surface(face, thickness))
+        .collect::<SketchResult<Vec<_>>>()?
+        .into_iter();
+
+    let top = shell.mapped(
+        |p| points[&point_key(*p)],
+        |c| curves[&curve_key(c)].clone(),
+        |_| surfaces.next().expect("one offset surface per face"),
+    );
+
+    // Matching top edge for every bottom edge, in the bottom edge's direction
+    let mut pairs = HashMap::new();
+    for (bottom, top) in faces.iter().zip(top.face_iter()) {
+        for (e0, e1) in bottom.edge_iter().zip(top.edge_iter()) {
+            pairs.insert(e0.id(), (e0, e1));
+        }
+    }
+
+    // Side walls along the free edges, wound as truck's sweeps wind them
+    let mut boundary: Shell = faces.iter().map(Face::inverse).collect();
+    let mut risers: HashMap<_, Edge> = HashMap::new();
+    for wire in shell.extract_boundaries() {
+        for e0 in wire.edge_iter() {
+            let (s0, s1) = &pairs[&e0.id()];
+            let e1 = if e0.orientation() == s0.orientation() {
+                s1.clone()
+            } else {
+                s1.inverse()
+            };
+            let mut riser = |v0: &truck_modeling::Vertex, v1: &truck_modeling::Vertex| {
+                risers
+                    .entry(v0.id())
+                    .or_insert_with(|| Edge::new(v0, v1, Curve::Line(Line(v0.point(), v1.point()))))
+                    .clone()
+            };
+            let front = riser(e0.front(), e1.front());
+            let back = riser(e0.back(), e1.back());
+            let wall: truck_modeling::Wire = if e0.orientation() {
+                vec![e0.clone(), back, e1.inverse(), front.inverse()].into()
+            } else {
+                vec![front, e1.clone(), back.inverse(), e0.inverse()].into()
+            };
+            let mut face = Face::new(vec![wall], connect(&e0.curve(), &e1.curve()));
+            if !e0.orientation() {
+                face.invert();
+            }
+            boundary.push(face);
+        }
+    }
+    boundary.extend(top);
+
+    let mut solid = Solid::try_new(vec![boundary])
+        .map_err(|e| SketchError::TruckSolidError(format!("{:?}", e)))?;
+    if thickness < 0.0 {
+        solid.not();
+    }
+    Ok(solid)
+}
+
+fn point_key(p: Point3) -> [u64; 3] {
+    [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]
+}
+
+fn curve_key(curve: &Curve) -> [[u64; 3]; 3] {
+    let (t0, t1) = curve.range_tuple();
+    [
+        point_key(curve.front()),
+        point_key(curve.subs((t0 + t1) / 2.0)),
+        point_key(curve.back()),
+    ]
+}
+
+/// Outward normal of `face` at a point on it
+fn normal_at(face: &Face, p: Point3) -> SketchResult<Vector3> {
+    let surface = face.surface();
+    let (u, v) = surface
+        .search_parameter(p, SPHint2D::None, SEARCH_TRIALS)
+        .ok_or_else(|| {
+            SketchError::InvalidFeature(format!(
+                "boundary point ({:.3}, {:.3}, {:.3}) is off its face",
+                p.x, p.y, p.z
+            ))
+        })?;
+    let n = surface.normal(u, v);
+    Ok(if face.orientation() { n } else { -n })
+}
+
+/// Unit-thickness displacement that moves a point `1` along every normal;
+/// faces folded back onto each other have no such point
+fn mitre(normals: &[Vector3]) -> SketchResult<Vector3> {
+    let mut distinct: Vec<Vector3> = Vec::new();
+    for &n in normals {
+        if distinct.iter().all(|m| m.dot(n) < 1.0 - ANGLE_TOLERANCE) {
+            distinct.push(n);
+        }
+    }
+    match distinct.as_slice() {
+        [n] => Ok(*n),
+        [n0, n1] if 1.0 + n0.dot(*n1) < ANGLE_TOLERANCE => Err(SketchError::InvalidFeature(
+            "cannot thicken faces that fold back onto each other".to_string(),
+        )),
+        [n0, n1] => Ok((n0 + n1) / (1.0 + n0.dot(*n1))),
+        _ => {
+            // Least squares over all the offset planes
+            let m = distinct.iter().fold(Matrix3::zero(), |m, n| {
+                m + Matrix3::from_cols(n * n.x, n * n.y, n * n.z)
+            });
+            let sum = distinct.iter().fold(Vector3::zero(), |s, n| s + n);
+            m.invert()
+                .map(|inv| Ok(inv * sum))
+                .unwrap_or_else(|| mitre(&distinct[..2]))
+        }
+    }
+}
+
+/// Greville abscissae of a spline's control points
+fn greville(knots: &KnotVec, degree: usize, count: usize) -> Vec<f64> {
+    (0..count)
+        .map(|i| (1..=degree).map(|k| knots[i + k]).sum::<f64>() / degree.max(1) as f64)
+        .collect()
+}
+
+/// Move homogeneous control points until the curve passes through
+/// `target(p)` for each of its points `p` at the Greville abscissae
+fn fit_curve(
+    curve: BSplineCurve<Vector4>,
+    target: impl Fn(Point3) -> SketchResult<Point3>,
+) -> SketchResult<BSplineCurve<Vector4>> {
+    let knots = curve.knot_vec().clone();
+    let params = greville(&knots, curve.degree(), curve.control_points().len());
+    let targets = params
+        .iter()
+        .map(|&t| target(Point3::from_homogeneous(curve.subs(t))))
+        .collect::<SketchResult<Vec<_>>>()?;
+    let mut points = curve.control_points().clone();
+    for _ in 0..FIT_PASSES {
+        let fitted = BSplineCurve::new(knots.clone(), points.clone());
+        for (k, &t) in params.iter().enumerate() {
+            let residual = targets[k] - Point3::from_homogeneous(fitted.subs(t));
+            let w = points[k].w;
+            points[k] += (residual * w).extend(0.0);
+        }
+    }
+    Ok(BSplineCurve::new(knots, points))
+}
+
+fn dehomogenized_curve(curve: BSplineCurve<Vector4>) -> BSplineCurve<Point3> {
+    let points = curve
+        .control_points()
+        .iter()
+        .map(|&p| Point3::from_homogeneous(p))
+        .collect();
+    BSplineCurve::new(curve.knot_vec().clone(), points)
+}
+
+/// Face surface moved `thickness` along its outward normal
+fn offset_surface(face: &Face, thickness: f64) -> SketchResult<Surface> {
+    let sign = if face.orientation() { 1.0 } else { -1.0 };
+    let surface = face.surface();
+    let lifted = match &surface {
+        Surface::Plane(plane) => {
+            let shift = Matrix4::from_translation(plane.normal() * (sign * thickness));
+            return Ok(Surface::Plane(plane.transformed(shift)));
+        }
+        Surface::BSplineSurface(s) => BSplineSurface::new(
+            s.knot_vecs().clone(),
+            s.control_points()
+                .iter()
+                .map(|row| row.iter().map(|p| p.to_homogeneous()).collect())
+                .collect(),
+        ),
+        Surface::NurbsSurface(s) => s.non_rationalized().clone(),
+        Surface::RevolutedCurve(_) => {
+            return Err(SketchError::InvalidFeature(
+                "revolved faces cannot be thickened".to_string(),
+            ))
+        }
+    };
+
+    let (uknots, vknots) = lifted.knot_vecs().clone();
+    let rows = lifted.control_points().len();
+    let cols = lifted.control_points()[0].len();
+    let us = greville(&uknots, lifted.udegree(), rows);
+    let vs = greville(&vknots, lifted.vdegree(), cols);
+    let target = |u: f64, v: f64| surface.subs(u, v) + surface.normal(u, v) * (sign * thickness);
+    let targets: Vec<Vec<Point3>> = us
+        .iter()
+        .map(|&u| vs.iter().map(|&v| target(u, v)).collect())
+        .collect();
+    let mut points = lifted.control_points().clone();
+    for _ in 0..FIT_PASSES {
+        let fitted = BSplineSurface::new((uknots.clone(), vknots.clone()), points.clone());
+        for (i, &u) in us.iter().enumerate() {
+            for (j, &v) in vs.iter().enumerate() {
+                let residual = targets[i][j] - Point3::from_homogeneous(fitted.subs(u, v));
+                let w = points[i][j].w;
+                points[i][j] += (residual * w).extend(0.0);
+            }
+        }
+    }
+    let fitted = BSplineSurface::new((uknots, vknots), points);
+    Ok(match surface {
+        Surface::BSplineSurface(_) => Surface::BSplineSurface(BSplineSurface::new(
+            fitted.knot_vecs().clone(),
+            fitted
+                .control_points()
+                .iter()
+                .map(|row| row.iter().map(|&p| Point3::from_homogeneous(p)).collect())
+                .collect(),
+        )),
+        _ => Surface::NurbsSurface(NurbsSurface::new(fitted)),
+    })
+}
+
+/// Ruled wall between a bottom edge curve and its offset
+fn connect(c0: &Curve, c1: &Curve) -> Surface {
+    if let (Curve::Line(a), Curve::Line(b)) = (c0, c1) {
+        let normal = (a.1 - a.0).cross(b.0 - a.0);
+        if normal.magnitude() > DEGENERATE_TOLERANCE
+            && normal.normalize().dot(b.1 - a.0).abs() < LENGTH_TOLERANCE
+        {
+            return Surface::Plane(Plane::new(a.0, a.1, b.0));
+        }
+    }
+    Surface::NurbsSurface(NurbsSurface::new(BSplineSurface::homotopy(
+        c0.clone().lift_up(),
+        c1.clone().lift_up(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::{Plane as SketchPlane, Shapes, Sketch};
+    use std::f64::consts::PI;
+    use truck_modeling::builder;
+
+    fn volume(solid: &Solid) -> f64 {
+        GpuMesh::from_solid(solid, 0.01).volume()
+    }
+
+    #[test]
+    fn test_thicken_planar_faces() {
+        let rect = Shapes::rectangle(Point2::origin(), 10.0, 4.0).unwrap();
+        let face = Sketch::new(rect).to_truck_face(SketchPlane::xy()).unwrap();
+        for t in [2.0, -2.0] {
+            let solid = thicken(std::slice::from_ref(&face), t).unwrap();
+            assert!((volume(&solid) - 80.0).abs() < 1e-6);
+        }
+
+        // Floor and one wall of a box: two slabs plus the mitred corner
+        let cube =
+            crate::model::box_solid(Point3::origin(), Vector3::new(10.0, 10.0, 10.0)).unwrap();
+        let shell = &cube.boundaries()[0];
+        let floor = shell
+            .face_iter()
+            .find(|f| normal_at(f, Point3::new(5.0, 5.0, 0.0)).is_ok_and(|n| n.z < -0.5))
+            .unwrap();
+        let wall = shell
+            .face_iter()
+            .find(|f| normal_at(f, Point3::new(5.0, 0.0, 5.0)).is_ok_and(|n| n.y < -0.5))
+            .unwrap();
+        let solid = thicken(&[floor.clone(), wall.clone()], 1.0).unwrap();
+        assert!((volume(&solid) - 210.0).abs() < 1e-6);
+
+        assert!(matches!(
+            thicken(&[face], 0.0),
+            Err(SketchError::InvalidThickness(_))
+        ));
+    }
+
+    #[test]
+    fn test_mitre_of_opposite_faces() {
+        let corner = mitre(&[Vector3::unit_z(), Vector3::unit_x()]).unwrap();
+        assert!((corner - Vector3::new(1.0, 0.0, 1.0)).magnitude() < 1e-12);
+        // A sheet folded flat has no offset point meeting both sides
+        assert!(matches!(
+            mitre(&[Vector3::unit_z(), -Vector3::unit_z()]),
+            Err(SketchError::InvalidFeature(_))
+        ));
+    }
+
+    #[test]
+    fn test_thicken_swept_face() {
+        // Quarter of a cylinder wall, radius 10 and height 5
+        let v0 = builder::vertex(Point3::new(10.0, 0.0, 0.0));
+        let v1 = builder::vertex(Point3::new(0.0, 10.0, 0.0));
+        let mid = Point3::new(10.0 / 2f64.sqrt(), 10.0 / 2f64.sqrt(), 0.0);
+        let arc = builder::circle_arc(&v0, &v1, mid);
+        let face: Face = builder::tsweep(&arc, Vector3::new(0.0, 0.0, 5.0));
+        let n = normal_at(&face, mid).unwrap();
+        let outward = n.dot(mid.to_vec()) > 0.0;
+
+        for t in [1.0, -1.0] {
+            let solid = thicken(std::slice::from_ref(&face), t).unwrap();
+            let r_out = if (t > 0.0) == outward { 11.0 } else { 10.0 };
+            let expected = PI / 4.0 * (r_out * r_out - (r_out - 1.0) * (r_out - 1.0)) * 5.0;
+            let v = volume(&solid);
+            assert!(
+                (v - expected).abs() < 0.005 * expected,
+                "{} vs {}",
+                v,
+                expected
+            );
+        }
+    }
+}
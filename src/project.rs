@@ -0,0 +1,259 @@
+use crate::model::{Assembly, History};
+use crate::renderer::camera::{OrbitCamera, ViewBookmarks};
+use crate::renderer::DisplaySettings;
+use crate::sketch::{Plane, Sketch, SketchError, SketchResult};
+use crate::units::Units;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Written into every saved project; files from a newer version are refused
+pub const PROJECT_VERSION: u32 = 1;
+
+/// `format` tag of project files
+const PROJECT_FORMAT: &str = "truck-playground project";
+
+/// File extension of saved projects (JSON inside)
+pub const PROJECT_EXTENSION: &str = "tpp";
+
+/// A named sketch on the plane it was drawn on
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlacedSketch {
+    /// Unique among the project's sketches, since features refer to it
+    pub name: String,
+    pub plane: Plane,
+    pub sketch: Sketch,
+}
+
+/// `base`, or `base` numbered from 2 up when one of `sketches` has that name
+pub fn unique_sketch_name(sketches: &[PlacedSketch], base: &str) -> String {
+    std::iter::once(base.to_string())
+        .chain((2..).map(|i| format!("{} {}", base, i)))
+        .find(|name| sketches.iter().all(|placed| &placed.name != name))
+        .expect("some numbered name is free")
+}
+
+/// Everything a user builds: sketches, the placed parts with their datums
+/// and the features that made them, and how the viewport looked.
+///
+/// Saved as JSON objects tagged with `format` and `version`, with parts as
+/// exact B-reps rather than meshes. The layout is described by
+/// `project.schema.json` next to this file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Project {
+    /// What model coordinates are in; projects from before units are in
+    /// millimetres
+    #[serde(default)]
+    pub units: Units,
+    pub sketches: Vec<PlacedSketch>,
+    pub assembly: Assembly,
+    /// Features that made the parts; projects from before history have none
+    #[serde(default)]
+    pub history: History,
+    pub camera: OrbitCamera,
+    /// Named viewpoints; projects from before bookmarks have none
+    #[serde(default)]
+    pub views: ViewBookmarks,
+    pub display: DisplaySettings,
+}
+
+#[derive(Serialize)]
+struct ProjectFile<'a> {
+    format: &'a str,
+    version: u32,
+    #[serde(flatten)]
+    project: &'a Project,
+}
+
+impl Project {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn to_json(&self) -> String {
+        let file = ProjectFile {
+            format: PROJECT_FORMAT,
+            version: PROJECT_VERSION,
+            project: self,
+        };
+        serde_json::to_string_pretty(&file).expect("projects serialize to JSON")
+    }
+
+    /// Read a project written by this or an earlier version
+    pub fn from_json(text: &str) -> SketchResult<Self> {
+        let invalid = |message: String| SketchError::InvalidProject(message);
+        let value: Value = serde_json::from_str(text).map_err(|e| invalid(e.to_string()))?;
+        if value["format"] != PROJECT_FORMAT {
+            return Err(invalid("not a truck-playground project".to_string()));
+        }
+        let version = value["version"]
+            .as_u64()
+            .ok_or_else(|| invalid("missing version".to_string()))?;
+        if version > PROJECT_VERSION as u64 {
+            return Err(invalid(format!(
+                "saved by a newer version (format {}, this build reads up to {})",
+                version, PROJECT_VERSION
+            )));
+        }
+        let project: Self = serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+        for (i, placed) in project.sketches.iter().enumerate() {
+            if project.sketches[..i].iter().any(|p| p.name == placed.name) {
+                return Err(invalid(format!(
+                    "more than one sketch is named '{}'",
+                    placed.name
+                )));
+            }
+        }
+        Ok(project)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> SketchResult<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()).map_err(|e| {
+            SketchError::InvalidProject(format!("could not write {}: {}", path.display(), e))
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> SketchResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            SketchError::InvalidProject(format!("could not read {}: {}", path.display(), e))
+        })?;
+        Self::from_json(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{cylinder, Csys, Feature};
+    use crate::renderer::background::BackgroundStyle;
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::construction::Construction;
+    use crate::sketch::{BSpline2D, Curve2D, Line2D, Loop2D, Plane, Shapes};
+    use truck_geometry::prelude::*;
+
+    /// A project touching every section, including a spline loop,
+    /// construction geometry and a recorded feature
+    fn sample_project() -> Project {
+        let mut project = Project::new();
+        let outer = Shapes::rounded_rectangle(Point2::origin(), 10.0, 6.0, 1.0).unwrap();
+        let hole = Shapes::circle(Point2::new(5.0, 3.0), 1.0).unwrap();
+        let mut plate = Sketch::with_holes(outer, vec![hole]);
+        plate
+            .construction
+            .push(Construction::Point(Point2::new(5.0, 3.0)));
+        project.sketches.push(PlacedSketch {
+            name: "plate".to_string(),
+            plane: Plane::xz(),
+            sketch: plate,
+        });
+        let arch = BSpline2D::from_truck_curve(BSplineCurve::new(
+            KnotVec::bezier_knot(2),
+            vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(1.0, 2.0),
+                Point2::new(2.0, 0.0),
+            ],
+        ));
+        let chord = Line2D::new(Point2::new(2.0, 0.0), Point2::origin()).unwrap();
+        let arch = Loop2D::new(vec![Curve2D::BSpline(arch), Curve2D::Line(chord)]).unwrap();
+        project.sketches.push(PlacedSketch {
+            name: "arch".to_string(),
+            plane: Plane::xy(),
+            sketch: Sketch::new(arch),
+        });
+        let rod = cylinder(Point3::origin(), Vector3::unit_z() * 4.0, 1.0).unwrap();
+        let placement = Matrix4::from_translation(Vector3::new(1.0, 2.0, 3.0));
+        project.assembly.add("rod", rod, placement).unwrap();
+        project.history.record(
+            "rod",
+            Feature::Extrude {
+                sketch: "plate".to_string(),
+                direction: Vector3::unit_y() * 4.0,
+            },
+        );
+        project.history.record(
+            "rod",
+            Feature::Import {
+                path: "rod.step".into(),
+            },
+        );
+        let frame = Csys::world().child(Vector3::unit_z(), Rad(0.5));
+        project.assembly.datums.register("top", frame).unwrap();
+        project.units = Units::Inch;
+        project.camera.distance = 12.5;
+        project.views.save_view("close", &project.camera);
+        project.display.background = [1.0, 1.0, 1.0];
+        project.display.background_style = BackgroundStyle::Sky;
+        project
+    }
+
+    #[test]
+    fn test_project_round_trip() {
+        let project = sample_project();
+        let placement = Matrix4::from_translation(Vector3::new(1.0, 2.0, 3.0));
+        let frame = Csys::world().child(Vector3::unit_z(), Rad(0.5));
+
+        let loaded = Project::from_json(&project.to_json()).unwrap();
+        let sketch = &loaded.sketches[0];
+        assert_eq!(sketch.name, "plate");
+        assert_eq!(sketch.plane.normal(), Plane::xz().normal());
+        assert!((sketch.sketch.area() - project.sketches[0].sketch.area()).abs() < 1e-12);
+        let rod = loaded.assembly.get("rod").unwrap();
+        assert_eq!(rod.transform, placement);
+        let volume = GpuMesh::from_solid(&rod.solid, 0.001).volume();
+        assert!((volume - 4.0 * std::f64::consts::PI).abs() < 0.01);
+        assert_eq!(loaded.assembly.datums.get("top").unwrap(), frame);
+        assert_eq!(loaded.history, project.history);
+        assert_eq!(loaded.units, Units::Inch);
+        assert_eq!(loaded.camera.distance, 12.5);
+        assert_eq!(loaded.views, project.views);
+        assert_eq!(loaded.display, project.display);
+
+        let newer = project
+            .to_json()
+            .replace("\"version\": 1", "\"version\": 99");
+        assert!(matches!(
+            Project::from_json(&newer),
+            Err(SketchError::InvalidProject(_))
+        ));
+        assert!(Project::from_json("{}").is_err());
+
+        // Features find sketches by name, so names have to stay unique
+        assert_eq!(unique_sketch_name(&project.sketches, "plate"), "plate 2");
+        assert_eq!(unique_sketch_name(&project.sketches, "rib"), "rib");
+        let duplicated = project
+            .to_json()
+            .replace("\"name\": \"arch\"", "\"name\": \"plate\"");
+        assert!(matches!(
+            Project::from_json(&duplicated),
+            Err(SketchError::InvalidProject(_))
+        ));
+    }
+
+    #[test]
+    fn test_saved_projects_match_schema() {
+        let schema: Value = serde_json::from_str(include_str!("project.schema.json")).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+
+        let saved: Value = serde_json::from_str(&sample_project().to_json()).unwrap();
+        let errors: Vec<String> = validator
+            .iter_errors(&saved)
+            .map(|e| format!("{} at {}", e, e.instance_path))
+            .collect();
+        assert!(errors.is_empty(), "{:#?}", errors);
+        let empty: Value = serde_json::from_str(&Project::new().to_json()).unwrap();
+        assert!(validator.is_valid(&empty));
+
+        let mut broken = saved.clone();
+        broken["sketches"][0]["plane"]
+            .as_object_mut()
+            .unwrap()
+            .remove("y_dir");
+        assert!(!validator.is_valid(&broken));
+        let mut broken = saved;
+        broken["history"]["features"][0]["feature"] = Value::from("Extrude");
+        assert!(!validator.is_valid(&broken));
+    }
+}
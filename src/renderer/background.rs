@@ -0,0 +1,166 @@
+use crate::renderer::camera::OrbitCamera;
+use bytemuck::{Pod, Zeroable};
+use eframe::wgpu;
+use serde::{Deserialize, Serialize};
+
+/// What fills the viewport behind the parts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackgroundStyle {
+    /// The background color only
+    #[default]
+    Solid,
+    /// From the background color at the bottom of the view to the top color
+    Gradient,
+    /// Top color overhead fading to the background color at the horizon,
+    /// with darker ground below; turns with the camera
+    Sky,
+}
+
+impl BackgroundStyle {
+    pub const ALL: [BackgroundStyle; 3] = [
+        BackgroundStyle::Solid,
+        BackgroundStyle::Gradient,
+        BackgroundStyle::Sky,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackgroundStyle::Solid => "Solid",
+            BackgroundStyle::Gradient => "Gradient",
+            BackgroundStyle::Sky => "Sky",
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct BackgroundUniforms {
+    /// Clip space back to world space, for the sky's view rays
+    pub inverse_view_proj: [[f32; 4]; 4],
+    pub top: [f32; 4],
+    pub bottom: [f32; 4],
+    /// [`BackgroundStyle`] in `x`
+    pub style: [u32; 4],
+}
+
+impl BackgroundUniforms {
+    pub fn new(
+        style: BackgroundStyle,
+        bottom: [f64; 3],
+        top: [f64; 3],
+        camera: &OrbitCamera,
+        aspect: f32,
+    ) -> Self {
+        let color = |[r, g, b]: [f64; 3]| [r as f32, g as f32, b as f32, 1.0];
+        Self {
+            inverse_view_proj: camera.view_projection(aspect).inverse().to_cols_array_2d(),
+            top: color(top),
+            bottom: color(bottom),
+            style: [style as u32, 0, 0, 0],
+        }
+    }
+}
+
+/// Fills the viewport with a gradient or sky before the scene is drawn;
+/// solid backgrounds are just the clear color
+pub struct BackgroundRenderer {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl BackgroundRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Background Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("background.wgsl").into()),
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Background Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Background Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Background Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // Leaves the depth cleared so everything draws over it
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Background Uniforms"),
+            size: std::mem::size_of::<BackgroundUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Background Bind Group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        Self {
+            pipeline,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn prepare(&self, queue: &wgpu::Queue, uniforms: &BackgroundUniforms) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[*uniforms]));
+    }
+
+    /// Fill the target, first thing in the pass
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,49 @@
+struct Background {
+    inverse_view_proj: mat4x4<f32>,
+    // Top of the gradient, zenith of the sky
+    top: vec4<f32>,
+    // Bottom of the gradient, horizon of the sky
+    bottom: vec4<f32>,
+    // Style in x: 1 gradient, 2 sky
+    style: vec4<u32>,
+};
+
+@group(0) @binding(0)
+var<uniform> background: Background;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+// One triangle covering the screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.ndc = uv * 2.0 - 1.0;
+    out.clip_position = vec4<f32>(out.ndc, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if (background.style.x == 1u) {
+        let t = in.ndc.y * 0.5 + 0.5;
+        return vec4<f32>(mix(background.bottom.rgb, background.top.rgb, t), 1.0);
+    }
+
+    // Sky: blend by how far the view ray through this pixel climbs above
+    // the horizon (camera up is +Y), darker ground below
+    // Reversed depth: the near plane is at 1
+    let near = background.inverse_view_proj * vec4<f32>(in.ndc, 1.0, 1.0);
+    let far = background.inverse_view_proj * vec4<f32>(in.ndc, 0.0, 1.0);
+    let direction = normalize(far.xyz / far.w - near.xyz / near.w);
+    let height = direction.y;
+    let horizon = background.bottom.rgb;
+    if (height >= 0.0) {
+        return vec4<f32>(mix(horizon, background.top.rgb, sqrt(height)), 1.0);
+    }
+    let ground = horizon * 0.45;
+    return vec4<f32>(mix(horizon, ground, min(-height * 6.0, 1.0)), 1.0);
+}
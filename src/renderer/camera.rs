@@ -1,5 +1,261 @@
-use glam::{Mat4, Vec3};
+use crate::renderer::mesh::BoundingBox3;
+use crate::renderer::pick::Ray;
+use glam::{EulerRot, Mat4, Quat, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU};
 
+/// Elevation is kept this far from the poles so the view never flips
+const MAX_ELEVATION: f32 = FRAC_PI_2 - 0.01;
+
+/// Seconds a switch to a standard view takes
+const VIEW_TRANSITION_SECS: f32 = 0.3;
+
+/// Closest the derived near plane gets to the eye, as a fraction of the
+/// far plane distance
+const MIN_NEAR_RATIO: f32 = 1e-5;
+
+/// Slack around the scene when deriving clipping planes, as a fraction of
+/// its radius
+const CLIP_MARGIN: f32 = 0.05;
+
+/// How the view is projected onto the screen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Projection {
+    #[default]
+    Perspective,
+    /// Parallel projection, so sizes on screen do not depend on depth
+    Orthographic,
+}
+
+/// What dragging to rotate does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RotationMode {
+    /// Turntable-style: sideways drags spin about the world Y axis and the
+    /// view stays upright, stopping short of the poles
+    #[default]
+    Orbit,
+    /// Free tumbling: drags turn the view about axes in the screen plane,
+    /// over the poles and with roll
+    Trackball,
+}
+
+impl RotationMode {
+    pub const ALL: [RotationMode; 2] = [RotationMode::Orbit, RotationMode::Trackball];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RotationMode::Orbit => "Orbit",
+            RotationMode::Trackball => "Trackball",
+        }
+    }
+}
+
+/// How mouse input moves the camera
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    /// Radians turned per pixel dragged
+    pub orbit_speed: f32,
+    /// Fraction of the distance (or view size) one unit of scrolling zooms
+    pub zoom_speed: f32,
+    /// How far panning moves the view; at 1 the point under the target
+    /// follows the cursor
+    pub pan_speed: f32,
+    /// Turn the other way on sideways drags
+    pub invert_x: bool,
+    /// Turn the other way on vertical drags
+    pub invert_y: bool,
+    /// Zoom in on what is under the cursor rather than the view centre
+    pub zoom_to_cursor: bool,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            orbit_speed: 0.01,
+            zoom_speed: 0.1,
+            pan_speed: 1.0,
+            invert_x: false,
+            invert_y: false,
+            zoom_to_cursor: true,
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Drag in pixels as turning angles, with the inversions applied
+    fn turn(&self, delta_x: f32, delta_y: f32) -> Vec2 {
+        let sign = |invert: bool| if invert { -1.0 } else { 1.0 };
+        Vec2::new(delta_x * sign(self.invert_x), delta_y * sign(self.invert_y)) * self.orbit_speed
+    }
+}
+
+/// Canonical orientations; Y points up, so Front looks down -Z and Top
+/// looks down -Y
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StandardView {
+    Front,
+    Back,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Isometric,
+}
+
+impl StandardView {
+    pub const ALL: [StandardView; 7] = [
+        StandardView::Front,
+        StandardView::Back,
+        StandardView::Left,
+        StandardView::Right,
+        StandardView::Top,
+        StandardView::Bottom,
+        StandardView::Isometric,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StandardView::Front => "Front",
+            StandardView::Back => "Back",
+            StandardView::Left => "Left",
+            StandardView::Right => "Right",
+            StandardView::Top => "Top",
+            StandardView::Bottom => "Bottom",
+            StandardView::Isometric => "Iso",
+        }
+    }
+
+    /// Azimuth and elevation of the eye, in radians
+    pub fn angles(&self) -> (f32, f32) {
+        match self {
+            StandardView::Front => (0.0, 0.0),
+            StandardView::Back => (PI, 0.0),
+            StandardView::Left => (-FRAC_PI_2, 0.0),
+            StandardView::Right => (FRAC_PI_2, 0.0),
+            StandardView::Top => (0.0, MAX_ELEVATION),
+            StandardView::Bottom => (0.0, -MAX_ELEVATION),
+            // Equal angles to all three axes
+            StandardView::Isometric => (FRAC_PI_4, (0.5f32).sqrt().atan()),
+        }
+    }
+}
+
+/// Orbit angles being eased from one orientation to another
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewTransition {
+    from: (f32, f32),
+    to: (f32, f32),
+    /// Roll at the start, eased out to upright
+    from_roll: f32,
+    elapsed: f32,
+}
+
+/// Spins a camera about its target at a steady rate, for product-style
+/// turntable renders
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Turntable {
+    /// Degrees of azimuth per second
+    pub speed_deg: f32,
+}
+
+impl Default for Turntable {
+    fn default() -> Self {
+        Self { speed_deg: 30.0 }
+    }
+}
+
+impl Turntable {
+    /// Turn `camera` by `dt` seconds worth of spin
+    pub fn advance(&self, camera: &mut OrbitCamera, dt: f32) {
+        camera.azimuth_rad =
+            (camera.azimuth_rad + self.speed_deg.to_radians() * dt).rem_euclid(TAU);
+    }
+
+    /// `count` evenly spaced cameras over one full turn from `camera`, in
+    /// the direction of the spin
+    pub fn frames(&self, camera: &OrbitCamera, count: usize) -> Vec<OrbitCamera> {
+        let step = TAU / count.max(1) as f32 * self.speed_deg.signum();
+        (0..count)
+            .map(|i| OrbitCamera {
+                azimuth_rad: (camera.azimuth_rad + step * i as f32).rem_euclid(TAU),
+                transition: None,
+                ..*camera
+            })
+            .collect()
+    }
+}
+
+/// Where a camera stands and looks, without its input settings or
+/// clipping planes
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraPose {
+    pub target: Vec3,
+    pub distance: f32,
+    pub azimuth_rad: f32,
+    pub elevation_rad: f32,
+    pub roll_rad: f32,
+    pub projection: Projection,
+    pub ortho_scale: f32,
+}
+
+/// Named viewpoints to come back to, kept with the project
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewBookmarks {
+    views: Vec<(String, CameraPose)>,
+}
+
+impl ViewBookmarks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember where `camera` looks from as `name`, replacing an earlier
+    /// view of that name
+    pub fn save_view(&mut self, name: &str, camera: &OrbitCamera) {
+        let pose = camera.pose();
+        match self.views.iter_mut().find(|(n, _)| n == name) {
+            Some((_, saved)) => *saved = pose,
+            None => self.views.push((name.to_string(), pose)),
+        }
+    }
+
+    /// Put `camera` back where the view `name` was saved; false when there
+    /// is none
+    pub fn restore_view(&self, name: &str, camera: &mut OrbitCamera) -> bool {
+        match self.get(name) {
+            Some(pose) => {
+                camera.set_pose(pose);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<CameraPose> {
+        self.views
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, pose)| pose)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<CameraPose> {
+        let index = self.views.iter().position(|(n, _)| n == name)?;
+        Some(self.views.remove(index).1)
+    }
+
+    /// Names in the order they were first saved
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.views.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OrbitCamera {
     /// Point the camera orbits around
     pub target: Vec3,
@@ -13,6 +269,14 @@ pub struct OrbitCamera {
     /// Vertical angle (radians, from horizontal)
     pub elevation_rad: f32,
 
+    /// Turn of the view about the viewing direction (radians), only left
+    /// by trackball rotation
+    pub roll_rad: f32,
+
+    pub rotation: RotationMode,
+
+    pub settings: CameraSettings,
+
     /// Field of view (radians)
     pub fov_rad: f32,
 
@@ -21,6 +285,19 @@ pub struct OrbitCamera {
 
     /// Far clipping plane
     pub far: f32,
+
+    /// Derive `near` and `far` from the scene each frame, see
+    /// [`OrbitCamera::fit_clip_planes`]; off keeps them as set
+    pub auto_clip: bool,
+
+    pub projection: Projection,
+
+    /// Half the view height in world units, in orthographic mode
+    pub ortho_scale: f32,
+
+    /// Running switch to a standard view, advanced by [`OrbitCamera::animate`]
+    #[serde(skip)]
+    pub transition: Option<ViewTransition>,
 }
 
 impl Default for OrbitCamera {
@@ -30,9 +307,16 @@ impl Default for OrbitCamera {
             distance: 100.0,
             azimuth_rad: std::f32::consts::FRAC_PI_4, // 45°
             elevation_rad: std::f32::consts::FRAC_PI_6, // 30°
-            fov_rad: std::f32::consts::FRAC_PI_4,     // 45°
+            roll_rad: 0.0,
+            rotation: RotationMode::default(),
+            settings: CameraSettings::default(),
+            fov_rad: std::f32::consts::FRAC_PI_4, // 45°
             near: 0.1,
             far: 1000.0,
+            auto_clip: true,
+            projection: Projection::Perspective,
+            ortho_scale: 100.0 * (std::f32::consts::FRAC_PI_8).tan(),
+            transition: None,
         }
     }
 }
@@ -47,18 +331,98 @@ impl OrbitCamera {
         self.target + Vec3::new(x, y, z)
     }
 
+    pub fn pose(&self) -> CameraPose {
+        CameraPose {
+            target: self.target,
+            distance: self.distance,
+            azimuth_rad: self.azimuth_rad,
+            elevation_rad: self.elevation_rad,
+            roll_rad: self.roll_rad,
+            projection: self.projection,
+            ortho_scale: self.ortho_scale,
+        }
+    }
+
+    /// Jump to `pose`, cancelling a running view switch; when orbiting the
+    /// view is levelled
+    pub fn set_pose(&mut self, pose: CameraPose) {
+        self.transition = None;
+        self.target = pose.target;
+        self.distance = pose.distance;
+        self.azimuth_rad = pose.azimuth_rad;
+        self.elevation_rad = pose.elevation_rad;
+        self.roll_rad = pose.roll_rad;
+        self.projection = pose.projection;
+        self.ortho_scale = pose.ortho_scale;
+        self.set_rotation(self.rotation);
+    }
+
+    /// Camera axes in world space as a rotation of the default frame: the
+    /// eye looks down -Z with Y up, turned by roll, then elevation, then
+    /// azimuth
+    pub fn orientation(&self) -> Quat {
+        Quat::from_euler(
+            EulerRot::YXZ,
+            self.azimuth_rad,
+            -self.elevation_rad,
+            self.roll_rad,
+        )
+    }
+
     /// View matrix (world → camera space)
     pub fn view_matrix(&self) -> Mat4 {
         Mat4::look_at_rh(
             self.eye_position(),
             self.target,
-            Vec3::Y, // Up vector
+            self.orientation() * Vec3::Y,
         )
     }
 
-    /// Projection matrix (camera → clip space)
+    /// Projection matrix (camera → clip space) with reversed depth: 1 at
+    /// the near plane and 0 at the far one, which with a float depth buffer
+    /// keeps precision far from the eye
     pub fn projection_matrix(&self, aspect_ratio: f32) -> Mat4 {
-        Mat4::perspective_rh(self.fov_rad, aspect_ratio, self.near, self.far)
+        match self.projection {
+            Projection::Perspective => {
+                Mat4::perspective_rh(self.fov_rad, aspect_ratio, self.far, self.near)
+            }
+            Projection::Orthographic => {
+                let (h, w) = (self.ortho_scale, self.ortho_scale * aspect_ratio);
+                Mat4::orthographic_rh(-w, w, -h, h, self.far, self.near)
+            }
+        }
+    }
+
+    /// Half the height of the view at the target, in world units
+    pub fn view_height(&self) -> f32 {
+        match self.projection {
+            Projection::Perspective => self.distance * (self.fov_rad * 0.5).tan(),
+            Projection::Orthographic => self.ortho_scale,
+        }
+    }
+
+    /// Pixels one world unit at `point` spans on a `viewport_height` tall
+    /// image
+    pub fn pixels_per_unit(&self, point: Vec3, viewport_height: f32) -> f32 {
+        let half_height = match self.projection {
+            Projection::Perspective => {
+                let depth = (point - self.eye_position()).dot(self.target - self.eye_position())
+                    / self.distance;
+                depth.max(self.near) * (self.fov_rad * 0.5).tan()
+            }
+            Projection::Orthographic => self.ortho_scale,
+        };
+        viewport_height * 0.5 / half_height
+    }
+
+    /// Switch projection, keeping the target the same size on screen
+    pub fn set_projection(&mut self, projection: Projection) {
+        if projection == Projection::Orthographic {
+            self.ortho_scale = self.distance * (self.fov_rad * 0.5).tan();
+        } else if self.projection == Projection::Orthographic {
+            self.distance = (self.ortho_scale / (self.fov_rad * 0.5).tan()).clamp(1.0, 1000.0);
+        }
+        self.projection = projection;
     }
 
     /// Combined view-projection matrix
@@ -66,21 +430,465 @@ impl OrbitCamera {
         self.projection_matrix(aspect_ratio) * self.view_matrix()
     }
 
-    /// Rotate camera (from mouse drag)
+    /// Ray from the eye through pixel `screen_pos` (from the top left) of a
+    /// `viewport`-sized image
+    pub fn screen_ray(&self, screen_pos: Vec2, viewport: Vec2) -> Ray {
+        let ndc = Vec2::new(
+            2.0 * screen_pos.x / viewport.x.max(1.0) - 1.0,
+            1.0 - 2.0 * screen_pos.y / viewport.y.max(1.0),
+        );
+        let inverse = self
+            .view_projection(viewport.x.max(1.0) / viewport.y.max(1.0))
+            .inverse();
+        let near = inverse.project_point3(ndc.extend(1.0));
+        let far = inverse.project_point3(ndc.extend(0.0));
+        Ray {
+            origin: near,
+            direction: (far - near).normalize(),
+        }
+    }
+
+    /// Pixel (from the top left) of a `viewport`-sized image that `point`
+    /// projects to, or `None` behind the eye
+    pub fn world_to_screen(&self, point: Vec3, viewport: Vec2) -> Option<Vec2> {
+        let aspect = viewport.x.max(1.0) / viewport.y.max(1.0);
+        let clip = self.view_projection(aspect) * point.extend(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        let ndc = clip.truncate() / clip.w;
+        Some(Vec2::new(
+            (ndc.x + 1.0) * 0.5 * viewport.x,
+            (1.0 - ndc.y) * 0.5 * viewport.y,
+        ))
+    }
+
+    /// Aim at the centre of `bounds` and back off (or scale the view) until
+    /// its bounding sphere fits an `aspect_ratio` wide view; the viewing
+    /// direction stays the same
+    pub fn fit(&mut self, bounds: &BoundingBox3, aspect_ratio: f32) {
+        let radius = bounds.radius().max(1e-3);
+        let half_fov = (self.fov_rad * 0.5).tan();
+        // Narrower of the vertical and horizontal half-angles
+        let narrow = half_fov.atan().min((half_fov * aspect_ratio).atan());
+        self.target = bounds.center();
+        self.distance = radius / narrow.sin();
+        self.ortho_scale = radius / aspect_ratio.min(1.0);
+    }
+
+    /// Move the clipping planes in around the bounding sphere of `bounds`,
+    /// so nothing in it is cut off and depth precision is spent on it; does
+    /// nothing unless `auto_clip` is on
+    pub fn fit_clip_planes(&mut self, bounds: &BoundingBox3) {
+        if !self.auto_clip {
+            return;
+        }
+        let radius = bounds.radius().max(1e-3) * (1.0 + CLIP_MARGIN);
+        let forward = (self.target - self.eye_position()).normalize();
+        let depth = (bounds.center() - self.eye_position()).dot(forward);
+        let far = (depth + radius).max(radius);
+        self.near = match self.projection {
+            Projection::Perspective => (depth - radius).max(far * MIN_NEAR_RATIO),
+            // Nothing converges on the eye, so the view may reach behind it
+            Projection::Orthographic => depth - radius,
+        };
+        self.far = far;
+    }
+
+    /// Start turning towards `view`, keeping target and distance; the turn
+    /// takes the short way round and finishes through [`OrbitCamera::animate`]
+    pub fn set_view(&mut self, view: StandardView) {
+        let (azimuth, elevation) = view.angles();
+        // Unwrap the goal azimuth to within half a turn of the current one
+        let turn = (azimuth - self.azimuth_rad + PI).rem_euclid(TAU) - PI;
+        self.transition = Some(ViewTransition {
+            from: (self.azimuth_rad, self.elevation_rad),
+            to: (self.azimuth_rad + turn, elevation),
+            from_roll: self.roll_rad,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Advance a running view switch by `dt` seconds; true while it runs
+    pub fn animate(&mut self, dt: f32) -> bool {
+        let Some(transition) = &mut self.transition else {
+            return false;
+        };
+        transition.elapsed += dt;
+        let t = (transition.elapsed / VIEW_TRANSITION_SECS).min(1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        let (from, to) = (transition.from, transition.to);
+        self.azimuth_rad = from.0 + (to.0 - from.0) * eased;
+        self.elevation_rad = from.1 + (to.1 - from.1) * eased;
+        self.roll_rad = transition.from_roll * (1.0 - eased);
+        if t >= 1.0 {
+            self.transition = None;
+        }
+        self.transition.is_some()
+    }
+
+    /// Rotate camera (from mouse drag) as the rotation mode says; cancels a
+    /// running view switch
     pub fn orbit(&mut self, delta_x: f32, delta_y: f32) {
-        self.azimuth_rad -= delta_x * 0.01;
-        self.elevation_rad += delta_y * 0.01;
+        self.transition = None;
+        let turn = self.settings.turn(delta_x, delta_y);
+        if self.rotation == RotationMode::Trackball {
+            self.tumble(turn);
+            return;
+        }
+        self.azimuth_rad -= turn.x;
+        self.elevation_rad += turn.y;
 
         // Clamp elevation to avoid flipping
-        self.elevation_rad = self.elevation_rad.clamp(
-            -std::f32::consts::FRAC_PI_2 + 0.01,
-            std::f32::consts::FRAC_PI_2 - 0.01,
+        self.elevation_rad = self.elevation_rad.clamp(-MAX_ELEVATION, MAX_ELEVATION);
+    }
+
+    /// Turn the view about the screen axis across the drag, as if rolling a
+    /// ball under the cursor
+    fn tumble(&mut self, drag: Vec2) {
+        let Some(axis) = Vec3::new(-drag.y, -drag.x, 0.0).try_normalize() else {
+            return;
+        };
+        let turn = Quat::from_axis_angle(axis, drag.length());
+        self.set_orientation(self.orientation() * turn);
+    }
+
+    /// Turn to look along `-rotation * Z` with `rotation * Y` up, keeping
+    /// target and distance; see [`OrbitCamera::orientation`]
+    pub fn set_orientation(&mut self, rotation: Quat) {
+        self.transition = None;
+        let (azimuth, pitch, roll) = rotation.normalize().to_euler(EulerRot::YXZ);
+        self.azimuth_rad = azimuth;
+        self.elevation_rad = -pitch;
+        self.roll_rad = roll;
+    }
+
+    /// Switch rotation mode; going back to orbiting levels the view
+    pub fn set_rotation(&mut self, rotation: RotationMode) {
+        if rotation == RotationMode::Orbit {
+            self.roll_rad = 0.0;
+            self.elevation_rad = self.elevation_rad.clamp(-MAX_ELEVATION, MAX_ELEVATION);
+        }
+        self.rotation = rotation;
+    }
+
+    /// Move the target (and eye with it) in the view plane by a drag of
+    /// `delta_x`, `delta_y` pixels in a `viewport_height` tall view
+    pub fn pan(&mut self, delta_x: f32, delta_y: f32, viewport_height: f32) {
+        self.transition = None;
+        let rotation = self.orientation();
+        let scale = 2.0 * self.view_height() / viewport_height.max(1.0) * self.settings.pan_speed;
+        self.target += (rotation * Vec3::new(-delta_x, delta_y, 0.0)) * scale;
+    }
+
+    /// Zoom (from scroll wheel): moves the eye in perspective, scales the
+    /// view in orthographic mode. With the cursor's `toward` ray and
+    /// zoom-to-cursor on, zooms in on what is under the cursor, which stays
+    /// put on screen.
+    pub fn zoom(&mut self, delta: f32, toward: Option<&Ray>) {
+        let step = 1.0 - delta * self.settings.zoom_speed;
+        let factor = match self.projection {
+            Projection::Perspective => {
+                let distance = (self.distance * step).clamp(1.0, 1000.0);
+                let factor = distance / self.distance;
+                self.distance = distance;
+                factor
+            }
+            Projection::Orthographic => {
+                let scale = (self.ortho_scale * step).clamp(0.01, 1000.0);
+                let factor = scale / self.ortho_scale;
+                self.ortho_scale = scale;
+                factor
+            }
+        };
+        // Scale the view about where the ray crosses the target's depth, so
+        // the eye slides along the ray
+        let Some(ray) = toward.filter(|_| self.settings.zoom_to_cursor) else {
+            return;
+        };
+        let forward = (self.target - self.eye_position()).normalize();
+        let along = ray.direction.dot(forward);
+        if along > 1e-6 {
+            let pivot = ray.at((self.target - ray.origin).dot(forward) / along);
+            self.target = pivot + (self.target - pivot) * factor;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orthographic_projection() {
+        let mut camera = OrbitCamera {
+            distance: 20.0,
+            ..Default::default()
+        };
+        let height = camera.view_height();
+        camera.set_projection(Projection::Orthographic);
+        assert!((camera.view_height() - height).abs() < 1e-4);
+
+        // Pick rays run parallel to the view direction
+        let viewport = Vec2::new(200.0, 100.0);
+        let centre = camera.screen_ray(viewport * 0.5, viewport);
+        let corner = camera.screen_ray(Vec2::ZERO, viewport);
+        assert!((centre.direction - corner.direction).length() < 1e-5);
+        // and the view spans ortho_scale up and down from the target
+        let top = camera.screen_ray(Vec2::new(100.0, 0.0), viewport);
+        let offset = top.origin - centre.origin;
+        assert!((offset.length() - camera.ortho_scale).abs() < 1e-3);
+        let scale = camera.pixels_per_unit(Vec3::new(0.0, 0.0, 50.0), viewport.y);
+        assert!((scale * camera.ortho_scale - 50.0).abs() < 1e-3);
+
+        // Zooming scales the view without moving the eye
+        camera.zoom(1.0, None);
+        assert_eq!(camera.distance, 20.0);
+        assert!((camera.view_height() - height * 0.9).abs() < 1e-4);
+        camera.set_projection(Projection::Perspective);
+        assert!((camera.view_height() - height * 0.9).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_trackball_rotation() {
+        let mut camera = OrbitCamera {
+            rotation: RotationMode::Trackball,
+            ..Default::default()
+        };
+        let eye = |camera: &OrbitCamera| camera.eye_position() - camera.target;
+        // The eye offset agrees with the orientation's viewing axis
+        let axis = camera.orientation() * Vec3::Z * camera.distance;
+        assert!((eye(&camera) - axis).length() < 1e-4);
+
+        // Dragging down tumbles over the top instead of stopping at it
+        for _ in 0..15 {
+            camera.orbit(0.0, 10.0);
+        }
+        let over = eye(&camera);
+        assert!((over.length() - camera.distance).abs() < 1e-3);
+        assert!(over.y > 0.0 && camera.elevation_rad < MAX_ELEVATION);
+        assert!(
+            (camera.orientation() * Vec3::Y).y < 0.0,
+            "view is upside down"
         );
+        let view = camera.view_matrix();
+        assert!(view.is_finite());
+        assert!(view.transform_point3(camera.target).z < 0.0);
+
+        // Orbiting levels the view again
+        camera.set_rotation(RotationMode::Orbit);
+        assert_eq!(camera.roll_rad, 0.0);
+        camera.orbit(0.0, 1000.0);
+        assert_eq!(camera.elevation_rad, MAX_ELEVATION);
     }
 
-    /// Zoom (from scroll wheel)
-    pub fn zoom(&mut self, delta: f32) {
-        self.distance *= 1.0 - delta * 0.1;
-        self.distance = self.distance.clamp(1.0, 1000.0);
+    #[test]
+    fn test_zoom_to_cursor() {
+        let viewport = Vec2::new(800.0, 600.0);
+        let cursor = Vec2::new(650.0, 420.0);
+        for projection in [Projection::Perspective, Projection::Orthographic] {
+            let mut camera = OrbitCamera {
+                projection,
+                ..Default::default()
+            };
+            let ray = camera.screen_ray(cursor, viewport);
+            let point = ray.at(camera.distance);
+            camera.zoom(2.0, Some(&ray));
+            // The point under the cursor stays there while the view closes in
+            let moved = camera.world_to_screen(point, viewport).unwrap();
+            assert!(
+                (moved - cursor).length() < 0.1,
+                "{:?}: {}",
+                projection,
+                moved
+            );
+            assert!(camera.target != Vec3::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_fit_clip_planes() {
+        // A large assembly reaches past the default far plane
+        let mut camera = OrbitCamera::default();
+        let large = BoundingBox3::new(Vec3::splat(-2000.0), Vec3::splat(2000.0));
+        camera.fit_clip_planes(&large);
+        assert!(camera.far > large.radius() + camera.distance);
+        assert!(camera.near > 0.0 && camera.near < camera.far);
+
+        // while a small part gets planes close around it
+        let small = BoundingBox3::new(Vec3::splat(-0.5), Vec3::splat(0.5));
+        camera.fit_clip_planes(&small);
+        let radius = small.radius();
+        assert!(camera.near > camera.distance - radius * 1.1);
+        assert!(camera.near < camera.distance - radius);
+        assert!(camera.far > camera.distance + radius);
+        assert!(camera.far < camera.distance + radius * 1.1);
+
+        let manual = OrbitCamera {
+            auto_clip: false,
+            ..Default::default()
+        };
+        let mut fitted = manual;
+        fitted.fit_clip_planes(&large);
+        assert_eq!((fitted.near, fitted.far), (manual.near, manual.far));
+    }
+
+    #[test]
+    fn test_camera_settings() {
+        let plain = OrbitCamera::default();
+        let mut inverted = OrbitCamera {
+            settings: CameraSettings {
+                invert_x: true,
+                orbit_speed: 0.02,
+                zoom_speed: 0.2,
+                zoom_to_cursor: false,
+                ..Default::default()
+            },
+            ..plain
+        };
+        let mut camera = plain;
+        camera.orbit(10.0, 10.0);
+        inverted.orbit(10.0, 10.0);
+        let turned = |c: &OrbitCamera| c.azimuth_rad - plain.azimuth_rad;
+        assert!((turned(&inverted) + 2.0 * turned(&camera)).abs() < 1e-5);
+        assert!(inverted.elevation_rad > camera.elevation_rad);
+
+        let viewport = Vec2::new(800.0, 600.0);
+        let ray = plain.screen_ray(Vec2::new(700.0, 500.0), viewport);
+        camera.zoom(1.0, None);
+        inverted.zoom(1.0, Some(&ray));
+        assert!(
+            (plain.distance - inverted.distance - 2.0 * (plain.distance - camera.distance)).abs()
+                < 1e-3
+        );
+        assert_eq!(inverted.target, plain.target);
+
+        // Panning drags the old target along with the cursor
+        let mut panned = plain;
+        panned.pan(40.0, -30.0, viewport.y);
+        let target = panned.world_to_screen(plain.target, viewport).unwrap();
+        assert!((target - (viewport * 0.5 + Vec2::new(40.0, -30.0))).length() < 0.01);
+    }
+
+    #[test]
+    fn test_view_bookmarks() {
+        let mut camera = OrbitCamera::default();
+        let mut views = ViewBookmarks::new();
+        camera.orbit(50.0, 20.0);
+        camera.target = Vec3::new(1.0, 2.0, 3.0);
+        views.save_view("detail", &camera);
+        let saved = camera.pose();
+
+        camera.set_view(StandardView::Top);
+        camera.zoom(3.0, None);
+        assert!(views.restore_view("detail", &mut camera));
+        assert_eq!(camera.pose(), saved);
+        assert!(camera.transition.is_none());
+        assert!(!views.restore_view("missing", &mut camera));
+
+        views.save_view("detail", &OrbitCamera::default());
+        assert_eq!(views.names().collect::<Vec<_>>(), ["detail"]);
+        assert!(views.remove("detail").is_some());
+        assert!(views.is_empty());
+    }
+
+    #[test]
+    fn test_reversed_depth() {
+        let camera = OrbitCamera::default();
+        let projection = camera.projection_matrix(1.0);
+        let depth = |distance: f32| projection.project_point3(Vec3::new(0.0, 0.0, -distance)).z;
+        assert!((depth(camera.near) - 1.0).abs() < 1e-6);
+        assert!(depth(camera.far).abs() < 1e-6);
+        // Surfaces 0.01 apart 500 units away still get different depths
+        assert!(depth(500.0) > depth(500.01));
+
+        let ortho = OrbitCamera {
+            projection: Projection::Orthographic,
+            ..camera
+        };
+        let projection = ortho.projection_matrix(1.0);
+        assert!(
+            (projection
+                .project_point3(Vec3::new(0.0, 0.0, -ortho.near))
+                .z
+                - 1.0)
+                .abs()
+                < 1e-6
+        );
+        // Pick rays still start at the eye end
+        let viewport = Vec2::new(100.0, 100.0);
+        let ray = camera.screen_ray(viewport * 0.5, viewport);
+        assert!(ray.origin.distance(camera.eye_position()) < 1.0);
+        assert!(ray.direction.dot(camera.target - camera.eye_position()) > 0.0);
+    }
+
+    #[test]
+    fn test_fit_bounds() {
+        let bounds = BoundingBox3::new(Vec3::new(10.0, 0.0, 0.0), Vec3::new(12.0, 2.0, 2.0));
+        let mut camera = OrbitCamera::default();
+        camera.fit(&bounds, 0.5);
+        assert_eq!(camera.target, Vec3::new(11.0, 1.0, 1.0));
+        // Every corner projects inside the tall, narrow view
+        let view_proj = camera.view_projection(0.5);
+        for x in [10.0, 12.0] {
+            for y in [0.0, 2.0] {
+                for z in [0.0, 2.0] {
+                    let ndc = view_proj.project_point3(Vec3::new(x, y, z));
+                    assert!(ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0, "{:?}", ndc);
+                }
+            }
+        }
+        camera.set_projection(Projection::Orthographic);
+        camera.fit(&bounds, 0.5);
+        assert!((camera.ortho_scale * 0.5 - bounds.radius()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_standard_views() {
+        let mut camera = OrbitCamera {
+            distance: 10.0,
+            ..Default::default()
+        };
+        camera.set_view(StandardView::Right);
+        assert!(camera.animate(0.1));
+        let halfway = camera.azimuth_rad;
+        assert!(halfway > FRAC_PI_4 && halfway < FRAC_PI_2);
+        assert!(!camera.animate(1.0));
+        assert!((camera.eye_position() - Vec3::new(10.0, 0.0, 0.0)).length() < 1e-4);
+
+        // Back is a quarter turn on from Right, not three quarters back
+        camera.set_view(StandardView::Back);
+        camera.animate(1.0);
+        assert!((camera.azimuth_rad - PI).abs() < 1e-5);
+        assert!((camera.eye_position() - Vec3::new(0.0, 0.0, -10.0)).length() < 1e-4);
+
+        camera.set_view(StandardView::Isometric);
+        camera.animate(1.0);
+        let eye = camera.eye_position().normalize();
+        assert!((eye - Vec3::ONE.normalize()).length() < 1e-5);
+
+        // Dragging takes over from a running switch
+        camera.set_view(StandardView::Top);
+        camera.orbit(1.0, 0.0);
+        assert!(!camera.animate(0.1));
+    }
+
+    #[test]
+    fn test_turntable() {
+        let mut camera = OrbitCamera {
+            azimuth_rad: 0.0,
+            ..Default::default()
+        };
+        let turntable = Turntable { speed_deg: 90.0 };
+        turntable.advance(&mut camera, 0.5);
+        assert!((camera.azimuth_rad - FRAC_PI_4).abs() < 1e-6);
+        turntable.advance(&mut camera, 3.5);
+        assert!(camera.azimuth_rad.abs() < 1e-5);
+
+        let frames = Turntable { speed_deg: -10.0 }.frames(&camera, 4);
+        for (frame, azimuth) in frames.iter().zip([0.0, 3.0 * FRAC_PI_2, PI, FRAC_PI_2]) {
+            assert!((frame.azimuth_rad - azimuth).abs() < 1e-5);
+        }
+        assert!(frames.iter().all(|c| c.distance == camera.distance));
     }
 }
@@ -0,0 +1,292 @@
+use crate::renderer::camera::{OrbitCamera, Projection};
+use crate::renderer::grid::{LineVertex, AXIS_COLORS};
+use crate::renderer::pick::Ray;
+use glam::{Mat4, Quat, Vec2, Vec3};
+
+/// Handle length as a fraction of the view height at the gizmo
+const GIZMO_SCALE: f32 = 0.25;
+
+/// How close in pixels the cursor has to come to grab a handle
+const GRAB_PIXELS: f32 = 8.0;
+
+/// Segments of each rotation ring
+const RING_SEGMENTS: usize = 48;
+
+/// Line vertices of the largest gizmo, three full rings
+pub const MAX_GIZMO_VERTICES: usize = 3 * RING_SEGMENTS * 2;
+
+/// Color of the handle being dragged
+const ACTIVE_COLOR: [f32; 3] = [1.0, 0.85, 0.1];
+
+/// What dragging a handle does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GizmoMode {
+    /// Arrows sliding along the world axes
+    #[default]
+    Translate,
+    /// Rings turning about the world axes
+    Rotate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GizmoAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl GizmoAxis {
+    pub const ALL: [GizmoAxis; 3] = [GizmoAxis::X, GizmoAxis::Y, GizmoAxis::Z];
+
+    pub fn direction(&self) -> Vec3 {
+        match self {
+            GizmoAxis::X => Vec3::X,
+            GizmoAxis::Y => Vec3::Y,
+            GizmoAxis::Z => Vec3::Z,
+        }
+    }
+
+    fn color(&self) -> [f32; 3] {
+        AXIS_COLORS[*self as usize]
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    axis: GizmoAxis,
+    /// Last point grabbed on the axis line when translating, or the last
+    /// unit direction from the centre in the ring plane when rotating
+    last: Vec3,
+}
+
+/// Move/rotate handles around `origin`, turning cursor drags into world
+/// transform deltas for the selected object
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TransformGizmo {
+    pub mode: GizmoMode,
+    /// Centre of the handles and pivot of rotations
+    pub origin: Vec3,
+    drag: Option<Drag>,
+}
+
+impl TransformGizmo {
+    pub fn new(mode: GizmoMode, origin: Vec3) -> Self {
+        Self {
+            mode,
+            origin,
+            drag: None,
+        }
+    }
+
+    /// World length of the handles, keeping them the same size on screen
+    pub fn size(&self, camera: &OrbitCamera) -> f32 {
+        let height = match camera.projection {
+            Projection::Perspective => {
+                (camera.eye_position() - self.origin).length() * (camera.fov_rad * 0.5).tan()
+            }
+            Projection::Orthographic => camera.ortho_scale,
+        };
+        height * GIZMO_SCALE
+    }
+
+    /// Polyline of the handle for `axis`
+    fn handle(&self, axis: GizmoAxis, size: f32) -> Vec<Vec3> {
+        let direction = axis.direction();
+        match self.mode {
+            GizmoMode::Translate => vec![self.origin, self.origin + direction * size],
+            GizmoMode::Rotate => {
+                let (u, v) = direction.any_orthonormal_pair();
+                (0..=RING_SEGMENTS)
+                    .map(|i| {
+                        let angle = std::f32::consts::TAU * i as f32 / RING_SEGMENTS as f32;
+                        self.origin + (u * angle.cos() + v * angle.sin()) * size
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Handles as a line list, the dragged one highlighted
+    pub fn lines(&self, camera: &OrbitCamera) -> Vec<LineVertex> {
+        let size = self.size(camera);
+        let mut lines = Vec::with_capacity(MAX_GIZMO_VERTICES);
+        for axis in GizmoAxis::ALL {
+            let color = match self.drag {
+                Some(drag) if drag.axis == axis => ACTIVE_COLOR,
+                _ => axis.color(),
+            };
+            for segment in self.handle(axis, size).windows(2) {
+                for point in segment {
+                    lines.push(LineVertex {
+                        position: point.to_array(),
+                        color,
+                    });
+                }
+            }
+        }
+        lines
+    }
+
+    /// Whether the `axis` handle can be dragged from where the camera is:
+    /// not an arrow pointing at the eye nor a ring seen edge-on
+    fn grabbable(&self, camera: &OrbitCamera, axis: GizmoAxis) -> bool {
+        let forward = (camera.target - camera.eye_position()).normalize();
+        let facing = axis.direction().dot(forward).abs();
+        match self.mode {
+            GizmoMode::Translate => facing < 0.98,
+            GizmoMode::Rotate => facing > 0.2,
+        }
+    }
+
+    /// Handle within grabbing distance of pixel `screen_pos`, nearest first
+    pub fn hit(&self, camera: &OrbitCamera, screen_pos: Vec2, viewport: Vec2) -> Option<GizmoAxis> {
+        let size = self.size(camera);
+        GizmoAxis::ALL
+            .into_iter()
+            .filter(|&axis| self.grabbable(camera, axis))
+            .filter_map(|axis| {
+                let points: Vec<Option<Vec2>> = self
+                    .handle(axis, size)
+                    .into_iter()
+                    .map(|p| camera.world_to_screen(p, viewport))
+                    .collect();
+                let distance = points
+                    .windows(2)
+                    .filter_map(|s| Some(segment_distance(screen_pos, s[0]?, s[1]?)))
+                    .fold(f32::INFINITY, f32::min);
+                (distance <= GRAB_PIXELS).then_some((axis, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(axis, _)| axis)
+    }
+
+    /// Grab the handle under `screen_pos`; false when there is none or it
+    /// cannot be dragged from this viewing direction
+    pub fn begin_drag(&mut self, camera: &OrbitCamera, screen_pos: Vec2, viewport: Vec2) -> bool {
+        self.drag = self.hit(camera, screen_pos, viewport).and_then(|axis| {
+            let ray = camera.screen_ray(screen_pos, viewport);
+            let last = self.anchor(camera, axis, &ray)?;
+            Some(Drag { axis, last })
+        });
+        self.drag.is_some()
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Handle being dragged
+    pub fn active_axis(&self) -> Option<GizmoAxis> {
+        self.drag.map(|drag| drag.axis)
+    }
+
+    /// Follow the cursor to `screen_pos`, returning the world transform
+    /// change since the previous call; the handles move along
+    pub fn drag(&mut self, camera: &OrbitCamera, screen_pos: Vec2, viewport: Vec2) -> Mat4 {
+        let Some(drag) = self.drag else {
+            return Mat4::IDENTITY;
+        };
+        let ray = camera.screen_ray(screen_pos, viewport);
+        let Some(now) = self.anchor(camera, drag.axis, &ray) else {
+            return Mat4::IDENTITY;
+        };
+        self.drag = Some(Drag { last: now, ..drag });
+        let direction = drag.axis.direction();
+        match self.mode {
+            GizmoMode::Translate => {
+                let offset = now - drag.last;
+                self.origin += offset;
+                Mat4::from_translation(offset)
+            }
+            GizmoMode::Rotate => {
+                let angle = direction
+                    .dot(drag.last.cross(now))
+                    .atan2(drag.last.dot(now));
+                Mat4::from_translation(self.origin)
+                    * Mat4::from_quat(Quat::from_axis_angle(direction, angle))
+                    * Mat4::from_translation(-self.origin)
+            }
+        }
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    /// Where `ray` grabs the `axis` handle, see [`Drag::last`]
+    fn anchor(&self, camera: &OrbitCamera, axis: GizmoAxis, ray: &Ray) -> Option<Vec3> {
+        let direction = axis.direction();
+        match self.mode {
+            GizmoMode::Translate => {
+                // Slide in the plane through the axis that faces the eye most
+                let forward = (camera.target - camera.eye_position()).normalize();
+                let normal = forward - direction * direction.dot(forward);
+                let point = ray_plane(ray, self.origin, normal.try_normalize()?)?;
+                Some(self.origin + direction * direction.dot(point - self.origin))
+            }
+            GizmoMode::Rotate => {
+                let point = ray_plane(ray, self.origin, direction)?;
+                (point - self.origin).try_normalize()
+            }
+        }
+    }
+}
+
+/// Where `ray` crosses the plane through `point` with unit `normal`
+fn ray_plane(ray: &Ray, point: Vec3, normal: Vec3) -> Option<Vec3> {
+    let denominator = ray.direction.dot(normal);
+    if denominator.abs() < 1e-6 {
+        return None;
+    }
+    let t = (point - ray.origin).dot(normal) / denominator;
+    (t >= 0.0).then(|| ray.at(t))
+}
+
+fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_squared().max(1e-12)).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gizmo_drags() {
+        // Looking down -Z, X to the right and Y up
+        let camera = OrbitCamera {
+            distance: 20.0,
+            azimuth_rad: 0.0,
+            elevation_rad: 0.0,
+            ..Default::default()
+        };
+        let viewport = Vec2::new(400.0, 300.0);
+        let mut gizmo = TransformGizmo::new(GizmoMode::Translate, Vec3::ZERO);
+        let size = gizmo.size(&camera);
+        let tip = camera
+            .world_to_screen(Vec3::X * size * 0.5, viewport)
+            .unwrap();
+        assert_eq!(gizmo.hit(&camera, tip, viewport), Some(GizmoAxis::X));
+        assert_eq!(gizmo.hit(&camera, Vec2::new(10.0, 10.0), viewport), None);
+
+        assert!(gizmo.begin_drag(&camera, tip, viewport));
+        let delta = gizmo.drag(&camera, tip + Vec2::new(40.0, 25.0), viewport);
+        let moved = delta.transform_point3(Vec3::ZERO);
+        assert!(moved.x > 0.0 && moved.y == 0.0 && moved.z == 0.0);
+        assert_eq!(gizmo.origin, moved);
+        gizmo.end_drag();
+        assert!(!gizmo.is_dragging());
+
+        // A quarter turn on the ring facing the eye
+        let mut gizmo = TransformGizmo::new(GizmoMode::Rotate, Vec3::new(1.0, 0.0, 0.0));
+        let size = gizmo.size(&camera);
+        let at = |p: Vec3| camera.world_to_screen(p, viewport).unwrap();
+        let right = at(gizmo.origin + Vec3::X * size);
+        assert!(gizmo.begin_drag(&camera, right, viewport));
+        assert_eq!(gizmo.active_axis(), Some(GizmoAxis::Z));
+        let delta = gizmo.drag(&camera, at(gizmo.origin + Vec3::Y * size), viewport);
+        let turned = delta.transform_point3(Vec3::new(2.0, 0.0, 0.0));
+        assert!((turned - Vec3::new(1.0, 1.0, 0.0)).length() < 1e-3);
+    }
+}
@@ -0,0 +1,397 @@
+use crate::renderer::camera::OrbitCamera;
+use crate::renderer::gizmo::MAX_GIZMO_VERTICES;
+use crate::renderer::mesh::BoundingBox3;
+use crate::renderer::overlay::MAX_OVERLAY_VERTICES;
+use crate::renderer::DEPTH_COMPARE;
+use bytemuck::{Pod, Zeroable};
+use eframe::wgpu;
+use eframe::wgpu::util::DeviceExt;
+use glam::{Mat3, Mat4, Vec3};
+
+/// Grid lines drawn on each side of the centre line
+const HALF_LINES: i32 = 20;
+
+/// Every n-th grid line is a major one
+const MAJOR_EVERY: i32 = 10;
+
+/// Side of the orientation gizmo in pixels
+const GIZMO_SIZE: u32 = 80;
+
+pub const AXIS_COLORS: [[f32; 3]; 3] = [[0.9, 0.2, 0.2], [0.2, 0.8, 0.2], [0.25, 0.4, 1.0]];
+
+/// Grid lines, then the world axes
+const MAX_VERTICES: usize = (2 * HALF_LINES as usize + 1) * 4 + 6;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+impl LineVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+        0 => Float32x3,  // position
+        1 => Float32x3,  // color
+    ];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<LineVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Spacing of the grid lines: a power of ten giving a few dozen cells
+/// across a view `view_height` world units from centre to top
+pub fn grid_spacing(view_height: f32) -> f32 {
+    10f32.powf((view_height.max(1e-6) * 0.25).log10().floor())
+}
+
+/// Grid on the XY plane around the point under the camera target, snapped
+/// to the spacing so lines stay put while panning, plus the world axes;
+/// `colors` are of the minor and major lines
+pub fn grid_lines(camera: &OrbitCamera, colors: [[f32; 3]; 2]) -> Vec<LineVertex> {
+    let spacing = grid_spacing(camera.view_height());
+    let extent = spacing * HALF_LINES as f32;
+    let centre = [
+        (camera.target.x / spacing).round() as i32,
+        (camera.target.y / spacing).round() as i32,
+    ];
+    let mut lines = Vec::with_capacity(MAX_VERTICES);
+    for i in -HALF_LINES..=HALF_LINES {
+        for axis in 0..2 {
+            let index = centre[axis] + i;
+            let color = colors[usize::from(index % MAJOR_EVERY == 0)];
+            let along = index as f32 * spacing;
+            let across = centre[1 - axis] as f32 * spacing;
+            for end in [-extent, extent] {
+                let mut position = [0.0; 3];
+                position[axis] = along;
+                position[1 - axis] = across + end;
+                lines.push(LineVertex { position, color });
+            }
+        }
+    }
+    lines.extend(axis_lines(spacing * MAJOR_EVERY as f32 * 0.5));
+    lines
+}
+
+/// Extent of [`grid_lines`] for `camera`
+pub fn grid_bounds(camera: &OrbitCamera) -> BoundingBox3 {
+    BoundingBox3::from_points(
+        grid_lines(camera, [[0.0; 3]; 2])
+            .iter()
+            .map(|vertex| Vec3::from(vertex.position)),
+    )
+    .expect("the grid has lines")
+}
+
+/// X, Y and Z axis from the origin, `length` long
+fn axis_lines(length: f32) -> Vec<LineVertex> {
+    let mut lines = Vec::with_capacity(6);
+    for (axis, color) in AXIS_COLORS.into_iter().enumerate() {
+        let mut position = [0.0; 3];
+        lines.push(LineVertex { position, color });
+        position[axis] = length;
+        lines.push(LineVertex { position, color });
+    }
+    lines
+}
+
+/// Line uniforms of one draw
+struct LineUniforms {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Ground grid, origin axes, transform handles, annotation lines and the
+/// orientation gizmo drawn in the bottom left corner of the viewport
+pub struct GridRenderer {
+    /// Depth-tested against the parts, without writing depth, and darkened
+    /// in the key light shadow
+    pipeline: wgpu::RenderPipeline,
+    /// Drawn on top of everything
+    overlay_pipeline: wgpu::RenderPipeline,
+    grid_vertices: wgpu::Buffer,
+    gizmo_vertices: wgpu::Buffer,
+    handle_vertices: wgpu::Buffer,
+    overlay_vertices: wgpu::Buffer,
+    world: LineUniforms,
+    gizmo: LineUniforms,
+}
+
+impl GridRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+        shadow_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Line Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("shadow_lookup.wgsl"),
+                    include_str!("line.wgsl")
+                )
+                .into(),
+            ),
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Line Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Line Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, shadow_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |label, fragment_entry, depth_compare| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[LineVertex::desc()],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(fragment_entry),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: surface_format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::LineList,
+                    ..Default::default()
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: false,
+                    depth_compare,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+        let uniforms = |label| {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: std::mem::size_of::<[[f32; 4]; 4]>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(label),
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+            LineUniforms { buffer, bind_group }
+        };
+
+        Self {
+            pipeline: pipeline("Grid Pipeline", "fs_shadowed", DEPTH_COMPARE),
+            overlay_pipeline: pipeline("Gizmo Pipeline", "fs_main", wgpu::CompareFunction::Always),
+            grid_vertices: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Grid Vertex Buffer"),
+                size: (MAX_VERTICES * std::mem::size_of::<LineVertex>()) as u64,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            gizmo_vertices: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Gizmo Vertex Buffer"),
+                contents: bytemuck::cast_slice(&axis_lines(1.0)),
+                usage: wgpu::BufferUsages::VERTEX,
+            }),
+            handle_vertices: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Handle Vertex Buffer"),
+                size: (MAX_GIZMO_VERTICES * std::mem::size_of::<LineVertex>()) as u64,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            overlay_vertices: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Overlay Vertex Buffer"),
+                size: (MAX_OVERLAY_VERTICES * std::mem::size_of::<LineVertex>()) as u64,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            world: uniforms("Grid Uniforms"),
+            gizmo: uniforms("Gizmo Uniforms"),
+        }
+    }
+
+    /// Upload the lines and matrices for a frame seen from `camera`
+    pub fn prepare(
+        &self,
+        queue: &wgpu::Queue,
+        camera: &OrbitCamera,
+        aspect: f32,
+        colors: [[f32; 3]; 2],
+    ) {
+        let lines = grid_lines(camera, colors);
+        queue.write_buffer(&self.grid_vertices, 0, bytemuck::cast_slice(&lines));
+
+        let view_proj = camera.view_projection(aspect);
+        queue.write_buffer(
+            &self.world.buffer,
+            0,
+            bytemuck::cast_slice(&view_proj.to_cols_array_2d()),
+        );
+        // The gizmo turns with the camera but never moves or scales
+        let rotation = Mat4::from_mat3(Mat3::from_mat4(camera.view_matrix()));
+        let projection = Mat4::orthographic_rh(-1.2, 1.2, -1.2, 1.2, -2.0, 2.0);
+        queue.write_buffer(
+            &self.gizmo.buffer,
+            0,
+            bytemuck::cast_slice(&(projection * rotation).to_cols_array_2d()),
+        );
+    }
+
+    /// Draw the grid and axes against the scene depth; `shadow` is the
+    /// [`ShadowMap`](crate::renderer::shadow::ShadowMap) bind group
+    pub fn draw_grid(&self, render_pass: &mut wgpu::RenderPass<'_>, shadow: &wgpu::BindGroup) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.world.bind_group, &[]);
+        render_pass.set_bind_group(1, shadow, &[]);
+        render_pass.set_vertex_buffer(0, self.grid_vertices.slice(..));
+        render_pass.draw(0..MAX_VERTICES as u32, 0..1);
+    }
+
+    /// Upload transform handle lines, returning how many vertices to draw
+    pub fn prepare_handles(&self, queue: &wgpu::Queue, lines: &[LineVertex]) -> u32 {
+        let lines = &lines[..lines.len().min(MAX_GIZMO_VERTICES)];
+        queue.write_buffer(&self.handle_vertices, 0, bytemuck::cast_slice(lines));
+        lines.len() as u32
+    }
+
+    /// Draw `count` handle vertices over the scene
+    pub fn draw_handles(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        shadow: &wgpu::BindGroup,
+        count: u32,
+    ) {
+        self.draw_on_top(render_pass, shadow, &self.handle_vertices, count);
+    }
+
+    /// Upload annotation lines, returning how many vertices to draw
+    pub fn prepare_overlay(&self, queue: &wgpu::Queue, lines: &[LineVertex]) -> u32 {
+        let lines = &lines[..lines.len().min(MAX_OVERLAY_VERTICES)];
+        queue.write_buffer(&self.overlay_vertices, 0, bytemuck::cast_slice(lines));
+        lines.len() as u32
+    }
+
+    /// Draw `count` annotation vertices over the scene
+    pub fn draw_overlay(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        shadow: &wgpu::BindGroup,
+        count: u32,
+    ) {
+        self.draw_on_top(render_pass, shadow, &self.overlay_vertices, count);
+    }
+
+    fn draw_on_top(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        shadow: &wgpu::BindGroup,
+        vertices: &wgpu::Buffer,
+        count: u32,
+    ) {
+        render_pass.set_pipeline(&self.overlay_pipeline);
+        render_pass.set_bind_group(0, &self.world.bind_group, &[]);
+        render_pass.set_bind_group(1, shadow, &[]);
+        render_pass.set_vertex_buffer(0, vertices.slice(..));
+        render_pass.draw(0..count, 0..1);
+    }
+
+    /// Draw the gizmo over the bottom left corner of a `width` × `height`
+    /// target
+    pub fn draw_gizmo(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        shadow: &wgpu::BindGroup,
+        size: (u32, u32),
+    ) {
+        let (width, height) = size;
+        if width >= GIZMO_SIZE && height >= GIZMO_SIZE {
+            render_pass.set_viewport(
+                0.0,
+                (height - GIZMO_SIZE) as f32,
+                GIZMO_SIZE as f32,
+                GIZMO_SIZE as f32,
+                0.0,
+                1.0,
+            );
+            render_pass.set_pipeline(&self.overlay_pipeline);
+            render_pass.set_bind_group(0, &self.gizmo.bind_group, &[]);
+            // Unused by the gizmo, but part of the shared layout
+            render_pass.set_bind_group(1, shadow, &[]);
+            render_pass.set_vertex_buffer(0, self.gizmo_vertices.slice(..));
+            render_pass.draw(0..6, 0..1);
+            render_pass.set_viewport(0.0, 0.0, width as f32, height as f32, 0.0, 1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    #[test]
+    fn test_adaptive_grid() {
+        assert_eq!(grid_spacing(40.0), 10.0);
+        assert_eq!(grid_spacing(39.0), 1.0);
+        assert_eq!(grid_spacing(2.0), 0.1);
+
+        let camera = OrbitCamera {
+            target: Vec3::new(23.0, -7.0, 5.0),
+            distance: 50.0,
+            ..Default::default()
+        };
+        let major = [0.25; 3];
+        let lines = grid_lines(&camera, [[0.13; 3], major]);
+        assert_eq!(lines.len(), MAX_VERTICES);
+        // Lines lie on XY, snapped to the 1 mm spacing around the target
+        let grid = &lines[..lines.len() - 6];
+        assert!(grid.iter().all(|v| v.position[2] == 0.0));
+        assert!(grid
+            .iter()
+            .all(|v| v.position[0].fract() == 0.0 && v.position[1].fract() == 0.0));
+        let xs = grid.iter().map(|v| v.position[0]);
+        assert_eq!(xs.clone().fold(f32::INFINITY, f32::min), 3.0);
+        assert_eq!(xs.fold(f32::NEG_INFINITY, f32::max), 43.0);
+        // x = 30 is a major line
+        assert!(grid
+            .chunks_exact(2)
+            .any(|l| l[0].position[0] == 30.0 && l[1].position[0] == 30.0 && l[0].color == major));
+        assert_eq!(lines[lines.len() - 1].position, [0.0, 0.0, 5.0]);
+    }
+}
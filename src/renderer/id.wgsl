@@ -0,0 +1,45 @@
+struct Uniforms {
+    view_proj: mat4x4<f32>,
+    eye_pos: vec3<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct Object {
+    model: mat4x4<f32>,
+    normal: mat4x4<f32>,
+    color: vec4<f32>,
+    // Object index + 1, 0 is background
+    id: vec4<u32>,
+};
+
+@group(1) @binding(0)
+var<uniform> object: Object;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    // B-rep face id + 1, 0 for meshes without
+    @location(4) face: u32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) @interpolate(flat) face: u32,
+};
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = uniforms.view_proj * object.model * vec4<f32>(in.position, 1.0);
+    out.face = in.face;
+    return out;
+}
+
+// Vertices are never shared by two faces, so the provoking vertex's face
+// is the triangle's
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec2<u32> {
+    return vec2<u32>(object.id.x, in.face);
+}
@@ -0,0 +1,159 @@
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvError, Sender, TryRecvError};
+use std::sync::Arc;
+
+/// What a job gave, or the message it panicked with
+pub type JobResult<T> = Result<T, String>;
+
+type Job<T> = Box<dyn FnOnce() -> T + Send>;
+
+/// Work such as tessellation run on a worker thread, so it never stalls the
+/// UI. Requests run one at a time; any superseded before they start are
+/// skipped, and only the result of the newest request is handed back.
+pub struct Jobs<T> {
+    /// Requests for the worker, which stops once this is dropped
+    requests: Sender<(u64, Job<T>)>,
+    /// Results from the worker, which holds the only sender
+    results: Receiver<(u64, JobResult<T>)>,
+    /// Number of the newest request, shared with the worker
+    latest: Arc<AtomicU64>,
+    /// Whether the newest request is still running
+    busy: bool,
+}
+
+impl<T: Send + 'static> Default for Jobs<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send + 'static> Jobs<T> {
+    pub fn new() -> Self {
+        let (requests, jobs) = channel::<(u64, Job<T>)>();
+        let (sender, results) = channel();
+        let latest = Arc::new(AtomicU64::new(0));
+        let newest = latest.clone();
+        std::thread::spawn(move || {
+            for (id, job) in jobs {
+                if id != newest.load(Ordering::Acquire) {
+                    continue;
+                }
+                let result = std::panic::catch_unwind(AssertUnwindSafe(job)).map_err(|payload| {
+                    payload
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "the job panicked".to_string())
+                });
+                if sender.send((id, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            requests,
+            results,
+            latest,
+            busy: false,
+        }
+    }
+
+    /// Queue `job`, superseding any request not yet handed back
+    pub fn submit(&mut self, job: impl FnOnce() -> T + Send + 'static) {
+        let id = self.latest.fetch_add(1, Ordering::AcqRel) + 1;
+        // The worker only stops once the sender is dropped
+        self.busy = self.requests.send((id, Box::new(job))).is_ok();
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.busy
+    }
+
+    /// Result of the newest request if it has finished since the last call
+    pub fn poll(&mut self) -> Option<JobResult<T>> {
+        let latest = self.latest.load(Ordering::Acquire);
+        let mut result = None;
+        loop {
+            match self.results.try_recv() {
+                Ok((id, value)) if id == latest => result = Some(value),
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.busy = false;
+                    break;
+                }
+            }
+        }
+        self.finish(result)
+    }
+
+    /// Block until the newest request finishes; `None` when none is running
+    pub fn wait(&mut self) -> Option<JobResult<T>> {
+        let latest = self.latest.load(Ordering::Acquire);
+        while self.busy {
+            match self.results.recv() {
+                Ok((id, value)) if id == latest => return self.finish(Some(value)),
+                Ok(_) => {}
+                // The worker is gone, so nothing more will come
+                Err(RecvError) => self.busy = false,
+            }
+        }
+        None
+    }
+
+    fn finish(&mut self, result: Option<JobResult<T>>) -> Option<JobResult<T>> {
+        if result.is_some() {
+            self.busy = false;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Barrier;
+
+    #[test]
+    fn test_newest_result_wins() {
+        let mut jobs = Jobs::new();
+        assert!(jobs.poll().is_none());
+        assert!(jobs.wait().is_none());
+
+        // The second request is superseded while the first runs, so it is
+        // skipped, and the first one's result is dropped
+        let barrier = Arc::new(Barrier::new(2));
+        let runs = Arc::new(AtomicUsize::new(0));
+        let (hold, count) = (barrier.clone(), runs.clone());
+        jobs.submit(move || {
+            // Started, then held until the others are queued
+            hold.wait();
+            hold.wait();
+            count.fetch_add(1, Ordering::SeqCst);
+            1
+        });
+        barrier.wait();
+        for n in [2, 3] {
+            let count = runs.clone();
+            jobs.submit(move || {
+                count.fetch_add(1, Ordering::SeqCst);
+                n
+            });
+        }
+        barrier.wait();
+        assert!(jobs.is_busy());
+        assert_eq!(jobs.wait(), Some(Ok(3)));
+        assert!(!jobs.is_busy());
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert_eq!(jobs.poll(), None);
+
+        // A panic is handed back instead of leaving the job running
+        jobs.submit(|| panic!("bad geometry"));
+        assert_eq!(jobs.wait(), Some(Err("bad geometry".to_string())));
+        assert!(!jobs.is_busy());
+        jobs.submit(|| 4);
+        assert_eq!(jobs.wait(), Some(Ok(4)));
+    }
+}
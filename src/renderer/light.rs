@@ -0,0 +1,124 @@
+use crate::renderer::matcap::ShadingMode;
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// Light shining from `direction`, given in camera space (x right, y up,
+/// z towards the viewer) so it turns with the view
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DirectionalLight {
+    pub direction: [f32; 3],
+    /// Linear RGB
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+/// Blinn-Phong lighting: a key light, a softer fill light from the other
+/// side and an ambient term, so faces turned away from the key light keep
+/// their shape
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Lighting {
+    pub key: DirectionalLight,
+    pub fill: DirectionalLight,
+    /// Linear RGB added to every surface
+    pub ambient: [f32; 3],
+    /// Strength of the highlights of both lights
+    pub specular: f32,
+    /// Blinn-Phong exponent; higher gives smaller highlights
+    pub shininess: f32,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            key: DirectionalLight {
+                direction: [-0.4, 0.6, 0.7],
+                color: [1.0, 1.0, 1.0],
+                intensity: 0.8,
+            },
+            fill: DirectionalLight {
+                direction: [0.6, -0.3, 0.5],
+                color: [0.85, 0.9, 1.0],
+                intensity: 0.35,
+            },
+            ambient: [0.15, 0.15, 0.15],
+            specular: 0.25,
+            shininess: 32.0,
+        }
+    }
+}
+
+/// Shader layout of [`Lighting`], with directions in world space
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct LightUniforms {
+    /// Unit vector towards the light in `xyz`
+    pub key_direction: [f32; 4],
+    /// Color times intensity
+    pub key_color: [f32; 4],
+    pub fill_direction: [f32; 4],
+    pub fill_color: [f32; 4],
+    /// Ambient color, and the specular strength in `w`
+    pub ambient: [f32; 4],
+    /// Shininess in `x`
+    pub shininess: [f32; 4],
+    /// Camera right and up axes in world space, for matcap lookups
+    pub camera_right: [f32; 4],
+    pub camera_up: [f32; 4],
+    /// [`ShadingMode`] in `x`: 0 lit, 1 matcap
+    pub mode: [u32; 4],
+}
+
+impl LightUniforms {
+    /// Lights of `lighting` seen through a camera with `view` matrix
+    pub fn new(lighting: &Lighting, view: Mat4, shading: ShadingMode) -> Self {
+        let to_world = view.inverse();
+        let direction = |light: &DirectionalLight| {
+            let world = to_world.transform_vector3(Vec3::from(light.direction));
+            world.normalize_or_zero().extend(0.0).to_array()
+        };
+        let color = |light: &DirectionalLight| {
+            (Vec3::from(light.color) * light.intensity)
+                .extend(1.0)
+                .to_array()
+        };
+        let [r, g, b] = lighting.ambient;
+        Self {
+            key_direction: direction(&lighting.key),
+            key_color: color(&lighting.key),
+            fill_direction: direction(&lighting.fill),
+            fill_color: color(&lighting.fill),
+            ambient: [r, g, b, lighting.specular],
+            shininess: [lighting.shininess.max(1.0), 0.0, 0.0, 0.0],
+            camera_right: to_world.transform_vector3(Vec3::X).extend(0.0).to_array(),
+            camera_up: to_world.transform_vector3(Vec3::Y).extend(0.0).to_array(),
+            mode: [shading as u32, 0, 0, 0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::camera::OrbitCamera;
+
+    #[test]
+    fn test_lights_follow_camera() {
+        let mut lighting = Lighting::default();
+        lighting.key.direction = [0.0, 0.0, 2.0];
+        lighting.fill.intensity = 0.5;
+
+        // A light along camera z shines from the eye
+        let camera = OrbitCamera::default();
+        let uniforms = LightUniforms::new(&lighting, camera.view_matrix(), ShadingMode::Lit);
+        let towards_eye = (camera.eye_position() - camera.target).normalize();
+        let key = Vec3::from_slice(&uniforms.key_direction);
+        assert!((key - towards_eye).length() < 1e-5);
+        assert_eq!(uniforms.fill_color, [0.425, 0.45, 0.5, 1.0]);
+        assert_eq!(uniforms.ambient[3], lighting.specular);
+        let up = Vec3::from_slice(&uniforms.camera_up);
+        assert!(up.dot(towards_eye).abs() < 1e-5 && up.y > 0.0);
+        assert_eq!(uniforms.mode[0], 0);
+    }
+}
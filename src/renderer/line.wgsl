@@ -0,0 +1,42 @@
+@group(0) @binding(0)
+var<uniform> view_proj: mat4x4<f32>;
+
+// Key light shadow map, see shadow_lookup.wgsl
+@group(1) @binding(0)
+var<uniform> shadow: Shadow;
+@group(1) @binding(1)
+var shadow_map: texture_depth_2d;
+@group(1) @binding(2)
+var shadow_sampler: sampler_comparison;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+    @location(1) world_position: vec3<f32>,
+};
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = view_proj * vec4<f32>(in.position, 1.0);
+    out.color = in.color;
+    out.world_position = in.position;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
+}
+
+// Grid lines darken where parts shadow the ground
+@fragment
+fn fs_shadowed(in: VertexOutput) -> @location(0) vec4<f32> {
+    let lit = 0.4 + 0.6 * shadow_factor(in.world_position);
+    return vec4<f32>(in.color * lit, 1.0);
+}
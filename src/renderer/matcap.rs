@@ -0,0 +1,159 @@
+use crate::renderer::texture::TextureImage;
+use crate::sketch::SketchResult;
+use eframe::wgpu;
+use eframe::wgpu::util::DeviceExt;
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Side of the built-in matcap in pixels
+const DEFAULT_MATCAP_SIZE: u32 = 128;
+
+/// How surfaces are shaded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShadingMode {
+    /// Key, fill and ambient lights with shadows, see [`super::light::Lighting`]
+    #[default]
+    Lit,
+    /// Color looked up in a matcap image by the view-space normal, so the
+    /// look is baked into the image and costs one texture fetch
+    Matcap,
+}
+
+impl ShadingMode {
+    pub const ALL: [ShadingMode; 2] = [ShadingMode::Lit, ShadingMode::Matcap];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShadingMode::Lit => "Lit",
+            ShadingMode::Matcap => "Matcap",
+        }
+    }
+}
+
+/// Material capture: a lit sphere seen head-on, as sRGB RGBA rows from the
+/// top; the part colors multiply it
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatcapImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl Default for MatcapImage {
+    /// Soft white clay lit from the upper left
+    fn default() -> Self {
+        let size = DEFAULT_MATCAP_SIZE;
+        let light = Vec3::new(-0.4, 0.5, 0.77).normalize();
+        let mut rgba = Vec::with_capacity((size * size * 4) as usize);
+        for row in 0..size {
+            for column in 0..size {
+                let x = (column as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                let y = 1.0 - (row as f32 + 0.5) / size as f32 * 2.0;
+                // Outside the sphere repeat its rim, for filtering at the edge
+                let flat = glam::Vec2::new(x, y).clamp_length_max(1.0);
+                let normal = flat.extend((1.0 - flat.length_squared()).max(0.0).sqrt());
+                let diffuse = normal.dot(light).max(0.0);
+                let specular = normal
+                    .dot((light + Vec3::Z).normalize())
+                    .max(0.0)
+                    .powf(40.0);
+                let rim = (1.0 - normal.z).powi(3) * 0.15;
+                let value = (0.18 + 0.72 * diffuse + 0.25 * specular + rim).min(1.0);
+                let srgb = (value.powf(1.0 / 2.2) * 255.0).round() as u8;
+                rgba.extend_from_slice(&[srgb, srgb, srgb, 255]);
+            }
+        }
+        Self {
+            width: size,
+            height: size,
+            rgba,
+        }
+    }
+}
+
+impl MatcapImage {
+    /// Read an 8- or 16-bit gray, RGB or RGBA PNG
+    pub fn load(path: &Path) -> SketchResult<Self> {
+        let TextureImage {
+            width,
+            height,
+            rgba,
+        } = TextureImage::load(path)?;
+        Ok(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+}
+
+/// Matcap texture and its sampler, bound with the camera uniforms
+pub struct Matcap {
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl Matcap {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, image: &MatcapImage) -> Self {
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Matcap Texture"),
+                size: wgpu::Extent3d {
+                    width: image.width,
+                    height: image.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &image.rgba,
+        );
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Matcap Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            sampler,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn test_matcap_image() {
+        let image = MatcapImage::default();
+        assert_eq!(image.rgba.len(), (image.width * image.height * 4) as usize);
+        let texel = |x: u32, y: u32| image.rgba[((y * image.width + x) * 4) as usize];
+        // Brighter towards the light in the upper left than in the lower right
+        let quarter = image.width / 4;
+        assert!(texel(quarter, quarter) > texel(3 * quarter, 3 * quarter));
+
+        // Round trip through a PNG file
+        let path = std::env::temp_dir().join("truck_playground_matcap_test.png");
+        let mut encoder =
+            png::Encoder::new(File::create(&path).unwrap(), image.width, image.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .unwrap()
+            .write_image_data(&image.rgba)
+            .unwrap();
+        assert_eq!(MatcapImage::load(&path).unwrap(), image);
+        assert!(MatcapImage::load(&path.with_extension("missing")).is_err());
+    }
+}
@@ -1,19 +1,199 @@
+use crate::model::Assembly;
+use crate::sketch::{SketchError, SketchResult};
 use bytemuck::{Pod, Zeroable};
+use rayon::prelude::*;
 use eframe::wgpu;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
 use truck_meshalgo::prelude::*;
-use truck_modeling::Solid;
+use truck_modeling::{builder, Edge, EdgeID, Face, Shell, Solid, Vertex as TopoVertex, Wire};
+
+/// Chord tolerance for showing an assembly: finer for smaller models
+pub fn display_tolerance(assembly: &Assembly) -> f64 {
+    let bounds: BoundingBox<Point3> = assembly
+        .placed_solids()
+        .iter()
+        .flat_map(|solid| solid.vertex_iter().map(|v| v.point()))
+        .collect();
+    (bounds.diameter() * 0.001).max(1e-6)
+}
+
+/// Times a face's chord tolerance is halved to meet
+/// [`MeshQuality::angle_tol`]
+const MAX_REFINEMENTS: usize = 8;
+
+/// Rounds of edge bisection for [`MeshQuality::max_edge_len`]
+const MAX_EDGE_SPLITS: usize = 16;
+
+/// Corners closer than this fraction of the mesh size are one point to
+/// [`GpuMesh::decimate`]
+const WELD_FRACTION: f64 = 1e-6;
+
+/// How much more moving an open border costs than moving a surface
+const BORDER_WEIGHT: f64 = 100.0;
+
+/// Vertices [`GpuMesh::optimize`] assumes the GPU keeps in its post
+/// transform cache
+const VERTEX_CACHE_SIZE: usize = 32;
+
+/// How closely a tessellated mesh follows its solid; tighter bounds give
+/// bigger meshes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshQuality {
+    /// Largest distance between the mesh and the surfaces
+    pub chord_tol: f64,
+    /// Largest angle (radians) between a triangle and the surface normals
+    /// at its corners
+    pub angle_tol: f64,
+    /// Longest triangle edge
+    pub max_edge_len: f64,
+    /// How vertex normals are made
+    pub normals: NormalMode,
+}
+
+impl Default for MeshQuality {
+    fn default() -> Self {
+        Self {
+            chord_tol: 0.01,
+            angle_tol: 15f64.to_radians(),
+            max_edge_len: f64::INFINITY,
+            normals: NormalMode::default(),
+        }
+    }
+}
+
+impl MeshQuality {
+    /// Every bound loosened `factor` times, angles up to a half turn
+    pub fn coarser(&self, factor: f64) -> Self {
+        Self {
+            chord_tol: self.chord_tol * factor,
+            angle_tol: (self.angle_tol * factor).min(std::f64::consts::PI),
+            max_edge_len: self.max_edge_len * factor,
+            normals: self.normals,
+        }
+    }
+}
+
+/// How finely the scene is tessellated, relative to its size
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeshDetail {
+    Coarse,
+    #[default]
+    Medium,
+    Fine,
+}
+
+impl MeshDetail {
+    pub const ALL: [MeshDetail; 3] = [MeshDetail::Coarse, MeshDetail::Medium, MeshDetail::Fine];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MeshDetail::Coarse => "Coarse",
+            MeshDetail::Medium => "Medium",
+            MeshDetail::Fine => "Fine",
+        }
+    }
+
+    /// Bounds for a scene meshed at `tolerance` by [`display_tolerance`]
+    pub fn quality(&self, tolerance: f64) -> MeshQuality {
+        match self {
+            MeshDetail::Coarse => MeshQuality {
+                chord_tol: tolerance * 4.0,
+                angle_tol: 30f64.to_radians(),
+                max_edge_len: f64::INFINITY,
+                normals: NormalMode::default(),
+            },
+            MeshDetail::Medium => MeshQuality {
+                chord_tol: tolerance,
+                angle_tol: 20f64.to_radians(),
+                max_edge_len: f64::INFINITY,
+                normals: NormalMode::default(),
+            },
+            MeshDetail::Fine => MeshQuality {
+                chord_tol: tolerance * 0.25,
+                angle_tol: 10f64.to_radians(),
+                max_edge_len: f64::INFINITY,
+                normals: NormalMode::default(),
+            },
+        }
+    }
+}
+
+/// Chord tolerance only, with no angle or edge length bound
+impl From<f64> for MeshQuality {
+    fn from(chord_tol: f64) -> Self {
+        Self {
+            chord_tol,
+            angle_tol: std::f64::consts::PI,
+            max_edge_len: f64::INFINITY,
+            normals: NormalMode::default(),
+        }
+    }
+}
+
+/// How [`GpuMesh::with_normals`] shades across triangles
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NormalMode {
+    /// Normals as tessellated: the surface normals for solids, so shading
+    /// is smooth within a face and sharp between faces
+    #[default]
+    Surface,
+    /// Averaged over triangles meeting at a point whose normals are within
+    /// this angle (radians) of each other; sharper creases stay hard
+    Smooth(f64),
+    /// Each triangle's own normal, for a faceted look
+    Flat,
+}
+
+impl NormalMode {
+    /// Default angle for [`NormalMode::Smooth`]
+    pub const CREASE_ANGLE: f64 = std::f64::consts::FRAC_PI_6;
+
+    pub const ALL: [NormalMode; 3] = [
+        NormalMode::Surface,
+        NormalMode::Smooth(Self::CREASE_ANGLE),
+        NormalMode::Flat,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NormalMode::Surface => "Surface",
+            NormalMode::Smooth(_) => "Smooth",
+            NormalMode::Flat => "Flat",
+        }
+    }
+}
+
+/// How far [`GpuMesh::decimate`] simplifies
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DecimateTarget {
+    /// Keep about this fraction of the triangles
+    Ratio(f64),
+    /// Keep collapsing while the surface moves less than this distance
+    Error(f64),
+}
 
 #[repr(C)]
-#[derive(Clone, Copy, Pod, Zeroable)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Vertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
+    /// Surface parameters at the vertex, for textures; zero on meshes
+    /// without any
+    pub uv: [f32; 2],
 }
 
 impl Vertex {
-    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+    const ATTRIBS: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
         0 => Float32x3,  // position
         1 => Float32x3,  // normal
+        3 => Float32x2,  // uv, after the color of the second buffer
+    ];
+    const COLOR_ATTRIBS: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![
+        2 => Float32x3,  // color
+    ];
+    const FACE_ATTRIBS: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![
+        4 => Uint32,  // face id + 1, 0 for none
     ];
 
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
@@ -23,46 +203,1357 @@ impl Vertex {
             attributes: &Self::ATTRIBS,
         }
     }
+
+    /// Second vertex buffer, of meshes with [`GpuMesh::colors`]
+    pub fn color_desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::COLOR_ATTRIBS,
+        }
+    }
+
+    /// Second vertex buffer of the ID pass, of [`GpuMesh::face_ids`] + 1
+    pub fn face_desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::FACE_ATTRIBS,
+        }
+    }
+}
+
+/// Blue through green to red for `t` from 0 to 1, clamped, for showing
+/// analysis values as vertex colors
+pub fn heat_color(t: f32) -> [f32; 3] {
+    let t = t.clamp(0.0, 1.0) * 4.0;
+    let ramp = |x: f32| x.clamp(0.0, 1.0);
+    [ramp(t - 2.0), ramp(t) - ramp(t - 3.0), 1.0 - ramp(t - 1.0)]
+}
+
+/// Axis-aligned box in render (single precision) coordinates
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox3 {
+    pub min: glam::Vec3,
+    pub max: glam::Vec3,
 }
 
+impl BoundingBox3 {
+    pub fn new(min: glam::Vec3, max: glam::Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_points(points: impl IntoIterator<Item = glam::Vec3>) -> Option<Self> {
+        points
+            .into_iter()
+            .map(|p| Self::new(p, p))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn center(&self) -> glam::Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Radius of the sphere through the corners
+    pub fn radius(&self) -> f32 {
+        (self.max - self.min).length() * 0.5
+    }
+
+    pub fn corners(&self) -> [glam::Vec3; 8] {
+        std::array::from_fn(|i| {
+            glam::Vec3::select(
+                glam::BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0),
+                self.max,
+                self.min,
+            )
+        })
+    }
+
+    /// Box around the eight corners moved by `m`
+    pub fn transformed(&self, m: glam::Mat4) -> Self {
+        let corners = self.corners().map(|corner| m.transform_point3(corner));
+        Self::from_points(corners).expect("eight corners")
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct GpuMesh {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    /// One per vertex when set, see [`GpuMesh::with_colors`]
+    colors: Option<Vec<[f32; 3]>>,
+    /// B-rep face of each vertex when made by [`GpuMesh::from_solid`], see
+    /// [`GpuMesh::face_ids`]
+    faces: Option<Vec<u32>>,
 }
 
 impl GpuMesh {
-    /// Convert a truck Solid to GPU-ready mesh data
-    pub fn from_solid(solid: &Solid, tolerance: f64) -> Self {
-        // 1. Triangulate the solid
-        let polygon_mesh = solid.triangulation(tolerance);
+    /// Convert a truck Solid to GPU-ready mesh data.
+    ///
+    /// With an angle bound, each face is refined only as far as its own
+    /// curvature needs, so planes stay coarse while fillets and small arcs
+    /// get dense. Without one the solid is meshed whole at the chord
+    /// tolerance. Every vertex records the face it was cut from.
+    pub fn from_solid(solid: &Solid, quality: impl Into<MeshQuality>) -> Self {
+        let quality = quality.into();
+        let mut mesh = if quality.angle_tol >= std::f64::consts::PI {
+            let meshed = solid.triangulation(quality.chord_tol);
+            let mut mesh = Self::on_faces();
+            let faces = meshed
+                .boundaries()
+                .iter()
+                .flat_map(|shell| shell.face_iter());
+            for (face, meshed) in faces.enumerate() {
+                if let Some(mut polygon) = meshed.surface() {
+                    if !meshed.orientation() {
+                        polygon.invert();
+                    }
+                    mesh.append(Self::from_tessellation(&polygon).on_face(face));
+                }
+            }
+            mesh
+        } else {
+            Self::from_faces(solid, &quality)
+        };
+        mesh.split_long_edges(quality.max_edge_len);
+        let mut mesh = mesh.with_normals(quality.normals);
+        mesh.optimize();
+        mesh
+    }
 
-        // 2. Get the raw polygon mesh
-        let mesh = polygon_mesh.to_polygon();
+    /// Mesh every face at the tolerance it needs. An edge is cut at the
+    /// finest tolerance of the faces on it, and faces meshed coarser are
+    /// given those same points, so no gaps open along the seams.
+    fn from_faces(solid: &Solid, quality: &MeshQuality) -> Self {
+        let faces: Vec<&Face> = solid
+            .boundaries()
+            .iter()
+            .flat_map(|shell| shell.face_iter())
+            .collect();
+        let refined: Vec<(f64, GpuMesh)> = faces
+            .iter()
+            .map(|face| Self::refine_face(face, quality))
+            .collect();
+        let mut edge_tol = HashMap::new();
+        for (face, (tolerance, _)) in faces.iter().zip(&refined) {
+            for edge in face.edge_iter() {
+                let tol = edge_tol.entry(edge.id()).or_insert(*tolerance);
+                *tol = tolerance.min(*tol);
+            }
+        }
 
-        // 3. Extract positions
+        let mut mesh = Self::on_faces();
+        let mut chains = HashMap::new();
+        for (index, (face, (tolerance, face_mesh))) in faces.into_iter().zip(refined).enumerate() {
+            let finer_edges = face
+                .edge_iter()
+                .any(|edge| edge_tol[&edge.id()] < tolerance);
+            let seamed = finer_edges
+                .then(|| with_polyline_edges(face, &edge_tol, &mut chains))
+                .flatten();
+            let face_mesh = match seamed {
+                Some(seamed) => Self::triangulate_face(&seamed, tolerance),
+                None => face_mesh,
+            };
+            mesh.append(face_mesh.on_face(index));
+        }
+        mesh
+    }
+
+    /// Empty mesh to [`GpuMesh::append`] faces to
+    fn on_faces() -> Self {
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            colors: None,
+            faces: Some(Vec::new()),
+        }
+    }
+
+    /// Mark every vertex as cut from face `face`
+    fn on_face(mut self, face: usize) -> Self {
+        self.faces = Some(vec![face as u32; self.vertices.len()]);
+        self
+    }
+
+    /// Mesh one face, halving the chord tolerance until its triangles
+    /// follow the surface normals within the angle bound, which truck
+    /// cannot take itself; returns the tolerance used too
+    fn refine_face(face: &Face, quality: &MeshQuality) -> (f64, Self) {
+        let mut tolerance = quality.chord_tol;
+        let mut mesh = Self::triangulate_face(face, tolerance);
+        for _ in 0..MAX_REFINEMENTS {
+            if mesh.normal_deviation() <= quality.angle_tol {
+                break;
+            }
+            tolerance *= 0.5;
+            mesh = Self::triangulate_face(face, tolerance);
+        }
+        (tolerance, mesh)
+    }
+
+    fn triangulate_face(face: &Face, tolerance: f64) -> Self {
+        let shell: Shell = vec![face.clone()].into();
+        Self::from_tessellation(&shell.triangulation(tolerance).to_polygon())
+    }
+
+    /// Take the triangles truck made, with the surface parameters of each
+    /// face as texture coordinates
+    fn from_tessellation(mesh: &PolygonMesh) -> Self {
+        // 1. Extract positions
         let positions = mesh.positions();
 
-        // 4. Compute normals (per-face, then average per-vertex)
+        // 2. Compute normals (per-face, then average per-vertex)
         //    truck_meshalgo provides this
         let normals = mesh.normals();
 
-        // 5. Build vertex array
+        // 3. Build vertex array
+        let uvs = mesh.uv_coords();
         let vertices: Vec<Vertex> = positions
             .iter()
             .zip(normals.iter())
-            .map(|(pos, norm)| Vertex {
+            .enumerate()
+            .map(|(i, (pos, norm))| Vertex {
                 position: [pos.x as f32, pos.y as f32, pos.z as f32],
                 normal: [norm.x as f32, norm.y as f32, norm.z as f32],
+                uv: uvs.get(i).map_or([0.0; 2], |uv| [uv.x as f32, uv.y as f32]),
             })
             .collect();
 
-        // 6. Build index array
+        // 4. Build index array
         let indices: Vec<u32> = mesh
             .tri_faces()
             .iter()
             .flat_map(|face| face.iter().map(|&idx| idx.pos as u32))
             .collect();
 
-        Self { vertices, indices }
+        Self {
+            vertices,
+            indices,
+            colors: None,
+            faces: None,
+        }
+    }
+
+    /// Add the triangles of `other`, keeping its vertices apart; face ids
+    /// are kept when both have them
+    fn append(&mut self, other: GpuMesh) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices);
+        self.indices
+            .extend(other.indices.into_iter().map(|i| i + offset));
+        self.faces = match (self.faces.take(), other.faces) {
+            (Some(mut faces), Some(more)) => {
+                faces.extend(more);
+                Some(faces)
+            }
+            _ => None,
+        };
+    }
+
+    /// Largest angle between a triangle and the normals at its corners
+    fn normal_deviation(&self) -> f64 {
+        let vertex = |i: u32| self.vertices[i as usize];
+        self.indices
+            .chunks_exact(3)
+            .flat_map(|t| {
+                let [p0, p1, p2] = [t[0], t[1], t[2]].map(|i| vec3(vertex(i).position));
+                let face = (p1 - p0).cross(p2 - p0);
+                // Slivers with next to no area have no reliable normal
+                let longest = [p1 - p0, p2 - p1, p0 - p2]
+                    .map(|e| e.magnitude2())
+                    .into_iter()
+                    .fold(0.0, f64::max);
+                let solid = face.magnitude2() > 1e-18 * longest * longest;
+                t.iter().filter_map(move |&i| {
+                    let normal = vec3(vertex(i).normal);
+                    (solid && normal.magnitude2() > 0.0).then(|| face.angle(normal).0)
+                })
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// Bisect every edge longer than `max_len` until none is left. Shared
+    /// edges get one shared midpoint, so closed meshes stay closed.
+    fn split_long_edges(&mut self, max_len: f64) {
+        for _ in 0..MAX_EDGE_SPLITS {
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            for t in self.indices.chunks_exact(3) {
+                for (a, b) in [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])] {
+                    let (p, q) = (self.vertices[a as usize], self.vertices[b as usize]);
+                    if vec3(p.position).distance(vec3(q.position)) > max_len {
+                        midpoints.insert((a.min(b), a.max(b)), 0);
+                    }
+                }
+            }
+            if midpoints.is_empty() {
+                return;
+            }
+            for (&(a, b), index) in &mut midpoints {
+                let (p, q) = (self.vertices[a as usize], self.vertices[b as usize]);
+                let position = (vec3(p.position) + vec3(q.position)) * 0.5;
+                let normal = vec3(p.normal) + vec3(q.normal);
+                let normal = if normal.magnitude2() > 0.0 {
+                    normal.normalize()
+                } else {
+                    normal
+                };
+                let uv = [0, 1].map(|k| (p.uv[k] + q.uv[k]) * 0.5);
+                self.vertices.push(Vertex {
+                    position: [position.x as f32, position.y as f32, position.z as f32],
+                    normal: [normal.x as f32, normal.y as f32, normal.z as f32],
+                    uv,
+                });
+                if let Some(faces) = &mut self.faces {
+                    faces.push(faces[a as usize]);
+                }
+                *index = self.vertices.len() as u32 - 1;
+            }
+            let triangles = std::mem::take(&mut self.indices);
+            for t in triangles.chunks_exact(3) {
+                split_triangle([t[0], t[1], t[2]], &midpoints, &mut self.indices);
+            }
+        }
+    }
+
+    /// Convert any polygon mesh, splitting polygons into triangle fans.
+    ///
+    /// Corners sharing a position, normal and texture coordinates share a
+    /// vertex; corners without a normal or coordinates get zero ones.
+    pub fn from_polygon(mesh: &PolygonMesh) -> Self {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut lookup = HashMap::new();
+        for triangle in mesh.faces().triangle_iter() {
+            for corner in triangle {
+                let key = (corner.pos, corner.uv, corner.nor);
+                let index = *lookup.entry(key).or_insert_with(|| {
+                    let p = mesh.positions()[corner.pos];
+                    let n = corner.nor.map_or(Vector3::zero(), |i| mesh.normals()[i]);
+                    let uv = corner.uv.map_or(Vector2::zero(), |i| mesh.uv_coords()[i]);
+                    vertices.push(Vertex {
+                        position: [p.x as f32, p.y as f32, p.z as f32],
+                        normal: [n.x as f32, n.y as f32, n.z as f32],
+                        uv: [uv.x as f32, uv.y as f32],
+                    });
+                    vertices.len() as u32 - 1
+                });
+                indices.push(index);
+            }
+        }
+        Self {
+            vertices,
+            indices,
+            colors: None,
+            faces: None,
+        }
+    }
+
+    /// Index of the B-rep face each vertex was cut from, counting faces
+    /// over the solid's shells in order; vertices are never shared by two
+    /// faces. `None` for meshes not made from a solid.
+    pub fn face_ids(&self) -> Option<&[u32]> {
+        self.faces.as_deref()
+    }
+
+    /// Face triangle `t` was cut from
+    pub fn triangle_face(&self, t: usize) -> Option<usize> {
+        let corner = self.indices.get(3 * t)?;
+        Some(self.faces.as_ref()?[corner as usize] as usize)
+    }
+
+    /// Edges around face `face`, as pairs of points; holes give loops of
+    /// their own
+    pub fn face_border(&self, face: usize) -> Vec<[glam::Vec3; 2]> {
+        let Some(faces) = &self.faces else {
+            return Vec::new();
+        };
+        let mut sides: HashMap<(u32, u32), usize> = HashMap::new();
+        for t in self.indices.chunks_exact(3) {
+            if faces[t[0] as usize] as usize != face {
+                continue;
+            }
+            for (a, b) in [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])] {
+                *sides.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+        let point = |i: u32| glam::Vec3::from(self.vertices[i as usize].position);
+        sides
+            .into_iter()
+            .filter(|&(_, count)| count == 1)
+            .map(|((a, b), _)| [point(a), point(b)])
+            .collect()
+    }
+
+    /// Paint the faces in `overrides` their color and the rest `base`
+    pub fn with_face_colors(
+        self,
+        base: [f32; 3],
+        overrides: &HashMap<usize, [f32; 3]>,
+    ) -> SketchResult<Self> {
+        let Some(faces) = &self.faces else {
+            return Err(SketchError::RenderFailed(
+                "mesh has no face ids to color".to_string(),
+            ));
+        };
+        let colors = faces
+            .iter()
+            .map(|&face| overrides.get(&(face as usize)).copied().unwrap_or(base))
+            .collect();
+        self.with_colors(colors)
+    }
+
+    /// Linear RGB per vertex, drawn instead of the material color
+    pub fn colors(&self) -> Option<&[[f32; 3]]> {
+        self.colors.as_deref()
+    }
+
+    /// Paint each vertex its own linear RGB color, e.g. baked analysis
+    /// results; one color per vertex
+    pub fn with_colors(mut self, colors: Vec<[f32; 3]>) -> SketchResult<Self> {
+        if colors.len() != self.vertices.len() {
+            return Err(SketchError::RenderFailed(format!(
+                "expected {} vertex colors, got {}",
+                self.vertices.len(),
+                colors.len()
+            )));
+        }
+        self.colors = Some(colors);
+        Ok(self)
+    }
+
+    /// Paint one value per vertex with [`heat_color`], `range` spanning the
+    /// colors from blue to red
+    pub fn with_values(self, values: &[f32], range: (f32, f32)) -> SketchResult<Self> {
+        let span = (range.1 - range.0).max(f32::EPSILON);
+        let colors = values
+            .iter()
+            .map(|v| heat_color((v - range.0) / span))
+            .collect();
+        self.with_colors(colors)
+    }
+
+    /// One mesh per part of an assembly, in assembly coordinates; parts are
+    /// tessellated in parallel
+    pub fn from_assembly(assembly: &Assembly, quality: impl Into<MeshQuality>) -> Vec<Self> {
+        let quality = quality.into();
+        assembly
+            .placed_solids()
+            .par_iter()
+            .map(|solid| Self::from_solid(solid, quality))
+            .collect()
+    }
+
+    /// Extent of the vertices, `None` for an empty mesh
+    pub fn bounding_box(&self) -> Option<BoundingBox3> {
+        BoundingBox3::from_points(self.vertices.iter().map(|v| glam::Vec3::from(v.position)))
+    }
+
+    /// Enclosed volume (positive when triangles wind outward)
+    pub fn volume(&self) -> f64 {
+        let p = |i: u32| {
+            let v = self.vertices[i as usize].position;
+            Vector3::new(v[0] as f64, v[1] as f64, v[2] as f64)
+        };
+        self.indices
+            .chunks_exact(3)
+            .map(|t| p(t[0]).dot(p(t[1]).cross(p(t[2]))) / 6.0)
+            .sum()
+    }
+
+    /// Weld duplicate vertices, then order triangles so vertices are
+    /// reused while still in the GPU's cache and vertices in the order
+    /// triangles first use them
+    pub fn optimize(&mut self) {
+        self.weld();
+        self.order_for_cache();
+    }
+
+    /// Merge vertices with the same position, normal, texture coordinates,
+    /// color and face, dropping unused ones
+    pub fn weld(&mut self) {
+        let mut first = HashMap::new();
+        let same: Vec<usize> = (0..self.vertices.len())
+            .map(|i| {
+                let v = self.vertices[i];
+                // Adding zero turns -0.0 into 0.0, so both weld
+                let bits = |xs: &[f32]| xs.iter().map(|x| (x + 0.0).to_bits()).collect::<Vec<_>>();
+                let key = (
+                    bits(&v.position),
+                    bits(&v.normal),
+                    bits(&v.uv),
+                    self.colors.as_ref().map(|c| bits(&c[i])),
+                    self.faces.as_ref().map(|f| f[i]),
+                );
+                *first.entry(key).or_insert(i)
+            })
+            .collect();
+        let indices = self.indices.iter().map(|&i| same[i as usize]);
+        *self = self.compact(indices, |v| self.vertices[v]);
+    }
+
+    /// Reorder triangles for the post transform cache, after Forsyth's
+    /// linear speed method: each step takes the triangle whose vertices
+    /// are most recently used or have fewest triangles left. Vertices are
+    /// then renumbered in order of first use.
+    pub fn order_for_cache(&mut self) {
+        let triangles: Vec<[usize; 3]> = self
+            .indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]].map(|i| i as usize))
+            .collect();
+        let mut around: Vec<Vec<usize>> = vec![Vec::new(); self.vertices.len()];
+        for (t, triangle) in triangles.iter().enumerate() {
+            for &v in triangle {
+                around[v].push(t);
+            }
+        }
+        let mut remaining: Vec<usize> = around.iter().map(Vec::len).collect();
+        let mut cache: Vec<usize> = Vec::with_capacity(VERTEX_CACHE_SIZE + 3);
+        let score = |v: usize, cache: &[usize], remaining: &[usize]| {
+            if remaining[v] == 0 {
+                return -1.0;
+            }
+            let recency = match cache.iter().position(|&c| c == v) {
+                Some(p) if p < 3 => 0.75,
+                Some(p) => (1.0 - (p - 3) as f32 / (VERTEX_CACHE_SIZE - 3) as f32).powf(1.5),
+                None => 0.0,
+            };
+            recency + 2.0 / (remaining[v] as f32).sqrt()
+        };
+        let mut scores: Vec<f32> = (0..self.vertices.len())
+            .map(|v| score(v, &cache, &remaining))
+            .collect();
+        let mut done = vec![false; triangles.len()];
+        let triangle_score =
+            |t: usize, scores: &[f32]| triangles[t].iter().map(|&v| scores[v]).sum::<f32>();
+
+        let mut order: Vec<usize> = Vec::with_capacity(self.indices.len());
+        let mut next_unused = 0;
+        let mut best: Option<usize> = None;
+        for _ in 0..triangles.len() {
+            // When nothing cached has triangles left, start on the first
+            // triangle not taken
+            let t = best.unwrap_or_else(|| {
+                while done[next_unused] {
+                    next_unused += 1;
+                }
+                next_unused
+            });
+            done[t] = true;
+            order.extend(triangles[t]);
+            for &v in &triangles[t] {
+                remaining[v] -= 1;
+            }
+            let mut updated = triangles[t].to_vec();
+            updated.extend(cache.iter().filter(|v| !triangles[t].contains(v)));
+            let evicted = updated.split_off(updated.len().min(VERTEX_CACHE_SIZE));
+            cache = updated;
+            for &v in cache.iter().chain(&evicted) {
+                scores[v] = score(v, &cache, &remaining);
+            }
+            best = cache
+                .iter()
+                .flat_map(|&v| around[v].iter().copied())
+                .filter(|&t| !done[t])
+                .max_by(|&a, &b| triangle_score(a, &scores).total_cmp(&triangle_score(b, &scores)));
+        }
+
+        *self = self.compact(order, |v| self.vertices[v]);
+    }
+
+    /// Mesh of the triangles whose corners `indices` lists by old vertex
+    /// index, over just the vertices they use, numbered in order of first
+    /// use; `vertex` makes each from its old index, and colors and faces
+    /// carry over
+    fn compact(
+        &self,
+        indices: impl IntoIterator<Item = usize>,
+        vertex: impl Fn(usize) -> Vertex,
+    ) -> Self {
+        let mut index = vec![u32::MAX; self.vertices.len()];
+        let mut kept = Vec::new();
+        let indices = indices
+            .into_iter()
+            .map(|v| {
+                if index[v] == u32::MAX {
+                    index[v] = kept.len() as u32;
+                    kept.push(v);
+                }
+                index[v]
+            })
+            .collect();
+        Self {
+            vertices: kept.iter().map(|&v| vertex(v)).collect(),
+            indices,
+            colors: self
+                .colors
+                .as_ref()
+                .map(|c| kept.iter().map(|&v| c[v]).collect()),
+            faces: self
+                .faces
+                .as_ref()
+                .map(|f| kept.iter().map(|&v| f[v]).collect()),
+        }
+    }
+
+    /// Normals made again by `mode`; [`NormalMode::Surface`] keeps them.
+    /// Corners that end up alike are welded, so flat meshes only split
+    /// vertices where triangles bend.
+    pub fn with_normals(self, mode: NormalMode) -> Self {
+        let crease = match mode {
+            NormalMode::Surface => return self,
+            NormalMode::Smooth(angle) => angle,
+            NormalMode::Flat => 0.0,
+        };
+        let (positions, point_of) = self.weld_points();
+        let triangles: Vec<[usize; 3]> = self
+            .indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]].map(|i| i as usize))
+            .collect();
+        // Area weighted, so slivers count for little
+        let areas: Vec<Vector3> = triangles
+            .iter()
+            .map(|t| {
+                let [p0, p1, p2] = t.map(|v| positions[point_of[v]]);
+                (p1 - p0).cross(p2 - p0)
+            })
+            .collect();
+        let mut around = vec![Vec::new(); positions.len()];
+        for (t, triangle) in triangles.iter().enumerate() {
+            for &v in triangle {
+                around[point_of[v]].push(t);
+            }
+        }
+        let cos_crease = crease.cos();
+        let corners: Vec<(usize, Vector3)> = triangles
+            .iter()
+            .enumerate()
+            .flat_map(|(t, triangle)| triangle.map(|v| (t, v)))
+            .map(|(t, v)| {
+                let own = areas[t];
+                if own.magnitude2() == 0.0 {
+                    return (v, vec3(self.vertices[v].normal));
+                }
+                let own = own.normalize();
+                let normal = around[point_of[v]]
+                    .iter()
+                    .map(|&u| areas[u])
+                    .filter(|area| area.magnitude2() > 0.0)
+                    .filter(|area| own.dot(area.normalize()) >= cos_crease - 1e-9)
+                    .fold(Vector3::zero(), |sum, area| sum + area);
+                (v, normal.normalize())
+            })
+            .collect();
+        let mut mesh = Self {
+            vertices: corners
+                .iter()
+                .map(|&(v, n)| Vertex {
+                    normal: [n.x as f32, n.y as f32, n.z as f32],
+                    ..self.vertices[v]
+                })
+                .collect(),
+            indices: (0..corners.len() as u32).collect(),
+            colors: self
+                .colors
+                .as_ref()
+                .map(|c| corners.iter().map(|&(v, _)| c[v]).collect()),
+            faces: self
+                .faces
+                .as_ref()
+                .map(|f| corners.iter().map(|&(v, _)| f[v]).collect()),
+        };
+        mesh.weld();
+        mesh
+    }
+
+    /// Vertices grouped into points by position, within
+    /// [`WELD_FRACTION`] of the mesh size: the points and the point of
+    /// each vertex
+    fn weld_points(&self) -> (Vec<Vector3>, Vec<usize>) {
+        let size = self.bounding_box().map_or(0.0, |b| b.radius() as f64 * 2.0);
+        let grid = (size * WELD_FRACTION).max(f64::MIN_POSITIVE);
+        let mut welded = HashMap::new();
+        let mut positions: Vec<Vector3> = Vec::new();
+        let point_of = self
+            .vertices
+            .iter()
+            .map(|v| {
+                let p = vec3(v.position);
+                let key = [p.x, p.y, p.z].map(|x| (x / grid).round() as i64);
+                *welded.entry(key).or_insert_with(|| {
+                    positions.push(p);
+                    positions.len() - 1
+                })
+            })
+            .collect();
+        (positions, point_of)
+    }
+
+    /// Simplify by collapsing the edges whose removal moves the surface
+    /// least, measured by quadric error, until `target` is met. Corners at
+    /// one position move together, so seams between faces stay closed, and
+    /// open borders are held in place. Corners keep their normals, texture
+    /// coordinates, colors and faces.
+    pub fn decimate(&self, target: DecimateTarget) -> Self {
+        let (mut positions, mut point_of) = self.weld_points();
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); positions.len()];
+        for (v, &point) in point_of.iter().enumerate() {
+            members[point].push(v);
+        }
+
+        let triangles: Vec<[usize; 3]> = self
+            .indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]].map(|i| i as usize))
+            .collect();
+        let points = |t: &[usize; 3], point_of: &[usize]| t.map(|v| point_of[v]);
+        let mut alive: Vec<bool> = triangles
+            .iter()
+            .map(|t| {
+                let [a, b, c] = points(t, &point_of);
+                a != b && b != c && c != a
+            })
+            .collect();
+        let mut around: Vec<Vec<usize>> = vec![Vec::new(); positions.len()];
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+        let mut sides: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (t, triangle) in triangles.iter().enumerate().filter(|(t, _)| alive[*t]) {
+            let corners = points(triangle, &point_of);
+            let [p0, p1, p2] = corners.map(|p| positions[p]);
+            let normal = (p1 - p0).cross(p2 - p0);
+            let plane = if normal.magnitude2() > 0.0 {
+                Quadric::plane(normal.normalize(), p0)
+            } else {
+                Quadric::default()
+            };
+            for (k, &p) in corners.iter().enumerate() {
+                around[p].push(t);
+                quadrics[p].add(&plane);
+                let q = corners[(k + 1) % 3];
+                sides.entry((p.min(q), p.max(q))).or_default().push(t);
+            }
+        }
+        // Planes across open borders keep them from moving
+        for (&(p, q), faces) in &sides {
+            let [t] = faces[..] else {
+                continue;
+            };
+            let [p0, p1, p2] = points(&triangles[t], &point_of).map(|p| positions[p]);
+            let across = (positions[q] - positions[p]).cross((p1 - p0).cross(p2 - p0));
+            if across.magnitude2() > 0.0 {
+                let mut border = Quadric::plane(across.normalize(), positions[p]);
+                border.scale(BORDER_WEIGHT);
+                quadrics[p].add(&border);
+                quadrics[q].add(&border);
+            }
+        }
+
+        let mut live = alive.iter().filter(|&&a| a).count();
+        let (goal, max_cost) = match target {
+            DecimateTarget::Ratio(ratio) => (
+                (live as f64 * ratio.clamp(0.0, 1.0)).ceil() as usize,
+                f64::INFINITY,
+            ),
+            DecimateTarget::Error(error) => (0, error * error),
+        };
+        let mut stamps = vec![0u32; positions.len()];
+        let mut removed = vec![false; positions.len()];
+        let candidate =
+            |a: usize, b: usize, positions: &[Vector3], quadrics: &[Quadric], stamps: &[u32]| {
+                let mut quadric = quadrics[a];
+                quadric.add(&quadrics[b]);
+                let (cost, target) = [
+                    positions[a],
+                    positions[b],
+                    (positions[a] + positions[b]) * 0.5,
+                ]
+                .into_iter()
+                .map(|p| (quadric.error(p).max(0.0), p))
+                .min_by(|x, y| x.0.total_cmp(&y.0))
+                .expect("three choices");
+                Collapse {
+                    cost,
+                    length: (positions[a] - positions[b]).magnitude2(),
+                    a,
+                    b,
+                    stamps: (stamps[a], stamps[b]),
+                    target,
+                }
+            };
+        let mut queue: BinaryHeap<Collapse> = sides
+            .keys()
+            .map(|&(a, b)| candidate(a, b, &positions, &quadrics, &stamps))
+            .collect();
+
+        while live > goal {
+            let Some(collapse) = queue.pop() else {
+                break;
+            };
+            let Collapse { a, b, target, .. } = collapse;
+            if removed[a] || removed[b] || collapse.stamps != (stamps[a], stamps[b]) {
+                continue;
+            }
+            if collapse.cost > max_cost {
+                break;
+            }
+            // Refuse collapses that would turn a triangle over
+            let flips = around[a].iter().chain(&around[b]).any(|&t| {
+                let corners = points(&triangles[t], &point_of);
+                if !alive[t] || (corners.contains(&a) && corners.contains(&b)) {
+                    return false;
+                }
+                let [p0, p1, p2] = corners.map(|p| positions[p]);
+                let [q0, q1, q2] = corners.map(|p| {
+                    if p == a || p == b {
+                        target
+                    } else {
+                        positions[p]
+                    }
+                });
+                (p1 - p0).cross(p2 - p0).dot((q1 - q0).cross(q2 - q0)) <= 0.0
+            });
+            if flips {
+                continue;
+            }
+
+            // Move `a` to the target and give it everything of `b`'s
+            positions[a] = target;
+            let quadric = quadrics[b];
+            quadrics[a].add(&quadric);
+            for &v in &members[b] {
+                point_of[v] = a;
+            }
+            let moved = std::mem::take(&mut members[b]);
+            members[a].extend(moved);
+            let triangles_b = std::mem::take(&mut around[b]);
+            around[a].extend(triangles_b);
+            around[a].sort_unstable();
+            around[a].dedup();
+            for &t in &around[a] {
+                let [p, q, r] = points(&triangles[t], &point_of);
+                if alive[t] && (p == q || q == r || r == p) {
+                    alive[t] = false;
+                    live -= 1;
+                }
+            }
+            around[a].retain(|&t| alive[t]);
+            removed[b] = true;
+            stamps[a] += 1;
+
+            let mut neighbours: Vec<usize> = around[a]
+                .iter()
+                .flat_map(|&t| points(&triangles[t], &point_of))
+                .filter(|&p| p != a)
+                .collect();
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            for n in neighbours {
+                queue.push(candidate(a, n, &positions, &quadrics, &stamps));
+            }
+        }
+
+        // Keep the corners still in use, at their points' positions
+        let corners = triangles
+            .iter()
+            .zip(&alive)
+            .filter(|(_, &a)| a)
+            .flat_map(|(triangle, _)| *triangle);
+        self.compact(corners, |v| {
+            let p = positions[point_of[v]];
+            Vertex {
+                position: [p.x as f32, p.y as f32, p.z as f32],
+                ..self.vertices[v]
+            }
+        })
+    }
+}
+
+/// `face` with each edge swapped for lines through the points it is cut
+/// into at its tolerance in `edge_tol`; the lines of each edge are kept in
+/// `chains` for the other face on it. `None` when truck rejects the new
+/// boundary.
+fn with_polyline_edges(
+    face: &Face,
+    edge_tol: &HashMap<EdgeID, f64>,
+    chains: &mut HashMap<EdgeID, Vec<Edge>>,
+) -> Option<Face> {
+    let mut wires = Vec::new();
+    for wire in face.absolute_boundaries() {
+        let mut lines = Wire::new();
+        for edge in wire.edge_iter() {
+            let chain = chains.entry(edge.id()).or_insert_with(|| {
+                let curve = edge.curve();
+                let points =
+                    PolylineCurve::from_curve(&curve, curve.range_tuple(), edge_tol[&edge.id()]).0;
+                let inner = points[1..points.len().saturating_sub(1)]
+                    .iter()
+                    .map(|&p| builder::vertex(p));
+                let vertices: Vec<TopoVertex> = std::iter::once(edge.absolute_front().clone())
+                    .chain(inner)
+                    .chain(std::iter::once(edge.absolute_back().clone()))
+                    .collect();
+                vertices
+                    .windows(2)
+                    .map(|pair| builder::line(&pair[0], &pair[1]))
+                    .collect()
+            });
+            match edge.orientation() {
+                true => lines.extend(chain.iter().cloned()),
+                false => lines.extend(chain.iter().rev().map(Edge::inverse)),
+            }
+        }
+        wires.push(lines);
+    }
+    let mut seamed = Face::try_new(wires, face.surface()).ok()?;
+    if !face.orientation() {
+        seamed.invert();
+    }
+    Some(seamed)
+}
+
+fn vec3(v: [f32; 3]) -> Vector3 {
+    Vector3::new(v[0] as f64, v[1] as f64, v[2] as f64)
+}
+
+/// Split a triangle along every edge that has a midpoint, one edge at a time
+fn split_triangle(t: [u32; 3], midpoints: &HashMap<(u32, u32), u32>, out: &mut Vec<u32>) {
+    for k in 0..3 {
+        let (a, b, c) = (t[k], t[(k + 1) % 3], t[(k + 2) % 3]);
+        if let Some(&m) = midpoints.get(&(a.min(b), a.max(b))) {
+            split_triangle([a, m, c], midpoints, out);
+            split_triangle([m, b, c], midpoints, out);
+            return;
+        }
+    }
+    out.extend(t);
+}
+
+/// Sum of squared distances to a set of planes, as the upper triangle of
+/// a symmetric 4x4 matrix
+#[derive(Clone, Copy, Debug, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// The plane with unit `normal` through `point`
+    fn plane(normal: Vector3, point: Vector3) -> Self {
+        let [a, b, c] = [normal.x, normal.y, normal.z];
+        let d = -normal.dot(point);
+        Self([
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ])
+    }
+
+    fn add(&mut self, other: &Self) {
+        for (q, o) in self.0.iter_mut().zip(other.0) {
+            *q += o;
+        }
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.0.iter_mut().for_each(|q| *q *= factor);
+    }
+
+    fn error(&self, p: Vector3) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        q[0] * x * x
+            + q[4] * y * y
+            + q[7] * z * z
+            + 2.0 * (q[1] * x * y + q[2] * x * z + q[5] * y * z)
+            + 2.0 * (q[3] * x + q[6] * y + q[8] * z)
+            + q[9]
+    }
+}
+
+/// Edge collapse waiting in the queue, cheapest and then shortest first;
+/// `stamps` tell whether its points changed since it was queued
+struct Collapse {
+    cost: f64,
+    length: f64,
+    a: usize,
+    b: usize,
+    stamps: (u32, u32),
+    target: Vector3,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then(other.length.total_cmp(&self.length))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{box_solid, cylinder};
+
+    #[test]
+    fn test_mesh_quality_bounds() {
+        let rod = cylinder(Point3::origin(), Vector3::unit_z() * 4.0, 1.0).unwrap();
+        let coarse = GpuMesh::from_solid(&rod, 0.1);
+        let quality = MeshQuality {
+            chord_tol: 0.1,
+            angle_tol: 5f64.to_radians(),
+            ..Default::default()
+        };
+        let fine = GpuMesh::from_solid(&rod, quality);
+        assert!(fine.normal_deviation() <= quality.angle_tol);
+        assert!(fine.indices.len() > coarse.indices.len());
+
+        let sizes: Vec<usize> = MeshDetail::ALL
+            .iter()
+            .map(|detail| {
+                GpuMesh::from_solid(&rod, detail.quality(0.01))
+                    .indices
+                    .len()
+            })
+            .collect();
+        assert!(
+            sizes.windows(2).all(|pair| pair[0] < pair[1]),
+            "{:?}",
+            sizes
+        );
+
+        let cube = box_solid(Point3::origin(), Vector3::new(4.0, 1.0, 1.0)).unwrap();
+        let quality = MeshQuality {
+            max_edge_len: 0.5,
+            ..Default::default()
+        };
+        let mesh = GpuMesh::from_solid(&cube, quality);
+        assert!((mesh.volume() - 4.0).abs() < 1e-5);
+        for t in mesh.indices.chunks_exact(3) {
+            for (a, b) in [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])] {
+                let (p, q) = (
+                    mesh.vertices[a as usize].position,
+                    mesh.vertices[b as usize].position,
+                );
+                let d = Vector3::from(p.map(f64::from)) - Vector3::from(q.map(f64::from));
+                assert!(d.magnitude() <= 0.5 + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_adaptive_tessellation() {
+        // A plate with a gently bowed side and one small round corner
+        let v = [
+            (0.2, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (0.0, 0.2),
+        ]
+        .map(|(x, y)| builder::vertex(Point3::new(x, y, 0.0)));
+        let corner = 0.2 - 0.2 * std::f64::consts::FRAC_1_SQRT_2;
+        let wire: Wire = vec![
+            builder::line(&v[0], &v[1]),
+            builder::circle_arc(&v[1], &v[2], Point3::new(10.5, 5.0, 0.0)),
+            builder::line(&v[2], &v[3]),
+            builder::line(&v[3], &v[4]),
+            builder::circle_arc(&v[4], &v[0], Point3::new(corner, corner, 0.0)),
+        ]
+        .into();
+        let profile = builder::try_attach_plane(&[wire]).unwrap();
+        let plate: Solid = builder::tsweep(&profile, Vector3::unit_z());
+
+        let quality = MeshQuality {
+            chord_tol: 0.01,
+            angle_tol: 5f64.to_radians(),
+            ..Default::default()
+        };
+        let adaptive = GpuMesh::from_solid(&plate, quality);
+        assert!(adaptive.normal_deviation() <= quality.angle_tol);
+
+        // Refining the whole solid for the corner crowds the bowed side too
+        let whole =
+            |tolerance| GpuMesh::from_tessellation(&plate.triangulation(tolerance).to_polygon());
+        let mut tolerance = quality.chord_tol;
+        let mut uniform = whole(tolerance);
+        while uniform.normal_deviation() > quality.angle_tol {
+            tolerance *= 0.5;
+            uniform = whole(tolerance);
+        }
+        assert!(
+            adaptive.indices.len() * 2 < uniform.indices.len(),
+            "{} vs {}",
+            adaptive.indices.len(),
+            uniform.indices.len()
+        );
+        // Faces meshed finer share their edges' points with the rest
+        assert_eq!(open_edges(&adaptive), 0);
+    }
+
+    /// Edges without exactly two triangles once corners closer than 1e-4
+    /// are welded
+    fn open_edges(mesh: &GpuMesh) -> usize {
+        let mut ids = HashMap::new();
+        let mut id = |p: [f32; 3]| {
+            let next = ids.len();
+            *ids.entry(p.map(|x| (x * 1e4).round() as i64))
+                .or_insert(next)
+        };
+        let mut sides: HashMap<(usize, usize), usize> = HashMap::new();
+        for t in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [t[0], t[1], t[2]].map(|i| id(mesh.vertices[i as usize].position));
+            for (p, q) in [(a, b), (b, c), (c, a)] {
+                if p != q {
+                    *sides.entry((p.min(q), p.max(q))).or_default() += 1;
+                }
+            }
+        }
+        sides.values().filter(|&&n| n != 2).count()
+    }
+
+    #[test]
+    fn test_decimate() {
+        // Flat faces split into many triangles lose them at no error
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let split = GpuMesh::from_solid(
+            &cube,
+            MeshQuality {
+                max_edge_len: 0.2,
+                ..Default::default()
+            },
+        );
+        let flat = split.decimate(DecimateTarget::Error(1e-9));
+        assert!(flat.indices.len() * 10 < split.indices.len());
+        assert!((flat.volume() - 1.0).abs() < 1e-6);
+        assert_eq!(flat.bounding_box(), split.bounding_box());
+        assert_eq!(open_edges(&flat), 0);
+
+        let ball = crate::model::sphere(Point3::origin(), 1.0).unwrap();
+        let mesh = GpuMesh::from_solid(&ball, 0.002);
+        let quarter = mesh.decimate(DecimateTarget::Ratio(0.25));
+        let triangles = |m: &GpuMesh| m.indices.len() / 3;
+        assert!(triangles(&quarter) <= triangles(&mesh) / 4 + 1);
+        assert!(triangles(&quarter) * 5 > triangles(&mesh));
+        let volume = 4.0 / 3.0 * std::f64::consts::PI;
+        assert!((quarter.volume() - volume).abs() < volume * 0.02);
+        assert!(open_edges(&quarter) <= open_edges(&mesh));
+
+        let painted = mesh
+            .clone()
+            .with_values(&vec![0.5; mesh.vertices.len()], (0.0, 1.0))
+            .unwrap();
+        let decimated = painted.decimate(DecimateTarget::Ratio(0.5));
+        assert_eq!(decimated.colors().unwrap().len(), decimated.vertices.len());
+    }
+
+    #[test]
+    fn test_weld_and_cache_order() {
+        let ball = crate::model::sphere(Point3::origin(), 1.0).unwrap();
+        let mesh = GpuMesh::from_solid(&ball, 0.01);
+        // One vertex per corner, with the triangles shuffled
+        let mut corners = mesh.clone();
+        let mut triangles: Vec<&[u32]> = mesh.indices.chunks_exact(3).collect();
+        triangles.sort_by_key(|t| (t[0] as u64 * 2654435761) % 1009);
+        corners.vertices = triangles
+            .iter()
+            .flat_map(|t| t.iter().map(|&i| mesh.vertices[i as usize]))
+            .collect();
+        corners.faces = mesh.faces.as_ref().map(|faces| {
+            triangles
+                .iter()
+                .flat_map(|t| t.iter().map(|&i| faces[i as usize]))
+                .collect()
+        });
+        corners.indices = (0..corners.vertices.len() as u32).collect();
+
+        corners.weld();
+        assert_eq!(corners.vertices.len(), mesh.vertices.len());
+        assert!((corners.volume() - mesh.volume()).abs() < 1e-9);
+        let shuffled = miss_ratio(&corners.indices);
+        corners.order_for_cache();
+        assert!(miss_ratio(&corners.indices) < shuffled * 0.5);
+        assert!(miss_ratio(&corners.indices) < 1.0);
+        assert!((corners.volume() - mesh.volume()).abs() < 1e-9);
+        // First use order
+        assert_eq!(corners.indices[..3], [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_face_ids() {
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        for quality in [MeshQuality::from(0.01), MeshDetail::Fine.quality(0.01)] {
+            let mesh = GpuMesh::from_solid(&cube, quality);
+            let faces = mesh.face_ids().unwrap();
+            assert_eq!(faces.len(), mesh.vertices.len());
+            let ids: std::collections::HashSet<usize> = (0..mesh.indices.len() / 3)
+                .map(|t| mesh.triangle_face(t).unwrap())
+                .collect();
+            assert_eq!(ids, (0..6).collect());
+            // Every triangle of a face lies in its plane
+            for t in 0..mesh.indices.len() / 3 {
+                let face = mesh.triangle_face(t).unwrap();
+                let normal = mesh.vertices[mesh.indices[3 * t] as usize].normal;
+                let first = (0..mesh.indices.len() / 3)
+                    .find(|&u| mesh.triangle_face(u) == Some(face))
+                    .unwrap();
+                assert_eq!(
+                    normal,
+                    mesh.vertices[mesh.indices[3 * first] as usize].normal
+                );
+            }
+            // A square face is outlined by its four sides
+            let border = mesh.face_border(0);
+            let length: f32 = border.iter().map(|[a, b]| a.distance(*b)).sum();
+            assert!((length - 4.0).abs() < 1e-5, "{}", length);
+        }
+
+        let mesh = GpuMesh::from_solid(&cube, 0.01);
+        let red = [1.0, 0.0, 0.0];
+        let painted = mesh
+            .clone()
+            .with_face_colors([0.5; 3], &HashMap::from([(2, red)]))
+            .unwrap();
+        let colors = painted.colors().unwrap();
+        for (face, color) in mesh.face_ids().unwrap().iter().zip(colors) {
+            assert_eq!(*color == red, *face == 2);
+        }
+        let plain = GpuMesh::from_tessellation(&cube.triangulation(0.01).to_polygon());
+        assert!(plain.triangle_face(0).is_none());
+        assert!(plain.with_face_colors([0.5; 3], &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_normal_modes() {
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let axis_aligned = |mesh: &GpuMesh| {
+            mesh.vertices
+                .iter()
+                .all(|v| v.normal.iter().filter(|x| x.abs() > 1e-6).count() == 1)
+        };
+        let surface = GpuMesh::from_solid(&cube, 0.01);
+        // Box corners are sharper than the crease angle, so they stay hard
+        let smooth = surface
+            .clone()
+            .with_normals(NormalMode::Smooth(NormalMode::CREASE_ANGLE));
+        assert!(axis_aligned(&smooth));
+        assert_eq!(smooth.face_ids().unwrap().len(), smooth.vertices.len());
+        // while smoothing over everything rounds them off
+        let rounded = surface
+            .clone()
+            .with_normals(NormalMode::Smooth(std::f64::consts::PI));
+        assert!(!axis_aligned(&rounded));
+
+        let rod = cylinder(Point3::origin(), Vector3::unit_z(), 1.0).unwrap();
+        let quality = |normals| MeshQuality {
+            normals,
+            ..MeshQuality::from(0.05)
+        };
+        let flat = GpuMesh::from_solid(&rod, quality(NormalMode::Flat));
+        for t in flat.indices.chunks_exact(3) {
+            let [p0, p1, p2] = [t[0], t[1], t[2]].map(|i| vec3(flat.vertices[i as usize].position));
+            let normal = (p1 - p0).cross(p2 - p0);
+            if normal.magnitude2() == 0.0 {
+                continue;
+            }
+            let normal = normal.normalize();
+            for &i in t {
+                assert!(vec3(flat.vertices[i as usize].normal).dot(normal) > 1.0 - 1e-6);
+            }
+        }
+        let smooth =
+            GpuMesh::from_solid(&rod, quality(NormalMode::Smooth(NormalMode::CREASE_ANGLE)));
+        assert!(smooth.vertices.len() < flat.vertices.len());
+        // Sides stay level and the rims sharp
+        assert!(smooth
+            .vertices
+            .iter()
+            .all(|v| v.normal[2].abs() < 1e-6 || v.normal[2].abs() > 1.0 - 1e-6));
+        assert!((smooth.volume() - flat.volume()).abs() < 1e-9);
+    }
+
+    /// Vertices transformed per triangle with a FIFO cache of 16
+    fn miss_ratio(indices: &[u32]) -> f64 {
+        let mut cache = std::collections::VecDeque::new();
+        let mut misses = 0;
+        for &i in indices {
+            if !cache.contains(&i) {
+                misses += 1;
+                cache.push_back(i);
+                if cache.len() > 16 {
+                    cache.pop_front();
+                }
+            }
+        }
+        misses as f64 / (indices.len() / 3) as f64
+    }
+
+    #[test]
+    fn test_surface_uvs() {
+        // The side of a unit cube is parameterized over the unit square
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let mesh = GpuMesh::from_solid(
+            &cube,
+            MeshQuality {
+                max_edge_len: 0.3,
+                ..Default::default()
+            },
+        );
+        let (min, max) =
+            mesh.vertices
+                .iter()
+                .fold(([f32::MAX; 2], [f32::MIN; 2]), |(lo, hi), v| {
+                    (
+                        [lo[0].min(v.uv[0]), lo[1].min(v.uv[1])],
+                        [hi[0].max(v.uv[0]), hi[1].max(v.uv[1])],
+                    )
+                });
+        assert_eq!((min, max), ([0.0; 2], [1.0; 2]));
+        // Split edges get the midpoint parameters
+        assert!(mesh.vertices.iter().any(|v| v.uv[0] > 0.0 && v.uv[0] < 1.0));
+    }
+
+    #[test]
+    fn test_vertex_colors() {
+        assert_eq!(heat_color(0.0), [0.0, 0.0, 1.0]);
+        assert_eq!(heat_color(1.0), [1.0, 0.0, 0.0]);
+        assert_eq!(heat_color(-5.0), heat_color(0.0));
+
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let mesh = GpuMesh::from_solid(&cube, 0.1);
+        assert!(mesh.colors().is_none());
+        let heights: Vec<f32> = mesh.vertices.iter().map(|v| v.position[2]).collect();
+        let painted = mesh.clone().with_values(&heights, (0.0, 1.0)).unwrap();
+        let colors = painted.colors().unwrap();
+        assert_eq!(colors.len(), mesh.vertices.len());
+        assert!(colors.contains(&heat_color(1.0)));
+        assert!(matches!(
+            mesh.with_colors(vec![[1.0; 3]]),
+            Err(SketchError::RenderFailed(_))
+        ));
     }
 }
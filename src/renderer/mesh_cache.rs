@@ -0,0 +1,167 @@
+use crate::renderer::mesh::{GpuMesh, MeshQuality, NormalMode};
+use rayon::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hasher;
+use std::io::Write;
+use truck_modeling::Solid;
+
+/// Hash of a solid's exact geometry and topology; solids built apart the
+/// same way hash the same
+pub fn geometry_hash(solid: &Solid) -> u64 {
+    let mut writer = HashWriter(DefaultHasher::new());
+    // Writing into a hasher cannot fail
+    let _ = serde_json::to_writer(&mut writer, &solid.compress());
+    writer.0.finish()
+}
+
+/// Feeds serialized bytes straight into a hasher
+struct HashWriter(DefaultHasher);
+
+impl Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A solid's [`geometry_hash`] and the bits of the quality it was meshed at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct MeshKey {
+    geometry: u64,
+    quality: [u64; 5],
+}
+
+impl MeshKey {
+    fn new(geometry: u64, quality: &MeshQuality) -> Self {
+        let (mode, crease) = match quality.normals {
+            NormalMode::Surface => (0, 0.0),
+            NormalMode::Smooth(angle) => (1, angle),
+            NormalMode::Flat => (2, 0.0),
+        };
+        Self {
+            geometry,
+            quality: [
+                quality.chord_tol.to_bits(),
+                quality.angle_tol.to_bits(),
+                quality.max_edge_len.to_bits(),
+                mode,
+                f64::to_bits(crease),
+            ],
+        }
+    }
+}
+
+/// Meshes made by [`GpuMesh::from_solid`], kept by [`geometry_hash`] and
+/// quality, so rebuilding a scene only tessellates the solids that changed
+#[derive(Default)]
+pub struct MeshCache {
+    meshes: HashMap<MeshKey, GpuMesh>,
+    /// Keys asked for since the last [`MeshCache::sweep`]
+    used: HashSet<MeshKey>,
+    /// Meshes tessellated rather than found
+    misses: usize,
+}
+
+impl MeshCache {
+    /// `GpuMesh::from_solid(solid, quality)`, tessellated only when no
+    /// solid with the same geometry `hash` was meshed at `quality` before
+    pub fn mesh(&mut self, solid: &Solid, hash: u64, quality: MeshQuality) -> GpuMesh {
+        self.meshes(&[(solid, hash, quality)]).remove(0)
+    }
+
+    /// [`MeshCache::mesh`] for each `(solid, hash, quality)`, with the
+    /// missing meshes tessellated in parallel
+    pub fn meshes(&mut self, requests: &[(&Solid, u64, MeshQuality)]) -> Vec<GpuMesh> {
+        let keys: Vec<MeshKey> = requests
+            .iter()
+            .map(|(_, hash, quality)| MeshKey::new(*hash, quality))
+            .collect();
+        // First request of each key not cached yet
+        let mut wanted = HashSet::new();
+        let missing: Vec<usize> = (0..keys.len())
+            .filter(|&i| !self.meshes.contains_key(&keys[i]) && wanted.insert(keys[i]))
+            .collect();
+        let made: Vec<GpuMesh> = missing
+            .par_iter()
+            .map(|&i| GpuMesh::from_solid(requests[i].0, requests[i].2))
+            .collect();
+        self.misses += made.len();
+        for (i, mesh) in missing.into_iter().zip(made) {
+            self.meshes.insert(keys[i], mesh);
+        }
+        self.used.extend(keys.iter().copied());
+        keys.iter().map(|key| self.meshes[key].clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.meshes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meshes.is_empty()
+    }
+
+    /// Meshes tessellated so far rather than found in the cache
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Drop the meshes not asked for since the last sweep, as once a scene
+    /// is rebuilt
+    pub fn sweep(&mut self) {
+        let used = std::mem::take(&mut self.used);
+        self.meshes.retain(|key, _| used.contains(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+    use truck_geometry::prelude::{Point3, Vector3};
+
+    #[test]
+    fn test_mesh_cache() {
+        let cube = || box_solid(Point3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let wide = box_solid(Point3::new(0.0, 0.0, 0.0), Vector3::new(2.0, 1.0, 1.0)).unwrap();
+        assert_eq!(geometry_hash(&cube()), geometry_hash(&cube()));
+        assert_ne!(geometry_hash(&cube()), geometry_hash(&wide));
+
+        let mut cache = MeshCache::default();
+        let coarse = MeshQuality::from(0.1);
+        let mesh = cache.mesh(&cube(), geometry_hash(&cube()), coarse);
+        let again = cache.mesh(&cube(), geometry_hash(&cube()), coarse);
+        assert_eq!(cache.misses(), 1);
+        assert_eq!(again.indices, mesh.indices);
+        assert_eq!(again.vertices.len(), mesh.vertices.len());
+
+        // Another quality or solid is meshed anew
+        let flat = MeshQuality {
+            normals: NormalMode::Flat,
+            ..coarse
+        };
+        cache.mesh(&cube(), geometry_hash(&cube()), flat);
+        cache.sweep();
+        cache.mesh(&wide, geometry_hash(&wide), coarse);
+        assert_eq!(cache.misses(), 3);
+        assert_eq!(cache.len(), 3);
+        // Only the wide box was asked for since the sweep
+        cache.sweep();
+        assert_eq!(cache.len(), 1);
+        cache.mesh(&wide, geometry_hash(&wide), coarse);
+        assert_eq!(cache.misses(), 3);
+
+        // A batch meshes each missing solid and quality once
+        let (a, b) = (cube(), cube());
+        let hash = geometry_hash(&a);
+        let meshes = cache.meshes(&[(&a, hash, coarse), (&wide, 0, coarse), (&b, hash, coarse)]);
+        assert_eq!(meshes.len(), 3);
+        assert_eq!(meshes[0].indices, meshes[2].indices);
+        assert_eq!(cache.misses(), 5);
+    }
+}
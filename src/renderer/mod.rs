@@ -1,7 +1,22 @@
 use crate::renderer::camera::OrbitCamera;
+use crate::sketch::{SketchError, SketchResult};
+use background::{BackgroundRenderer, BackgroundStyle};
 use eframe::wgpu;
 use eframe::wgpu::util::DeviceExt;
-use mesh::{GpuMesh, Vertex};
+use gizmo::TransformGizmo;
+use glam::Vec2;
+use grid::GridRenderer;
+use light::{LightUniforms, Lighting};
+use matcap::{Matcap, MatcapImage, ShadingMode};
+use mesh::{BoundingBox3, GpuMesh, Vertex};
+use outline::OutlineRenderer;
+use overlay::Overlay;
+use pass::{default_passes, Frame, RenderPass};
+use pick::{pick_scene, Bvh, PickResult};
+use scene::{MeshHandle, ObjectId, RenderObject, Scene};
+use serde::{Deserialize, Serialize};
+use shadow::{ShadowMap, ShadowQuality};
+use texture::{texture_bind_group_layout, ObjectTexture, TextureImage};
 
 #[repr(C)]
 #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -24,23 +39,234 @@ impl Uniforms {
     }
 }
 
+/// Depth the scene and ID targets are cleared to, and how fragments are
+/// tested against it: depth is reversed, see
+/// [`OrbitCamera::projection_matrix`]
+pub const DEPTH_CLEAR: f32 = 0.0;
+pub const DEPTH_COMPARE: wgpu::CompareFunction = wgpu::CompareFunction::Greater;
+
+/// Multisample counts offered, of which the device supports a subset
+pub const SAMPLE_COUNTS: [u32; 3] = [1, 4, 8];
+
+/// Entries of [`SAMPLE_COUNTS`] usable with `format` and the depth buffer
+pub fn supported_sample_counts(adapter: &wgpu::Adapter, format: wgpu::TextureFormat) -> Vec<u32> {
+    let color = adapter.get_texture_format_features(format).flags;
+    let depth = adapter
+        .get_texture_format_features(wgpu::TextureFormat::Depth32Float)
+        .flags;
+    SAMPLE_COUNTS
+        .into_iter()
+        .filter(|&count| {
+            count == 1
+                || (color.sample_count_supported(count) && depth.sample_count_supported(count))
+        })
+        .collect()
+}
+
+/// Highlighted object, and optionally one of its B-rep faces
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Highlight {
+    pub object: ObjectId,
+    /// Face under the cursor, see [`mesh::GpuMesh::face_ids`]; the object
+    /// is still tinted whole and the app outlines the face
+    pub face: Option<usize>,
+}
+
+/// Format of the ID target: object index + 1 and face id + 1, 0 for none
+const ID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Uint;
+
+/// Per-object shader data
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ObjectUniforms {
+    model: [[f32; 4]; 4],
+    /// Inverse transpose of `model`, for normals
+    normal: [[f32; 4]; 4],
+    color: [f32; 4],
+    /// Object index + 1 in `x` for the ID pass, where 0 is background
+    id: [u32; 4],
+    /// Highlight color, mixed in by `w`
+    highlight: [f32; 4],
+    /// [`scene::Material::texture_scale`] in `x`
+    texture: [f32; 4],
+}
+
+impl ObjectUniforms {
+    fn from_object(id: ObjectId, object: &RenderObject, highlight: [f32; 4]) -> Self {
+        let [r, g, b] = object.material.base_color;
+        Self {
+            model: object.transform.to_cols_array_2d(),
+            normal: object.transform.inverse().transpose().to_cols_array_2d(),
+            color: [r, g, b, object.opacity()],
+            id: [id.index() as u32 + 1, 0, 0, 0],
+            highlight,
+            texture: [object.material.texture_scale, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+/// How the viewport is drawn, saved with projects
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    /// Clear color (linear RGB), also the bottom of the gradient and the
+    /// horizon of the sky
+    pub background: [f64; 3],
+    pub background_style: BackgroundStyle,
+    /// Top of the gradient and zenith of the sky (linear RGB)
+    pub background_top: [f64; 3],
+    /// Ground grid on the XY plane and the origin axes
+    pub show_grid: bool,
+    /// Orientation axes in the bottom left corner
+    pub show_gizmo: bool,
+    /// Silhouette and crease lines over the parts
+    pub show_outline: bool,
+    /// Outline color (linear RGB)
+    pub outline_color: [f32; 3],
+    pub shading: ShadingMode,
+    /// Draw back faces too, tinted, instead of culling them, so open shells
+    /// and inverted faces show up rather than vanish
+    pub double_sided: bool,
+    pub lighting: Lighting,
+    /// Tint of the selected object (linear RGB), and how much of it is
+    /// mixed in
+    pub selection_tint: [f32; 4],
+    /// Tint of the object under the cursor
+    pub hover_tint: [f32; 4],
+    /// Minor and major grid lines (linear RGB)
+    pub grid_colors: [[f32; 3]; 2],
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            background: [0.1, 0.1, 0.1],
+            background_style: BackgroundStyle::default(),
+            background_top: [0.3, 0.38, 0.5],
+            show_grid: true,
+            show_gizmo: true,
+            show_outline: false,
+            outline_color: [0.02, 0.02, 0.02],
+            shading: ShadingMode::default(),
+            double_sided: false,
+            lighting: Lighting::default(),
+            selection_tint: [1.0, 0.55, 0.1, 0.45],
+            hover_tint: [0.4, 0.75, 1.0, 0.25],
+            grid_colors: [[0.13, 0.13, 0.13], [0.25, 0.25, 0.25]],
+        }
+    }
+}
+
+/// GPU buffers of one uploaded mesh
+struct MeshBuffers {
+    vertex_buffer: wgpu::Buffer,
+    /// Second vertex buffer of meshes with colors
+    color_buffer: Option<wgpu::Buffer>,
+    /// Face id + 1 per vertex, 0 for meshes without, for the ID pass
+    face_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+}
+
+/// Object-ID target and its depth buffer for GPU picking
+struct IdTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    depth: wgpu::TextureView,
+    size: (u32, u32),
+}
+
+/// Uniforms of one scene object
+struct ObjectBuffers {
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Scene object pipelines sharing one cull mode, one per
+/// [`PipelineVariant`]
+struct ScenePipelines {
+    variants: [wgpu::RenderPipeline; 8],
+}
+
+impl ScenePipelines {
+    fn get(&self, transparent: bool, vertex_colors: bool, textured: bool) -> &wgpu::RenderPipeline {
+        &self.variants
+            [transparent as usize | (vertex_colors as usize) << 1 | (textured as usize) << 2]
+    }
+}
+
+/// Which scene pipeline to build
+#[derive(Clone, Copy, Debug)]
+struct PipelineVariant {
+    cull_mode: Option<wgpu::Face>,
+    /// Alpha-blended, without depth writes
+    transparent: bool,
+    /// Taking the color from a second vertex buffer, see [`GpuMesh::colors`]
+    vertex_colors: bool,
+    /// Multiplying the color by an [`ObjectTexture`] bound as group 3
+    textured: bool,
+}
+
 pub struct Renderer {
-    pipeline: wgpu::RenderPipeline,
+    /// Back faces skipped
+    culled_pipelines: ScenePipelines,
+    /// Back faces drawn too, see [`DisplaySettings::double_sided`]
+    double_sided_pipelines: ScenePipelines,
+    // Kept to rebuild the pipeline when the sample count changes
+    shader: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+    /// With the object texture as a fourth bind group
+    textured_pipeline_layout: wgpu::PipelineLayout,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    surface_format: wgpu::TextureFormat,
+    sample_count: u32,
+    grid: GridRenderer,
+    shadow: ShadowMap,
+    outline: OutlineRenderer,
+    background: BackgroundRenderer,
+    /// Writes object IDs instead of colors, for [`Renderer::pick_gpu`]
+    id_pipeline: wgpu::RenderPipeline,
     depth_texture: wgpu::TextureView,
+    /// Multisampled color target resolved into the output, when MSAA is on
+    msaa_target: Option<wgpu::TextureView>,
+    size: (u32, u32),
+    /// Created on the first GPU pick and again after resizing
+    id_target: Option<IdTarget>,
     uniform_buffer: wgpu::Buffer,
+    light_buffer: wgpu::Buffer,
     uniform_bind_group: wgpu::BindGroup,
+    uniform_bind_group_layout: wgpu::BindGroupLayout,
+    matcap: Matcap,
+    object_bind_group_layout: wgpu::BindGroupLayout,
 
-    // Mesh data (optional, loaded later)
-    vertex_buffer: Option<wgpu::Buffer>,
-    index_buffer: Option<wgpu::Buffer>,
-    index_count: u32,
+    // What is drawn, with GPU buffers per scene mesh and object
+    scene: Scene,
+    meshes: Vec<MeshBuffers>,
+    objects: Vec<ObjectBuffers>,
+    /// Textures set with [`Renderer::set_texture`], one slot per object
+    textures: Vec<Option<ObjectTexture>>,
+    /// Ray-casting hierarchies, one per scene mesh
+    bvhs: Vec<Bvh>,
+    /// Mesh extents in mesh coordinates, one per scene mesh
+    bounds: Vec<Option<BoundingBox3>>,
+    /// Selected and hovered objects, tinted when drawn
+    highlight: Vec<Highlight>,
+    hover: Option<Highlight>,
 
     pub camera: OrbitCamera,
+    pub display: DisplaySettings,
+    /// Move/rotate handles drawn over the scene, usually on the selection
+    pub gizmo: Option<TransformGizmo>,
+    /// Measurements and debug marks drawn over everything
+    pub overlay: Overlay,
+    /// Run by [`Renderer::render`] in order
+    passes: Vec<Box<dyn RenderPass>>,
 }
 
 impl Renderer {
     pub fn new(
         device: &wgpu::Device,
+        queue: &wgpu::Queue,
         surface_format: wgpu::TextureFormat,
         width: u32,
         height: u32,
@@ -48,7 +274,13 @@ impl Renderer {
         // 1. Load shader
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("shadow_lookup.wgsl"),
+                    include_str!("shader.wgsl")
+                )
+                .into(),
+            ),
         });
 
         // 2. Create uniform buffer
@@ -58,100 +290,365 @@ impl Renderer {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
+        let light_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Light Buffer"),
+            size: std::mem::size_of::<LightUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
 
         // 3. Create bind group layout
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Bind Group Layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
                 },
-                count: None,
-            }],
+            ],
         });
 
         // 4. Create bind group
-        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Uniform Bind Group"),
-            layout: &bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
-            }],
-        });
+        let matcap = Matcap::new(device, queue, &MatcapImage::default());
+        let uniform_bind_group = Self::create_uniform_bind_group(
+            device,
+            &bind_group_layout,
+            &uniform_buffer,
+            &light_buffer,
+            &matcap,
+        );
 
-        // 5. Create pipeline layout
+        // 5. Create per-object bind group layout and pipeline layout
+        let object_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Object Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let shadow = ShadowMap::new(device, &object_bind_group_layout, ShadowQuality::default());
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
+            bind_group_layouts: &[
+                &bind_group_layout,
+                &object_bind_group_layout,
+                shadow.layout(),
+            ],
             push_constant_ranges: &[],
         });
+        let texture_bind_group_layout = texture_bind_group_layout(device);
+        let textured_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Textured Pipeline Layout"),
+                bind_group_layouts: &[
+                    &bind_group_layout,
+                    &object_bind_group_layout,
+                    shadow.layout(),
+                    &texture_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+
+        // 6. Create render pipelines, culling back faces or not
+        let pipelines = |cull_mode| {
+            Self::create_pipelines(
+                device,
+                [&pipeline_layout, &textured_pipeline_layout],
+                &shader,
+                surface_format,
+                1,
+                cull_mode,
+            )
+        };
+        let culled_pipelines = pipelines(Some(wgpu::Face::Back));
+        let double_sided_pipelines = pipelines(None);
 
-        // 6. Create render pipeline
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&pipeline_layout),
+        // 7. Create ID pipeline: same geometry, object IDs as color
+        let id_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("ID Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("id.wgsl").into()),
+        });
+        let id_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ID Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, &object_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let id_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("ID Pipeline"),
+            layout: Some(&id_layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: &id_shader,
                 entry_point: Some("vs_main"),
-                buffers: &[Vertex::desc()],
+                buffers: &[Vertex::desc(), Vertex::face_desc()],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
+                module: &id_shader,
                 entry_point: Some("fs_main"),
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
-                    blend: Some(wgpu::BlendState::REPLACE),
+                    format: ID_FORMAT,
+                    blend: None,
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
                 compilation_options: Default::default(),
             }),
+            // Back faces too, so double-sided open shells can be picked;
+            // closed solids hide theirs behind front faces anyway
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
+                cull_mode: None,
+                ..Default::default()
             },
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: wgpu::TextureFormat::Depth32Float,
                 depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
+                depth_compare: DEPTH_COMPARE,
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
+            multisample: wgpu::MultisampleState::default(),
             multiview: None,
             cache: None,
         });
 
-        // 7. Create depth texture
-        let depth_texture = Self::create_depth_texture(device, width, height);
+        // 8. Create depth texture
+        let depth_texture = Self::create_depth_texture(device, width, height, 1);
+        let mut outline = OutlineRenderer::new(device, surface_format, 1);
+        outline.set_depth(device, &depth_texture);
 
         Self {
-            pipeline,
+            culled_pipelines,
+            double_sided_pipelines,
+            shader,
+            pipeline_layout,
+            textured_pipeline_layout,
+            texture_bind_group_layout,
+            surface_format,
+            sample_count: 1,
+            grid: GridRenderer::new(device, surface_format, 1, shadow.layout()),
+            shadow,
+            outline,
+            background: BackgroundRenderer::new(device, surface_format, 1),
+            id_pipeline,
             depth_texture,
+            msaa_target: None,
+            size: (width, height),
+            id_target: None,
             uniform_buffer,
+            light_buffer,
             uniform_bind_group,
-            vertex_buffer: None,
-            index_buffer: None,
-            index_count: 0,
+            uniform_bind_group_layout: bind_group_layout,
+            matcap,
+            object_bind_group_layout,
+            scene: Scene::new(),
+            meshes: Vec::new(),
+            objects: Vec::new(),
+            textures: Vec::new(),
+            bvhs: Vec::new(),
+            bounds: Vec::new(),
+            highlight: Vec::new(),
+            hover: None,
             camera: OrbitCamera::default(),
+            display: DisplaySettings::default(),
+            gizmo: None,
+            overlay: Overlay::new(),
+            passes: default_passes(),
         }
     }
 
-    fn create_depth_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::TextureView {
+    fn create_uniform_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        light_buffer: &wgpu::Buffer,
+        matcap: &Matcap,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Uniform Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: light_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&matcap.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&matcap.sampler),
+                },
+            ],
+        })
+    }
+
+    /// Shade with `image` in [`ShadingMode::Matcap`]
+    pub fn set_matcap(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, image: &MatcapImage) {
+        self.matcap = Matcap::new(device, queue, image);
+        self.uniform_bind_group = Self::create_uniform_bind_group(
+            device,
+            &self.uniform_bind_group_layout,
+            &self.uniform_buffer,
+            &self.light_buffer,
+            &self.matcap,
+        );
+    }
+
+    /// Every variant for `cull_mode`, untextured ones with the first layout
+    fn create_pipelines(
+        device: &wgpu::Device,
+        [layout, textured_layout]: [&wgpu::PipelineLayout; 2],
+        shader: &wgpu::ShaderModule,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+        cull_mode: Option<wgpu::Face>,
+    ) -> ScenePipelines {
+        let variants = std::array::from_fn(|i| {
+            let variant = PipelineVariant {
+                cull_mode,
+                transparent: i & 1 != 0,
+                vertex_colors: i & 2 != 0,
+                textured: i & 4 != 0,
+            };
+            Self::create_pipeline(
+                device,
+                if variant.textured {
+                    textured_layout
+                } else {
+                    layout
+                },
+                shader,
+                surface_format,
+                sample_count,
+                variant,
+            )
+        });
+        ScenePipelines { variants }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+        variant: PipelineVariant,
+    ) -> wgpu::RenderPipeline {
+        let PipelineVariant {
+            cull_mode,
+            transparent,
+            vertex_colors,
+            textured,
+        } = variant;
+        let buffers = [Vertex::desc(), Vertex::color_desc()];
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(if transparent {
+                "Transparent Pipeline"
+            } else {
+                "Render Pipeline"
+            }),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some(if vertex_colors {
+                    "vs_colored"
+                } else {
+                    "vs_main"
+                }),
+                buffers: &buffers[..if vertex_colors { 2 } else { 1 }],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some(if textured { "fs_textured" } else { "fs_main" }),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(if transparent {
+                        wgpu::BlendState::ALPHA_BLENDING
+                    } else {
+                        wgpu::BlendState::REPLACE
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: !transparent,
+                depth_compare: DEPTH_COMPARE,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    fn create_depth_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        sample_count: u32,
+    ) -> wgpu::TextureView {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Depth Texture"),
             size: wgpu::Extent3d {
@@ -160,7 +657,7 @@ impl Renderer {
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
-            sample_count: 1,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Depth32Float,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
@@ -170,84 +667,657 @@ impl Renderer {
         texture.create_view(&wgpu::TextureViewDescriptor::default())
     }
 
+    /// Multisampled color target matching the depth texture, if MSAA is on
+    fn create_msaa_target(&self, device: &wgpu::Device) -> Option<wgpu::TextureView> {
+        if self.sample_count == 1 {
+            return None;
+        }
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MSAA Color Texture"),
+            size: wgpu::Extent3d {
+                width: self.size.0.max(1),
+                height: self.size.1.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.surface_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+    }
+
     /// Call when window resizes
     pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
-        self.depth_texture = Self::create_depth_texture(device, width, height);
+        self.size = (width, height);
+        self.depth_texture = Self::create_depth_texture(device, width, height, self.sample_count);
+        self.msaa_target = self.create_msaa_target(device);
+        self.outline.set_depth(device, &self.depth_texture);
+        self.id_target = None;
+    }
+
+    /// Width and height the depth and multisample targets were made for
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Samples per pixel of the rendered image
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
     }
 
-    /// Upload mesh data to GPU
+    /// Switch multisampling to 1 (off), 4 or 8 samples per pixel; frames are
+    /// resolved into the target passed to [`Renderer::render`]. Counts the
+    /// device does not support for the target format are rejected.
+    pub fn set_sample_count(
+        &mut self,
+        device: &wgpu::Device,
+        adapter: &wgpu::Adapter,
+        count: u32,
+    ) -> SketchResult<()> {
+        if count == self.sample_count {
+            return Ok(());
+        }
+        if !supported_sample_counts(adapter, self.surface_format).contains(&count) {
+            return Err(SketchError::RenderFailed(format!(
+                "{}x multisampling is not supported here",
+                count
+            )));
+        }
+        self.sample_count = count;
+        let pipelines = |cull_mode| {
+            Self::create_pipelines(
+                device,
+                [&self.pipeline_layout, &self.textured_pipeline_layout],
+                &self.shader,
+                self.surface_format,
+                count,
+                cull_mode,
+            )
+        };
+        self.culled_pipelines = pipelines(Some(wgpu::Face::Back));
+        self.double_sided_pipelines = pipelines(None);
+        self.grid = GridRenderer::new(device, self.surface_format, count, self.shadow.layout());
+        self.outline = OutlineRenderer::new(device, self.surface_format, count);
+        self.background = BackgroundRenderer::new(device, self.surface_format, count);
+        let (width, height) = self.size;
+        self.resize(device, width, height);
+        Ok(())
+    }
+
+    pub fn shadow_quality(&self) -> ShadowQuality {
+        self.shadow.quality()
+    }
+
+    /// Resize the key light shadow map, or stop drawing shadows with
+    /// [`ShadowQuality::Off`]
+    pub fn set_shadow_quality(&mut self, device: &wgpu::Device, quality: ShadowQuality) {
+        self.shadow.set_quality(device, quality);
+    }
+
+    /// Upload mesh data to GPU, replacing everything shown
     pub fn set_mesh(&mut self, device: &wgpu::Device, mesh: &GpuMesh) {
-        self.vertex_buffer = Some(
-            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Vertex Buffer"),
-                contents: bytemuck::cast_slice(&mesh.vertices),
-                usage: wgpu::BufferUsages::VERTEX,
-            }),
-        );
+        self.set_meshes(device, std::slice::from_ref(mesh));
+    }
 
-        self.index_buffer = Some(
-            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(&mesh.indices),
-                usage: wgpu::BufferUsages::INDEX,
-            }),
-        );
+    /// Upload several meshes in world coordinates as one object each,
+    /// replacing everything shown
+    pub fn set_meshes(&mut self, device: &wgpu::Device, meshes: &[GpuMesh]) {
+        let mut scene = Scene::new();
+        for (i, mesh) in meshes.iter().enumerate() {
+            scene.add(format!("mesh {}", i + 1), mesh.clone());
+        }
+        self.set_scene(device, scene);
+    }
 
-        self.index_count = mesh.indices.len() as u32;
+    /// Upload the meshes of `scene` and show its objects, replacing
+    /// everything shown
+    pub fn set_scene(&mut self, device: &wgpu::Device, scene: Scene) {
+        self.meshes = scene
+            .meshes()
+            .iter()
+            .map(|mesh| MeshBuffers {
+                vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&mesh.vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+                color_buffer: mesh.colors().map(|colors| {
+                    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Vertex Color Buffer"),
+                        contents: bytemuck::cast_slice(colors),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    })
+                }),
+                face_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Face ID Buffer"),
+                    contents: bytemuck::cast_slice(&match mesh.face_ids() {
+                        Some(faces) => faces.iter().map(|face| face + 1).collect(),
+                        None => vec![0u32; mesh.vertices.len()],
+                    }),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+                index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Index Buffer"),
+                    contents: bytemuck::cast_slice(&mesh.indices),
+                    usage: wgpu::BufferUsages::INDEX,
+                }),
+                index_count: mesh.indices.len() as u32,
+            })
+            .collect();
+        self.objects = scene
+            .objects()
+            .map(|(id, object)| {
+                let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Object Uniform Buffer"),
+                    contents: bytemuck::cast_slice(&[ObjectUniforms::from_object(
+                        id, object, [0.0; 4],
+                    )]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Object Bind Group"),
+                    layout: &self.object_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    }],
+                });
+                ObjectBuffers {
+                    uniform_buffer,
+                    bind_group,
+                }
+            })
+            .collect();
+        self.textures = scene.objects().map(|_| None).collect();
+        self.bvhs = scene.meshes().iter().map(Bvh::new).collect();
+        self.bounds = scene.meshes().iter().map(GpuMesh::bounding_box).collect();
+        self.scene = scene;
+        self.highlight.clear();
+        self.hover = None;
     }
 
-    /// Render to a texture view
-    pub fn render(
-        &self,
-        encoder: &mut wgpu::CommandEncoder,
-        target: &wgpu::TextureView,
+    /// Wrap `image` around object `id` by its surface parameters, or draw it
+    /// plain again with `None`; kept until the next [`Renderer::set_scene`]
+    pub fn set_texture(
+        &mut self,
+        device: &wgpu::Device,
         queue: &wgpu::Queue,
-        width: u32,
-        height: u32,
+        id: ObjectId,
+        image: Option<&TextureImage>,
     ) {
-        // Update uniforms
+        if let Some(slot) = self.textures.get_mut(id.index()) {
+            *slot = image.map(|image| {
+                ObjectTexture::new(device, queue, &self.texture_bind_group_layout, image)
+            });
+        }
+    }
+
+    /// Highlight the selected objects, or nothing with an empty slice; see
+    /// [`Highlight::face`] for narrowing one to a face
+    pub fn set_highlight(&mut self, highlights: &[Highlight]) {
+        self.highlight = highlights.to_vec();
+    }
+
+    pub fn highlight(&self) -> &[Highlight] {
+        &self.highlight
+    }
+
+    /// Highlight `object` more faintly as the one under the cursor
+    pub fn set_hover(&mut self, object: Option<ObjectId>, face: Option<usize>) {
+        self.hover = object.map(|object| Highlight { object, face });
+    }
+
+    pub fn hover(&self) -> Option<Highlight> {
+        self.hover
+    }
+
+    /// Tint of object `id`; selection wins over hover
+    fn highlight_tint(&self, id: ObjectId) -> [f32; 4] {
+        if self.highlight.iter().any(|h| h.object == id) {
+            self.display.selection_tint
+        } else if self.hover.is_some_and(|h| h.object == id) {
+            self.display.hover_tint
+        } else {
+            [0.0; 4]
+        }
+    }
+
+    /// What is being drawn
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    /// Change the transform, material or visibility of a shown object; the
+    /// next frame picks it up without uploading again
+    pub fn object_mut(&mut self, id: ObjectId) -> Option<&mut RenderObject> {
+        self.scene.object_mut(id)
+    }
+
+    /// World-space extent of one object, whether shown or not
+    pub fn object_bounds(&self, id: ObjectId) -> Option<BoundingBox3> {
+        let object = self.scene.object(id)?;
+        let bounds = self.bounds.get(object.mesh.index()).copied().flatten()?;
+        Some(bounds.transformed(object.transform))
+    }
+
+    /// World-space extent of the visible objects
+    pub fn scene_bounds(&self) -> Option<BoundingBox3> {
+        self.scene
+            .objects()
+            .filter(|(_, object)| object.visible)
+            .filter_map(|(id, _)| self.object_bounds(id))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Fit the camera's clipping planes around the visible objects and the
+    /// grid, unless they are set by hand; call before each frame
+    pub fn fit_clip_planes(&mut self) {
+        let grid = self
+            .display
+            .show_grid
+            .then(|| grid::grid_bounds(&self.camera));
+        let bounds = [self.scene_bounds(), grid]
+            .into_iter()
+            .flatten()
+            .reduce(|a, b| a.union(&b));
+        if let Some(bounds) = bounds {
+            self.camera.fit_clip_planes(&bounds);
+        }
+    }
+
+    /// Move the camera so `bounds` fills the view, keeping its direction
+    pub fn fit_view(&mut self, bounds: &BoundingBox3) {
+        let aspect = self.size.0.max(1) as f32 / self.size.1.max(1) as f32;
+        self.camera.fit(bounds, aspect);
+    }
+
+    /// Nearest visible object under pixel `screen_pos` (from the top left)
+    /// of a `viewport`-sized view, found by casting a ray on the CPU
+    pub fn pick(&self, screen_pos: Vec2, viewport: Vec2) -> Option<PickResult> {
+        let ray = self.camera.screen_ray(screen_pos, viewport);
+        pick_scene(&self.scene, &self.bvhs, &ray)
+    }
+
+    /// Visible object and B-rep face at pixel (`x`, `y`) from the top left,
+    /// read back from a one-pixel ID pass; cheaper than [`Renderer::pick`]
+    /// for big scenes
+    pub fn pick_gpu(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        x: u32,
+        y: u32,
+    ) -> Option<Highlight> {
+        let (width, height) = (self.size.0.max(1), self.size.1.max(1));
+        if x >= width || y >= height || self.scene.is_empty() {
+            return None;
+        }
+        if self
+            .id_target
+            .as_ref()
+            .is_none_or(|t| t.size != (width, height))
+        {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("ID Target"),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: ID_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            self.id_target = Some(IdTarget {
+                view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                texture,
+                depth: Self::create_depth_texture(device, width, height, 1),
+                size: (width, height),
+            });
+        }
+        let target = self.id_target.as_ref()?;
+
+        self.write_uniforms(queue, width, height);
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ID Readback"),
+            size: 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("ID Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("ID Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &target.depth,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(DEPTH_CLEAR),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            // Only the picked pixel is shaded
+            render_pass.set_scissor_rect(x, y, 1, 1);
+            render_pass.set_pipeline(&self.id_pipeline);
+            render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            let pickable = self.scene.objects().filter(|(_, o)| o.is_pickable());
+            self.draw_objects(&mut render_pass, pickable.map(|(id, _)| id), true);
+        }
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &target.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: None,
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver.recv().ok()?.ok()?;
+        let texel = slice.get_mapped_range();
+        let channel = |i: usize| u32::from_le_bytes(texel[4 * i..][..4].try_into().unwrap());
+        let (object, face) = (channel(0), channel(1));
+        drop(texel);
+        readback.unmap();
+        let index = object.checked_sub(1)? as usize;
+        let (object, _) = self.scene.objects().nth(index)?;
+        Some(Highlight {
+            object,
+            face: face.checked_sub(1).map(|face| face as usize),
+        })
+    }
+
+    /// Upload camera, light and per-object uniforms for a `width` × `height`
+    /// frame; true when the shadow pass has to be drawn
+    fn write_uniforms(&self, queue: &wgpu::Queue, width: u32, height: u32) -> bool {
         let aspect = width as f32 / height.max(1) as f32;
         let uniforms = Uniforms::from_camera(&self.camera, aspect);
         queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+        let lights = LightUniforms::new(
+            &self.display.lighting,
+            self.camera.view_matrix(),
+            self.display.shading,
+        );
+        queue.write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(&[lights]));
+        // Take in the ground under the parts so they shadow the grid; a
+        // matcap has its lighting baked in and casts none
+        let bounds = self
+            .scene_bounds()
+            .filter(|_| self.display.shading == ShadingMode::Lit)
+            .map(|b| b.union(&BoundingBox3::new(b.min.with_z(0.0), b.max.with_z(0.0))));
+        let key = glam::Vec4::from(lights.key_direction).truncate();
+        let shadowed = self.shadow.prepare(queue, key, bounds);
+        for ((id, object), buffers) in self.scene.objects().zip(&self.objects) {
+            let uniforms = ObjectUniforms::from_object(id, object, self.highlight_tint(id));
+            queue.write_buffer(
+                &buffers.uniform_buffer,
+                0,
+                bytemuck::cast_slice(&[uniforms]),
+            );
+        }
+        shadowed
+    }
+
+    /// Visible opaque objects, in scene order
+    fn opaque_objects(&self) -> impl Iterator<Item = ObjectId> + '_ {
+        self.scene
+            .objects()
+            .filter(|(_, object)| object.visible && !object.is_transparent())
+            .map(|(id, _)| id)
+    }
+
+    /// Visible transparent objects, farthest from the eye first so blending
+    /// composes front over back
+    fn transparent_objects(&self) -> Vec<ObjectId> {
+        let eye = self.camera.eye_position();
+        let mut sorted: Vec<(ObjectId, f32)> = self
+            .scene
+            .objects()
+            .filter(|(_, object)| object.visible && object.is_transparent())
+            .map(|(id, object)| {
+                let center = self
+                    .object_bounds(id)
+                    .map_or(object.transform.w_axis.truncate(), |b| b.center());
+                (id, center.distance_squared(eye))
+            })
+            .collect();
+        sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+        sorted.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Level of detail of `object` for its current size on screen
+    fn lod_mesh(&self, id: ObjectId, object: &RenderObject) -> MeshHandle {
+        match self.object_bounds(id) {
+            Some(bounds) if !object.lods.is_empty() => {
+                let scale = self
+                    .camera
+                    .pixels_per_unit(bounds.center(), self.size.1.max(1) as f32);
+                object.mesh_for_size(bounds.radius() * scale)
+            }
+            _ => object.mesh,
+        }
+    }
+
+    /// Draw the visible objects among `ids` with the pipeline and shared
+    /// bind groups already set; the second vertex buffer holds face ids
+    /// with `face_ids`, for the ID pass, and colors otherwise
+    fn draw_objects(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        ids: impl IntoIterator<Item = ObjectId>,
+        face_ids: bool,
+    ) {
+        for id in ids {
+            let (Some(object), Some(buffers)) =
+                (self.scene.object(id), self.objects.get(id.index()))
+            else {
+                continue;
+            };
+            if !object.visible {
+                continue;
+            }
+            let mesh = &self.meshes[self.lod_mesh(id, object).index()];
+            render_pass.set_bind_group(1, &buffers.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            if face_ids {
+                render_pass.set_vertex_buffer(1, mesh.face_buffer.slice(..));
+            } else if let Some(colors) = &mesh.color_buffer {
+                render_pass.set_vertex_buffer(1, colors.slice(..));
+            }
+            render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+        }
+    }
 
-        // Begin render pass
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+    /// Whether `id` is drawn from a mesh with vertex colors
+    fn has_vertex_colors(&self, id: ObjectId) -> bool {
+        self.scene.object(id).is_some_and(|object| {
+            self.meshes[self.lod_mesh(id, object).index()]
+                .color_buffer
+                .is_some()
+        })
+    }
+
+    fn texture(&self, id: ObjectId) -> Option<&ObjectTexture> {
+        self.textures.get(id.index()).and_then(Option::as_ref)
+    }
+
+    /// Draw the visible objects among `ids` in order with the shaded
+    /// pipelines, switching to the vertex-color ones where meshes have colors
+    /// and to the textured ones where objects have textures
+    fn draw_shaded(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        pipelines: &ScenePipelines,
+        ids: &[ObjectId],
+        transparent: bool,
+    ) {
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_bind_group(2, self.shadow.bind_group(), &[]);
+        let variant = |id| (self.has_vertex_colors(id), self.texture(id).is_some());
+        for run in ids.chunk_by(|&a, &b| variant(a) == variant(b)) {
+            let (colored, textured) = variant(run[0]);
+            render_pass.set_pipeline(pipelines.get(transparent, colored, textured));
+            if !textured {
+                self.draw_objects(render_pass, run.iter().copied(), false);
+                continue;
+            }
+            for &id in run {
+                if let Some(texture) = self.texture(id) {
+                    render_pass.set_bind_group(3, &texture.bind_group, &[]);
+                }
+                self.draw_objects(render_pass, [id], false);
+            }
+        }
+    }
+
+    /// Where the scene is drawn: the MSAA target if on, else `target`
+    fn color_target<'t>(&'t self, target: &'t wgpu::TextureView) -> &'t wgpu::TextureView {
+        self.msaa_target.as_ref().unwrap_or(target)
+    }
+
+    /// Pass drawing the scene into `target`, clearing it first or adding to
+    /// what earlier passes drew
+    fn begin_main_pass<'e>(
+        &self,
+        encoder: &'e mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        clear: bool,
+    ) -> wgpu::RenderPass<'e> {
+        let (color_load, depth_load) = if clear {
+            let [r, g, b] = self.display.background;
+            (
+                wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a: 1.0 }),
+                wgpu::LoadOp::Clear(DEPTH_CLEAR),
+            )
+        } else {
+            (wgpu::LoadOp::Load, wgpu::LoadOp::Load)
+        };
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: target,
-                resolve_target: None,
+                view: self.color_target(target),
+                resolve_target: self.msaa_target.as_ref().map(|_| target),
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.1,
-                        g: 0.1,
-                        b: 0.1,
-                        a: 1.0,
-                    }),
+                    load: color_load,
                     store: wgpu::StoreOp::Store,
                 },
             })],
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                 view: &self.depth_texture,
                 depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
+                    load: depth_load,
                     store: wgpu::StoreOp::Store,
                 }),
                 stencil_ops: None,
             }),
             timestamp_writes: None,
             occlusion_query_set: None,
-        });
+        })
+    }
 
-        // Draw mesh if loaded
-        if let (Some(vb), Some(ib)) = (&self.vertex_buffer, &self.index_buffer) {
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, vb.slice(..));
-            render_pass.set_index_buffer(ib.slice(..), wgpu::IndexFormat::Uint32);
-            render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    /// Render to a texture view, running the passes in order
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        queue: &wgpu::Queue,
+        width: u32,
+        height: u32,
+    ) {
+        // Uniforms shared by the passes
+        let shadowed = self.write_uniforms(queue, width, height);
+        let aspect = width as f32 / height.max(1) as f32;
+        self.grid
+            .prepare(queue, &self.camera, aspect, self.display.grid_colors);
+
+        let frame = Frame {
+            renderer: self,
+            queue,
+            target,
+            size: (width, height),
+            shadowed,
+        };
+        for pass in &self.passes {
+            pass.record(&frame, encoder);
         }
     }
+
+    /// Names of the passes [`Renderer::render`] runs, in order
+    pub fn pass_names(&self) -> Vec<&'static str> {
+        self.passes.iter().map(|pass| pass.name()).collect()
+    }
+
+    /// Run `pass` before the one named `before`, or last when there is none
+    /// by that name
+    pub fn insert_pass(&mut self, before: &str, pass: Box<dyn RenderPass>) {
+        let index = self
+            .passes
+            .iter()
+            .position(|p| p.name() == before)
+            .unwrap_or(self.passes.len());
+        self.passes.insert(index, pass);
+    }
+
+    /// Take out the pass named `name`, e.g. to replace it
+    pub fn remove_pass(&mut self, name: &str) -> Option<Box<dyn RenderPass>> {
+        let index = self.passes.iter().position(|p| p.name() == name)?;
+        Some(self.passes.remove(index))
+    }
 }
 
+pub mod background;
 pub mod camera;
+pub mod gizmo;
+pub mod grid;
+pub mod jobs;
+pub mod light;
+pub mod matcap;
 pub mod mesh;
+pub mod mesh_cache;
+pub mod offscreen;
+pub mod outline;
+pub mod overlay;
+pub mod pass;
+pub mod pick;
+pub mod scene;
+pub mod shadow;
+pub mod texture;
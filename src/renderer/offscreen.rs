@@ -0,0 +1,724 @@
+use crate::model::Assembly;
+use crate::renderer::camera::{OrbitCamera, Turntable};
+use crate::renderer::mesh::display_tolerance;
+use crate::renderer::scene::Scene;
+use crate::renderer::{Highlight, Renderer};
+use crate::sketch::{SketchError, SketchResult};
+use eframe::wgpu;
+use std::path::{Path, PathBuf};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Largest width or height of an offscreen image; the device is set up with
+/// wgpu's default limits
+pub fn max_image_size() -> u32 {
+    wgpu::Limits::default().max_texture_dimension_2d
+}
+
+/// Renderer drawing into a texture on its own windowless device, for
+/// thumbnails and turntable frames
+pub struct OffscreenRenderer {
+    adapter: wgpu::Adapter,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    renderer: Renderer,
+    target: wgpu::Texture,
+    size: (u32, u32),
+}
+
+impl OffscreenRenderer {
+    /// Set up a `width` × `height` target on the first adapter wgpu finds,
+    /// or on its software fallback on machines without a GPU
+    pub fn new(size: (u32, u32)) -> SketchResult<Self> {
+        let (width, height) = (size.0.max(1), size.1.max(1));
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let request = |force_fallback_adapter| {
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                force_fallback_adapter,
+                compatible_surface: None,
+            }))
+        };
+        let adapter = request(false)
+            .or_else(|| request(true))
+            .ok_or_else(|| SketchError::RenderFailed("no graphics adapter found".to_string()))?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+                .map_err(|e| SketchError::RenderFailed(e.to_string()))?;
+        let max = device.limits().max_texture_dimension_2d;
+        if width > max || height > max {
+            return Err(SketchError::RenderFailed(format!(
+                "{} × {} image is over the {} pixel limit",
+                width, height, max
+            )));
+        }
+
+        let renderer = Renderer::new(&device, &queue, FORMAT, width, height);
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        Ok(Self {
+            adapter,
+            device,
+            queue,
+            renderer,
+            target,
+            size: (width, height),
+        })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Render with 1, 4 or 8 samples per pixel where the adapter allows
+    pub fn set_sample_count(&mut self, count: u32) -> SketchResult<()> {
+        self.renderer
+            .set_sample_count(&self.device, &self.adapter, count)
+    }
+
+    /// Tessellate and upload the parts to draw, replacing earlier ones
+    pub fn set_assembly(&mut self, assembly: &Assembly) {
+        let scene = Scene::from_assembly(assembly, display_tolerance(assembly));
+        self.renderer.set_scene(&self.device, scene);
+    }
+
+    /// One frame seen from `camera`, as RGBA8 rows from the top
+    pub fn render(&mut self, camera: &OrbitCamera) -> SketchResult<Vec<u8>> {
+        let (width, height) = self.size;
+        self.renderer.camera = *camera;
+        self.renderer.fit_clip_planes();
+
+        // Buffer rows have to be padded to the copy alignment
+        let row = width as usize * 4;
+        let padded_row = row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize);
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen Readback"),
+            size: (padded_row * height as usize) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let view = self
+            .target
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Offscreen Encoder"),
+            });
+        self.renderer
+            .render(&mut encoder, &view, &self.queue, width, height);
+        encoder.copy_texture_to_buffer(
+            self.target.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row as u32),
+                    rows_per_image: Some(height),
+                },
+            },
+            self.target.size(),
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|e| SketchError::RenderFailed(e.to_string()))?
+            .map_err(|e| SketchError::RenderFailed(e.to_string()))?;
+
+        let pixels = slice
+            .get_mapped_range()
+            .chunks_exact(padded_row)
+            .flat_map(|padded| &padded[..row])
+            .copied()
+            .collect();
+        readback.unmap();
+        Ok(pixels)
+    }
+
+    /// `camera` looking the same way but moved so the uploaded parts fill
+    /// the target
+    pub fn framed(&self, camera: &OrbitCamera) -> OrbitCamera {
+        let mut framed = *camera;
+        if let Some(bounds) = self.renderer.scene_bounds() {
+            let (width, height) = self.size;
+            framed.fit(&bounds, width as f32 / height as f32);
+        }
+        framed
+    }
+
+    /// One frame seen from `camera`, written as a PNG
+    pub fn render_png(&mut self, camera: &OrbitCamera, path: impl AsRef<Path>) -> SketchResult<()> {
+        let pixels = self.render(camera)?;
+        write_png(path, self.size, &pixels)
+    }
+
+    /// One turn of `turntable` from `camera` as `frame_count` numbered PNGs
+    /// (`frame_0000.png`, ...) in `dir`; returns the files written
+    pub fn render_turntable(
+        &mut self,
+        camera: &OrbitCamera,
+        turntable: &Turntable,
+        frame_count: usize,
+        dir: impl AsRef<Path>,
+    ) -> SketchResult<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir).map_err(|e| {
+            SketchError::RenderFailed(format!("could not create {}: {}", dir.display(), e))
+        })?;
+        let mut paths = Vec::with_capacity(frame_count);
+        for (i, frame) in turntable.frames(camera, frame_count).iter().enumerate() {
+            let path = dir.join(format!("frame_{:04}.png", i));
+            self.render_png(frame, &path)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Object and face seen from `camera` at pixel (`x`, `y`) from the top
+    /// left
+    pub fn pick_gpu(&mut self, camera: &OrbitCamera, x: u32, y: u32) -> Option<Highlight> {
+        self.renderer.camera = *camera;
+        self.renderer.fit_clip_planes();
+        self.renderer.pick_gpu(&self.device, &self.queue, x, y)
+    }
+}
+
+/// Render `assembly` from `camera` into a `width` × `height` PNG without
+/// opening a window
+pub fn render_to_png(
+    assembly: &Assembly,
+    camera: &OrbitCamera,
+    size: (u32, u32),
+    path: impl AsRef<Path>,
+) -> SketchResult<()> {
+    let mut offscreen = OffscreenRenderer::new(size)?;
+    offscreen.set_assembly(assembly);
+    offscreen.render_png(camera, path)
+}
+
+/// Render one turn of `turntable` around `assembly` as `frame_count`
+/// numbered PNGs (`frame_0000.png`, ...) in `dir`, starting from `camera`;
+/// returns the files written
+pub fn render_turntable(
+    assembly: &Assembly,
+    camera: &OrbitCamera,
+    turntable: &Turntable,
+    frame_count: usize,
+    size: (u32, u32),
+    dir: impl AsRef<Path>,
+) -> SketchResult<Vec<PathBuf>> {
+    let mut offscreen = OffscreenRenderer::new(size)?;
+    offscreen.set_assembly(assembly);
+    offscreen.render_turntable(camera, turntable, frame_count, dir)
+}
+
+/// Write RGBA8 rows (top first) as an 8-bit sRGB PNG
+pub fn write_png(path: impl AsRef<Path>, size: (u32, u32), rgba: &[u8]) -> SketchResult<()> {
+    let path = path.as_ref();
+    let bytes = encode_png(size, rgba)?;
+    std::fs::write(path, bytes).map_err(|e| {
+        SketchError::RenderFailed(format!("could not write {}: {}", path.display(), e))
+    })
+}
+
+/// RGBA8 rows (top first) as the bytes of an 8-bit sRGB PNG
+pub fn encode_png(size: (u32, u32), rgba: &[u8]) -> SketchResult<Vec<u8>> {
+    let failed =
+        |e: png::EncodingError| SketchError::RenderFailed(format!("could not encode PNG: {}", e));
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, size.0, size.1);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    let mut writer = encoder.write_header().map_err(failed)?;
+    writer.write_image_data(rgba).map_err(failed)?;
+    writer.finish().map_err(failed)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+    use crate::renderer::background::BackgroundStyle;
+    use crate::renderer::matcap::{MatcapImage, ShadingMode};
+    use crate::renderer::mesh::GpuMesh;
+    use crate::renderer::scene::DisplayMode;
+    use crate::renderer::texture::TextureImage;
+    use std::fs::File;
+    use truck_geometry::prelude::*;
+
+    /// Renderer for the GPU checks; they fail rather than pass unchecked on
+    /// machines where not even the software adapter is available
+    fn offscreen_renderer(size: (u32, u32)) -> OffscreenRenderer {
+        OffscreenRenderer::new(size).expect("no graphics adapter, not even wgpu's fallback")
+    }
+
+    #[test]
+    fn test_offscreen_png() {
+        let path = std::env::temp_dir().join("truck_playground_offscreen_test.png");
+        let checker: Vec<u8> = (0..4u8)
+            .flat_map(|i| [i * 60, 0, 255 - i * 60, 255])
+            .collect();
+        write_png(&path, (2, 2), &checker).unwrap();
+        let decoder = png::Decoder::new(std::io::BufReader::new(File::open(&path).unwrap()));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, checker);
+
+        let mut offscreen = offscreen_renderer((64, 48));
+        let mut assembly = Assembly::new();
+        let cube = box_solid(Point3::new(-1.0, -1.0, -1.0), Vector3::new(2.0, 2.0, 2.0)).unwrap();
+        assembly.add("cube", cube, Matrix4::identity()).unwrap();
+        offscreen.set_assembly(&assembly);
+        let camera = OrbitCamera {
+            distance: 8.0,
+            ..Default::default()
+        };
+        let frame = offscreen.render(&camera).unwrap();
+        assert_eq!(frame.len(), 64 * 48 * 4);
+        // The cube covers the centre and the background the corner
+        let pixel = |x: usize, y: usize| &frame[(y * 64 + x) * 4..][..4];
+        assert_ne!(pixel(32, 24), pixel(0, 0));
+
+        // Multisampled frames resolve into the same image
+        offscreen.set_sample_count(4).unwrap();
+        let smooth = offscreen.render(&camera).unwrap();
+        assert_eq!(smooth.len(), frame.len());
+        assert_eq!(&smooth[(24 * 64 + 32) * 4..][..4], pixel(32, 24));
+        assert_eq!(&smooth[..4], pixel(0, 0));
+        assert!(offscreen.set_sample_count(3).is_err());
+
+        // Targets past the device limit are refused instead of panicking
+        let huge = max_image_size() + 1;
+        assert!(matches!(
+            OffscreenRenderer::new((huge, 16)),
+            Err(SketchError::RenderFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_gpu_pick() {
+        let mut offscreen = offscreen_renderer((64, 48));
+        let cube = box_solid(Point3::new(-1.0, -1.0, -1.0), Vector3::new(2.0, 2.0, 2.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly
+            .add(
+                "left",
+                cube.clone(),
+                Matrix4::from_translation(Vector3::new(-2.0, 0.0, 0.0)),
+            )
+            .unwrap();
+        assembly
+            .add(
+                "right",
+                cube,
+                Matrix4::from_translation(Vector3::new(2.0, 0.0, 0.0)),
+            )
+            .unwrap();
+        offscreen.set_assembly(&assembly);
+        let camera = OrbitCamera {
+            distance: 12.0,
+            azimuth_rad: 0.0,
+            elevation_rad: 0.0,
+            ..Default::default()
+        };
+        let scene = offscreen.renderer.scene();
+        let (left, right) = (scene.find("left"), scene.find("right"));
+        for (x, y, expected) in [
+            (18, 24, left),
+            (46, 24, right),
+            (32, 2, None),
+            (64, 0, None),
+        ] {
+            let hit = offscreen.pick_gpu(&camera, x, y);
+            assert_eq!(hit.map(|hit| hit.object), expected);
+        }
+
+        // The same face as a ray cast through the pixel's centre
+        let hit = offscreen.pick_gpu(&camera, 18, 24).unwrap();
+        let ray = offscreen
+            .renderer
+            .pick(glam::Vec2::new(18.5, 24.5), glam::Vec2::new(64.0, 48.0))
+            .unwrap();
+        assert!(hit.face.is_some());
+        assert_eq!(hit.face, ray.face);
+
+        // Highlighting tints only the chosen object
+        let pixel = |frame: &[u8], x: usize| frame[(24 * 64 + x) * 4..][..4].to_vec();
+        let plain = offscreen.render(&camera).unwrap();
+        let selected = right.map(|object| crate::renderer::Highlight { object, face: None });
+        offscreen.renderer.set_highlight(&Vec::from_iter(selected));
+        let lit = offscreen.render(&camera).unwrap();
+        assert_eq!(pixel(&lit, 18), pixel(&plain, 18));
+        assert_ne!(pixel(&lit, 46), pixel(&plain, 46));
+        assert!(pixel(&lit, 46)[0] > pixel(&lit, 46)[2]);
+        offscreen.renderer.set_hover(left, None);
+        let hovered = offscreen.render(&camera).unwrap();
+        assert_ne!(pixel(&hovered, 18), pixel(&plain, 18));
+        assert_eq!(pixel(&hovered, 46), pixel(&lit, 46));
+    }
+
+    #[test]
+    fn test_ghost_blending() {
+        let mut offscreen = offscreen_renderer((64, 48));
+        let cube = box_solid(Point3::new(-1.0, -1.0, -1.0), Vector3::new(2.0, 2.0, 2.0)).unwrap();
+        let mut assembly = Assembly::new();
+        let shift = |z| Matrix4::from_translation(Vector3::new(0.0, 0.0, z));
+        assembly.add("front", cube.clone(), shift(2.0)).unwrap();
+        assembly.add("back", cube, shift(-2.0)).unwrap();
+        offscreen.set_assembly(&assembly);
+        let camera = OrbitCamera {
+            distance: 12.0,
+            azimuth_rad: 0.0,
+            elevation_rad: 0.0,
+            ..Default::default()
+        };
+        let centre = |frame: Vec<u8>| frame[(24 * 64 + 32) * 4..][..4].to_vec();
+        let scene = offscreen.renderer.scene();
+        let (front, back) = (scene.find("front").unwrap(), scene.find("back").unwrap());
+        offscreen
+            .renderer
+            .object_mut(front)
+            .unwrap()
+            .material
+            .base_color = [0.8, 0.1, 0.1];
+        let solid = centre(offscreen.render(&camera).unwrap());
+
+        // A ghost in front lets the part behind show through
+        offscreen.renderer.object_mut(front).unwrap().display = DisplayMode::Ghost;
+        let ghosted = centre(offscreen.render(&camera).unwrap());
+        offscreen.renderer.object_mut(front).unwrap().visible = false;
+        let hidden = centre(offscreen.render(&camera).unwrap());
+        assert_ne!(ghosted, solid);
+        assert_ne!(ghosted, hidden);
+        let hit = offscreen.pick_gpu(&camera, 32, 24);
+        assert_eq!(hit.map(|hit| hit.object), Some(back));
+
+        // Blended objects are drawn back to front
+        offscreen.renderer.object_mut(front).unwrap().visible = true;
+        offscreen
+            .renderer
+            .object_mut(back)
+            .unwrap()
+            .material
+            .opacity = 0.5;
+        assert_eq!(offscreen.renderer.transparent_objects(), vec![back, front]);
+    }
+
+    #[test]
+    fn test_outline() {
+        let mut offscreen = offscreen_renderer((64, 48));
+        let cube = box_solid(Point3::new(-1.0, -1.0, -1.0), Vector3::new(2.0, 2.0, 2.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly.add("cube", cube, Matrix4::identity()).unwrap();
+        offscreen.set_assembly(&assembly);
+        offscreen.renderer.display.show_grid = false;
+        offscreen.renderer.display.show_gizmo = false;
+        offscreen.renderer.display.outline_color = [1.0, 0.0, 0.0];
+        let camera = OrbitCamera {
+            distance: 8.0,
+            ..Default::default()
+        };
+        let plain = offscreen.render(&camera).unwrap();
+        offscreen.renderer.display.show_outline = true;
+        let outlined = offscreen.render(&camera).unwrap();
+
+        // Only edge pixels change, to the outline color; face interiors and
+        // the background stay as they were
+        let pixels = |frame: &[u8]| frame.chunks(4).map(<[u8]>::to_vec).collect::<Vec<_>>();
+        let (plain, outlined) = (pixels(&plain), pixels(&outlined));
+        let background = plain[0].clone();
+        let changed: Vec<_> = (0..plain.len())
+            .filter(|&i| plain[i] != outlined[i])
+            .collect();
+        let part = plain.iter().filter(|&p| *p != background).count();
+        assert!(!changed.is_empty());
+        assert!(changed.iter().all(|&i| outlined[i] == [255, 0, 0, 255]));
+        assert!(changed.len() < part);
+        assert_eq!(outlined[0], background);
+    }
+
+    #[test]
+    fn test_matcap_shading() {
+        let mut offscreen = offscreen_renderer((64, 48));
+        let cube = box_solid(Point3::new(-1.0, -1.0, -1.0), Vector3::new(2.0, 2.0, 2.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly.add("cube", cube, Matrix4::identity()).unwrap();
+        offscreen.set_assembly(&assembly);
+        let camera = OrbitCamera {
+            distance: 8.0,
+            azimuth_rad: 0.3,
+            ..Default::default()
+        };
+        let centre = |frame: Vec<u8>| frame[(24 * 64 + 32) * 4..][..4].to_vec();
+        let lit = centre(offscreen.render(&camera).unwrap());
+        offscreen.renderer.display.shading = ShadingMode::Matcap;
+        let clay = centre(offscreen.render(&camera).unwrap());
+        assert_ne!(lit, clay);
+
+        // A plain matcap gives its color times the part color everywhere
+        let image = MatcapImage {
+            width: 2,
+            height: 2,
+            rgba: [255, 0, 0, 255].repeat(4),
+        };
+        offscreen
+            .renderer
+            .set_matcap(&offscreen.device, &offscreen.queue, &image);
+        let expected = (0.7f32.powf(1.0 / 2.4) * 1.055 - 0.055) * 255.0;
+        let red = centre(offscreen.render(&camera).unwrap());
+        assert!((red[0] as f32 - expected).abs() < 3.0);
+        assert_eq!(&red[1..], [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_double_sided() {
+        let mut offscreen = offscreen_renderer((64, 48));
+        let cube = box_solid(Point3::new(-2.0, -2.0, -2.0), Vector3::new(4.0, 4.0, 4.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly.add("cube", cube, Matrix4::identity()).unwrap();
+        offscreen.set_assembly(&assembly);
+        offscreen.renderer.display.show_grid = false;
+        offscreen.renderer.display.show_gizmo = false;
+
+        // From inside the cube only back faces are in view
+        let camera = OrbitCamera {
+            distance: 1.0,
+            ..Default::default()
+        };
+        let background = offscreen.render(&camera).unwrap();
+        assert!(background.chunks(4).all(|p| p == &background[..4]));
+        offscreen.renderer.display.double_sided = true;
+        let inside = offscreen.render(&camera).unwrap();
+        let centre = &inside[(24 * 64 + 32) * 4..][..4];
+        assert_ne!(centre, &background[..4]);
+        assert!(centre[0] > centre[1]);
+    }
+
+    #[test]
+    fn test_vertex_colors() {
+        let mut offscreen = offscreen_renderer((64, 48));
+        let cube = box_solid(Point3::new(-1.0, -1.0, -1.0), Vector3::new(2.0, 2.0, 2.0)).unwrap();
+        let mesh = GpuMesh::from_solid(&cube, 0.1);
+        let colors = vec![[0.0, 1.0, 0.0]; mesh.vertices.len()];
+        let mut scene = Scene::new();
+        scene.add("painted", mesh.with_colors(colors).unwrap());
+        offscreen.renderer.set_scene(&offscreen.device, scene);
+        offscreen.renderer.display.shading = ShadingMode::Matcap;
+        offscreen.renderer.set_matcap(
+            &offscreen.device,
+            &offscreen.queue,
+            &MatcapImage {
+                width: 1,
+                height: 1,
+                rgba: vec![255; 4],
+            },
+        );
+        let camera = OrbitCamera {
+            distance: 8.0,
+            ..Default::default()
+        };
+        let frame = offscreen.render(&camera).unwrap();
+        // The vertex color replaces the material color
+        assert_eq!(&frame[(24 * 64 + 32) * 4..][..4], [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_texture() {
+        let mut offscreen = offscreen_renderer((64, 48));
+        let cube = box_solid(Point3::new(-1.0, -1.0, -1.0), Vector3::new(2.0, 2.0, 2.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly.add("cube", cube, Matrix4::identity()).unwrap();
+        offscreen.set_assembly(&assembly);
+        offscreen.renderer.display.show_grid = false;
+        offscreen.renderer.display.show_gizmo = false;
+        let camera = OrbitCamera {
+            distance: 5.0,
+            azimuth_rad: 0.0,
+            elevation_rad: 0.0,
+            ..Default::default()
+        };
+        let shades = |frame: &[u8]| {
+            let mut reds: Vec<u8> = frame.chunks(4).map(|p| p[0]).collect();
+            reds.sort();
+            reds.dedup();
+            reds.len()
+        };
+        let plain = shades(&offscreen.render(&camera).unwrap());
+
+        // A fine checker puts dark and light squares on the flat faces
+        let cube = offscreen.renderer.scene().find("cube").unwrap();
+        let image = TextureImage::checker(8, 2, [0, 0, 0, 255], [255; 4]);
+        offscreen
+            .renderer
+            .set_texture(&offscreen.device, &offscreen.queue, cube, Some(&image));
+        offscreen
+            .renderer
+            .object_mut(cube)
+            .unwrap()
+            .material
+            .texture_scale = 2.0;
+        let textured = offscreen.render(&camera).unwrap();
+        assert!(textured.chunks(4).any(|p| p[0] < 20));
+        assert!(shades(&textured) > plain);
+
+        offscreen
+            .renderer
+            .set_texture(&offscreen.device, &offscreen.queue, cube, None);
+        assert_eq!(shades(&offscreen.render(&camera).unwrap()), plain);
+    }
+
+    #[test]
+    fn test_overlay_on_top() {
+        let mut offscreen = offscreen_renderer((64, 48));
+        let cube = box_solid(Point3::new(-1.0, -1.0, -1.0), Vector3::new(2.0, 2.0, 2.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly.add("cube", cube, Matrix4::identity()).unwrap();
+        offscreen.set_assembly(&assembly);
+        let camera = OrbitCamera {
+            distance: 8.0,
+            ..Default::default()
+        };
+        let plain = offscreen.render(&camera).unwrap();
+        // Inside the cube, yet drawn over it
+        offscreen
+            .renderer
+            .overlay
+            .marker(glam::Vec3::ZERO, [1.0, 0.0, 0.0]);
+        let marked = offscreen.render(&camera).unwrap();
+        let red = |frame: &[u8]| {
+            frame
+                .chunks(4)
+                .filter(|p| p[0] == 255 && p[1] == 0 && p[2] == 0)
+                .count()
+        };
+        assert_eq!(red(&plain), 0);
+        assert!(red(&marked) > 0);
+    }
+
+    #[test]
+    fn test_custom_pass() {
+        use crate::renderer::pass::{Frame, RenderPass};
+
+        /// Paints the whole frame red
+        struct Flood;
+
+        impl RenderPass for Flood {
+            fn name(&self) -> &'static str {
+                "flood"
+            }
+
+            fn record(&self, frame: &Frame<'_>, encoder: &mut wgpu::CommandEncoder) {
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Flood"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: frame.target,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::RED),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+            }
+        }
+
+        let mut offscreen = offscreen_renderer((64, 48));
+        let renderer = &mut offscreen.renderer;
+        assert_eq!(
+            renderer.pass_names(),
+            ["shadow", "opaque", "outline", "transparent", "overlay"]
+        );
+        renderer.insert_pass("overlay", Box::new(Flood));
+        assert_eq!(renderer.pass_names()[4..], ["flood", "overlay"]);
+        assert!(renderer.remove_pass("outline").is_some());
+        assert!(renderer.remove_pass("outline").is_none());
+
+        // Everything but the annotations drawn after it is red
+        offscreen
+            .renderer
+            .overlay
+            .marker(glam::Vec3::ZERO, [0.0, 1.0, 0.0]);
+        let frame = offscreen.render(&OrbitCamera::default()).unwrap();
+        let red = frame.chunks(4).filter(|p| p == &[255, 0, 0, 255]).count();
+        assert!(red > 64 * 48 * 9 / 10);
+        assert!(red < 64 * 48);
+    }
+
+    #[test]
+    fn test_background_styles() {
+        let mut offscreen = offscreen_renderer((64, 48));
+        offscreen.renderer.display.show_grid = false;
+        offscreen.renderer.display.show_gizmo = false;
+        offscreen.renderer.display.background = [0.0, 0.0, 0.0];
+        offscreen.renderer.display.background_top = [0.0, 0.0, 1.0];
+        let camera = OrbitCamera {
+            elevation_rad: 0.0,
+            ..Default::default()
+        };
+        let blue = |frame: &[u8], row: usize| frame[(row * 64 + 32) * 4 + 2];
+
+        let solid = offscreen.render(&camera).unwrap();
+        assert_eq!((blue(&solid, 0), blue(&solid, 47)), (0, 0));
+
+        // Blue at the top fading to black at the bottom
+        offscreen.renderer.display.background_style = BackgroundStyle::Gradient;
+        let gradient = offscreen.render(&camera).unwrap();
+        assert!(blue(&gradient, 0) > 240 && blue(&gradient, 47) < 40);
+        assert!(blue(&gradient, 12) > blue(&gradient, 36));
+
+        // Level with the horizon, the sky is black in the middle and bluer
+        // above it; looking up, all of it is
+        offscreen.renderer.display.background_style = BackgroundStyle::Sky;
+        let level = offscreen.render(&camera).unwrap();
+        assert!(blue(&level, 0) > blue(&level, 24) && blue(&level, 24) < 40);
+        let up = OrbitCamera {
+            elevation_rad: -1.2,
+            ..camera
+        };
+        assert!(blue(&offscreen.render(&up).unwrap(), 24) > 200);
+    }
+
+    #[test]
+    fn test_turntable_frames() {
+        let bar = box_solid(Point3::new(-2.0, -0.5, -0.5), Vector3::new(4.0, 1.0, 1.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly.add("bar", bar, Matrix4::identity()).unwrap();
+        let dir = std::env::temp_dir().join("truck_playground_turntable_test");
+        let camera = OrbitCamera {
+            distance: 10.0,
+            ..Default::default()
+        };
+        let paths =
+            render_turntable(&assembly, &camera, &Turntable::default(), 4, (32, 24), &dir).unwrap();
+        assert_eq!(paths.len(), 4);
+        assert_eq!(paths[3], dir.join("frame_0003.png"));
+        let first = std::fs::read(&paths[0]).unwrap();
+        assert_ne!(first, std::fs::read(&paths[1]).unwrap());
+    }
+}
@@ -0,0 +1,57 @@
+struct Outline {
+    // Line color, crease threshold in w
+    color: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> outline: Outline;
+
+// Depth read as plain floats, which GL can load from; replaced by
+// texture_multisampled_2d when MSAA is on
+@group(0) @binding(1)
+var scene_depth: texture_2d<f32>;
+
+// One triangle covering the screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn depth_at(p: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(scene_depth));
+    // Mip level, or sample index when multisampled
+    return textureLoad(scene_depth, clamp(p, vec2<i32>(0), size - 1), 0).x;
+}
+
+// Whether the depth profile bends at `centre` along one axis: NDC depth is
+// affine in screen space across a plane, so both slopes agree unless a
+// crease or a silhouette lies here
+fn bends(before: f32, centre: f32, after: f32) -> bool {
+    let left = centre - before;
+    let right = after - centre;
+    return abs(right - left) > outline.color.w * (abs(left) + abs(right)) + 1e-6;
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let p = vec2<i32>(position.xy);
+    let d = depth_at(p);
+    let l = depth_at(p - vec2<i32>(1, 0));
+    let r = depth_at(p + vec2<i32>(1, 0));
+    let u = depth_at(p - vec2<i32>(0, 1));
+    let b = depth_at(p + vec2<i32>(0, 1));
+
+    // Empty sky all around; depth is reversed, so cleared to 0
+    let background = d <= 0.0;
+    if (background && max(max(l, r), max(u, b)) <= 0.0) {
+        discard;
+    }
+    // Silhouettes against the background, then creases and overlaps
+    let edge = background || min(min(l, r), min(u, b)) <= 0.0
+        || bends(l, d, r) || bends(u, d, b);
+    if (!edge) {
+        discard;
+    }
+    return vec4<f32>(outline.color.rgb, 1.0);
+}
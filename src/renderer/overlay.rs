@@ -0,0 +1,190 @@
+use crate::renderer::camera::OrbitCamera;
+use crate::renderer::grid::LineVertex;
+use crate::units::Units;
+use glam::{Mat3, Vec2, Vec3};
+
+/// Half the width of point markers in pixels
+const MARKER_PIXELS: f32 = 5.0;
+
+/// Half the length of the ticks at the ends of dimension lines in pixels
+const TICK_PIXELS: f32 = 6.0;
+
+/// Line vertices uploaded per frame; annotations past this are dropped
+pub const MAX_OVERLAY_VERTICES: usize = 4096;
+
+/// Text anchored at a point in the world
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub position: Vec3,
+    pub text: String,
+    pub color: [f32; 3],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Annotation {
+    Line {
+        from: Vec3,
+        to: Vec3,
+    },
+    Marker {
+        position: Vec3,
+    },
+    /// Line with ticks across its ends
+    Dimension {
+        from: Vec3,
+        to: Vec3,
+    },
+}
+
+/// Measurement and debug annotations drawn over the scene without depth
+/// testing, so parts never hide them. Markers and ticks keep their size in
+/// pixels at any distance.
+///
+/// Lines are drawn by the renderer; labels only come out as screen
+/// positions from [`Overlay::screen_labels`], for the UI to paint text at.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overlay {
+    annotations: Vec<(Annotation, [f32; 3])>,
+    labels: Vec<Label>,
+    /// Unit dimension labels are written in
+    pub units: Units,
+}
+
+impl Overlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.annotations.clear();
+        self.labels.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty() && self.labels.is_empty()
+    }
+
+    pub fn line(&mut self, from: Vec3, to: Vec3, color: [f32; 3]) {
+        self.annotations
+            .push((Annotation::Line { from, to }, color));
+    }
+
+    /// Cross facing the camera at `position`
+    pub fn marker(&mut self, position: Vec3, color: [f32; 3]) {
+        self.annotations
+            .push((Annotation::Marker { position }, color));
+    }
+
+    pub fn label(&mut self, position: Vec3, text: impl Into<String>, color: [f32; 3]) {
+        self.labels.push(Label {
+            position,
+            text: text.into(),
+            color,
+        });
+    }
+
+    /// Dimension line between two points, labelled with their distance at
+    /// the middle
+    pub fn dimension(&mut self, from: Vec3, to: Vec3, color: [f32; 3]) {
+        self.annotations
+            .push((Annotation::Dimension { from, to }, color));
+        let length = from.distance(to);
+        let text = self.units.format(length as f64);
+        self.label((from + to) * 0.5, text, color);
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    /// Line list of everything but the labels, as seen from `camera` in a
+    /// viewport `viewport_height` pixels high
+    pub fn lines(&self, camera: &OrbitCamera, viewport_height: f32) -> Vec<LineVertex> {
+        // Camera axes in world space, to draw markers and ticks facing it
+        let rotation = Mat3::from_mat4(camera.view_matrix()).transpose();
+        let (right, up) = (rotation.x_axis, rotation.y_axis);
+        let world = |pixels: f32, at: Vec3| pixels / camera.pixels_per_unit(at, viewport_height);
+        let mut lines = Vec::new();
+        let mut segment = |a: Vec3, b: Vec3, color: [f32; 3]| {
+            lines.push(LineVertex {
+                position: a.to_array(),
+                color,
+            });
+            lines.push(LineVertex {
+                position: b.to_array(),
+                color,
+            });
+        };
+        for &(annotation, color) in &self.annotations {
+            match annotation {
+                Annotation::Line { from, to } => segment(from, to, color),
+                Annotation::Marker { position } => {
+                    let size = world(MARKER_PIXELS, position);
+                    for offset in [(right + up) * size, (right - up) * size] {
+                        segment(position - offset, position + offset, color);
+                    }
+                }
+                Annotation::Dimension { from, to } => {
+                    segment(from, to, color);
+                    // Ticks across the line in the screen plane
+                    let along = to - from;
+                    let facing = (camera.eye_position() - from).normalize_or_zero();
+                    let across = along.cross(facing).try_normalize().unwrap_or(up);
+                    for end in [from, to] {
+                        let tick = across * world(TICK_PIXELS, end);
+                        segment(end - tick, end + tick, color);
+                    }
+                }
+            }
+        }
+        lines.truncate(MAX_OVERLAY_VERTICES);
+        lines
+    }
+
+    /// Labels in front of the camera with their pixel positions in a
+    /// `viewport`-sized view
+    pub fn screen_labels(&self, camera: &OrbitCamera, viewport: Vec2) -> Vec<(Vec2, &Label)> {
+        self.labels
+            .iter()
+            .filter_map(|label| Some((camera.world_to_screen(label.position, viewport)?, label)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_scales_with_distance() {
+        let mut overlay = Overlay::new();
+        overlay.marker(Vec3::ZERO, [1.0; 3]);
+        overlay.dimension(Vec3::ZERO, Vec3::new(3.0, 4.0, 0.0), [1.0; 3]);
+        assert_eq!(overlay.labels()[0].text, "5.000 mm");
+
+        // Markers stay the same size on screen, so grow in the world
+        let near = OrbitCamera {
+            distance: 10.0,
+            ..Default::default()
+        };
+        let far = OrbitCamera {
+            distance: 40.0,
+            ..near
+        };
+        let width = |camera: &OrbitCamera| {
+            let lines = overlay.lines(camera, 600.0);
+            Vec3::from(lines[0].position).distance(Vec3::from(lines[1].position))
+        };
+        assert!((width(&far) / width(&near) - 4.0).abs() < 0.1);
+        // Two marker strokes, the dimension line and two ticks
+        assert_eq!(overlay.lines(&near, 600.0).len(), 10);
+
+        let viewport = Vec2::new(800.0, 600.0);
+        let labels = overlay.screen_labels(&near, viewport);
+        assert_eq!(labels.len(), 1);
+        let expected = near.world_to_screen(Vec3::new(1.5, 2.0, 0.0), viewport);
+        assert_eq!(Some(labels[0].0), expected);
+        overlay.clear();
+        assert!(overlay.is_empty());
+    }
+}
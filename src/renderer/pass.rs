@@ -0,0 +1,198 @@
+use crate::renderer::background::{BackgroundStyle, BackgroundUniforms};
+use crate::renderer::scene::ObjectId;
+use crate::renderer::{Renderer, ScenePipelines};
+use eframe::wgpu;
+
+/// What the passes of one frame share: the renderer with its scene and GPU
+/// resources, and where the frame goes
+pub struct Frame<'f> {
+    pub renderer: &'f Renderer,
+    pub queue: &'f wgpu::Queue,
+    /// Output the scene is drawn or resolved into
+    pub target: &'f wgpu::TextureView,
+    pub size: (u32, u32),
+    /// Whether the shadow map is in use this frame
+    pub shadowed: bool,
+}
+
+impl Frame<'_> {
+    pub fn aspect(&self) -> f32 {
+        self.size.0 as f32 / self.size.1.max(1) as f32
+    }
+
+    /// Pass drawing into the scene color and depth targets, clearing them
+    /// first or adding to what earlier passes drew
+    pub fn begin_scene_pass<'e>(
+        &self,
+        encoder: &'e mut wgpu::CommandEncoder,
+        clear: bool,
+    ) -> wgpu::RenderPass<'e> {
+        self.renderer.begin_main_pass(encoder, self.target, clear)
+    }
+
+    /// Shaded pipelines for the current cull setting
+    fn pipelines(&self) -> &ScenePipelines {
+        if self.renderer.display.double_sided {
+            &self.renderer.double_sided_pipelines
+        } else {
+            &self.renderer.culled_pipelines
+        }
+    }
+}
+
+/// One stage of [`Renderer::render`]; the renderer runs its passes in order,
+/// see [`Renderer::insert_pass`]
+pub trait RenderPass {
+    fn name(&self) -> &'static str;
+
+    /// Upload what the pass needs and record its GPU work; buffer writes go
+    /// through `frame.queue` and land before the encoder runs
+    fn record(&self, frame: &Frame<'_>, encoder: &mut wgpu::CommandEncoder);
+}
+
+/// Passes of a new renderer, in drawing order
+pub fn default_passes() -> Vec<Box<dyn RenderPass>> {
+    vec![
+        Box::new(ShadowPass),
+        Box::new(OpaquePass),
+        Box::new(OutlinePass),
+        Box::new(TransparentPass),
+        Box::new(OverlayPass),
+    ]
+}
+
+/// Depth from the key light, sampled by the shaded passes; see-through
+/// objects cast no shadow
+pub struct ShadowPass;
+
+impl RenderPass for ShadowPass {
+    fn name(&self) -> &'static str {
+        "shadow"
+    }
+
+    fn record(&self, frame: &Frame<'_>, encoder: &mut wgpu::CommandEncoder) {
+        let renderer = frame.renderer;
+        if frame.shadowed {
+            let mut shadow_pass = renderer.shadow.begin_pass(encoder);
+            renderer.draw_objects(&mut shadow_pass, renderer.opaque_objects(), false);
+        }
+    }
+}
+
+/// Clears the targets, then draws the background, the opaque objects and
+/// the grid behind them
+pub struct OpaquePass;
+
+impl RenderPass for OpaquePass {
+    fn name(&self) -> &'static str {
+        "opaque"
+    }
+
+    fn record(&self, frame: &Frame<'_>, encoder: &mut wgpu::CommandEncoder) {
+        let renderer = frame.renderer;
+        let display = &renderer.display;
+        let background = BackgroundUniforms::new(
+            display.background_style,
+            display.background,
+            display.background_top,
+            &renderer.camera,
+            frame.aspect(),
+        );
+        renderer.background.prepare(frame.queue, &background);
+
+        let mut render_pass = frame.begin_scene_pass(encoder, true);
+        if display.background_style != BackgroundStyle::Solid {
+            renderer.background.draw(&mut render_pass);
+        }
+        if !renderer.scene.is_empty() {
+            let opaque: Vec<ObjectId> = renderer.opaque_objects().collect();
+            renderer.draw_shaded(&mut render_pass, frame.pipelines(), &opaque, false);
+        }
+        if display.show_grid {
+            renderer
+                .grid
+                .draw_grid(&mut render_pass, renderer.shadow.bind_group());
+        }
+    }
+}
+
+/// Silhouettes and creases from the depth of the opaque parts, so it runs
+/// before anything drawn without depth writes
+pub struct OutlinePass;
+
+impl RenderPass for OutlinePass {
+    fn name(&self) -> &'static str {
+        "outline"
+    }
+
+    fn record(&self, frame: &Frame<'_>, encoder: &mut wgpu::CommandEncoder) {
+        let renderer = frame.renderer;
+        if renderer.display.show_outline && !renderer.scene.is_empty() {
+            renderer.outline.draw(
+                encoder,
+                frame.queue,
+                renderer.color_target(frame.target),
+                renderer.display.outline_color,
+            );
+        }
+    }
+}
+
+/// Blended objects, farthest first
+pub struct TransparentPass;
+
+impl RenderPass for TransparentPass {
+    fn name(&self) -> &'static str {
+        "transparent"
+    }
+
+    fn record(&self, frame: &Frame<'_>, encoder: &mut wgpu::CommandEncoder) {
+        let renderer = frame.renderer;
+        let transparent = renderer.transparent_objects();
+        if !transparent.is_empty() {
+            let mut render_pass = frame.begin_scene_pass(encoder, false);
+            renderer.draw_shaded(&mut render_pass, frame.pipelines(), &transparent, true);
+        }
+    }
+}
+
+/// Transform handles, annotations and the orientation gizmo over everything
+pub struct OverlayPass;
+
+impl RenderPass for OverlayPass {
+    fn name(&self) -> &'static str {
+        "overlay"
+    }
+
+    fn record(&self, frame: &Frame<'_>, encoder: &mut wgpu::CommandEncoder) {
+        let renderer = frame.renderer;
+        let (queue, camera) = (frame.queue, &renderer.camera);
+        let handles = renderer.gizmo.map_or(0, |gizmo| {
+            renderer.grid.prepare_handles(queue, &gizmo.lines(camera))
+        });
+        let annotations = renderer
+            .grid
+            .prepare_overlay(queue, &renderer.overlay.lines(camera, frame.size.1 as f32));
+        if handles == 0 && annotations == 0 && !renderer.display.show_gizmo {
+            return;
+        }
+
+        let shadow = renderer.shadow.bind_group();
+        let mut render_pass = frame.begin_scene_pass(encoder, false);
+        if handles > 0 {
+            renderer
+                .grid
+                .draw_handles(&mut render_pass, shadow, handles);
+        }
+        if annotations > 0 {
+            renderer
+                .grid
+                .draw_overlay(&mut render_pass, shadow, annotations);
+        }
+        if renderer.display.show_gizmo {
+            renderer
+                .grid
+                .draw_gizmo(&mut render_pass, shadow, frame.size);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::renderer::mesh::GpuMesh;
+use crate::renderer::scene::{ObjectId, Scene};
+use glam::{Mat4, Vec3};
+
+/// Most triangles kept in one BVH leaf
+const LEAF_SIZE: usize = 4;
+
+/// Half-line from `origin` along `direction`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// The same ray in the coordinates `m` maps to; ray parameters carry
+    /// over unchanged
+    pub fn transformed(&self, m: Mat4) -> Self {
+        Self {
+            origin: m.transform_point3(self.origin),
+            direction: m.transform_vector3(self.direction),
+        }
+    }
+}
+
+/// Nearest object under the cursor
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickResult {
+    pub object: ObjectId,
+    /// Index of the hit triangle in the object's mesh
+    pub triangle: usize,
+    /// B-rep face the triangle was cut from, see [`GpuMesh::face_ids`]
+    pub face: Option<usize>,
+    /// Hit point in world coordinates
+    pub point: Vec3,
+    /// Distance along the pick ray
+    pub distance: f32,
+}
+
+#[derive(Clone, Debug)]
+enum NodeKind {
+    Leaf { first: usize, count: usize },
+    Branch { left: usize, right: usize },
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    min: Vec3,
+    max: Vec3,
+    kind: NodeKind,
+}
+
+/// Bounding volume hierarchy over the triangles of one mesh
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    /// Triangle indices, grouped so every leaf owns a contiguous run
+    triangles: Vec<usize>,
+}
+
+impl Bvh {
+    /// Split the triangles of `mesh` at the centroid median of the longest
+    /// axis until leaves are small
+    pub fn new(mesh: &GpuMesh) -> Self {
+        let corners = |t: usize| triangle(mesh, t);
+        let count = mesh.indices.len() / 3;
+        let centroids: Vec<Vec3> = (0..count)
+            .map(|t| corners(t).into_iter().sum::<Vec3>() / 3.0)
+            .collect();
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            triangles: (0..count).collect(),
+        };
+        if count > 0 {
+            bvh.build(mesh, &centroids, 0, count);
+        }
+        bvh
+    }
+
+    /// Add the node for `triangles[first..first + count]`, returning its index
+    fn build(&mut self, mesh: &GpuMesh, centroids: &[Vec3], first: usize, count: usize) -> usize {
+        let run = &mut self.triangles[first..first + count];
+        let (mut min, mut max) = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+        for p in run.iter().flat_map(|&t| triangle(mesh, t)) {
+            min = min.min(p);
+            max = max.max(p);
+        }
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            min,
+            max,
+            kind: NodeKind::Leaf { first, count },
+        });
+        if count <= LEAF_SIZE {
+            return index;
+        }
+
+        let (mut low, mut high) = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+        for &t in run.iter() {
+            low = low.min(centroids[t]);
+            high = high.max(centroids[t]);
+        }
+        let axis = (high - low).max_position();
+        let half = count / 2;
+        run.select_nth_unstable_by(half, |&a, &b| {
+            centroids[a][axis].total_cmp(&centroids[b][axis])
+        });
+        let left = self.build(mesh, centroids, first, half);
+        let right = self.build(mesh, centroids, first + half, count - half);
+        self.nodes[index].kind = NodeKind::Branch { left, right };
+        index
+    }
+
+    /// Nearest triangle of `mesh` the ray crosses from either side, with the
+    /// ray parameter of the hit
+    pub fn intersect(&self, mesh: &GpuMesh, ray: &Ray) -> Option<(usize, f32)> {
+        let inverse = ray.direction.recip();
+        let mut best: Option<(usize, f32)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let limit = best.map_or(f32::INFINITY, |(_, t)| t);
+            if !slab(node, ray.origin, inverse, limit) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf { first, count } => {
+                    for &t in &self.triangles[first..first + count] {
+                        if let Some(hit) = ray_triangle(ray, triangle(mesh, t)) {
+                            if hit < best.map_or(f32::INFINITY, |(_, t)| t) {
+                                best = Some((t, hit));
+                            }
+                        }
+                    }
+                }
+                NodeKind::Branch { left, right } => stack.extend([left, right]),
+            }
+        }
+        best
+    }
+}
+
+/// Nearest pickable object of `scene` hit by a world-space `ray`; `bvhs`
+/// holds one hierarchy per scene mesh
+pub fn pick_scene(scene: &Scene, bvhs: &[Bvh], ray: &Ray) -> Option<PickResult> {
+    let mut best: Option<PickResult> = None;
+    for (id, object) in scene.objects().filter(|(_, object)| object.is_pickable()) {
+        let Some(bvh) = bvhs.get(object.mesh.index()) else {
+            continue;
+        };
+        let local = ray.transformed(object.transform.inverse());
+        let Some((triangle, distance)) = bvh.intersect(scene.mesh(object.mesh), &local) else {
+            continue;
+        };
+        if best.is_none_or(|b| distance < b.distance) {
+            best = Some(PickResult {
+                object: id,
+                triangle,
+                face: scene.mesh(object.mesh).triangle_face(triangle),
+                point: ray.at(distance),
+                distance,
+            });
+        }
+    }
+    best
+}
+
+fn triangle(mesh: &GpuMesh, t: usize) -> [Vec3; 3] {
+    let corner = |k: usize| Vec3::from(mesh.vertices[mesh.indices[3 * t + k] as usize].position);
+    [corner(0), corner(1), corner(2)]
+}
+
+/// Whether the ray meets the node box before parameter `limit`
+fn slab(node: &Node, origin: Vec3, inverse: Vec3, limit: f32) -> bool {
+    let a = (node.min - origin) * inverse;
+    let b = (node.max - origin) * inverse;
+    // NaN from 0 * inf means the ray runs inside a slab; min/max skip it
+    let near = a.min(b).max_element().max(0.0);
+    let far = a.max(b).min_element().min(limit);
+    near <= far
+}
+
+/// Möller–Trumbore intersection, accepting both windings
+fn ray_triangle(ray: &Ray, [p0, p1, p2]: [Vec3; 3]) -> Option<f32> {
+    let (e1, e2) = (p1 - p0, p2 - p0);
+    let h = ray.direction.cross(e2);
+    let det = e1.dot(h);
+    if det.abs() < f32::EPSILON * e1.length() * e2.length() * ray.direction.length() {
+        return None;
+    }
+    let s = ray.origin - p0;
+    let u = s.dot(h) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let v = ray.direction.dot(q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = e2.dot(q) / det;
+    (t >= 0.0).then_some(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{box_solid, Assembly};
+    use crate::renderer::camera::OrbitCamera;
+    use glam::Vec2;
+    use truck_geometry::prelude::*;
+
+    #[test]
+    fn test_pick_nearest_object() {
+        let cube = box_solid(Point3::new(-1.0, -1.0, -1.0), Vector3::new(2.0, 2.0, 2.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly
+            .add("near", cube.clone(), Matrix4::identity())
+            .unwrap();
+        let behind = Matrix4::from_translation(Vector3::new(0.0, 0.0, -10.0));
+        assembly.add("far", cube, behind).unwrap();
+        let mut scene = crate::renderer::scene::Scene::from_assembly(&assembly, 0.01);
+        let bvhs: Vec<Bvh> = scene.meshes().iter().map(Bvh::new).collect();
+
+        // Looking down -Z through both cubes
+        let camera = OrbitCamera {
+            distance: 20.0,
+            azimuth_rad: 0.0,
+            elevation_rad: 0.0,
+            ..Default::default()
+        };
+        let viewport = Vec2::new(200.0, 100.0);
+        let ray = camera.screen_ray(viewport * 0.5, viewport);
+        let hit = pick_scene(&scene, &bvhs, &ray).unwrap();
+        let near = scene.find("near").unwrap();
+        assert_eq!(hit.object, near);
+        assert!((hit.point - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-4);
+        // Rays start on the near plane
+        assert!((hit.distance - (19.0 - camera.near)).abs() < 1e-4);
+
+        // Hidden objects let the ray through
+        scene.object_mut(near).unwrap().visible = false;
+        let hit = pick_scene(&scene, &bvhs, &ray).unwrap();
+        assert_eq!(hit.object, scene.find("far").unwrap());
+        assert!((hit.point.z + 9.0).abs() < 1e-4);
+
+        // and so do ghosts
+        let near = scene.object_mut(near).unwrap();
+        near.visible = true;
+        near.display = crate::renderer::scene::DisplayMode::Ghost;
+        let hit = pick_scene(&scene, &bvhs, &ray).unwrap();
+        assert_eq!(hit.object, scene.find("far").unwrap());
+
+        assert!(pick_scene(&scene, &bvhs, &camera.screen_ray(Vec2::ZERO, viewport)).is_none());
+    }
+}
@@ -0,0 +1,352 @@
+use crate::model::Assembly;
+use crate::renderer::mesh::{GpuMesh, MeshQuality};
+use crate::renderer::mesh_cache::{geometry_hash, MeshCache};
+use glam::Mat4;
+use rayon::prelude::*;
+use truck_geometry::prelude::Matrix4;
+
+/// Handle to a mesh stored in a [`Scene`]; several objects can share one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MeshHandle(usize);
+
+impl MeshHandle {
+    /// Index of the mesh in the scene
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Handle to an object in a [`Scene`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(usize);
+
+impl ObjectId {
+    /// Index of the object in the scene
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Screen radii in pixels below which an object switches to its next
+/// coarser mesh
+pub const LOD_PIXELS: [f32; 2] = [120.0, 30.0];
+
+/// How much looser each level of detail is tessellated than the one before
+const LOD_COARSENING: f64 = 4.0;
+
+/// Opacity of objects in [`DisplayMode::Ghost`]
+pub const GHOST_OPACITY: f32 = 0.25;
+
+/// Surface appearance of an object
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Material {
+    /// Diffuse color (linear RGB)
+    pub base_color: [f32; 3],
+    /// 1 is opaque; anything less is alpha-blended after the opaque objects
+    pub opacity: f32,
+    /// Texture repeats per unit of surface parameter, when the renderer has
+    /// a texture for the object
+    pub texture_scale: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            base_color: [0.7, 0.7, 0.7],
+            opacity: 1.0,
+            texture_scale: 1.0,
+        }
+    }
+}
+
+/// How an object is drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DisplayMode {
+    #[default]
+    Shaded,
+    /// See-through, casting no shadow and ignored by picking, for reference
+    /// bodies and boolean tools next to the part being edited
+    Ghost,
+}
+
+/// One drawn instance of a mesh
+#[derive(Clone, Debug)]
+pub struct RenderObject {
+    pub name: String,
+    pub mesh: MeshHandle,
+    /// Coarser stand-ins for `mesh`, finest first, drawn while the object
+    /// is small on screen
+    pub lods: Vec<MeshHandle>,
+    /// Model matrix from mesh to world coordinates
+    pub transform: Mat4,
+    pub material: Material,
+    pub display: DisplayMode,
+    pub visible: bool,
+}
+
+impl RenderObject {
+    /// Visible, untransformed and with the default material
+    pub fn new(name: impl Into<String>, mesh: MeshHandle) -> Self {
+        Self {
+            name: name.into(),
+            mesh,
+            lods: Vec::new(),
+            transform: Mat4::IDENTITY,
+            material: Material::default(),
+            display: DisplayMode::Shaded,
+            visible: true,
+        }
+    }
+
+    /// Opacity drawn with, after the display mode
+    pub fn opacity(&self) -> f32 {
+        match self.display {
+            DisplayMode::Shaded => self.material.opacity,
+            DisplayMode::Ghost => self.material.opacity.min(GHOST_OPACITY),
+        }
+    }
+
+    /// Drawn blended, after every opaque object
+    pub fn is_transparent(&self) -> bool {
+        self.opacity() < 1.0
+    }
+
+    /// Mesh to draw while the object's bounding sphere covers
+    /// `screen_radius` pixels, see [`LOD_PIXELS`]
+    pub fn mesh_for_size(&self, screen_radius: f32) -> MeshHandle {
+        let level = LOD_PIXELS
+            .iter()
+            .take_while(|&&pixels| screen_radius < pixels)
+            .count();
+        match level.checked_sub(1) {
+            Some(lod) => self
+                .lods
+                .get(lod)
+                .or(self.lods.last())
+                .copied()
+                .unwrap_or(self.mesh),
+            None => self.mesh,
+        }
+    }
+
+    /// Whether clicks can select the object
+    pub fn is_pickable(&self) -> bool {
+        self.visible && self.display != DisplayMode::Ghost
+    }
+}
+
+/// Meshes and the objects placing them, as handed to the renderer
+#[derive(Clone, Debug, Default)]
+pub struct Scene {
+    meshes: Vec<GpuMesh>,
+    objects: Vec<RenderObject>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// One mesh per part, tessellated in part coordinates, in parallel, and
+    /// placed by the part transform
+    pub fn from_assembly(assembly: &Assembly, quality: impl Into<MeshQuality>) -> Self {
+        let quality = quality.into();
+        let meshes: Vec<GpuMesh> = assembly
+            .parts()
+            .par_iter()
+            .map(|part| GpuMesh::from_solid(&part.solid, quality))
+            .collect();
+        let mut scene = Self::new();
+        for (part, mesh) in assembly.parts().iter().zip(meshes) {
+            let mesh = scene.add_mesh(mesh);
+            let mut object = RenderObject::new(part.name().to_string(), mesh);
+            object.transform = to_mat4(part.transform);
+            object.visible = part.visible;
+            scene.add_object(object);
+        }
+        scene
+    }
+
+    /// Like [`Scene::from_assembly`], with [`LOD_PIXELS`]`.len()` coarser
+    /// meshes per part, so big assemblies stay interactive from afar
+    pub fn from_assembly_lod(assembly: &Assembly, quality: impl Into<MeshQuality>) -> Self {
+        Self::from_assembly_cached(assembly, quality, &mut MeshCache::default())
+    }
+
+    /// Like [`Scene::from_assembly_lod`], taking meshes from `cache` for
+    /// solids meshed before, as when a rebuild leaves most parts alone
+    pub fn from_assembly_cached(
+        assembly: &Assembly,
+        quality: impl Into<MeshQuality>,
+        cache: &mut MeshCache,
+    ) -> Self {
+        let quality = quality.into();
+        let mut levels = vec![quality];
+        let mut factor = 1.0;
+        for _ in LOD_PIXELS {
+            factor *= LOD_COARSENING;
+            levels.push(quality.coarser(factor));
+        }
+        let parts = assembly.parts();
+        let hashes: Vec<u64> = parts
+            .par_iter()
+            .map(|part| geometry_hash(&part.solid))
+            .collect();
+        let requests: Vec<_> = parts
+            .iter()
+            .zip(&hashes)
+            .flat_map(|(part, &hash)| levels.iter().map(move |&level| (&part.solid, hash, level)))
+            .collect();
+        let mut meshes = cache.meshes(&requests).into_iter();
+        let mut scene = Self::new();
+        for part in parts {
+            let mut handles = meshes
+                .by_ref()
+                .take(levels.len())
+                .map(|mesh| scene.add_mesh(mesh));
+            let mut object = RenderObject::new(
+                part.name().to_string(),
+                handles.next().expect("one mesh per level"),
+            );
+            object.lods.extend(handles);
+            object.transform = to_mat4(part.transform);
+            object.visible = part.visible;
+            scene.add_object(object);
+        }
+        scene
+    }
+
+    pub fn add_mesh(&mut self, mesh: GpuMesh) -> MeshHandle {
+        self.meshes.push(mesh);
+        MeshHandle(self.meshes.len() - 1)
+    }
+
+    /// Add an object; its mesh must belong to this scene
+    pub fn add_object(&mut self, object: RenderObject) -> ObjectId {
+        assert!(
+            std::iter::once(&object.mesh)
+                .chain(&object.lods)
+                .all(|mesh| mesh.0 < self.meshes.len()),
+            "mesh handle from another scene"
+        );
+        self.objects.push(object);
+        ObjectId(self.objects.len() - 1)
+    }
+
+    /// Add `mesh` as a new object of its own
+    pub fn add(&mut self, name: impl Into<String>, mesh: GpuMesh) -> ObjectId {
+        let mesh = self.add_mesh(mesh);
+        self.add_object(RenderObject::new(name, mesh))
+    }
+
+    pub fn mesh(&self, handle: MeshHandle) -> &GpuMesh {
+        &self.meshes[handle.0]
+    }
+
+    pub fn meshes(&self) -> &[GpuMesh] {
+        &self.meshes
+    }
+
+    pub fn object(&self, id: ObjectId) -> Option<&RenderObject> {
+        self.objects.get(id.0)
+    }
+
+    pub fn object_mut(&mut self, id: ObjectId) -> Option<&mut RenderObject> {
+        self.objects.get_mut(id.0)
+    }
+
+    pub fn objects(&self) -> impl Iterator<Item = (ObjectId, &RenderObject)> {
+        self.objects
+            .iter()
+            .enumerate()
+            .map(|(i, object)| (ObjectId(i), object))
+    }
+
+    /// Object with the given name, e.g. the part it was made from
+    pub fn find(&self, name: &str) -> Option<ObjectId> {
+        self.objects
+            .iter()
+            .position(|object| object.name == name)
+            .map(ObjectId)
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+/// Single-precision copy of a modelling transform
+pub fn to_mat4(m: Matrix4) -> Mat4 {
+    let columns: [[f64; 4]; 4] = m.into();
+    Mat4::from_cols_array_2d(&columns.map(|column| column.map(|x| x as f32)))
+}
+
+/// Double-precision copy of a display transform, for the modelling side
+pub fn to_matrix4(m: Mat4) -> Matrix4 {
+    Matrix4::from(m.to_cols_array_2d().map(|column| column.map(f64::from)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+    use glam::Vec3;
+    use truck_geometry::prelude::*;
+
+    #[test]
+    fn test_scene_from_assembly() {
+        let cube = box_solid(Point3::origin(), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let mut assembly = Assembly::new();
+        assembly
+            .add("a", cube.clone(), Matrix4::identity())
+            .unwrap();
+        let shift = Matrix4::from_translation(Vector3::new(5.0, 0.0, 0.0));
+        assembly.add("b", cube, shift).unwrap();
+
+        let mut scene = Scene::from_assembly(&assembly, 0.01);
+        assert_eq!(scene.len(), 2);
+        let b = scene.find("b").unwrap();
+        let object = scene.object(b).unwrap();
+        // Meshes stay in part coordinates
+        assert!((scene.mesh(object.mesh).volume() - 1.0).abs() < 1e-6);
+        let moved = object.transform.transform_point3(Vec3::ZERO);
+        assert_eq!(moved, Vec3::new(5.0, 0.0, 0.0));
+        assert_eq!(to_matrix4(object.transform), shift);
+
+        // Objects can share a mesh
+        let mesh = scene.object(b).unwrap().mesh;
+        let copy = scene.add_object(RenderObject::new("copy", mesh));
+        scene.object_mut(copy).unwrap().visible = false;
+        assert_eq!(scene.meshes().len(), 2);
+        assert_eq!(scene.objects().filter(|(_, o)| o.visible).count(), 2);
+    }
+
+    #[test]
+    fn test_levels_of_detail() {
+        let rod = crate::model::cylinder(Point3::origin(), Vector3::unit_z() * 4.0, 1.0).unwrap();
+        let mut assembly = Assembly::new();
+        assembly.add("rod", rod, Matrix4::identity()).unwrap();
+        let scene = Scene::from_assembly_lod(&assembly, 0.001);
+        let object = scene.object(scene.find("rod").unwrap()).unwrap();
+        assert_eq!(object.lods.len(), LOD_PIXELS.len());
+        let triangles: Vec<usize> = std::iter::once(object.mesh)
+            .chain(object.lods.iter().copied())
+            .map(|handle| scene.mesh(handle).indices.len() / 3)
+            .collect();
+        assert!(
+            triangles.windows(2).all(|pair| pair[0] > pair[1]),
+            "{:?}",
+            triangles
+        );
+
+        assert_eq!(object.mesh_for_size(500.0), object.mesh);
+        assert_eq!(object.mesh_for_size(50.0), object.lods[0]);
+        assert_eq!(object.mesh_for_size(5.0), object.lods[1]);
+        let plain = RenderObject::new("plain", object.mesh);
+        assert_eq!(plain.mesh_for_size(5.0), object.mesh);
+    }
+}
@@ -6,46 +6,154 @@ struct Uniforms {
 @group(0) @binding(0)
 var<uniform> uniforms: Uniforms;
 
+struct Lights {
+    // Unit vectors towards the lights, in world space
+    key_direction: vec4<f32>,
+    key_color: vec4<f32>,
+    fill_direction: vec4<f32>,
+    fill_color: vec4<f32>,
+    // Ambient color, specular strength in w
+    ambient: vec4<f32>,
+    // Blinn-Phong exponent in x
+    shininess: vec4<f32>,
+    // Camera axes in world space
+    camera_right: vec4<f32>,
+    camera_up: vec4<f32>,
+    // Shading mode in x: 0 lit, 1 matcap
+    mode: vec4<u32>,
+};
+
+@group(0) @binding(1)
+var<uniform> lights: Lights;
+
+@group(0) @binding(2)
+var matcap: texture_2d<f32>;
+@group(0) @binding(3)
+var matcap_sampler: sampler;
+
+struct Object {
+    model: mat4x4<f32>,
+    // Inverse transpose of model, for normals
+    normal: mat4x4<f32>,
+    color: vec4<f32>,
+    // Object index + 1, for the ID pass
+    id: vec4<u32>,
+    // Selection or hover tint, mixed in by w
+    highlight: vec4<f32>,
+    // Texture repeats per unit of uv in x
+    texture: vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> object: Object;
+
+// Key light shadow map, see shadow_lookup.wgsl
+@group(2) @binding(0)
+var<uniform> shadow: Shadow;
+@group(2) @binding(1)
+var shadow_map: texture_depth_2d;
+@group(2) @binding(2)
+var shadow_sampler: sampler_comparison;
+
+// Object texture, bound for the textured pipelines only
+@group(3) @binding(0)
+var surface_texture: texture_2d<f32>;
+@group(3) @binding(1)
+var surface_sampler: sampler;
+
+// Mixed into back faces, which are only drawn double-sided
+const BACK_FACE_TINT: vec4<f32> = vec4<f32>(0.85, 0.2, 0.55, 0.5);
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
+    @location(3) uv: vec2<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) world_normal: vec3<f32>,
     @location(1) world_position: vec3<f32>,
+    // Material color, or the vertex color if the mesh has them
+    @location(2) base_color: vec3<f32>,
+    @location(3) uv: vec2<f32>,
 };
 
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn transform_vertex(in: VertexInput, base_color: vec3<f32>) -> VertexOutput {
     var out: VertexOutput;
 
-    // Transform position to clip space
-    out.clip_position = uniforms.view_proj * vec4<f32>(in.position, 1.0);
+    // Place the mesh, then transform to clip space
+    let world_position = object.model * vec4<f32>(in.position, 1.0);
+    out.clip_position = uniforms.view_proj * world_position;
 
     // Pass world-space data to fragment shader
-    out.world_normal = in.normal;
-    out.world_position = in.position;
+    out.world_normal = (object.normal * vec4<f32>(in.normal, 0.0)).xyz;
+    out.world_position = world_position.xyz;
+    out.base_color = base_color;
+    out.uv = in.uv;
 
     return out;
 }
 
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    return transform_vertex(in, object.color.rgb);
+}
+
+// Same, for meshes with a color per vertex in a second buffer
+@vertex
+fn vs_colored(in: VertexInput, @location(2) color: vec3<f32>) -> VertexOutput {
+    return transform_vertex(in, color);
+}
+
+// Blinn-Phong contribution of one directional light
+fn shade(base_color: vec3<f32>, normal: vec3<f32>, view_dir: vec3<f32>, light_dir: vec3<f32>, light_color: vec3<f32>) -> vec3<f32> {
+    let diffuse = max(dot(normal, light_dir), 0.0) * base_color;
+    let half_dir = normalize(light_dir + view_dir);
+    let highlight = pow(max(dot(normal, half_dir), 0.0), lights.shininess.x);
+    let specular = select(0.0, highlight * lights.ambient.w, dot(normal, light_dir) > 0.0);
+    return light_color * (diffuse + vec3<f32>(specular));
+}
+
+// Lit sphere image looked up by where the normal points on screen
+fn matcap_color(base_color: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let screen = vec2<f32>(dot(normal, lights.camera_right.xyz), dot(normal, lights.camera_up.xyz));
+    let uv = vec2<f32>(screen.x, -screen.y) * 0.5 + 0.5;
+    return textureSample(matcap, matcap_sampler, uv).rgb * base_color;
+}
+
+@fragment
+fn fs_main(in: VertexOutput, @builtin(front_facing) front_facing: bool) -> @location(0) vec4<f32> {
+    return shade_surface(in, front_facing, in.base_color);
+}
+
+// Same, with the color multiplied by the object texture
 @fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    // Simple directional lighting
-    let light_dir = normalize(vec3<f32>(1.0, 1.0, 1.0));
-    let normal = normalize(in.world_normal);
+fn fs_textured(in: VertexOutput, @builtin(front_facing) front_facing: bool) -> @location(0) vec4<f32> {
+    let texel = textureSample(surface_texture, surface_sampler, in.uv * object.texture.x);
+    return shade_surface(in, front_facing, in.base_color * texel.rgb);
+}
 
-    // Lambertian diffuse
-    let diffuse = max(dot(normal, light_dir), 0.0);
+fn shade_surface(in: VertexOutput, front_facing: bool, base_color: vec3<f32>) -> vec4<f32> {
+    // Back faces are lit from their own side
+    let normal = normalize(select(-in.world_normal, in.world_normal, front_facing));
+    let view_dir = normalize(uniforms.eye_pos - in.world_position);
 
-    // Ambient
-    let ambient = 0.2;
+    // Sampled outside the branch, which needs uniform control flow
+    let captured = matcap_color(base_color, normal);
+    var color = lights.ambient.rgb * base_color;
+    if (lights.mode.x == 1u) {
+        color = captured;
+    } else {
+        let key = shade(base_color, normal, view_dir, lights.key_direction.xyz, lights.key_color.rgb);
+        color += key * shadow_factor(in.world_position);
+        color += shade(base_color, normal, view_dir, lights.fill_direction.xyz, lights.fill_color.rgb);
+    }
 
-    // Final color (gray material)
-    let base_color = vec3<f32>(0.7, 0.7, 0.7);
-    let color = base_color * (ambient + diffuse * 0.8);
+    if (!front_facing) {
+        color = mix(color, BACK_FACE_TINT.rgb, BACK_FACE_TINT.w);
+    }
+    color = mix(color, object.highlight.rgb, object.highlight.w);
 
-    return vec4<f32>(color, 1.0);
+    return vec4<f32>(color, object.color.a);
 }
@@ -0,0 +1,369 @@
+use crate::renderer::mesh::{BoundingBox3, Vertex};
+use bytemuck::{Pod, Zeroable};
+use eframe::wgpu;
+use glam::{Mat4, Vec3};
+
+const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Shadow depth offset in light clip space, against acne on lit faces
+const DEPTH_BIAS: f32 = 0.002;
+
+/// Shadow map resolution and filtering; lower settings keep weak GPUs usable
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShadowQuality {
+    /// No shadow pass at all
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    pub const ALL: [ShadowQuality; 4] = [
+        ShadowQuality::Off,
+        ShadowQuality::Low,
+        ShadowQuality::Medium,
+        ShadowQuality::High,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShadowQuality::Off => "Off",
+            ShadowQuality::Low => "Low",
+            ShadowQuality::Medium => "Medium",
+            ShadowQuality::High => "High",
+        }
+    }
+
+    /// Side of the shadow map in texels
+    pub fn map_size(&self) -> u32 {
+        match self {
+            ShadowQuality::Off => 1,
+            ShadowQuality::Low => 1024,
+            ShadowQuality::Medium => 2048,
+            ShadowQuality::High => 4096,
+        }
+    }
+
+    /// PCF kernel radius in texels, giving (2r + 1)² filtered taps
+    pub fn pcf_radius(&self) -> u32 {
+        match self {
+            ShadowQuality::Off | ShadowQuality::Low => 0,
+            ShadowQuality::Medium => 1,
+            ShadowQuality::High => 2,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct ShadowUniforms {
+    light_view_proj: [[f32; 4]; 4],
+    /// Enabled (0 or 1), texel size, PCF radius and depth bias
+    params: [f32; 4],
+}
+
+/// Orthographic view-projection of a light shining from `direction` (towards
+/// the light) that takes in everything inside the bounding sphere of `bounds`
+pub fn light_view_projection(direction: Vec3, bounds: &BoundingBox3) -> Mat4 {
+    let center = bounds.center();
+    let radius = bounds.radius().max(1e-3);
+    let direction = direction.normalize();
+    let up = if direction.cross(Vec3::Y).length_squared() < 1e-6 {
+        Vec3::X
+    } else {
+        Vec3::Y
+    };
+    let view = Mat4::look_at_rh(center + direction * radius * 2.0, center, up);
+    let projection = Mat4::orthographic_rh(-radius, radius, -radius, radius, radius, radius * 3.0);
+    projection * view
+}
+
+/// Depth map rendered from the key light, with the bind group the shaded
+/// passes sample it through
+pub struct ShadowMap {
+    quality: ShadowQuality,
+    /// Depth-only pass drawing the objects from the light
+    pipeline: wgpu::RenderPipeline,
+    pass_bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    depth: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+impl ShadowMap {
+    /// `object_layout` is the per-object bind group layout of the renderer,
+    /// bound at group 1 while drawing the shadow pass
+    pub fn new(
+        device: &wgpu::Device,
+        object_layout: &wgpu::BindGroupLayout,
+        quality: ShadowQuality,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shadow Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shadow.wgsl").into()),
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Uniforms"),
+            size: std::mem::size_of::<ShadowUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_entry = |visibility| wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let pass_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Pass Bind Group Layout"),
+            entries: &[uniform_entry(wgpu::ShaderStages::VERTEX)],
+        });
+        let pass_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Pass Bind Group"),
+            layout: &pass_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[&pass_layout, object_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shadow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: None,
+            // Both sides cast, so open shells still throw shadows
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: SHADOW_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Bind Group Layout"),
+            entries: &[
+                uniform_entry(wgpu::ShaderStages::FRAGMENT),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+        let depth = Self::create_depth(device, quality);
+        let bind_group =
+            Self::create_bind_group(device, &layout, &uniform_buffer, &depth, &sampler);
+
+        Self {
+            quality,
+            pipeline,
+            pass_bind_group,
+            uniform_buffer,
+            depth,
+            sampler,
+            layout,
+            bind_group,
+        }
+    }
+
+    fn create_depth(device: &wgpu::Device, quality: ShadowQuality) -> wgpu::TextureView {
+        let size = quality.map_size();
+        device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Shadow Map"),
+                size: wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: SHADOW_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        depth: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(depth),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    pub fn quality(&self) -> ShadowQuality {
+        self.quality
+    }
+
+    /// Resize the map for `quality`; the bind group layout stays the same
+    pub fn set_quality(&mut self, device: &wgpu::Device, quality: ShadowQuality) {
+        if quality == self.quality {
+            return;
+        }
+        self.quality = quality;
+        self.depth = Self::create_depth(device, quality);
+        self.bind_group = Self::create_bind_group(
+            device,
+            &self.layout,
+            &self.uniform_buffer,
+            &self.depth,
+            &self.sampler,
+        );
+    }
+
+    /// Layout of [`ShadowMap::bind_group`], for pipelines sampling the map
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    /// Light matrix and parameters, the depth map and its sampler
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Aim the light from `direction` at `bounds`; with no bounds, or with
+    /// shadows off, nothing is shadowed. Returns whether to draw the pass.
+    pub fn prepare(
+        &self,
+        queue: &wgpu::Queue,
+        direction: Vec3,
+        bounds: Option<BoundingBox3>,
+    ) -> bool {
+        let bounds = bounds.filter(|_| self.quality != ShadowQuality::Off);
+        let uniforms = match bounds {
+            Some(bounds) => ShadowUniforms {
+                light_view_proj: light_view_projection(direction, &bounds).to_cols_array_2d(),
+                params: [
+                    1.0,
+                    1.0 / self.quality.map_size() as f32,
+                    self.quality.pcf_radius() as f32,
+                    DEPTH_BIAS,
+                ],
+            },
+            None => ShadowUniforms::zeroed(),
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+        bounds.is_some()
+    }
+
+    /// Start the depth pass from the light, with the pipeline and group 0
+    /// set; the caller binds and draws each object
+    pub fn begin_pass<'a>(&self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.pass_bind_group, &[]);
+        render_pass
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_light_frustum_covers_bounds() {
+        let bounds = BoundingBox3::new(Vec3::new(-1.0, -2.0, 0.0), Vec3::new(3.0, 2.0, 5.0));
+        for direction in [Vec3::Y, Vec3::new(-0.4, 0.6, 0.7), Vec3::NEG_Z] {
+            let light = light_view_projection(direction, &bounds);
+            for i in 0..8 {
+                let corner = Vec3::select(
+                    glam::BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0),
+                    bounds.max,
+                    bounds.min,
+                );
+                let ndc = light.project_point3(corner);
+                assert!(ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0, "{:?}", ndc);
+                assert!((0.0..=1.0).contains(&ndc.z), "{:?}", ndc);
+            }
+            // Points nearer the light get smaller depths
+            let near = light.project_point3(bounds.center() + direction.normalize());
+            assert!(near.z < light.project_point3(bounds.center()).z);
+        }
+        assert_eq!(ShadowQuality::default().pcf_radius(), 1);
+    }
+}
@@ -0,0 +1,28 @@
+struct Shadow {
+    light_view_proj: mat4x4<f32>,
+    params: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> shadow: Shadow;
+
+struct Object {
+    model: mat4x4<f32>,
+    normal: mat4x4<f32>,
+    color: vec4<f32>,
+    id: vec4<u32>,
+};
+
+@group(1) @binding(0)
+var<uniform> object: Object;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+// Depth only: no fragment stage
+@vertex
+fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+    return shadow.light_view_proj * object.model * vec4<f32>(in.position, 1.0);
+}
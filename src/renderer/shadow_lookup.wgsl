@@ -0,0 +1,33 @@
+// Shared by the shaders sampling the key light shadow map; each declares
+// `shadow`, `shadow_map` and `shadow_sampler` in its own bind group
+
+struct Shadow {
+    light_view_proj: mat4x4<f32>,
+    // Enabled, texel size, PCF radius, depth bias
+    params: vec4<f32>,
+};
+
+// Fraction of the key light reaching `world_position`
+fn shadow_factor(world_position: vec3<f32>) -> f32 {
+    if (shadow.params.x == 0.0) {
+        return 1.0;
+    }
+    let clip = shadow.light_view_proj * vec4<f32>(world_position, 1.0);
+    let ndc = clip.xyz / clip.w;
+    let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+    // Outside the light frustum counts as lit
+    if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0) {
+        return 1.0;
+    }
+    let radius = i32(shadow.params.z);
+    let depth = ndc.z - shadow.params.w;
+    var lit = 0.0;
+    for (var x = -radius; x <= radius; x += 1) {
+        for (var y = -radius; y <= radius; y += 1) {
+            let offset = vec2<f32>(f32(x), f32(y)) * shadow.params.y;
+            lit += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, depth);
+        }
+    }
+    let side = f32(2 * radius + 1);
+    return lit / (side * side);
+}
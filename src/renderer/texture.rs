@@ -0,0 +1,175 @@
+use crate::sketch::{SketchError, SketchResult};
+use eframe::wgpu;
+use eframe::wgpu::util::DeviceExt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Image wrapped around a part by the surface parameters of its faces, see
+/// [`super::mesh::Vertex::uv`]; sRGB RGBA rows from the top
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl TextureImage {
+    /// `cells` by `cells` squares alternating between `a` and `b`, for
+    /// checking how a part is parameterized
+    pub fn checker(size: u32, cells: u32, a: [u8; 4], b: [u8; 4]) -> Self {
+        let cell = (size / cells.max(1)).max(1);
+        let rgba = (0..size * size)
+            .flat_map(|i| {
+                let (x, y) = (i % size / cell, i / size / cell);
+                if (x + y) % 2 == 0 {
+                    a
+                } else {
+                    b
+                }
+            })
+            .collect();
+        Self {
+            width: size,
+            height: size,
+            rgba,
+        }
+    }
+
+    /// Read an 8- or 16-bit gray, RGB or RGBA PNG
+    pub fn load(path: &Path) -> SketchResult<Self> {
+        let fail = |e: &dyn std::fmt::Display| {
+            SketchError::ImportFailed(format!("{}: {}", path.display(), e))
+        };
+        let file = File::open(path).map_err(|e| fail(&e))?;
+        let mut decoder = png::Decoder::new(BufReader::new(file));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|e| fail(&e))?;
+        let size = reader
+            .output_buffer_size()
+            .ok_or_else(|| fail(&"image too large"))?;
+        let mut pixels = vec![0; size];
+        let info = reader.next_frame(&mut pixels).map_err(|e| fail(&e))?;
+        pixels.truncate(info.buffer_size());
+        let rgba = match info.color_type {
+            png::ColorType::Rgba => pixels,
+            png::ColorType::Rgb => pixels
+                .chunks(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => pixels
+                .chunks(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+            png::ColorType::Indexed => return Err(fail(&"unexpanded palette")),
+        };
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            rgba,
+        })
+    }
+}
+
+/// Layout of [`ObjectTexture`] bind groups: the image and a repeating sampler
+pub fn texture_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Texture Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    })
+}
+
+/// Uploaded texture of one object, bound by the textured pipelines
+pub struct ObjectTexture {
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl ObjectTexture {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        image: &TextureImage,
+    ) -> Self {
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Object Texture"),
+                size: wgpu::Extent3d {
+                    width: image.width,
+                    height: image.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &image.rgba,
+        );
+        // Surface parameters run past 0..1, so the image tiles
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Object Texture Sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Object Texture Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        Self { bind_group }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checker() {
+        let black = [0, 0, 0, 255];
+        let white = [255; 4];
+        let image = TextureImage::checker(8, 2, black, white);
+        assert_eq!(image.rgba.len(), 8 * 8 * 4);
+        let texel = |x: u32, y: u32| &image.rgba[((y * 8 + x) * 4) as usize..][..4];
+        assert_eq!(texel(0, 0), black);
+        assert_eq!(texel(4, 0), white);
+        assert_eq!(texel(4, 4), black);
+        assert!(TextureImage::load(Path::new("/nonexistent/texture.png")).is_err());
+    }
+}
@@ -0,0 +1,479 @@
+use crate::export::step::{write_step, StepExportOptions};
+use crate::export::svg::{write_svg, SvgDrawing};
+use crate::geometry;
+use crate::model::{box_solid, cylinder, sphere, Assembly};
+use crate::project::PlacedSketch;
+use crate::sketch::{Loop2D, Plane, Shapes, Sketch, SketchBuilder, SketchError, SketchResult};
+use crate::units::Units;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, INT};
+use std::cell::RefCell;
+use std::rc::Rc;
+use truck_geometry::prelude::*;
+use truck_modeling::{builder as truck_builder, Solid};
+
+/// File extension of saved scripts
+pub const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Operations one run may take before it is stopped, so a runaway loop
+/// cannot hang the app
+const MAX_OPERATIONS: u64 = 50_000_000;
+
+type ScriptResult<T> = std::result::Result<T, Box<EvalAltResult>>;
+
+/// What a script run added, in the order it added it
+#[derive(Clone, Debug, Default)]
+pub struct ScriptOutput {
+    /// Parts from `add_part`, in world coordinates
+    pub parts: Vec<(String, Solid)>,
+    pub sketches: Vec<PlacedSketch>,
+    /// Lines from `print` and `debug`
+    pub log: Vec<String>,
+    /// Value of the last statement, unless it was `()`
+    pub value: Option<String>,
+}
+
+/// What the registered functions share with the engine's owner
+#[derive(Default)]
+struct Shared {
+    output: ScriptOutput,
+    /// Parts `part(name)` can read, placed in the world
+    parts: Vec<(String, Solid)>,
+    units: Units,
+}
+
+/// Rhai engine with the modeling API registered, keeping its variables
+/// between runs so a console can build on earlier lines.
+///
+/// Lengths are plain numbers in the project units; angles are in degrees.
+/// Profiles come from the shape functions or `builder(x, y)`, become
+/// sketches with `sketch(profile)`, and solids with `extrude` or
+/// `revolve`; `add_part` and `add_sketch` hand results back.
+pub struct ScriptEngine {
+    engine: Engine,
+    scope: Scope<'static>,
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptEngine {
+    pub fn new() -> Self {
+        let shared = Rc::new(RefCell::new(Shared::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let log = shared.clone();
+        engine.on_print(move |text| log.borrow_mut().output.log.push(text.to_string()));
+        let log = shared.clone();
+        engine.on_debug(move |text, _, _| log.borrow_mut().output.log.push(text.to_string()));
+        register_sketching(&mut engine);
+        register_modeling(&mut engine);
+        register_output(&mut engine, &shared);
+        Self {
+            engine,
+            scope: Scope::new(),
+            shared,
+        }
+    }
+
+    /// Let `part(name)` read the parts of `assembly`
+    pub fn set_assembly(&mut self, assembly: &Assembly) {
+        self.shared.borrow_mut().parts = assembly
+            .parts()
+            .iter()
+            .map(|part| (part.name().to_string(), part.placed_solid()))
+            .collect();
+    }
+
+    /// Units exports declare
+    pub fn set_units(&mut self, units: Units) {
+        self.shared.borrow_mut().units = units;
+    }
+
+    /// Forget the variables earlier runs defined
+    pub fn reset(&mut self) {
+        self.scope.clear();
+    }
+
+    /// Run `code`, returning what it added. Variables it defines stay for
+    /// the next run; on an error, what ran before it is dropped.
+    pub fn run(&mut self, code: &str) -> SketchResult<ScriptOutput> {
+        let result = self
+            .engine
+            .eval_with_scope::<Dynamic>(&mut self.scope, code);
+        let mut output = std::mem::take(&mut self.shared.borrow_mut().output);
+        let value = result.map_err(|e| SketchError::ScriptFailed(e.to_string()))?;
+        output.value = match value {
+            _ if value.is_unit() => None,
+            // Registered types show their script name
+            _ if value.is_variant() => Some(self.engine.map_type_name(value.type_name()).into()),
+            _ => Some(value.to_string()),
+        };
+        Ok(output)
+    }
+}
+
+/// Run `code` once in a fresh engine
+pub fn run_script(code: &str, units: Units) -> SketchResult<ScriptOutput> {
+    let mut engine = ScriptEngine::new();
+    engine.set_units(units);
+    engine.run(code)
+}
+
+fn fail(e: impl ToString) -> Box<EvalAltResult> {
+    e.to_string().into()
+}
+
+/// Integers and floats both, so scripts can write `10` for a length
+fn num(value: &Dynamic) -> ScriptResult<f64> {
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|i| i as f64))
+        .map_err(|kind| fail(format!("expected a number, got {}", kind)))
+}
+
+fn point(x: &Dynamic, y: &Dynamic) -> ScriptResult<Point2> {
+    Ok(Point2::new(num(x)?, num(y)?))
+}
+
+fn vector(x: &Dynamic, y: &Dynamic, z: &Dynamic) -> ScriptResult<Vector3> {
+    Ok(Vector3::new(num(x)?, num(y)?, num(z)?))
+}
+
+/// Profiles, the builder, sketches and planes
+fn register_sketching(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Loop2D>("Profile")
+        .register_type_with_name::<SketchBuilder>("Builder")
+        .register_type_with_name::<Sketch>("Sketch")
+        .register_type_with_name::<Plane>("Plane");
+
+    engine
+        .register_fn(
+            "rectangle",
+            |x: Dynamic, y: Dynamic, w: Dynamic, h: Dynamic| -> ScriptResult<Loop2D> {
+                Shapes::rectangle(point(&x, &y)?, num(&w)?, num(&h)?).map_err(fail)
+            },
+        )
+        .register_fn(
+            "rectangle_centered",
+            |x: Dynamic, y: Dynamic, w: Dynamic, h: Dynamic| -> ScriptResult<Loop2D> {
+                Shapes::rectangle_centered(point(&x, &y)?, num(&w)?, num(&h)?).map_err(fail)
+            },
+        )
+        .register_fn(
+            "rounded_rectangle",
+            |x: Dynamic, y: Dynamic, w: Dynamic, h: Dynamic, r: Dynamic| -> ScriptResult<Loop2D> {
+                Shapes::rounded_rectangle(point(&x, &y)?, num(&w)?, num(&h)?, num(&r)?)
+                    .map_err(fail)
+            },
+        )
+        .register_fn(
+            "circle",
+            |x: Dynamic, y: Dynamic, r: Dynamic| -> ScriptResult<Loop2D> {
+                Shapes::circle(point(&x, &y)?, num(&r)?).map_err(fail)
+            },
+        )
+        .register_fn(
+            "polygon",
+            |x: Dynamic, y: Dynamic, r: Dynamic, n: INT| -> ScriptResult<Loop2D> {
+                let sides = usize::try_from(n).map_err(fail)?;
+                Shapes::regular_polygon(point(&x, &y)?, num(&r)?, sides).map_err(fail)
+            },
+        )
+        .register_fn(
+            "slot",
+            |x: Dynamic, y: Dynamic, length: Dynamic, width: Dynamic| -> ScriptResult<Loop2D> {
+                Shapes::slot(point(&x, &y)?, num(&length)?, num(&width)?, true).map_err(fail)
+            },
+        )
+        .register_fn(
+            "hexagon",
+            |x: Dynamic, y: Dynamic, size: Dynamic| -> ScriptResult<Loop2D> {
+                Shapes::hexagon(point(&x, &y)?, num(&size)?).map_err(fail)
+            },
+        );
+
+    // Builder steps return the builder so they chain
+    engine
+        .register_fn(
+            "builder",
+            |x: Dynamic, y: Dynamic| -> ScriptResult<SketchBuilder> {
+                Ok(SketchBuilder::new().move_to(point(&x, &y)?))
+            },
+        )
+        .register_fn(
+            "line_to",
+            |b: &mut SketchBuilder, x: Dynamic, y: Dynamic| -> ScriptResult<SketchBuilder> {
+                let end = point(&x, &y)?;
+                *b = b.clone().line_to(end).map_err(fail)?;
+                Ok(b.clone())
+            },
+        )
+        .register_fn(
+            "line_by",
+            |b: &mut SketchBuilder, dx: Dynamic, dy: Dynamic| -> ScriptResult<SketchBuilder> {
+                let (dx, dy) = (num(&dx)?, num(&dy)?);
+                *b = b.clone().line_by(dx, dy).map_err(fail)?;
+                Ok(b.clone())
+            },
+        )
+        .register_fn(
+            "horizontal",
+            |b: &mut SketchBuilder, dx: Dynamic| -> ScriptResult<SketchBuilder> {
+                let dx = num(&dx)?;
+                *b = b.clone().horizontal(dx).map_err(fail)?;
+                Ok(b.clone())
+            },
+        )
+        .register_fn(
+            "vertical",
+            |b: &mut SketchBuilder, dy: Dynamic| -> ScriptResult<SketchBuilder> {
+                let dy = num(&dy)?;
+                *b = b.clone().vertical(dy).map_err(fail)?;
+                Ok(b.clone())
+            },
+        )
+        .register_fn(
+            "arc_through",
+            |b: &mut SketchBuilder,
+             mx: Dynamic,
+             my: Dynamic,
+             x: Dynamic,
+             y: Dynamic|
+             -> ScriptResult<SketchBuilder> {
+                let (mid, end) = (point(&mx, &my)?, point(&x, &y)?);
+                *b = b.clone().arc_through(mid, end).map_err(fail)?;
+                Ok(b.clone())
+            },
+        )
+        .register_fn("close", |b: &mut SketchBuilder| -> ScriptResult<Loop2D> {
+            b.clone().close().map_err(fail)
+        });
+
+    engine
+        .register_fn("sketch", Sketch::new)
+        .register_fn("hole", |sketch: &mut Sketch, hole: Loop2D| -> Sketch {
+            sketch.add_hole(hole);
+            sketch.clone()
+        })
+        .register_get("area", |sketch: &mut Sketch| sketch.area())
+        .register_get("perimeter", |sketch: &mut Sketch| sketch.perimeter());
+
+    engine
+        .register_fn("plane_xy", Plane::xy)
+        .register_fn("plane_xz", Plane::xz)
+        .register_fn("plane_yz", Plane::yz)
+        .register_fn(
+            "offset",
+            |plane: &mut Plane, distance: Dynamic| -> ScriptResult<Plane> {
+                Ok(plane.offset(num(&distance)?))
+            },
+        );
+}
+
+/// Solids from sketches and primitives, booleans and placement
+fn register_modeling(engine: &mut Engine) {
+    engine.register_type_with_name::<Solid>("Solid");
+
+    engine
+        .register_fn(
+            "extrude",
+            |sketch: Sketch, plane: Plane, depth: Dynamic| -> ScriptResult<Solid> {
+                let direction = plane.normal() * num(&depth)?;
+                sketch.extrude(plane, direction).map_err(fail)
+            },
+        )
+        // About the plane's Y axis through its origin
+        .register_fn(
+            "revolve",
+            |sketch: Sketch, plane: Plane, degrees: Dynamic| -> ScriptResult<Solid> {
+                let angle = Rad::from(Deg(num(&degrees)?));
+                let (origin, axis) = (plane.origin(), plane.y_dir());
+                sketch.revolve(plane, origin, axis, angle).map_err(fail)
+            },
+        )
+        .register_fn(
+            "box_solid",
+            |x: Dynamic,
+             y: Dynamic,
+             z: Dynamic,
+             dx: Dynamic,
+             dy: Dynamic,
+             dz: Dynamic|
+             -> ScriptResult<Solid> {
+                let min = Point3::from_vec(vector(&x, &y, &z)?);
+                box_solid(min, vector(&dx, &dy, &dz)?).map_err(fail)
+            },
+        )
+        // Standing on the XY plane at (x, y, z)
+        .register_fn(
+            "cylinder",
+            |x: Dynamic, y: Dynamic, z: Dynamic, r: Dynamic, h: Dynamic| -> ScriptResult<Solid> {
+                let base = Point3::from_vec(vector(&x, &y, &z)?);
+                cylinder(base, Vector3::unit_z() * num(&h)?, num(&r)?).map_err(fail)
+            },
+        )
+        .register_fn(
+            "sphere",
+            |x: Dynamic, y: Dynamic, z: Dynamic, r: Dynamic| -> ScriptResult<Solid> {
+                let centre = Point3::from_vec(vector(&x, &y, &z)?);
+                sphere(centre, num(&r)?).map_err(fail)
+            },
+        );
+
+    let union =
+        |a: Solid, b: Solid| -> ScriptResult<Solid> { geometry::union(&a, &b).map_err(fail) };
+    let cut = |a: Solid, b: Solid| -> ScriptResult<Solid> { geometry::cut(&a, &b).map_err(fail) };
+    engine
+        .register_fn("union", union)
+        .register_fn("+", union)
+        .register_fn("cut", cut)
+        .register_fn("-", cut);
+
+    engine
+        .register_fn(
+            "translate",
+            |solid: &mut Solid, dx: Dynamic, dy: Dynamic, dz: Dynamic| -> ScriptResult<Solid> {
+                Ok(truck_builder::translated(&*solid, vector(&dx, &dy, &dz)?))
+            },
+        )
+        .register_fn(
+            "rotate_x",
+            |solid: &mut Solid, degrees: Dynamic| -> ScriptResult<Solid> {
+                rotate(solid, Vector3::unit_x(), &degrees)
+            },
+        )
+        .register_fn(
+            "rotate_y",
+            |solid: &mut Solid, degrees: Dynamic| -> ScriptResult<Solid> {
+                rotate(solid, Vector3::unit_y(), &degrees)
+            },
+        )
+        .register_fn(
+            "rotate_z",
+            |solid: &mut Solid, degrees: Dynamic| -> ScriptResult<Solid> {
+                rotate(solid, Vector3::unit_z(), &degrees)
+            },
+        );
+}
+
+/// `solid` turned about a world axis through the origin
+fn rotate(solid: &Solid, axis: Vector3, degrees: &Dynamic) -> ScriptResult<Solid> {
+    let angle = Rad::from(Deg(num(degrees)?));
+    Ok(truck_builder::rotated(solid, Point3::origin(), axis, angle))
+}
+
+/// Handing results back, reading existing parts, and export
+fn register_output(engine: &mut Engine, shared: &Rc<RefCell<Shared>>) {
+    let state = shared.clone();
+    engine.register_fn("add_part", move |name: &str, solid: Solid| {
+        state
+            .borrow_mut()
+            .output
+            .parts
+            .push((name.to_string(), solid));
+    });
+    let state = shared.clone();
+    engine.register_fn(
+        "add_sketch",
+        move |name: &str, plane: Plane, sketch: Sketch| {
+            state.borrow_mut().output.sketches.push(PlacedSketch {
+                name: name.to_string(),
+                plane,
+                sketch,
+            });
+        },
+    );
+    let state = shared.clone();
+    engine.register_fn("part", move |name: &str| -> ScriptResult<Solid> {
+        let state = state.borrow();
+        let (_, solid) = state
+            .parts
+            .iter()
+            .find(|(part, _)| part == name)
+            .ok_or_else(|| fail(format!("no part named '{}'", name)))?;
+        Ok(solid.clone())
+    });
+
+    let state = shared.clone();
+    engine.register_fn(
+        "export_step",
+        move |solid: Solid, path: &str| -> ScriptResult<()> {
+            let options = StepExportOptions {
+                unit: state.borrow().units,
+                ..Default::default()
+            };
+            write_step(path, &solid, &options).map_err(fail)
+        },
+    );
+    let state = shared.clone();
+    engine.register_fn(
+        "export_svg",
+        move |sketch: Sketch, path: &str| -> ScriptResult<()> {
+            let mut drawing = SvgDrawing::new();
+            drawing.units = state.borrow().units;
+            drawing.add_sketch(&sketch);
+            write_svg(path, &drawing).map_err(fail)
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::mesh::GpuMesh;
+
+    #[test]
+    fn test_script_engine() {
+        let mut engine = ScriptEngine::new();
+        let output = engine
+            .run(
+                r#"
+                let plate = sketch(rectangle(0, 0, 10, 6)).hole(circle(5, 3, 1.5));
+                let body = extrude(sketch(rectangle(0, 0, 10, 6)), plane_xy(), 2);
+                add_part("plate", body);
+                add_sketch("outline", plane_xy(), plate);
+                print(`area ${plate.area}`);
+                plate.perimeter
+                "#,
+            )
+            .unwrap();
+        assert_eq!(output.parts.len(), 1);
+        assert_eq!(output.parts[0].0, "plate");
+        let volume = GpuMesh::from_solid(&output.parts[0].1, 0.001).volume();
+        assert!((volume - 120.0).abs() < 1e-6, "{}", volume);
+        assert_eq!(output.sketches[0].name, "outline");
+        assert!(output.log[0].starts_with("area 52.9"));
+        assert!(output.value.is_some());
+
+        // Variables carry over, and parts of the assembly can be read
+        let mut assembly = Assembly::new();
+        let rod = cylinder(Point3::origin(), Vector3::unit_z() * 4.0, 1.0).unwrap();
+        assembly.add("rod", rod, Matrix4::identity()).unwrap();
+        engine.set_assembly(&assembly);
+        let output = engine
+            .run(
+                r#"
+                let profile = builder(0, 0).horizontal(4).vertical(3).line_to(0, 0).close();
+                add_part("wedge", extrude(sketch(profile), plane_xz(), 1).translate(0, 0, 1));
+                add_part("rod copy", part("rod").rotate_x(90));
+                body
+                "#,
+            )
+            .unwrap();
+        assert_eq!(output.parts.len(), 2);
+        assert_eq!(output.value.as_deref(), Some("Solid"));
+
+        let error = engine
+            .run("add_part(\"x\", part(\"missing\"))")
+            .unwrap_err();
+        assert!(matches!(error, SketchError::ScriptFailed(_)));
+        assert!(error.to_string().contains("no part named 'missing'"));
+        engine.reset();
+        assert!(engine.run("body").is_err());
+    }
+}
@@ -2,13 +2,17 @@ use crate::sketch::constants::*;
 use crate::sketch::error::*;
 use crate::sketch::loop2d::Loop2D;
 use crate::sketch::primitives::{Arc2D, BSpline2D, Curve2D, Line2D};
+use crate::sketch::snap::SnapSettings;
 use truck_geometry::prelude::*;
 
 /// Fluent builder for creating sketch loops
+#[derive(Clone)]
 pub struct SketchBuilder {
     curves: Vec<Curve2D>,
     current_pos: Option<Point2>,
     start_pos: Option<Point2>,
+    /// Settings and radius points are snapped with, see [`SketchBuilder::snapping`]
+    snap: Option<(SnapSettings, f64)>,
 }
 
 impl SketchBuilder {
@@ -18,11 +22,32 @@ impl SketchBuilder {
             curves: Vec::new(),
             current_pos: None,
             start_pos: None,
+            snap: None,
         }
     }
 
+    /// Snap the points given after this onto the builder's own curves, the
+    /// angle from the current point and the grid of `settings.grid` (none
+    /// when 0), as `settings` say; curve points within `radius` win
+    pub fn snapping(mut self, settings: SnapSettings, radius: f64) -> Self {
+        self.snap = Some((settings, radius));
+        self
+    }
+
+    fn snapped(&self, pt: Point2) -> Point2 {
+        let Some((settings, radius)) = self.snap else {
+            return pt;
+        };
+        let mut targets = settings.targets(&self.curves);
+        if settings.to_endpoints {
+            targets.extend(self.start_pos);
+        }
+        settings.snap(pt, self.current_pos, &targets, radius, settings.grid)
+    }
+
     /// Start at a point (required before drawing)
     pub fn move_to(mut self, pt: Point2) -> Self {
+        let pt = self.snapped(pt);
         self.current_pos = Some(pt);
         if self.start_pos.is_none() {
             self.start_pos = Some(pt);
@@ -33,6 +58,7 @@ impl SketchBuilder {
     /// Draw a line to a point
     pub fn line_to(mut self, pt: Point2) -> SketchResult<Self> {
         let start = self.current_pos.ok_or(SketchError::NoStartingPoint)?;
+        let pt = self.snapped(pt);
 
         let line = Line2D::new(start, pt)?;
         self.curves.push(Curve2D::Line(line));
@@ -63,6 +89,7 @@ impl SketchBuilder {
     /// Draw an arc to a point with given center
     pub fn arc_to(mut self, end: Point2, center: Point2, ccw: bool) -> SketchResult<Self> {
         let start = self.current_pos.ok_or(SketchError::NoStartingPoint)?;
+        let end = self.snapped(end);
 
         let arc = Arc2D::from_start_end_center(start, end, center, ccw)?;
         self.curves.push(Curve2D::Arc(arc));
@@ -75,6 +102,7 @@ impl SketchBuilder {
     #[allow(dead_code)]
     pub fn arc_through(mut self, mid: Point2, end: Point2) -> SketchResult<Self> {
         let start = self.current_pos.ok_or(SketchError::NoStartingPoint)?;
+        let end = self.snapped(end);
 
         let arc = Arc2D::from_three_points(start, mid, end)?;
         self.curves.push(Curve2D::Arc(arc));
@@ -0,0 +1,229 @@
+use crate::sketch::construction::Construction;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::primitives::{Curve2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use std::collections::hash_map::DefaultHasher;
+use std::f64::consts::TAU;
+use std::hash::{Hash, Hasher};
+use truck_geometry::prelude::*;
+
+fn points_close(a: Point2, b: Point2, tol: f64) -> bool {
+    (a - b).magnitude() <= tol
+}
+
+/// Angle difference in (-π, π]
+fn angle_diff(a: f64, b: f64) -> f64 {
+    let d = (a - b).rem_euclid(TAU);
+    if d > TAU / 2.0 {
+        d - TAU
+    } else {
+        d
+    }
+}
+
+fn hash_f64<H: Hasher>(x: f64, state: &mut H) {
+    // -0.0 and 0.0 describe the same geometry
+    let x = if x == 0.0 { 0.0 } else { x };
+    x.to_bits().hash(state);
+}
+
+fn hash_point<H: Hasher>(p: Point2, state: &mut H) {
+    hash_f64(p.x, state);
+    hash_f64(p.y, state);
+}
+
+impl Curve2D {
+    /// Structural comparison: same curve type with every defining quantity
+    /// within `tol` (angles compared as arc length at the curve's radius)
+    pub fn approx_eq(&self, other: &Curve2D, tol: f64) -> bool {
+        match (self, other) {
+            (Curve2D::Line(a), Curve2D::Line(b)) => {
+                points_close(a.start(), b.start(), tol) && points_close(a.end(), b.end(), tol)
+            }
+            (Curve2D::Arc(a), Curve2D::Arc(b)) => {
+                points_close(a.center(), b.center(), tol)
+                    && (a.radius() - b.radius()).abs() <= tol
+                    && angle_diff(a.start_angle(), b.start_angle()).abs() * a.radius() <= tol
+                    && (a.sweep_angle() - b.sweep_angle()).abs() * a.radius() <= tol
+            }
+            (Curve2D::Circle(a), Curve2D::Circle(b)) => {
+                points_close(a.center(), b.center(), tol)
+                    && (a.radius() - b.radius()).abs() <= tol
+                    && a.is_ccw() == b.is_ccw()
+                    && points_close(a.start(), b.start(), tol)
+            }
+            (Curve2D::BSpline(a), Curve2D::BSpline(b)) => {
+                let (ka, kb) = (a.inner().knot_vec(), b.inner().knot_vec());
+                a.degree() == b.degree()
+                    && a.control_points().len() == b.control_points().len()
+                    && ka.len() == kb.len()
+                    && ka.iter().zip(kb.iter()).all(|(x, y)| (x - y).abs() <= tol)
+                    && a.control_points()
+                        .iter()
+                        .zip(b.control_points())
+                        .all(|(p, q)| points_close(*p, *q, tol))
+            }
+            _ => false,
+        }
+    }
+
+    /// Hash of the exact curve definition
+    pub fn content_hash(&self) -> u64 {
+        let mut state = DefaultHasher::new();
+        self.hash_content(&mut state);
+        state.finish()
+    }
+
+    fn hash_content<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Curve2D::Line(line) => {
+                0u8.hash(state);
+                hash_point(line.start(), state);
+                hash_point(line.end(), state);
+            }
+            Curve2D::Arc(arc) => {
+                1u8.hash(state);
+                hash_point(arc.center(), state);
+                hash_f64(arc.radius(), state);
+                hash_f64(arc.start_angle(), state);
+                hash_f64(arc.sweep_angle(), state);
+            }
+            Curve2D::Circle(circle) => {
+                2u8.hash(state);
+                hash_point(circle.center(), state);
+                hash_f64(circle.radius(), state);
+                hash_point(circle.start(), state);
+                circle.is_ccw().hash(state);
+            }
+            Curve2D::BSpline(spline) => {
+                3u8.hash(state);
+                spline.degree().hash(state);
+                spline
+                    .inner()
+                    .knot_vec()
+                    .iter()
+                    .for_each(|&k| hash_f64(k, state));
+                spline
+                    .control_points()
+                    .iter()
+                    .for_each(|&p| hash_point(p, state));
+            }
+        }
+    }
+}
+
+impl Loop2D {
+    /// Curve-by-curve comparison (same start curve and direction)
+    pub fn approx_eq(&self, other: &Loop2D, tol: f64) -> bool {
+        self.len() == other.len()
+            && self
+                .curves()
+                .iter()
+                .zip(other.curves())
+                .all(|(a, b)| a.approx_eq(b, tol))
+    }
+
+    /// Hash of the exact loop definition
+    pub fn content_hash(&self) -> u64 {
+        let mut state = DefaultHasher::new();
+        self.hash_content(&mut state);
+        state.finish()
+    }
+
+    fn hash_content<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for curve in self.curves() {
+            curve.hash_content(state);
+        }
+    }
+}
+
+impl Construction {
+    /// Structural comparison within `tol`
+    pub fn approx_eq(&self, other: &Construction, tol: f64) -> bool {
+        match (self, other) {
+            (Construction::Point(a), Construction::Point(b)) => points_close(*a, *b, tol),
+            (Construction::Line(a), Construction::Line(b)) => {
+                Curve2D::Line(a.clone()).approx_eq(&Curve2D::Line(b.clone()), tol)
+            }
+            (Construction::Circle(a), Construction::Circle(b)) => {
+                Curve2D::Circle(a.clone()).approx_eq(&Curve2D::Circle(b.clone()), tol)
+            }
+            _ => false,
+        }
+    }
+
+    fn hash_content<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Construction::Point(p) => {
+                4u8.hash(state);
+                hash_point(*p, state);
+            }
+            Construction::Line(line) => Curve2D::Line(line.clone()).hash_content(state),
+            Construction::Circle(circle) => Curve2D::Circle(circle.clone()).hash_content(state),
+        }
+    }
+}
+
+impl Sketch {
+    /// Structural comparison of outer loop, holes and construction geometry
+    /// (holes and construction compared in order)
+    pub fn approx_eq(&self, other: &Sketch, tol: f64) -> bool {
+        self.outer.approx_eq(&other.outer, tol)
+            && self.holes.len() == other.holes.len()
+            && self
+                .holes
+                .iter()
+                .zip(&other.holes)
+                .all(|(a, b)| a.approx_eq(b, tol))
+            && self.construction.len() == other.construction.len()
+            && self
+                .construction
+                .iter()
+                .zip(&other.construction)
+                .all(|(a, b)| a.approx_eq(b, tol))
+    }
+
+    /// Hash of the exact sketch definition, suitable as a cache key
+    pub fn content_hash(&self) -> u64 {
+        let mut state = DefaultHasher::new();
+        self.outer.hash_content(&mut state);
+        self.holes.len().hash(&mut state);
+        for hole in &self.holes {
+            hole.hash_content(&mut state);
+        }
+        self.construction.len().hash(&mut state);
+        for geometry in &self.construction {
+            geometry.hash_content(&mut state);
+        }
+        state.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::Shapes;
+
+    #[test]
+    fn test_sketch_approx_eq_and_hash() {
+        let a = Sketch::with_holes(
+            Shapes::rectangle(Point2::origin(), 10.0, 5.0).unwrap(),
+            vec![Shapes::circle(Point2::new(5.0, 2.5), 1.0).unwrap()],
+        );
+        let b = a.clone();
+        assert!(a.approx_eq(&b, 1e-9));
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        let c = Sketch::with_holes(
+            Shapes::rectangle(Point2::origin(), 10.0, 5.0 + 1e-7).unwrap(),
+            vec![Shapes::circle(Point2::new(5.0, 2.5), 1.0).unwrap()],
+        );
+        assert!(a.approx_eq(&c, 1e-6));
+        assert!(!a.approx_eq(&c, 1e-9));
+        assert_ne!(a.content_hash(), c.content_hash());
+
+        let d = Sketch::new(Shapes::rectangle(Point2::origin(), 10.0, 5.0).unwrap());
+        assert!(!a.approx_eq(&d, 1e-6));
+    }
+}
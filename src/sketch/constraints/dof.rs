@@ -0,0 +1,268 @@
+use super::solver::{free_variables, jacobian, residual_owners};
+use super::{ConstraintSystem, Entity, EntityId};
+use crate::sketch::error::*;
+use std::collections::HashMap;
+
+/// Relative tolerance for treating a Jacobian pivot as zero
+const RANK_TOLERANCE: f64 = 1e-8;
+
+/// Overall constraint state of a sketch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstraintStatus {
+    /// Some geometry can still move
+    UnderConstrained,
+    /// Every entity is fully determined
+    WellConstrained,
+    /// Some constraints are redundant or conflicting
+    OverConstrained,
+}
+
+/// Degrees-of-freedom analysis of a constraint system
+#[derive(Clone, Debug)]
+pub struct DofReport {
+    /// Overall status
+    pub status: ConstraintStatus,
+    /// Remaining degrees of freedom of the whole sketch
+    pub dof: usize,
+    /// Indices (into `ConstraintSystem::constraints`) of redundant constraints
+    pub redundant: Vec<usize>,
+    entity_dof: HashMap<EntityId, usize>,
+}
+
+impl DofReport {
+    /// Remaining degrees of freedom of one entity
+    pub fn entity_dof(&self, id: EntityId) -> usize {
+        self.entity_dof.get(&id).copied().unwrap_or(0)
+    }
+
+    /// True when the entity cannot move without violating a constraint
+    pub fn is_fully_constrained(&self, id: EntityId) -> bool {
+        self.entity_dof(id) == 0
+    }
+
+    /// Entities that can still move, with their remaining DOF
+    pub fn unconstrained_entities(&self) -> Vec<(EntityId, usize)> {
+        let mut result: Vec<_> = self
+            .entity_dof
+            .iter()
+            .filter(|(_, &dof)| dof > 0)
+            .map(|(&id, &dof)| (id, dof))
+            .collect();
+        result.sort();
+        result
+    }
+}
+
+impl ConstraintSystem {
+    /// Analyze remaining degrees of freedom at the current geometry
+    ///
+    /// Uses the rank of the constraint Jacobian: the null space gives the
+    /// directions the sketch can still move in, and each entity's DOF is the
+    /// rank of that null space restricted to the entity's own variables.
+    pub fn analyze_dof(&self) -> SketchResult<DofReport> {
+        let free = free_variables(self);
+        let params = self.params();
+        let jac = jacobian(self, params, &free)?;
+        let owners = residual_owners(self)?;
+
+        let redundant = redundant_constraints(&jac, &owners);
+        let null_space = null_space(&jac, free.len());
+
+        // Map parameter index -> column in the free variable set
+        let column: HashMap<usize, usize> =
+            free.iter().enumerate().map(|(k, &var)| (var, k)).collect();
+
+        let mut entity_dof = HashMap::new();
+        for (i, entity) in self.entities().iter().enumerate() {
+            let vars = self.entity_variables(entity);
+            let rows: Vec<Vec<f64>> = vars
+                .iter()
+                .filter_map(|v| column.get(v))
+                .map(|&c| null_space.iter().map(|basis| basis[c]).collect())
+                .collect();
+            entity_dof.insert(EntityId(i), rank(&rows));
+        }
+
+        let dof = null_space.len();
+        let status = if !redundant.is_empty() {
+            ConstraintStatus::OverConstrained
+        } else if dof > 0 {
+            ConstraintStatus::UnderConstrained
+        } else {
+            ConstraintStatus::WellConstrained
+        };
+
+        Ok(DofReport {
+            status,
+            dof,
+            redundant,
+            entity_dof,
+        })
+    }
+
+    /// Parameter indices that define an entity's geometry
+    fn entity_variables(&self, entity: &Entity) -> Vec<usize> {
+        let point_vars = |id: EntityId| match self.entities().get(id.index()) {
+            Some(Entity::Point { offset }) => vec![*offset, *offset + 1],
+            _ => Vec::new(),
+        };
+        match *entity {
+            Entity::Point { offset } => vec![offset, offset + 1],
+            Entity::Line { start, end } => [point_vars(start), point_vars(end)].concat(),
+            Entity::Circle { center, radius } => {
+                let mut vars = point_vars(center);
+                vars.push(radius);
+                vars
+            }
+            Entity::Arc {
+                center, start, end, ..
+            } => [point_vars(center), point_vars(start), point_vars(end)].concat(),
+        }
+    }
+}
+
+/// Constraints whose residual rows are linearly dependent on earlier rows
+fn redundant_constraints(jac: &[Vec<f64>], owners: &[Option<usize>]) -> Vec<usize> {
+    let mut basis: Vec<Vec<f64>> = Vec::new();
+    let mut redundant = Vec::new();
+
+    for (row, owner) in jac.iter().zip(owners) {
+        let scale = row.iter().map(|x| x.abs()).fold(0.0, f64::max);
+        let mut v = row.clone();
+        for b in &basis {
+            let dot: f64 = v.iter().zip(b).map(|(x, y)| x * y).sum();
+            v.iter_mut().zip(b).for_each(|(x, y)| *x -= dot * y);
+        }
+        let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+
+        if norm <= RANK_TOLERANCE * scale.max(1.0) {
+            if let Some(index) = *owner {
+                if !redundant.contains(&index) {
+                    redundant.push(index);
+                }
+            }
+        } else {
+            v.iter_mut().for_each(|x| *x /= norm);
+            basis.push(v);
+        }
+    }
+
+    redundant
+}
+
+/// Reduced row echelon form; returns the pivot columns
+fn rref(matrix: &mut [Vec<f64>], cols: usize) -> Vec<usize> {
+    let scale = matrix
+        .iter()
+        .flatten()
+        .map(|x| x.abs())
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let mut pivots = Vec::new();
+    let mut row = 0;
+
+    for col in 0..cols {
+        if row >= matrix.len() {
+            break;
+        }
+        let best = (row..matrix.len())
+            .max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))
+            .unwrap();
+        if matrix[best][col].abs() <= RANK_TOLERANCE * scale {
+            continue;
+        }
+        matrix.swap(row, best);
+
+        let pivot = matrix[row][col];
+        matrix[row].iter_mut().for_each(|x| *x /= pivot);
+        let pivot_row = matrix[row].clone();
+        for (r, other) in matrix.iter_mut().enumerate() {
+            if r != row {
+                let factor = other[col];
+                if factor != 0.0 {
+                    other
+                        .iter_mut()
+                        .zip(&pivot_row)
+                        .for_each(|(x, p)| *x -= factor * p);
+                }
+            }
+        }
+
+        pivots.push(col);
+        row += 1;
+    }
+
+    pivots
+}
+
+fn rank(rows: &[Vec<f64>]) -> usize {
+    let cols = rows.first().map_or(0, |r| r.len());
+    let mut m = rows.to_vec();
+    rref(&mut m, cols).len()
+}
+
+/// Basis of the null space of `jac` (each vector has `cols` entries)
+fn null_space(jac: &[Vec<f64>], cols: usize) -> Vec<Vec<f64>> {
+    let mut m = jac.to_vec();
+    let pivots = rref(&mut m, cols);
+
+    (0..cols)
+        .filter(|c| !pivots.contains(c))
+        .map(|free_col| {
+            let mut v = vec![0.0; cols];
+            v[free_col] = 1.0;
+            for (row, &pivot_col) in pivots.iter().enumerate() {
+                v[pivot_col] = -m[row][free_col];
+            }
+            v
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::constraints::Constraint;
+    use truck_geometry::prelude::*;
+
+    #[test]
+    fn test_dof_of_constrained_line() {
+        let mut sys = ConstraintSystem::new();
+        let a = sys.add_point(Point2::new(0.0, 0.0));
+        let b = sys.add_point(Point2::new(4.0, 0.5));
+        let line = sys.add_line(a, b).unwrap();
+
+        let report = sys.analyze_dof().unwrap();
+        assert_eq!(report.dof, 4);
+        assert_eq!(report.status, ConstraintStatus::UnderConstrained);
+
+        sys.constrain(Constraint::Fixed(a)).unwrap();
+        sys.constrain(Constraint::Horizontal(line)).unwrap();
+        sys.solve().unwrap();
+        let report = sys.analyze_dof().unwrap();
+        assert_eq!(report.dof, 1);
+        assert!(report.is_fully_constrained(a));
+        assert_eq!(report.entity_dof(b), 1);
+
+        sys.constrain(Constraint::Length(line, 5.0)).unwrap();
+        sys.solve().unwrap();
+        let report = sys.analyze_dof().unwrap();
+        assert_eq!(report.status, ConstraintStatus::WellConstrained);
+        assert!(report.is_fully_constrained(line));
+    }
+
+    #[test]
+    fn test_redundant_constraint_detected() {
+        let mut sys = ConstraintSystem::new();
+        let a = sys.add_point(Point2::new(0.0, 0.0));
+        let b = sys.add_point(Point2::new(4.0, 0.0));
+        let line = sys.add_line(a, b).unwrap();
+        sys.constrain(Constraint::Horizontal(line)).unwrap();
+        sys.constrain(Constraint::Length(line, 4.0)).unwrap();
+        sys.constrain(Constraint::Horizontal(line)).unwrap();
+
+        let report = sys.analyze_dof().unwrap();
+        assert_eq!(report.status, ConstraintStatus::OverConstrained);
+        assert_eq!(report.redundant, vec![2]);
+    }
+}
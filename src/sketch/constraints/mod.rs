@@ -0,0 +1,579 @@
+pub mod dof;
+pub mod solver;
+
+pub use dof::{ConstraintStatus, DofReport};
+pub use solver::SolveReport;
+
+use crate::sketch::constants::*;
+use crate::sketch::error::*;
+use crate::sketch::primitives::{Arc2D, Circle2D, Curve2D, Line2D, SketchCurve2D};
+use std::collections::HashSet;
+use truck_geometry::prelude::*;
+
+/// Handle to an entity in a [`ConstraintSystem`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntityId(usize);
+
+impl EntityId {
+    /// Index of the entity in the system
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Geometric entity whose coordinates are solver variables
+#[derive(Clone, Debug)]
+pub enum Entity {
+    /// Free point; `x` lives at `params[offset]`, `y` at `params[offset + 1]`
+    Point {
+        offset: usize,
+    },
+    Line {
+        start: EntityId,
+        end: EntityId,
+    },
+    Circle {
+        center: EntityId,
+        /// Index of the radius in the parameter vector
+        radius: usize,
+    },
+    /// Arc whose radius is implied by `|start - center| == |end - center|`
+    Arc {
+        center: EntityId,
+        start: EntityId,
+        end: EntityId,
+        ccw: bool,
+    },
+}
+
+/// Geometric constraint between entities
+#[derive(Clone, Debug)]
+pub enum Constraint {
+    /// Two points coincide
+    Coincident(EntityId, EntityId),
+    /// Point is locked at its current position
+    Fixed(EntityId),
+    /// Line is parallel to the x axis
+    Horizontal(EntityId),
+    /// Line is parallel to the y axis
+    Vertical(EntityId),
+    /// Two lines are parallel
+    Parallel(EntityId, EntityId),
+    /// Two lines are perpendicular
+    Perpendicular(EntityId, EntityId),
+    /// Line is tangent to a circle/arc, or two circles/arcs are tangent
+    Tangent(EntityId, EntityId),
+    /// Two circles/arcs have the same radius
+    EqualRadius(EntityId, EntityId),
+    /// Two lines have the same length
+    EqualLength(EntityId, EntityId),
+    /// Distance between two points, or from a point to a line
+    Distance(EntityId, EntityId, f64),
+    /// Length of a line
+    Length(EntityId, f64),
+    /// Radius of a circle/arc
+    Radius(EntityId, f64),
+    /// Signed angle (radians, CCW) from the first line to the second
+    Angle(EntityId, EntityId, f64),
+    /// Point lies on a line (infinite extension) or circle/arc
+    PointOn(EntityId, EntityId),
+}
+
+impl Constraint {
+    /// Entities referenced by this constraint
+    pub fn entities(&self) -> Vec<EntityId> {
+        match *self {
+            Constraint::Fixed(a)
+            | Constraint::Horizontal(a)
+            | Constraint::Vertical(a)
+            | Constraint::Length(a, _)
+            | Constraint::Radius(a, _) => vec![a],
+            Constraint::Coincident(a, b)
+            | Constraint::Parallel(a, b)
+            | Constraint::Perpendicular(a, b)
+            | Constraint::Tangent(a, b)
+            | Constraint::EqualRadius(a, b)
+            | Constraint::EqualLength(a, b)
+            | Constraint::Distance(a, b, _)
+            | Constraint::Angle(a, b, _)
+            | Constraint::PointOn(a, b) => vec![a, b],
+        }
+    }
+}
+
+/// A set of sketch entities plus constraints, solved numerically
+///
+/// Points and radii are stored in a flat parameter vector that the solver
+/// adjusts until every constraint residual vanishes.
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintSystem {
+    params: Vec<f64>,
+    entities: Vec<Entity>,
+    constraints: Vec<Constraint>,
+    construction: HashSet<EntityId>,
+}
+
+impl ConstraintSystem {
+    /// Create an empty system
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a system from sketch curves, sharing coincident endpoints.
+    ///
+    /// Returns the system and the entity created for each input curve.
+    pub fn from_curves(curves: &[Curve2D]) -> SketchResult<(Self, Vec<EntityId>)> {
+        let mut system = Self::new();
+        let mut ids = Vec::with_capacity(curves.len());
+
+        for curve in curves {
+            let id = match curve {
+                Curve2D::Line(line) => {
+                    let start = system.find_or_add_point(line.start());
+                    let end = system.find_or_add_point(line.end());
+                    system.add_line(start, end)?
+                }
+                Curve2D::Arc(arc) => {
+                    let center = system.add_point(arc.center());
+                    let start = system.find_or_add_point(arc.start());
+                    let end = system.find_or_add_point(arc.end());
+                    system.add_arc(center, start, end, arc.is_ccw())?
+                }
+                Curve2D::Circle(circle) => {
+                    let center = system.add_point(circle.center());
+                    system.add_circle(center, circle.radius())?
+                }
+                Curve2D::BSpline(_) => {
+                    return Err(SketchError::InvalidConstraint(
+                        "B-splines cannot be constrained".to_string(),
+                    ))
+                }
+            };
+            ids.push(id);
+        }
+
+        Ok((system, ids))
+    }
+
+    /// Add a free point
+    pub fn add_point(&mut self, p: Point2) -> EntityId {
+        let offset = self.params.len();
+        self.params.push(p.x);
+        self.params.push(p.y);
+        self.push_entity(Entity::Point { offset })
+    }
+
+    /// Add a line between two points
+    pub fn add_line(&mut self, start: EntityId, end: EntityId) -> SketchResult<EntityId> {
+        self.expect_point(start)?;
+        self.expect_point(end)?;
+        Ok(self.push_entity(Entity::Line { start, end }))
+    }
+
+    /// Add a circle around a center point
+    pub fn add_circle(&mut self, center: EntityId, radius: f64) -> SketchResult<EntityId> {
+        self.expect_point(center)?;
+        if radius <= DEGENERATE_TOLERANCE {
+            return Err(SketchError::InvalidCircleRadius(radius));
+        }
+        let radius_index = self.params.len();
+        self.params.push(radius);
+        Ok(self.push_entity(Entity::Circle {
+            center,
+            radius: radius_index,
+        }))
+    }
+
+    /// Add an arc from `start` to `end` around `center`
+    pub fn add_arc(
+        &mut self,
+        center: EntityId,
+        start: EntityId,
+        end: EntityId,
+        ccw: bool,
+    ) -> SketchResult<EntityId> {
+        self.expect_point(center)?;
+        self.expect_point(start)?;
+        self.expect_point(end)?;
+        Ok(self.push_entity(Entity::Arc {
+            center,
+            start,
+            end,
+            ccw,
+        }))
+    }
+
+    /// Add a constraint on existing entities of the kinds it applies to
+    pub fn constrain(&mut self, constraint: Constraint) -> SketchResult<()> {
+        for id in constraint.entities() {
+            self.entity(id)?;
+        }
+        self.check_kinds(&constraint)?;
+        self.constraints.push(constraint);
+        Ok(())
+    }
+
+    /// Refuse constraints on entities they do not apply to, which the solver
+    /// would reject later or, for `Fixed`, ignore
+    fn check_kinds(&self, constraint: &Constraint) -> SketchResult<()> {
+        let point = |id| self.is_point(id);
+        let line = |id| self.is_line(id);
+        let round = |id| !point(id) && !line(id);
+        let (applies, needs) = match *constraint {
+            Constraint::Coincident(a, b) => (point(a) && point(b), "two points"),
+            Constraint::Fixed(a) => (point(a), "a point"),
+            Constraint::Horizontal(a) | Constraint::Vertical(a) | Constraint::Length(a, _) => {
+                (line(a), "a line")
+            }
+            Constraint::Parallel(a, b)
+            | Constraint::Perpendicular(a, b)
+            | Constraint::EqualLength(a, b)
+            | Constraint::Angle(a, b, _) => (line(a) && line(b), "two lines"),
+            Constraint::Tangent(a, b) => (
+                !point(a) && !point(b) && (round(a) || round(b)),
+                "a circle or arc and a line, circle or arc",
+            ),
+            Constraint::EqualRadius(a, b) => (round(a) && round(b), "two circles or arcs"),
+            Constraint::Radius(a, _) => (round(a), "a circle or arc"),
+            Constraint::Distance(a, b, _) => (
+                point(a) && point(b) || point(a) && line(b) || line(a) && point(b),
+                "two points or a point and a line",
+            ),
+            Constraint::PointOn(a, b) => {
+                (point(a) && !point(b), "a point and a line, circle or arc")
+            }
+        };
+        if applies {
+            Ok(())
+        } else {
+            let ids: Vec<String> = constraint
+                .entities()
+                .iter()
+                .map(|id| id.0.to_string())
+                .collect();
+            Err(SketchError::InvalidConstraint(format!(
+                "constraint on entities {} needs {}",
+                ids.join(", "),
+                needs
+            )))
+        }
+    }
+
+    /// Remove all constraints referencing an entity
+    pub fn remove_constraints_on(&mut self, id: EntityId) {
+        self.constraints.retain(|c| !c.entities().contains(&id));
+    }
+
+    /// All entities
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// All constraints
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Look up an entity
+    pub fn entity(&self, id: EntityId) -> SketchResult<&Entity> {
+        self.entities
+            .get(id.0)
+            .ok_or_else(|| SketchError::InvalidConstraint(format!("unknown entity {}", id.0)))
+    }
+
+    /// Current position of a point entity
+    pub fn point(&self, id: EntityId) -> SketchResult<Point2> {
+        let offset = self.expect_point(id)?;
+        Ok(Point2::new(self.params[offset], self.params[offset + 1]))
+    }
+
+    /// Move a point entity (e.g. when dragging in an editor)
+    pub fn set_point(&mut self, id: EntityId, p: Point2) -> SketchResult<()> {
+        let offset = self.expect_point(id)?;
+        self.params[offset] = p.x;
+        self.params[offset + 1] = p.y;
+        Ok(())
+    }
+
+    /// Current radius of a circle or arc entity
+    pub fn radius(&self, id: EntityId) -> SketchResult<f64> {
+        self.radius_in(&self.params, id)
+    }
+
+    /// Convert an entity to a sketch curve (`None` for points)
+    pub fn curve(&self, id: EntityId) -> SketchResult<Option<Curve2D>> {
+        Ok(match *self.entity(id)? {
+            Entity::Point { .. } => None,
+            Entity::Line { start, end } => Some(Curve2D::Line(Line2D::new(
+                self.point(start)?,
+                self.point(end)?,
+            )?)),
+            Entity::Circle { center, radius } => Some(Curve2D::Circle(Circle2D::new(
+                self.point(center)?,
+                self.params[radius],
+            )?)),
+            Entity::Arc {
+                center,
+                start,
+                end,
+                ccw,
+            } => Some(Curve2D::Arc(Arc2D::from_start_end_center(
+                self.point(start)?,
+                self.point(end)?,
+                self.point(center)?,
+                ccw,
+            )?)),
+        })
+    }
+
+    /// Mark or unmark an entity as construction geometry
+    pub fn set_construction(&mut self, id: EntityId, construction: bool) -> SketchResult<()> {
+        self.entity(id)?;
+        if construction {
+            self.construction.insert(id);
+        } else {
+            self.construction.remove(&id);
+        }
+        Ok(())
+    }
+
+    /// Check if an entity is construction geometry
+    pub fn is_construction(&self, id: EntityId) -> bool {
+        self.construction.contains(&id)
+    }
+
+    /// Convert all profile (non-construction) curve entities to sketch curves,
+    /// in creation order
+    pub fn to_curves(&self) -> SketchResult<Vec<Curve2D>> {
+        self.collect_curves(false)
+    }
+
+    /// Convert construction curve entities to sketch curves, in creation order
+    pub fn construction_curves(&self) -> SketchResult<Vec<Curve2D>> {
+        self.collect_curves(true)
+    }
+
+    fn collect_curves(&self, construction: bool) -> SketchResult<Vec<Curve2D>> {
+        let mut curves = Vec::new();
+        for i in 0..self.entities.len() {
+            if self.is_construction(EntityId(i)) != construction {
+                continue;
+            }
+            if let Some(curve) = self.curve(EntityId(i))? {
+                curves.push(curve);
+            }
+        }
+        Ok(curves)
+    }
+
+    /// Solve all constraints, moving geometry as little as possible
+    pub fn solve(&mut self) -> SketchResult<SolveReport> {
+        solver::solve(self)
+    }
+
+    // Internal helpers
+
+    fn push_entity(&mut self, entity: Entity) -> EntityId {
+        self.entities.push(entity);
+        EntityId(self.entities.len() - 1)
+    }
+
+    fn find_or_add_point(&mut self, p: Point2) -> EntityId {
+        for (i, entity) in self.entities.iter().enumerate() {
+            if let Entity::Point { offset } = *entity {
+                let q = Point2::new(self.params[offset], self.params[offset + 1]);
+                if (q - p).magnitude() < HEAL_TOLERANCE {
+                    return EntityId(i);
+                }
+            }
+        }
+        self.add_point(p)
+    }
+
+    fn expect_point(&self, id: EntityId) -> SketchResult<usize> {
+        match self.entity(id)? {
+            Entity::Point { offset } => Ok(*offset),
+            _ => Err(SketchError::InvalidConstraint(format!(
+                "entity {} is not a point",
+                id.0
+            ))),
+        }
+    }
+
+    pub(crate) fn params(&self) -> &[f64] {
+        &self.params
+    }
+
+    pub(crate) fn set_params(&mut self, params: Vec<f64>) {
+        self.params = params;
+    }
+
+    pub(crate) fn point_in(&self, params: &[f64], id: EntityId) -> SketchResult<Point2> {
+        let offset = self.expect_point(id)?;
+        Ok(Point2::new(params[offset], params[offset + 1]))
+    }
+
+    pub(crate) fn radius_in(&self, params: &[f64], id: EntityId) -> SketchResult<f64> {
+        match *self.entity(id)? {
+            Entity::Circle { radius, .. } => Ok(params[radius]),
+            Entity::Arc { center, start, .. } => {
+                Ok((self.point_in(params, start)? - self.point_in(params, center)?).magnitude())
+            }
+            _ => Err(SketchError::InvalidConstraint(format!(
+                "entity {} has no radius",
+                id.0
+            ))),
+        }
+    }
+
+    pub(crate) fn center_in(&self, params: &[f64], id: EntityId) -> SketchResult<Point2> {
+        match *self.entity(id)? {
+            Entity::Circle { center, .. } | Entity::Arc { center, .. } => {
+                self.point_in(params, center)
+            }
+            _ => Err(SketchError::InvalidConstraint(format!(
+                "entity {} has no center",
+                id.0
+            ))),
+        }
+    }
+
+    pub(crate) fn line_in(&self, params: &[f64], id: EntityId) -> SketchResult<(Point2, Point2)> {
+        match *self.entity(id)? {
+            Entity::Line { start, end } => {
+                Ok((self.point_in(params, start)?, self.point_in(params, end)?))
+            }
+            _ => Err(SketchError::InvalidConstraint(format!(
+                "entity {} is not a line",
+                id.0
+            ))),
+        }
+    }
+
+    pub(crate) fn is_line(&self, id: EntityId) -> bool {
+        matches!(self.entities.get(id.0), Some(Entity::Line { .. }))
+    }
+
+    pub(crate) fn is_point(&self, id: EntityId) -> bool {
+        matches!(self.entities.get(id.0), Some(Entity::Point { .. }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::SketchBuilder;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_square_from_sloppy_quad() {
+        let quad = SketchBuilder::new()
+            .move_to(Point2::new(0.0, 0.0))
+            .line_to(Point2::new(10.3, 0.4))
+            .unwrap()
+            .line_to(Point2::new(9.8, 9.5))
+            .unwrap()
+            .line_to(Point2::new(-0.2, 10.1))
+            .unwrap()
+            .close()
+            .unwrap();
+
+        let (mut sys, ids) = ConstraintSystem::from_curves(quad.curves()).unwrap();
+        let first = match sys.entity(ids[0]).unwrap() {
+            Entity::Line { start, .. } => *start,
+            _ => unreachable!(),
+        };
+        sys.constrain(Constraint::Fixed(first)).unwrap();
+        sys.constrain(Constraint::Horizontal(ids[0])).unwrap();
+        sys.constrain(Constraint::Vertical(ids[1])).unwrap();
+        sys.constrain(Constraint::Horizontal(ids[2])).unwrap();
+        sys.constrain(Constraint::Vertical(ids[3])).unwrap();
+        sys.constrain(Constraint::Length(ids[0], 10.0)).unwrap();
+        sys.constrain(Constraint::EqualLength(ids[0], ids[1]))
+            .unwrap();
+
+        let report = sys.solve().unwrap();
+        assert!(report.residual < 1e-9);
+
+        let curves = sys.to_curves().unwrap();
+        let lengths: Vec<f64> = curves.iter().map(|c| c.length()).collect();
+        for len in lengths {
+            assert!((len - 10.0).abs() < 1e-6);
+        }
+        assert!((sys.point(first).unwrap() - Point2::origin()).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn test_tangent_and_angle() {
+        let mut sys = ConstraintSystem::new();
+        let a = sys.add_point(Point2::new(-5.0, 3.0));
+        let b = sys.add_point(Point2::new(5.0, 2.0));
+        let line = sys.add_line(a, b).unwrap();
+        let c = sys.add_point(Point2::new(0.0, 0.0));
+        let circle = sys.add_circle(c, 1.5).unwrap();
+        sys.constrain(Constraint::Fixed(c)).unwrap();
+        sys.constrain(Constraint::Radius(circle, 2.0)).unwrap();
+        sys.constrain(Constraint::Horizontal(line)).unwrap();
+        sys.constrain(Constraint::Tangent(line, circle)).unwrap();
+
+        sys.solve().unwrap();
+        let (p, q) = sys.line_in(sys.params(), line).unwrap();
+        assert!((p.y - 2.0).abs() < 1e-6 && (q.y - 2.0).abs() < 1e-6);
+
+        let d = sys.add_point(Point2::new(0.0, 0.0));
+        let e = sys.add_point(Point2::new(3.0, 1.0));
+        let other = sys.add_line(d, e).unwrap();
+        sys.constrain(Constraint::Angle(line, other, FRAC_PI_2))
+            .unwrap();
+        sys.solve().unwrap();
+        let (d, e) = sys.line_in(sys.params(), other).unwrap();
+        assert!((e.x - d.x).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_constraints_check_entity_kinds() {
+        let mut sys = ConstraintSystem::new();
+        let a = sys.add_point(Point2::new(0.0, 0.0));
+        let b = sys.add_point(Point2::new(4.0, 1.0));
+        let line = sys.add_line(a, b).unwrap();
+        let circle = sys.add_circle(a, 2.0).unwrap();
+
+        // Fixed used to be dropped silently for anything but a point
+        for constraint in [
+            Constraint::Fixed(line),
+            Constraint::Fixed(circle),
+            Constraint::Horizontal(a),
+            Constraint::Radius(line, 1.0),
+            Constraint::Coincident(a, line),
+            Constraint::Parallel(line, circle),
+            Constraint::Tangent(line, line),
+            Constraint::Distance(line, circle, 1.0),
+            Constraint::PointOn(a, b),
+        ] {
+            assert!(matches!(
+                sys.constrain(constraint),
+                Err(SketchError::InvalidConstraint(_))
+            ));
+        }
+        assert!(sys.constraints().is_empty());
+
+        sys.constrain(Constraint::Fixed(a)).unwrap();
+        sys.constrain(Constraint::Tangent(circle, line)).unwrap();
+        sys.constrain(Constraint::Distance(line, b, 0.0)).unwrap();
+        sys.constrain(Constraint::PointOn(b, circle)).unwrap();
+        assert_eq!(sys.constraints().len(), 4);
+    }
+
+    #[test]
+    fn test_inconsistent_constraints_fail() {
+        let mut sys = ConstraintSystem::new();
+        let a = sys.add_point(Point2::new(0.0, 0.0));
+        let b = sys.add_point(Point2::new(1.0, 0.0));
+        sys.constrain(Constraint::Distance(a, b, 1.0)).unwrap();
+        sys.constrain(Constraint::Distance(a, b, 2.0)).unwrap();
+        assert!(matches!(
+            sys.solve(),
+            Err(SketchError::ConstraintSolveFailed { .. })
+        ));
+    }
+}
@@ -0,0 +1,366 @@
+use super::{Constraint, ConstraintSystem, Entity, EntityId};
+use crate::sketch::error::*;
+use std::collections::HashSet;
+use std::f64::consts::{PI, TAU};
+use truck_geometry::prelude::*;
+
+/// Residual norm below which a system counts as solved
+pub const SOLVE_TOLERANCE: f64 = 1e-9;
+
+/// Iteration cap for the Levenberg-Marquardt loop
+const MAX_ITERATIONS: usize = 200;
+
+/// Outcome of a successful solve
+#[derive(Clone, Debug)]
+pub struct SolveReport {
+    /// Number of iterations taken
+    pub iterations: usize,
+    /// Final residual norm
+    pub residual: f64,
+}
+
+/// Solve the system in place with damped minimum-norm Gauss-Newton steps.
+///
+/// Each step solves `(J Jᵀ + λI) y = r` and moves by `-Jᵀ y`, so geometry
+/// that is not pinned down by constraints stays where the user put it.
+pub(crate) fn solve(system: &mut ConstraintSystem) -> SketchResult<SolveReport> {
+    let free = free_variables(system);
+    let mut params = system.params().to_vec();
+    let mut r = residuals(system, &params)?;
+    let mut norm = norm2(&r);
+    let mut lambda = 1e-6;
+    let mut iterations = 0;
+
+    while norm > SOLVE_TOLERANCE && iterations < MAX_ITERATIONS && !free.is_empty() {
+        iterations += 1;
+        let jac = jacobian(system, &params, &free)?;
+        let m = r.len();
+
+        // Normal matrix J Jᵀ (m × m)
+        let mut a = vec![vec![0.0; m]; m];
+        for i in 0..m {
+            for j in i..m {
+                let dot: f64 = jac[i].iter().zip(&jac[j]).map(|(x, y)| x * y).sum();
+                a[i][j] = dot;
+                a[j][i] = dot;
+            }
+        }
+
+        let mut improved = false;
+        while lambda < 1e12 {
+            let mut damped = a.clone();
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += lambda;
+            }
+            let Some(y) = solve_linear(damped, r.clone()) else {
+                lambda *= 10.0;
+                continue;
+            };
+
+            let mut trial = params.clone();
+            for (k, &var) in free.iter().enumerate() {
+                let step: f64 = (0..m).map(|i| jac[i][k] * y[i]).sum();
+                trial[var] -= step;
+            }
+
+            let trial_r = residuals(system, &trial)?;
+            let trial_norm = norm2(&trial_r);
+            if trial_norm < norm {
+                params = trial;
+                r = trial_r;
+                norm = trial_norm;
+                lambda = (lambda / 10.0).max(1e-12);
+                improved = true;
+                break;
+            }
+            lambda *= 10.0;
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    if norm > SOLVE_TOLERANCE {
+        return Err(SketchError::ConstraintSolveFailed { residual: norm });
+    }
+
+    system.set_params(params);
+    Ok(SolveReport {
+        iterations,
+        residual: norm,
+    })
+}
+
+/// Parameter indices the solver may change (everything not `Fixed`)
+pub(crate) fn free_variables(system: &ConstraintSystem) -> Vec<usize> {
+    let mut fixed = HashSet::new();
+    for constraint in system.constraints() {
+        if let Constraint::Fixed(id) = *constraint {
+            if let Some(Entity::Point { offset }) = system.entities().get(id.index()) {
+                fixed.insert(*offset);
+                fixed.insert(*offset + 1);
+            }
+        }
+    }
+    (0..system.params().len())
+        .filter(|i| !fixed.contains(i))
+        .collect()
+}
+
+/// Numeric Jacobian of the residuals with respect to the free variables
+pub(crate) fn jacobian(
+    system: &ConstraintSystem,
+    params: &[f64],
+    free: &[usize],
+) -> SketchResult<Vec<Vec<f64>>> {
+    let m = residuals(system, params)?.len();
+    let mut jac = vec![vec![0.0; free.len()]; m];
+    let mut probe = params.to_vec();
+
+    for (k, &var) in free.iter().enumerate() {
+        let h = 1e-7 * params[var].abs().max(1.0);
+        probe[var] = params[var] + h;
+        let plus = residuals(system, &probe)?;
+        probe[var] = params[var] - h;
+        let minus = residuals(system, &probe)?;
+        probe[var] = params[var];
+
+        for i in 0..m {
+            jac[i][k] = (plus[i] - minus[i]) / (2.0 * h);
+        }
+    }
+
+    Ok(jac)
+}
+
+/// Residual vector: implicit arc conditions followed by each constraint
+pub(crate) fn residuals(system: &ConstraintSystem, params: &[f64]) -> SketchResult<Vec<f64>> {
+    let mut r = Vec::new();
+
+    for entity in system.entities() {
+        if let Entity::Arc {
+            center, start, end, ..
+        } = *entity
+        {
+            let c = system.point_in(params, center)?;
+            let r0 = (system.point_in(params, start)? - c).magnitude();
+            let r1 = (system.point_in(params, end)? - c).magnitude();
+            r.push(r0 - r1);
+        }
+    }
+
+    for constraint in system.constraints() {
+        constraint_residuals(system, params, constraint, &mut r)?;
+    }
+
+    Ok(r)
+}
+
+/// For each residual row, the index of the constraint that produced it
+/// (`None` for implicit arc conditions)
+pub(crate) fn residual_owners(system: &ConstraintSystem) -> SketchResult<Vec<Option<usize>>> {
+    let params = system.params();
+    let arcs = system
+        .entities()
+        .iter()
+        .filter(|e| matches!(e, Entity::Arc { .. }))
+        .count();
+    let mut owners = vec![None; arcs];
+
+    for (i, constraint) in system.constraints().iter().enumerate() {
+        let mut rows = Vec::new();
+        constraint_residuals(system, params, constraint, &mut rows)?;
+        owners.extend(std::iter::repeat_n(Some(i), rows.len()));
+    }
+
+    Ok(owners)
+}
+
+fn constraint_residuals(
+    system: &ConstraintSystem,
+    params: &[f64],
+    constraint: &Constraint,
+    r: &mut Vec<f64>,
+) -> SketchResult<()> {
+    match *constraint {
+        Constraint::Coincident(a, b) => {
+            let d = system.point_in(params, b)? - system.point_in(params, a)?;
+            r.push(d.x);
+            r.push(d.y);
+        }
+        Constraint::Fixed(_) => {}
+        Constraint::Horizontal(l) => {
+            let (p, q) = system.line_in(params, l)?;
+            r.push(q.y - p.y);
+        }
+        Constraint::Vertical(l) => {
+            let (p, q) = system.line_in(params, l)?;
+            r.push(q.x - p.x);
+        }
+        Constraint::Parallel(a, b) => {
+            let (u, v) = (direction(system, params, a)?, direction(system, params, b)?);
+            r.push(cross(u, v));
+        }
+        Constraint::Perpendicular(a, b) => {
+            let (u, v) = (direction(system, params, a)?, direction(system, params, b)?);
+            r.push(u.dot(v));
+        }
+        Constraint::Tangent(a, b) => {
+            r.push(tangent_residual(system, params, a, b)?);
+        }
+        Constraint::EqualRadius(a, b) => {
+            r.push(system.radius_in(params, a)? - system.radius_in(params, b)?);
+        }
+        Constraint::EqualLength(a, b) => {
+            r.push(line_length(system, params, a)? - line_length(system, params, b)?);
+        }
+        Constraint::Distance(a, b, d) => {
+            let dist = if system.is_point(a) && system.is_point(b) {
+                (system.point_in(params, b)? - system.point_in(params, a)?).magnitude()
+            } else if system.is_point(a) && system.is_line(b) {
+                point_line_distance(system, params, a, b)?.abs()
+            } else if system.is_line(a) && system.is_point(b) {
+                point_line_distance(system, params, b, a)?.abs()
+            } else {
+                return Err(SketchError::InvalidConstraint(
+                    "distance needs two points or a point and a line".to_string(),
+                ));
+            };
+            r.push(dist - d);
+        }
+        Constraint::Length(l, d) => {
+            r.push(line_length(system, params, l)? - d);
+        }
+        Constraint::Radius(c, radius) => {
+            r.push(system.radius_in(params, c)? - radius);
+        }
+        Constraint::Angle(a, b, angle) => {
+            let (u, v) = (direction(system, params, a)?, direction(system, params, b)?);
+            let actual = cross(u, v).atan2(u.dot(v));
+            r.push(wrap_angle(actual - angle));
+        }
+        Constraint::PointOn(p, e) => {
+            if system.is_line(e) {
+                r.push(point_line_distance(system, params, p, e)?);
+            } else {
+                let c = system.center_in(params, e)?;
+                let radius = system.radius_in(params, e)?;
+                r.push((system.point_in(params, p)? - c).magnitude() - radius);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn tangent_residual(
+    system: &ConstraintSystem,
+    params: &[f64],
+    a: EntityId,
+    b: EntityId,
+) -> SketchResult<f64> {
+    match (system.is_line(a), system.is_line(b)) {
+        (true, true) => Err(SketchError::InvalidConstraint(
+            "tangency needs at least one circle or arc".to_string(),
+        )),
+        (true, false) | (false, true) => {
+            let (line, round) = if system.is_line(a) { (a, b) } else { (b, a) };
+            let (p, q) = system.line_in(params, line)?;
+            let c = system.center_in(params, round)?;
+            let radius = system.radius_in(params, round)?;
+            let dir = safe_normalize(q - p);
+            Ok(cross(dir, c - p).abs() - radius)
+        }
+        (false, false) => {
+            let d = (system.center_in(params, b)? - system.center_in(params, a)?).magnitude();
+            let (ra, rb) = (system.radius_in(params, a)?, system.radius_in(params, b)?);
+            let external = d - (ra + rb);
+            let internal = d - (ra - rb).abs();
+            Ok(if external.abs() < internal.abs() {
+                external
+            } else {
+                internal
+            })
+        }
+    }
+}
+
+fn direction(system: &ConstraintSystem, params: &[f64], l: EntityId) -> SketchResult<Vector2> {
+    let (p, q) = system.line_in(params, l)?;
+    Ok(safe_normalize(q - p))
+}
+
+fn line_length(system: &ConstraintSystem, params: &[f64], l: EntityId) -> SketchResult<f64> {
+    let (p, q) = system.line_in(params, l)?;
+    Ok((q - p).magnitude())
+}
+
+/// Signed distance from a point to the infinite extension of a line
+fn point_line_distance(
+    system: &ConstraintSystem,
+    params: &[f64],
+    point: EntityId,
+    line: EntityId,
+) -> SketchResult<f64> {
+    let (p, q) = system.line_in(params, line)?;
+    let x = system.point_in(params, point)?;
+    Ok(cross(safe_normalize(q - p), x - p))
+}
+
+fn safe_normalize(v: Vector2) -> Vector2 {
+    let len = v.magnitude();
+    if len < 1e-300 {
+        Vector2::new(0.0, 0.0)
+    } else {
+        v / len
+    }
+}
+
+fn cross(u: Vector2, v: Vector2) -> f64 {
+    u.x * v.y - u.y * v.x
+}
+
+fn wrap_angle(a: f64) -> f64 {
+    let w = (a + PI).rem_euclid(TAU) - PI;
+    if w <= -PI {
+        w + TAU
+    } else {
+        w
+    }
+}
+
+fn norm2(r: &[f64]) -> f64 {
+    r.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Gaussian elimination with partial pivoting; `None` if singular
+fn solve_linear(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-14 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let pivot_row = a[col].clone();
+        for row in (col + 1)..n {
+            let factor = a[row][col] / pivot_row[col];
+            if factor == 0.0 {
+                continue;
+            }
+            for (k, value) in a[row].iter_mut().enumerate().skip(col) {
+                *value -= factor * pivot_row[k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = ((row + 1)..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
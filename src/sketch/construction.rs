@@ -0,0 +1,151 @@
+use crate::sketch::constraints::{ConstraintSystem, EntityId};
+use crate::sketch::error::*;
+use crate::sketch::primitives::{Circle2D, Curve2D, Line2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::FRAC_PI_2;
+use truck_geometry::prelude::*;
+
+/// Reference geometry (centerlines, reference points and circles).
+///
+/// Construction geometry takes part in constraints and snapping but is never
+/// turned into wires, faces, or solids.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Construction {
+    Point(Point2),
+    Line(Line2D),
+    Circle(Circle2D),
+}
+
+impl Construction {
+    /// Points an editor can snap to (ends, midpoints, centers, quadrants)
+    pub fn snap_points(&self) -> Vec<Point2> {
+        match self {
+            Construction::Point(p) => vec![*p],
+            Construction::Line(line) => vec![line.start(), line.end(), line.midpoint()],
+            Construction::Circle(circle) => {
+                let mut points = vec![circle.center()];
+                points.extend((0..4).map(|i| circle.point_at_angle(i as f64 * FRAC_PI_2)));
+                points
+            }
+        }
+    }
+
+    /// Add this geometry to a constraint system as construction entities
+    pub fn add_to(&self, system: &mut ConstraintSystem) -> SketchResult<EntityId> {
+        let id = match self {
+            Construction::Point(p) => system.add_point(*p),
+            Construction::Line(line) => {
+                let start = system.add_point(line.start());
+                let end = system.add_point(line.end());
+                system.add_line(start, end)?
+            }
+            Construction::Circle(circle) => {
+                let center = system.add_point(circle.center());
+                system.add_circle(center, circle.radius())?
+            }
+        };
+        system.set_construction(id, true)?;
+        Ok(id)
+    }
+}
+
+impl From<Point2> for Construction {
+    fn from(p: Point2) -> Self {
+        Construction::Point(p)
+    }
+}
+
+impl From<Line2D> for Construction {
+    fn from(line: Line2D) -> Self {
+        Construction::Line(line)
+    }
+}
+
+impl From<Circle2D> for Construction {
+    fn from(circle: Circle2D) -> Self {
+        Construction::Circle(circle)
+    }
+}
+
+impl Sketch {
+    /// Add construction geometry (not extruded)
+    pub fn add_construction(&mut self, geometry: impl Into<Construction>) {
+        self.construction.push(geometry.into());
+    }
+
+    /// Snap candidates from profile curves and construction geometry
+    pub fn snap_points(&self) -> Vec<Point2> {
+        let mut points = Vec::new();
+        for curve in self
+            .holes
+            .iter()
+            .chain(std::iter::once(&self.outer))
+            .flat_map(|l| l.curves())
+        {
+            points.push(curve.start());
+            match curve {
+                Curve2D::Line(line) => points.push(line.midpoint()),
+                Curve2D::Arc(arc) => points.push(arc.center()),
+                Curve2D::Circle(circle) => points.push(circle.center()),
+                Curve2D::BSpline(_) => points.push(curve.end()),
+            }
+        }
+        for geometry in &self.construction {
+            points.extend(geometry.snap_points());
+        }
+        points
+    }
+
+    /// Nearest snap candidate within `radius` of `p`
+    pub fn snap(&self, p: Point2, radius: f64) -> Option<Point2> {
+        self.snap_points()
+            .into_iter()
+            .map(|q| (q, (q - p).magnitude()))
+            .filter(|(_, d)| *d <= radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(q, _)| q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::constraints::Constraint;
+    use crate::sketch::{Plane, Shapes};
+
+    #[test]
+    fn test_construction_excluded_from_face() {
+        let mut sketch = Sketch::new(Shapes::rectangle(Point2::origin(), 10.0, 4.0).unwrap());
+        let centerline = Line2D::new(Point2::new(5.0, -1.0), Point2::new(5.0, 5.0)).unwrap();
+        sketch.add_construction(centerline);
+        sketch.add_construction(Circle2D::new(Point2::new(5.0, 2.0), 1.0).unwrap());
+
+        let face = sketch.to_truck_face(Plane::xy()).unwrap();
+        assert_eq!(face.boundaries().len(), 1);
+        assert_eq!(
+            sketch.snap(Point2::new(5.1, 2.9), 0.5),
+            Some(Point2::new(5.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn test_construction_in_constraints() {
+        let mut sys = ConstraintSystem::new();
+        let axis =
+            Construction::Line(Line2D::new(Point2::new(0.0, 0.0), Point2::new(0.2, 10.0)).unwrap())
+                .add_to(&mut sys)
+                .unwrap();
+        let a = sys.add_point(Point2::new(1.0, 0.0));
+        let b = sys.add_point(Point2::new(1.0, 5.0));
+        let edge = sys.add_line(a, b).unwrap();
+
+        sys.constrain(Constraint::Vertical(axis)).unwrap();
+        sys.constrain(Constraint::Parallel(axis, edge)).unwrap();
+        sys.solve().unwrap();
+
+        let curves = sys.to_curves().unwrap();
+        assert_eq!(curves.len(), 1);
+        assert_eq!(sys.construction_curves().unwrap().len(), 1);
+    }
+}
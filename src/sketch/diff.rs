@@ -0,0 +1,277 @@
+use crate::sketch::constants::POINT_TOLERANCE;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::primitives::Curve2D;
+use crate::sketch::Sketch;
+use std::fmt;
+use std::mem::discriminant;
+
+/// Which loop of a sketch a change refers to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopRef {
+    Outer,
+    Hole(usize),
+}
+
+impl fmt::Display for LoopRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopRef::Outer => write!(f, "outer"),
+            LoopRef::Hole(i) => write!(f, "hole {}", i),
+        }
+    }
+}
+
+/// A single curve-level change. Indices of removed/modified curves refer to
+/// the old sketch, indices of added curves to the new one.
+#[derive(Clone, Debug)]
+pub enum CurveChange {
+    Added {
+        loop_ref: LoopRef,
+        index: usize,
+        curve: Curve2D,
+    },
+    Removed {
+        loop_ref: LoopRef,
+        index: usize,
+        curve: Curve2D,
+    },
+    Modified {
+        loop_ref: LoopRef,
+        index: usize,
+        before: Curve2D,
+        after: Curve2D,
+    },
+}
+
+/// Structured difference between two sketches
+#[derive(Clone, Debug, Default)]
+pub struct SketchDiff {
+    /// Curve changes, ordered by loop and then by position
+    pub curves: Vec<CurveChange>,
+    /// Holes only present in the new sketch
+    pub holes_added: Vec<usize>,
+    /// Holes only present in the old sketch
+    pub holes_removed: Vec<usize>,
+    /// Indices of construction geometry that differs (added, removed or changed)
+    pub construction_changed: Vec<usize>,
+}
+
+impl SketchDiff {
+    /// True when the sketches are equal within tolerance
+    pub fn is_empty(&self) -> bool {
+        self.curves.is_empty()
+            && self.holes_added.is_empty()
+            && self.holes_removed.is_empty()
+            && self.construction_changed.is_empty()
+    }
+
+    /// Added curves
+    pub fn added(&self) -> impl Iterator<Item = &CurveChange> {
+        self.curves
+            .iter()
+            .filter(|c| matches!(c, CurveChange::Added { .. }))
+    }
+
+    /// Removed curves
+    pub fn removed(&self) -> impl Iterator<Item = &CurveChange> {
+        self.curves
+            .iter()
+            .filter(|c| matches!(c, CurveChange::Removed { .. }))
+    }
+
+    /// Modified curves
+    pub fn modified(&self) -> impl Iterator<Item = &CurveChange> {
+        self.curves
+            .iter()
+            .filter(|c| matches!(c, CurveChange::Modified { .. }))
+    }
+}
+
+impl fmt::Display for SketchDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no changes");
+        }
+        let mut parts = Vec::new();
+        for (label, count) in [
+            ("added", self.added().count()),
+            ("removed", self.removed().count()),
+            ("modified", self.modified().count()),
+        ] {
+            if count > 0 {
+                parts.push(format!("{} curve(s) {}", count, label));
+            }
+        }
+        if !self.holes_added.is_empty() {
+            parts.push(format!("{} hole(s) added", self.holes_added.len()));
+        }
+        if !self.holes_removed.is_empty() {
+            parts.push(format!("{} hole(s) removed", self.holes_removed.len()));
+        }
+        if !self.construction_changed.is_empty() {
+            parts.push(format!(
+                "{} construction item(s) changed",
+                self.construction_changed.len()
+            ));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl Sketch {
+    /// Change set turning `self` into `other`
+    ///
+    /// Curves are matched with a longest-common-subsequence pass; unmatched
+    /// curves of the same type at the same gap are reported as modified.
+    pub fn diff(&self, other: &Sketch) -> SketchDiff {
+        let mut diff = SketchDiff::default();
+        diff_loop(LoopRef::Outer, &self.outer, &other.outer, &mut diff.curves);
+
+        let shared = self.holes.len().min(other.holes.len());
+        for i in 0..shared {
+            diff_loop(
+                LoopRef::Hole(i),
+                &self.holes[i],
+                &other.holes[i],
+                &mut diff.curves,
+            );
+        }
+        for (i, hole) in self.holes.iter().enumerate().skip(shared) {
+            diff.holes_removed.push(i);
+            for (index, curve) in hole.curves().iter().enumerate() {
+                diff.curves.push(CurveChange::Removed {
+                    loop_ref: LoopRef::Hole(i),
+                    index,
+                    curve: curve.clone(),
+                });
+            }
+        }
+        for (i, hole) in other.holes.iter().enumerate().skip(shared) {
+            diff.holes_added.push(i);
+            for (index, curve) in hole.curves().iter().enumerate() {
+                diff.curves.push(CurveChange::Added {
+                    loop_ref: LoopRef::Hole(i),
+                    index,
+                    curve: curve.clone(),
+                });
+            }
+        }
+
+        let count = self.construction.len().max(other.construction.len());
+        diff.construction_changed = (0..count)
+            .filter(
+                |&i| match (self.construction.get(i), other.construction.get(i)) {
+                    (Some(a), Some(b)) => !a.approx_eq(b, POINT_TOLERANCE),
+                    _ => true,
+                },
+            )
+            .collect();
+
+        diff
+    }
+}
+
+fn diff_loop(loop_ref: LoopRef, old: &Loop2D, new: &Loop2D, out: &mut Vec<CurveChange>) {
+    let (a, b) = (old.curves(), new.curves());
+    let same = |i: usize, j: usize| a[i].approx_eq(&b[j], POINT_TOLERANCE);
+
+    // LCS table over suffixes
+    let mut table = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            table[i][j] = if same(i, j) {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let (mut gap_old, mut gap_new) = (Vec::new(), Vec::new());
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && same(i, j) {
+            flush_gap(loop_ref, a, b, &mut gap_old, &mut gap_new, out);
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || table[i][j + 1] >= table[i + 1][j]) {
+            gap_new.push(j);
+            j += 1;
+        } else {
+            gap_old.push(i);
+            i += 1;
+        }
+    }
+    flush_gap(loop_ref, a, b, &mut gap_old, &mut gap_new, out);
+}
+
+/// Emit changes for a run of unmatched curves between two matched ones
+fn flush_gap(
+    loop_ref: LoopRef,
+    a: &[Curve2D],
+    b: &[Curve2D],
+    gap_old: &mut Vec<usize>,
+    gap_new: &mut Vec<usize>,
+    out: &mut Vec<CurveChange>,
+) {
+    let mut new_left: Vec<usize> = std::mem::take(gap_new);
+    for i in std::mem::take(gap_old) {
+        let partner = new_left
+            .iter()
+            .position(|&j| discriminant(&a[i]) == discriminant(&b[j]));
+        match partner {
+            Some(k) => {
+                let j = new_left.remove(k);
+                out.push(CurveChange::Modified {
+                    loop_ref,
+                    index: i,
+                    before: a[i].clone(),
+                    after: b[j].clone(),
+                });
+            }
+            None => out.push(CurveChange::Removed {
+                loop_ref,
+                index: i,
+                curve: a[i].clone(),
+            }),
+        }
+    }
+    for j in new_left {
+        out.push(CurveChange::Added {
+            loop_ref,
+            index: j,
+            curve: b[j].clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::{Circle2D, Shapes};
+    use truck_geometry::prelude::*;
+
+    #[test]
+    fn test_diff_after_parameter_edit() {
+        let old = Sketch::new(Shapes::rectangle(Point2::origin(), 10.0, 5.0).unwrap());
+        assert!(old.diff(&old.clone()).is_empty());
+
+        // Changing the width moves the right edge and stretches top/bottom
+        let mut new = Sketch::new(Shapes::rectangle(Point2::origin(), 12.0, 5.0).unwrap());
+        let diff = old.diff(&new);
+        assert_eq!(diff.modified().count(), 3);
+        assert_eq!(diff.added().count(), 0);
+        assert_eq!(diff.removed().count(), 0);
+
+        new.add_hole(Shapes::circle(Point2::new(5.0, 2.5), 1.0).unwrap());
+        new.add_construction(Circle2D::new(Point2::new(5.0, 2.5), 2.0).unwrap());
+        let diff = old.diff(&new);
+        assert_eq!(diff.holes_added, vec![0]);
+        assert_eq!(diff.added().count(), 1);
+        assert_eq!(diff.construction_changed, vec![0]);
+        assert_eq!(
+            diff.to_string(),
+            "1 curve(s) added, 3 curve(s) modified, 1 hole(s) added, 1 construction item(s) changed"
+        );
+    }
+}
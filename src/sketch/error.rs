@@ -6,6 +6,15 @@ pub enum SketchError {
     #[error("Degenerate plane: x_dir and y_dir are collinear or zero-length")]
     DegeneratePlane,
 
+    #[error("Plane axes are not orthonormal")]
+    NonOrthonormalPlane,
+
+    #[error("Rotation axis has zero length")]
+    ZeroAxis,
+
+    #[error("Planes are not parallel")]
+    NonParallelPlanes,
+
     // Loop errors
     #[error("Loop is not closed: gap of {gap:.6} at curve index {index}")]
     OpenLoop { index: usize, gap: f64 },
@@ -13,6 +22,9 @@ pub enum SketchError {
     #[error("Loop has no curves")]
     EmptyLoop,
 
+    #[error("Curve chain is broken: gap of {gap:.6} after curve index {index}")]
+    BrokenChain { index: usize, gap: f64 },
+
     // Curve errors
     #[error("Degenerate curve: zero or near-zero length")]
     DegenerateCurve,
@@ -46,6 +58,91 @@ pub enum SketchError {
     #[error("Cannot close loop: need at least one curve")]
     CannotCloseEmpty,
 
+    // Parameter errors
+    #[error("Invalid expression: {0}")]
+    ExpressionParse(String),
+
+    #[error("Unknown parameter: {0}")]
+    UnknownParameter(String),
+
+    // Layout errors
+    #[error("Text needs {required:.3} units of path but only {available:.3} are available")]
+    TextExceedsPath { required: f64, available: f64 },
+
+    // Extrusion errors
+    #[error("Invalid extrusion depth: total must be positive, got {0}")]
+    InvalidExtrudeDepth(f64),
+    #[error("Invalid section count: need at least 1, got {0}")]
+    InvalidSectionCount(usize),
+    #[error("Invalid wall thickness: must be positive, got {0}")]
+    InvalidThickness(f64),
+
+    // Offset errors
+    #[error("Offset failed: {0}")]
+    OffsetFailed(String),
+
+    // Revolve errors
+    #[error("Invalid revolve: {0}")]
+    InvalidRevolve(String),
+
+    // Loft errors
+    #[error("Incompatible profiles: {0}")]
+    IncompatibleProfiles(String),
+
+    // Sweep errors
+    #[error("Invalid sweep path: {0}")]
+    InvalidSweepPath(String),
+
+    // Primitive errors
+    #[error("Invalid primitive: {0}")]
+    InvalidPrimitive(String),
+
+    // Feature errors
+    #[error("Invalid feature: {0}")]
+    InvalidFeature(String),
+
+    // Datum errors
+    #[error("Unknown datum: {0}")]
+    UnknownDatum(String),
+
+    // Import errors
+    #[error("Import failed: {0}")]
+    ImportFailed(String),
+
+    // Project errors
+    #[error("Invalid project file: {0}")]
+    InvalidProject(String),
+
+    // Script errors
+    #[error("Script failed: {0}")]
+    ScriptFailed(String),
+
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+
+    // Export errors
+    #[error("Export failed: {0}")]
+    ExportFailed(String),
+
+    // Settings errors
+    #[error("Invalid settings: {0}")]
+    InvalidSettings(String),
+
+    // Render errors
+    #[error("Rendering failed: {0}")]
+    RenderFailed(String),
+
+    // Boolean errors
+    #[error("Boolean operation failed: {0}")]
+    BooleanFailed(String),
+
+    // Constraint errors
+    #[error("Invalid constraint: {0}")]
+    InvalidConstraint(String),
+
+    #[error("Constraint solver did not converge (residual {residual:.3e})")]
+    ConstraintSolveFailed { residual: f64 },
+
     // Topology errors
     #[error("Failed to create truck edge: {0}")]
     TruckEdgeError(String),
@@ -55,6 +152,9 @@ pub enum SketchError {
 
     #[error("Failed to create truck face: {0}")]
     TruckFaceError(String),
+
+    #[error("Failed to create truck solid: {0}")]
+    TruckSolidError(String),
 }
 
 pub type SketchResult<T> = Result<T, SketchError>;
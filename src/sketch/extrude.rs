@@ -0,0 +1,446 @@
+use crate::sketch::constants::*;
+use crate::sketch::error::*;
+use crate::sketch::loft::Loft;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::offset::offset_chain;
+use crate::sketch::plane::Plane;
+use crate::sketch::primitives::{Curve2D, Line2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use std::f64::consts::FRAC_PI_2;
+use truck_geometry::prelude::*;
+use truck_meshalgo::prelude::{MeshableShape, MeshedShape};
+use truck_modeling::{builder as truck_builder, Face, Shell, Solid};
+
+/// Extra tool length behind the sketch plane for cuts, relative to the depth
+const CUT_CLEARANCE: f64 = 0.01;
+
+/// Extra length past the target for up-to extrusions, relative to the reach
+const TARGET_OVERSHOOT: f64 = 0.1;
+
+/// Tessellation tolerance for finding which way a target face points
+const TARGET_MESH_TOLERANCE: f64 = 0.05;
+
+impl Sketch {
+    /// Profile of a wall `thickness` wide centered on a chain of curves
+    /// (e.g. from [`SketchBuilder::build_open`](crate::sketch::SketchBuilder::build_open))
+    ///
+    /// An open chain gets square end caps; a closed chain becomes a ring.
+    pub fn thin_wall(chain: &[Curve2D], thickness: f64) -> SketchResult<Sketch> {
+        if thickness <= LENGTH_TOLERANCE || !thickness.is_finite() {
+            return Err(SketchError::InvalidThickness(thickness));
+        }
+        let (first, last) = match (chain.first(), chain.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(SketchError::EmptyLoop),
+        };
+        for (index, pair) in chain.windows(2).enumerate() {
+            let gap = (pair[1].start() - pair[0].end()).magnitude();
+            if gap > HEAL_TOLERANCE {
+                return Err(SketchError::BrokenChain { index, gap });
+            }
+        }
+
+        let half = thickness / 2.0;
+        if (last.end() - first.start()).magnitude() <= HEAL_TOLERANCE {
+            let centre = Loop2D::new(chain.to_vec())?;
+            return Ok(Sketch::with_holes(
+                centre.offset(half)?,
+                vec![centre.offset(-half)?],
+            ));
+        }
+
+        // Right side forward, then back along the left side
+        let left = offset_chain(chain, half, false)?;
+        let right = offset_chain(chain, -half, false)?;
+        let (left_start, left_end) = (left[0].start(), left[left.len() - 1].end());
+        let (right_start, right_end) = (right[0].start(), right[right.len() - 1].end());
+        let mut curves = right;
+        curves.push(Curve2D::Line(Line2D::new(right_end, left_end)?));
+        curves.extend(left.iter().rev().map(|c| c.reversed()));
+        curves.push(Curve2D::Line(Line2D::new(left_start, right_start)?));
+        Ok(Sketch::new(Loop2D::new(curves)?))
+    }
+
+    /// Extrude a chain of curves as a wall `thickness` wide along `direction`
+    pub fn extrude_thin(
+        chain: &[Curve2D],
+        plane: impl Into<Plane>,
+        direction: Vector3,
+        thickness: f64,
+    ) -> SketchResult<Solid> {
+        Self::thin_wall(chain, thickness)?.extrude(plane, direction)
+    }
+
+    /// Machine the profile out of `solid`, cutting `depth` along `direction`
+    /// from `plane`
+    ///
+    /// The tool starts slightly behind the plane so that a sketch drawn on
+    /// a face of the solid cuts cleanly through it. `solid` is left
+    /// untouched if the cut fails.
+    pub fn cut_into(
+        &self,
+        solid: &mut Solid,
+        plane: impl Into<Plane>,
+        direction: Vector3,
+        depth: f64,
+    ) -> SketchResult<()> {
+        let plane = &plane.into();
+        if depth <= LENGTH_TOLERANCE || !depth.is_finite() {
+            return Err(SketchError::InvalidExtrudeDepth(depth));
+        }
+        if direction.magnitude() < DEGENERATE_TOLERANCE {
+            return Err(SketchError::InvalidExtrudeDepth(0.0));
+        }
+        let direction = direction.normalize();
+        let clearance = depth * CUT_CLEARANCE;
+        let face = self.to_truck_face(plane)?;
+        let start = truck_builder::translated(&face, -direction * clearance);
+        let mut tool = truck_builder::tsweep(&start, direction * (depth + clearance));
+        // The sweep faces outward only when it runs along the face normal
+        if direction.dot(plane.normal()) < 0.0 {
+            tool.not();
+        }
+        *solid = crate::geometry::cut(solid, &tool)?;
+        Ok(())
+    }
+
+    /// Extrude along `direction` until the profile lands on `target`, which
+    /// may be curved (e.g. a face of another solid)
+    ///
+    /// The profile is extruded past the target and everything beyond the
+    /// face is cut away, so the face must cover the whole profile as seen
+    /// along `direction`.
+    pub fn extrude_up_to(
+        &self,
+        plane: impl Into<Plane>,
+        direction: Vector3,
+        target: &Face,
+    ) -> SketchResult<Solid> {
+        let plane = &plane.into();
+        if direction.magnitude() < DEGENERATE_TOLERANCE {
+            return Err(SketchError::InvalidExtrudeDepth(0.0));
+        }
+        let direction = direction.normalize();
+        let shell: Shell = vec![target.clone()].into();
+        let mesh = shell.triangulation(TARGET_MESH_TOLERANCE).to_polygon();
+        let positions = mesh.positions();
+        // Curved faces can bulge past their vertices, so measure the mesh
+        let reach = positions
+            .iter()
+            .map(|p| (p - plane.origin()).dot(direction))
+            .fold(f64::NEG_INFINITY, f64::max);
+        if reach <= LENGTH_TOLERANCE {
+            return Err(SketchError::InvalidExtrudeDepth(reach));
+        }
+        let length = reach * (1.0 + TARGET_OVERSHOOT) + TARGET_MESH_TOLERANCE;
+
+        let mut prism = self.extrude(plane, direction * length)?;
+        if direction.dot(plane.normal()) < 0.0 {
+            prism.not();
+        }
+
+        // Everything on the far side of the target, out past the prism
+        let mut beyond = truck_builder::tsweep(target, direction * length);
+        let facing: f64 = mesh
+            .tri_faces()
+            .iter()
+            .map(|tri| {
+                let [a, b, c] = tri.map(|v| positions[v.pos]);
+                (b - a).cross(c - a).dot(direction)
+            })
+            .sum();
+        if facing < 0.0 {
+            beyond.not();
+        }
+        crate::geometry::cut(&prism, &beyond)
+    }
+
+    /// Extrude `total_depth` along the plane normal, centered on the plane
+    pub fn extrude_symmetric(
+        &self,
+        plane: impl Into<Plane>,
+        total_depth: f64,
+    ) -> SketchResult<Solid> {
+        self.extrude_two_sided(plane, total_depth / 2.0, total_depth / 2.0)
+    }
+
+    /// Extrude `depth_pos` along the plane normal and `depth_neg` against it
+    ///
+    /// Either depth may be negative as long as the total stays positive.
+    pub fn extrude_two_sided(
+        &self,
+        plane: impl Into<Plane>,
+        depth_pos: f64,
+        depth_neg: f64,
+    ) -> SketchResult<Solid> {
+        let plane = &plane.into();
+        let total = depth_pos + depth_neg;
+        if total <= LENGTH_TOLERANCE || !total.is_finite() {
+            return Err(SketchError::InvalidExtrudeDepth(total));
+        }
+        let normal = plane.normal();
+        let face = self.to_truck_face(plane)?;
+        let start = truck_builder::translated(&face, -normal * depth_neg);
+        Ok(truck_builder::tsweep(&start, normal * total))
+    }
+
+    /// Extrude along `direction` with side walls tapered by `draft_angle`
+    ///
+    /// A positive angle narrows the profile towards the far end (holes
+    /// widen); the walls are ruled surfaces between the profile and its
+    /// offset copy.
+    pub fn extrude_with_draft(
+        &self,
+        plane: impl Into<Plane>,
+        direction: Vector3,
+        draft_angle: Rad<f64>,
+    ) -> SketchResult<Solid> {
+        let plane = &plane.into();
+        let height = direction.dot(plane.normal()).abs();
+        if height <= LENGTH_TOLERANCE || !height.is_finite() {
+            return Err(SketchError::InvalidExtrudeDepth(height));
+        }
+        if draft_angle.0.abs() >= FRAC_PI_2 {
+            return Err(SketchError::OffsetFailed(format!(
+                "draft angle {:.3} rad is not between -pi/2 and pi/2",
+                draft_angle.0
+            )));
+        }
+        let top = self.offset(-height * draft_angle.0.tan())?;
+        let top_plane = Plane::new(plane.origin() + direction, plane.x_dir(), plane.y_dir())?;
+        Loft::new()
+            .section(self.clone(), plane.clone())
+            .section(top, top_plane)
+            .build()
+    }
+
+    /// Extrude along `direction` while rotating the profile by
+    /// `twist_angle` about the plane origin
+    ///
+    /// The twist is split evenly over `sections` steps; the walls between
+    /// steps are ruled, so more sections follow the helix more closely.
+    pub fn extrude_twisted(
+        &self,
+        plane: impl Into<Plane>,
+        direction: Vector3,
+        twist_angle: Rad<f64>,
+        sections: usize,
+    ) -> SketchResult<Solid> {
+        let plane = &plane.into();
+        if sections == 0 {
+            return Err(SketchError::InvalidSectionCount(sections));
+        }
+        let height = direction.dot(plane.normal()).abs();
+        if height <= LENGTH_TOLERANCE || !height.is_finite() {
+            return Err(SketchError::InvalidExtrudeDepth(height));
+        }
+        let mut loft = Loft::new();
+        for k in 0..=sections {
+            let s = k as f64 / sections as f64;
+            let profile = self.rotated(Point2::origin(), twist_angle.0 * s);
+            let section = Plane::new(plane.origin() + direction * s, plane.x_dir(), plane.y_dir())?;
+            loft = loft.section(profile, section);
+        }
+        loft.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::box_solid;
+    use crate::sketch::{SketchBuilder, Shapes};
+    use truck_modeling::Vertex;
+
+    fn z_range(solid: &Solid) -> (f64, f64) {
+        solid
+            .vertex_iter()
+            .map(|v: Vertex| v.point().z)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), z| {
+                (lo.min(z), hi.max(z))
+            })
+    }
+
+    #[test]
+    fn test_symmetric_and_two_sided() {
+        let sketch = Sketch::new(Shapes::rectangle(Point2::origin(), 4.0, 2.0).unwrap());
+        let plane = Plane::xy();
+
+        let solid = sketch.extrude_symmetric(&plane, 6.0).unwrap();
+        let (lo, hi) = z_range(&solid);
+        assert!((lo + 3.0).abs() < 1e-9 && (hi - 3.0).abs() < 1e-9);
+
+        let solid = sketch.extrude_two_sided(&plane, 5.0, 1.0).unwrap();
+        let (lo, hi) = z_range(&solid);
+        assert!((lo + 1.0).abs() < 1e-9 && (hi - 5.0).abs() < 1e-9);
+
+        assert!(matches!(
+            sketch.extrude_two_sided(&plane, 1.0, -1.0),
+            Err(SketchError::InvalidExtrudeDepth(_))
+        ));
+    }
+
+    #[test]
+    fn test_draft_extrusion() {
+        let sketch = Sketch::with_holes(
+            Shapes::rectangle(Point2::origin(), 10.0, 10.0).unwrap(),
+            vec![Shapes::circle(Point2::new(5.0, 5.0), 2.0).unwrap()],
+        );
+        let draft = Rad(5f64.to_radians());
+        let solid = sketch
+            .extrude_with_draft(Plane::xy(), Vector3::new(0.0, 0.0, 10.0), draft)
+            .unwrap();
+        assert_eq!(solid.boundaries().len(), 1);
+
+        let t = 10.0 * draft.0.tan();
+        for v in solid.vertex_iter() {
+            let p = v.point();
+            if (p.z - 10.0).abs() < 1e-9 && p.x.min(p.y) < 5.0 - 2.0 - t - 1e-6 {
+                // Outer top corners are pulled in by the draft
+                assert!((p.x - t).abs() < 1e-9 || (p.x - 10.0 + t).abs() < 1e-9);
+                assert!((p.y - t).abs() < 1e-9 || (p.y - 10.0 + t).abs() < 1e-9);
+            }
+        }
+        let (lo, hi) = z_range(&solid);
+        assert!(lo.abs() < 1e-9 && (hi - 10.0).abs() < 1e-9);
+
+        assert!(matches!(
+            sketch.extrude_with_draft(Plane::xy(), Vector3::new(1.0, 0.0, 0.0), draft),
+            Err(SketchError::InvalidExtrudeDepth(_))
+        ));
+    }
+
+    #[test]
+    fn test_thin_wall_bracket() {
+        // L-shaped bracket: 10 along X, then 5 up
+        let chain = SketchBuilder::new()
+            .move_to(Point2::origin())
+            .line_to(Point2::new(10.0, 0.0))
+            .unwrap()
+            .line_to(Point2::new(10.0, 5.0))
+            .unwrap()
+            .build_open();
+        let wall = Sketch::thin_wall(&chain, 1.0).unwrap();
+        assert!((wall.area() - 15.0).abs() < 1e-9);
+        let bbox = wall.bounding_box().unwrap();
+        assert!((bbox.min - Point2::new(0.0, -0.5)).magnitude() < 1e-9);
+        assert!((bbox.max - Point2::new(10.5, 5.0)).magnitude() < 1e-9);
+
+        let solid = Sketch::extrude_thin(&chain, Plane::xy(), Vector3::new(0.0, 0.0, 3.0), 1.0)
+            .unwrap();
+        assert_eq!(solid.boundaries().len(), 1);
+
+        // A closed chain becomes a ring
+        let square = Shapes::rectangle(Point2::origin(), 4.0, 4.0).unwrap();
+        let ring = Sketch::thin_wall(square.curves(), 1.0).unwrap();
+        assert_eq!(ring.hole_count(), 1);
+        assert!((ring.area() - (25.0 - 9.0)).abs() < 1e-9);
+
+        assert!(matches!(
+            Sketch::thin_wall(&chain[..1], 0.0),
+            Err(SketchError::InvalidThickness(_))
+        ));
+        assert!(matches!(
+            Sketch::thin_wall(&[chain[1].clone(), chain[0].clone()], 1.0),
+            Err(SketchError::BrokenChain { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_cut_pocket_and_through_hole() {
+        let top = Plane::new(
+            Point3::new(0.0, 0.0, 20.0),
+            Vector3::unit_x(),
+            Vector3::unit_y(),
+        )
+        .unwrap();
+        let hole = Sketch::new(Shapes::circle(Point2::origin(), 3.0).unwrap());
+        let down = Vector3::new(0.0, 0.0, -1.0);
+
+        let block = box_solid(Point3::new(-10.0, -10.0, 0.0), Vector3::new(20.0, 20.0, 20.0))
+            .unwrap();
+        let mut pocketed = block.clone();
+        hole.cut_into(&mut pocketed, &top, down, 8.0).unwrap();
+        assert_eq!(pocketed.boundaries().len(), 1);
+        assert!(pocketed
+            .vertex_iter()
+            .any(|v: Vertex| (v.point().z - 12.0).abs() < 1e-6));
+
+        let mut drilled = block;
+        hole.cut_into(&mut drilled, &top, down, 25.0).unwrap();
+        let (lo, hi) = z_range(&drilled);
+        assert!(lo.abs() < 1e-6 && (hi - 20.0).abs() < 1e-6);
+        let on_bore = |z: f64| {
+            drilled.vertex_iter().any(|v: Vertex| {
+                let p = v.point();
+                (p.z - z).abs() < 1e-6 && ((p.x * p.x + p.y * p.y).sqrt() - 3.0).abs() < 1e-6
+            })
+        };
+        assert!(on_bore(0.0) && on_bore(20.0));
+    }
+
+    #[test]
+    fn test_extrude_up_to_curved_face() {
+        // Cylindrical patch (radius 7.5, axis along Y at z = 12.5) whose
+        // lowest line is at z = 5
+        let v0 = truck_builder::vertex(Point3::new(-6.0, -10.0, 8.0));
+        let v1 = truck_builder::vertex(Point3::new(6.0, -10.0, 8.0));
+        let arc = truck_builder::circle_arc(&v0, &v1, Point3::new(0.0, -10.0, 5.0));
+        let target = truck_builder::tsweep(&arc, Vector3::new(0.0, 20.0, 0.0));
+
+        let boss = Sketch::new(Shapes::circle(Point2::origin(), 2.0).unwrap());
+        let solid = boss
+            .extrude_up_to(Plane::xy(), Vector3::unit_z(), &target)
+            .unwrap();
+        let (lo, hi) = z_range(&solid);
+        let rim = 12.5 - (7.5f64 * 7.5 - 4.0).sqrt();
+        assert!(lo.abs() < 1e-6 && (hi - rim).abs() < 1e-3, "{} {}", lo, hi);
+
+        assert!(matches!(
+            boss.extrude_up_to(Plane::xy_at(20.0), Vector3::unit_z(), &target),
+            Err(SketchError::InvalidExtrudeDepth(_))
+        ));
+
+        // The same patch bulging the other way (axis at z = 3.5), up to
+        // z = 11 between edges at z = 8
+        let arc = truck_builder::circle_arc(&v0, &v1, Point3::new(0.0, -10.0, 11.0));
+        let dome = truck_builder::tsweep(&arc, Vector3::new(0.0, 20.0, 0.0));
+        let solid = boss
+            .extrude_up_to(Plane::xy(), Vector3::unit_z(), &dome)
+            .unwrap();
+        let (_, hi) = z_range(&solid);
+        let rim = 3.5 + (7.5f64 * 7.5 - 4.0).sqrt();
+        assert!(hi > rim - 1e-3, "stopped short at {}", hi);
+        // Every vertex off the base lies on the dome
+        for v in solid.vertex_iter() {
+            let p = v.point();
+            if p.z > 1e-6 {
+                let r = (p.x * p.x + (p.z - 3.5) * (p.z - 3.5)).sqrt();
+                assert!((r - 7.5).abs() < 1e-3, "{:?} is off the dome", p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_twisted_extrusion() {
+        let sketch = Sketch::new(Shapes::rectangle_centered(Point2::origin(), 4.0, 2.0).unwrap());
+        let twist = Rad(std::f64::consts::FRAC_PI_2);
+        let solid = sketch
+            .extrude_twisted(Plane::xy(), Vector3::new(0.0, 0.0, 8.0), twist, 8)
+            .unwrap();
+        assert_eq!(solid.boundaries().len(), 1);
+
+        // The top face is the profile turned a quarter turn
+        let top: Vec<Point3> = solid
+            .vertex_iter()
+            .map(|v: Vertex| v.point())
+            .filter(|p| (p.z - 8.0).abs() < 1e-9)
+            .collect();
+        assert!(top.iter().all(|p| (p.x.abs() - 1.0).abs() < 1e-9 && (p.y.abs() - 2.0).abs() < 1e-9));
+
+        assert!(matches!(
+            sketch.extrude_twisted(Plane::xy(), Vector3::new(0.0, 0.0, 8.0), twist, 0),
+            Err(SketchError::InvalidSectionCount(0))
+        ));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::sketch::constants::LENGTH_TOLERANCE;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::primitives::{Curve2D, Line2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use truck_geometry::prelude::*;
+
+/// Chord tolerance for flattening, relative to the sketch size
+const RELATIVE_CHORD_TOLERANCE: f64 = 1e-6;
+
+/// Segments used to flatten a B-spline
+const SPLINE_SEGMENTS: usize = 256;
+
+/// Upper bound on segments per arc
+const MAX_ARC_SEGMENTS: usize = 4096;
+
+/// Upper bound on hatch lines across a sketch
+const MAX_HATCH_LINES: f64 = 100_000.0;
+
+impl Sketch {
+    /// Parallel hatch lines clipped to the profile (outer loop minus holes).
+    ///
+    /// `angle` is the hatch direction in radians from +X; lines lie at
+    /// multiples of `spacing` from the origin so adjacent regions hatched
+    /// with the same settings line up. Returns nothing for non-positive
+    /// spacing, or spacing so fine it would take over 100 000 lines.
+    pub fn hatch(&self, angle: f64, spacing: f64) -> Vec<Line2D> {
+        if spacing <= 0.0 || !spacing.is_finite() {
+            return Vec::new();
+        }
+
+        // Work in a frame where the hatch lines are horizontal
+        let local = self.rotated(Point2::origin(), -angle);
+        let Some(bbox) = local.bounding_box() else {
+            return Vec::new();
+        };
+        if (bbox.max.y - bbox.min.y) / spacing > MAX_HATCH_LINES {
+            return Vec::new();
+        }
+        let tol = RELATIVE_CHORD_TOLERANCE * (bbox.max - bbox.min).magnitude().max(1.0);
+
+        let polygons: Vec<Vec<Point2>> = std::iter::once(&local.outer)
+            .chain(&local.holes)
+            .map(|l| flatten(l, tol))
+            .collect();
+
+        let (sin, cos) = angle.sin_cos();
+        let to_world = |x: f64, y: f64| Point2::new(cos * x - sin * y, sin * x + cos * y);
+
+        let mut lines = Vec::new();
+        let first = (bbox.min.y / spacing).ceil() as i64;
+        let last = (bbox.max.y / spacing).floor() as i64;
+        for k in first..=last {
+            let y = k as f64 * spacing;
+            let mut xs = Vec::new();
+            for polygon in &polygons {
+                for (p, q) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+                    // Half-open rule counts shared vertices exactly once
+                    if (p.y <= y) != (q.y <= y) {
+                        xs.push(p.x + (y - p.y) / (q.y - p.y) * (q.x - p.x));
+                    }
+                }
+            }
+            xs.sort_by(f64::total_cmp);
+
+            for pair in xs.chunks_exact(2) {
+                if pair[1] - pair[0] > LENGTH_TOLERANCE {
+                    if let Ok(line) = Line2D::new(to_world(pair[0], y), to_world(pair[1], y)) {
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+        lines
+    }
+}
+
+/// Closed polygon approximating a loop (last point not repeated)
+fn flatten(l: &Loop2D, tol: f64) -> Vec<Point2> {
+    l.curves().iter().flat_map(|c| flatten_curve(c, tol)).collect()
+}
+
+/// Polyline approximating a curve within `tol`, without its end point
+pub(crate) fn flatten_curve(curve: &Curve2D, tol: f64) -> Vec<Point2> {
+    let segments = match curve {
+        Curve2D::Line(_) => 1,
+        Curve2D::Arc(arc) => arc_segments(arc.radius(), arc.sweep_angle().abs(), tol),
+        Curve2D::Circle(circle) => arc_segments(circle.radius(), std::f64::consts::TAU, tol),
+        Curve2D::BSpline(_) => SPLINE_SEGMENTS,
+    };
+    (0..segments)
+        .map(|i| curve.point_at(i as f64 / segments as f64))
+        .collect()
+}
+
+fn arc_segments(radius: f64, sweep: f64, tol: f64) -> usize {
+    let step = 2.0 * (1.0 - (tol / radius).min(1.0)).acos();
+    if step <= 0.0 {
+        return MAX_ARC_SEGMENTS;
+    }
+    ((sweep / step).ceil() as usize).clamp(4, MAX_ARC_SEGMENTS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::Shapes;
+    use std::f64::consts::FRAC_PI_4;
+
+    #[test]
+    fn test_hatch_square_with_hole() {
+        let outer = Shapes::rectangle(Point2::origin(), 10.0, 10.0).unwrap();
+        let hole = Shapes::rectangle(Point2::new(4.0, 4.0), 2.0, 2.0).unwrap();
+        let sketch = Sketch::with_holes(outer, vec![hole]);
+
+        let lines = sketch.hatch(0.0, 1.0);
+        // Rows y = 0..=9 (the top edge is excluded by the half-open rule);
+        // rows 4 and 5 are split by the hole
+        assert_eq!(lines.len(), 12);
+        let total: f64 = lines.iter().map(|l| l.length()).sum();
+        assert!((total - (10.0 * 10.0 - 2.0 * 2.0)).abs() < 1e-9);
+        assert!(lines.iter().all(|l| l.direction().y.abs() < 1e-12));
+
+        let diagonal = sketch.hatch(FRAC_PI_4, 0.5);
+        assert!(!diagonal.is_empty());
+        for line in &diagonal {
+            let d = line.direction();
+            assert!((d.x - d.y).abs() < 1e-9);
+        }
+        assert!(sketch.hatch(0.0, 0.0).is_empty());
+        // A spacing far below the sketch size would never finish
+        assert!(sketch.hatch(0.0, 1e-9).is_empty());
+        assert_eq!(sketch.hatch(0.0, 1e-3).len(), 10_000 + 2_000);
+    }
+}
@@ -0,0 +1,629 @@
+use crate::sketch::constants::HEAL_TOLERANCE;
+use crate::sketch::error::*;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::plane::Plane;
+use crate::sketch::primitives::{Arc2D, BSpline2D, Curve2D, Line2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use std::f64::consts::FRAC_1_SQRT_2;
+use truck_geometry::prelude::*;
+use truck_modeling::{builder as truck_builder, Face, Shell, Solid, Surface, Wire};
+
+/// Breakpoints closer than this (as a fraction of loop length) are merged
+const FRACTION_TOLERANCE: f64 = 1e-9;
+
+/// Samples per curve when searching for a seam point
+const SEAM_SAMPLES: usize = 256;
+
+impl Sketch {
+    /// Loft a solid between this profile on `plane_a` and `other` on `plane_b`.
+    ///
+    /// Profiles may have different curve counts (e.g. square to round): the
+    /// second loop's seam is moved to the point in the same direction from
+    /// its center as the first loop's start, both loops are split at each
+    /// other's vertices by arc-length fraction, and corresponding pieces are
+    /// joined by ruled surfaces. Both sketches need the same number of holes,
+    /// and the planes should face the same way.
+    pub fn loft(
+        &self,
+        plane_a: impl Into<Plane>,
+        other: &Sketch,
+        plane_b: impl Into<Plane>,
+    ) -> SketchResult<Solid> {
+        Loft::new()
+            .section(self.clone(), plane_a.into())
+            .section(other.clone(), plane_b.into())
+            .build()
+    }
+}
+
+/// Loft through an ordered list of sections, optionally steered by guides.
+///
+/// Consecutive sections are joined by ruled surfaces. Guides are 3D
+/// polylines that cross every section plane on the outer profile: the first
+/// guide fixes the seam of each section, and every guide becomes a vertex
+/// track, so the profiles are synchronized span by span between guides.
+/// Without guides, each seam is aligned with the previous section's.
+#[derive(Clone, Debug, Default)]
+pub struct Loft {
+    sections: Vec<(Sketch, Plane)>,
+    /// Affine map applied to each section once lifted onto its plane
+    stretches: Vec<Option<Matrix4>>,
+    guides: Vec<Vec<Point3>>,
+}
+
+impl Loft {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a section profile
+    pub fn section(mut self, sketch: Sketch, plane: Plane) -> Self {
+        self.sections.push((sketch, plane));
+        self.stretches.push(None);
+        self
+    }
+
+    /// Append a section stretched by `factor` along `direction` (in the
+    /// plane) about `center`, as at the mitre of a swept corner
+    pub(crate) fn stretched_section(
+        mut self,
+        sketch: Sketch,
+        plane: Plane,
+        center: Point3,
+        direction: Vector3,
+        factor: f64,
+    ) -> Self {
+        let d = direction.normalize();
+        let o = center.to_vec();
+        let stretch =
+            Matrix3::identity() + Matrix3::from_cols(d * d.x, d * d.y, d * d.z) * (factor - 1.0);
+        let map =
+            Matrix4::from_translation(o) * Matrix4::from(stretch) * Matrix4::from_translation(-o);
+        self.sections.push((sketch, plane));
+        self.stretches.push(Some(map));
+        self
+    }
+
+    /// Add a guide polyline
+    pub fn guide(mut self, points: Vec<Point3>) -> Self {
+        self.guides.push(points);
+        self
+    }
+
+    /// Build the lofted solid
+    pub fn build(&self) -> SketchResult<Solid> {
+        let n = self.sections.len();
+        if n < 2 {
+            return Err(SketchError::IncompatibleProfiles(
+                "a loft needs at least two sections".to_string(),
+            ));
+        }
+        let hole_count = self.sections[0].0.holes.len();
+        if let Some((i, _)) = self
+            .sections
+            .iter()
+            .enumerate()
+            .find(|(_, (s, _))| s.holes.len() != hole_count)
+        {
+            return Err(SketchError::IncompatibleProfiles(format!(
+                "section {} has {} holes, section 0 has {}",
+                i,
+                self.sections[i].0.holes.len(),
+                hole_count
+            )));
+        }
+
+        let outers: Vec<Loop2D> = self
+            .sections
+            .iter()
+            .map(|(s, _)| oriented(&s.outer, true))
+            .collect();
+        let mut loop_sets = vec![if self.guides.is_empty() {
+            synchronize_aligned(outers)?
+        } else {
+            self.synchronize_guided(outers)?
+        }];
+        for h in 0..hole_count {
+            let holes = self
+                .sections
+                .iter()
+                .map(|(s, _)| oriented(&s.holes[h], false))
+                .collect();
+            loop_sets.push(synchronize_aligned(holes)?);
+        }
+
+        let mut wires: Vec<Vec<Wire>> = vec![Vec::new(); n];
+        for set in &loop_sets {
+            for (i, l) in set.iter().enumerate() {
+                let wire = l.to_truck_wire(&self.sections[i].1)?;
+                wires[i].push(match self.stretches[i] {
+                    Some(map) => truck_builder::transformed(&wire, map),
+                    None => wire,
+                });
+            }
+        }
+
+        let mut shell = Shell::new();
+        for pair in wires.windows(2) {
+            for (a, b) in pair[0].iter().zip(&pair[1]) {
+                shell.extend(ruled_faces(a, b)?);
+            }
+        }
+        let (first, last) = (&self.sections[0].1, &self.sections[n - 1].1);
+        shell.push(planar_face(wires[0].clone(), first)?.inverse());
+        shell.push(planar_face(wires[n - 1].clone(), last)?);
+
+        // Outer loops run CCW, so the shell faces outward when the second
+        // section lies on the positive side of the first
+        let second = &self.sections[1].1;
+        if (second.origin() - first.origin()).dot(first.normal()) < 0.0 {
+            shell.face_iter_mut().for_each(|face| {
+                face.invert();
+            });
+        }
+
+        Solid::try_new(vec![shell]).map_err(|e| SketchError::TruckSolidError(format!("{:?}", e)))
+    }
+
+    /// Split outer loops at the guide crossings and synchronize between them
+    fn synchronize_guided(&self, outers: Vec<Loop2D>) -> SketchResult<Vec<Loop2D>> {
+        let mut split = Vec::with_capacity(outers.len());
+        let mut anchors = Vec::with_capacity(outers.len());
+        for (i, (l, (_, plane))) in outers.iter().zip(&self.sections).enumerate() {
+            let points = self
+                .guides
+                .iter()
+                .map(|g| {
+                    guide_crossing(g, plane)
+                        .map(|p| plane.project_point(p))
+                        .ok_or_else(|| {
+                            SketchError::IncompatibleProfiles(format!(
+                                "a guide does not cross section {}",
+                                i
+                            ))
+                        })
+                })
+                .collect::<SketchResult<Vec<_>>>()?;
+            let (l, vertices) = split_at_points(l, &points, i)?;
+            split.push(l);
+            anchors.push(vertices);
+        }
+        synchronize(&split, &anchors)
+    }
+}
+
+/// Align each loop's seam with the previous loop's, then synchronize
+///
+/// Loops that already share the same sequence of curve kinds (such as
+/// offset or rotated copies of one profile) are paired curve-to-curve.
+fn synchronize_aligned(loops: Vec<Loop2D>) -> SketchResult<Vec<Loop2D>> {
+    if same_structure(&loops) {
+        return Ok(loops);
+    }
+    let mut aligned: Vec<Loop2D> = Vec::with_capacity(loops.len());
+    for l in loops {
+        let l = match aligned.last() {
+            Some(prev) => align_seam(&l, seam_direction(prev))?,
+            None => l,
+        };
+        aligned.push(l);
+    }
+    let anchors = vec![vec![0]; aligned.len()];
+    synchronize(&aligned, &anchors)
+}
+
+/// Whether every loop has the same curve kinds in the same order (and more
+/// than one curve) with its seam near the previous loop's
+fn same_structure(loops: &[Loop2D]) -> bool {
+    let kinds = |l: &Loop2D| -> Vec<_> { l.curves().iter().map(std::mem::discriminant).collect() };
+    let first = kinds(&loops[0]);
+    first.len() > 1
+        && loops.windows(2).all(|pair| {
+            let (a, b) = (seam_direction(&pair[0]), seam_direction(&pair[1]));
+            kinds(&pair[1]) == first && a.dot(b) > FRAC_1_SQRT_2 * a.magnitude() * b.magnitude()
+        })
+}
+
+/// First point where a guide polyline meets a plane
+fn guide_crossing(guide: &[Point3], plane: &Plane) -> Option<Point3> {
+    let dist = |p: &Point3| (p - plane.origin()).dot(plane.normal());
+    for pair in guide.windows(2) {
+        let (d0, d1) = (dist(&pair[0]), dist(&pair[1]));
+        if d0.abs() <= HEAL_TOLERANCE {
+            return Some(pair[0]);
+        }
+        if d0 * d1 < 0.0 {
+            return Some(pair[0] + (pair[1] - pair[0]) * (d0 / (d0 - d1)));
+        }
+    }
+    guide
+        .last()
+        .filter(|p| dist(p).abs() <= HEAL_TOLERANCE)
+        .copied()
+}
+
+/// Closest point on a loop as (curve index, parameter, distance)
+fn nearest_on_loop(l: &Loop2D, p: Point2) -> (usize, f64, f64) {
+    let mut best = (0, 0.0, f64::INFINITY);
+    for (k, curve) in l.curves().iter().enumerate() {
+        let dist = |t: f64| (curve.point_at(t) - p).magnitude();
+        for i in 0..=SEAM_SAMPLES {
+            let t = i as f64 / SEAM_SAMPLES as f64;
+            let d = dist(t);
+            if d < best.2 {
+                best = (k, t, d);
+            }
+        }
+    }
+
+    // Golden-section refinement around the best sample
+    let (k, t, _) = best;
+    let curve = &l.curves()[k];
+    let dist = |t: f64| (curve.point_at(t) - p).magnitude();
+    let h = 1.0 / SEAM_SAMPLES as f64;
+    let (mut a, mut b) = ((t - h).max(0.0), (t + h).min(1.0));
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    for _ in 0..40 {
+        let c = b - ratio * (b - a);
+        let d = a + ratio * (b - a);
+        if dist(c) < dist(d) {
+            b = d;
+        } else {
+            a = c;
+        }
+    }
+    let t = (a + b) / 2.0;
+    (k, t, dist(t))
+}
+
+/// Rotate and split a loop so it starts at `points[0]` and has a vertex at
+/// every point; returns the vertex index of each point
+fn split_at_points(
+    l: &Loop2D,
+    points: &[Point2],
+    section: usize,
+) -> SketchResult<(Loop2D, Vec<usize>)> {
+    let bbox = l.bounding_box().expect("loops are never empty");
+    let tol = HEAL_TOLERANCE.max(1e-4 * (bbox.max - bbox.min).magnitude());
+
+    let mut cuts: Vec<(usize, f64)> = Vec::with_capacity(points.len());
+    for &p in points {
+        let (k, t, d) = nearest_on_loop(l, p);
+        if d > tol {
+            return Err(SketchError::IncompatibleProfiles(format!(
+                "a guide misses the profile of section {} by {:.3e}",
+                section, d
+            )));
+        }
+        // Snap to existing vertices
+        cuts.push(if t < FRACTION_TOLERANCE.sqrt() {
+            (k, 0.0)
+        } else if t > 1.0 - FRACTION_TOLERANCE.sqrt() {
+            ((k + 1) % l.len(), 0.0)
+        } else {
+            (k, t)
+        });
+    }
+
+    // Split all curves at the cut parameters, remembering where each cut lands
+    let mut pieces = Vec::new();
+    let mut vertex_of = vec![0; cuts.len()];
+    for (k, curve) in l.curves().iter().enumerate() {
+        let mut ts: Vec<(f64, Vec<usize>)> = Vec::new();
+        for (g, &(ck, t)) in cuts.iter().enumerate() {
+            if ck != k {
+                continue;
+            }
+            match ts
+                .iter_mut()
+                .find(|(u, _)| (u - t).abs() < FRACTION_TOLERANCE)
+            {
+                Some((_, gs)) => gs.push(g),
+                None => ts.push((t, vec![g])),
+            }
+        }
+        ts.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if ts.first().is_none_or(|(t, _)| *t > 0.0) {
+            ts.insert(0, (0.0, Vec::new()));
+        }
+        for (j, (t0, gs)) in ts.iter().enumerate() {
+            for &g in gs {
+                vertex_of[g] = pieces.len();
+            }
+            let t1 = ts.get(j + 1).map_or(1.0, |(t, _)| *t);
+            pieces.push(subcurve(curve, *t0, t1)?);
+        }
+    }
+
+    // Start the loop at the first guide
+    let shift = vertex_of[0];
+    pieces.rotate_left(shift);
+    let count = pieces.len();
+    let vertices: Vec<usize> = vertex_of
+        .iter()
+        .map(|&v| (v + count - shift) % count)
+        .collect();
+    if vertices.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(SketchError::IncompatibleProfiles(format!(
+            "guides cross section {} out of order",
+            section
+        )));
+    }
+    Ok((Loop2D::new_unchecked(pieces), vertices))
+}
+
+/// Ruled surfaces between corresponding edges of two wires
+pub(crate) fn ruled_faces(wire_a: &Wire, wire_b: &Wire) -> SketchResult<Shell> {
+    truck_builder::try_wire_homotopy(wire_a, wire_b)
+        .map_err(|e| SketchError::TruckFaceError(format!("{:?}", e)))
+}
+
+/// Planar face bounded by an outer wire followed by hole wires
+pub(crate) fn planar_face(wires: Vec<Wire>, plane: &Plane) -> SketchResult<Face> {
+    Face::try_new(wires, Surface::Plane(plane.to_truck_plane()?))
+        .map_err(|e| SketchError::TruckFaceError(format!("{:?}", e)))
+}
+
+/// Copy of a loop with the requested winding
+pub(crate) fn oriented(l: &Loop2D, ccw: bool) -> Loop2D {
+    if l.is_ccw() == ccw {
+        l.clone()
+    } else {
+        l.reversed()
+    }
+}
+
+fn loop_center(l: &Loop2D) -> Point2 {
+    let bbox = l.bounding_box().expect("loops are never empty");
+    bbox.min.midpoint(bbox.max)
+}
+
+/// Direction from a loop's center to its start point
+pub(crate) fn seam_direction(l: &Loop2D) -> Vector2 {
+    l.curves()[0].start() - loop_center(l)
+}
+
+/// Copy of a loop that starts at the point lying in `direction` from its
+/// center, splitting a curve if needed
+pub(crate) fn align_seam(l: &Loop2D, direction: Vector2) -> SketchResult<Loop2D> {
+    let center = loop_center(l);
+    let angle = direction.y.atan2(direction.x);
+    let score = |p: Point2| {
+        let d = p - center;
+        (d.y.atan2(d.x) - angle).cos()
+    };
+
+    let curves = l.curves();
+    let mut best = (0, 0.0, f64::NEG_INFINITY);
+    for (k, curve) in curves.iter().enumerate() {
+        for i in 0..SEAM_SAMPLES {
+            let t = i as f64 / SEAM_SAMPLES as f64;
+            let s = score(curve.point_at(t));
+            if s > best.2 {
+                best = (k, t, s);
+            }
+        }
+    }
+
+    let (k, t, _) = best;
+    let mut result = Vec::with_capacity(curves.len() + 1);
+    if t == 0.0 {
+        result.extend_from_slice(&curves[k..]);
+        result.extend_from_slice(&curves[..k]);
+    } else {
+        result.push(subcurve(&curves[k], t, 1.0)?);
+        result.extend_from_slice(&curves[k + 1..]);
+        result.extend_from_slice(&curves[..k]);
+        result.push(subcurve(&curves[k], 0.0, t)?);
+    }
+    Ok(Loop2D::new_unchecked(result))
+}
+
+/// Split loops so they all have the same number of curves.
+///
+/// `anchors[i]` lists vertex indices of loop `i` that must correspond across
+/// loops (the first is always 0). Within each span between anchors the
+/// breakpoints are the union of the loops' vertex arc-length fractions.
+pub(crate) fn synchronize(loops: &[Loop2D], anchors: &[Vec<usize>]) -> SketchResult<Vec<Loop2D>> {
+    let spans = anchors[0].len();
+    let mut result = vec![Vec::new(); loops.len()];
+
+    for s in 0..spans {
+        let chains: Vec<&[Curve2D]> = loops
+            .iter()
+            .zip(anchors)
+            .map(|(l, a)| {
+                let end = a.get(s + 1).copied().unwrap_or(l.len());
+                &l.curves()[a[s]..end]
+            })
+            .collect();
+        let fractions: Vec<Vec<f64>> = chains.iter().map(|c| vertex_fractions(c)).collect();
+
+        let mut breaks: Vec<f64> = fractions.iter().flatten().copied().collect();
+        breaks.sort_by(f64::total_cmp);
+        breaks.dedup_by(|a, b| (*a - *b).abs() < FRACTION_TOLERANCE);
+        // Closed loops need at least two edges
+        while spans == 1 && breaks.len() < 2 {
+            let last = *breaks.last().unwrap_or(&0.0);
+            breaks.push((last + 1.0) / 2.0);
+        }
+
+        for ((chain, starts), out) in chains.iter().zip(&fractions).zip(&mut result) {
+            out.extend(split_chain(chain, starts, &breaks)?);
+        }
+    }
+
+    Ok(result.into_iter().map(Loop2D::new_unchecked).collect())
+}
+
+/// Arc-length fraction at the start of each curve of a chain
+fn vertex_fractions(curves: &[Curve2D]) -> Vec<f64> {
+    let total: f64 = curves.iter().map(|c| c.length()).sum();
+    let mut acc = 0.0;
+    curves
+        .iter()
+        .map(|c| {
+            let f = acc / total;
+            acc += c.length();
+            f
+        })
+        .collect()
+}
+
+fn split_chain(curves: &[Curve2D], starts: &[f64], breaks: &[f64]) -> SketchResult<Vec<Curve2D>> {
+    let mut pieces = Vec::with_capacity(breaks.len());
+    for (i, &f0) in breaks.iter().enumerate() {
+        let f1 = breaks.get(i + 1).copied().unwrap_or(1.0);
+        // The curve containing this interval
+        let k = starts
+            .iter()
+            .rposition(|&s| s <= f0 + FRACTION_TOLERANCE)
+            .unwrap_or(0);
+        let a = starts[k];
+        let b = starts.get(k + 1).copied().unwrap_or(1.0);
+        let t0 = ((f0 - a) / (b - a)).clamp(0.0, 1.0);
+        let t1 = ((f1 - a) / (b - a)).clamp(0.0, 1.0);
+        pieces.push(subcurve(&curves[k], t0, t1)?);
+    }
+    Ok(pieces)
+}
+
+/// Portion of a curve between normalized parameters `t0 < t1`
+fn subcurve(curve: &Curve2D, t0: f64, t1: f64) -> SketchResult<Curve2D> {
+    if t0 <= FRACTION_TOLERANCE && t1 >= 1.0 - FRACTION_TOLERANCE {
+        if let Curve2D::Circle(circle) = curve {
+            return Ok(Curve2D::Arc(circle.to_arc()));
+        }
+        return Ok(curve.clone());
+    }
+    match curve {
+        Curve2D::Line(_) => Ok(Curve2D::Line(Line2D::new(
+            curve.point_at(t0),
+            curve.point_at(t1),
+        )?)),
+        Curve2D::Arc(arc) => Ok(Curve2D::Arc(sub_arc(arc, t0, t1)?)),
+        Curve2D::Circle(circle) => Ok(Curve2D::Arc(sub_arc(&circle.to_arc(), t0, t1)?)),
+        Curve2D::BSpline(spline) => {
+            let (r0, r1) = spline.inner().range_tuple();
+            let mut piece = spline.inner().clone();
+            if t0 > FRACTION_TOLERANCE {
+                piece = piece.cut(r0 + t0 * (r1 - r0));
+            }
+            if t1 < 1.0 - FRACTION_TOLERANCE {
+                piece.cut(r0 + t1 * (r1 - r0));
+            }
+            Ok(Curve2D::BSpline(BSpline2D::from_truck_curve(piece)))
+        }
+    }
+}
+
+fn sub_arc(arc: &Arc2D, t0: f64, t1: f64) -> SketchResult<Arc2D> {
+    let sweep = arc.sweep_angle();
+    Arc2D::new(
+        arc.center(),
+        arc.radius(),
+        arc.start_angle() + t0 * sweep,
+        (t1 - t0) * sweep,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::Shapes;
+
+    fn mesh_volume(solid: &Solid) -> f64 {
+        GpuMesh::from_solid(solid, 0.01).volume()
+    }
+
+    #[test]
+    fn test_square_to_round_loft() {
+        let square = Sketch::new(Shapes::rectangle_centered(Point2::origin(), 10.0, 10.0).unwrap());
+        let round = Sketch::new(Shapes::circle(Point2::origin(), 5.0).unwrap());
+        let bottom = Plane::xy();
+        let top = Plane::xy_at(10.0);
+
+        let solid = square.loft(&bottom, &round, &top).unwrap();
+        let volume = mesh_volume(&solid);
+        // Between the cylinder (~785) and the box (1000)
+        assert!(volume > 785.0 && volume < 1000.0, "volume {}", volume);
+
+        // Lofting downward still yields an outward-facing solid
+        let solid = round.loft(&top, &square, &bottom).unwrap();
+        assert!((mesh_volume(&solid) - volume).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_loft_requires_matching_holes() {
+        let outer = Shapes::circle(Point2::origin(), 10.0).unwrap();
+        let hole = Shapes::circle(Point2::origin(), 2.0).unwrap();
+        let a = Sketch::with_holes(outer.clone(), vec![hole]);
+        let b = Sketch::new(outer);
+        assert!(matches!(
+            a.loft(Plane::xy(), &b, Plane::xy_at(5.0)),
+            Err(SketchError::IncompatibleProfiles(_))
+        ));
+    }
+
+    #[test]
+    fn test_multi_section_loft() {
+        let hull = Loft::new()
+            .section(
+                Sketch::new(Shapes::rectangle_centered(Point2::origin(), 8.0, 4.0).unwrap()),
+                Plane::xy(),
+            )
+            .section(
+                Sketch::new(Shapes::circle(Point2::origin(), 4.0).unwrap()),
+                Plane::xy_at(5.0),
+            )
+            .section(
+                Sketch::new(Shapes::rectangle_centered(Point2::origin(), 2.0, 2.0).unwrap()),
+                Plane::xy_at(10.0),
+            )
+            .build()
+            .unwrap();
+        let volume = mesh_volume(&hull);
+        assert!(volume > 100.0 && volume < 400.0, "volume {}", volume);
+
+        assert!(Loft::new()
+            .section(
+                Sketch::new(Shapes::circle(Point2::origin(), 1.0).unwrap()),
+                Plane::xy()
+            )
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_guides_set_vertex_tracks() {
+        let square = Sketch::new(Shapes::rectangle_centered(Point2::origin(), 10.0, 10.0).unwrap());
+        let round = Sketch::new(Shapes::circle(Point2::origin(), 5.0).unwrap());
+        let r = 5.0 / 2f64.sqrt();
+
+        // Twist the corner (5, -5) round to the circle point at 45°
+        let solid = Loft::new()
+            .section(square.clone(), Plane::xy())
+            .section(round.clone(), Plane::xy_at(10.0))
+            .guide(vec![Point3::new(5.0, -5.0, 0.0), Point3::new(r, r, 10.0)])
+            .build()
+            .unwrap();
+        let has_track = solid.edge_iter().any(|e| {
+            let (a, b) = (e.front().point(), e.back().point());
+            let ends = [Point3::new(5.0, -5.0, 0.0), Point3::new(r, r, 10.0)];
+            (a.near(&ends[0]) && b.near(&ends[1])) || (a.near(&ends[1]) && b.near(&ends[0]))
+        });
+        assert!(has_track);
+
+        // A guide that never reaches the top section is rejected
+        let result = Loft::new()
+            .section(square, Plane::xy())
+            .section(round, Plane::xy_at(10.0))
+            .guide(vec![
+                Point3::new(5.0, -5.0, 0.0),
+                Point3::new(5.0, -5.0, 4.0),
+            ])
+            .build();
+        assert!(matches!(result, Err(SketchError::IncompatibleProfiles(_))));
+    }
+}
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use truck_modeling::InnerSpace;
 
 use crate::sketch::constants::*;
@@ -5,7 +6,7 @@ use crate::sketch::error::*;
 use crate::sketch::primitives::{BoundingBox2D, Curve2D, SketchCurve2D};
 
 /// A closed loop of connected curves
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Loop2D {
     curves: Vec<Curve2D>,
 }
@@ -138,6 +139,19 @@ impl Loop2D {
         Some(bbox)
     }
 
+    /// Signed enclosed area (positive = CCW)
+    ///
+    /// Lines, arcs and circles are integrated exactly (Green's theorem);
+    /// B-splines use Simpson's rule.
+    pub fn signed_area(&self) -> f64 {
+        self.curves.iter().map(curve_area_term).sum()
+    }
+
+    /// Unsigned enclosed area
+    pub fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
     /// Check winding direction (true = CCW, false = CW)
     #[allow(dead_code)]
     pub fn is_ccw(&self) -> bool {
@@ -174,3 +188,51 @@ impl Loop2D {
         Self { curves }
     }
 }
+
+/// Contribution of one curve to ½∮(x dy − y dx)
+fn curve_area_term(curve: &Curve2D) -> f64 {
+    match curve {
+        Curve2D::Line(line) => {
+            let (p, q) = (line.start(), line.end());
+            0.5 * (p.x * q.y - q.x * p.y)
+        }
+        Curve2D::Arc(arc) => {
+            let (c, r) = (arc.center(), arc.radius());
+            let (a, b) = (arc.start_angle(), arc.end_angle());
+            0.5 * (r * c.x * (b.sin() - a.sin()) - r * c.y * (b.cos() - a.cos()) + r * r * (b - a))
+        }
+        Curve2D::Circle(circle) => {
+            let area = circle.area();
+            if circle.is_ccw() {
+                area
+            } else {
+                -area
+            }
+        }
+        Curve2D::BSpline(spline) => {
+            const SAMPLES: usize = 256;
+            let f = |t: f64| {
+                let p = curve.point_at(t);
+                let d = curve.tangent_at(t);
+                p.x * d.y - p.y * d.x
+            };
+            let h = 1.0 / SAMPLES as f64;
+            let sum: f64 = (0..=SAMPLES)
+                .map(|i| {
+                    let w = if i == 0 || i == SAMPLES {
+                        1.0
+                    } else if i % 2 == 1 {
+                        4.0
+                    } else {
+                        2.0
+                    };
+                    w * f(i as f64 * h)
+                })
+                .sum();
+            // tangent_at is the raw derivative, so dt over [0, 1] scales by
+            // the knot span
+            let (t0, t1) = spline.param_range();
+            0.5 * sum * h / 3.0 * (t1 - t0)
+        }
+    }
+}
@@ -1,26 +1,57 @@
 pub mod builder;
+pub mod compare;
 pub mod constants;
+pub mod constraints;
+pub mod construction;
+pub mod diff;
 pub mod error;
+pub mod extrude;
+pub mod hatch;
+pub mod loft;
 pub mod loop2d;
+pub mod offset;
+pub mod param;
 pub mod plane;
 pub mod primitives;
+pub mod projection;
+pub mod regions;
+pub mod revolve;
 pub mod shapes;
+pub mod snap;
+pub mod sweep;
+pub mod text;
 pub mod topology;
+pub mod transform;
+pub mod wrap;
 
 pub use builder::SketchBuilder;
+pub use constraints::{Constraint, ConstraintSystem, EntityId};
+pub use construction::Construction;
+pub use diff::{CurveChange, LoopRef, SketchDiff};
 pub use error::{SketchError, SketchResult};
+pub use loft::Loft;
 pub use loop2d::Loop2D;
-pub use plane::Plane;
-pub use primitives::{Arc2D, BSpline2D, Circle2D, Curve2D, Line2D, SketchCurve2D};
+pub use param::{Expr, ParamLoop, ParamPath, ParamSet, ParamSketch};
+pub use plane::{Plane, StandardPlane};
+pub use primitives::{
+    Arc2D, BSpline2D, BoundingBox2D, Circle2D, Curve2D, Line2D, SketchCurve2D,
+};
 pub use shapes::Shapes;
+pub use snap::SnapSettings;
+pub use sweep::{polyline_path, spline_path};
+pub use text::{layout_along_path, Glyph};
 
+use serde::{Deserialize, Serialize};
 use truck_geometry::prelude::*;
 use truck_modeling::{builder as truck_builder, Face, Solid, Surface, Wire};
 
 /// A complete sketch with outer boundary and optional holes
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Sketch {
     pub outer: Loop2D,
     pub holes: Vec<Loop2D>,
+    /// Reference geometry, ignored by face/solid conversion
+    pub construction: Vec<Construction>,
 }
 
 impl Sketch {
@@ -29,12 +60,17 @@ impl Sketch {
         Self {
             outer,
             holes: Vec::new(),
+            construction: Vec::new(),
         }
     }
 
     /// Create sketch with holes
     pub fn with_holes(outer: Loop2D, holes: Vec<Loop2D>) -> Self {
-        Self { outer, holes }
+        Self {
+            outer,
+            holes,
+            construction: Vec::new(),
+        }
     }
 
     /// Add a hole
@@ -43,14 +79,45 @@ impl Sketch {
         self.holes.push(hole);
     }
 
+    /// Number of holes
+    pub fn hole_count(&self) -> usize {
+        self.holes.len()
+    }
+
+    /// Boundary curves, the outer loop's first and then each hole's
+    pub fn curves(&self) -> impl Iterator<Item = &Curve2D> {
+        std::iter::once(&self.outer)
+            .chain(&self.holes)
+            .flat_map(|l| l.curves())
+    }
+
+    /// Profile area (outer area minus hole areas)
+    pub fn area(&self) -> f64 {
+        self.outer.area() - self.holes.iter().map(|h| h.area()).sum::<f64>()
+    }
+
+    /// Total boundary length (outer plus holes)
+    pub fn perimeter(&self) -> f64 {
+        self.outer.total_length() + self.holes.iter().map(|h| h.total_length()).sum::<f64>()
+    }
+
+    /// Bounding box of the profile (construction geometry excluded)
+    pub fn bounding_box(&self) -> Option<BoundingBox2D> {
+        self.outer.bounding_box()
+    }
+
     /// Convert to truck Wire (outer boundary only)
     #[allow(dead_code)]
-    pub fn to_truck_wire(&self, plane: &Plane) -> SketchResult<Wire> {
+    pub fn to_truck_wire(&self, plane: impl Into<Plane>) -> SketchResult<Wire> {
         self.outer.to_truck_wire(plane)
     }
 
-    /// Convert to truck Face
-    pub fn to_truck_face(&self, plane: &Plane) -> SketchResult<Face> {
+    /// Convert to truck Face (construction geometry is not included)
+    pub fn to_truck_face(&self, plane: impl Into<Plane>) -> SketchResult<Face> {
+        let plane = &plane.into();
+        if !plane.is_orthonormal() {
+            return Err(SketchError::NonOrthonormalPlane);
+        }
         let truck_plane = plane.to_truck_plane()?;
         let outer_wire = self.outer.to_truck_wire(plane)?;
 
@@ -68,22 +135,20 @@ impl Sketch {
     }
 
     /// Extrude sketch into a solid
-    pub fn extrude(&self, plane: &Plane, direction: Vector3) -> SketchResult<Solid> {
+    pub fn extrude(&self, plane: impl Into<Plane>, direction: Vector3) -> SketchResult<Solid> {
         let face = self.to_truck_face(plane)?;
         Ok(truck_builder::tsweep(&face, direction))
     }
 
-    /// Revolve sketch into a solid
-    #[allow(dead_code)]
+    /// Revolve sketch into a solid (see [`Sketch::revolve_with_seam`])
     pub fn revolve(
         &self,
-        plane: &Plane,
+        plane: impl Into<Plane>,
         axis_origin: Point3,
         axis_direction: Vector3,
         angle: Rad<f64>,
     ) -> SketchResult<Solid> {
-        let face = self.to_truck_face(plane)?;
-        Ok(truck_builder::rsweep(&face, axis_origin, axis_direction, angle))
+        self.revolve_with_seam(plane, axis_origin, axis_direction, Rad(0.0), angle)
     }
 }
 
@@ -109,4 +174,40 @@ mod tests {
         let solid = sketch.extrude(&plane, Vector3::unit_z() * 10.0);
         assert!(solid.is_ok());
     }
+
+    #[test]
+    fn test_measurements() {
+        let outer = Shapes::rectangle(Point2::origin(), 10.0, 6.0).unwrap();
+        let hole = Shapes::circle(Point2::new(5.0, 3.0), 1.0).unwrap();
+        let sketch = Sketch::with_holes(outer, vec![hole]);
+
+        assert_eq!(sketch.hole_count(), 1);
+        assert!((sketch.area() - (60.0 - std::f64::consts::PI)).abs() < 1e-9);
+        assert!((sketch.perimeter() - (32.0 + 2.0 * std::f64::consts::PI)).abs() < 1e-6);
+
+        let bbox = sketch.bounding_box().unwrap();
+        assert!((bbox.max.x - bbox.min.x - 10.0).abs() < 1e-9);
+        assert!((bbox.max.y - bbox.min.y - 6.0).abs() < 1e-9);
+
+        let rounded = Shapes::rounded_rectangle(Point2::origin(), 10.0, 6.0, 1.0).unwrap();
+        let expected = 60.0 - (4.0 - std::f64::consts::PI);
+        assert!((Sketch::new(rounded).area() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_area_of_spline_with_wide_knots() {
+        // Parabolic arch under a chord: 2/3 · base · height
+        let knots = KnotVec::from(vec![0.0, 0.0, 0.0, 5.0, 5.0, 5.0]);
+        let points = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 2.0),
+            Point2::new(2.0, 0.0),
+        ];
+        let arch = BSpline2D::from_truck_curve(BSplineCurve::new(knots, points));
+        let chord = Line2D::new(Point2::new(2.0, 0.0), Point2::new(0.0, 0.0)).unwrap();
+        let outer = Loop2D::new(vec![Curve2D::BSpline(arch), Curve2D::Line(chord)]).unwrap();
+
+        assert!((outer.signed_area() + 4.0 / 3.0).abs() < 1e-9);
+        assert!((Sketch::new(outer).area() - 4.0 / 3.0).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,311 @@
+use crate::sketch::constants::*;
+use crate::sketch::error::*;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::primitives::{Arc2D, BSpline2D, Circle2D, Curve2D, Line2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use std::f64::consts::{PI, TAU};
+use truck_geometry::prelude::*;
+
+impl Loop2D {
+    /// Offset the loop by `distance` away from the region it encloses
+    /// (negative shrinks it).
+    ///
+    /// Lines move parallel, arcs and circles change radius about the same
+    /// center, and B-splines move their control points along the curve
+    /// normal (approximate). Corners are re-joined with sharp (mitre)
+    /// intersections.
+    pub fn offset(&self, distance: f64) -> SketchResult<Loop2D> {
+        // Distance to the left of the direction of travel
+        let left = if self.is_ccw() { -distance } else { distance };
+        Ok(Loop2D::new_unchecked(offset_chain(self.curves(), left, true)?))
+    }
+}
+
+impl Sketch {
+    /// Grow the profile by `distance` (outer loop outward, holes inward);
+    /// negative shrinks it. Construction geometry is kept unchanged.
+    pub fn offset(&self, distance: f64) -> SketchResult<Sketch> {
+        Ok(Sketch {
+            outer: self.outer.offset(distance)?,
+            holes: self
+                .holes
+                .iter()
+                .map(|h| h.offset(-distance))
+                .collect::<SketchResult<_>>()?,
+            construction: self.construction.clone(),
+        })
+    }
+}
+
+/// Offset connected curves by `left` to the left of their direction of
+/// travel, re-joining neighbours with mitred corners. For an open chain the
+/// two free ends stay square to their curves.
+pub(crate) fn offset_chain(
+    chain: &[Curve2D],
+    left: f64,
+    closed: bool,
+) -> SketchResult<Vec<Curve2D>> {
+    let curves = chain
+        .iter()
+        .map(|c| offset_curve(c, left))
+        .collect::<SketchResult<Vec<_>>>()?;
+    let n = curves.len();
+    if n < 2 {
+        return Ok(curves);
+    }
+
+    // joints[i] sits between curve i and curve i + 1
+    let joints: Vec<Point2> = (0..n)
+        .map(|i| {
+            let next = (i + 1) % n;
+            if !closed && next == 0 {
+                return Ok(curves[i].end());
+            }
+            join(&curves[i], &curves[next], chain[next].start())
+        })
+        .collect::<SketchResult<_>>()?;
+    (0..n)
+        .map(|i| {
+            let start = if !closed && i == 0 {
+                curves[0].start()
+            } else {
+                joints[(i + n - 1) % n]
+            };
+            with_ends(&curves[i], start, joints[i])
+        })
+        .collect()
+}
+
+fn left_normal(v: Vector2) -> Vector2 {
+    Vector2::new(-v.y, v.x).normalize()
+}
+
+fn collapsed(radius: f64) -> SketchError {
+    SketchError::OffsetFailed(format!("a round collapses (radius {:.3e})", radius))
+}
+
+fn offset_curve(curve: &Curve2D, left: f64) -> SketchResult<Curve2D> {
+    match curve {
+        Curve2D::Line(line) => {
+            let shift = left_normal(line.end() - line.start()) * left;
+            Ok(Curve2D::Line(Line2D::new(
+                line.start() + shift,
+                line.end() + shift,
+            )?))
+        }
+        Curve2D::Arc(arc) => {
+            // The center lies to the left of a CCW arc
+            let radius = if arc.is_ccw() {
+                arc.radius() - left
+            } else {
+                arc.radius() + left
+            };
+            if radius <= DEGENERATE_TOLERANCE {
+                return Err(collapsed(radius));
+            }
+            Ok(Curve2D::Arc(Arc2D::new(
+                arc.center(),
+                radius,
+                arc.start_angle(),
+                arc.sweep_angle(),
+            )?))
+        }
+        Curve2D::Circle(circle) => {
+            let radius = if circle.is_ccw() {
+                circle.radius() - left
+            } else {
+                circle.radius() + left
+            };
+            if radius <= DEGENERATE_TOLERANCE {
+                return Err(collapsed(radius));
+            }
+            let seam = circle.start() - circle.center();
+            Ok(Curve2D::Circle(Circle2D::with_seam(
+                circle.center(),
+                radius,
+                seam.y.atan2(seam.x),
+                circle.is_ccw(),
+            )?))
+        }
+        Curve2D::BSpline(spline) => {
+            let inner = spline.inner();
+            let knots = inner.knot_vec();
+            let degree = inner.degree();
+            let points = inner
+                .control_points()
+                .iter()
+                .enumerate()
+                .map(|(i, &p)| {
+                    // Greville abscissa of control point i
+                    let u = (1..=degree).map(|k| knots[i + k]).sum::<f64>() / degree.max(1) as f64;
+                    p + left_normal(inner.der(u)) * left
+                })
+                .collect();
+            Ok(Curve2D::BSpline(BSpline2D::from_truck_curve(
+                BSplineCurve::new(knots.clone(), points),
+            )))
+        }
+    }
+}
+
+/// Carrier circle of an arc or circle
+fn carrier_circle(curve: &Curve2D) -> Option<(Point2, f64)> {
+    match curve {
+        Curve2D::Arc(arc) => Some((arc.center(), arc.radius())),
+        Curve2D::Circle(circle) => Some((circle.center(), circle.radius())),
+        _ => None,
+    }
+}
+
+/// Meeting point of two consecutive offset curves, nearest the original
+/// vertex when the carriers intersect more than once
+fn join(a: &Curve2D, b: &Curve2D, vertex: Point2) -> SketchResult<Point2> {
+    let (end, start) = (a.end(), b.start());
+    if (end - start).magnitude() < POINT_TOLERANCE {
+        return Ok(end);
+    }
+
+    let nearest = |candidates: Vec<Point2>| {
+        candidates.into_iter().min_by(|p, q| {
+            (p - vertex)
+                .magnitude()
+                .total_cmp(&(q - vertex).magnitude())
+        })
+    };
+    let point = match (a, b, carrier_circle(a), carrier_circle(b)) {
+        (Curve2D::Line(l), Curve2D::Line(m), _, _) => line_line(
+            l.start(),
+            l.end() - l.start(),
+            m.start(),
+            m.end() - m.start(),
+        ),
+        (Curve2D::Line(l), _, _, Some((c, r))) => {
+            nearest(line_circle(l.start(), l.end() - l.start(), c, r))
+        }
+        (_, Curve2D::Line(m), Some((c, r)), _) => {
+            nearest(line_circle(m.start(), m.end() - m.start(), c, r))
+        }
+        (_, _, Some((c0, r0)), Some((c1, r1))) => nearest(circle_circle(c0, r0, c1, r1)),
+        // B-spline joints: meet halfway
+        _ => Some(end.midpoint(start)),
+    };
+    point.ok_or_else(|| {
+        SketchError::OffsetFailed(format!(
+            "neighbouring curves no longer meet near ({:.3}, {:.3})",
+            vertex.x, vertex.y
+        ))
+    })
+}
+
+fn cross(u: Vector2, v: Vector2) -> f64 {
+    u.x * v.y - u.y * v.x
+}
+
+fn line_line(p: Point2, u: Vector2, q: Point2, v: Vector2) -> Option<Point2> {
+    let denom = cross(u, v);
+    if denom.abs() < ANGLE_TOLERANCE * u.magnitude() * v.magnitude() {
+        return None;
+    }
+    Some(p + u * (cross(q - p, v) / denom))
+}
+
+fn line_circle(p: Point2, u: Vector2, c: Point2, r: f64) -> Vec<Point2> {
+    let u = u.normalize();
+    let foot = p + u * (c - p).dot(u);
+    let h2 = r * r - (foot - c).magnitude2();
+    if h2 < -LENGTH_TOLERANCE {
+        return Vec::new();
+    }
+    let h = h2.max(0.0).sqrt();
+    vec![foot - u * h, foot + u * h]
+}
+
+fn circle_circle(c0: Point2, r0: f64, c1: Point2, r1: f64) -> Vec<Point2> {
+    let d = (c1 - c0).magnitude();
+    if d < DEGENERATE_TOLERANCE
+        || d > r0 + r1 + LENGTH_TOLERANCE
+        || d < (r0 - r1).abs() - LENGTH_TOLERANCE
+    {
+        return Vec::new();
+    }
+    let axis = (c1 - c0) / d;
+    let a = (r0 * r0 - r1 * r1 + d * d) / (2.0 * d);
+    let h = (r0 * r0 - a * a).max(0.0).sqrt();
+    let base = c0 + axis * a;
+    let perp = Vector2::new(-axis.y, axis.x);
+    vec![base - perp * h, base + perp * h]
+}
+
+fn wrap(angle: f64) -> f64 {
+    let w = (angle + PI).rem_euclid(TAU) - PI;
+    if w <= -PI {
+        w + TAU
+    } else {
+        w
+    }
+}
+
+/// Copy of an offset curve moved to new end points
+fn with_ends(curve: &Curve2D, start: Point2, end: Point2) -> SketchResult<Curve2D> {
+    match curve {
+        Curve2D::Line(_) => Ok(Curve2D::Line(Line2D::new(start, end).map_err(|_| {
+            SketchError::OffsetFailed("an edge collapses to zero length".to_string())
+        })?)),
+        Curve2D::Arc(arc) => {
+            let c = arc.center();
+            let angle = |p: Point2| (p.y - c.y).atan2(p.x - c.x);
+            let d0 = wrap(angle(start) - arc.start_angle());
+            let d1 = wrap(angle(end) - arc.end_angle());
+            let sweep = arc.sweep_angle() - d0 + d1;
+            if sweep * arc.sweep_angle() <= 0.0 {
+                return Err(SketchError::OffsetFailed("an arc collapses".to_string()));
+            }
+            Ok(Curve2D::Arc(Arc2D::new(
+                c,
+                arc.radius(),
+                arc.start_angle() + d0,
+                sweep,
+            )?))
+        }
+        Curve2D::Circle(_) => Ok(curve.clone()),
+        Curve2D::BSpline(spline) => {
+            let inner = spline.inner();
+            let mut points = inner.control_points().clone();
+            let last = points.len() - 1;
+            points[0] = start;
+            points[last] = end;
+            Ok(Curve2D::BSpline(BSpline2D::from_truck_curve(
+                BSplineCurve::new(inner.knot_vec().clone(), points),
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::Shapes;
+
+    #[test]
+    fn test_offset_rectangle_and_rounded() {
+        let rect = Shapes::rectangle(Point2::origin(), 10.0, 4.0).unwrap();
+        let grown = rect.offset(1.0).unwrap();
+        let bbox = grown.bounding_box().unwrap();
+        assert!((bbox.min - Point2::new(-1.0, -1.0)).magnitude() < 1e-9);
+        assert!((bbox.max - Point2::new(11.0, 5.0)).magnitude() < 1e-9);
+        assert!((grown.area() - 72.0).abs() < 1e-9);
+        assert!(grown.validate(POINT_TOLERANCE).is_ok());
+
+        // Shrinking a rounded rectangle by its corner radius leaves sharp corners
+        let rounded = Shapes::rounded_rectangle(Point2::origin(), 10.0, 6.0, 2.0).unwrap();
+        let shrunk = rounded.offset(-1.0).unwrap();
+        assert!(shrunk.validate(POINT_TOLERANCE).is_ok());
+        let expected = 8.0 * 4.0 - (4.0 - PI);
+        assert!((shrunk.area() - expected).abs() < 1e-9);
+
+        assert!(matches!(
+            rounded.offset(-2.5),
+            Err(SketchError::OffsetFailed(_))
+        ));
+    }
+}
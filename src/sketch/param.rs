@@ -0,0 +1,640 @@
+use crate::sketch::builder::SketchBuilder;
+use crate::sketch::error::*;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::shapes::Shapes;
+use crate::sketch::Sketch;
+use std::collections::HashMap;
+use std::str::FromStr;
+use truck_geometry::prelude::*;
+
+/// Values for named sketch parameters
+#[derive(Clone, Debug, Default)]
+pub struct ParamSet {
+    values: HashMap<String, f64>,
+}
+
+impl ParamSet {
+    /// Create an empty parameter set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a parameter (builder style)
+    pub fn with(mut self, name: &str, value: f64) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Set a parameter
+    pub fn set(&mut self, name: &str, value: f64) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Get a parameter value
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values.get(name).copied()
+    }
+
+    /// Check if a parameter is set
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Iterate over all parameters
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+}
+
+/// Arithmetic expression over named parameters
+///
+/// Supports `+ - * / ^`, parentheses, unary minus, the constant `pi`, and the
+/// functions `sin cos tan sqrt abs min max` (angles in radians).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Param(String),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    /// Parse an expression from text
+    pub fn parse(text: &str) -> SketchResult<Self> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.expr()?;
+        if parser.pos != parser.tokens.len() {
+            return Err(SketchError::ExpressionParse(format!(
+                "unexpected trailing input in '{}'",
+                text
+            )));
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate against a parameter set
+    pub fn eval(&self, params: &ParamSet) -> SketchResult<f64> {
+        Ok(match self {
+            Expr::Number(v) => *v,
+            Expr::Param(name) => params
+                .get(name)
+                .ok_or_else(|| SketchError::UnknownParameter(name.clone()))?,
+            Expr::Neg(a) => -a.eval(params)?,
+            Expr::Add(a, b) => a.eval(params)? + b.eval(params)?,
+            Expr::Sub(a, b) => a.eval(params)? - b.eval(params)?,
+            Expr::Mul(a, b) => a.eval(params)? * b.eval(params)?,
+            Expr::Div(a, b) => a.eval(params)? / b.eval(params)?,
+            Expr::Pow(a, b) => a.eval(params)?.powf(b.eval(params)?),
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|a| a.eval(params))
+                    .collect::<SketchResult<Vec<_>>>()?;
+                call_function(name, &args)?
+            }
+        })
+    }
+
+    /// Names of all parameters referenced by this expression
+    pub fn parameters(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_parameters(&mut names);
+        names
+    }
+
+    fn collect_parameters(&self, names: &mut Vec<String>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Param(name) => {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            Expr::Neg(a) => a.collect_parameters(names),
+            Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Mul(a, b)
+            | Expr::Div(a, b)
+            | Expr::Pow(a, b) => {
+                a.collect_parameters(names);
+                b.collect_parameters(names);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|a| a.collect_parameters(names)),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = SketchError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl From<f64> for Expr {
+    fn from(v: f64) -> Self {
+        Expr::Number(v)
+    }
+}
+
+fn call_function(name: &str, args: &[f64]) -> SketchResult<f64> {
+    let arity_error = || {
+        SketchError::ExpressionParse(format!(
+            "wrong number of arguments to '{}': got {}",
+            name,
+            args.len()
+        ))
+    };
+    match (name, args) {
+        ("sin", [a]) => Ok(a.sin()),
+        ("cos", [a]) => Ok(a.cos()),
+        ("tan", [a]) => Ok(a.tan()),
+        ("sqrt", [a]) => Ok(a.sqrt()),
+        ("abs", [a]) => Ok(a.abs()),
+        ("min", [a, b]) => Ok(a.min(*b)),
+        ("max", [a, b]) => Ok(a.max(*b)),
+        ("sin" | "cos" | "tan" | "sqrt" | "abs" | "min" | "max", _) => Err(arity_error()),
+        _ => Err(SketchError::ExpressionParse(format!(
+            "unknown function '{}'",
+            name
+        ))),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+fn tokenize(text: &str) -> SketchResult<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponent (e.g. 1e-3)
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let value = literal.parse().map_err(|_| {
+                SketchError::ExpressionParse(format!("invalid number '{}'", literal))
+            })?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push(Token::Op(c));
+            i += 1;
+        } else {
+            return Err(SketchError::ExpressionParse(format!(
+                "unexpected character '{}' in '{}'",
+                c, text
+            )));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive-descent parser: expr → term (('+'|'-') term)*
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: char) -> SketchResult<()> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(SketchError::ExpressionParse(format!("expected '{}'", op)))
+        }
+    }
+
+    fn expr(&mut self) -> SketchResult<Expr> {
+        let mut lhs = self.term()?;
+        loop {
+            if self.eat('+') {
+                lhs = Expr::Add(Box::new(lhs), Box::new(self.term()?));
+            } else if self.eat('-') {
+                lhs = Expr::Sub(Box::new(lhs), Box::new(self.term()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn term(&mut self) -> SketchResult<Expr> {
+        let mut lhs = self.unary()?;
+        loop {
+            if self.eat('*') {
+                lhs = Expr::Mul(Box::new(lhs), Box::new(self.unary()?));
+            } else if self.eat('/') {
+                lhs = Expr::Div(Box::new(lhs), Box::new(self.unary()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> SketchResult<Expr> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> SketchResult<Expr> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            // Right-associative
+            let exponent = self.unary()?;
+            return Ok(Expr::Pow(Box::new(base), Box::new(exponent)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> SketchResult<Expr> {
+        match self.peek().cloned() {
+            Some(Token::Number(v)) => {
+                self.pos += 1;
+                Ok(Expr::Number(v))
+            }
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                if self.eat('(') {
+                    let mut args = Vec::new();
+                    if !self.eat(')') {
+                        loop {
+                            args.push(self.expr()?);
+                            if self.eat(')') {
+                                break;
+                            }
+                            self.expect(',')?;
+                        }
+                    }
+                    Ok(Expr::Call(name, args))
+                } else if name == "pi" {
+                    Ok(Expr::Number(std::f64::consts::PI))
+                } else {
+                    Ok(Expr::Param(name))
+                }
+            }
+            Some(Token::Op('(')) => {
+                self.pos += 1;
+                let inner = self.expr()?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Some(Token::Op(c)) => Err(SketchError::ExpressionParse(format!("unexpected '{}'", c))),
+            None => Err(SketchError::ExpressionParse(
+                "unexpected end of expression".to_string(),
+            )),
+        }
+    }
+}
+
+/// A 2D point whose coordinates are expressions
+#[derive(Clone, Debug)]
+pub struct ParamPoint {
+    pub x: Expr,
+    pub y: Expr,
+}
+
+impl ParamPoint {
+    /// Parse both coordinates
+    pub fn new(x: &str, y: &str) -> SketchResult<Self> {
+        Ok(Self {
+            x: Expr::parse(x)?,
+            y: Expr::parse(y)?,
+        })
+    }
+
+    /// Evaluate to a concrete point
+    pub fn eval(&self, params: &ParamSet) -> SketchResult<Point2> {
+        Ok(Point2::new(self.x.eval(params)?, self.y.eval(params)?))
+    }
+}
+
+/// One drawing step of a parametric path (mirrors `SketchBuilder`)
+#[derive(Clone, Debug)]
+pub enum ParamCommand {
+    MoveTo(ParamPoint),
+    LineTo(ParamPoint),
+    Horizontal(Expr),
+    Vertical(Expr),
+    LineBy(Expr, Expr),
+    ArcTo {
+        end: ParamPoint,
+        center: ParamPoint,
+        ccw: bool,
+    },
+}
+
+/// A closed profile whose dimensions are expressions
+#[derive(Clone, Debug)]
+pub enum ParamLoop {
+    /// Path closed with a straight line back to its start
+    Path(Vec<ParamCommand>),
+    Rectangle {
+        corner: ParamPoint,
+        width: Expr,
+        height: Expr,
+    },
+    Circle {
+        center: ParamPoint,
+        radius: Expr,
+    },
+}
+
+impl ParamLoop {
+    /// Rectangle from corner and dimensions
+    pub fn rectangle(x: &str, y: &str, width: &str, height: &str) -> SketchResult<Self> {
+        Ok(ParamLoop::Rectangle {
+            corner: ParamPoint::new(x, y)?,
+            width: Expr::parse(width)?,
+            height: Expr::parse(height)?,
+        })
+    }
+
+    /// Circle from center and radius
+    pub fn circle(x: &str, y: &str, radius: &str) -> SketchResult<Self> {
+        Ok(ParamLoop::Circle {
+            center: ParamPoint::new(x, y)?,
+            radius: Expr::parse(radius)?,
+        })
+    }
+
+    /// Evaluate to a concrete loop
+    pub fn evaluate(&self, params: &ParamSet) -> SketchResult<Loop2D> {
+        match self {
+            ParamLoop::Rectangle {
+                corner,
+                width,
+                height,
+            } => Shapes::rectangle(
+                corner.eval(params)?,
+                width.eval(params)?,
+                height.eval(params)?,
+            ),
+            ParamLoop::Circle { center, radius } => {
+                Shapes::circle(center.eval(params)?, radius.eval(params)?)
+            }
+            ParamLoop::Path(commands) => {
+                let mut builder = SketchBuilder::new();
+                for command in commands {
+                    builder = match command {
+                        ParamCommand::MoveTo(p) => builder.move_to(p.eval(params)?),
+                        ParamCommand::LineTo(p) => builder.line_to(p.eval(params)?)?,
+                        ParamCommand::Horizontal(dx) => builder.horizontal(dx.eval(params)?)?,
+                        ParamCommand::Vertical(dy) => builder.vertical(dy.eval(params)?)?,
+                        ParamCommand::LineBy(dx, dy) => {
+                            builder.line_by(dx.eval(params)?, dy.eval(params)?)?
+                        }
+                        ParamCommand::ArcTo { end, center, ccw } => {
+                            builder.arc_to(end.eval(params)?, center.eval(params)?, *ccw)?
+                        }
+                    };
+                }
+                builder.close()
+            }
+        }
+    }
+}
+
+/// Fluent builder for parametric paths
+#[derive(Clone, Debug, Default)]
+pub struct ParamPath {
+    commands: Vec<ParamCommand>,
+}
+
+impl ParamPath {
+    /// Create an empty path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start at a point
+    pub fn move_to(mut self, x: &str, y: &str) -> SketchResult<Self> {
+        self.commands
+            .push(ParamCommand::MoveTo(ParamPoint::new(x, y)?));
+        Ok(self)
+    }
+
+    /// Draw a line to a point
+    pub fn line_to(mut self, x: &str, y: &str) -> SketchResult<Self> {
+        self.commands
+            .push(ParamCommand::LineTo(ParamPoint::new(x, y)?));
+        Ok(self)
+    }
+
+    /// Draw a horizontal line by dx
+    pub fn horizontal(mut self, dx: &str) -> SketchResult<Self> {
+        self.commands
+            .push(ParamCommand::Horizontal(Expr::parse(dx)?));
+        Ok(self)
+    }
+
+    /// Draw a vertical line by dy
+    pub fn vertical(mut self, dy: &str) -> SketchResult<Self> {
+        self.commands.push(ParamCommand::Vertical(Expr::parse(dy)?));
+        Ok(self)
+    }
+
+    /// Draw a line by relative offset
+    pub fn line_by(mut self, dx: &str, dy: &str) -> SketchResult<Self> {
+        self.commands
+            .push(ParamCommand::LineBy(Expr::parse(dx)?, Expr::parse(dy)?));
+        Ok(self)
+    }
+
+    /// Draw an arc to a point with given center
+    pub fn arc_to(
+        mut self,
+        end: (&str, &str),
+        center: (&str, &str),
+        ccw: bool,
+    ) -> SketchResult<Self> {
+        self.commands.push(ParamCommand::ArcTo {
+            end: ParamPoint::new(end.0, end.1)?,
+            center: ParamPoint::new(center.0, center.1)?,
+            ccw,
+        });
+        Ok(self)
+    }
+
+    /// Finish as a closed loop
+    pub fn close(self) -> ParamLoop {
+        ParamLoop::Path(self.commands)
+    }
+}
+
+/// A sketch whose dimensions are expressions over named parameters
+///
+/// Driving parameters get defaults via [`ParamSketch::param`] and can be
+/// overridden at evaluation time; derived parameters (`width = 2*t + 5`) are
+/// defined in order with [`ParamSketch::define`].
+#[derive(Clone, Debug)]
+pub struct ParamSketch {
+    defaults: ParamSet,
+    definitions: Vec<(String, Expr)>,
+    outer: ParamLoop,
+    holes: Vec<ParamLoop>,
+}
+
+impl ParamSketch {
+    /// Create a parametric sketch with an outer boundary
+    pub fn new(outer: ParamLoop) -> Self {
+        Self {
+            defaults: ParamSet::new(),
+            definitions: Vec::new(),
+            outer,
+            holes: Vec::new(),
+        }
+    }
+
+    /// Declare a driving parameter with a default value
+    pub fn param(mut self, name: &str, default: f64) -> Self {
+        self.defaults.set(name, default);
+        self
+    }
+
+    /// Define a derived parameter from an expression
+    pub fn define(mut self, name: &str, expr: &str) -> SketchResult<Self> {
+        self.definitions
+            .push((name.to_string(), Expr::parse(expr)?));
+        Ok(self)
+    }
+
+    /// Add a hole
+    pub fn hole(mut self, hole: ParamLoop) -> Self {
+        self.holes.push(hole);
+        self
+    }
+
+    /// Default parameter values
+    pub fn defaults(&self) -> &ParamSet {
+        &self.defaults
+    }
+
+    /// Resolve driving and derived parameters for an evaluation
+    pub fn resolve(&self, params: &ParamSet) -> SketchResult<ParamSet> {
+        let mut resolved = self.defaults.clone();
+        for (name, value) in params.iter() {
+            resolved.set(name, value);
+        }
+        for (name, expr) in &self.definitions {
+            let value = expr.eval(&resolved)?;
+            resolved.set(name, value);
+        }
+        Ok(resolved)
+    }
+
+    /// Evaluate into a concrete sketch
+    pub fn evaluate(&self, params: &ParamSet) -> SketchResult<Sketch> {
+        let resolved = self.resolve(params)?;
+        let outer = self.outer.evaluate(&resolved)?;
+        let holes = self
+            .holes
+            .iter()
+            .map(|h| h.evaluate(&resolved))
+            .collect::<SketchResult<Vec<_>>>()?;
+        Ok(Sketch::with_holes(outer, holes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::primitives::SketchCurve2D;
+
+    #[test]
+    fn test_expression_precedence() {
+        let params = ParamSet::new().with("t", 3.0);
+        let expr = Expr::parse("2*t + 5").unwrap();
+        assert_eq!(expr.eval(&params).unwrap(), 11.0);
+
+        let expr = Expr::parse("-(t - 1)^2 / 2").unwrap();
+        assert_eq!(expr.eval(&params).unwrap(), -2.0);
+
+        let expr = Expr::parse("max(t, 4) + sqrt(16)").unwrap();
+        assert_eq!(expr.eval(&params).unwrap(), 8.0);
+    }
+
+    #[test]
+    fn test_expression_errors() {
+        assert!(Expr::parse("2 +").is_err());
+        assert!(Expr::parse("2 $ 3").is_err());
+        let expr = Expr::parse("w * 2").unwrap();
+        assert!(matches!(
+            expr.eval(&ParamSet::new()),
+            Err(SketchError::UnknownParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_param_sketch_family() {
+        let sketch = ParamSketch::new(ParamLoop::rectangle("0", "0", "width", "t").unwrap())
+            .param("t", 2.0)
+            .define("width", "2*t + 5")
+            .unwrap()
+            .hole(ParamLoop::circle("width/2", "t/2", "t/4").unwrap());
+
+        let small = sketch.evaluate(&ParamSet::new()).unwrap();
+        let bbox = small.outer.bounding_box().unwrap();
+        assert!((bbox.max.x - 9.0).abs() < 1e-9);
+
+        let large = sketch.evaluate(&ParamSet::new().with("t", 10.0)).unwrap();
+        let bbox = large.outer.bounding_box().unwrap();
+        assert!((bbox.max.x - 25.0).abs() < 1e-9);
+        assert_eq!(large.holes.len(), 1);
+        assert!((large.holes[0].curves()[0].length() - 5.0 * std::f64::consts::PI).abs() < 1e-9);
+    }
+}
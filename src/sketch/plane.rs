@@ -1,9 +1,15 @@
 use crate::sketch::constants::*;
 use crate::sketch::error::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use truck_geometry::prelude::*;
 
 /// A plane in 3D space for lifting 2D sketches
-#[derive(Clone, Debug)]
+///
+/// Serializes as its origin and axes; deserializing goes through
+/// [`Plane::new`], so stored axes are validated and squared up again.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(into = "PlaneDef", try_from = "PlaneDef")]
 pub struct Plane {
     origin: Point3,
     x_dir: Vector3,
@@ -12,20 +18,41 @@ pub struct Plane {
 
 impl Plane {
     /// Create plane from origin and two direction vectors
+    ///
+    /// `y_dir` is squared up against `x_dir` (Gram–Schmidt), so it only has
+    /// to point to the +Y side. The frame is right-handed by construction:
+    /// the normal is `x_dir × y_dir`; use [`Plane::flipped`] to face the
+    /// other way.
     pub fn new(origin: Point3, x_dir: Vector3, y_dir: Vector3) -> SketchResult<Self> {
         // Validate non-collinear
         let normal = x_dir.cross(y_dir);
-        if normal.magnitude() < DEGENERATE_TOLERANCE {
+        if normal.magnitude() < DEGENERATE_TOLERANCE * x_dir.magnitude().max(1.0) {
             return Err(SketchError::DegeneratePlane);
         }
 
+        let x_dir = x_dir.normalize();
         Ok(Self {
             origin,
-            x_dir: x_dir.normalize(),
-            y_dir: y_dir.normalize(),
+            x_dir,
+            y_dir: (y_dir - x_dir * y_dir.dot(x_dir)).normalize(),
         })
     }
 
+    /// Same plane facing the other way (Y reversed, X kept)
+    pub fn flipped(&self) -> Self {
+        Self {
+            y_dir: -self.y_dir,
+            ..self.clone()
+        }
+    }
+
+    /// Whether the axes are unit length and perpendicular
+    pub fn is_orthonormal(&self) -> bool {
+        (self.x_dir.magnitude() - 1.0).abs() < ANGLE_TOLERANCE
+            && (self.y_dir.magnitude() - 1.0).abs() < ANGLE_TOLERANCE
+            && self.x_dir.dot(self.y_dir).abs() < ANGLE_TOLERANCE
+    }
+
     /// XY plane at origin
     pub fn xy() -> Self {
         Self {
@@ -65,6 +92,24 @@ impl Plane {
         }
     }
 
+    /// Plane through `origin` facing `normal`, with in-plane axes chosen
+    /// so the standard planes come out as [`Plane::xy`], [`Plane::xz`] and
+    /// [`Plane::yz`]: X is `Z × normal`, or world X when the normal is
+    /// (anti)parallel to Z
+    pub fn from_normal(origin: Point3, normal: Vector3) -> SketchResult<Self> {
+        if normal.magnitude() < DEGENERATE_TOLERANCE {
+            return Err(SketchError::DegeneratePlane);
+        }
+        let normal = normal.normalize();
+        let x_dir = Vector3::unit_z().cross(normal);
+        let x_dir = if x_dir.magnitude() < ANGLE_TOLERANCE {
+            Vector3::unit_x()
+        } else {
+            x_dir.normalize()
+        };
+        Self::new(origin, x_dir, normal.cross(x_dir))
+    }
+
     /// Create from three points
     #[allow(dead_code)]
     pub fn from_three_points(p0: Point3, p1: Point3, p2: Point3) -> SketchResult<Self> {
@@ -76,6 +121,85 @@ impl Plane {
         Self::new(p0, x_dir, y_dir)
     }
 
+    /// Parallel copy moved `distance` along the normal
+    pub fn offset(&self, distance: f64) -> Self {
+        Self {
+            origin: self.origin + self.normal() * distance,
+            ..self.clone()
+        }
+    }
+
+    /// Copy rotated by `angle` about an axis (right-hand rule)
+    pub fn rotated_about(
+        &self,
+        axis_origin: Point3,
+        axis_direction: Vector3,
+        angle: Rad<f64>,
+    ) -> SketchResult<Self> {
+        if axis_direction.magnitude() < DEGENERATE_TOLERANCE {
+            return Err(SketchError::ZeroAxis);
+        }
+        let rotation = Matrix3::from_axis_angle(axis_direction.normalize(), angle);
+        Ok(Self {
+            origin: axis_origin + rotation * (self.origin - axis_origin),
+            x_dir: rotation * self.x_dir,
+            y_dir: rotation * self.y_dir,
+        })
+    }
+
+    /// Plane halfway between `a` and `b`: midway and parallel when they are
+    /// parallel, otherwise the bisector through their intersection line
+    /// (the one between the normals once `b` is flipped to face like `a`).
+    /// The origin is kept as close as possible to the two origins' midpoint.
+    pub fn midplane(a: &Plane, b: &Plane) -> SketchResult<Self> {
+        let (na, mut nb) = (a.normal(), b.normal());
+        if na.dot(nb) < 0.0 {
+            nb = -nb;
+        }
+        let mid = a.origin.midpoint(b.origin);
+        let line = na.cross(nb);
+        if line.magnitude() < ANGLE_TOLERANCE {
+            let gap = (b.origin - a.origin).dot(na);
+            return Ok(Self {
+                origin: mid + na * (gap / 2.0 - (mid - a.origin).dot(na)),
+                ..a.clone()
+            });
+        }
+
+        // Closest point to `mid` on the intersection line
+        let (da, db) = (na.dot(a.origin.to_vec()), nb.dot(b.origin.to_vec()));
+        let c = na.dot(nb);
+        let on_line = (na * (da - db * c) + nb * (db - da * c)) / line.magnitude2();
+        let x_dir = line.normalize();
+        let origin = Point3::from_vec(on_line) + x_dir * (mid.to_vec() - on_line).dot(x_dir);
+        let normal = (na + nb).normalize();
+        Self::new(origin, x_dir, normal.cross(x_dir))
+    }
+
+    /// Rigid transform carrying this plane's frame onto `other`'s, so that
+    /// geometry built on this plane lands in the same place on `other`
+    /// (`lift_point` on `self` maps to `lift_point` on `other`)
+    pub fn transform_to(&self, other: impl Into<Plane>) -> Matrix4 {
+        let other = &other.into();
+        let from = self.frame();
+        // Inverse of a rigid frame: transposed rotation, back-rotated origin
+        let rotation =
+            Matrix3::from_cols(from.x.truncate(), from.y.truncate(), from.z.truncate()).transpose();
+        let inverse =
+            Matrix4::from_translation(rotation * -self.origin.to_vec()) * Matrix4::from(rotation);
+        other.frame() * inverse
+    }
+
+    /// Local-to-world matrix (X, Y, normal, origin)
+    fn frame(&self) -> Matrix4 {
+        Matrix4::from_cols(
+            self.x_dir.extend(0.0),
+            self.y_dir.extend(0.0),
+            self.normal().extend(0.0),
+            self.origin.to_homogeneous(),
+        )
+    }
+
     /// Normal vector
     pub fn normal(&self) -> Vector3 {
         self.x_dir.cross(self.y_dir).normalize()
@@ -107,6 +231,11 @@ impl Plane {
         Point2::new(v.dot(self.x_dir), v.dot(self.y_dir))
     }
 
+    /// Project 3D vector to 2D (in-plane components)
+    pub fn project_vector(&self, v: Vector3) -> Vector2 {
+        Vector2::new(v.dot(self.x_dir), v.dot(self.y_dir))
+    }
+
     // Getters
     #[allow(dead_code)]
     pub fn origin(&self) -> Point3 {
@@ -122,9 +251,136 @@ impl Plane {
     }
 }
 
+/// Stored form of a [`Plane`]
+#[derive(Serialize, Deserialize)]
+struct PlaneDef {
+    origin: [f64; 3],
+    x_dir: [f64; 3],
+    y_dir: [f64; 3],
+}
+
+impl From<Plane> for PlaneDef {
+    fn from(plane: Plane) -> Self {
+        Self {
+            origin: plane.origin.into(),
+            x_dir: plane.x_dir.into(),
+            y_dir: plane.y_dir.into(),
+        }
+    }
+}
+
+impl TryFrom<PlaneDef> for Plane {
+    type Error = SketchError;
+
+    fn try_from(def: PlaneDef) -> SketchResult<Self> {
+        Plane::new(def.origin.into(), def.x_dir.into(), def.y_dir.into())
+    }
+}
+
+/// `origin (0, 0, 5), x (1, 0, 0), y (0, 1, 0), normal (0, 0, 1)`;
+/// a precision (`{:.3}`) applies to every coordinate
+impl fmt::Display for Plane {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let triples = [
+            ("origin", self.origin.to_vec()),
+            ("x", self.x_dir),
+            ("y", self.y_dir),
+            ("normal", self.normal()),
+        ];
+        for (i, (label, v)) in triples.into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            match f.precision() {
+                Some(p) => write!(f, "{} ({:.p$}, {:.p$}, {:.p$})", label, v.x, v.y, v.z)?,
+                None => write!(f, "{} ({}, {}, {})", label, v.x, v.y, v.z)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// World coordinate plane moved along its normal, usable wherever a
+/// plane is taken (`sketch.extrude(StandardPlane::XY(5.0), dir)`)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StandardPlane {
+    XY(f64),
+    XZ(f64),
+    YZ(f64),
+}
+
+impl StandardPlane {
+    pub fn to_plane(self) -> Plane {
+        match self {
+            StandardPlane::XY(offset) => Plane::xy().offset(offset),
+            StandardPlane::XZ(offset) => Plane::xz().offset(offset),
+            StandardPlane::YZ(offset) => Plane::yz().offset(offset),
+        }
+    }
+}
+
+impl From<&Plane> for Plane {
+    fn from(plane: &Plane) -> Self {
+        plane.clone()
+    }
+}
+
+impl From<StandardPlane> for Plane {
+    fn from(plane: StandardPlane) -> Self {
+        plane.to_plane()
+    }
+}
+
+impl From<&StandardPlane> for Plane {
+    fn from(plane: &StandardPlane) -> Self {
+        plane.to_plane()
+    }
+}
+
+/// Unit vector perpendicular to `axis`
+pub(crate) fn perpendicular(axis: Vector3) -> Vector3 {
+    let axis = axis.normalize();
+    let helper = if axis.x.abs() < 0.9 {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    };
+    (helper - axis * helper.dot(axis)).normalize()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn same_frame(a: &Plane, b: &Plane) -> bool {
+        (a.origin - b.origin).magnitude() < 1e-9
+            && (a.x_dir - b.x_dir).magnitude() < 1e-9
+            && (a.y_dir - b.y_dir).magnitude() < 1e-9
+    }
+
+    #[test]
+    fn test_offset_rotated_and_midplane() {
+        assert!(same_frame(&Plane::xy().offset(3.0), &Plane::xy_at(3.0)));
+
+        // XY turned a quarter about X stands up as the XZ plane, facing -Y
+        let turned = Plane::xy()
+            .rotated_about(Point3::origin(), Vector3::unit_x(), Rad(FRAC_PI_2))
+            .unwrap();
+        assert!(same_frame(&turned, &Plane::xz()));
+        assert!(Plane::xy()
+            .rotated_about(Point3::origin(), Vector3::zero(), Rad(1.0))
+            .is_err());
+
+        let mid = Plane::midplane(&Plane::xy(), &Plane::xy_at(4.0)).unwrap();
+        assert!(same_frame(&mid, &Plane::xy_at(2.0)));
+
+        // XZ (normal -Y) and YZ (normal +X) bisect along the Z axis
+        let mid = Plane::midplane(&Plane::xz(), &Plane::yz()).unwrap();
+        let expected = Vector3::new(1.0, -1.0, 0.0).normalize();
+        assert!((mid.normal() - expected).magnitude() < 1e-9);
+        assert!(mid.origin().to_vec().magnitude() < 1e-9);
+    }
 
     #[test]
     fn test_degenerate_plane() {
@@ -143,4 +399,84 @@ mod tests {
         let p3 = plane.lift_point(p2);
         assert!((p3 - Point3::new(1.0, 2.0, 0.0)).magnitude() < 1e-10);
     }
+
+    #[test]
+    fn test_from_normal_and_standard_planes() {
+        for (normal, expected) in [
+            (Vector3::unit_z(), Plane::xy()),
+            (-Vector3::unit_y(), Plane::xz()),
+            (Vector3::unit_x(), Plane::yz()),
+        ] {
+            assert!(same_frame(
+                &Plane::from_normal(Point3::origin(), normal).unwrap(),
+                &expected
+            ));
+        }
+        let tilted = Plane::from_normal(Point3::origin(), Vector3::new(1.0, 2.0, 3.0)).unwrap();
+        assert!((tilted.normal() - Vector3::new(1.0, 2.0, 3.0).normalize()).magnitude() < 1e-12);
+        assert!(Plane::from_normal(Point3::origin(), Vector3::zero()).is_err());
+
+        // Standard planes are offset along their own normals (XZ faces -Y)
+        let xz = Plane::from(StandardPlane::XZ(2.0));
+        assert!((xz.origin() - Point3::new(0.0, -2.0, 0.0)).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn test_new_squares_up_axes() {
+        let skewed = Plane::new(
+            Point3::origin(),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+        )
+        .unwrap();
+        assert!(skewed.is_orthonormal());
+        assert!((skewed.y_dir() - Vector3::unit_y()).magnitude() < 1e-12);
+        let p = skewed.lift_point(Point2::new(0.0, 1.0));
+        assert!((p - Point3::new(0.0, 1.0, 0.0)).magnitude() < 1e-12);
+
+        let flipped = Plane::xy().flipped();
+        assert!((flipped.normal() + Vector3::unit_z()).magnitude() < 1e-12);
+        assert!(flipped.is_orthonormal());
+    }
+
+    #[test]
+    fn test_transform_to() {
+        let from = Plane::xy().rotated_about(Point3::origin(), Vector3::unit_x(), Rad(0.3));
+        let from = from.unwrap().offset(2.0);
+        let to = Plane::yz().offset(-1.0);
+        let m = from.transform_to(&to);
+        for p in [Point2::new(0.0, 0.0), Point2::new(3.0, -1.5)] {
+            let moved = m.transform_point(from.lift_point(p));
+            assert!((moved - to.lift_point(p)).magnitude() < 1e-12);
+        }
+        let n = m.transform_vector(from.normal());
+        assert!((n - to.normal()).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn test_serde_round_trip_and_display() {
+        let plane = Plane::xz().offset(2.5);
+        let json = serde_json::to_string(&plane).unwrap();
+        assert_eq!(
+            json,
+            r#"{"origin":[0.0,-2.5,0.0],"x_dir":[1.0,0.0,0.0],"y_dir":[0.0,0.0,1.0]}"#
+        );
+        let back: Plane = serde_json::from_str(&json).unwrap();
+        assert!(same_frame(&plane, &back));
+
+        let collinear = r#"{"origin":[0,0,0],"x_dir":[1,0,0],"y_dir":[2,0,0]}"#;
+        assert!(serde_json::from_str::<Plane>(collinear).is_err());
+
+        assert_eq!(
+            format!("{:.1}", plane),
+            concat!(
+                "origin (0.0, -2.5, 0.0), x (1.0, 0.0, 0.0), ",
+                "y (0.0, 0.0, 1.0), normal (0.0, -1.0, 0.0)"
+            )
+        );
+        assert_eq!(
+            format!("{}", Plane::xy()),
+            "origin (0, 0, 0), x (1, 0, 0), y (0, 1, 0), normal (0, 0, 1)"
+        );
+    }
 }
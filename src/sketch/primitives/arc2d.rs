@@ -1,6 +1,7 @@
 use super::traits::{BoundingBox2D, SketchCurve2D};
 use crate::sketch::constants::*;
 use crate::sketch::error::*;
+use serde::{Deserialize, Serialize};
 use std::f64::consts::{PI, TAU};
 use truck_geometry::prelude::*;
 
@@ -9,7 +10,7 @@ use truck_geometry::prelude::*;
 /// - `sweep_angle > 0` means counter-clockwise (CCW)
 /// - `sweep_angle < 0` means clockwise (CW)
 /// - `|sweep_angle|` must be in (0, 2π] for valid arcs
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Arc2D {
     center: Point2,
     radius: f64,
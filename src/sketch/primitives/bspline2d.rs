@@ -1,9 +1,10 @@
 use super::traits::{BoundingBox2D, SketchCurve2D};
 use crate::sketch::error::*;
+use serde::{Deserialize, Serialize};
 use std::ops::Bound;
 use truck_geometry::prelude::*;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BSpline2D {
     curve: BSplineCurve<Point2>,
 }
@@ -66,7 +67,8 @@ impl BSpline2D {
         self.curve.control_points()
     }
 
-    fn param_range(&self) -> (f64, f64) {
+    /// Raw parameter range of the underlying curve, which `t ∈ [0, 1]` maps onto
+    pub(crate) fn param_range(&self) -> (f64, f64) {
         let (b0, b1) = self.curve.parameter_range();
         (bound_value(b0), bound_value(b1))
     }
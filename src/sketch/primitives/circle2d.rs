@@ -2,6 +2,7 @@ use super::arc2d::Arc2D;
 use super::traits::{BoundingBox2D, SketchCurve2D};
 use crate::sketch::constants::*;
 use crate::sketch::error::*;
+use serde::{Deserialize, Serialize};
 use std::f64::consts::{PI, TAU};
 use truck_geometry::prelude::*;
 
@@ -9,7 +10,7 @@ use truck_geometry::prelude::*;
 ///
 /// Unlike Arc2D, a Circle2D always represents a complete 360° curve.
 /// It has a seam point where start() == end().
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Circle2D {
     center: Point2,
     radius: f64,
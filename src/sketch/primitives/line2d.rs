@@ -1,9 +1,10 @@
 use super::traits::{BoundingBox2D, SketchCurve2D};
 use crate::sketch::constants::*;
 use crate::sketch::error::*;
+use serde::{Deserialize, Serialize};
 use truck_geometry::prelude::*;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Line2D {
     start: Point2,
     end: Point2,
@@ -10,10 +10,11 @@ pub use circle2d::Circle2D;
 pub use line2d::Line2D;
 pub use traits::{BoundingBox2D, SketchCurve2D};
 
+use serde::{Deserialize, Serialize};
 use truck_geometry::prelude::*;
 
 /// Unified curve type for heterogeneous collections
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Curve2D {
     Line(Line2D),
     Arc(Arc2D),
@@ -0,0 +1,230 @@
+use crate::sketch::constants::*;
+use crate::sketch::plane::Plane;
+use crate::sketch::primitives::{Arc2D, BSpline2D, Circle2D, Curve2D, Line2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use std::collections::HashSet;
+use truck_geometry::prelude::*;
+use truck_modeling::{Curve, Edge, Solid};
+
+/// Number of samples used to classify a projected edge
+const PROJECTION_SAMPLES: usize = 32;
+
+/// Tolerance for recognizing projected lines and arcs
+const PROJECTION_TOLERANCE: f64 = 1e-6;
+
+impl Sketch {
+    /// Project the edges of a solid onto a plane as 2D curves ("convert entities").
+    ///
+    /// Edges that project to lines or circular arcs are converted exactly; all
+    /// other edges are approximated by cubic B-splines. Edges perpendicular to
+    /// the plane (which project to a point) are skipped, as are duplicates.
+    pub fn project_edges(solid: &Solid, plane: impl Into<Plane>) -> Vec<Curve2D> {
+        let plane = &plane.into();
+        let mut seen = HashSet::new();
+        let mut curves: Vec<Curve2D> = Vec::new();
+
+        for edge in solid.edge_iter() {
+            if !seen.insert(edge.id()) {
+                continue;
+            }
+            if let Some(curve) = project_edge(&edge, plane) {
+                if !curves.iter().any(|c| same_projection(c, &curve)) {
+                    curves.push(curve);
+                }
+            }
+        }
+
+        curves
+    }
+}
+
+/// Project a single edge onto the plane
+fn project_edge(edge: &Edge, plane: &Plane) -> Option<Curve2D> {
+    let curve = edge.oriented_curve();
+    let (t0, t1) = curve.range_tuple();
+
+    let samples: Vec<Point2> = (0..=PROJECTION_SAMPLES)
+        .map(|i| {
+            let t = t0 + (t1 - t0) * i as f64 / PROJECTION_SAMPLES as f64;
+            plane.project_point(curve.subs(t))
+        })
+        .collect();
+
+    let start = samples[0];
+    let extent = samples
+        .iter()
+        .map(|p| (p - start).magnitude())
+        .fold(0.0, f64::max);
+
+    // Edge runs along the plane normal
+    if extent < PROJECTION_TOLERANCE {
+        return None;
+    }
+
+    if let Some(line) = fit_line(&samples) {
+        return Some(Curve2D::Line(line));
+    }
+
+    if let Some(arc) = fit_arc(&samples) {
+        return Some(arc);
+    }
+
+    let projected = ProjectedCurve { curve, plane };
+    if let Some(spline) =
+        BSplineCurve::cubic_approximation(&projected, (t0, t1), PROJECTION_TOLERANCE, 1e-3, 10)
+    {
+        return Some(Curve2D::BSpline(BSpline2D::from_truck_curve(spline)));
+    }
+
+    // Fall back to a polyline through the samples
+    BSpline2D::from_control_points(samples, 1)
+        .ok()
+        .map(Curve2D::BSpline)
+}
+
+/// Fit a line segment to collinear samples, spanning their extreme points
+fn fit_line(samples: &[Point2]) -> Option<Line2D> {
+    let start = samples[0];
+    let far = samples
+        .iter()
+        .copied()
+        .max_by(|a, b| (a - start).magnitude().total_cmp(&(b - start).magnitude()))?;
+    let dir = (far - start).normalize();
+
+    let collinear = samples.iter().all(|p| {
+        let v = p - start;
+        (v.x * dir.y - v.y * dir.x).abs() < PROJECTION_TOLERANCE
+    });
+    if !collinear {
+        return None;
+    }
+
+    // Extreme points along the line (closed edges seen edge-on fold back)
+    let params: Vec<f64> = samples.iter().map(|p| (p - start).dot(dir)).collect();
+    let (mut lo, mut hi) = (0, 0);
+    for (i, &t) in params.iter().enumerate() {
+        if t < params[lo] {
+            lo = i;
+        }
+        if t > params[hi] {
+            hi = i;
+        }
+    }
+
+    // Keep the edge direction when the projection does not fold back
+    let end = samples[samples.len() - 1];
+    if (end - samples[hi]).magnitude() < PROJECTION_TOLERANCE && lo == 0 {
+        return Line2D::new(start, end).ok();
+    }
+    Line2D::new(samples[lo], samples[hi]).ok()
+}
+
+/// Fit a circular arc (or full circle) to concyclic samples
+fn fit_arc(samples: &[Point2]) -> Option<Curve2D> {
+    let n = samples.len() - 1;
+    let start = samples[0];
+    let end = samples[n];
+    let closed = (end - start).magnitude() < PROJECTION_TOLERANCE;
+
+    // Pick three well-separated points on the curve
+    let (a, b, c) = if closed {
+        (start, samples[n / 3], samples[2 * n / 3])
+    } else {
+        (start, samples[n / 2], end)
+    };
+
+    let circle = Circle2D::from_three_points(a, b, c).ok()?;
+    let center = circle.center();
+    let radius = circle.radius();
+    let concyclic = samples.iter().all(|p| {
+        ((p - center).magnitude() - radius).abs() < PROJECTION_TOLERANCE * radius.max(1.0)
+    });
+    if !concyclic {
+        return None;
+    }
+
+    if closed {
+        let seam_angle = (start.y - center.y).atan2(start.x - center.x);
+        let v0 = samples[0] - center;
+        let v1 = samples[1] - center;
+        let ccw = v0.x * v1.y - v0.y * v1.x > 0.0;
+        return Circle2D::with_seam(center, radius, seam_angle, ccw)
+            .ok()
+            .map(Curve2D::Circle);
+    }
+
+    Arc2D::from_three_points(start, samples[n / 2], end)
+        .ok()
+        .map(Curve2D::Arc)
+}
+
+/// Check whether two projected curves cover the same geometry
+fn same_projection(a: &Curve2D, b: &Curve2D) -> bool {
+    let close =
+        |p: Point2, q: Point2| (p - q).magnitude() < POINT_TOLERANCE.max(PROJECTION_TOLERANCE);
+    let (a0, a1, am) = (a.start(), a.end(), a.point_at(0.5));
+    let (b0, b1, bm) = (b.start(), b.end(), b.point_at(0.5));
+
+    let forward = close(a0, b0) && close(a1, b1);
+    let backward = close(a0, b1) && close(a1, b0);
+    (forward || backward) && close(am, bm)
+}
+
+/// A 3D curve viewed through its projection onto a plane
+#[derive(Clone)]
+struct ProjectedCurve<'a> {
+    curve: Curve,
+    plane: &'a Plane,
+}
+
+impl ParametricCurve for ProjectedCurve<'_> {
+    type Point = Point2;
+    type Vector = Vector2;
+
+    fn subs(&self, t: f64) -> Point2 {
+        self.plane.project_point(self.curve.subs(t))
+    }
+
+    fn der(&self, t: f64) -> Vector2 {
+        self.plane.project_vector(self.curve.der(t))
+    }
+
+    fn der2(&self, t: f64) -> Vector2 {
+        self.plane.project_vector(self.curve.der2(t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::Shapes;
+
+    #[test]
+    fn test_project_box_edges() {
+        let rect = Shapes::rectangle(Point2::origin(), 10.0, 5.0).unwrap();
+        let solid = Sketch::new(rect)
+            .extrude(Plane::xy(), Vector3::new(0.0, 0.0, 2.0))
+            .unwrap();
+
+        let curves = Sketch::project_edges(&solid, Plane::xy());
+        assert_eq!(curves.len(), 4);
+        assert!(curves.iter().all(|c| matches!(c, Curve2D::Line(_))));
+    }
+
+    #[test]
+    fn test_project_cylinder_edges() {
+        let circle = Shapes::circle(Point2::new(1.0, 2.0), 3.0).unwrap();
+        let solid = Sketch::new(circle)
+            .extrude(Plane::xy(), Vector3::new(0.0, 0.0, 4.0))
+            .unwrap();
+
+        let curves = Sketch::project_edges(&solid, Plane::xy());
+        assert!(!curves.is_empty());
+        for curve in &curves {
+            let Curve2D::Arc(arc) = curve else {
+                panic!("expected arc, got {:?}", curve);
+            };
+            assert!((arc.radius() - 3.0).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::sketch::error::*;
+use crate::sketch::hatch::flatten_curve;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::primitives::{Curve2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use truck_geometry::prelude::*;
+
+impl Sketch {
+    /// Profiles bounded by `curves`, given in any order and direction.
+    ///
+    /// Curves are chained end to start within `tol` into closed loops;
+    /// chains that never close are left out. Each loop not inside another
+    /// becomes an outer boundary (CCW) with the loops directly inside it as
+    /// holes (CW), and loops inside a hole start a sketch of their own.
+    pub fn regions(curves: &[Curve2D], tol: f64) -> SketchResult<Vec<Sketch>> {
+        let loops = closed_loops(curves, tol)?;
+        let polygons: Vec<Vec<Point2>> = loops
+            .iter()
+            .map(|l| {
+                l.curves()
+                    .iter()
+                    .flat_map(|c| flatten_curve(c, tol))
+                    .collect()
+            })
+            .collect();
+
+        // A loop's parent is the smallest other loop around a point of it
+        let parents: Vec<Option<usize>> = (0..loops.len())
+            .map(|i| {
+                let probe = loops[i].curves()[0].point_at(0.5);
+                (0..loops.len())
+                    .filter(|&j| j != i && contains(&polygons[j], probe))
+                    .min_by(|&a, &b| loops[a].area().total_cmp(&loops[b].area()))
+            })
+            .collect();
+        let depth = |mut i: usize| {
+            let mut depth = 0;
+            while let Some(parent) = parents[i] {
+                depth += 1;
+                i = parent;
+            }
+            depth
+        };
+
+        let oriented = |l: &Loop2D, ccw: bool| {
+            if l.is_ccw() == ccw {
+                l.clone()
+            } else {
+                l.reversed()
+            }
+        };
+        let mut sketches = Vec::new();
+        for (i, l) in loops.iter().enumerate() {
+            if depth(i) % 2 == 1 {
+                continue;
+            }
+            let holes = (0..loops.len())
+                .filter(|&j| parents[j] == Some(i))
+                .map(|j| oriented(&loops[j], false))
+                .collect();
+            sketches.push(Sketch::with_holes(oriented(l, true), holes));
+        }
+        Ok(sketches)
+    }
+}
+
+/// Closed curves as they are, plus every chain of open curves that closes
+fn closed_loops(curves: &[Curve2D], tol: f64) -> SketchResult<Vec<Loop2D>> {
+    let mut loops = Vec::new();
+    let mut open = Vec::new();
+    for curve in curves {
+        if curve.is_closed(tol) {
+            loops.push(Loop2D::new(vec![curve.clone()])?);
+        } else {
+            open.push(curve);
+        }
+    }
+
+    let mut used = vec![false; open.len()];
+    for first in 0..open.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut chain = vec![open[first].clone()];
+        let start = chain[0].start();
+        let closed = loop {
+            let end = chain[chain.len() - 1].end();
+            if chain.len() > 1 && (end - start).magnitude() <= tol {
+                break true;
+            }
+            let next = (0..open.len()).filter(|&i| !used[i]).find_map(|i| {
+                if (open[i].start() - end).magnitude() <= tol {
+                    Some((i, open[i].clone()))
+                } else if (open[i].end() - end).magnitude() <= tol {
+                    Some((i, open[i].reversed()))
+                } else {
+                    None
+                }
+            });
+            match next {
+                Some((i, curve)) => {
+                    used[i] = true;
+                    chain.push(curve);
+                }
+                None => break false,
+            }
+        };
+        if closed {
+            let mut l = Loop2D::new_unchecked(chain);
+            l.heal_gaps(tol);
+            loops.push(Loop2D::new(l.curves().to_vec())?);
+        }
+    }
+    Ok(loops)
+}
+
+/// Even-odd point-in-polygon test
+fn contains(polygon: &[Point2], p: Point2) -> bool {
+    let mut inside = false;
+    for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        if (a.y <= p.y) != (b.y <= p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::constants::HEAL_TOLERANCE;
+    use crate::sketch::{Circle2D, Line2D, Shapes};
+
+    #[test]
+    fn test_regions_nest_holes_and_islands() {
+        // Square ring drawn as shuffled, partly reversed lines around a
+        // circular hole that holds a smaller square island
+        let square = |size: f64| {
+            let h = size / 2.0;
+            let p = [(-h, -h), (h, -h), (h, h), (-h, h)].map(|(x, y)| Point2::new(x, y));
+            (0..4)
+                .map(|i| Curve2D::Line(Line2D::new(p[i], p[(i + 1) % 4]).unwrap()))
+                .collect::<Vec<_>>()
+        };
+        let mut curves = square(10.0);
+        curves.swap(0, 2);
+        curves[1] = curves[1].reversed();
+        curves.push(Circle2D::new(Point2::origin(), 3.0).unwrap().into());
+        curves.extend(square(2.0));
+        // A dangling line never closes and is dropped
+        curves.push(
+            Line2D::new(Point2::new(20.0, 0.0), Point2::new(21.0, 0.0))
+                .unwrap()
+                .into(),
+        );
+
+        let sketches = Sketch::regions(&curves, HEAL_TOLERANCE).unwrap();
+        assert_eq!(sketches.len(), 2);
+        let ring = &sketches[0];
+        assert_eq!(ring.hole_count(), 1);
+        assert!(ring.outer.is_ccw() && !ring.holes[0].is_ccw());
+        assert!((ring.area() - (100.0 - 9.0 * std::f64::consts::PI)).abs() < 1e-9);
+        assert!((sketches[1].area() - 4.0).abs() < 1e-9);
+
+        let rect = Shapes::rectangle(Point2::origin(), 1.0, 1.0).unwrap();
+        let open = &rect.curves()[..3];
+        assert!(Sketch::regions(open, HEAL_TOLERANCE).unwrap().is_empty());
+    }
+}
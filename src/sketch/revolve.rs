@@ -0,0 +1,156 @@
+use crate::sketch::constants::*;
+use crate::sketch::error::*;
+use crate::sketch::plane::Plane;
+use crate::sketch::primitives::SketchCurve2D;
+use crate::sketch::Sketch;
+use std::f64::consts::TAU;
+use truck_geometry::prelude::*;
+use truck_modeling::{builder as truck_builder, Solid};
+
+/// Samples per curve when checking which side of the axis a profile lies on
+const SIDE_SAMPLES: usize = 16;
+
+impl Sketch {
+    /// Revolve by `angle` about an axis, starting from the profile turned
+    /// `seam` about the same axis
+    ///
+    /// `seam` places the start cap (or, for a full turn, the seam edges);
+    /// the sign of `angle` sets the direction. Partial turns are closed
+    /// with planar caps, and the shell always faces outward whichever way
+    /// the plane normal points. The profile must not cross the axis.
+    pub fn revolve_with_seam(
+        &self,
+        plane: impl Into<Plane>,
+        axis_origin: Point3,
+        axis_direction: Vector3,
+        seam: Rad<f64>,
+        angle: Rad<f64>,
+    ) -> SketchResult<Solid> {
+        let plane = &plane.into();
+        if axis_direction.magnitude() < DEGENERATE_TOLERANCE {
+            return Err(SketchError::InvalidRevolve(
+                "axis direction is zero".to_string(),
+            ));
+        }
+        if angle.0.abs() < ANGLE_TOLERANCE || !angle.0.is_finite() {
+            return Err(SketchError::InvalidRevolve(format!(
+                "angle must be non-zero, got {}",
+                angle.0
+            )));
+        }
+        let axis = axis_direction.normalize();
+        let angle = Rad(angle.0.clamp(-TAU, TAU));
+
+        // Signed distance of the profile from the axis, across the plane
+        let side = |p: Point2| {
+            (plane.lift_point(p) - axis_origin)
+                .cross(axis)
+                .dot(plane.normal())
+        };
+        let (lo, hi) = self
+            .outer
+            .curves()
+            .iter()
+            .flat_map(|c| {
+                (0..=SIDE_SAMPLES).map(move |i| c.point_at(i as f64 / SIDE_SAMPLES as f64))
+            })
+            .map(side)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), s| {
+                (lo.min(s), hi.max(s))
+            });
+        if lo < -LENGTH_TOLERANCE && hi > LENGTH_TOLERANCE {
+            return Err(SketchError::InvalidRevolve(
+                "profile crosses the axis".to_string(),
+            ));
+        }
+
+        let face = self.to_truck_face(plane)?;
+        let face = truck_builder::rotated(&face, axis_origin, axis, seam);
+        let mut solid = truck_builder::rsweep(&face, axis_origin, axis, angle);
+
+        // The sweep faces outward only when the profile moves along its normal
+        let bbox = self.outer.bounding_box().ok_or(SketchError::EmptyLoop)?;
+        let radial = plane.lift_point(bbox.min.midpoint(bbox.max)) - axis_origin;
+        let motion = axis.cross(radial) * angle.0.signum();
+        if motion.dot(plane.normal()) < 0.0 {
+            solid.not();
+        }
+        Ok(solid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::Shapes;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    fn volume(solid: &Solid) -> f64 {
+        GpuMesh::from_solid(solid, 0.01).volume()
+    }
+
+    #[test]
+    fn test_partial_revolve_orientation() {
+        // Ring section x = 2..4, z = 0..2 revolved about Z
+        let profile = Sketch::new(Shapes::rectangle(Point2::new(2.0, 0.0), 2.0, 2.0).unwrap());
+        let quarter = FRAC_PI_2 / 2.0 * (16.0 - 4.0) * 2.0;
+        let flipped = Plane::new(Point3::origin(), Vector3::unit_x(), -Vector3::unit_z()).unwrap();
+        let flipped_profile =
+            Sketch::new(Shapes::rectangle(Point2::new(2.0, -2.0), 2.0, 2.0).unwrap());
+        for (sketch, plane) in [(&profile, Plane::xz()), (&flipped_profile, flipped)] {
+            for angle in [FRAC_PI_2, -FRAC_PI_2] {
+                let solid = sketch
+                    .revolve(&plane, Point3::origin(), Vector3::unit_z(), Rad(angle))
+                    .unwrap();
+                assert_eq!(solid.boundaries().len(), 1);
+                assert!(
+                    (volume(&solid) - quarter).abs() < 0.05,
+                    "{}",
+                    volume(&solid)
+                );
+            }
+        }
+        let full = profile
+            .revolve(
+                Plane::xz(),
+                Point3::origin(),
+                Vector3::unit_z(),
+                Rad(2.0 * PI),
+            )
+            .unwrap();
+        assert!((volume(&full) - 4.0 * quarter).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_revolve_seam_and_axis_checks() {
+        let profile = Sketch::new(Shapes::rectangle(Point2::new(2.0, 0.0), 2.0, 2.0).unwrap());
+        // Start a quarter turn round, so the caps lie in the YZ and -XZ planes
+        let solid = profile
+            .revolve_with_seam(
+                Plane::xz(),
+                Point3::origin(),
+                Vector3::unit_z(),
+                Rad(FRAC_PI_2),
+                Rad(FRAC_PI_2),
+            )
+            .unwrap();
+        // Everything lies in the second quadrant, from +Y round to -X
+        assert!(solid.vertex_iter().all(|v| {
+            let p = v.point();
+            p.x < 1e-9 && p.y > -1e-9
+        }));
+        let has = |x: f64, y: f64| {
+            solid
+                .vertex_iter()
+                .any(|v| (v.point() - Point3::new(x, y, 0.0)).magnitude() < 1e-9)
+        };
+        assert!(has(0.0, 4.0) && has(-4.0, 0.0));
+
+        let straddling = Sketch::new(Shapes::rectangle(Point2::new(-1.0, 0.0), 2.0, 2.0).unwrap());
+        assert!(matches!(
+            straddling.revolve(Plane::xz(), Point3::origin(), Vector3::unit_z(), Rad(PI)),
+            Err(SketchError::InvalidRevolve(_))
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::sketch::primitives::{Curve2D, SketchCurve2D};
+use serde::{Deserialize, Serialize};
+use truck_geometry::prelude::*;
+
+/// What points are pulled onto while sketching. Curve points win over the
+/// angle from the previous point, which wins over the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapSettings {
+    pub to_grid: bool,
+    /// Grid spacing; 0 follows the grid drawn in the view
+    pub grid: f64,
+    /// Curve ends, and points already placed
+    pub to_endpoints: bool,
+    /// Middles of lines and arcs
+    pub to_midpoints: bool,
+    /// Centers of arcs and circles
+    pub to_centers: bool,
+    pub to_angle: bool,
+    /// Angle in degrees lines from the previous point turn in steps of
+    pub angle_step: f64,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            to_grid: true,
+            grid: 0.0,
+            to_endpoints: true,
+            to_midpoints: false,
+            to_centers: false,
+            to_angle: false,
+            angle_step: 15.0,
+        }
+    }
+}
+
+impl SnapSettings {
+    /// Points of `curves` the settings snap to
+    pub fn targets(&self, curves: &[Curve2D]) -> Vec<Point2> {
+        let mut points = Vec::new();
+        for curve in curves {
+            if self.to_endpoints {
+                points.extend([curve.start(), curve.end()]);
+            }
+            if self.to_midpoints && matches!(curve, Curve2D::Line(_) | Curve2D::Arc(_)) {
+                points.push(curve.point_at(0.5));
+            }
+            if self.to_centers {
+                match curve {
+                    Curve2D::Arc(arc) => points.push(arc.center()),
+                    Curve2D::Circle(circle) => points.push(circle.center()),
+                    _ => {}
+                }
+            }
+        }
+        points
+    }
+
+    /// `point` moved onto the nearest of `targets` within `radius`, or
+    /// else turned about `from` to the nearest angle step, or else onto a
+    /// grid of `grid` spacing
+    pub fn snap(
+        &self,
+        point: Point2,
+        from: Option<Point2>,
+        targets: &[Point2],
+        radius: f64,
+        grid: f64,
+    ) -> Point2 {
+        let nearest = targets
+            .iter()
+            .map(|&target| (target, target.distance(point)))
+            .filter(|&(_, distance)| distance <= radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((target, _)) = nearest {
+            return target;
+        }
+        if let Some(from) = from.filter(|_| self.to_angle && self.angle_step > 0.0) {
+            let offset = point - from;
+            let step = self.angle_step.to_radians();
+            let angle = (offset.y.atan2(offset.x) / step).round() * step;
+            return from + Vector2::new(angle.cos(), angle.sin()) * offset.magnitude();
+        }
+        if self.to_grid && grid > 0.0 {
+            return Point2::new(
+                (point.x / grid).round() * grid,
+                (point.y / grid).round() * grid,
+            );
+        }
+        point
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::{Circle2D, Line2D, SketchBuilder};
+
+    #[test]
+    fn test_snap_settings() {
+        let curves: Vec<Curve2D> = vec![
+            Line2D::new(Point2::origin(), Point2::new(4.0, 0.0))
+                .unwrap()
+                .into(),
+            Circle2D::new(Point2::new(10.0, 0.0), 1.0).unwrap().into(),
+        ];
+        let mut settings = SnapSettings::default();
+        assert_eq!(settings.targets(&curves).len(), 4);
+        settings.to_midpoints = true;
+        settings.to_centers = true;
+        let targets = settings.targets(&curves);
+        assert!(targets.contains(&Point2::new(2.0, 0.0)));
+        assert!(targets.contains(&Point2::new(10.0, 0.0)));
+
+        let snap = |settings: &SnapSettings, x, y| {
+            settings.snap(
+                Point2::new(x, y),
+                Some(Point2::origin()),
+                &targets,
+                0.5,
+                1.0,
+            )
+        };
+        assert_eq!(snap(&settings, 2.2, 0.3), Point2::new(2.0, 0.0));
+        assert_eq!(snap(&settings, 5.3, 2.6), Point2::new(5.0, 3.0));
+        settings.to_angle = true;
+        settings.angle_step = 45.0;
+        let turned = snap(&settings, 3.0, 2.6);
+        assert!((turned.x - turned.y).abs() < 1e-9);
+        assert!((turned.to_vec().magnitude() - Vector2::new(3.0, 2.6).magnitude()).abs() < 1e-9);
+        settings.to_grid = false;
+        settings.to_angle = false;
+        assert_eq!(snap(&settings, 5.3, 2.6), Point2::new(5.3, 2.6));
+
+        // The builder snaps onto its own start and the grid
+        settings.to_grid = true;
+        settings.grid = 1.0;
+        let outline = SketchBuilder::new()
+            .snapping(settings, 0.2)
+            .move_to(Point2::new(0.1, -0.1))
+            .line_to(Point2::new(3.2, 0.1))
+            .and_then(|b| b.line_to(Point2::new(2.9, 1.8)))
+            .and_then(|b| b.line_to(Point2::new(0.1, 0.05)))
+            .unwrap()
+            .build_open();
+        assert_eq!(outline[1].end(), Point2::new(3.0, 2.0));
+        assert_eq!(outline[2].end(), Point2::origin());
+    }
+}
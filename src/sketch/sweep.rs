@@ -0,0 +1,267 @@
+use crate::sketch::constants::*;
+use crate::sketch::error::*;
+use crate::sketch::loft::Loft;
+use crate::sketch::plane::Plane;
+use crate::sketch::Sketch;
+use std::f64::consts::PI;
+use truck_geometry::prelude::*;
+use truck_modeling::{builder as truck_builder, Curve, Edge, Solid, Wire};
+
+/// Stations per curved path edge
+const CURVE_SEGMENTS: usize = 16;
+
+/// Largest twist between two stations
+const MAX_TWIST_STEP: f64 = PI / 8.0;
+
+/// Straight-segment path through 3D points
+pub fn polyline_path(points: &[Point3]) -> SketchResult<Wire> {
+    if points.len() < 2 {
+        return Err(SketchError::InvalidSweepPath(
+            "a path needs at least two points".to_string(),
+        ));
+    }
+    let vertices: Vec<_> = points.iter().map(|&p| truck_builder::vertex(p)).collect();
+    Ok(vertices
+        .windows(2)
+        .map(|v| truck_builder::line(&v[0], &v[1]))
+        .collect())
+}
+
+/// Smooth B-spline path using `points` as control points
+pub fn spline_path(points: &[Point3], degree: usize) -> SketchResult<Wire> {
+    if points.len() <= degree {
+        return Err(SketchError::InsufficientControlPoints {
+            min: degree + 1,
+            degree,
+            got: points.len(),
+        });
+    }
+    let curve = BSplineCurve::new(
+        KnotVec::uniform_knot(degree, points.len() - degree),
+        points.to_vec(),
+    );
+    let v0 = truck_builder::vertex(points[0]);
+    let v1 = truck_builder::vertex(*points.last().unwrap());
+    let edge = Edge::try_new(&v0, &v1, Curve::BSplineCurve(curve))
+        .map_err(|e| SketchError::TruckEdgeError(format!("{:?}", e)))?;
+    Ok(vec![edge].into())
+}
+
+impl Sketch {
+    /// Sweep the profile on `plane` along `path`, twisting by `twist` in
+    /// total (distributed by arc length).
+    ///
+    /// The profile is carried rigidly from the path start with
+    /// rotation-minimizing frames, so it should sit where the path begins
+    /// and the path must leave the sketch plane. Sections are joined by
+    /// ruled surfaces at a fixed number of stations per curved edge; at
+    /// corners of a polyline path the section is mitred along the mean
+    /// tangent and stretched by 1 / cos(half the bend) across it, so the
+    /// pipe keeps its size through the corner.
+    pub fn sweep_along(
+        &self,
+        plane: impl Into<Plane>,
+        path: &Wire,
+        twist: Rad<f64>,
+    ) -> SketchResult<Solid> {
+        let plane = &plane.into();
+        let stations = sample_path(path, twist.0)?;
+        let (first, last) = (&stations[0], &stations[stations.len() - 1]);
+        if (first.0 - last.0).magnitude() < POINT_TOLERANCE {
+            return Err(SketchError::InvalidSweepPath(
+                "closed paths are not supported".to_string(),
+            ));
+        }
+        let t0 = first.1;
+        if t0.dot(plane.normal()).abs() < ANGLE_TOLERANCE.sqrt() {
+            return Err(SketchError::InvalidSweepPath(
+                "path starts tangent to the sketch plane".to_string(),
+            ));
+        }
+
+        // Initial frame: sketch X projected perpendicular to the tangent
+        let mut x = plane.x_dir() - t0 * plane.x_dir().dot(t0);
+        if x.magnitude() < DEGENERATE_TOLERANCE {
+            x = plane.y_dir().cross(t0);
+        }
+        let x0 = x.normalize();
+        let frame0 = Matrix3::from_cols(x0, t0.cross(x0), t0);
+
+        let total = stations.last().unwrap().2;
+        let mut loft = Loft::new();
+        let mut x = x0;
+        let mut prev: Option<&Station> = None;
+        for station in &stations {
+            let (p, t, s, bend) = *station;
+            if let Some(&(p_prev, t_prev, _, _)) = prev {
+                x = transport(x, p_prev, t_prev, p, t);
+            }
+            prev = Some(station);
+
+            let angle = twist.0 * if total > 0.0 { s / total } else { 0.0 };
+            let (sin, cos) = angle.sin_cos();
+            let y = t.cross(x);
+            let xk = x * cos + y * sin;
+            let frame = Matrix3::from_cols(xk, t.cross(xk), t);
+
+            // Rigid motion taking the start frame to this station
+            let rotation = frame * frame0.transpose();
+            let origin = p + rotation * (plane.origin() - first.0);
+            let section = Plane::new(origin, rotation * plane.x_dir(), rotation * plane.y_dir())?;
+            // |bend| is 2 sin(half the bend angle)
+            let half_sin = bend.magnitude() / 2.0;
+            loft = if half_sin > ANGLE_TOLERANCE {
+                let stretch = 1.0 / (1.0 - half_sin * half_sin).sqrt();
+                loft.stretched_section(self.clone(), section, p, bend, stretch)
+            } else {
+                loft.section(self.clone(), section)
+            };
+        }
+        loft.build()
+    }
+}
+
+/// Point, unit tangent, arc length and, at a corner, the outgoing minus the
+/// incoming tangent
+type Station = (Point3, Vector3, f64, Vector3);
+
+/// Stations along a wire
+fn sample_path(path: &Wire, twist: f64) -> SketchResult<Vec<Station>> {
+    if path.is_empty() {
+        return Err(SketchError::InvalidSweepPath(
+            "path has no edges".to_string(),
+        ));
+    }
+    let curves: Vec<Curve> = path.edge_iter().map(|e| e.oriented_curve()).collect();
+    let mut counts: Vec<usize> = curves
+        .iter()
+        .map(|c| match c {
+            Curve::Line(_) => 1,
+            _ => CURVE_SEGMENTS,
+        })
+        .collect();
+    let needed = (twist.abs() / MAX_TWIST_STEP).ceil() as usize;
+    let total: usize = counts.iter().sum();
+    if needed > total {
+        let factor = needed.div_ceil(total);
+        counts.iter_mut().for_each(|c| *c *= factor);
+    }
+
+    let mut stations: Vec<Station> = Vec::new();
+    let mut length = 0.0;
+    for (curve, &count) in curves.iter().zip(&counts) {
+        let (t0, t1) = curve.range_tuple();
+        for i in 0..=count {
+            let u = t0 + (t1 - t0) * i as f64 / count as f64;
+            let p = curve.subs(u);
+            let d = curve.der(u);
+            if d.magnitude() < DEGENERATE_TOLERANCE {
+                return Err(SketchError::InvalidSweepPath(
+                    "path has a degenerate tangent".to_string(),
+                ));
+            }
+            let tangent = d.normalize();
+            match stations.last_mut() {
+                // Shared vertex between edges: mitre along the mean tangent
+                Some(last) if i == 0 && (last.0 - p).magnitude() < HEAL_TOLERANCE => {
+                    let mean = last.1 + tangent;
+                    if mean.magnitude() > DEGENERATE_TOLERANCE {
+                        last.3 = tangent - last.1;
+                        last.1 = mean.normalize();
+                    }
+                }
+                Some(last) if i == 0 => {
+                    return Err(SketchError::InvalidSweepPath(format!(
+                        "gap of {:.3e} between path edges",
+                        (last.0 - p).magnitude()
+                    )));
+                }
+                _ => {
+                    if let Some(last) = stations.last() {
+                        length += (p - last.0).magnitude();
+                    }
+                    stations.push((p, tangent, length, Vector3::zero()));
+                }
+            }
+        }
+    }
+    Ok(stations)
+}
+
+/// Carry a frame axis from one station to the next by double reflection
+/// (rotation-minimizing frame)
+fn transport(x: Vector3, p0: Point3, t0: Vector3, p1: Point3, t1: Vector3) -> Vector3 {
+    let v1 = p1 - p0;
+    let c1 = v1.dot(v1);
+    if c1 < DEGENERATE_TOLERANCE {
+        return x;
+    }
+    let x_l = x - v1 * (2.0 / c1 * v1.dot(x));
+    let t_l = t0 - v1 * (2.0 / c1 * v1.dot(t0));
+    let v2 = t1 - t_l;
+    let c2 = v2.dot(v2);
+    let x = if c2 < DEGENERATE_TOLERANCE {
+        x_l
+    } else {
+        x_l - v2 * (2.0 / c2 * v2.dot(x_l))
+    };
+    // Keep exactly perpendicular to the new tangent
+    (x - t1 * x.dot(t1)).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::Shapes;
+
+    #[test]
+    fn test_pipe_along_polyline() {
+        let profile = Sketch::with_holes(
+            Shapes::circle(Point2::origin(), 1.0).unwrap(),
+            vec![Shapes::circle(Point2::origin(), 0.5).unwrap()],
+        );
+        let path = polyline_path(&[
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, 10.0),
+            Point3::new(10.0, 0.0, 10.0),
+        ])
+        .unwrap();
+        let pipe = profile.sweep_along(Plane::xy(), &path, Rad(0.0)).unwrap();
+        assert_eq!(pipe.boundaries().len(), 1);
+        // Mitred at the corner, so area times path length
+        let volume = GpuMesh::from_solid(&pipe, 0.001).volume();
+        let expected = 0.75 * PI * 20.0;
+        assert!(
+            (volume - expected).abs() < 0.005 * expected,
+            "{} vs {}",
+            volume,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_twisted_sweep_along_spline() {
+        let profile = Sketch::new(Shapes::rectangle_centered(Point2::origin(), 2.0, 1.0).unwrap());
+        let path = spline_path(
+            &[
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(0.0, 0.0, 5.0),
+                Point3::new(5.0, 0.0, 10.0),
+                Point3::new(10.0, 0.0, 10.0),
+            ],
+            3,
+        )
+        .unwrap();
+        assert!(profile
+            .sweep_along(Plane::xy(), &path, Rad(PI / 2.0))
+            .is_ok());
+
+        // The path may not lie in the sketch plane
+        let flat = polyline_path(&[Point3::origin(), Point3::new(5.0, 0.0, 0.0)]).unwrap();
+        assert!(matches!(
+            profile.sweep_along(Plane::xy(), &flat, Rad(0.0)),
+            Err(SketchError::InvalidSweepPath(_))
+        ));
+    }
+}
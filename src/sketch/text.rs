@@ -0,0 +1,134 @@
+use crate::sketch::constants::LENGTH_TOLERANCE;
+use crate::sketch::error::*;
+use crate::sketch::primitives::{Curve2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use truck_geometry::prelude::*;
+
+/// Samples used to build the arc-length table of a path
+const ARC_LENGTH_SAMPLES: usize = 512;
+
+/// A glyph outline in its own em-space: baseline along +X, pen starting at
+/// the origin, and `advance` the distance to the next pen position.
+#[derive(Clone, Debug)]
+pub struct Glyph {
+    pub outline: Sketch,
+    pub advance: f64,
+}
+
+impl Glyph {
+    pub fn new(outline: Sketch, advance: f64) -> Self {
+        Self { outline, advance }
+    }
+}
+
+/// Arc-length parameterization of a curve by sampled chord lengths
+struct ArcLengthTable<'a> {
+    path: &'a Curve2D,
+    lengths: Vec<f64>,
+}
+
+impl<'a> ArcLengthTable<'a> {
+    fn new(path: &'a Curve2D) -> Self {
+        let mut lengths = Vec::with_capacity(ARC_LENGTH_SAMPLES + 1);
+        let mut total = 0.0;
+        let mut prev = path.point_at(0.0);
+        lengths.push(0.0);
+        for i in 1..=ARC_LENGTH_SAMPLES {
+            let p = path.point_at(i as f64 / ARC_LENGTH_SAMPLES as f64);
+            total += (p - prev).magnitude();
+            lengths.push(total);
+            prev = p;
+        }
+        Self { path, lengths }
+    }
+
+    fn total(&self) -> f64 {
+        *self.lengths.last().unwrap()
+    }
+
+    /// Curve parameter in [0, 1] at arc length `s`
+    fn parameter(&self, s: f64) -> f64 {
+        let s = s.clamp(0.0, self.total());
+        let i = self
+            .lengths
+            .partition_point(|&l| l < s)
+            .clamp(1, ARC_LENGTH_SAMPLES);
+        let (l0, l1) = (self.lengths[i - 1], self.lengths[i]);
+        let frac = if l1 > l0 { (s - l0) / (l1 - l0) } else { 0.0 };
+        (i as f64 - 1.0 + frac) / ARC_LENGTH_SAMPLES as f64
+    }
+
+    /// Point and tangent angle at arc length `s`
+    fn frame(&self, s: f64) -> (Point2, f64) {
+        let t = self.parameter(s);
+        let tangent = self.path.tangent_at(t);
+        (self.path.point_at(t), tangent.y.atan2(tangent.x))
+    }
+}
+
+/// Lay glyphs out along `path`, starting `start` units from its beginning.
+///
+/// Each glyph is positioned so the middle of its advance sits on the path
+/// and its baseline follows the local tangent; the glyph's +Y points to the
+/// left of the path direction.
+pub fn layout_along_path(
+    glyphs: &[Glyph],
+    path: &Curve2D,
+    start: f64,
+) -> SketchResult<Vec<Sketch>> {
+    let table = ArcLengthTable::new(path);
+    let required = start + glyphs.iter().map(|g| g.advance).sum::<f64>();
+    if required > table.total() + LENGTH_TOLERANCE {
+        return Err(SketchError::TextExceedsPath {
+            required,
+            available: table.total(),
+        });
+    }
+
+    let mut pen = start;
+    let mut placed = Vec::with_capacity(glyphs.len());
+    for glyph in glyphs {
+        let half = glyph.advance / 2.0;
+        let (anchor, angle) = table.frame(pen + half);
+        let sketch = glyph
+            .outline
+            .translated(Vector2::new(-half, 0.0))
+            .rotated(Point2::origin(), angle)
+            .translated(anchor.to_vec());
+        placed.push(sketch);
+        pen += glyph.advance;
+    }
+    Ok(placed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::{Arc2D, Shapes};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_glyphs_follow_arc() {
+        // Simple block "glyphs" 4 wide, 6 tall, with 1 unit of spacing
+        let block = Sketch::new(Shapes::rectangle(Point2::new(0.5, 0.0), 4.0, 6.0).unwrap());
+        let glyphs = vec![Glyph::new(block, 5.0); 4];
+        let path = Curve2D::Arc(Arc2D::new(Point2::origin(), 50.0, 0.0, PI).unwrap());
+
+        let placed = layout_along_path(&glyphs, &path, 10.0).unwrap();
+        assert_eq!(placed.len(), 4);
+        for (i, sketch) in placed.iter().enumerate() {
+            let bbox = sketch.bounding_box().unwrap();
+            let center = Point2::new(
+                (bbox.min.x + bbox.max.x) / 2.0,
+                (bbox.min.y + bbox.max.y) / 2.0,
+            );
+            // Glyph centers sit 3 units inside the arc, evenly spaced
+            assert!((center.to_vec().magnitude() - 47.0).abs() < 1e-3);
+            let expected_angle = (12.5 + 5.0 * i as f64) / 50.0;
+            assert!((center.y.atan2(center.x) - expected_angle).abs() < 1e-3);
+            assert!((sketch.area() - 24.0).abs() < 1e-9);
+        }
+
+        assert!(layout_along_path(&glyphs, &path, 150.0).is_err());
+    }
+}
@@ -8,7 +8,8 @@ use truck_modeling::{builder, Curve, Edge, Vertex, Wire};
 
 impl Loop2D {
     /// Convert to truck Wire
-    pub fn to_truck_wire(&self, plane: &Plane) -> SketchResult<Wire> {
+    pub fn to_truck_wire(&self, plane: impl Into<Plane>) -> SketchResult<Wire> {
+        let plane = &plane.into();
         let curves = self.curves();
         if curves.is_empty() {
             return Err(SketchError::EmptyLoop);
@@ -144,10 +145,8 @@ fn bspline_to_edge_with_vertices(
         .map(|&p| plane.lift_point(p))
         .collect();
 
-    let inner = spline.inner();
-    let degree = inner.degree();
-    let n = lifted_pts.len();
-    let knots = KnotVec::uniform_knot(n, degree);
+    // Keep the original knot vector so non-uniform splines lift exactly
+    let knots = spline.inner().knot_vec().clone();
     let lifted_bspline = BSplineCurve::new(knots, lifted_pts);
 
     Edge::try_new(v0, v1, Curve::BSplineCurve(lifted_bspline))
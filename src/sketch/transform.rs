@@ -0,0 +1,238 @@
+use crate::sketch::constants::*;
+use crate::sketch::construction::Construction;
+use crate::sketch::error::*;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::primitives::{Arc2D, BSpline2D, Circle2D, Curve2D, Line2D, SketchCurve2D};
+use crate::sketch::{Plane, Sketch};
+use truck_geometry::prelude::*;
+
+/// Rigid motion `p ↦ R(angle)·S·(p − about) + about + offset`, where `S`
+/// mirrors across the X axis when `mirror` is set
+#[derive(Clone, Copy, Debug)]
+struct Rigid {
+    about: Point2,
+    cos: f64,
+    sin: f64,
+    offset: Vector2,
+    angle: f64,
+    mirror: bool,
+}
+
+impl Rigid {
+    fn rotation(about: Point2, angle: f64) -> Self {
+        Self {
+            about,
+            cos: angle.cos(),
+            sin: angle.sin(),
+            offset: Vector2::zero(),
+            angle,
+            mirror: false,
+        }
+    }
+
+    fn translation(offset: Vector2) -> Self {
+        Self {
+            offset,
+            ..Self::rotation(Point2::origin(), 0.0)
+        }
+    }
+
+    /// Reflection across the line through `about` at `angle` from +X
+    fn reflection(about: Point2, angle: f64) -> Self {
+        Self {
+            mirror: true,
+            ..Self::rotation(about, 2.0 * angle)
+        }
+    }
+
+    /// Coordinates on `from` to coordinates on the parallel plane `to`
+    fn between(from: &Plane, to: &Plane) -> SketchResult<Self> {
+        if from.normal().cross(to.normal()).magnitude() >= ANGLE_TOLERANCE {
+            return Err(SketchError::NonParallelPlanes);
+        }
+        let x = to.project_vector(from.x_dir());
+        let angle = x.y.atan2(x.x);
+        Ok(Self {
+            offset: to.project_point(from.origin()).to_vec(),
+            mirror: from.normal().dot(to.normal()) < 0.0,
+            ..Self::rotation(Point2::origin(), angle)
+        })
+    }
+
+    fn point(&self, p: Point2) -> Point2 {
+        let mut d = p - self.about;
+        if self.mirror {
+            d.y = -d.y;
+        }
+        self.about
+            + Vector2::new(
+                self.cos * d.x - self.sin * d.y,
+                self.sin * d.x + self.cos * d.y,
+            )
+            + self.offset
+    }
+
+    /// Image of the direction at `angle`
+    fn direction(&self, angle: f64) -> f64 {
+        if self.mirror {
+            self.angle - angle
+        } else {
+            self.angle + angle
+        }
+    }
+}
+
+// Rigid motions keep lengths and radii, so rebuilding the primitives cannot fail
+fn curve(c: &Curve2D, m: &Rigid) -> Curve2D {
+    match c {
+        Curve2D::Line(line) => Curve2D::Line(line_of(line, m)),
+        Curve2D::Arc(arc) => Curve2D::Arc(
+            Arc2D::new(
+                m.point(arc.center()),
+                arc.radius(),
+                m.direction(arc.start_angle()),
+                if m.mirror {
+                    -arc.sweep_angle()
+                } else {
+                    arc.sweep_angle()
+                },
+            )
+            .expect("rigid motion preserves arc validity"),
+        ),
+        Curve2D::Circle(circle) => Curve2D::Circle(circle_of(circle, m)),
+        Curve2D::BSpline(spline) => {
+            let points = spline
+                .control_points()
+                .iter()
+                .map(|&p| m.point(p))
+                .collect();
+            Curve2D::BSpline(BSpline2D::from_truck_curve(BSplineCurve::new(
+                spline.inner().knot_vec().clone(),
+                points,
+            )))
+        }
+    }
+}
+
+fn line_of(line: &Line2D, m: &Rigid) -> Line2D {
+    Line2D::new(m.point(line.start()), m.point(line.end()))
+        .expect("rigid motion preserves line length")
+}
+
+fn circle_of(circle: &Circle2D, m: &Rigid) -> Circle2D {
+    let center = m.point(circle.center());
+    let seam = m.point(circle.start()) - center;
+    Circle2D::with_seam(
+        center,
+        circle.radius(),
+        seam.y.atan2(seam.x),
+        circle.is_ccw() != m.mirror,
+    )
+    .expect("rigid motion preserves circle radius")
+}
+
+fn loop_of(l: &Loop2D, m: &Rigid) -> Loop2D {
+    Loop2D::new_unchecked(l.curves().iter().map(|c| curve(c, m)).collect())
+}
+
+fn construction_of(c: &Construction, m: &Rigid) -> Construction {
+    match c {
+        Construction::Point(p) => Construction::Point(m.point(*p)),
+        Construction::Line(line) => Construction::Line(line_of(line, m)),
+        Construction::Circle(circle) => Construction::Circle(circle_of(circle, m)),
+    }
+}
+
+fn sketch_of(s: &Sketch, m: &Rigid) -> Sketch {
+    Sketch {
+        outer: loop_of(&s.outer, m),
+        holes: s.holes.iter().map(|h| loop_of(h, m)).collect(),
+        construction: s
+            .construction
+            .iter()
+            .map(|c| construction_of(c, m))
+            .collect(),
+    }
+}
+
+impl Curve2D {
+    /// Translated copy
+    pub fn translated(&self, offset: Vector2) -> Self {
+        curve(self, &Rigid::translation(offset))
+    }
+
+    /// Copy rotated by `angle` (radians, CCW) about `about`
+    pub fn rotated(&self, about: Point2, angle: f64) -> Self {
+        curve(self, &Rigid::rotation(about, angle))
+    }
+
+    /// Mirror image across the line through `about` at `angle` (radians from +X)
+    pub fn mirrored(&self, about: Point2, angle: f64) -> Self {
+        curve(self, &Rigid::reflection(about, angle))
+    }
+}
+
+impl Loop2D {
+    /// Translated copy
+    pub fn translated(&self, offset: Vector2) -> Self {
+        loop_of(self, &Rigid::translation(offset))
+    }
+
+    /// Copy rotated by `angle` (radians, CCW) about `about`
+    pub fn rotated(&self, about: Point2, angle: f64) -> Self {
+        loop_of(self, &Rigid::rotation(about, angle))
+    }
+}
+
+impl Sketch {
+    /// Translated copy (including construction geometry)
+    pub fn translated(&self, offset: Vector2) -> Self {
+        sketch_of(self, &Rigid::translation(offset))
+    }
+
+    /// Copy rotated by `angle` (radians, CCW) about `about`
+    pub fn rotated(&self, about: Point2, angle: f64) -> Self {
+        sketch_of(self, &Rigid::rotation(about, angle))
+    }
+
+    /// The sketch drawn on `from`, in the coordinates of the parallel plane
+    /// `to` (projected along the normal; facing the other way mirrors it)
+    pub fn reprojected(&self, from: impl Into<Plane>, to: impl Into<Plane>) -> SketchResult<Self> {
+        Ok(sketch_of(self, &Rigid::between(&from.into(), &to.into())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sketch::Shapes;
+
+    #[test]
+    fn test_reprojected_onto_parallel_planes() {
+        let outer = Shapes::rounded_rectangle(Point2::origin(), 10.0, 6.0, 1.0).unwrap();
+        let hole = Shapes::circle(Point2::new(5.0, 3.0), 1.0).unwrap();
+        let sketch = Sketch::with_holes(outer, vec![hole]);
+        let from = Plane::xy()
+            .rotated_about(Point3::origin(), Vector3::unit_z(), Rad(0.4))
+            .unwrap();
+
+        for to in [Plane::xy_at(5.0), Plane::xy_at(-2.0).flipped()] {
+            let moved = sketch.reprojected(&from, &to).unwrap();
+            let loops = [&sketch.outer, &sketch.holes[0]];
+            let moved_loops = [&moved.outer, &moved.holes[0]];
+            for (l, m) in loops.iter().zip(moved_loops) {
+                for (a, b) in l.curves().iter().zip(m.curves()) {
+                    for t in [0.0, 0.3, 1.0] {
+                        let expected = to.project_point(from.lift_point(a.point_at(t)));
+                        assert!((b.point_at(t) - expected).magnitude() < 1e-9);
+                    }
+                }
+            }
+        }
+
+        assert!(matches!(
+            sketch.reprojected(Plane::xy(), Plane::xz()),
+            Err(SketchError::NonParallelPlanes)
+        ));
+    }
+}
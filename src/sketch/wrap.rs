@@ -0,0 +1,378 @@
+use crate::sketch::constants::*;
+use crate::sketch::error::*;
+use crate::sketch::hatch::flatten_curve;
+use crate::sketch::loop2d::Loop2D;
+use crate::sketch::plane::perpendicular;
+use crate::sketch::primitives::{Arc2D, Curve2D, SketchCurve2D};
+use crate::sketch::Sketch;
+use std::f64::consts::{PI, TAU};
+use truck_geometry::prelude::*;
+use truck_modeling::{
+    builder as truck_builder, Curve, Edge, Face, Shell, Solid, Surface, Vertex, Wire,
+};
+
+/// Angle spanned by one facet of the wrapped cylinder
+const FACET_ANGLE: f64 = PI / 90.0;
+
+/// Chord tolerance for flattening the profile, relative to the radius
+const RELATIVE_CHORD_TOLERANCE: f64 = 1e-4;
+
+/// How far past the cylinder surface the body reaches, as a fraction of
+/// the depth, so joining or cutting it leaves no skin or touching faces
+const SURFACE_OVERLAP: f64 = 0.25;
+
+impl Sketch {
+    /// Emboss (positive `depth`) or engrave (negative) the profile on
+    /// `target`, whose cylindrical face of `radius` lies round the axis;
+    /// see [`Sketch::wrap_on_cylinder`]
+    pub fn wrap_onto(
+        &self,
+        target: &Solid,
+        axis_origin: Point3,
+        axis_direction: Vector3,
+        radius: f64,
+        depth: f64,
+    ) -> SketchResult<Solid> {
+        let tool = self.wrap_on_cylinder(axis_origin, axis_direction, radius, depth)?;
+        if depth > 0.0 {
+            crate::geometry::union(target, &tool)
+        } else {
+            crate::geometry::cut(target, &tool)
+        }
+    }
+
+    /// Wrap the profile round a cylinder and give it `depth` radially:
+    /// positive depth embosses (material outside `radius`), negative
+    /// engraves (the returned body is the material to cut away).
+    ///
+    /// Sketch X is arc length round the cylinder starting from an
+    /// automatically chosen direction perpendicular to the axis; sketch Y
+    /// runs along the axis from `axis_origin`. The cylinder is faceted every
+    /// 2 degrees and curved profile edges are flattened, so the result is
+    /// exact on the facets rather than on the true cylinder. The face that
+    /// meets the cylinder is pushed a quarter of the depth past it, outside
+    /// the facets' chords, so [`Sketch::wrap_onto`] leaves no skin.
+    pub fn wrap_on_cylinder(
+        &self,
+        axis_origin: Point3,
+        axis_direction: Vector3,
+        radius: f64,
+        depth: f64,
+    ) -> SketchResult<Solid> {
+        if axis_direction.magnitude() < DEGENERATE_TOLERANCE || radius <= 0.0 || !radius.is_finite()
+        {
+            return Err(SketchError::InvalidFeature(format!(
+                "cannot wrap onto a cylinder of radius {} with a zero axis",
+                radius
+            )));
+        }
+        if depth.abs() <= LENGTH_TOLERANCE || radius + depth <= 0.0 || !depth.is_finite() {
+            return Err(SketchError::InvalidExtrudeDepth(depth));
+        }
+        let overlap = depth.abs() * SURFACE_OVERLAP;
+        let (r_in, r_out) = if depth > 0.0 {
+            // Sunk into the cylinder, so the two overlap when joined
+            ((radius - overlap).max(radius * 0.5), radius + depth)
+        } else {
+            // Out past the cylinder, whose chords fall inside it by up to
+            // 1 - cos(half a facet)
+            (radius + depth, radius / (FACET_ANGLE / 2.0).cos() + overlap)
+        };
+        let bbox = self.outer.bounding_box().ok_or(SketchError::EmptyLoop)?;
+        let step = radius * FACET_ANGLE;
+        if bbox.max.x - bbox.min.x > TAU * radius - 2.0 * step {
+            return Err(SketchError::InvalidFeature(
+                "profile is too wide to wrap once round the cylinder".to_string(),
+            ));
+        }
+
+        let axis = axis_direction.normalize();
+        let e1 = perpendicular(axis);
+        let margin = (bbox.max.y - bbox.min.y).max(step);
+        let wrap = Wrap {
+            origin: axis_origin,
+            axis,
+            e1,
+            e2: axis.cross(e1),
+            step,
+            first: (bbox.min.x / step).floor() as i64 - 1,
+            last: (bbox.max.x / step).ceil() as i64 + 1,
+            bottom: bbox.min.y - margin,
+            top: bbox.max.y + margin,
+        };
+        let tol = radius * RELATIVE_CHORD_TOLERANCE;
+
+        let mut shell = Shell::new();
+        let (mut inner_wires, mut outer_wires) = (Vec::new(), Vec::new());
+        for (index, l) in std::iter::once(&self.outer).chain(&self.holes).enumerate() {
+            // Outer loop CCW and holes CW so every wall faces out of the body
+            let l = if l.is_ccw() == (index == 0) {
+                l.clone()
+            } else {
+                l.reversed()
+            };
+            let (inner, outer) = wrap.walls(&l, r_in, r_out, tol, &mut shell)?;
+            inner_wires.push(inner);
+            outer_wires.push(outer);
+        }
+        shell.push(Face::new(inner_wires, Surface::BSplineSurface(wrap.surface(r_in))).inverse());
+        shell.push(Face::new(
+            outer_wires,
+            Surface::BSplineSurface(wrap.surface(r_out)),
+        ));
+        Solid::try_new(vec![shell]).map_err(|e| SketchError::TruckSolidError(format!("{:?}", e)))
+    }
+}
+
+/// Faceted cylinder frame: sketch (x, y) at radius ρ maps onto the facet
+/// containing angle x / radius
+struct Wrap {
+    origin: Point3,
+    axis: Vector3,
+    e1: Vector3,
+    e2: Vector3,
+    /// Arc length per facet
+    step: f64,
+    /// Facet boundary indices covering the profile
+    first: i64,
+    last: i64,
+    /// Axial extent of the cap surfaces
+    bottom: f64,
+    top: f64,
+}
+
+impl Wrap {
+    /// Facet corner `k` at radius `rho` on the axis origin's level
+    fn corner(&self, k: i64, rho: f64) -> Point3 {
+        let (sin, cos) = (k as f64 * FACET_ANGLE).sin_cos();
+        self.origin + (self.e1 * cos + self.e2 * sin) * rho
+    }
+
+    fn point(&self, p: Point2, rho: f64) -> Point3 {
+        let k = ((p.x / self.step).floor() as i64).clamp(self.first, self.last - 1);
+        let s = p.x / self.step - k as f64;
+        let (a, b) = (self.corner(k, rho), self.corner(k + 1, rho));
+        a + (b - a) * s + self.axis * p.y
+    }
+
+    /// Polyline with extra points wherever it crosses a facet boundary, so
+    /// every segment stays on one (planar) facet
+    fn refine(&self, points: &[Point2]) -> Vec<Point2> {
+        let mut refined = vec![points[0]];
+        for pair in points.windows(2) {
+            let (p, q) = (pair[0], pair[1]);
+            let (kp, kq) = (p.x / self.step, q.x / self.step);
+            let mut crossings: Vec<f64> = if kp < kq {
+                (kp.floor() as i64 + 1..=kq.ceil() as i64 - 1)
+                    .map(|k| k as f64)
+                    .collect()
+            } else {
+                (kq.floor() as i64 + 1..=kp.ceil() as i64 - 1)
+                    .rev()
+                    .map(|k| k as f64)
+                    .collect()
+            };
+            crossings.retain(|&k| (k - kp).abs() > 1e-9 && (k - kq).abs() > 1e-9);
+            refined.extend(
+                crossings
+                    .into_iter()
+                    .map(|k| p + (q - p) * ((k - kp) / (kq - kp))),
+            );
+            refined.push(q);
+        }
+        refined
+    }
+
+    /// The faceted cylinder of radius `rho` as a degree-1 surface
+    /// parameterized by sketch (x, y)
+    fn surface(&self, rho: f64) -> BSplineSurface<Point3> {
+        let us: Vec<f64> = (self.first..=self.last)
+            .map(|k| k as f64 * self.step)
+            .collect();
+        let points = (self.first..=self.last)
+            .map(|k| {
+                let c = self.corner(k, rho);
+                vec![c + self.axis * self.bottom, c + self.axis * self.top]
+            })
+            .collect();
+        BSplineSurface::new(
+            (
+                clamped_linear(&us),
+                clamped_linear(&[self.bottom, self.top]),
+            ),
+            points,
+        )
+    }
+
+    /// Side walls of one loop; returns the loop's inner and outer wires
+    fn walls(
+        &self,
+        l: &Loop2D,
+        r_in: f64,
+        r_out: f64,
+        tol: f64,
+        shell: &mut Shell,
+    ) -> SketchResult<(Wire, Wire)> {
+        let curves: Vec<Curve2D> = l
+            .curves()
+            .iter()
+            .map(split_circle)
+            .collect::<SketchResult<Vec<_>>>()?
+            .concat();
+        let n = curves.len();
+        let polylines: Vec<Vec<Point2>> = (0..n)
+            .map(|i| {
+                let mut points = flatten_curve(&curves[i], tol);
+                points.push(curves[(i + 1) % n].start());
+                self.refine(&points)
+            })
+            .collect();
+        let vertices = |rho: f64| -> Vec<Vertex> {
+            polylines
+                .iter()
+                .map(|p| truck_builder::vertex(self.point(p[0], rho)))
+                .collect()
+        };
+        let (v_in, v_out) = (vertices(r_in), vertices(r_out));
+        let radials: Vec<Edge> = v_in
+            .iter()
+            .zip(&v_out)
+            .map(|(a, b)| truck_builder::line(a, b))
+            .collect();
+
+        let (mut inner, mut outer) = (Wire::new(), Wire::new());
+        for (i, points) in polylines.iter().enumerate() {
+            let j = (i + 1) % n;
+            let lift =
+                |rho: f64| -> Vec<Point3> { points.iter().map(|&p| self.point(p, rho)).collect() };
+            let (p_in, p_out) = (lift(r_in), lift(r_out));
+            let params: Vec<f64> = (0..points.len()).map(|k| k as f64).collect();
+            let knots = clamped_linear(&params);
+            let e_in = Edge::new(
+                &v_in[i],
+                &v_in[j],
+                Curve::BSplineCurve(BSplineCurve::new(knots.clone(), p_in.clone())),
+            );
+            let e_out = Edge::new(
+                &v_out[i],
+                &v_out[j],
+                Curve::BSplineCurve(BSplineCurve::new(knots.clone(), p_out.clone())),
+            );
+            let wall = BSplineSurface::new(
+                (knots, clamped_linear(&[0.0, 1.0])),
+                p_in.into_iter()
+                    .zip(p_out)
+                    .map(|(a, b)| vec![a, b])
+                    .collect(),
+            );
+            let boundary: Wire = vec![
+                e_in.clone(),
+                radials[j].clone(),
+                e_out.inverse(),
+                radials[i].inverse(),
+            ]
+            .into();
+            shell.push(Face::new(vec![boundary], Surface::BSplineSurface(wall)));
+            inner.push_back(e_in);
+            outer.push_back(e_out);
+        }
+        Ok((inner, outer))
+    }
+}
+
+/// Knot vector for a degree-1 spline with breakpoints `params`
+fn clamped_linear(params: &[f64]) -> KnotVec {
+    let mut knots = vec![params[0]];
+    knots.extend_from_slice(params);
+    knots.push(params[params.len() - 1]);
+    KnotVec::from(knots)
+}
+
+/// Full circles become two half arcs so every edge has distinct ends
+fn split_circle(curve: &Curve2D) -> SketchResult<Vec<Curve2D>> {
+    match curve {
+        Curve2D::Circle(circle) => {
+            let seam = circle.start() - circle.center();
+            let start = seam.y.atan2(seam.x);
+            let sweep = if circle.is_ccw() { PI } else { -PI };
+            Ok(vec![
+                Curve2D::Arc(Arc2D::new(circle.center(), circle.radius(), start, sweep)?),
+                Curve2D::Arc(Arc2D::new(
+                    circle.center(),
+                    circle.radius(),
+                    start + sweep,
+                    sweep,
+                )?),
+            ])
+        }
+        _ => Ok(vec![curve.clone()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::mesh::GpuMesh;
+    use crate::sketch::Shapes;
+
+    #[test]
+    fn test_wrap_label_on_cylinder() {
+        // Quarter turn wide at radius 10, with a round window
+        let outer = Shapes::rectangle(Point2::origin(), 5.0 * PI, 5.0).unwrap();
+        let hole = Shapes::circle(Point2::new(5.0, 2.5), 1.0).unwrap();
+        let label = Sketch::with_holes(outer, vec![hole]);
+        let area = 25.0 * PI - PI;
+
+        // Volume of a wrapped region: area / r * (r_out² - r_in²) / 2, with
+        // the face on the cylinder a quarter of the depth past it
+        let past = 10.0 / (FACET_ANGLE / 2.0).cos() + 0.25;
+        for (depth, r_in, r_out) in [(1.0, 9.75, 11.0), (-1.0, 9.0, past)] {
+            let solid = label
+                .wrap_on_cylinder(Point3::origin(), Vector3::unit_z(), 10.0, depth)
+                .unwrap();
+            let volume = GpuMesh::from_solid(&solid, 0.01).volume();
+            let expected = area / 10.0 * (r_out * r_out - r_in * r_in) / 2.0;
+            assert!(
+                (volume - expected).abs() < 0.005 * expected,
+                "{} vs {}",
+                volume,
+                expected
+            );
+        }
+
+        let wide = Sketch::new(Shapes::rectangle(Point2::origin(), 70.0, 1.0).unwrap());
+        assert!(matches!(
+            wide.wrap_on_cylinder(Point3::origin(), Vector3::unit_z(), 10.0, 1.0),
+            Err(SketchError::InvalidFeature(_))
+        ));
+    }
+
+    #[test]
+    fn test_wrap_onto_rod() {
+        // Truck meshes curved faces with holes in them coarsely, so the rod is
+        // a 90-sided prism, whose volume within the label is the cylinder's
+        // to a tenth of a percent
+        let section = Shapes::regular_polygon(Point2::origin(), 10.0, 90).unwrap();
+        let rod = Sketch::new(section)
+            .extrude(crate::sketch::Plane::xy(), Vector3::unit_z() * 10.0)
+            .unwrap();
+        let rod_volume = GpuMesh::from_solid(&rod, 0.01).volume();
+        let label = Sketch::new(Shapes::rectangle(Point2::new(1.4, 0.0), 4.0, 5.0).unwrap());
+        let origin = Point3::new(0.0, 0.0, 2.5);
+        // The label's area times (r_out² - r²) / 2r for the emboss, and
+        // (r² - r_in²) / 2r for the engrave
+        for (depth, change) in [(1.0, 21.0), (-1.0, -19.0)] {
+            let part = label
+                .wrap_onto(&rod, origin, Vector3::unit_z(), 10.0, depth)
+                .unwrap();
+            assert_eq!(part.boundaries().len(), 1);
+            let volume = GpuMesh::from_solid(&part, 0.01).volume();
+            assert!(
+                (volume - rod_volume - change).abs() < 0.01 * change.abs(),
+                "{} vs {}",
+                volume - rod_volume,
+                change
+            );
+        }
+    }
+}
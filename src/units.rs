@@ -0,0 +1,106 @@
+use crate::sketch::{SketchError, SketchResult};
+use serde::{Deserialize, Serialize};
+
+/// Length unit model coordinates are in. Values are never scaled when the
+/// unit changes; it says what a coordinate of 10 means to dimension input,
+/// measurements, the grid and exporters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Units {
+    #[default]
+    Millimetre,
+    Centimetre,
+    Metre,
+    Inch,
+}
+
+impl Units {
+    pub const ALL: [Units; 4] = [
+        Units::Millimetre,
+        Units::Centimetre,
+        Units::Metre,
+        Units::Inch,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Units::Millimetre => "Millimetres",
+            Units::Centimetre => "Centimetres",
+            Units::Metre => "Metres",
+            Units::Inch => "Inches",
+        }
+    }
+
+    /// Symbol written after values
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Units::Millimetre => "mm",
+            Units::Centimetre => "cm",
+            Units::Metre => "m",
+            Units::Inch => "in",
+        }
+    }
+
+    /// Length of one unit in millimetres
+    pub fn millimetres(&self) -> f64 {
+        match self {
+            Units::Millimetre => 1.0,
+            Units::Centimetre => 10.0,
+            Units::Metre => 1000.0,
+            Units::Inch => 25.4,
+        }
+    }
+
+    /// `value` in this unit expressed in `to`
+    pub fn convert(&self, value: f64, to: Units) -> f64 {
+        value * self.millimetres() / to.millimetres()
+    }
+
+    /// `value` with three decimals and the unit symbol
+    pub fn format(&self, value: f64) -> String {
+        format!("{:.3} {}", value, self.suffix())
+    }
+
+    /// Length typed as a number with an optional unit symbol ("10", "2.5 cm",
+    /// "1in"), in this unit; bare numbers are taken to be in this unit
+    pub fn parse(&self, text: &str) -> SketchResult<f64> {
+        let text = text.trim();
+        let number = text.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '"');
+        let symbol = &text[number.len()..];
+        let value: f64 = number
+            .trim()
+            .parse()
+            .map_err(|_| SketchError::InvalidFeature(format!("'{}' is not a length", text)))?;
+        let from = match symbol.trim() {
+            "" => *self,
+            "\"" => Units::Inch,
+            symbol => Units::ALL
+                .into_iter()
+                .find(|units| units.suffix() == symbol)
+                .ok_or_else(|| {
+                    SketchError::InvalidFeature(format!("unknown length unit '{}'", symbol))
+                })?,
+        };
+        Ok(from.convert(value, *self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_units() {
+        assert_eq!(Units::Inch.convert(2.0, Units::Millimetre), 50.8);
+        assert_eq!(Units::Millimetre.format(10.0), "10.000 mm");
+
+        let cm = Units::Centimetre;
+        assert_eq!(cm.parse("12").unwrap(), 12.0);
+        assert_eq!(cm.parse(" 25 mm ").unwrap(), 2.5);
+        assert_eq!(cm.parse("1in").unwrap(), 2.54);
+        assert_eq!(cm.parse("1\"").unwrap(), 2.54);
+        assert_eq!(cm.parse("-0.5 m").unwrap(), -50.0);
+        assert_eq!(cm.parse("1e3mm").unwrap(), 100.0);
+        assert!(cm.parse("10 ft").is_err());
+        assert!(cm.parse("mm").is_err());
+    }
+}